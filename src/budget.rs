@@ -0,0 +1,240 @@
+use std::cell::Cell;
+use std::time::Duration;
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen::prelude::*;
+
+/// Limits on the work a feature may do for a single request. Once a limit is reached, the
+/// feature stops and returns the results it has so far, flagged as truncated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Budget {
+    /// The time after which no more results are produced.
+    pub max_duration: Option<Duration>,
+    /// The maximum number of results.
+    pub max_items: Option<usize>,
+}
+
+impl Budget {
+    /// A budget without any limit.
+    pub const UNLIMITED: Budget = Budget {
+        max_duration: None,
+        max_items: None,
+    };
+
+    /// Starts tracking the work done against this budget.
+    pub fn start(&self) -> BudgetTracker {
+        BudgetTracker {
+            budget: *self,
+            stopwatch: Stopwatch::start(),
+            items: Cell::new(0),
+            truncated: Cell::new(false),
+        }
+    }
+}
+
+/// The budget of each feature, used by the `LanguageService`.
+///
+/// The default budgets only limit the number of results, so the same document always gives
+/// the same results. Time limits are opt-in, as what fits in them depends on the machine.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Budgets {
+    pub folding: Budget,
+    pub symbols: Budget,
+    pub colors: Budget,
+    pub diagnostics: Budget,
+}
+
+impl Default for Budgets {
+    fn default() -> Self {
+        let interactive = Budget {
+            max_duration: None,
+            max_items: Some(5000),
+        };
+        Budgets {
+            folding: interactive,
+            symbols: interactive,
+            colors: interactive,
+            diagnostics: Budget {
+                max_duration: None,
+                max_items: Some(1000),
+            },
+        }
+    }
+}
+
+/// The results of a feature run against a `Budget`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Budgeted<T> {
+    pub items: Vec<T>,
+    /// Whether the budget ran out before all the results were produced.
+    pub truncated: bool,
+    /// How long the feature ran.
+    pub duration: Duration,
+}
+
+/// Tracks the work done by a feature against its `Budget`.
+#[derive(Debug)]
+pub struct BudgetTracker {
+    budget: Budget,
+    stopwatch: Stopwatch,
+    items: Cell<usize>,
+    truncated: Cell<bool>,
+}
+
+impl BudgetTracker {
+    /// Checks whether one more result may be produced, and counts it if so.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the result fits in the budget, `false` once the budget is exhausted.
+    pub fn allow(&self) -> bool {
+        let full = self
+            .budget
+            .max_items
+            .is_some_and(|max_items| self.items.get() >= max_items);
+        if full {
+            self.truncated.set(true);
+            return false;
+        }
+        if !self.has_time() {
+            return false;
+        }
+        self.items.set(self.items.get() + 1);
+        true
+    }
+
+    /// Checks whether there is time left for more work, without counting a result.
+    pub fn has_time(&self) -> bool {
+        if self.truncated.get() {
+            return false;
+        }
+        let out_of_time = self
+            .budget
+            .max_duration
+            .is_some_and(|max_duration| self.stopwatch.elapsed() >= max_duration);
+        if out_of_time {
+            self.truncated.set(true);
+        }
+        !out_of_time
+    }
+
+    /// Returns the budget left, to run a part of the work against it.
+    pub fn remaining(&self) -> Budget {
+        Budget {
+            max_duration: self
+                .budget
+                .max_duration
+                .map(|max_duration| max_duration.saturating_sub(self.stopwatch.elapsed())),
+            max_items: self
+                .budget
+                .max_items
+                .map(|max_items| max_items.saturating_sub(self.items.get())),
+        }
+    }
+
+    /// Returns whether results were dropped so far.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated.get()
+    }
+
+    /// Stops tracking, wrapping the produced results.
+    pub fn finish<T>(self, items: Vec<T>) -> Budgeted<T> {
+        Budgeted {
+            items,
+            truncated: self.truncated.get(),
+            duration: self.stopwatch.elapsed(),
+        }
+    }
+}
+
+/// Measures elapsed time, including in WASM where `std::time::Instant` isn't available.
+#[derive(Debug, Clone, Copy)]
+struct Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    start: std::time::Instant,
+    #[cfg(target_arch = "wasm32")]
+    start: f64,
+}
+
+#[cfg(target_arch = "wasm32")]
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = Date, js_name = now)]
+    fn date_now() -> f64;
+}
+
+impl Stopwatch {
+    #[cfg(not(target_arch = "wasm32"))]
+    fn start() -> Self {
+        Stopwatch {
+            start: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn start() -> Self {
+        Stopwatch { start: date_now() }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    #[cfg(target_arch = "wasm32")]
+    fn elapsed(&self) -> Duration {
+        Duration::from_secs_f64((date_now() - self.start).max(0.0) / 1000.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_budget_max_items() {
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let tracker = budget.start();
+        let items: Vec<_> = (0..5).take_while(|_| tracker.allow()).collect();
+        let result = tracker.finish(items);
+
+        assert_eq!(result.items, vec![0, 1]);
+        assert!(result.truncated, "Dropped items should be flagged");
+    }
+
+    #[test]
+    fn test_budget_max_duration() {
+        let budget = Budget {
+            max_duration: Some(Duration::ZERO),
+            max_items: None,
+        };
+        let tracker = budget.start();
+        assert!(!tracker.has_time(), "A zero budget is exhausted at once");
+        assert!(!tracker.allow());
+        assert!(tracker.is_truncated());
+    }
+
+    #[test]
+    fn test_default_budgets_have_no_time_limit() {
+        let budgets = Budgets::default();
+        for budget in [
+            budgets.folding,
+            budgets.symbols,
+            budgets.colors,
+            budgets.diagnostics,
+        ] {
+            assert_eq!(budget.max_duration, None);
+            assert!(budget.max_items.is_some());
+        }
+    }
+
+    #[test]
+    fn test_budget_unlimited() {
+        let tracker = Budget::UNLIMITED.start();
+        assert!((0..10_000).all(|_| tracker.allow()));
+        assert!(!tracker.finish::<()>(Vec::new()).truncated);
+    }
+}
@@ -0,0 +1,88 @@
+use std::borrow::Cow;
+
+/// Folds the case of an identifier for matching, lowercasing ASCII letters only.
+///
+/// Property names, at-keywords, keywords like named colors, and hex digits are
+/// case-insensitive in CSS, but only for ASCII letters: `COLOR: RED` is `color: red`, while
+/// non-ASCII identifiers must match exactly.
+///
+/// # Arguments
+///
+/// * `text` - The identifier to fold.
+///
+/// # Returns
+///
+/// * The folded identifier, borrowed when it is already lowercase.
+pub fn fold_case(text: &str) -> Cow<'_, str> {
+    if text.bytes().any(|byte| byte.is_ascii_uppercase()) {
+        Cow::Owned(text.to_ascii_lowercase())
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Returns whether two identifiers are equal, ignoring ASCII case.
+pub fn eq_ignore_case(a: &str, b: &str) -> bool {
+    a.eq_ignore_ascii_case(b)
+}
+
+/// Returns whether an identifier starts with a prefix, ignoring ASCII case, like when
+/// filtering completions by what was typed.
+pub fn starts_with_ignore_case(text: &str, prefix: &str) -> bool {
+    text.len() >= prefix.len()
+        && text.is_char_boundary(prefix.len())
+        && text[..prefix.len()].eq_ignore_ascii_case(prefix)
+}
+
+/// Returns whether a list of lowercase keywords contains an identifier, ignoring ASCII case.
+pub fn contains_ignore_case(keywords: &[&str], text: &str) -> bool {
+    let text = fold_case(text);
+    keywords.contains(&text.as_ref())
+}
+
+/// Adapts the case of a lowercase replacement to the identifier it replaces, so edits
+/// follow the style of the document: `RED` is replaced in uppercase, anything else as is.
+///
+/// # Arguments
+///
+/// * `replacement` - The lowercase replacement text.
+/// * `original` - The replaced text.
+///
+/// # Returns
+///
+/// * The replacement, in uppercase if the original is all uppercase.
+pub fn match_case(replacement: &str, original: &str) -> String {
+    let has_letters = original.bytes().any(|byte| byte.is_ascii_alphabetic());
+    if has_letters && !original.bytes().any(|byte| byte.is_ascii_lowercase()) {
+        replacement.to_ascii_uppercase()
+    } else {
+        replacement.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fold_case() {
+        assert_eq!(fold_case("COLOR"), "color");
+        assert!(matches!(fold_case("color"), Cow::Borrowed(_)));
+        assert_eq!(fold_case("ÉCRAN"), "Écran", "Only ASCII letters are folded");
+    }
+
+    #[test]
+    fn test_matching_ignores_case() {
+        assert!(eq_ignore_case("Font-Face", "font-face"));
+        assert!(starts_with_ignore_case("BACKGROUND-COLOR", "back"));
+        assert!(!starts_with_ignore_case("é", "\u{65}"));
+        assert!(contains_ignore_case(&["woff2", "woff"], "WOFF2"));
+    }
+
+    #[test]
+    fn test_match_case() {
+        assert_eq!(match_case("#ff0000", "RED"), "#FF0000");
+        assert_eq!(match_case("red", "Red"), "red");
+        assert_eq!(match_case("local", "#123"), "local");
+    }
+}
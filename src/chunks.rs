@@ -0,0 +1,279 @@
+use crate::budget::{Budget, Budgeted};
+use crate::line_index::PositionEncoding;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use crate::rules::new_diagnostic;
+use lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
+
+/// Limits on the size of the documents parsed at once, so multi-megabyte generated
+/// stylesheets, like utility CSS, don't freeze the host.
+///
+/// Larger documents are parsed in chunks of whole top-level rules by the features that
+/// don't need the whole tree, folding, symbols and colors, keeping a single chunk in
+/// memory at a time. The other features skip them, diagnostics reporting why.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SizeLimits {
+    /// The size in bytes above which a document isn't parsed at once.
+    pub max_document_size: usize,
+    /// The size in bytes from which a chunk ends, at the end of the next top-level rule.
+    pub chunk_size: usize,
+}
+
+impl Default for SizeLimits {
+    fn default() -> Self {
+        SizeLimits {
+            max_document_size: 2 * 1024 * 1024,
+            chunk_size: 256 * 1024,
+        }
+    }
+}
+
+impl SizeLimits {
+    /// Returns whether a document is too large to be parsed at once.
+    pub fn exceeds(&self, source: &str) -> bool {
+        source.len() > self.max_document_size
+    }
+}
+
+/// A slice of a stylesheet made of whole top-level rules and statements, parsed on its own.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Chunk<'a> {
+    pub text: &'a str,
+    /// The byte offset of the chunk in the stylesheet.
+    pub offset: usize,
+    /// The position of the chunk in the stylesheet, in the encoding it was split with.
+    pub start: Position,
+}
+
+impl Chunk<'_> {
+    /// Converts a position in the chunk into a position in the stylesheet.
+    pub fn shift_position(&self, position: Position) -> Position {
+        if position.line == 0 {
+            Position::new(self.start.line, self.start.character + position.character)
+        } else {
+            Position::new(self.start.line + position.line, position.character)
+        }
+    }
+
+    /// Converts a range in the chunk into a range in the stylesheet.
+    pub fn shift_range(&self, range: Range) -> Range {
+        Range::new(
+            self.shift_position(range.start),
+            self.shift_position(range.end),
+        )
+    }
+}
+
+/// Splits a stylesheet into chunks of whole top-level rules and statements, each ending
+/// after the first top-level `}` or `;` once it reaches `chunk_size`.
+///
+/// The source is read as CSS tokens, so the braces of strings, comments and escapes don't
+/// count. A block that
+/// isn't closed runs to the end of the last chunk.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `chunk_size` - The size in bytes from which a chunk ends.
+/// * `encoding` - The `PositionEncoding` of the positions of the chunks.
+///
+/// # Returns
+///
+/// * The chunks, covering the whole source in order; a single chunk if it's small enough.
+pub fn split_chunks(source: &str, chunk_size: usize, encoding: PositionEncoding) -> Vec<Chunk<'_>> {
+    let mut chunks = Vec::new();
+    let mut chunk_offset = 0;
+    // The last position computed, to count characters from it rather than from line starts
+    let mut last = (0, Position::new(0, 0));
+    let mut depth = 0usize;
+    for token in Tokenizer::new(source, 0) {
+        let ends_statement = match token.kind {
+            TokenKind::LeftBrace => {
+                depth += 1;
+                false
+            }
+            TokenKind::RightBrace if depth > 0 => {
+                depth -= 1;
+                depth == 0
+            }
+            TokenKind::Semicolon => depth == 0,
+            _ => false,
+        };
+        let end = token.range.end;
+        if ends_statement && end - chunk_offset >= chunk_size && end < source.len() {
+            let start = advance(source, last, chunk_offset, encoding);
+            chunks.push(Chunk {
+                text: &source[chunk_offset..end],
+                offset: chunk_offset,
+                start,
+            });
+            last = (chunk_offset, start);
+            chunk_offset = end;
+        }
+    }
+    chunks.push(Chunk {
+        text: &source[chunk_offset..],
+        offset: chunk_offset,
+        start: advance(source, last, chunk_offset, encoding),
+    });
+    chunks
+}
+
+/// Runs a feature on each chunk of a stylesheet, against what's left of a shared budget.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `chunk_size` - The size in bytes from which a chunk ends, see `split_chunks`.
+/// * `encoding` - The `PositionEncoding` of the positions of the results.
+/// * `budget` - The `Budget` of the whole run.
+/// * `run` - Runs the feature on a chunk, shifting the positions of its results.
+///
+/// # Returns
+///
+/// * The results of every chunk in order, flagged as truncated if the budget ran out.
+pub(crate) fn run_in_chunks<T>(
+    source: &str,
+    chunk_size: usize,
+    encoding: PositionEncoding,
+    budget: &Budget,
+    mut run: impl FnMut(&Chunk, &Budget) -> Budgeted<T>,
+) -> Budgeted<T> {
+    let tracker = budget.start();
+    let mut items = Vec::new();
+    let mut truncated = false;
+    for chunk in split_chunks(source, chunk_size, encoding) {
+        if truncated || !tracker.has_time() {
+            break;
+        }
+        let chunk_result = run(&chunk, &tracker.remaining());
+        truncated = chunk_result.truncated;
+        items.extend(
+            chunk_result
+                .items
+                .into_iter()
+                .take_while(|_| tracker.allow()),
+        );
+    }
+    let mut result = tracker.finish(items);
+    result.truncated |= truncated;
+    result
+}
+
+/// Returns the diagnostic reported instead of the others for a document too large to be
+/// parsed at once.
+pub(crate) fn size_limit_diagnostic(source: &str, limits: &SizeLimits) -> Diagnostic {
+    new_diagnostic(
+        Range::default(),
+        DiagnosticSeverity::INFORMATION,
+        "size-limit",
+        format!(
+            "This stylesheet is too large to be checked ({}, the limit is {}): only folding, symbols and colors are available.",
+            format_size(source.len()),
+            format_size(limits.max_document_size),
+        ),
+        Vec::new(),
+    )
+}
+
+/// Formats a size in bytes, like `1.5 MB`.
+fn format_size(size: usize) -> String {
+    match size {
+        size if size >= 1024 * 1024 => format!("{:.1} MB", size as f64 / (1024.0 * 1024.0)),
+        size if size >= 1024 => format!("{:.1} KB", size as f64 / 1024.0),
+        size => format!("{size} bytes"),
+    }
+}
+
+/// Computes the position of an offset from an earlier offset and its position.
+fn advance(
+    source: &str,
+    (from, position): (usize, Position),
+    offset: usize,
+    encoding: PositionEncoding,
+) -> Position {
+    let text = &source[from..offset];
+    let length = |text: &str| text.chars().map(|c| encoding.len(c)).sum::<usize>() as u32;
+    match text.rfind('\n') {
+        Some(newline) => Position::new(
+            position.line + text.matches('\n').count() as u32,
+            length(&text[newline + 1..]),
+        ),
+        None => Position::new(position.line, position.character + length(text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::line_index::LineIndex;
+
+    #[test]
+    fn test_split_chunks() {
+        let source = "a { content: '}'; }\n/* } */ b { c {} }\n@import 'x';é{}";
+        let chunks = split_chunks(source, 1, PositionEncoding::Utf16);
+        let texts: Vec<&str> = chunks.iter().map(|chunk| chunk.text).collect();
+        assert_eq!(
+            texts,
+            vec![
+                "a { content: '}'; }",
+                "\n/* } */ b { c {} }",
+                "\n@import 'x';",
+                "é{}"
+            ]
+        );
+        let line_index = LineIndex::new(source);
+        for chunk in &chunks {
+            assert_eq!(chunk.start, line_index.position(source, chunk.offset));
+        }
+        let starts: Vec<Position> = split_chunks("a{}é;b{}", 1, PositionEncoding::Utf8)
+            .iter()
+            .map(|chunk| chunk.start)
+            .collect();
+        assert_eq!(starts[2], Position::new(0, 6));
+        let texts: Vec<&str> = split_chunks("a { b: url(}) }\nc {}", 1, PositionEncoding::Utf16)
+            .iter()
+            .map(|chunk| chunk.text)
+            .collect();
+        assert_eq!(texts, vec!["a { b: url(}) }", "\nc {}"]);
+        assert_eq!(split_chunks(source, 1024, PositionEncoding::Utf16).len(), 1);
+        assert_eq!(split_chunks("", 1, PositionEncoding::Utf16)[0].text, "");
+    }
+
+    #[test]
+    fn test_shift_range() {
+        let chunk = Chunk {
+            text: "a {}\nb {}",
+            offset: 10,
+            start: Position::new(3, 4),
+        };
+        let range = Range::new(Position::new(0, 2), Position::new(1, 3));
+        assert_eq!(
+            chunk.shift_range(range),
+            Range::new(Position::new(3, 6), Position::new(4, 3))
+        );
+    }
+
+    #[test]
+    fn test_run_in_chunks() {
+        let source = "a {}\nb {}\nc {}";
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let result = run_in_chunks(
+            source,
+            1,
+            PositionEncoding::Utf16,
+            &budget,
+            |chunk, budget| {
+                let tracker = budget.start();
+                let offsets = std::iter::once(chunk.offset)
+                    .take_while(|_| tracker.allow())
+                    .collect();
+                tracker.finish(offsets)
+            },
+        );
+        assert_eq!(result.items, vec![0, 4]);
+        assert!(result.truncated);
+    }
+}
@@ -0,0 +1,82 @@
+use wasm_bindgen::prelude::*;
+
+/// Selects whose conventions the features follow when several outputs are valid.
+///
+/// `VscodeCss` reproduces the output of `vscode-css-languageservice` for the same input,
+/// so editor extensions can swap implementations without behavioral differences.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CompatibilityMode {
+    /// The csslsrs conventions.
+    #[default]
+    Default,
+    /// The conventions of `vscode-css-languageservice`.
+    VscodeCss,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::folding::{get_folding_ranges_with_options, FoldingOptions};
+    use crate::features::symbols::{get_document_symbols_with_options, DocumentSymbolOptions};
+
+    /// Inputs with the folding ranges `vscode-css-languageservice` returns for them, as
+    /// `(start_line, end_line)` pairs.
+    const FOLDING_CASES: &[(&str, &[(u32, u32)])] = &[
+        ("body {\n  margin: 0;\n}\n", &[(0, 1)]),
+        ("body {\n  margin: 0; }\n", &[(0, 1)]),
+        ("body { margin: 0;\n}\n", &[]),
+        (
+            "@media screen {\n  .a {\n    color: red;\n  }\n}\n",
+            &[(0, 3), (1, 2)],
+        ),
+        (".a {\n\n}\n.b {\n  color: red;\n\n}\n", &[(0, 1), (3, 5)]),
+    ];
+
+    /// Inputs with the names of the symbols `vscode-css-languageservice` returns for them.
+    const SYMBOL_CASES: &[(&str, &[&str])] = &[
+        ("body, html { margin: 0; }", &["body", "html"]),
+        (
+            "@media screen and (min-width: 1px) { a {} }",
+            &["@media screen and (min-width: 1px)"],
+        ),
+        (
+            "@keyframes fade { to { opacity: 0; } }",
+            &["@keyframes fade"],
+        ),
+        ("@supports (display: grid) { a {} }", &["a"]),
+        ("@font-face { font-family: A; }", &["@font-face"]),
+    ];
+
+    #[test]
+    fn test_vscode_css_folding_ranges() {
+        let options = FoldingOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        for (code, expected) in FOLDING_CASES {
+            let folding_ranges: Vec<_> = get_folding_ranges_with_options(code, &options)
+                .iter()
+                .map(|range| (range.start_line, range.end_line))
+                .collect();
+            assert_eq!(
+                &folding_ranges, expected,
+                "Folding ranges differ for {:?}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_vscode_css_document_symbols() {
+        let options = DocumentSymbolOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        for (code, expected) in SYMBOL_CASES {
+            let symbols = get_document_symbols_with_options(code, &options);
+            let names: Vec<_> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+            assert_eq!(&names, expected, "Symbols differ for {:?}", code);
+        }
+    }
+}
@@ -0,0 +1,89 @@
+/// The built-in abbreviations, in the spirit of Emmet: an abbreviation either expands to a
+/// whole declaration, like `df` to `display: flex`, or to a property taking the numbers
+/// written after it, like `m` in `m10` to `margin`.
+pub const BUILTIN_ABBREVIATIONS: &[(&str, &str)] = &[
+    ("ac", "align-content"),
+    ("aic", "align-items: center"),
+    ("aifs", "align-items: flex-start"),
+    ("aife", "align-items: flex-end"),
+    ("b", "bottom"),
+    ("bd", "border"),
+    ("bdn", "border: none"),
+    ("bdrs", "border-radius"),
+    ("bdw", "border-width"),
+    ("bg", "background"),
+    ("bgc", "background-color"),
+    ("bxzbb", "box-sizing: border-box"),
+    ("c", "color"),
+    ("cp", "cursor: pointer"),
+    ("db", "display: block"),
+    ("df", "display: flex"),
+    ("dg", "display: grid"),
+    ("di", "display: inline"),
+    ("dib", "display: inline-block"),
+    ("dif", "display: inline-flex"),
+    ("dn", "display: none"),
+    ("fl", "float: left"),
+    ("fr", "float: right"),
+    ("fw", "font-weight"),
+    ("fwb", "font-weight: bold"),
+    ("fxdc", "flex-direction: column"),
+    ("fxdr", "flex-direction: row"),
+    ("fxg", "flex-grow"),
+    ("fxs", "flex-shrink"),
+    ("fxww", "flex-wrap: wrap"),
+    ("fz", "font-size"),
+    ("g", "gap"),
+    ("h", "height"),
+    ("jcc", "justify-content: center"),
+    ("jcsb", "justify-content: space-between"),
+    ("l", "left"),
+    ("lh", "line-height"),
+    ("m", "margin"),
+    ("mb", "margin-bottom"),
+    ("mah", "max-height"),
+    ("maw", "max-width"),
+    ("mih", "min-height"),
+    ("miw", "min-width"),
+    ("ml", "margin-left"),
+    ("mr", "margin-right"),
+    ("mt", "margin-top"),
+    ("op", "opacity"),
+    ("ord", "order"),
+    ("ova", "overflow: auto"),
+    ("ovh", "overflow: hidden"),
+    ("p", "padding"),
+    ("pb", "padding-bottom"),
+    ("pl", "padding-left"),
+    ("pr", "padding-right"),
+    ("pt", "padding-top"),
+    ("posa", "position: absolute"),
+    ("posf", "position: fixed"),
+    ("posr", "position: relative"),
+    ("poss", "position: sticky"),
+    ("r", "right"),
+    ("t", "top"),
+    ("tac", "text-align: center"),
+    ("tal", "text-align: left"),
+    ("tar", "text-align: right"),
+    ("tdn", "text-decoration: none"),
+    ("ttu", "text-transform: uppercase"),
+    ("w", "width"),
+    ("wsnw", "white-space: nowrap"),
+    ("z", "z-index"),
+];
+
+/// The properties whose numbers have no unit by default, like `z10` to `z-index: 10`.
+pub const UNITLESS_PROPERTIES: &[&str] = &[
+    "flex",
+    "flex-grow",
+    "flex-shrink",
+    "font-weight",
+    "line-height",
+    "opacity",
+    "order",
+    "z-index",
+];
+
+/// The unit aliases of abbreviations, like `e` in `m1.5e` to `margin: 1.5em`.
+pub const UNIT_ALIASES: &[(&str, &str)] = &[("p", "%"), ("e", "em"), ("r", "rem"), ("x", "ex")];
@@ -0,0 +1,372 @@
+use crate::case_folding::eq_ignore_case;
+use crate::dialect::Dialect;
+
+/// What is known about an at-rule, to complete and document it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AtRuleData {
+    /// The at-rule name, with its `@`.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The grammar of the rule, like `@supports <supports-condition> { <rule-list> }`.
+    pub syntax: &'static str,
+    /// The inserted rule, in the snippet syntax of the protocol, with tab stops like
+    /// `${1:name}`.
+    pub snippet: &'static str,
+    /// The dialects supporting the at-rule, or every dialect if empty.
+    pub dialects: &'static [Dialect],
+    /// Whether the at-rule is only valid at the top level of a stylesheet, like `@import`.
+    pub top_level: bool,
+}
+
+impl AtRuleData {
+    /// Returns whether the at-rule can be written in a document of a dialect.
+    pub fn supports(&self, dialect: Dialect) -> bool {
+        self.dialects.is_empty() || self.dialects.contains(&dialect)
+    }
+}
+
+const fn at_rule(
+    name: &'static str,
+    description: &'static str,
+    syntax: &'static str,
+    snippet: &'static str,
+) -> AtRuleData {
+    AtRuleData {
+        name,
+        description,
+        syntax,
+        snippet,
+        dialects: &[],
+        top_level: false,
+    }
+}
+
+const fn top_level(data: AtRuleData) -> AtRuleData {
+    AtRuleData {
+        top_level: true,
+        ..data
+    }
+}
+
+const fn only(dialects: &'static [Dialect], data: AtRuleData) -> AtRuleData {
+    AtRuleData { dialects, ..data }
+}
+
+/// The built-in at-rules, in alphabetical order.
+pub const BUILTIN_AT_RULES: &[AtRuleData] = &[
+    top_level(at_rule(
+        "@charset",
+        "Specifies the character encoding of the stylesheet. Must be its very first statement.",
+        "@charset \"<charset>\";",
+        "@charset \"${1:utf-8}\";",
+    )),
+    at_rule(
+        "@container",
+        "Applies styles depending on the size or style of a container.",
+        "@container <container-name>? <container-condition> { <rule-list> }",
+        "@container ${1:(min-width: ${2:400px})} {\n\t$0\n}",
+    ),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@each",
+            "Repeats styles for each element of a list or each pair of a map.",
+            "@each <variable># in <expression> { <rule-list> }",
+            "@each \\$${1:item} in ${2:\\$list} {\n\t$0\n}",
+        ),
+    ),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@extend",
+            "Inherits the styles of another selector.",
+            "@extend <selector> !optional?;",
+            "@extend ${1:selector};",
+        ),
+    ),
+    at_rule(
+        "@font-face",
+        "Declares a web font, with its family name and source.",
+        "@font-face { <declaration-list> }",
+        "@font-face {\n\tfont-family: \"${1:family}\";\n\tsrc: url(\"${2:font.woff2}\") format(\"woff2\");\n}",
+    ),
+    at_rule(
+        "@font-feature-values",
+        "Names the OpenType features of a font family, for `font-variant-alternates`.",
+        "@font-feature-values <family-name># { <declaration-rule-list> }",
+        "@font-feature-values ${1:family} {\n\t$0\n}",
+    ),
+    only(
+        &[Dialect::Scss],
+        top_level(at_rule(
+            "@forward",
+            "Loads a Sass module, and makes its members available to the modules loading this one.",
+            "@forward <url> [ show | hide <member># ]?;",
+            "@forward \"${1:module}\";",
+        )),
+    ),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@function",
+            "Defines a Sass function returning a value.",
+            "@function <name>( <arguments> ) { <statement-list> }",
+            "@function ${1:name}(${2:\\$argument}) {\n\t@return $0;\n}",
+        ),
+    ),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@if",
+            "Applies styles when a condition is true.",
+            "@if <expression> { <rule-list> } [ @else if <expression> { <rule-list> } ]* [ @else { <rule-list> } ]?",
+            "@if ${1:condition} {\n\t$0\n}",
+        ),
+    ),
+    top_level(at_rule(
+        "@import",
+        "Imports the rules of another stylesheet.",
+        "@import [ <url> | <string> ] [ layer | layer( <layer-name> ) ]? [ supports( <supports-condition> ) ]? <media-query-list>?;",
+        "@import \"${1:file.css}\";",
+    )),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@include",
+            "Includes the styles of a mixin.",
+            "@include <name>[( <arguments> )]?;",
+            "@include ${1:mixin};",
+        ),
+    ),
+    at_rule(
+        "@keyframes",
+        "Defines an animation, from its first to its last keyframe.",
+        "@keyframes <keyframes-name> { <keyframe-block-list> }",
+        "@keyframes ${1:name} {\n\tfrom {\n\t\t$2\n\t}\n\tto {\n\t\t$0\n\t}\n}",
+    ),
+    at_rule(
+        "@layer",
+        "Declares a cascade layer, whose rules are overridden by the layers declared after it.",
+        "@layer <layer-name>? { <rule-list> } | @layer <layer-name>#;",
+        "@layer ${1:name} {\n\t$0\n}",
+    ),
+    at_rule(
+        "@media",
+        "Applies styles depending on the device or the viewport, like its width.",
+        "@media <media-query-list> { <rule-list> }",
+        "@media (${1:min-width}: ${2:value}) {\n\t$0\n}",
+    ),
+    only(
+        &[Dialect::Scss],
+        at_rule(
+            "@mixin",
+            "Defines styles that can be included in other rules.",
+            "@mixin <name>[( <arguments> )]? { <rule-list> }",
+            "@mixin ${1:name} {\n\t$0\n}",
+        ),
+    ),
+    top_level(at_rule(
+        "@namespace",
+        "Declares the XML namespace of the type selectors of the stylesheet.",
+        "@namespace <namespace-prefix>? [ <string> | <url> ];",
+        "@namespace ${1:prefix} url(${2:http://www.w3.org/1999/xhtml});",
+    )),
+    at_rule(
+        "@page",
+        "Styles the pages of a printed document, like their margins.",
+        "@page <page-selector-list>? { <declaration-rule-list> }",
+        "@page {\n\t$0\n}",
+    ),
+    only(
+        &[Dialect::Less],
+        top_level(at_rule(
+            "@plugin",
+            "Loads a JavaScript plugin defining Less functions.",
+            "@plugin <string>;",
+            "@plugin \"${1:plugin}\";",
+        )),
+    ),
+    at_rule(
+        "@property",
+        "Registers a custom property, with its syntax, inheritance and initial value.",
+        "@property <custom-property-name> { <declaration-list> }",
+        "@property --${1:name} {\n\tsyntax: \"${2:<length>}\";\n\tinherits: ${3:false};\n\tinitial-value: ${4:0px};\n}",
+    ),
+    at_rule(
+        "@scope",
+        "Applies styles to the elements of a subtree, optionally up to a lower boundary.",
+        "@scope [ ( <scope-start> ) ]? [ to ( <scope-end> ) ]? { <rule-list> }",
+        "@scope (${1:.root}) {\n\t$0\n}",
+    ),
+    at_rule(
+        "@starting-style",
+        "Defines the styles transitioned from when an element is first rendered.",
+        "@starting-style { <rule-list> }",
+        "@starting-style {\n\t$0\n}",
+    ),
+    at_rule(
+        "@supports",
+        "Applies styles depending on whether the browser supports a feature.",
+        "@supports <supports-condition> { <rule-list> }",
+        "@supports (${1:display: grid}) {\n\t$0\n}",
+    ),
+    only(
+        &[Dialect::Scss],
+        top_level(at_rule(
+            "@use",
+            "Loads a Sass module, with its members available under a namespace.",
+            "@use <url> [ as <namespace> | as * ]? [ with ( <configuration> ) ]?;",
+            "@use \"${1:module}\";",
+        )),
+    ),
+];
+
+/// The descriptors of the built-in at-rules: at-rule, name, description, syntax and initial
+/// value.
+pub const BUILTIN_DESCRIPTORS: &[(&str, &str, &str, &str, Option<&str>)] = &[
+    (
+        "@font-face",
+        "ascent-override",
+        "The ascent of the font, as a percentage of the font size, to match the metrics of a fallback font.",
+        "normal | <percentage [0,∞]>",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "descent-override",
+        "The descent of the font, as a percentage of the font size, to match the metrics of a fallback font.",
+        "normal | <percentage [0,∞]>",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "font-display",
+        "How the font is displayed while it loads: with a fallback font or an invisible text, and for how long.",
+        "auto | block | swap | fallback | optional",
+        Some("auto"),
+    ),
+    (
+        "@font-face",
+        "font-family",
+        "The family name the font is referred to with, in `font-family` properties.",
+        "<family-name>",
+        None,
+    ),
+    (
+        "@font-face",
+        "font-feature-settings",
+        "The OpenType features enabled by default when the font is used, like `\"liga\" 0`.",
+        "normal | [ <string> [ <integer [0,∞]> | on | off ]? ]#",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "font-language-override",
+        "The language system used to choose the glyphs of the font, as an OpenType tag like `\"TRK\"`.",
+        "normal | <string>",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "font-named-instance",
+        "The named instance of a variable font used by default, like `\"Bold Condensed\"`.",
+        "auto | <string>",
+        Some("auto"),
+    ),
+    (
+        "@font-face",
+        "font-stretch",
+        "The widths of the font, as a keyword or a percentage, or a range of them.",
+        "auto | <font-stretch-absolute>{1,2}",
+        Some("auto"),
+    ),
+    (
+        "@font-face",
+        "font-style",
+        "The style of the font, or the range of angles of an oblique variable font.",
+        "auto | normal | italic | oblique [ <angle>{1,2} ]?",
+        Some("auto"),
+    ),
+    (
+        "@font-face",
+        "font-variation-settings",
+        "The values of the variation axes of a variable font used by default, like `\"wght\" 400`.",
+        "normal | [ <string> <number> ]#",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "font-weight",
+        "The weight of the font, or the range of weights of a variable font.",
+        "auto | <font-weight-absolute>{1,2}",
+        Some("auto"),
+    ),
+    (
+        "@font-face",
+        "line-gap-override",
+        "The line gap of the font, as a percentage of the font size, to match the metrics of a fallback font.",
+        "normal | <percentage [0,∞]>",
+        Some("normal"),
+    ),
+    (
+        "@font-face",
+        "size-adjust",
+        "A factor scaling the glyphs of the font, to match the size of a fallback font.",
+        "<percentage [0,∞]>",
+        Some("100%"),
+    ),
+    (
+        "@font-face",
+        "src",
+        "The sources of the font, tried in order: remote files with their format, or fonts installed locally.",
+        "[ <url> [ format( <font-format> ) ]? [ tech( <font-tech># ) ]? | local( <family-name> ) ]#",
+        None,
+    ),
+    (
+        "@font-face",
+        "unicode-range",
+        "The code points the font is used for, so it is only downloaded when the page uses one of them.",
+        "<unicode-range-token>#",
+        Some("U+0-10FFFF"),
+    ),
+    (
+        "@property",
+        "inherits",
+        "Whether the custom property inherits its value by default.",
+        "true | false",
+        None,
+    ),
+    (
+        "@property",
+        "initial-value",
+        "The initial value of the custom property, which must match its syntax unless the syntax is `*`.",
+        "<declaration-value>?",
+        None,
+    ),
+    (
+        "@property",
+        "syntax",
+        "The grammar of the values of the custom property, like `\"<length>\"` or `\"<color> | none\"`.",
+        "<string>",
+        None,
+    ),
+];
+
+/// Returns the grammar of a descriptor of a built-in at-rule, ignoring case.
+///
+/// # Arguments
+///
+/// * `at_rule` - The at-rule name, with its `@`, like `@font-face`.
+/// * `descriptor` - The descriptor name, like `font-display`.
+///
+/// # Returns
+///
+/// * The grammar of the descriptor, or `None` if it isn't a descriptor of the at-rule.
+pub fn descriptor_syntax(at_rule: &str, descriptor: &str) -> Option<&'static str> {
+    BUILTIN_DESCRIPTORS
+        .iter()
+        .find(|(name, known, ..)| {
+            eq_ignore_case(name, at_rule) && eq_ignore_case(known, descriptor)
+        })
+        .map(|(.., syntax, _)| *syntax)
+}
@@ -0,0 +1,163 @@
+use crate::case_folding::eq_ignore_case;
+use crate::data::properties::PropertyStatus;
+
+/// A keyword of a property that browsers are dropping or never standardized, with what to
+/// use instead. Whole properties are deprecated by their status in `CssData`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deprecation {
+    pub property: &'static str,
+    /// The deprecated keyword, like `break-word` for `word-break`.
+    pub value: &'static str,
+    pub status: PropertyStatus,
+    /// What to write instead, like `flex` for `-webkit-box`.
+    pub replacement: &'static str,
+    /// Whether the replacement can be written in place of the deprecated keyword, meaning
+    /// the same thing.
+    pub replaceable: bool,
+}
+
+const fn value(
+    property: &'static str,
+    value: &'static str,
+    status: PropertyStatus,
+    replacement: &'static str,
+    replaceable: bool,
+) -> Deprecation {
+    Deprecation {
+        property,
+        value,
+        status,
+        replacement,
+        replaceable,
+    }
+}
+
+/// The deprecated keywords, by property.
+pub const DEPRECATIONS: &[Deprecation] = &[
+    value(
+        "display",
+        "-moz-box",
+        PropertyStatus::Nonstandard,
+        "flex",
+        true,
+    ),
+    value(
+        "display",
+        "-moz-inline-box",
+        PropertyStatus::Nonstandard,
+        "inline-flex",
+        true,
+    ),
+    value(
+        "display",
+        "-webkit-box",
+        PropertyStatus::Nonstandard,
+        "flex",
+        true,
+    ),
+    value(
+        "display",
+        "-webkit-inline-box",
+        PropertyStatus::Nonstandard,
+        "inline-flex",
+        true,
+    ),
+    value(
+        "overflow",
+        "overlay",
+        PropertyStatus::Obsolete,
+        "auto",
+        true,
+    ),
+    value(
+        "overflow-x",
+        "overlay",
+        PropertyStatus::Obsolete,
+        "auto",
+        true,
+    ),
+    value(
+        "overflow-y",
+        "overlay",
+        PropertyStatus::Obsolete,
+        "auto",
+        true,
+    ),
+    value(
+        "white-space",
+        "-moz-pre-wrap",
+        PropertyStatus::Nonstandard,
+        "pre-wrap",
+        true,
+    ),
+    value(
+        "word-break",
+        "break-word",
+        PropertyStatus::Obsolete,
+        "overflow-wrap: anywhere",
+        false,
+    ),
+];
+
+/// What to write instead of the obsolete built-in properties, and whether it means the
+/// same thing.
+pub const PROPERTY_REPLACEMENTS: &[(&str, &str, bool)] = &[
+    ("clip", "clip-path", false),
+    ("grid-column-gap", "column-gap", true),
+    ("grid-gap", "gap", true),
+    ("grid-row-gap", "row-gap", true),
+    ("page-break-after", "break-after", false),
+    ("page-break-before", "break-before", false),
+    ("page-break-inside", "break-inside", false),
+    ("scroll-snap-coordinate", "scroll-snap-align", false),
+    ("scroll-snap-destination", "scroll-snap-align", false),
+    ("scroll-snap-points-x", "scroll-snap-type", false),
+    ("scroll-snap-points-y", "scroll-snap-type", false),
+    ("text-decoration-skip", "text-decoration-skip-ink", false),
+    ("word-wrap", "overflow-wrap", true),
+];
+
+/// Returns what to write instead of an obsolete property, ignoring case, and whether it
+/// means the same thing.
+pub fn property_replacement(name: &str) -> Option<(&'static str, bool)> {
+    PROPERTY_REPLACEMENTS
+        .iter()
+        .find(|(property, ..)| eq_ignore_case(property, name))
+        .map(|(_, replacement, replaceable)| (*replacement, *replaceable))
+}
+
+/// Returns the deprecation of a keyword of a property, ignoring case.
+pub fn deprecated_value(property: &str, value: &str) -> Option<&'static Deprecation> {
+    DEPRECATIONS.iter().find(|deprecation| {
+        eq_ignore_case(deprecation.property, property) && eq_ignore_case(deprecation.value, value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::properties::CssData;
+
+    #[test]
+    fn test_deprecations() {
+        assert!(DEPRECATIONS
+            .windows(2)
+            .all(|pair| pair[0].property <= pair[1].property));
+        assert!(PROPERTY_REPLACEMENTS
+            .windows(2)
+            .all(|pair| pair[0] < pair[1]));
+        assert!(PROPERTY_REPLACEMENTS.iter().all(|(property, ..)| {
+            CssData::builtin()
+                .property(property)
+                .is_some_and(|property| property.status == PropertyStatus::Obsolete)
+        }));
+        assert_eq!(
+            property_replacement("Word-Wrap"),
+            Some(("overflow-wrap", true))
+        );
+        assert_eq!(property_replacement("word-break"), None);
+        assert!(deprecated_value("word-break", "BREAK-WORD").is_some());
+        assert!(deprecated_value("word-break", "break-all").is_none());
+        assert!(deprecated_value("display", "-webkit-box").is_some());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::case_folding::{eq_ignore_case, fold_case};
+
+/// The values a media feature takes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaFeatureType {
+    /// A `<length>`, like `600px`.
+    Length,
+    /// A `<resolution>`, like `2dppx`, or `infinite`.
+    Resolution,
+    /// A `<ratio>`, like `16 / 9`.
+    Ratio,
+    /// A non-negative `<integer>`, like the bits per color component of `color`.
+    Integer,
+    /// One of a few keywords, like `portrait` or `landscape`.
+    Keywords(&'static [&'static str]),
+}
+
+/// What is known about a media feature, to validate, complete and document it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MediaFeatureData {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub value_type: MediaFeatureType,
+    /// Whether the feature is a range feature, compared with `min-` and `max-` prefixes or
+    /// the range syntax, like `width`, rather than a discrete one, like `orientation`.
+    pub range: bool,
+    /// The feature to use instead, if the feature is deprecated.
+    pub replacement: Option<&'static str>,
+}
+
+const fn range(
+    name: &'static str,
+    value_type: MediaFeatureType,
+    description: &'static str,
+) -> MediaFeatureData {
+    MediaFeatureData {
+        name,
+        description,
+        value_type,
+        range: true,
+        replacement: None,
+    }
+}
+
+const fn discrete(
+    name: &'static str,
+    keywords: &'static [&'static str],
+    description: &'static str,
+) -> MediaFeatureData {
+    MediaFeatureData {
+        name,
+        description,
+        value_type: MediaFeatureType::Keywords(keywords),
+        range: false,
+        replacement: None,
+    }
+}
+
+const fn deprecated(
+    name: &'static str,
+    value_type: MediaFeatureType,
+    replacement: &'static str,
+    description: &'static str,
+) -> MediaFeatureData {
+    MediaFeatureData {
+        name,
+        description,
+        value_type,
+        range: true,
+        replacement: Some(replacement),
+    }
+}
+
+const POINTERS: &[&str] = &["none", "coarse", "fine"];
+const HOVER: &[&str] = &["none", "hover"];
+const DYNAMIC_RANGES: &[&str] = &["standard", "high"];
+const PREFERENCES: &[&str] = &["no-preference", "reduce"];
+
+/// The media features of Media Queries Level 5, in alphabetical order.
+pub const MEDIA_FEATURES: &[MediaFeatureData] = &[
+    discrete(
+        "any-hover",
+        HOVER,
+        "Whether any input mechanism can hover over elements.",
+    ),
+    discrete(
+        "any-pointer",
+        POINTERS,
+        "The accuracy of the most accurate pointing device, if any.",
+    ),
+    range(
+        "aspect-ratio",
+        MediaFeatureType::Ratio,
+        "The ratio of the width to the height of the viewport.",
+    ),
+    range(
+        "color",
+        MediaFeatureType::Integer,
+        "The number of bits per color component of the output device, 0 if it has no color.",
+    ),
+    discrete(
+        "color-gamut",
+        &["srgb", "p3", "rec2020"],
+        "The range of colors supported by the browser and the output device.",
+    ),
+    range(
+        "color-index",
+        MediaFeatureType::Integer,
+        "The number of entries in the color lookup table of the output device.",
+    ),
+    deprecated(
+        "device-aspect-ratio",
+        MediaFeatureType::Ratio,
+        "aspect-ratio",
+        "The ratio of the width to the height of the output device.",
+    ),
+    deprecated(
+        "device-height",
+        MediaFeatureType::Length,
+        "height",
+        "The height of the rendering surface of the output device.",
+    ),
+    deprecated(
+        "device-width",
+        MediaFeatureType::Length,
+        "width",
+        "The width of the rendering surface of the output device.",
+    ),
+    discrete(
+        "display-mode",
+        &[
+            "browser",
+            "fullscreen",
+            "minimal-ui",
+            "picture-in-picture",
+            "standalone",
+            "window-controls-overlay",
+        ],
+        "The mode in which a web application is displayed, like `standalone`.",
+    ),
+    discrete(
+        "dynamic-range",
+        DYNAMIC_RANGES,
+        "The combination of brightness, contrast and color depth supported by the browser and the output device.",
+    ),
+    discrete(
+        "forced-colors",
+        &["none", "active"],
+        "Whether the browser restricts the colors of the page, like in high contrast modes.",
+    ),
+    MediaFeatureData {
+        name: "grid",
+        description: "Whether the output device is grid-based, like a terminal, rather than bitmap-based.",
+        value_type: MediaFeatureType::Integer,
+        range: false,
+        replacement: None,
+    },
+    range(
+        "height",
+        MediaFeatureType::Length,
+        "The height of the viewport, scrollbar included.",
+    ),
+    discrete(
+        "hover",
+        HOVER,
+        "Whether the primary input mechanism can hover over elements.",
+    ),
+    discrete(
+        "inverted-colors",
+        &["none", "inverted"],
+        "Whether the browser or the operating system inverts colors.",
+    ),
+    range(
+        "monochrome",
+        MediaFeatureType::Integer,
+        "The number of bits per pixel of a monochrome output device, 0 if it isn't monochrome.",
+    ),
+    discrete(
+        "orientation",
+        &["portrait", "landscape"],
+        "Whether the viewport is taller than it is wide.",
+    ),
+    discrete(
+        "overflow-block",
+        &["none", "scroll", "paged"],
+        "How the output device handles content overflowing the viewport along the block axis.",
+    ),
+    discrete(
+        "overflow-inline",
+        &["none", "scroll"],
+        "Whether content overflowing the viewport along the inline axis can be scrolled.",
+    ),
+    discrete(
+        "pointer",
+        POINTERS,
+        "The accuracy of the primary pointing device, if any.",
+    ),
+    discrete(
+        "prefers-color-scheme",
+        &["light", "dark"],
+        "Whether the user prefers a light or a dark color theme.",
+    ),
+    discrete(
+        "prefers-contrast",
+        &["no-preference", "more", "less", "custom"],
+        "Whether the user prefers more or less contrast between colors.",
+    ),
+    discrete(
+        "prefers-reduced-data",
+        PREFERENCES,
+        "Whether the user prefers content using less data.",
+    ),
+    discrete(
+        "prefers-reduced-motion",
+        PREFERENCES,
+        "Whether the user prefers less motion, like fewer animations.",
+    ),
+    discrete(
+        "prefers-reduced-transparency",
+        PREFERENCES,
+        "Whether the user prefers fewer transparency and blur effects.",
+    ),
+    range(
+        "resolution",
+        MediaFeatureType::Resolution,
+        "The pixel density of the output device.",
+    ),
+    discrete(
+        "scan",
+        &["interlace", "progressive"],
+        "How a television output device draws the lines of the screen.",
+    ),
+    discrete(
+        "scripting",
+        &["none", "initial-only", "enabled"],
+        "Whether scripts, like JavaScript, are available.",
+    ),
+    discrete(
+        "update",
+        &["none", "slow", "fast"],
+        "How often the output device can update the content once it's rendered.",
+    ),
+    discrete(
+        "video-dynamic-range",
+        DYNAMIC_RANGES,
+        "The combination of brightness, contrast and color depth supported by the video plane of the output device.",
+    ),
+    range(
+        "width",
+        MediaFeatureType::Length,
+        "The width of the viewport, scrollbar included.",
+    ),
+];
+
+/// Returns a media feature by name, ignoring case, with its `min-` or `max-` prefix
+/// removed for range features.
+///
+/// # Arguments
+///
+/// * `name` - The name of the feature as written, like `min-width`.
+///
+/// # Returns
+///
+/// * The `MediaFeatureData` of the feature, and whether the name is prefixed, or `None`
+///   for unknown features and prefixed discrete features, like `min-orientation`.
+pub fn media_feature(name: &str) -> Option<(&'static MediaFeatureData, bool)> {
+    let name = fold_case(name);
+    let find = |name: &str| {
+        MEDIA_FEATURES
+            .binary_search_by(|feature| feature.name.cmp(name))
+            .ok()
+            .map(|idx| &MEDIA_FEATURES[idx])
+    };
+    if let Some(feature) = find(&name) {
+        return Some((feature, false));
+    }
+    let unprefixed = name
+        .strip_prefix("min-")
+        .or_else(|| name.strip_prefix("max-"))?;
+    find(unprefixed)
+        .filter(|feature| feature.range)
+        .map(|feature| (feature, true))
+}
+
+/// Returns whether a keyword is a value of a discrete media feature, ignoring case.
+pub fn is_media_feature_keyword(feature: &MediaFeatureData, keyword: &str) -> bool {
+    match feature.value_type {
+        MediaFeatureType::Keywords(keywords) => {
+            keywords.iter().any(|known| eq_ignore_case(known, keyword))
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_media_features_are_sorted() {
+        assert!(MEDIA_FEATURES
+            .windows(2)
+            .all(|pair| pair[0].name < pair[1].name));
+    }
+
+    #[test]
+    fn test_media_feature() {
+        assert_eq!(
+            media_feature("Min-Width").map(|(f, p)| (f.name, p)),
+            Some(("width", true))
+        );
+        assert_eq!(
+            media_feature("hover").map(|(f, p)| (f.name, p)),
+            Some(("hover", false))
+        );
+        assert_eq!(media_feature("min-hover"), None);
+        assert_eq!(media_feature("widht"), None);
+        let (orientation, _) = media_feature("orientation").unwrap();
+        assert!(is_media_feature_keyword(orientation, "PORTRAIT"));
+        assert!(!is_media_feature_keyword(orientation, "diagonal"));
+    }
+}
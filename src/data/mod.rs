@@ -0,0 +1,10 @@
+pub mod abbreviations;
+pub mod at_rules;
+pub mod colors;
+pub mod deprecated;
+pub mod media_features;
+pub mod properties;
+pub mod property_names;
+pub mod property_syntaxes;
+pub mod pseudos;
+pub mod values;
@@ -0,0 +1,680 @@
+use crate::case_folding::fold_case;
+use crate::data::at_rules::{BUILTIN_AT_RULES, BUILTIN_DESCRIPTORS};
+use crate::data::property_syntaxes::property_syntax;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+/// A keyword of a property, like `flex` for `display`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ValueData {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+/// The standardization status of a property, as in VS Code custom data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PropertyStatus {
+    #[default]
+    Standard,
+    /// Specified, but not yet supported by every browser.
+    Experimental,
+    /// Only supported by some browsers, outside of any specification.
+    Nonstandard,
+    /// Deprecated, and possibly removed from browsers.
+    Obsolete,
+}
+
+/// What is known about a property, to complete and document it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct PropertyData {
+    /// The property name, lowercased unless it is a custom property.
+    pub name: String,
+    pub description: Option<String>,
+    /// The grammar of the value, like `<length> | auto`.
+    pub syntax: Option<String>,
+    /// The keywords of the value.
+    #[serde(default)]
+    pub values: Vec<ValueData>,
+    /// The initial value, like `visible` for `overflow`.
+    #[serde(default)]
+    pub initial: Option<String>,
+    #[serde(default)]
+    pub status: PropertyStatus,
+}
+
+/// What is known about an at-rule, to document it, as the `atDirectives` of VS Code custom
+/// data.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct AtDirectiveData {
+    /// The at-rule name, lowercased, with its `@`.
+    pub name: String,
+    pub description: Option<String>,
+    /// The grammar of the rule, like `@supports <supports-condition> { <rule-list> }`.
+    #[serde(default)]
+    pub syntax: Option<String>,
+    /// The descriptors of its block, like `font-display` in `@font-face`, lowercased.
+    #[serde(default)]
+    pub descriptors: Vec<PropertyData>,
+}
+
+/// The properties and at-rules known to the language service, built in or added by
+/// embedders.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CssData {
+    properties: BTreeMap<String, PropertyData>,
+    at_directives: BTreeMap<String, AtDirectiveData>,
+}
+
+/// The built-in properties: name, description, and keywords.
+const BUILTIN_PROPERTIES: &[(&str, &str, &[&str])] = &[
+    (
+        "align-items",
+        "Aligns the items of a flex or grid container on the cross axis.",
+        &[
+            "normal",
+            "stretch",
+            "center",
+            "start",
+            "end",
+            "flex-start",
+            "flex-end",
+            "baseline",
+        ],
+    ),
+    (
+        "box-sizing",
+        "Whether the width and height of boxes include their padding and border.",
+        &["content-box", "border-box"],
+    ),
+    (
+        "clip",
+        "Clips an absolutely positioned element to a rectangle. Replaced by `clip-path`.",
+        &["auto"],
+    ),
+    (
+        "cursor",
+        "The mouse cursor displayed when the pointer is over an element.",
+        &[
+            "auto",
+            "default",
+            "pointer",
+            "text",
+            "move",
+            "not-allowed",
+            "grab",
+            "wait",
+        ],
+    ),
+    (
+        "display",
+        "How an element generates boxes, and how its children are laid out.",
+        &[
+            "none",
+            "block",
+            "inline",
+            "inline-block",
+            "flex",
+            "inline-flex",
+            "grid",
+            "inline-grid",
+            "contents",
+            "table",
+            "list-item",
+        ],
+    ),
+    (
+        "field-sizing",
+        "Whether form controls are sized to their content.",
+        &["content", "fixed"],
+    ),
+    (
+        "flex-direction",
+        "The direction of the main axis of a flex container.",
+        &["row", "row-reverse", "column", "column-reverse"],
+    ),
+    (
+        "flex-wrap",
+        "Whether the items of a flex container wrap onto several lines.",
+        &["nowrap", "wrap", "wrap-reverse"],
+    ),
+    (
+        "font-style",
+        "Whether the font is normal, italic or oblique.",
+        &["normal", "italic", "oblique"],
+    ),
+    (
+        "font-weight",
+        "The weight, or boldness, of the font.",
+        &["normal", "bold", "bolder", "lighter"],
+    ),
+    (
+        "grid-column-gap",
+        "The gap between the columns of a grid. Replaced by `column-gap`.",
+        &["normal"],
+    ),
+    (
+        "grid-gap",
+        "The gaps between the rows and the columns of a grid. Replaced by `gap`.",
+        &["normal"],
+    ),
+    (
+        "grid-row-gap",
+        "The gap between the rows of a grid. Replaced by `row-gap`.",
+        &["normal"],
+    ),
+    (
+        "ime-mode",
+        "The state of the input method editor of a text field. Dropped by browsers.",
+        &["auto", "normal", "active", "inactive", "disabled"],
+    ),
+    (
+        "justify-content",
+        "Distributes the space between and around the items of a container on the main axis.",
+        &[
+            "normal",
+            "center",
+            "start",
+            "end",
+            "flex-start",
+            "flex-end",
+            "space-between",
+            "space-around",
+            "space-evenly",
+        ],
+    ),
+    (
+        "overflow",
+        "What happens to content overflowing an element's box.",
+        &["visible", "hidden", "clip", "scroll", "auto"],
+    ),
+    (
+        "page-break-after",
+        "Whether a page break happens after an element when printing. Replaced by `break-after`.",
+        &["auto", "always", "avoid", "left", "right"],
+    ),
+    (
+        "page-break-before",
+        "Whether a page break happens before an element when printing. Replaced by `break-before`.",
+        &["auto", "always", "avoid", "left", "right"],
+    ),
+    (
+        "page-break-inside",
+        "Whether a page break can happen inside an element when printing. Replaced by `break-inside`.",
+        &["auto", "avoid"],
+    ),
+    (
+        "pointer-events",
+        "Whether an element can be the target of pointer events.",
+        &["auto", "none"],
+    ),
+    (
+        "position",
+        "How an element is positioned in the document.",
+        &["static", "relative", "absolute", "fixed", "sticky"],
+    ),
+    (
+        "scroll-snap-coordinate",
+        "The snap points of an element in its scroll container. Replaced by `scroll-snap-align`.",
+        &["none"],
+    ),
+    (
+        "scroll-snap-destination",
+        "Where a scroll container snaps its snap points. Replaced by `scroll-snap-align`.",
+        &[],
+    ),
+    (
+        "scroll-snap-points-x",
+        "The horizontal snap points of a scroll container. Replaced by `scroll-snap-type`.",
+        &["none"],
+    ),
+    (
+        "scroll-snap-points-y",
+        "The vertical snap points of a scroll container. Replaced by `scroll-snap-type`.",
+        &["none"],
+    ),
+    (
+        "text-align",
+        "The horizontal alignment of the inline content of a block.",
+        &["start", "end", "left", "right", "center", "justify"],
+    ),
+    (
+        "text-decoration-skip",
+        "What the text decoration skips over. Replaced by `text-decoration-skip-ink`.",
+        &["none", "objects", "spaces", "edges", "box-decoration"],
+    ),
+    (
+        "text-transform",
+        "How the text of an element is capitalized.",
+        &["none", "capitalize", "uppercase", "lowercase", "full-width"],
+    ),
+    (
+        "visibility",
+        "Shows or hides an element without changing the layout.",
+        &["visible", "hidden", "collapse"],
+    ),
+    (
+        "white-space",
+        "How white space and line breaks inside an element are handled.",
+        &[
+            "normal",
+            "nowrap",
+            "pre",
+            "pre-wrap",
+            "pre-line",
+            "break-spaces",
+        ],
+    ),
+    (
+        "word-wrap",
+        "Whether words can break to prevent overflowing. Replaced by `overflow-wrap`.",
+        &["normal", "break-word", "anywhere"],
+    ),
+];
+
+/// The built-in properties that aren't standard.
+const BUILTIN_STATUSES: &[(&str, PropertyStatus)] = &[
+    ("clip", PropertyStatus::Obsolete),
+    ("field-sizing", PropertyStatus::Experimental),
+    ("grid-column-gap", PropertyStatus::Obsolete),
+    ("grid-gap", PropertyStatus::Obsolete),
+    ("grid-row-gap", PropertyStatus::Obsolete),
+    ("ime-mode", PropertyStatus::Obsolete),
+    ("page-break-after", PropertyStatus::Obsolete),
+    ("page-break-before", PropertyStatus::Obsolete),
+    ("page-break-inside", PropertyStatus::Obsolete),
+    ("scroll-snap-coordinate", PropertyStatus::Obsolete),
+    ("scroll-snap-destination", PropertyStatus::Obsolete),
+    ("scroll-snap-points-x", PropertyStatus::Obsolete),
+    ("scroll-snap-points-y", PropertyStatus::Obsolete),
+    ("text-decoration-skip", PropertyStatus::Obsolete),
+    ("word-wrap", PropertyStatus::Obsolete),
+];
+
+/// The initial values of the built-in properties.
+const BUILTIN_INITIAL_VALUES: &[(&str, &str)] = &[
+    ("align-items", "normal"),
+    ("box-sizing", "content-box"),
+    ("clip", "auto"),
+    ("cursor", "auto"),
+    ("display", "inline"),
+    ("field-sizing", "fixed"),
+    ("flex-direction", "row"),
+    ("flex-wrap", "nowrap"),
+    ("font-style", "normal"),
+    ("font-weight", "normal"),
+    ("grid-column-gap", "normal"),
+    ("grid-gap", "normal"),
+    ("grid-row-gap", "normal"),
+    ("ime-mode", "auto"),
+    ("justify-content", "normal"),
+    ("overflow", "visible"),
+    ("page-break-after", "auto"),
+    ("page-break-before", "auto"),
+    ("page-break-inside", "auto"),
+    ("pointer-events", "auto"),
+    ("position", "static"),
+    ("scroll-snap-coordinate", "none"),
+    ("scroll-snap-destination", "0px 0px"),
+    ("scroll-snap-points-x", "none"),
+    ("scroll-snap-points-y", "none"),
+    ("text-align", "start"),
+    ("text-decoration-skip", "objects"),
+    ("text-transform", "none"),
+    ("visibility", "visible"),
+    ("white-space", "normal"),
+    ("word-wrap", "normal"),
+];
+
+impl CssData {
+    /// Returns the built-in properties, shared by every feature without custom data.
+    pub fn builtin() -> &'static CssData {
+        static BUILTIN: OnceLock<CssData> = OnceLock::new();
+        BUILTIN.get_or_init(|| {
+            let properties = BUILTIN_PROPERTIES
+                .iter()
+                .map(|(name, description, values)| {
+                    let property = PropertyData {
+                        name: name.to_string(),
+                        description: Some(description.to_string()),
+                        syntax: property_syntax(name).map(str::to_string),
+                        values: values
+                            .iter()
+                            .map(|value| ValueData {
+                                name: value.to_string(),
+                                description: None,
+                            })
+                            .collect(),
+                        initial: BUILTIN_INITIAL_VALUES
+                            .iter()
+                            .find(|(property, _)| property == name)
+                            .map(|(_, initial)| initial.to_string()),
+                        status: BUILTIN_STATUSES
+                            .iter()
+                            .find(|(property, _)| property == name)
+                            .map_or(PropertyStatus::Standard, |(_, status)| *status),
+                    };
+                    (name.to_string(), property)
+                })
+                .collect();
+            let at_directives = BUILTIN_AT_RULES
+                .iter()
+                .map(|at_rule| {
+                    let descriptors = BUILTIN_DESCRIPTORS
+                        .iter()
+                        .filter(|(name, ..)| *name == at_rule.name)
+                        .map(|(_, name, description, syntax, initial)| PropertyData {
+                            name: name.to_string(),
+                            description: Some(description.to_string()),
+                            syntax: Some(syntax.to_string()),
+                            values: Vec::new(),
+                            initial: initial.map(str::to_string),
+                            status: PropertyStatus::Standard,
+                        })
+                        .collect();
+                    let at_directive = AtDirectiveData {
+                        name: at_rule.name.to_string(),
+                        description: Some(at_rule.description.to_string()),
+                        syntax: Some(at_rule.syntax.to_string()),
+                        descriptors,
+                    };
+                    (at_rule.name.to_string(), at_directive)
+                })
+                .collect();
+            CssData {
+                properties,
+                at_directives,
+            }
+        })
+    }
+
+    /// Returns a property, ignoring case unless it is a custom property.
+    pub fn property(&self, name: &str) -> Option<&PropertyData> {
+        self.properties.get(property_key(name).as_str())
+    }
+
+    /// Returns every property, sorted by name.
+    pub fn properties(&self) -> impl Iterator<Item = &PropertyData> {
+        self.properties.values()
+    }
+
+    /// Returns an at-rule, like `@supports`, ignoring case.
+    pub fn at_directive(&self, name: &str) -> Option<&AtDirectiveData> {
+        self.at_directives.get(fold_case(name).as_ref())
+    }
+
+    /// Returns every at-rule, sorted by name.
+    pub fn at_directives(&self) -> impl Iterator<Item = &AtDirectiveData> {
+        self.at_directives.values()
+    }
+
+    /// Returns a descriptor of an at-rule, like `font-display` in `@font-face`, ignoring
+    /// case.
+    pub fn descriptor(&self, at_rule: &str, name: &str) -> Option<&PropertyData> {
+        let name = fold_case(name);
+        self.at_directive(at_rule)?
+            .descriptors
+            .iter()
+            .find(|descriptor| descriptor.name == name)
+    }
+}
+
+/// Normalizes a property name; custom properties are case-sensitive.
+fn property_key(name: &str) -> String {
+    if name.starts_with("--") {
+        name.to_string()
+    } else {
+        fold_case(name).into_owned()
+    }
+}
+
+/// Alters a set of properties, adding, overriding or removing properties, for embedders
+/// that don't want to generate custom data JSON.
+///
+/// ```
+/// use csslsrs::data::properties::CssDataBuilder;
+///
+/// let data = CssDataBuilder::new()
+///     .property("--ds-radius")
+///     .description("The corner radius of the design system.")
+///     .values(["var(--ds-radius-sm)", "var(--ds-radius-lg)"])
+///     .property("display")
+///     .value("masonry", Some("Experimental masonry layout."))
+///     .build();
+/// assert_eq!(data.property("--ds-radius").unwrap().values.len(), 2);
+/// assert!(data.property("DISPLAY").unwrap().values.len() > 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct CssDataBuilder {
+    data: CssData,
+}
+
+impl Default for CssDataBuilder {
+    fn default() -> Self {
+        CssDataBuilder {
+            data: CssData::builtin().clone(),
+        }
+    }
+}
+
+impl CssDataBuilder {
+    /// Starts from the built-in properties.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts from an existing set of properties, like the data of a `LanguageService`.
+    pub fn from_data(data: &CssData) -> Self {
+        CssDataBuilder { data: data.clone() }
+    }
+
+    /// Starts from no property at all.
+    pub fn empty() -> Self {
+        CssDataBuilder {
+            data: CssData::default(),
+        }
+    }
+
+    /// Starts editing a property, creating it if it isn't known yet.
+    pub fn property(self, name: &str) -> PropertyBuilder {
+        let key = property_key(name);
+        PropertyBuilder {
+            property: self
+                .data
+                .properties
+                .get(&key)
+                .cloned()
+                .unwrap_or_else(|| PropertyData {
+                    name: key,
+                    description: None,
+                    syntax: None,
+                    values: Vec::new(),
+                    initial: None,
+                    status: PropertyStatus::Standard,
+                }),
+            builder: self,
+        }
+    }
+
+    /// Removes a property.
+    pub fn remove(mut self, name: &str) -> Self {
+        self.data.properties.remove(&property_key(name));
+        self
+    }
+
+    /// Adds the properties and at-rules of a VS Code custom data file, like
+    /// `{ "version": 1.1, "properties": [{ "name": "--a", "values": [{ "name": "b" }] }] }`,
+    /// replacing the properties and at-rules with the same name.
+    ///
+    /// # Returns
+    ///
+    /// * The builder, or the error of the invalid JSON.
+    pub fn custom_data(mut self, json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename_all = "camelCase")]
+        struct CustomData {
+            #[serde(default)]
+            properties: Vec<PropertyData>,
+            #[serde(default)]
+            at_directives: Vec<AtDirectiveData>,
+        }
+
+        let custom_data: CustomData = serde_json::from_str(json)?;
+        for mut property in custom_data.properties {
+            property.name = property_key(&property.name);
+            self.data.properties.insert(property.name.clone(), property);
+        }
+        for mut at_directive in custom_data.at_directives {
+            at_directive.name = fold_case(&at_directive.name).into_owned();
+            for descriptor in &mut at_directive.descriptors {
+                descriptor.name = fold_case(&descriptor.name).into_owned();
+            }
+            self.data
+                .at_directives
+                .insert(at_directive.name.clone(), at_directive);
+        }
+        Ok(self)
+    }
+
+    pub fn build(self) -> CssData {
+        self.data
+    }
+}
+
+/// Edits a property of a `CssDataBuilder`, see `CssDataBuilder::property`.
+#[derive(Debug, Clone)]
+pub struct PropertyBuilder {
+    builder: CssDataBuilder,
+    property: PropertyData,
+}
+
+impl PropertyBuilder {
+    pub fn description(mut self, description: &str) -> Self {
+        self.property.description = Some(description.to_string());
+        self
+    }
+
+    pub fn syntax(mut self, syntax: &str) -> Self {
+        self.property.syntax = Some(syntax.to_string());
+        self
+    }
+
+    pub fn initial(mut self, initial: &str) -> Self {
+        self.property.initial = Some(initial.to_string());
+        self
+    }
+
+    pub fn status(mut self, status: PropertyStatus) -> Self {
+        self.property.status = status;
+        self
+    }
+
+    /// Replaces the keywords of the property.
+    pub fn values<I, S>(mut self, values: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.property.values = values
+            .into_iter()
+            .map(|name| ValueData {
+                name: name.into(),
+                description: None,
+            })
+            .collect();
+        self
+    }
+
+    /// Adds a keyword to the property, replacing any keyword with the same name.
+    pub fn value(mut self, name: &str, description: Option<&str>) -> Self {
+        self.property.values.retain(|value| value.name != name);
+        self.property.values.push(ValueData {
+            name: name.to_string(),
+            description: description.map(str::to_string),
+        });
+        self
+    }
+
+    /// Saves the property and starts editing another one.
+    pub fn property(self, name: &str) -> PropertyBuilder {
+        self.finish().property(name)
+    }
+
+    /// Saves the property, to go on with the other methods of the `CssDataBuilder`.
+    pub fn finish(mut self) -> CssDataBuilder {
+        self.builder
+            .data
+            .properties
+            .insert(self.property.name.clone(), self.property);
+        self.builder
+    }
+
+    /// Saves the property and builds the data.
+    pub fn build(self) -> CssData {
+        self.finish().build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_css_data_builder() {
+        let data = CssDataBuilder::new()
+            .property("Position")
+            .values(["static", "relative"])
+            .property("--Brand")
+            .description("The brand color.")
+            .finish()
+            .remove("display")
+            .build();
+        assert_eq!(data.property("position").unwrap().values.len(), 2);
+        assert_eq!(
+            data.property("position").unwrap().description,
+            CssData::builtin().property("position").unwrap().description,
+            "Editing keeps what isn't changed"
+        );
+        assert!(data.property("--Brand").is_some());
+        assert!(data.property("--brand").is_none());
+        assert!(data.property("display").is_none());
+        assert!(CssData::builtin().property("display").is_some());
+        assert!(
+            CssData::builtin()
+                .properties()
+                .all(|property| property.initial.is_some()),
+            "Every built-in property has an initial value"
+        );
+    }
+
+    #[test]
+    fn test_css_data_custom_data() {
+        let data = CssDataBuilder::empty()
+            .custom_data(
+                r#"{ "version": 1.1, "properties": [{ "name": "--space", "description": "Spacing", "values": [{ "name": "var(--space-1)" }], "status": "experimental" }] }"#,
+            )
+            .unwrap()
+            .build();
+        let property = data.property("--space").unwrap();
+        assert_eq!(property.values[0].name, "var(--space-1)");
+        assert_eq!(property.status, PropertyStatus::Experimental);
+        assert_eq!(data.properties().count(), 1);
+        assert!(CssDataBuilder::new().custom_data("{").is_err());
+
+        let data = CssDataBuilder::new()
+            .custom_data(
+                r#"{ "atDirectives": [{ "name": "@Tailwind", "description": "Inserts the styles of Tailwind." }] }"#,
+            )
+            .unwrap()
+            .build();
+        assert!(data.at_directive("@tailwind").is_some());
+        assert!(data.at_directive("@SUPPORTS").is_some());
+        assert_eq!(
+            data.descriptor("@font-face", "Font-Display")
+                .and_then(|descriptor| descriptor.initial.as_deref()),
+            Some("auto")
+        );
+        assert!(data.descriptor("@font-face", "color").is_none());
+    }
+}
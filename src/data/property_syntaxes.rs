@@ -0,0 +1,405 @@
+use crate::case_folding::fold_case;
+
+/// The grammars of the properties whose values are validated, in the value definition syntax
+/// of CSS, in alphabetical order. Shorthands too complex to validate, like `background` or
+/// `font`, and properties validated by other rules, like `animation` or `cursor`, are left
+/// out.
+pub const PROPERTY_SYNTAXES: &[(&str, &str)] = &[
+    ("accent-color", "auto | <color>"),
+    (
+        "align-content",
+        "normal | <baseline-position> | <content-distribution> | <overflow-position>? <content-position>",
+    ),
+    (
+        "align-items",
+        "normal | stretch | <baseline-position> | <overflow-position>? <self-position> | anchor-center",
+    ),
+    (
+        "align-self",
+        "auto | normal | stretch | <baseline-position> | <overflow-position>? <self-position> | anchor-center",
+    ),
+    ("animation-delay", "<time>#"),
+    ("animation-direction", "[ normal | reverse | alternate | alternate-reverse ]#"),
+    ("animation-duration", "[ auto | <time [0s,∞]> ]#"),
+    ("animation-fill-mode", "[ none | forwards | backwards | both ]#"),
+    ("animation-iteration-count", "[ infinite | <number [0,∞]> ]#"),
+    ("animation-name", "[ none | <keyframes-name> ]#"),
+    ("animation-play-state", "[ running | paused ]#"),
+    ("animation-timing-function", "<easing-function>#"),
+    (
+        "appearance",
+        "none | auto | base | textfield | menulist-button | searchfield | textarea | checkbox | radio | menulist | listbox | meter | progress-bar | button",
+    ),
+    ("aspect-ratio", "auto || <ratio>"),
+    ("backdrop-filter", "none | <filter-value-list>"),
+    ("backface-visibility", "visible | hidden"),
+    ("background-attachment", "[ scroll | fixed | local ]#"),
+    ("background-blend-mode", "<blend-mode>#"),
+    ("background-clip", "[ <visual-box> | border-area | text ]#"),
+    ("background-color", "<color>"),
+    ("background-image", "[ none | <image> ]#"),
+    ("background-origin", "<visual-box>#"),
+    ("background-position", "<bg-position>#"),
+    ("background-repeat", "<repeat-style>#"),
+    ("background-size", "<bg-size>#"),
+    ("block-size", "<'width'>"),
+    ("border", "<line-width> || <line-style> || <color>"),
+    ("border-block", "<line-width> || <line-style> || <color>"),
+    ("border-block-color", "<color>{1,2}"),
+    ("border-block-end", "<line-width> || <line-style> || <color>"),
+    ("border-block-start", "<line-width> || <line-style> || <color>"),
+    ("border-block-style", "<line-style>{1,2}"),
+    ("border-block-width", "<line-width>{1,2}"),
+    ("border-bottom", "<line-width> || <line-style> || <color>"),
+    ("border-bottom-color", "<color>"),
+    ("border-bottom-left-radius", "<length-percentage [0,∞]>{1,2}"),
+    ("border-bottom-right-radius", "<length-percentage [0,∞]>{1,2}"),
+    ("border-bottom-style", "<line-style>"),
+    ("border-bottom-width", "<line-width>"),
+    ("border-collapse", "collapse | separate"),
+    ("border-color", "<color>{1,4}"),
+    ("border-inline", "<line-width> || <line-style> || <color>"),
+    ("border-inline-color", "<color>{1,2}"),
+    ("border-inline-end", "<line-width> || <line-style> || <color>"),
+    ("border-inline-start", "<line-width> || <line-style> || <color>"),
+    ("border-inline-style", "<line-style>{1,2}"),
+    ("border-inline-width", "<line-width>{1,2}"),
+    ("border-left", "<line-width> || <line-style> || <color>"),
+    ("border-left-color", "<color>"),
+    ("border-left-style", "<line-style>"),
+    ("border-left-width", "<line-width>"),
+    (
+        "border-radius",
+        "<length-percentage [0,∞]>{1,4} [ / <length-percentage [0,∞]>{1,4} ]?",
+    ),
+    ("border-right", "<line-width> || <line-style> || <color>"),
+    ("border-right-color", "<color>"),
+    ("border-right-style", "<line-style>"),
+    ("border-right-width", "<line-width>"),
+    ("border-spacing", "<length>{1,2}"),
+    ("border-style", "<line-style>{1,4}"),
+    ("border-top", "<line-width> || <line-style> || <color>"),
+    ("border-top-color", "<color>"),
+    ("border-top-left-radius", "<length-percentage [0,∞]>{1,2}"),
+    ("border-top-right-radius", "<length-percentage [0,∞]>{1,2}"),
+    ("border-top-style", "<line-style>"),
+    ("border-top-width", "<line-width>"),
+    ("border-width", "<line-width>{1,4}"),
+    ("bottom", "<'top'>"),
+    ("box-shadow", "none | <shadow>#"),
+    ("box-sizing", "content-box | border-box"),
+    ("caret-color", "auto | <color>"),
+    (
+        "clear",
+        "inline-start | inline-end | block-start | block-end | left | right | top | bottom | both-inline | both-block | both | none",
+    ),
+    ("color", "<color>"),
+    ("column-count", "<integer> | auto"),
+    ("column-gap", "normal | <length-percentage [0,∞]>"),
+    ("column-rule", "<line-width> || <line-style> || <color>"),
+    ("column-rule-color", "<color>"),
+    ("column-rule-style", "<line-style>"),
+    ("column-rule-width", "<line-width>"),
+    ("column-width", "auto | <length [0,∞]>"),
+    ("container-type", "normal | [ [ size | inline-size ] || scroll-state ]"),
+    ("content-visibility", "visible | auto | hidden"),
+    ("counter-increment", "none | [ <custom-ident> <integer>? ]+"),
+    ("counter-reset", "none | [ <custom-ident> <integer>? ]+"),
+    ("counter-set", "none | [ <custom-ident> <integer>? ]+"),
+    ("direction", "ltr | rtl"),
+    (
+        "display",
+        "[ <display-outside> || <display-inside> ] | <display-listitem> | <display-internal> | <display-box> | <display-legacy>",
+    ),
+    ("empty-cells", "show | hide"),
+    ("field-sizing", "content | fixed"),
+    ("filter", "none | <filter-value-list>"),
+    (
+        "flex",
+        "none | [ <'flex-grow'> <'flex-shrink'>? || <'flex-basis'> ]",
+    ),
+    ("flex-basis", "content | <'width'>"),
+    ("flex-direction", "row | row-reverse | column | column-reverse"),
+    ("flex-flow", "<'flex-direction'> || <'flex-wrap'>"),
+    ("flex-grow", "<number [0,∞]>"),
+    ("flex-shrink", "<number [0,∞]>"),
+    ("flex-wrap", "nowrap | wrap | wrap-reverse"),
+    ("float", "left | right | none | inline-start | inline-end"),
+    ("font-family", "[ <family-name> | <generic-family> ]#"),
+    (
+        "font-size",
+        "<absolute-size> | <relative-size> | <length-percentage [0,∞]> | math",
+    ),
+    (
+        "font-stretch",
+        "normal | <percentage [0,∞]> | ultra-condensed | extra-condensed | condensed | semi-condensed | semi-expanded | expanded | extra-expanded | ultra-expanded",
+    ),
+    ("font-style", "normal | italic | oblique <angle [-90deg,90deg]>?"),
+    ("font-weight", "<font-weight-absolute> | bolder | lighter"),
+    ("gap", "<'row-gap'> <'column-gap'>?"),
+    ("grid-area", "<grid-line> [ / <grid-line> ]{0,3}"),
+    ("grid-auto-flow", "[ row | column ] || dense"),
+    ("grid-column", "<grid-line> [ / <grid-line> ]?"),
+    ("grid-column-end", "<grid-line>"),
+    ("grid-column-start", "<grid-line>"),
+    ("grid-row", "<grid-line> [ / <grid-line> ]?"),
+    ("grid-row-end", "<grid-line>"),
+    ("grid-row-start", "<grid-line>"),
+    ("height", "<'width'>"),
+    ("hyphens", "none | manual | auto"),
+    ("inline-size", "<'width'>"),
+    ("inset", "<'top'>{1,4}"),
+    ("inset-block", "<'top'>{1,2}"),
+    ("inset-block-end", "<'top'>"),
+    ("inset-block-start", "<'top'>"),
+    ("inset-inline", "<'top'>{1,2}"),
+    ("inset-inline-end", "<'top'>"),
+    ("inset-inline-start", "<'top'>"),
+    ("isolation", "auto | isolate"),
+    (
+        "justify-content",
+        "normal | <content-distribution> | <overflow-position>? [ <content-position> | left | right ]",
+    ),
+    (
+        "justify-items",
+        "normal | stretch | <baseline-position> | <overflow-position>? [ <self-position> | left | right ] | legacy | legacy && [ left | right | center ] | anchor-center",
+    ),
+    (
+        "justify-self",
+        "auto | normal | stretch | <baseline-position> | <overflow-position>? [ <self-position> | left | right ] | anchor-center",
+    ),
+    ("left", "<'top'>"),
+    ("letter-spacing", "normal | <length-percentage>"),
+    ("line-height", "normal | <number [0,∞]> | <length-percentage [0,∞]>"),
+    ("list-style-position", "inside | outside"),
+    ("list-style-type", "<custom-ident> | <string> | none"),
+    ("margin", "<'margin-top'>{1,4}"),
+    ("margin-block", "<'margin-top'>{1,2}"),
+    ("margin-block-end", "<'margin-top'>"),
+    ("margin-block-start", "<'margin-top'>"),
+    ("margin-bottom", "<'margin-top'>"),
+    ("margin-inline", "<'margin-top'>{1,2}"),
+    ("margin-inline-end", "<'margin-top'>"),
+    ("margin-inline-start", "<'margin-top'>"),
+    ("margin-left", "<'margin-top'>"),
+    ("margin-right", "<'margin-top'>"),
+    ("margin-top", "<length-percentage> | auto"),
+    ("max-block-size", "<'max-width'>"),
+    ("max-height", "<'max-width'>"),
+    ("max-inline-size", "<'max-width'>"),
+    (
+        "max-width",
+        "none | <length-percentage [0,∞]> | min-content | max-content | fit-content | fit-content( <length-percentage [0,∞]> ) | stretch",
+    ),
+    ("min-block-size", "<'width'>"),
+    ("min-height", "<'width'>"),
+    ("min-inline-size", "<'width'>"),
+    ("min-width", "<'width'>"),
+    ("mix-blend-mode", "<blend-mode> | plus-darker | plus-lighter"),
+    ("object-fit", "fill | contain | cover | none | scale-down"),
+    ("object-position", "<position>"),
+    ("opacity", "<alpha-value>"),
+    ("order", "<integer>"),
+    ("outline", "<'outline-width'> || <'outline-style'> || <'outline-color'>"),
+    ("outline-color", "auto | <color>"),
+    ("outline-offset", "<length>"),
+    ("outline-style", "auto | <line-style>"),
+    ("outline-width", "<line-width>"),
+    ("overflow", "[ visible | hidden | clip | scroll | auto | overlay ]{1,2}"),
+    ("overflow-wrap", "normal | break-word | anywhere"),
+    ("overflow-x", "visible | hidden | clip | scroll | auto | overlay"),
+    ("overflow-y", "visible | hidden | clip | scroll | auto | overlay"),
+    ("padding", "<'padding-top'>{1,4}"),
+    ("padding-block", "<'padding-top'>{1,2}"),
+    ("padding-block-end", "<'padding-top'>"),
+    ("padding-block-start", "<'padding-top'>"),
+    ("padding-bottom", "<'padding-top'>"),
+    ("padding-inline", "<'padding-top'>{1,2}"),
+    ("padding-inline-end", "<'padding-top'>"),
+    ("padding-inline-start", "<'padding-top'>"),
+    ("padding-left", "<'padding-top'>"),
+    ("padding-right", "<'padding-top'>"),
+    ("padding-top", "<length-percentage [0,∞]>"),
+    ("perspective", "none | <length [0,∞]>"),
+    (
+        "pointer-events",
+        "auto | bounding-box | visiblePainted | visibleFill | visibleStroke | visible | painted | fill | stroke | all | none",
+    ),
+    ("position", "static | relative | absolute | sticky | fixed"),
+    ("resize", "none | both | horizontal | vertical | block | inline"),
+    ("right", "<'top'>"),
+    ("rotate", "none | <angle> | [ x | y | z | <number>{3} ] && <angle>"),
+    ("row-gap", "normal | <length-percentage [0,∞]>"),
+    ("scale", "none | [ <number> | <percentage> ]{1,3}"),
+    ("scroll-behavior", "auto | smooth"),
+    ("tab-size", "<number [0,∞]> | <length [0,∞]>"),
+    ("table-layout", "auto | fixed"),
+    (
+        "text-align",
+        "start | end | left | right | center | justify | match-parent | justify-all",
+    ),
+    (
+        "text-decoration",
+        "<'text-decoration-line'> || <'text-decoration-style'> || <'text-decoration-color'> || <'text-decoration-thickness'>",
+    ),
+    ("text-decoration-color", "<color>"),
+    (
+        "text-decoration-line",
+        "none | [ underline || overline || line-through || blink ] | spelling-error | grammar-error",
+    ),
+    ("text-decoration-style", "solid | double | dotted | dashed | wavy"),
+    ("text-decoration-thickness", "auto | from-font | <length-percentage>"),
+    ("text-indent", "<length-percentage> && hanging? && each-line?"),
+    ("text-overflow", "[ clip | ellipsis | <string> | fade ]{1,2}"),
+    ("text-shadow", "none | [ <length>{2,3} && <color>? ]#"),
+    (
+        "text-transform",
+        "none | [ capitalize | uppercase | lowercase ] || full-width || full-size-kana | math-auto",
+    ),
+    ("text-wrap", "<'text-wrap-mode'> || <'text-wrap-style'>"),
+    ("text-wrap-mode", "wrap | nowrap"),
+    ("text-wrap-style", "auto | balance | stable | pretty"),
+    ("top", "auto | <length-percentage>"),
+    (
+        "touch-action",
+        "auto | none | [ [ pan-x | pan-left | pan-right ] || [ pan-y | pan-up | pan-down ] || pinch-zoom ] | manipulation",
+    ),
+    ("transform", "none | <transform-function>+"),
+    ("transition", "<single-transition>#"),
+    ("transition-behavior", "[ normal | allow-discrete ]#"),
+    ("transition-delay", "<time>#"),
+    ("transition-duration", "<time [0s,∞]>#"),
+    ("transition-property", "none | <single-transition-property>#"),
+    ("transition-timing-function", "<easing-function>#"),
+    (
+        "translate",
+        "none | <length-percentage> [ <length-percentage> <length>? ]?",
+    ),
+    ("user-select", "auto | text | none | contain | all"),
+    (
+        "vertical-align",
+        "baseline | sub | super | text-top | text-bottom | middle | top | bottom | <length-percentage>",
+    ),
+    ("visibility", "visible | hidden | collapse"),
+    (
+        "white-space",
+        "normal | pre | pre-wrap | pre-line | <'white-space-collapse'> || <'text-wrap-mode'>",
+    ),
+    (
+        "white-space-collapse",
+        "collapse | discard | preserve | preserve-breaks | preserve-spaces | break-spaces",
+    ),
+    (
+        "width",
+        "auto | <length-percentage [0,∞]> | min-content | max-content | fit-content | fit-content( <length-percentage [0,∞]> ) | stretch",
+    ),
+    ("will-change", "auto | [ scroll-position | contents | <custom-ident> ]#"),
+    ("word-break", "normal | break-all | keep-all | break-word | auto-phrase"),
+    (
+        "writing-mode",
+        "horizontal-tb | vertical-rl | vertical-lr | sideways-rl | sideways-lr",
+    ),
+    ("z-index", "auto | <integer>"),
+];
+
+/// The grammars of the data types made of other types or keywords, like `<line-style>`.
+/// Types matching a single component, like `<length>` or `<color>`, are built into the
+/// matcher.
+pub const TYPE_SYNTAXES: &[(&str, &str)] = &[
+    ("absolute-size", "xx-small | x-small | small | medium | large | x-large | xx-large | xxx-large"),
+    ("alpha-value", "<number> | <percentage>"),
+    ("baseline-position", "[ first | last ]? baseline"),
+    (
+        "bg-position",
+        "[ left | center | right | top | bottom | <length-percentage> ] | [ left | center | right | <length-percentage> ] [ top | center | bottom | <length-percentage> ] | [ center | [ left | right ] <length-percentage>? ] && [ center | [ top | bottom ] <length-percentage>? ]",
+    ),
+    ("bg-size", "[ <length-percentage [0,∞]> | auto ]{1,2} | cover | contain"),
+    (
+        "blend-mode",
+        "normal | multiply | screen | overlay | darken | lighten | color-dodge | color-burn | hard-light | soft-light | difference | exclusion | hue | saturation | color | luminosity",
+    ),
+    ("content-distribution", "space-between | space-around | space-evenly | stretch"),
+    ("content-position", "center | start | end | flex-start | flex-end"),
+    ("display-box", "contents | none"),
+    ("display-inside", "flow | flow-root | table | flex | grid | ruby | math"),
+    (
+        "display-internal",
+        "table-row-group | table-header-group | table-footer-group | table-row | table-cell | table-column-group | table-column | table-caption | ruby-base | ruby-text | ruby-base-container | ruby-text-container",
+    ),
+    ("display-legacy", "inline-block | inline-table | inline-flex | inline-grid"),
+    ("display-listitem", "<display-outside>? && [ flow | flow-root ]? && list-item"),
+    ("display-outside", "block | inline | run-in"),
+    ("family-name", "<string> | <custom-ident>+"),
+    ("filter-value-list", "[ <filter-function> | <url> ]+"),
+    (
+        "font-stretch-absolute",
+        "normal | ultra-condensed | extra-condensed | condensed | semi-condensed | semi-expanded | expanded | extra-expanded | ultra-expanded | <percentage [0,∞]>",
+    ),
+    ("font-weight-absolute", "normal | bold | <number [1,1000]>"),
+    (
+        "generic-family",
+        "serif | sans-serif | cursive | fantasy | monospace | system-ui | emoji | math | fangsong | ui-serif | ui-sans-serif | ui-monospace | ui-rounded",
+    ),
+    (
+        "grid-line",
+        "auto | <custom-ident> | [ <integer> && <custom-ident>? ] | [ span && [ <integer [1,∞]> || <custom-ident> ] ]",
+    ),
+    ("keyframes-name", "<custom-ident> | <string>"),
+    ("line-style", "none | hidden | dotted | dashed | solid | double | groove | ridge | inset | outset"),
+    ("line-width", "<length [0,∞]> | thin | medium | thick"),
+    ("overflow-position", "unsafe | safe"),
+    (
+        "position",
+        "[ left | center | right | top | bottom | <length-percentage> ] | [ left | center | right | <length-percentage> ] [ top | center | bottom | <length-percentage> ] | [ center | [ left | right ] <length-percentage>? ] && [ center | [ top | bottom ] <length-percentage>? ]",
+    ),
+    ("ratio", "<number [0,∞]> [ / <number [0,∞]> ]?"),
+    ("relative-size", "larger | smaller"),
+    ("repeat-style", "repeat-x | repeat-y | [ repeat | space | round | no-repeat ]{1,2}"),
+    ("self-position", "center | start | end | self-start | self-end | flex-start | flex-end"),
+    ("shadow", "<color>? && [ <length>{2} <length [0,∞]>? <length>? ] && inset?"),
+    (
+        "single-transition",
+        "[ none | <single-transition-property> ] || <time> || <easing-function> || <time> || [ normal | allow-discrete ]",
+    ),
+    ("single-transition-property", "all | <custom-ident>"),
+    ("visual-box", "content-box | padding-box | border-box"),
+];
+
+/// Returns the grammar of a property, ignoring case.
+pub fn property_syntax(name: &str) -> Option<&'static str> {
+    let name = fold_case(name);
+    PROPERTY_SYNTAXES
+        .binary_search_by(|(property, _)| (*property).cmp(name.as_ref()))
+        .ok()
+        .map(|idx| PROPERTY_SYNTAXES[idx].1)
+}
+
+/// Returns the grammar of a data type made of other types, like `line-style`.
+pub fn type_syntax(name: &str) -> Option<&'static str> {
+    TYPE_SYNTAXES
+        .binary_search_by(|(data_type, _)| (*data_type).cmp(name))
+        .ok()
+        .map(|idx| TYPE_SYNTAXES[idx].1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_syntaxes_are_sorted() {
+        for syntaxes in [PROPERTY_SYNTAXES, TYPE_SYNTAXES] {
+            assert!(syntaxes.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        }
+    }
+
+    #[test]
+    fn test_property_syntax() {
+        assert_eq!(property_syntax("Z-Index"), Some("auto | <integer>"));
+        assert_eq!(property_syntax("grid-template-columns"), None);
+        assert_eq!(
+            type_syntax("visual-box"),
+            Some("content-box | padding-box | border-box")
+        );
+    }
+}
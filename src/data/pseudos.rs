@@ -0,0 +1,467 @@
+use crate::case_folding::fold_case;
+
+/// What is known about a pseudo-class or a pseudo-element, to complete and document it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PseudoData {
+    /// The name, with its `:` or `::`.
+    pub name: &'static str,
+    pub description: &'static str,
+    /// The inserted text of a functional pseudo-class, in the snippet syntax of the protocol,
+    /// like `:nth-child(${1:2n+1})`.
+    pub snippet: Option<&'static str>,
+    /// The first version of the main browsers supporting it, like `Chrome 1, Firefox 1`.
+    pub browsers: &'static str,
+}
+
+const fn pseudo(
+    name: &'static str,
+    description: &'static str,
+    browsers: &'static str,
+) -> PseudoData {
+    PseudoData {
+        name,
+        description,
+        snippet: None,
+        browsers,
+    }
+}
+
+const fn function(
+    name: &'static str,
+    snippet: &'static str,
+    description: &'static str,
+    browsers: &'static str,
+) -> PseudoData {
+    PseudoData {
+        name,
+        description,
+        snippet: Some(snippet),
+        browsers,
+    }
+}
+
+/// The built-in pseudo-classes, in alphabetical order.
+pub const BUILTIN_PSEUDO_CLASSES: &[PseudoData] = &[
+    pseudo(
+        ":active",
+        "Matches an element being activated, like a button being pressed.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1",
+    ),
+    pseudo(
+        ":checked",
+        "Matches a checked radio button or checkbox, or a selected option.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":default",
+        "Matches the default form elements of a group, like the initially checked checkbox.",
+        "Chrome 10, Edge 79, Firefox 4, Safari 5",
+    ),
+    function(
+        ":dir",
+        ":dir(${1|ltr,rtl|})",
+        "Matches an element by the directionality of its text.",
+        "Chrome 120, Edge 120, Firefox 49, Safari 16.4",
+    ),
+    pseudo(
+        ":disabled",
+        "Matches a disabled form element.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":empty",
+        "Matches an element without children, text included.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":enabled",
+        "Matches an enabled form element.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":first-child",
+        "Matches the first element of its siblings.",
+        "Chrome 4, Edge 12, Firefox 3, Safari 3.1",
+    ),
+    pseudo(
+        ":first-of-type",
+        "Matches the first element of its type among its siblings.",
+        "Chrome 1, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    pseudo(
+        ":focus",
+        "Matches the element having the focus.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1",
+    ),
+    pseudo(
+        ":focus-visible",
+        "Matches the focused element when the browser shows a focus indicator, like after keyboard navigation.",
+        "Chrome 86, Edge 86, Firefox 85, Safari 15.4",
+    ),
+    pseudo(
+        ":focus-within",
+        "Matches an element having the focus or containing the element having it.",
+        "Chrome 60, Edge 79, Firefox 52, Safari 10.1",
+    ),
+    function(
+        ":has",
+        ":has(${1})",
+        "Matches an element when one of the relative selectors matches, like a parent of an image with `:has(> img)`.",
+        "Chrome 105, Edge 105, Firefox 121, Safari 15.4",
+    ),
+    pseudo(
+        ":hover",
+        "Matches an element under the pointer.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 2",
+    ),
+    pseudo(
+        ":indeterminate",
+        "Matches a form element in an indeterminate state, like a checkbox neither checked nor unchecked.",
+        "Chrome 1, Edge 12, Firefox 2, Safari 3",
+    ),
+    pseudo(
+        ":invalid",
+        "Matches a form element whose value fails its validation constraints.",
+        "Chrome 10, Edge 12, Firefox 4, Safari 5",
+    ),
+    function(
+        ":is",
+        ":is(${1})",
+        "Matches an element matching any selector of the list, with the specificity of the most specific one.",
+        "Chrome 88, Edge 88, Firefox 78, Safari 14",
+    ),
+    function(
+        ":lang",
+        ":lang(${1:en})",
+        "Matches an element by its content language.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":last-child",
+        "Matches the last element of its siblings.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    pseudo(
+        ":last-of-type",
+        "Matches the last element of its type among its siblings.",
+        "Chrome 1, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    pseudo(
+        ":link",
+        "Matches a link that hasn't been visited.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1",
+    ),
+    function(
+        ":not",
+        ":not(${1})",
+        "Matches an element matching none of the selectors of the list.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 3.1",
+    ),
+    function(
+        ":nth-child",
+        ":nth-child(${1:2n+1})",
+        "Matches an element by its position among its siblings, like every odd element with `2n+1`.",
+        "Chrome 1, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    function(
+        ":nth-last-child",
+        ":nth-last-child(${1:2n+1})",
+        "Matches an element by its position among its siblings, counting from the last one.",
+        "Chrome 4, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    function(
+        ":nth-last-of-type",
+        ":nth-last-of-type(${1:2n+1})",
+        "Matches an element by its position among its siblings of the same type, counting from the last one.",
+        "Chrome 4, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    function(
+        ":nth-of-type",
+        ":nth-of-type(${1:2n+1})",
+        "Matches an element by its position among its siblings of the same type.",
+        "Chrome 1, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    pseudo(
+        ":only-child",
+        "Matches an element without siblings.",
+        "Chrome 2, Edge 12, Firefox 1.5, Safari 3.1",
+    ),
+    pseudo(
+        ":only-of-type",
+        "Matches an element without siblings of the same type.",
+        "Chrome 1, Edge 12, Firefox 3.5, Safari 3.1",
+    ),
+    pseudo(
+        ":optional",
+        "Matches a form element that isn't required.",
+        "Chrome 10, Edge 12, Firefox 4, Safari 5",
+    ),
+    pseudo(
+        ":placeholder-shown",
+        "Matches an input showing its placeholder.",
+        "Chrome 47, Edge 79, Firefox 51, Safari 9",
+    ),
+    pseudo(
+        ":read-only",
+        "Matches an element the user can't edit.",
+        "Chrome 1, Edge 13, Firefox 78, Safari 4",
+    ),
+    pseudo(
+        ":required",
+        "Matches a required form element.",
+        "Chrome 10, Edge 12, Firefox 4, Safari 5",
+    ),
+    pseudo(
+        ":root",
+        "Matches the root element of the document, `<html>` in HTML.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1",
+    ),
+    pseudo(
+        ":target",
+        "Matches the element whose ID is the fragment of the URL.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1.3",
+    ),
+    pseudo(
+        ":valid",
+        "Matches a form element whose value meets its validation constraints.",
+        "Chrome 10, Edge 12, Firefox 4, Safari 5",
+    ),
+    pseudo(
+        ":visited",
+        "Matches a link that has been visited.",
+        "Chrome 1, Edge 12, Firefox 1, Safari 1",
+    ),
+    function(
+        ":where",
+        ":where(${1})",
+        "Matches an element matching any selector of the list, without adding specificity.",
+        "Chrome 88, Edge 88, Firefox 78, Safari 14",
+    ),
+];
+
+/// The built-in pseudo-elements, in alphabetical order.
+pub const BUILTIN_PSEUDO_ELEMENTS: &[PseudoData] = &[
+    pseudo(
+        "::after",
+        "Creates a last child of the element, whose content is set by the `content` property.",
+        "Chrome 1, Edge 12, Firefox 1.5, Safari 4",
+    ),
+    pseudo(
+        "::backdrop",
+        "The box behind a dialog or an element displayed in fullscreen.",
+        "Chrome 37, Edge 79, Firefox 47, Safari 15.4",
+    ),
+    pseudo(
+        "::before",
+        "Creates a first child of the element, whose content is set by the `content` property.",
+        "Chrome 1, Edge 12, Firefox 1.5, Safari 4",
+    ),
+    pseudo(
+        "::first-letter",
+        "The first letter of the first line of a block.",
+        "Chrome 1, Edge 12, Firefox 1.5, Safari 1",
+    ),
+    pseudo(
+        "::first-line",
+        "The first line of a block.",
+        "Chrome 1, Edge 12, Firefox 1.5, Safari 1",
+    ),
+    pseudo(
+        "::marker",
+        "The marker of a list item, like its bullet or number.",
+        "Chrome 86, Edge 86, Firefox 68, Safari 11.1",
+    ),
+    function(
+        "::part",
+        "::part(${1:name})",
+        "An element of a shadow tree exposed with a matching `part` attribute.",
+        "Chrome 73, Edge 79, Firefox 72, Safari 13.1",
+    ),
+    pseudo(
+        "::placeholder",
+        "The placeholder text of an input or a text area.",
+        "Chrome 57, Edge 79, Firefox 51, Safari 10.1",
+    ),
+    pseudo(
+        "::selection",
+        "The part of the document selected by the user.",
+        "Chrome 1, Edge 12, Firefox 62, Safari 1.1",
+    ),
+    function(
+        "::slotted",
+        "::slotted(${1:*})",
+        "An element placed into a slot of a shadow tree.",
+        "Chrome 50, Edge 79, Firefox 63, Safari 10",
+    ),
+];
+
+/// The standard pseudo-classes without built-in data, in alphabetical order, to tell them
+/// from misspelled ones.
+const OTHER_PSEUDO_CLASSES: &[&str] = &[
+    ":active-view-transition",
+    ":active-view-transition-type",
+    ":any-link",
+    ":autofill",
+    ":blank",
+    ":buffering",
+    ":closed",
+    ":current",
+    ":defined",
+    ":first",
+    ":fullscreen",
+    ":future",
+    ":has-slotted",
+    ":host",
+    ":host-context",
+    ":in-range",
+    ":left",
+    ":local-link",
+    ":modal",
+    ":muted",
+    ":nth-col",
+    ":nth-last-col",
+    ":open",
+    ":out-of-range",
+    ":past",
+    ":paused",
+    ":picture-in-picture",
+    ":playing",
+    ":popover-open",
+    ":read-write",
+    ":right",
+    ":scope",
+    ":seeking",
+    ":stalled",
+    ":state",
+    ":target-current",
+    ":target-within",
+    ":user-invalid",
+    ":user-valid",
+    ":volume-locked",
+];
+
+/// The pseudo-elements written with a single colon by CSS 2, still supported by browsers.
+const LEGACY_PSEUDO_ELEMENTS: &[&str] = &[":after", ":before", ":first-letter", ":first-line"];
+
+/// The pseudo-classes of CSS Modules, scoping selectors and sharing values.
+const CSS_MODULES_PSEUDO_CLASSES: &[&str] = &[":export", ":global", ":import", ":local"];
+
+/// The standard pseudo-elements without built-in data, in alphabetical order.
+const OTHER_PSEUDO_ELEMENTS: &[&str] = &[
+    "::checkmark",
+    "::column",
+    "::cue",
+    "::cue-region",
+    "::details-content",
+    "::file-selector-button",
+    "::grammar-error",
+    "::highlight",
+    "::picker",
+    "::picker-icon",
+    "::scroll-button",
+    "::scroll-marker",
+    "::scroll-marker-group",
+    "::search-text",
+    "::spelling-error",
+    "::target-text",
+    "::view-transition",
+    "::view-transition-group",
+    "::view-transition-image-pair",
+    "::view-transition-new",
+    "::view-transition-old",
+];
+
+/// The vendor-prefixed pseudo-classes and pseudo-elements commonly used to style the
+/// controls of a browser engine, in alphabetical order.
+pub const VENDOR_PSEUDOS: &[&str] = &[
+    ":-moz-any",
+    ":-moz-focusring",
+    ":-moz-placeholder",
+    ":-moz-ui-invalid",
+    ":-ms-input-placeholder",
+    ":-webkit-any",
+    ":-webkit-any-link",
+    ":-webkit-autofill",
+    ":-webkit-full-screen",
+    "::-moz-focus-inner",
+    "::-moz-placeholder",
+    "::-moz-progress-bar",
+    "::-moz-range-progress",
+    "::-moz-range-thumb",
+    "::-moz-range-track",
+    "::-moz-selection",
+    "::-ms-clear",
+    "::-ms-expand",
+    "::-ms-fill-lower",
+    "::-ms-fill-upper",
+    "::-ms-input-placeholder",
+    "::-ms-reveal",
+    "::-ms-thumb",
+    "::-ms-track",
+    "::-webkit-calendar-picker-indicator",
+    "::-webkit-clear-button",
+    "::-webkit-details-marker",
+    "::-webkit-file-upload-button",
+    "::-webkit-inner-spin-button",
+    "::-webkit-input-placeholder",
+    "::-webkit-media-controls",
+    "::-webkit-meter-bar",
+    "::-webkit-outer-spin-button",
+    "::-webkit-progress-bar",
+    "::-webkit-progress-value",
+    "::-webkit-resizer",
+    "::-webkit-scrollbar",
+    "::-webkit-scrollbar-button",
+    "::-webkit-scrollbar-corner",
+    "::-webkit-scrollbar-thumb",
+    "::-webkit-scrollbar-track",
+    "::-webkit-scrollbar-track-piece",
+    "::-webkit-search-cancel-button",
+    "::-webkit-search-decoration",
+    "::-webkit-search-results-button",
+    "::-webkit-slider-runnable-track",
+    "::-webkit-slider-thumb",
+];
+
+/// Returns the names of the standard pseudo-classes and pseudo-elements, with their colons,
+/// CSS 2 pseudo-elements and CSS Modules pseudo-classes included.
+pub fn standard_pseudos() -> impl Iterator<Item = &'static str> {
+    BUILTIN_PSEUDO_CLASSES
+        .iter()
+        .chain(BUILTIN_PSEUDO_ELEMENTS)
+        .map(|pseudo| pseudo.name)
+        .chain(OTHER_PSEUDO_CLASSES.iter().copied())
+        .chain(LEGACY_PSEUDO_ELEMENTS.iter().copied())
+        .chain(CSS_MODULES_PSEUDO_CLASSES.iter().copied())
+        .chain(OTHER_PSEUDO_ELEMENTS.iter().copied())
+}
+
+/// Returns whether a pseudo-class or a pseudo-element, written with its colons like
+/// `::placeholder`, is standard or a known vendor-prefixed one, ignoring case.
+pub fn is_known_pseudo(name: &str) -> bool {
+    let name = fold_case(name);
+    standard_pseudos().any(|pseudo| pseudo == name) || VENDOR_PSEUDOS.binary_search(&&*name).is_ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pseudo_names_are_sorted() {
+        for names in [OTHER_PSEUDO_CLASSES, OTHER_PSEUDO_ELEMENTS, VENDOR_PSEUDOS] {
+            assert!(names.windows(2).all(|pair| pair[0] < pair[1]), "{names:?}");
+        }
+    }
+
+    #[test]
+    fn test_is_known_pseudo() {
+        assert!(is_known_pseudo(":HOVER"));
+        assert!(is_known_pseudo(":before"));
+        assert!(is_known_pseudo("::view-transition-group"));
+        assert!(is_known_pseudo("::-webkit-scrollbar"));
+        assert!(!is_known_pseudo(":hoover"));
+        assert!(!is_known_pseudo("::hover"));
+        assert!(!is_known_pseudo("::-webkit-scrolbar"));
+    }
+}
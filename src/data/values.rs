@@ -0,0 +1,21 @@
+/// Keywords valid for every property, which aren't `<custom-ident>`.
+pub const CSS_WIDE_KEYWORDS: &[&str] = &["inherit", "initial", "revert", "revert-layer", "unset"];
+
+/// The units of lengths, in alphabetical order. Zero lengths don't need one, outside of
+/// math functions.
+pub const LENGTH_UNITS: &[&str] = &[
+    "cap", "ch", "cm", "cqb", "cqh", "cqi", "cqmax", "cqmin", "cqw", "dvb", "dvh", "dvi", "dvmax",
+    "dvmin", "dvw", "em", "ex", "ic", "in", "lh", "lvb", "lvh", "lvi", "lvmax", "lvmin", "lvw",
+    "mm", "pc", "pt", "px", "q", "rcap", "rch", "rem", "rex", "ric", "rlh", "svb", "svh", "svi",
+    "svmax", "svmin", "svw", "vb", "vh", "vi", "vmax", "vmin", "vw",
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_units_are_sorted() {
+        assert!(LENGTH_UNITS.windows(2).all(|pair| pair[0] < pair[1]));
+    }
+}
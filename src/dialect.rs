@@ -0,0 +1,84 @@
+use wasm_bindgen::prelude::*;
+
+/// The stylesheet language of a document.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Dialect {
+    #[default]
+    Css,
+    Scss,
+    Less,
+}
+
+impl Dialect {
+    /// Returns the dialect matching an LSP language identifier, like `scss`.
+    ///
+    /// # Arguments
+    ///
+    /// * `language_id` - The language identifier of a text document.
+    ///
+    /// # Returns
+    ///
+    /// * The matching `Dialect`, defaulting to `Css` for unknown identifiers. The indented
+    ///   syntax of Sass, `sass`, isn't SCSS and isn't supported.
+    pub fn from_language_id(language_id: &str) -> Dialect {
+        match language_id.to_ascii_lowercase().as_str() {
+            "scss" => Dialect::Scss,
+            "less" => Dialect::Less,
+            _ => Dialect::Css,
+        }
+    }
+
+    /// Returns the dialect of a stylesheet from the extension of its path or URI.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The path or URI of the stylesheet, like `styles/_base.scss`.
+    ///
+    /// # Returns
+    ///
+    /// * The matching `Dialect`, or `None` if the path isn't a stylesheet, or is one written
+    ///   in the indented syntax of Sass, like `_base.sass`.
+    pub fn from_path(path: &str) -> Option<Dialect> {
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        let (_, extension) = path.rsplit_once('.')?;
+        match extension.to_ascii_lowercase().as_str() {
+            "css" => Some(Dialect::Css),
+            "scss" => Some(Dialect::Scss),
+            "less" => Some(Dialect::Less),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the dialect supports `//` line comments.
+    pub fn has_line_comments(self) -> bool {
+        matches!(self, Dialect::Scss | Dialect::Less)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_language_id() {
+        assert_eq!(Dialect::from_language_id("css"), Dialect::Css);
+        assert_eq!(Dialect::from_language_id("SCSS"), Dialect::Scss);
+        assert_eq!(Dialect::from_language_id("less"), Dialect::Less);
+        assert_eq!(Dialect::from_language_id("postcss"), Dialect::Css);
+    }
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Dialect::from_path("file:///a/_b.SCSS"), Some(Dialect::Scss));
+        assert_eq!(Dialect::from_path("a.less?raw"), Some(Dialect::Less));
+        assert_eq!(Dialect::from_path("file:///a.css.map"), None);
+        assert_eq!(Dialect::from_path("file:///a.b/README"), None);
+    }
+
+    #[test]
+    fn test_indented_sass_is_not_scss() {
+        assert_eq!(Dialect::from_language_id("sass"), Dialect::Css);
+        assert_eq!(Dialect::from_path("file:///a/_b.sass"), None);
+    }
+}
@@ -0,0 +1,641 @@
+use crate::dialect::Dialect;
+use crate::file_system::FileSystemProvider;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::incremental::reparse_css;
+use crate::parser::parse_css_with_dialect;
+use crate::text_document::TextDocument;
+use crate::text_range::{FromLsp, SourceText, TextSize, ToLsp};
+use biome_css_parser::CssParse;
+use lsp_types::{Position, Range, TextDocumentContentChangeEvent, Uri};
+use serde::Serialize;
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+
+/// Holds the documents opened in the editor, whatever the scheme of their URI.
+///
+/// Documents are keyed by their URI as sent by the client, so `untitled:`, `vscode-vfs:` and
+/// other schemes are stored the same way as `file://` documents, even if they aren't valid
+/// `Uri`s.
+///
+/// The store may keep the last versions of each document with the changes that produced
+/// them, to replay the edits sent by a client when diagnosing synchronization bugs.
+///
+/// Documents are parsed on demand, and their parse is kept up to date as they are edited,
+/// reparsing only the rules touched by each change.
+#[derive(Debug, Default)]
+pub struct DocumentStore {
+    documents: HashMap<String, TextDocument>,
+    history_limit: usize,
+    histories: HashMap<String, DocumentHistory>,
+    /// The parses of the open documents, made when first asked for.
+    parses: RefCell<HashMap<String, CssParse>>,
+    /// The encoding of the positions of the changes sent by the client.
+    position_encoding: PositionEncoding,
+}
+
+/// The last versions of a document, with the changes that produced them.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentHistory {
+    /// The version of the oldest retained text.
+    pub base_version: i64,
+    /// The oldest retained text, the changes of the first entry apply to it.
+    pub base_text: String,
+    pub entries: VecDeque<HistoryEntry>,
+}
+
+/// A version of a document in its `DocumentHistory`.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HistoryEntry {
+    pub version: i64,
+    /// The changes applied to the previous version, as sent by the client.
+    pub changes: Vec<TextDocumentContentChangeEvent>,
+    /// The text after the changes were applied.
+    pub text: String,
+    /// The text of the version as reported by the client, to check the replay against.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reported_text: Option<String>,
+}
+
+/// A problem found while replaying the history of a document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ReplayIssue {
+    /// A change targets a position outside of the text it applies to, which was clamped.
+    #[serde(rename_all = "camelCase")]
+    OutOfBounds {
+        version: i64,
+        /// The index of the change in its version.
+        change: usize,
+        range: Range,
+    },
+    /// Replaying the changes of a version doesn't give the text reported by the client.
+    #[serde(rename_all = "camelCase")]
+    TextMismatch {
+        version: i64,
+        /// The first position where the texts differ, in the replayed text.
+        position: Position,
+    },
+}
+
+impl DocumentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a store reading the positions of changes in the given encoding.
+    pub fn with_position_encoding(position_encoding: PositionEncoding) -> Self {
+        DocumentStore {
+            position_encoding,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the encoding of the positions of changes.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding
+    }
+
+    /// Keeps the last `limit` versions of each open document, or none if `limit` is 0.
+    /// Histories start when documents are opened.
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.history_limit = limit;
+        if limit == 0 {
+            self.histories.clear();
+        }
+        for history in self.histories.values_mut() {
+            while history.entries.len() > limit {
+                history.pop_front();
+            }
+        }
+    }
+
+    /// Opens a document, replacing any document with the same URI.
+    pub fn open(&mut self, document: TextDocument) {
+        let key = document_key(&document.uri);
+        if self.history_limit > 0 {
+            self.histories.insert(
+                key.clone(),
+                DocumentHistory {
+                    base_version: document.version,
+                    base_text: document.text.clone(),
+                    entries: VecDeque::new(),
+                },
+            );
+        }
+        self.parses.get_mut().remove(&key);
+        self.documents.insert(key, document);
+    }
+
+    /// Replaces the text of an open document.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    /// * `version` - The new version of the document.
+    /// * `text` - The new text of the document.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the document was updated, `false` if it isn't open or the version is
+    ///   older than the stored one.
+    pub fn update(&mut self, uri: &str, version: i64, text: &str) -> bool {
+        let change = TextDocumentContentChangeEvent {
+            range: None,
+            range_length: None,
+            text: text.to_string(),
+        };
+        self.apply_changes(uri, version, &[change])
+    }
+
+    /// Applies the changes of a `textDocument/didChange` notification to an open document,
+    /// in order, whether they replace a range or the whole text.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    /// * `version` - The new version of the document.
+    /// * `changes` - The `TextDocumentContentChangeEvent`s, with positions in the encoding of
+    ///   the store.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the document was updated, `false` if it isn't open or the version is
+    ///   older than the stored one.
+    pub fn apply_changes(
+        &mut self,
+        uri: &str,
+        version: i64,
+        changes: &[TextDocumentContentChangeEvent],
+    ) -> bool {
+        let key = document_key(uri);
+        let Some(document) = self.documents.get_mut(&key) else {
+            return false;
+        };
+        if version < document.version {
+            return false;
+        }
+        let mut line_index = LineIndex::new(&document.text);
+        for change in changes {
+            let edit = apply_change(
+                &mut document.text,
+                &mut line_index,
+                change,
+                self.position_encoding,
+            );
+            if let Some(previous) = self.parses.get_mut().remove(&key) {
+                if let Some((replaced, inserted_len)) = edit {
+                    let dialect = Dialect::from_language_id(&document.language_id);
+                    let parse =
+                        reparse_css(&previous, &document.text, replaced, inserted_len, dialect);
+                    self.parses.get_mut().insert(key.clone(), parse);
+                }
+            }
+        }
+        document.version = version;
+        if let Some(history) = self.histories.get_mut(&key) {
+            history.entries.push_back(HistoryEntry {
+                version,
+                changes: changes.to_vec(),
+                text: document.text.clone(),
+                reported_text: None,
+            });
+            while history.entries.len() > self.history_limit {
+                history.pop_front();
+            }
+        }
+        true
+    }
+
+    /// Returns the retained versions of an open document, if histories are kept.
+    pub fn history(&self, uri: &str) -> Option<&DocumentHistory> {
+        self.histories.get(&document_key(uri))
+    }
+
+    /// Records the text of a retained version as the client has it, like the text sent with
+    /// `textDocument/didSave`, for `replay` to compare against.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    /// * `version` - The version of the text.
+    /// * `text` - The text of the document in the client.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the version is retained in the history of the document.
+    pub fn report_text(&mut self, uri: &str, version: i64, text: &str) -> bool {
+        let Some(entry) = self
+            .histories
+            .get_mut(&document_key(uri))
+            .and_then(|history| {
+                history
+                    .entries
+                    .iter_mut()
+                    .rfind(|entry| entry.version == version)
+            })
+        else {
+            return false;
+        };
+        entry.reported_text = Some(text.to_string());
+        true
+    }
+
+    /// Replays the changes retained for a document, checking that each change targets
+    /// positions inside the text it applies to, and that each version gives the text
+    /// reported by the client, if any, see `report_text`.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    ///
+    /// # Returns
+    ///
+    /// * The `ReplayIssue`s found, empty if the history is consistent, or `None` if there is
+    ///   no history for the document.
+    pub fn replay(&self, uri: &str) -> Option<Vec<ReplayIssue>> {
+        let history = self.history(uri)?;
+        let mut issues = Vec::new();
+        let mut text = history.base_text.clone();
+        let mut line_index = LineIndex::new(&text);
+        for entry in &history.entries {
+            for (idx, change) in entry.changes.iter().enumerate() {
+                if let Some(range) = change.range {
+                    if !contains_position(&text, range.start, self.position_encoding)
+                        || !contains_position(&text, range.end, self.position_encoding)
+                    {
+                        issues.push(ReplayIssue::OutOfBounds {
+                            version: entry.version,
+                            change: idx,
+                            range,
+                        });
+                    }
+                }
+                apply_change(&mut text, &mut line_index, change, self.position_encoding);
+            }
+            let Some(reported_text) = &entry.reported_text else {
+                continue;
+            };
+            if text != *reported_text {
+                issues.push(ReplayIssue::TextMismatch {
+                    version: entry.version,
+                    position: first_difference(&text, reported_text, self.position_encoding),
+                });
+                // Check the next versions against the text of the client
+                text = reported_text.clone();
+                line_index = LineIndex::new(&text);
+            }
+        }
+        Some(issues)
+    }
+
+    /// Closes a document.
+    ///
+    /// # Returns
+    ///
+    /// * The closed `TextDocument`, or `None` if it wasn't open.
+    pub fn close(&mut self, uri: &str) -> Option<TextDocument> {
+        self.histories.remove(&document_key(uri));
+        self.parses.get_mut().remove(&document_key(uri));
+        self.documents.remove(&document_key(uri))
+    }
+
+    /// Returns an open document.
+    pub fn get(&self, uri: &str) -> Option<&TextDocument> {
+        self.documents.get(&document_key(uri))
+    }
+
+    /// Returns the parse of an open document, parsing it if it wasn't since it was opened or
+    /// its whole text was replaced.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document.
+    ///
+    /// # Returns
+    ///
+    /// * The `CssParse` of the current text of the document, or `None` if it isn't open.
+    pub fn parse(&self, uri: &str) -> Option<CssParse> {
+        let key = document_key(uri);
+        let document = self.documents.get(&key)?;
+        let mut parses = self.parses.borrow_mut();
+        let parse = parses.entry(key).or_insert_with(|| {
+            parse_css_with_dialect(
+                &document.text,
+                Dialect::from_language_id(&document.language_id),
+            )
+        });
+        Some(CssParse::new(parse.syntax(), parse.diagnostics().to_vec()))
+    }
+
+    /// Returns the URIs of the open documents, sorted.
+    pub fn uris(&self) -> Vec<&str> {
+        let mut uris: Vec<&str> = self
+            .documents
+            .values()
+            .map(|document| document.uri.as_str())
+            .collect();
+        uris.sort();
+        uris
+    }
+
+    pub fn len(&self) -> usize {
+        self.documents.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.documents.is_empty()
+    }
+
+    /// Layers the open documents over a file system, so unsaved changes and documents that
+    /// only exist in the editor take precedence.
+    pub fn overlay<'a>(
+        &'a self,
+        file_system: &'a dyn FileSystemProvider,
+    ) -> impl FileSystemProvider + 'a {
+        Overlay {
+            documents: self,
+            file_system,
+        }
+    }
+}
+
+impl DocumentHistory {
+    /// Drops the oldest entry, its text becoming the base of the next one.
+    fn pop_front(&mut self) {
+        if let Some(entry) = self.entries.pop_front() {
+            self.base_version = entry.version;
+            self.base_text = entry.text;
+        }
+    }
+}
+
+/// Applies a change to a text, with positions in the given encoding, clamping positions
+/// outside of it.
+///
+/// # Returns
+///
+/// * The byte range replaced in the previous text and the length of the inserted text, or
+///   `None` if the whole text was replaced.
+fn apply_change(
+    text: &mut String,
+    line_index: &mut LineIndex,
+    change: &TextDocumentContentChangeEvent,
+    encoding: PositionEncoding,
+) -> Option<(std::ops::Range<usize>, usize)> {
+    match change.range {
+        Some(range) => {
+            let start = line_index.offset_in(text, range.start, encoding);
+            let end = line_index.offset_in(text, range.end, encoding).max(start);
+            text.replace_range(start..end, &change.text);
+            line_index.edit(start..end, &change.text);
+            Some((start..end, change.text.len()))
+        }
+        None => {
+            *text = change.text.clone();
+            *line_index = LineIndex::new(text);
+            None
+        }
+    }
+}
+
+/// Returns whether a position is inside a text, at most at the end of its line.
+fn contains_position(text: &str, position: Position, encoding: PositionEncoding) -> bool {
+    // Positions outside of the text are clamped by the conversion
+    let source = SourceText::new(text, encoding);
+    TextSize::from_lsp(position, &source).to_lsp(&source) == position
+}
+
+/// Returns the position of the first character that differs between two texts, in `a`.
+fn first_difference(a: &str, b: &str, encoding: PositionEncoding) -> Position {
+    let offset = a
+        .char_indices()
+        .zip(b.chars())
+        .find(|((_, x), y)| x != y)
+        .map_or(a.len().min(b.len()), |((offset, _), _)| offset);
+    LineIndex::new(a).position_in(a, offset, encoding)
+}
+
+/// Normalizes a URI for lookups; schemes are case-insensitive.
+fn document_key(uri: &str) -> String {
+    match uri.split_once(':') {
+        Some((scheme, rest)) => format!("{}:{rest}", scheme.to_ascii_lowercase()),
+        None => uri.to_string(),
+    }
+}
+
+struct Overlay<'a> {
+    documents: &'a DocumentStore,
+    file_system: &'a dyn FileSystemProvider,
+}
+
+impl FileSystemProvider for Overlay<'_> {
+    fn read_file(&self, uri: &Uri) -> Option<String> {
+        match self.documents.get(uri.as_str()) {
+            Some(document) => Some(document.text.clone()),
+            None => self.file_system.read_file(uri),
+        }
+    }
+
+    fn exists(&self, uri: &Uri) -> bool {
+        self.documents.get(uri.as_str()).is_some() || self.file_system.exists(uri)
+    }
+
+    fn resolve(&self, base: &Uri, reference: &str) -> Option<Uri> {
+        self.file_system.resolve(base, reference)
+    }
+
+    fn list_files(&self, folder: &Uri) -> Vec<Uri> {
+        self.file_system.list_files(folder)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::file_system::SchemeFileSystem;
+    use crate::parser::parse_css;
+
+    #[test]
+    fn test_document_store_custom_schemes() {
+        let mut store = DocumentStore::new();
+        store.open(TextDocument::new("untitled:Untitled-1", "css", 1, "a {}"));
+        store.open(TextDocument::new("not a uri", "css", 1, "b {}"));
+
+        assert!(store.update("UNTITLED:Untitled-1", 2, "a { color: red; }"));
+        assert!(!store.update("untitled:Untitled-1", 1, "stale"));
+        assert!(!store.update("untitled:Untitled-2", 1, "closed"));
+        assert_eq!(
+            store.get("untitled:Untitled-1").unwrap().text,
+            "a { color: red; }"
+        );
+        assert_eq!(store.uris(), vec!["not a uri", "untitled:Untitled-1"]);
+
+        assert!(store.close("not a uri").is_some());
+        assert_eq!(store.len(), 1);
+    }
+
+    #[test]
+    fn test_document_store_overlay() {
+        let mut store = DocumentStore::new();
+        store.open(TextDocument::new(
+            "vscode-vfs://github/o/r/a.css",
+            "css",
+            1,
+            "a {}",
+        ));
+        let file_system = SchemeFileSystem::default();
+        let overlay = store.overlay(&file_system);
+
+        let base: Uri = "vscode-vfs://github/o/r/main.css".parse().unwrap();
+        let uri = overlay.resolve(&base, "./a.css").unwrap();
+        assert_eq!(overlay.read_file(&uri).as_deref(), Some("a {}"));
+        assert!(!overlay.exists(&"vscode-vfs://github/o/r/b.css".parse().unwrap()));
+    }
+
+    fn change(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+        TextDocumentContentChangeEvent {
+            range: Some(Range::new(
+                Position::new(start.0, start.1),
+                Position::new(end.0, end.1),
+            )),
+            range_length: None,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_document_store_apply_changes() {
+        let mut store = DocumentStore::new();
+        store.open(TextDocument::new(
+            "file:///a.css",
+            "css",
+            1,
+            "a {\n  color: red;\n}",
+        ));
+        assert!(store.apply_changes(
+            "file:///a.css",
+            2,
+            &[
+                change((1, 9), (1, 12), "blue"),
+                change((2, 1), (2, 1), "\nb {}")
+            ]
+        ));
+        assert_eq!(
+            store.get("file:///a.css").unwrap().text,
+            "a {\n  color: blue;\n}\nb {}"
+        );
+        assert!(
+            store.history("file:///a.css").is_none(),
+            "Histories are off by default"
+        );
+    }
+
+    #[test]
+    fn test_document_store_position_encoding() {
+        let code = "a { content: \"é😀\"; }";
+        for (encoding, character) in [
+            (PositionEncoding::Utf8, 21),
+            (PositionEncoding::Utf16, 18),
+            (PositionEncoding::Utf32, 17),
+        ] {
+            let mut store = DocumentStore::with_position_encoding(encoding);
+            store.set_history_limit(1);
+            store.open(TextDocument::new("file:///a.css", "css", 1, code));
+            store.apply_changes(
+                "file:///a.css",
+                2,
+                &[change((0, character), (0, character + 1), "")],
+            );
+            assert_eq!(
+                store.get("file:///a.css").unwrap().text,
+                "a { content: \"é😀\" }",
+                "{encoding:?}"
+            );
+            assert_eq!(store.replay("file:///a.css"), Some(vec![]));
+        }
+    }
+
+    #[test]
+    fn test_document_store_parse() {
+        let mut store = DocumentStore::new();
+        assert!(store.parse("file:///a.css").is_none());
+        store.open(TextDocument::new("file:///a.css", "css", 1, "a {}\nb {}"));
+        assert_eq!(
+            store.parse("file:///a.css").unwrap().syntax().to_string(),
+            "a {}\nb {}"
+        );
+
+        store.apply_changes(
+            "file:///a.css",
+            2,
+            &[
+                change((0, 3), (0, 3), " colo"),
+                change((0, 8), (0, 8), "r: red;"),
+            ],
+        );
+        let parse = store.parse("file:///a.css").unwrap();
+        let expected = parse_css("a { color: red;}\nb {}");
+        assert_eq!(parse.syntax().to_string(), "a { color: red;}\nb {}");
+        assert_eq!(
+            format!("{:#?}", parse.syntax()),
+            format!("{:#?}", expected.syntax())
+        );
+        assert!(parse.diagnostics().is_empty());
+
+        store.update("file:///a.css", 3, "a { colo }");
+        assert_eq!(store.parse("file:///a.css").unwrap().diagnostics().len(), 1);
+    }
+
+    #[test]
+    fn test_document_store_replay() {
+        let mut store = DocumentStore::new();
+        store.set_history_limit(2);
+        store.open(TextDocument::new("file:///a.css", "css", 1, "a {}"));
+        store.apply_changes(
+            "file:///a.css",
+            2,
+            &[change((0, 3), (0, 3), " color: red; ")],
+        );
+        store.update("file:///a.css", 3, "a { color: blue; }");
+        // The client thinks the line is longer than it is
+        store.apply_changes("file:///a.css", 4, &[change((0, 18), (0, 40), "\nb {}")]);
+
+        let history = store.history("file:///a.css").unwrap();
+        assert_eq!(history.entries.len(), 2);
+        assert_eq!(history.base_version, 2);
+        assert_eq!(history.base_text, "a { color: red; }");
+        assert_eq!(
+            store.replay("file:///a.css"),
+            Some(vec![ReplayIssue::OutOfBounds {
+                version: 4,
+                change: 0,
+                range: Range::new(Position::new(0, 18), Position::new(0, 40)),
+            }])
+        );
+
+        assert!(store.report_text("file:///a.css", 4, "a { color: blue; }\nb {}"));
+        assert!(!store.report_text("file:///a.css", 2, "a {}"));
+        let issues = store.replay("file:///a.css").unwrap();
+        assert_eq!(issues.len(), 1);
+        // The client missed the replacement of version 3
+        store.report_text("file:///a.css", 4, "a {}\nb {}");
+        assert_eq!(
+            store.replay("file:///a.css").unwrap()[1],
+            ReplayIssue::TextMismatch {
+                version: 4,
+                position: Position::new(0, 3),
+            }
+        );
+
+        store.set_history_limit(1);
+        assert_eq!(store.history("file:///a.css").unwrap().base_version, 3);
+        assert_eq!(
+            first_difference("a {\n  b\n}", "a {\n  c\n}", PositionEncoding::Utf16),
+            Position::new(1, 2)
+        );
+        store.close("file:///a.css");
+        assert_eq!(store.replay("file:///a.css"), None);
+    }
+}
@@ -0,0 +1,359 @@
+use crate::case_folding::fold_case;
+use crate::parser::{find_function_calls, split_list};
+use std::ops::Range;
+
+/// Where the jumps of a `steps()` easing function happen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepPosition {
+    /// `jump-start` or `start`: the first jump happens when the animation starts.
+    JumpStart,
+    /// `jump-end` or `end`: the last jump happens when the animation ends.
+    JumpEnd,
+    /// `jump-none`: no jump at the start or the end.
+    JumpNone,
+    /// `jump-both`: jumps at the start and the end.
+    JumpBoth,
+}
+
+/// An easing function, with its keywords resolved to the function they stand for.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Easing {
+    /// A cubic Bézier curve from `(0, 0)` to `(1, 1)`, by its two control points.
+    CubicBezier(f64, f64, f64, f64),
+    /// A staircase of `steps` intervals.
+    Steps(u32, StepPosition),
+}
+
+/// An error found while parsing an easing function.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EasingError {
+    pub message: String,
+    /// The byte range of the offending argument or call in the source.
+    pub range: Range<usize>,
+}
+
+const STEP_POSITIONS: &[(&str, StepPosition)] = &[
+    ("jump-start", StepPosition::JumpStart),
+    ("start", StepPosition::JumpStart),
+    ("jump-end", StepPosition::JumpEnd),
+    ("end", StepPosition::JumpEnd),
+    ("jump-none", StepPosition::JumpNone),
+    ("jump-both", StepPosition::JumpBoth),
+];
+
+/// Parses an easing keyword, like `ease-in`, or a `cubic-bezier()` or `steps()` call.
+///
+/// # Arguments
+///
+/// * `text` - A component of a value.
+/// * `start` - The byte offset of the component in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * The `Easing`, `Ok(None)` if the component isn't one of these easing functions, or an
+///   `EasingError` for invalid arguments.
+pub fn parse_easing(text: &str, start: usize) -> Result<Option<Easing>, EasingError> {
+    let easing = match fold_case(text).as_ref() {
+        "linear" => Easing::CubicBezier(0.0, 0.0, 1.0, 1.0),
+        "ease" => Easing::CubicBezier(0.25, 0.1, 0.25, 1.0),
+        "ease-in" => Easing::CubicBezier(0.42, 0.0, 1.0, 1.0),
+        "ease-out" => Easing::CubicBezier(0.0, 0.0, 0.58, 1.0),
+        "ease-in-out" => Easing::CubicBezier(0.42, 0.0, 0.58, 1.0),
+        "step-start" => Easing::Steps(1, StepPosition::JumpStart),
+        "step-end" => Easing::Steps(1, StepPosition::JumpEnd),
+        _ => {
+            let Some(call) = find_function_calls(text, start)
+                .into_iter()
+                .next()
+                .filter(|call| call.range.len() == text.len())
+            else {
+                return Ok(None);
+            };
+            let arguments = split_list(call.arguments, call.arguments_start);
+            match fold_case(call.name).as_ref() {
+                "cubic-bezier" => parse_cubic_bezier(&arguments, call.range)?,
+                "steps" => parse_steps(&arguments, call.range)?,
+                _ => return Ok(None),
+            }
+        }
+    };
+    Ok(Some(easing))
+}
+
+fn parse_cubic_bezier(
+    arguments: &[(&str, Range<usize>)],
+    call_range: Range<usize>,
+) -> Result<Easing, EasingError> {
+    if arguments.len() != 4 {
+        return Err(EasingError {
+            message: format!(
+                "`cubic-bezier()` takes 4 numbers, the coordinates of its control points, but got {}.",
+                arguments.len()
+            ),
+            range: call_range,
+        });
+    }
+    let mut numbers = [0.0; 4];
+    for (idx, (argument, range)) in arguments.iter().enumerate() {
+        let number = argument
+            .parse::<f64>()
+            .ok()
+            .filter(|number| number.is_finite())
+            .ok_or_else(|| EasingError {
+                message: format!("`{argument}` isn't a number."),
+                range: range.clone(),
+            })?;
+        // The x coordinates are times, the y coordinates may overshoot
+        if idx % 2 == 0 && !(0.0..=1.0).contains(&number) {
+            return Err(EasingError {
+                message: format!(
+                    "The x coordinates of `cubic-bezier()` must be between 0 and 1, but got `{argument}`."
+                ),
+                range: range.clone(),
+            });
+        }
+        numbers[idx] = number;
+    }
+    let [x1, y1, x2, y2] = numbers;
+    Ok(Easing::CubicBezier(x1, y1, x2, y2))
+}
+
+fn parse_steps(
+    arguments: &[(&str, Range<usize>)],
+    call_range: Range<usize>,
+) -> Result<Easing, EasingError> {
+    let arguments = match arguments {
+        [("", _)] => &[],
+        _ => arguments,
+    };
+    let Some(((count_text, count_range), rest)) = arguments.split_first() else {
+        return Err(EasingError {
+            message: "`steps()` takes a number of steps.".to_string(),
+            range: call_range,
+        });
+    };
+    let count = count_text
+        .parse::<u32>()
+        .ok()
+        .filter(|count| *count > 0)
+        .ok_or_else(|| EasingError {
+            message: format!(
+                "The number of steps must be a positive integer, but got `{count_text}`."
+            ),
+            range: count_range.clone(),
+        })?;
+    let position = match rest {
+        [] => StepPosition::JumpEnd,
+        [(position, range)] => STEP_POSITIONS
+            .iter()
+            .find(|(keyword, _)| position.eq_ignore_ascii_case(keyword))
+            .map(|(_, position)| *position)
+            .ok_or_else(|| EasingError {
+                message: format!("`{position}` isn't a step position."),
+                range: range.clone(),
+            })?,
+        [_, (_, range), ..] => {
+            return Err(EasingError {
+                message: "`steps()` takes at most a number of steps and a position.".to_string(),
+                range: range.clone(),
+            })
+        }
+    };
+    if position == StepPosition::JumpNone && count < 2 {
+        return Err(EasingError {
+            message: "`steps()` with `jump-none` takes at least 2 steps.".to_string(),
+            range: count_range.clone(),
+        });
+    }
+    Ok(Easing::Steps(count, position))
+}
+
+impl Easing {
+    /// Computes the output progress of the easing function at an input progress between 0
+    /// and 1.
+    pub fn evaluate(&self, x: f64) -> f64 {
+        match *self {
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                let bezier = |a: f64, b: f64, t: f64| {
+                    3.0 * a * t * (1.0 - t).powi(2) + 3.0 * b * t.powi(2) * (1.0 - t) + t.powi(3)
+                };
+                // The x coordinate grows with t, as control points lie between 0 and 1
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..32 {
+                    let t = (low + high) / 2.0;
+                    if bezier(x1, x2, t) < x {
+                        low = t;
+                    } else {
+                        high = t;
+                    }
+                }
+                bezier(y1, y2, (low + high) / 2.0)
+            }
+            Easing::Steps(count, position) => {
+                let jumps = match position {
+                    StepPosition::JumpStart | StepPosition::JumpEnd => count,
+                    StepPosition::JumpNone => count - 1,
+                    StepPosition::JumpBoth => count + 1,
+                };
+                let mut step = (x * count as f64).floor();
+                if matches!(position, StepPosition::JumpStart | StepPosition::JumpBoth) {
+                    step += 1.0;
+                }
+                (step / jumps as f64).clamp(0.0, 1.0)
+            }
+        }
+    }
+
+    /// Describes the shape of the easing function in words, like `Starts slowly and ends
+    /// quickly.`.
+    pub fn describe(&self) -> String {
+        match *self {
+            Easing::CubicBezier(x1, y1, x2, y2) => {
+                if x1 == y1 && x2 == y2 {
+                    return "Moves at a constant speed.".to_string();
+                }
+                // The curve leaves its ends towards the nearest distinct control point
+                let start = if (x1, y1) == (0.0, 0.0) {
+                    (x2, y2)
+                } else {
+                    (x1, y1)
+                };
+                let end = if (x2, y2) == (1.0, 1.0) {
+                    (x1, y1)
+                } else {
+                    (x2, y2)
+                };
+                let speed = |dx: f64, dy: f64| {
+                    if dy < dx {
+                        "slowly"
+                    } else if dy > dx {
+                        "quickly"
+                    } else {
+                        "at a constant speed"
+                    }
+                };
+                let mut description = format!(
+                    "Starts {} and ends {}.",
+                    speed(start.0, start.1),
+                    speed(1.0 - end.0, 1.0 - end.1)
+                );
+                if y1 < 0.0 || y2 < 0.0 {
+                    description.push_str(" Moves backwards before its start value.");
+                }
+                if y1 > 1.0 || y2 > 1.0 {
+                    description.push_str(" Overshoots its end value.");
+                }
+                description
+            }
+            Easing::Steps(count, position) => {
+                let steps = if count == 1 {
+                    "a single step".to_string()
+                } else {
+                    format!("{count} steps")
+                };
+                let jumps = match position {
+                    StepPosition::JumpStart => "jumping at the start of each interval",
+                    StepPosition::JumpEnd => "jumping at the end of each interval",
+                    StepPosition::JumpNone => "holding its start and end values",
+                    StepPosition::JumpBoth => "jumping at the start and the end",
+                };
+                format!("Moves in {steps}, {jumps}.")
+            }
+        }
+    }
+
+    /// Draws the curve of the easing function as a sparkline, like `▁▁▂▃▅▇██`.
+    ///
+    /// # Arguments
+    ///
+    /// * `width` - The number of samples, at least 2.
+    pub fn sparkline(&self, width: usize) -> String {
+        const LEVELS: &[char] = &['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let width = width.max(2);
+        let samples: Vec<f64> = (0..width)
+            .map(|idx| self.evaluate(idx as f64 / (width - 1) as f64))
+            .collect();
+        // Curves overshooting 0 or 1 are scaled to fit
+        let low = samples.iter().copied().fold(0.0, f64::min);
+        let high = samples.iter().copied().fold(1.0, f64::max);
+        samples
+            .iter()
+            .map(|sample| {
+                let level = ((sample - low) / (high - low) * (LEVELS.len() - 1) as f64).round();
+                LEVELS[(level as usize).min(LEVELS.len() - 1)]
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_easing() {
+        assert_eq!(
+            parse_easing("EASE-IN", 0),
+            Ok(Some(Easing::CubicBezier(0.42, 0.0, 1.0, 1.0)))
+        );
+        assert_eq!(
+            parse_easing("cubic-bezier(0.1, -0.6, 0.2, 1.5)", 0),
+            Ok(Some(Easing::CubicBezier(0.1, -0.6, 0.2, 1.5)))
+        );
+        assert_eq!(
+            parse_easing("steps(4, jump-both)", 0),
+            Ok(Some(Easing::Steps(4, StepPosition::JumpBoth)))
+        );
+        assert_eq!(
+            parse_easing("steps(2)", 0),
+            Ok(Some(Easing::Steps(2, StepPosition::JumpEnd)))
+        );
+        assert_eq!(parse_easing("1s", 0), Ok(None));
+        assert_eq!(parse_easing("linear(0, 1)", 0), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_easing_errors() {
+        let cases = [
+            ("cubic-bezier(0.1, 0.2, 0.3)", 10..37),
+            ("cubic-bezier(1.1, 0, 0.5, 1)", 23..26),
+            ("cubic-bezier(0, a, 0.5, 1)", 26..27),
+            ("steps(0)", 16..17),
+            ("steps(2.5, end)", 16..19),
+            ("steps(3, middle)", 19..25),
+            ("steps(1, jump-none)", 16..17),
+            ("steps(1, end, end)", 24..27),
+            ("steps()", 10..17),
+        ];
+        for (text, range) in cases {
+            let error = parse_easing(text, 10).unwrap_err();
+            assert_eq!(error.range, range, "{text:?}: {}", error.message);
+        }
+    }
+
+    #[test]
+    fn test_describe_easing() {
+        let describe = |text| parse_easing(text, 0).unwrap().unwrap().describe();
+        assert_eq!(describe("linear"), "Moves at a constant speed.");
+        assert_eq!(describe("ease-in"), "Starts slowly and ends quickly.");
+        assert_eq!(describe("ease-in-out"), "Starts slowly and ends slowly.");
+        assert_eq!(
+            describe("cubic-bezier(0.3, -0.5, 0.7, 1.5)"),
+            "Starts slowly and ends slowly. Moves backwards before its start value. Overshoots its end value."
+        );
+        assert_eq!(
+            describe("step-end"),
+            "Moves in a single step, jumping at the end of each interval."
+        );
+    }
+
+    #[test]
+    fn test_easing_sparkline() {
+        let sparkline = |text| parse_easing(text, 0).unwrap().unwrap().sparkline(8);
+        assert_eq!(sparkline("linear"), "▁▂▃▄▅▆▇█");
+        assert_eq!(sparkline("steps(2, jump-none)"), "▁▁▁▁████");
+        assert_eq!(sparkline("steps(2, start)"), "▅▅▅▅████");
+        let ease_in = sparkline("ease-in");
+        assert!(ease_in.starts_with("▁▁") && ease_in.ends_with('█'));
+    }
+}
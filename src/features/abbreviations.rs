@@ -0,0 +1,252 @@
+use crate::data::abbreviations::{BUILTIN_ABBREVIATIONS, UNITLESS_PROPERTIES, UNIT_ALIASES};
+use crate::features::completion::{find_completion_context, CompletionContext, CompletionProvider};
+use crate::line_index::LineIndex;
+use crate::text_document::TextDocument;
+use crate::value_syntax::{parse_components, validate_value};
+use lsp_types::{CompletionItem, CompletionItemKind, CompletionTextEdit, Position, TextEdit};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// The units that can be written after the numbers of an abbreviation, besides aliases.
+const UNITS: &[&str] = &[
+    "%", "ch", "cm", "deg", "em", "ex", "fr", "mm", "ms", "px", "pt", "rem", "s", "vh", "vmax",
+    "vmin", "vw",
+];
+
+/// Expands abbreviations into declarations where a property name is expected, in the
+/// spirit of Emmet: `df` to `display: flex;`, `posa` to `position: absolute;`, and `m10`
+/// to `margin: 10px;`. Opt in by registering it with
+/// `LanguageService::register_completion_provider`.
+///
+/// Numbers get `px` unless the property is unitless, like `z-index`, or the number is 0.
+/// They may end with a unit or an alias: `p` for `%`, `e` for `em`, `r` for `rem` and
+/// `x` for `ex`. Several numbers are separated by `-`, and a `-` before a number makes it
+/// negative: `m10-5` expands to `margin: 10px 5px;` and `m10--5` to `margin: 10px -5px;`.
+/// Properties whose grammar rejects the numbers, like `color` in `c10`, aren't expanded.
+#[derive(Debug, Clone)]
+pub struct AbbreviationProvider {
+    /// The abbreviations and their expansion, a declaration or a property name.
+    abbreviations: BTreeMap<String, String>,
+}
+
+impl Default for AbbreviationProvider {
+    fn default() -> Self {
+        AbbreviationProvider {
+            abbreviations: BUILTIN_ABBREVIATIONS
+                .iter()
+                .map(|(name, expansion)| (name.to_string(), expansion.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl AbbreviationProvider {
+    /// Creates a provider with the built-in abbreviations.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds the abbreviations of a custom data file, like
+    /// `{ "abbreviations": { "dfc": "display: flex; flex-direction: column" } }`, replacing
+    /// the abbreviations with the same name. The other members of the file, like its
+    /// properties, are ignored, so the file can also be given to `CssDataBuilder::custom_data`.
+    ///
+    /// # Returns
+    ///
+    /// * The provider, or the error of the invalid JSON.
+    pub fn custom_data(mut self, json: &str) -> Result<Self, serde_json::Error> {
+        #[derive(Deserialize)]
+        struct CustomData {
+            #[serde(default)]
+            abbreviations: BTreeMap<String, String>,
+        }
+
+        let custom_data: CustomData = serde_json::from_str(json)?;
+        self.abbreviations.extend(custom_data.abbreviations);
+        Ok(self)
+    }
+
+    /// Expands an abbreviation, like `m10` to `margin: 10px`, without the final `;`.
+    ///
+    /// # Returns
+    ///
+    /// * The declaration, or `None` if the text isn't a known abbreviation or its numbers
+    ///   don't match the grammar of the property.
+    pub fn expand(&self, text: &str) -> Option<String> {
+        if let Some(expansion) = self.abbreviations.get(text) {
+            return expansion.contains(':').then(|| expansion.clone());
+        }
+        // The longest abbreviation of a property followed by numbers
+        self.abbreviations
+            .iter()
+            .filter(|(name, expansion)| text.starts_with(name.as_str()) && !expansion.contains(':'))
+            .max_by_key(|(name, _)| name.len())
+            .and_then(|(name, property)| {
+                let unitless = UNITLESS_PROPERTIES.contains(&property.as_str());
+                let values = expand_numbers(&text[name.len()..], unitless)?;
+                validate_value(property, &parse_components(&values, 0))
+                    .is_none()
+                    .then(|| format!("{property}: {values}"))
+            })
+    }
+}
+
+impl CompletionProvider for AbbreviationProvider {
+    fn provide_completions(
+        &self,
+        document: &TextDocument,
+        position: Position,
+    ) -> Vec<CompletionItem> {
+        let source = document.text.as_str();
+        let line_index = LineIndex::new(source);
+        let offset = line_index.offset(source, position);
+        let start = source[..offset]
+            .char_indices()
+            .rev()
+            .take_while(|&(_, c)| c.is_ascii_alphanumeric() || matches!(c, '.' | '%' | '-'))
+            .last()
+            .map_or(offset, |(idx, _)| idx);
+        let text = &source[start..offset];
+        if !text.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Vec::new();
+        }
+        // A property name is expected where the abbreviation starts
+        let is_declaration = matches!(
+            find_completion_context(source, start),
+            Some(CompletionContext::PropertyName { word_range }) if word_range.is_empty()
+        );
+        let Some(declaration) = self.expand(text).filter(|_| is_declaration) else {
+            return Vec::new();
+        };
+        let new_text = if source[offset..].trim_start().starts_with(';') {
+            declaration.clone()
+        } else {
+            format!("{declaration};")
+        };
+        vec![CompletionItem {
+            label: text.to_string(),
+            kind: Some(CompletionItemKind::SNIPPET),
+            detail: Some(format!("{declaration};")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range: line_index.range(source, start, offset),
+                new_text,
+            })),
+            ..Default::default()
+        }]
+    }
+}
+
+/// Expands the numbers written after the abbreviation of a property, like `10-2e` to
+/// `10px 2em`.
+fn expand_numbers(text: &str, unitless: bool) -> Option<String> {
+    let mut values: Vec<String> = Vec::new();
+    let mut rest = text;
+    while !rest.is_empty() || values.is_empty() {
+        if !values.is_empty() {
+            rest = rest.strip_prefix('-')?;
+        }
+        let sign = if let Some(number) = rest.strip_prefix('-') {
+            rest = number;
+            "-"
+        } else {
+            ""
+        };
+        let number_len = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let number = &rest[..number_len];
+        let is_zero = number.parse::<f64>().ok()? == 0.0;
+        rest = &rest[number_len..];
+        let unit_len = rest
+            .find(|c: char| !(c.is_ascii_alphabetic() || c == '%'))
+            .unwrap_or(rest.len());
+        let unit = match &rest[..unit_len] {
+            "" if unitless || is_zero => "",
+            "" => "px",
+            unit => UNIT_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == unit)
+                .map(|(_, unit)| *unit)
+                .or_else(|| UNITS.iter().find(|known| **known == unit).copied())?,
+        };
+        rest = &rest[unit_len..];
+        values.push(format!("{sign}{number}{unit}"));
+    }
+    Some(values.join(" "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete(provider: &AbbreviationProvider, code: &str) -> Option<String> {
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let document = TextDocument::new("file:///test.css", "css", 1, &code);
+        let position = LineIndex::new(&code).position(&code, offset);
+        let item = provider
+            .provide_completions(&document, position)
+            .into_iter()
+            .next()?;
+        match item.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => Some(edit.new_text),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_expand() {
+        let provider = AbbreviationProvider::new();
+        let cases = [
+            ("df", Some("display: flex")),
+            ("posa", Some("position: absolute")),
+            ("m10", Some("margin: 10px")),
+            ("m0", Some("margin: 0")),
+            ("m-10", Some("margin: -10px")),
+            ("p10-2e", Some("padding: 10px 2em")),
+            ("mt1.5r", Some("margin-top: 1.5rem")),
+            ("w50p", Some("width: 50%")),
+            ("m10--5", Some("margin: 10px -5px")),
+            ("z10", Some("z-index: 10")),
+            ("lh1.5", Some("line-height: 1.5")),
+            ("c10", None),
+            ("bgc0", None),
+            ("m", None),
+            ("df1", None),
+            ("m10q", None),
+            ("m10-", None),
+            ("xyz", None),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(provider.expand(text).as_deref(), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_abbreviation_completions() {
+        let provider = AbbreviationProvider::new();
+        assert_eq!(
+            complete(&provider, "a { m10| }").as_deref(),
+            Some("margin: 10px;")
+        );
+        assert_eq!(
+            complete(&provider, "a {\n  color: red;\n  df|;\n}").as_deref(),
+            Some("display: flex")
+        );
+        assert_eq!(complete(&provider, "a { color: df| }"), None);
+        assert_eq!(complete(&provider, "df| {}"), None);
+
+        let provider = provider
+            .custom_data(r#"{ "properties": [], "abbreviations": { "df": "display: contents", "bs": "border-spacing" } }"#)
+            .unwrap();
+        assert_eq!(
+            complete(&provider, "a { df| }").as_deref(),
+            Some("display: contents;")
+        );
+        assert_eq!(
+            provider.expand("bs2").as_deref(),
+            Some("border-spacing: 2px")
+        );
+        assert!(AbbreviationProvider::new().custom_data("{").is_err());
+    }
+}
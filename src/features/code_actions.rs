@@ -0,0 +1,816 @@
+use crate::budget::Budget;
+use crate::case_folding::{eq_ignore_case, starts_with_ignore_case};
+use crate::dialect::Dialect;
+use crate::features::diagnostics::get_diagnostics_with_rules;
+use crate::features::palette::ColorCluster;
+use crate::file_system::FileSystemProvider;
+use crate::import_graph::{find_imports, is_external, resolve_import, ImportKind};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::find_function_calls;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use crate::rules::{quick_fixes, QuickFix, RuleRegistry};
+use crate::text_document::TextDocument;
+use crate::types::TextEditWASM;
+use lsp_types::{
+    CodeAction, CodeActionKind, Diagnostic, NumberOrString, Position, Range, TextEdit, Uri,
+    WorkspaceEdit,
+};
+use std::collections::HashMap;
+use wasm_bindgen::prelude::*;
+
+/// The size of the largest stylesheet `get_inline_import_action` inlines, in bytes.
+const MAX_INLINED_SIZE: usize = 16 * 1024;
+
+/// The rules whose fixes only remove unnecessary code, safe to apply without review, like
+/// on save.
+const FIX_ALL_RULES: &[&str] = &[
+    "duplicate-declaration",
+    "empty-rule",
+    "unreachable-font-source",
+    "zero-units",
+];
+
+/// Represents a code action, with the edits it makes to the document.
+#[wasm_bindgen(js_name = CodeAction)]
+pub struct CodeActionWASM(CodeAction);
+
+#[wasm_bindgen(js_class = CodeAction)]
+impl CodeActionWASM {
+    #[wasm_bindgen(getter)]
+    pub fn title(&self) -> String {
+        self.0.title.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> Option<String> {
+        self.0.kind.as_ref().map(|kind| kind.as_str().to_string())
+    }
+
+    #[wasm_bindgen(getter, js_name = isPreferred)]
+    pub fn is_preferred(&self) -> bool {
+        self.0.is_preferred == Some(true)
+    }
+
+    /// The edits of the action, all made to the document the action was computed for.
+    #[wasm_bindgen(getter)]
+    pub fn edits(&self) -> Vec<TextEditWASM> {
+        self.0
+            .edit
+            .iter()
+            .filter_map(|edit| edit.changes.as_ref())
+            .flat_map(|changes| changes.values().flatten())
+            .cloned()
+            .map(TextEditWASM::from)
+            .collect()
+    }
+}
+
+impl From<CodeAction> for CodeActionWASM {
+    fn from(code_action: CodeAction) -> Self {
+        CodeActionWASM(code_action)
+    }
+}
+
+/// What code actions are requested for, as in `textDocument/codeAction`.
+#[derive(Debug, Clone, Copy)]
+pub struct CodeActionContext<'a> {
+    pub uri: &'a Uri,
+    pub document: &'a TextDocument,
+    /// The range of the selection, or of the cursor.
+    pub range: Range,
+    /// The diagnostics overlapping the range, as reported to the client.
+    pub diagnostics: &'a [Diagnostic],
+    /// The kinds of actions requested, or `None` for every kind.
+    pub only: Option<&'a [CodeActionKind]>,
+}
+
+impl CodeActionContext<'_> {
+    /// Returns whether actions of a kind are requested, `refactor` including
+    /// `refactor.inline` for example.
+    pub fn accepts(&self, kind: &CodeActionKind) -> bool {
+        self.only.is_none_or(|only| {
+            only.iter().any(|requested| {
+                let requested = requested.as_str();
+                kind.as_str()
+                    .strip_prefix(requested)
+                    .is_some_and(|rest| rest.is_empty() || rest.starts_with('.'))
+            })
+        })
+    }
+}
+
+/// Provides code actions on top of the built-in ones: fixes for the diagnostics of a rule,
+/// like a `LintRule` registered by a downstream crate, or refactorings of a range.
+pub trait CodeActionProvider {
+    /// The code of the rule whose diagnostics are fixed, like `no-id-selectors`, or `None`
+    /// for refactorings, offered whatever the diagnostics.
+    fn rule(&self) -> Option<&str> {
+        None
+    }
+
+    /// Computes code actions.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The `CodeActionContext` of the request.
+    /// * `diagnostic` - The diagnostic of the rule to fix, or `None` for refactorings.
+    ///
+    /// # Returns
+    ///
+    /// * A vector of `CodeAction`, kept if their kind is requested.
+    fn provide_code_actions(
+        &self,
+        context: &CodeActionContext,
+        diagnostic: Option<&Diagnostic>,
+    ) -> Vec<CodeAction>;
+}
+
+/// The code action providers registered on top of the built-in ones, run in registration
+/// order.
+#[derive(Default)]
+pub struct CodeActionRegistry {
+    providers: Vec<Box<dyn CodeActionProvider>>,
+}
+
+impl std::fmt::Debug for CodeActionRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let rules: Vec<Option<&str>> = self
+            .providers
+            .iter()
+            .map(|provider| provider.rule())
+            .collect();
+        f.debug_struct("CodeActionRegistry")
+            .field("providers", &rules)
+            .finish()
+    }
+}
+
+impl CodeActionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a provider after those already registered; several providers may fix
+    /// the same rule.
+    pub fn register(&mut self, provider: impl CodeActionProvider + 'static) {
+        self.providers.push(Box::new(provider));
+    }
+
+    /// Returns the providers fixing the diagnostics of a rule.
+    pub fn fixes(&self, rule: &str) -> impl Iterator<Item = &dyn CodeActionProvider> + '_ {
+        let rule = rule.to_string();
+        self.providers
+            .iter()
+            .map(Box::as_ref)
+            .filter(move |provider| provider.rule() == Some(rule.as_str()))
+    }
+
+    /// Returns the providers of refactorings.
+    pub fn refactorings(&self) -> impl Iterator<Item = &dyn CodeActionProvider> {
+        self.providers
+            .iter()
+            .map(Box::as_ref)
+            .filter(|provider| provider.rule().is_none())
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+}
+
+/// Creates the edit of a code action changing a single document.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `edits` - The `TextEdit` made to the document.
+///
+/// # Returns
+///
+/// * The `WorkspaceEdit`.
+pub fn document_edit(uri: &Uri, edits: Vec<TextEdit>) -> WorkspaceEdit {
+    WorkspaceEdit {
+        changes: Some(HashMap::from([(uri.clone(), edits)])),
+        ..Default::default()
+    }
+}
+
+/// Creates the `quickfix` action applying a fix to a document.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `fix` - The `QuickFix`, with its title and edits.
+/// * `diagnostic` - The diagnostic the fix resolves.
+/// * `is_preferred` - Whether the fix is the one to apply when fixing problems in bulk,
+///   like with the "auto fix" command of VS Code.
+///
+/// # Returns
+///
+/// * The `CodeAction`.
+pub fn quick_fix_action(
+    uri: &Uri,
+    fix: QuickFix,
+    diagnostic: &Diagnostic,
+    is_preferred: bool,
+) -> CodeAction {
+    CodeAction {
+        title: fix.title,
+        kind: Some(CodeActionKind::QUICKFIX),
+        diagnostics: Some(vec![diagnostic.clone()]),
+        edit: Some(document_edit(uri, fix.edits)),
+        is_preferred: is_preferred.then_some(true),
+        ..Default::default()
+    }
+}
+
+/// Computes the code actions for a range of a document: the fixes of its diagnostics, the
+/// built-in ones carried by the diagnostics then those of the registered providers, the
+/// refactorings of the providers, and the `source.fixAll` action when requested explicitly.
+///
+/// The first built-in fix of a diagnostic is preferred, as rules list their safest fix
+/// first.
+///
+/// # Arguments
+///
+/// * `context` - The `CodeActionContext` of the request.
+/// * `registry` - The `CodeActionRegistry` of additional providers.
+///
+/// # Returns
+///
+/// * A vector of `CodeAction` of the requested kinds.
+pub fn get_code_actions(
+    context: &CodeActionContext,
+    registry: &CodeActionRegistry,
+) -> Vec<CodeAction> {
+    get_code_actions_with_encoding(context, registry, PositionEncoding::Utf16)
+}
+
+/// Computes the code actions for a range of a document with the position encoding of the
+/// client, see `get_code_actions`.
+///
+/// # Arguments
+///
+/// * `context` - The `CodeActionContext` of the request.
+/// * `registry` - The `CodeActionRegistry` of additional providers.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `CodeAction` of the requested kinds.
+pub fn get_code_actions_with_encoding(
+    context: &CodeActionContext,
+    registry: &CodeActionRegistry,
+    encoding: PositionEncoding,
+) -> Vec<CodeAction> {
+    let mut actions = Vec::new();
+    for diagnostic in context.diagnostics {
+        for (idx, fix) in quick_fixes(diagnostic).into_iter().enumerate() {
+            actions.push(quick_fix_action(context.uri, fix, diagnostic, idx == 0));
+        }
+        if let Some(NumberOrString::String(code)) = &diagnostic.code {
+            for provider in registry.fixes(code) {
+                actions.extend(provider.provide_code_actions(context, Some(diagnostic)));
+            }
+        }
+    }
+    for provider in registry.refactorings() {
+        actions.extend(provider.provide_code_actions(context, None));
+    }
+    // Computed from every diagnostic of the document, so only when asked for, like on save
+    if context.only.is_some() && context.accepts(&CodeActionKind::SOURCE_FIX_ALL) {
+        let source = &context.document.text;
+        actions.extend(get_fix_all_action_with_encoding(
+            context.uri,
+            source,
+            encoding,
+        ));
+    }
+    actions.retain(|action| {
+        action
+            .kind
+            .as_ref()
+            .is_none_or(|kind| context.accepts(kind))
+    });
+    actions
+}
+
+/// Computes the `source.fixAll` action of a document, applying the fixes of every
+/// diagnostic that only removes unnecessary code in a single edit.
+///
+/// When fixes overlap, like a duplicate declaration inside a rule that becomes empty, only
+/// the first one is applied, and running the action again applies the others.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document.
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * The `CodeAction`, or `None` if there is nothing to fix.
+pub fn get_fix_all_action(uri: &Uri, source: &str) -> Option<CodeAction> {
+    get_fix_all_action_with_encoding(uri, source, PositionEncoding::Utf16)
+}
+
+/// Computes the `source.fixAll` action of a document with the position encoding of the
+/// client, see `get_fix_all_action`.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document.
+/// * `source` - The original CSS source code as a string slice.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `CodeAction`, or `None` if there is nothing to fix.
+pub fn get_fix_all_action_with_encoding(
+    uri: &Uri,
+    source: &str,
+    encoding: PositionEncoding,
+) -> Option<CodeAction> {
+    let mut rules = RuleRegistry::new();
+    rules.set_position_encoding(encoding);
+    let diagnostics = get_diagnostics_with_rules(uri, source, &rules, &Budget::UNLIMITED).items;
+    let mut fixed: Vec<Diagnostic> = Vec::new();
+    let mut edits: Vec<TextEdit> = Vec::new();
+    for diagnostic in diagnostics {
+        let Some(NumberOrString::String(code)) = &diagnostic.code else {
+            continue;
+        };
+        if !FIX_ALL_RULES.contains(&code.as_str()) {
+            continue;
+        }
+        let Some(fix) = quick_fixes(&diagnostic).into_iter().next() else {
+            continue;
+        };
+        let overlaps = fix.edits.iter().any(|edit| {
+            edits.iter().any(|accepted| {
+                edit.range.start < accepted.range.end && accepted.range.start < edit.range.end
+            })
+        });
+        if !overlaps {
+            edits.extend(fix.edits);
+            fixed.push(diagnostic);
+        }
+    }
+    if edits.is_empty() {
+        return None;
+    }
+
+    edits.sort_by_key(|edit| edit.range.start);
+    Some(CodeAction {
+        title: "Fix all auto-fixable problems".to_string(),
+        kind: Some(CodeActionKind::SOURCE_FIX_ALL),
+        diagnostics: Some(fixed),
+        edit: Some(document_edit(uri, edits)),
+        ..Default::default()
+    })
+}
+
+/// Computes the action replacing every color of a palette cluster with a single value,
+/// like a design token.
+///
+/// # Arguments
+///
+/// * `cluster` - The `ColorCluster` to unify, from `get_color_palette`.
+/// * `replacement` - The value replacing the colors, like `var(--brand)` or `#36f`.
+///
+/// # Returns
+///
+/// * The `CodeAction`, editing every stylesheet of the cluster, or `None` if every color
+///   is already the replacement.
+pub fn get_unify_colors_action(cluster: &ColorCluster, replacement: &str) -> Option<CodeAction> {
+    let mut changes: Vec<(Uri, Vec<TextEdit>)> = Vec::new();
+    let mut count = 0;
+    for usage in &cluster.usages {
+        if usage.text == replacement {
+            continue;
+        }
+        count += 1;
+        let edit = TextEdit {
+            range: usage.range,
+            new_text: replacement.to_string(),
+        };
+        match changes.iter_mut().find(|(uri, _)| *uri == usage.uri) {
+            Some((_, edits)) => edits.push(edit),
+            None => changes.push((usage.uri.clone(), vec![edit])),
+        }
+    }
+    if changes.is_empty() {
+        return None;
+    }
+
+    Some(CodeAction {
+        title: format!(
+            "Replace {count} similar {} with `{replacement}`",
+            if count == 1 { "color" } else { "colors" }
+        ),
+        kind: Some(CodeActionKind::REFACTOR_REWRITE),
+        edit: Some(WorkspaceEdit {
+            changes: Some(changes.into_iter().collect()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    })
+}
+
+/// Removes the `@charset` statement starting a stylesheet, which is only valid as the first
+/// statement, keeping the rules written after it on the same line.
+fn without_charset(contents: &str) -> String {
+    let mut tokens = Tokenizer::new(contents, 0)
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment));
+    match tokens.next() {
+        Some(token)
+            if token.kind == TokenKind::AtKeyword && eq_ignore_case(token.text, "@charset") =>
+        {
+            let end = tokens
+                .find(|token| token.kind == TokenKind::Semicolon)
+                .map_or(contents.len(), |token| token.range.end);
+            [&contents[..token.range.start], &contents[end..]].concat()
+        }
+        _ => contents.to_string(),
+    }
+}
+
+/// Computes the action replacing the `@import` at a position with the contents of the
+/// imported stylesheet, wrapped in the `@layer`, `@supports` and `@media` rules of its
+/// conditions, to flatten legacy import chains.
+///
+/// Only small stylesheets are inlined, and only when it keeps the meaning of both:
+///
+/// * the import is the last one, as rules before other imports would invalidate them;
+/// * the imported stylesheet has no imports of its own, which should be inlined first;
+/// * it is in the same folder, or has no `url()`, which would resolve differently.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` containing the import.
+/// * `position` - The `Position` of the cursor, anywhere in the import.
+/// * `file_system` - The `FileSystemProvider` reading the imported stylesheet.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `CodeAction`, or `None` if there is no import at the position or it can't be
+///   inlined.
+pub fn get_inline_import_action(
+    document: &TextDocument,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+    encoding: PositionEncoding,
+) -> Option<CodeAction> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+    let imports: Vec<_> = find_imports(source)
+        .into_iter()
+        .filter(|import| import.kind == ImportKind::Import)
+        .collect();
+    let (idx, import) = imports
+        .iter()
+        .enumerate()
+        .find(|(_, import)| (import.range.start..=import.range.end).contains(&offset))?;
+    if idx + 1 < imports.len() || is_external(&import.target) {
+        return None;
+    }
+
+    let uri = document.lsp_uri()?;
+    let dialect = Dialect::from_language_id(&document.language_id);
+    let target = resolve_import(&uri, &import.target, dialect, file_system)?;
+    let contents = file_system.read_file(&target.uri)?;
+    if contents.len() > MAX_INLINED_SIZE || !find_imports(&contents).is_empty() {
+        return None;
+    }
+    let folder = |uri: &Uri| {
+        uri.as_str()
+            .rsplit_once('/')
+            .map(|(folder, _)| folder.to_string())
+    };
+    let has_urls = find_function_calls(&contents, 0)
+        .iter()
+        .any(|call| starts_with_ignore_case(call.name, "url"));
+    if has_urls && folder(&uri) != folder(&target.uri) {
+        return None;
+    }
+
+    let mut inlined = without_charset(&contents).trim().to_string();
+    let conditions = source[import.target_range.end..import.range.end].trim_end_matches(';');
+    for wrapper in import_wrappers(conditions).into_iter().rev() {
+        let indented: Vec<String> = inlined
+            .lines()
+            .map(|line| match line {
+                "" => String::new(),
+                line => format!("  {line}"),
+            })
+            .collect();
+        inlined = format!("{wrapper} {{\n{}\n}}", indented.join("\n"));
+    }
+
+    let edit = TextEdit {
+        range: line_index.range(source, import.range.start, import.range.end),
+        new_text: inlined,
+    };
+    Some(CodeAction {
+        title: format!("Inline `{}`", import.target),
+        kind: Some(CodeActionKind::REFACTOR_INLINE),
+        edit: Some(document_edit(&uri, vec![edit])),
+        ..Default::default()
+    })
+}
+
+/// Translates the conditions of an import, like `layer(base) supports(display: grid)
+/// print`, to the at-rules applying them once inlined, outermost first.
+fn import_wrappers(conditions: &str) -> Vec<String> {
+    let mut wrappers = Vec::new();
+    let mut rest = conditions.trim();
+    for (keyword, at_rule) in [("layer", "@layer"), ("supports", "@supports")] {
+        if !starts_with_ignore_case(rest, keyword) {
+            continue;
+        }
+        let after = &rest[keyword.len()..];
+        if after.starts_with('(') {
+            let Some(call) = find_function_calls(rest, 0).into_iter().next() else {
+                break;
+            };
+            let arguments = call.arguments.trim();
+            // A declaration, like `display: grid`, needs parentheses
+            if keyword == "supports" && !arguments.starts_with('(') && arguments.contains(':') {
+                wrappers.push(format!("{at_rule} ({arguments})"));
+            } else {
+                wrappers.push(format!("{at_rule} {arguments}"));
+            }
+            rest = rest[call.range.end..].trim_start();
+        } else if keyword == "layer" && after.chars().next().is_none_or(char::is_whitespace) {
+            // An anonymous layer
+            wrappers.push(at_rule.to_string());
+            rest = after.trim_start();
+        }
+    }
+    if !rest.is_empty() {
+        let media = rest.split_whitespace().collect::<Vec<_>>().join(" ");
+        wrappers.push(format!("@media {media}"));
+    }
+    wrappers
+}
+
+#[wasm_bindgen]
+pub fn get_fix_all_action_wasm(document: &TextDocument) -> Option<CodeActionWASM> {
+    let uri = document.lsp_uri()?;
+    get_fix_all_action(&uri, &document.text).map(CodeActionWASM::from)
+}
+
+/// Computes the action unifying the colors of a palette cluster, see
+/// `get_unify_colors_action`.
+///
+/// # Arguments
+///
+/// * `cluster` - The `ColorCluster` as JSON, as returned by `get_color_palette_wasm`.
+/// * `replacement` - The value replacing the colors.
+///
+/// # Returns
+///
+/// * The `CodeAction` as JSON, or `None` if there is nothing to replace.
+#[wasm_bindgen]
+pub fn get_unify_colors_action_wasm(cluster: &str, replacement: &str) -> Option<String> {
+    let cluster: ColorCluster = serde_json::from_str(cluster).ok()?;
+    let action = get_unify_colors_action(&cluster, replacement)?;
+    serde_json::to_string(&action).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::diagnostics::get_diagnostics;
+    use crate::line_index::LineIndex;
+
+    fn fix_all(code: &str) -> String {
+        let uri = "file:///test.css".parse().unwrap();
+        let Some(action) = get_fix_all_action(&uri, code) else {
+            return code.to_string();
+        };
+        assert_eq!(action.kind, Some(CodeActionKind::SOURCE_FIX_ALL));
+        let edits = &action.edit.unwrap().changes.unwrap()[&uri];
+        let line_index = LineIndex::new(code);
+        let mut fixed = code.to_string();
+        for edit in edits.iter().rev() {
+            let start = line_index.offset(code, edit.range.start);
+            let end = line_index.offset(code, edit.range.end);
+            fixed.replace_range(start..end, &edit.new_text);
+        }
+        fixed
+    }
+
+    #[test]
+    fn test_fix_all() {
+        let code =
+            "a {\n  margin: 0px;\n  color: red;\n  color: red;\n}\nb {}\nc { content: \"x\n}\n";
+        assert_eq!(
+            fix_all(code),
+            "a {\n  margin: 0;\n  color: red;\n}\nc { content: \"x\n}\n",
+            "Errors aren't fixed without review"
+        );
+    }
+
+    #[test]
+    fn test_fix_all_overlapping_fixes() {
+        let code = ".a { .b {} }\n";
+        let once = fix_all(code);
+        assert_eq!(once, ".a {  }\n");
+        assert_eq!(fix_all(&once), "");
+        assert!(
+            get_fix_all_action(&"file:///a.css".parse().unwrap(), "a { color: red; }").is_none()
+        );
+    }
+
+    struct RemoveIds;
+
+    impl CodeActionProvider for RemoveIds {
+        fn rule(&self) -> Option<&str> {
+            Some("no-id-selectors")
+        }
+
+        fn provide_code_actions(
+            &self,
+            context: &CodeActionContext,
+            diagnostic: Option<&Diagnostic>,
+        ) -> Vec<CodeAction> {
+            let fix = QuickFix {
+                title: "Use a class".to_string(),
+                edits: vec![TextEdit {
+                    range: diagnostic.unwrap().range,
+                    new_text: ".a".to_string(),
+                }],
+            };
+            vec![quick_fix_action(
+                context.uri,
+                fix,
+                diagnostic.unwrap(),
+                false,
+            )]
+        }
+    }
+
+    struct Wrap;
+
+    impl CodeActionProvider for Wrap {
+        fn provide_code_actions(
+            &self,
+            context: &CodeActionContext,
+            diagnostic: Option<&Diagnostic>,
+        ) -> Vec<CodeAction> {
+            assert!(diagnostic.is_none());
+            vec![CodeAction {
+                title: "Wrap in @layer".to_string(),
+                kind: Some(CodeActionKind::REFACTOR_REWRITE),
+                edit: Some(document_edit(context.uri, Vec::new())),
+                ..Default::default()
+            }]
+        }
+    }
+
+    #[test]
+    fn test_get_code_actions() {
+        let uri: Uri = "file:///test.css".parse().unwrap();
+        let code = "a { margin: 0px; }\n#id {}";
+        let document = TextDocument::new(uri.as_str(), "css", 1, code);
+        let mut diagnostics = get_diagnostics(&uri, code);
+        diagnostics.retain(|diagnostic| {
+            diagnostic.code == Some(NumberOrString::String("zero-units".to_string()))
+        });
+        diagnostics.push(Diagnostic {
+            range: lsp_types::Range::new(Position::new(1, 0), Position::new(1, 3)),
+            code: Some(NumberOrString::String("no-id-selectors".to_string())),
+            ..Default::default()
+        });
+        let mut registry = CodeActionRegistry::new();
+        registry.register(RemoveIds);
+        registry.register(Wrap);
+        let context = CodeActionContext {
+            uri: &uri,
+            document: &document,
+            range: lsp_types::Range::default(),
+            diagnostics: &diagnostics,
+            only: None,
+        };
+        let actions: Vec<(String, Option<bool>)> = get_code_actions(&context, &registry)
+            .into_iter()
+            .map(|action| (action.title, action.is_preferred))
+            .collect();
+        assert_eq!(
+            actions,
+            vec![
+                ("Remove the unit".to_string(), Some(true)),
+                ("Use a class".to_string(), None),
+                ("Wrap in @layer".to_string(), None),
+            ]
+        );
+
+        let only = [CodeActionKind::REFACTOR];
+        let context = CodeActionContext {
+            only: Some(&only),
+            ..context
+        };
+        let actions = get_code_actions(&context, &registry);
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].kind, Some(CodeActionKind::REFACTOR_REWRITE));
+
+        let only = [CodeActionKind::SOURCE];
+        let context = CodeActionContext {
+            only: Some(&only),
+            ..context
+        };
+        let actions = get_code_actions(&context, &registry);
+        assert_eq!(actions[0].kind, Some(CodeActionKind::SOURCE_FIX_ALL));
+        assert!(!context.accepts(&CodeActionKind::new("sourceX")));
+    }
+
+    struct MemoryFileSystem(HashMap<&'static str, &'static str>);
+
+    impl FileSystemProvider for MemoryFileSystem {
+        fn read_file(&self, uri: &Uri) -> Option<String> {
+            self.0
+                .get(uri.as_str())
+                .map(|contents| contents.to_string())
+        }
+    }
+
+    fn inline_import(code: &str) -> Option<String> {
+        let file_system = MemoryFileSystem(HashMap::from([
+            (
+                "file:///p/base.css",
+                "@charset \"utf-8\";\n.a { color: red; }\n\n.b {}\n",
+            ),
+            ("file:///p/chain.css", "@import \"base.css\";\n.c {}"),
+            ("file:///p/compact.css", "@charset \"utf-8\"; .a {}\n.b {}"),
+            ("file:///p/lib/icons.css", ".i { background: url(i.svg); }"),
+        ]));
+        let document = TextDocument::new("file:///p/main.css", "css", 1, code);
+        let action = get_inline_import_action(
+            &document,
+            Position::new(0, 3),
+            &file_system,
+            PositionEncoding::Utf16,
+        )?;
+        let edit = action.edit?.changes?.into_values().next()?.remove(0);
+        assert_eq!(edit.range.start, Position::new(0, 0));
+        Some(edit.new_text)
+    }
+
+    #[test]
+    fn test_inline_import_action() {
+        assert_eq!(
+            inline_import("@import url(base.css);\nmain {}").as_deref(),
+            Some(".a { color: red; }\n\n.b {}")
+        );
+        assert_eq!(
+            inline_import("@import \"base.css\" layer(base) supports(display: grid) screen and (min-width: 600px);").as_deref(),
+            Some("@layer base {\n  @supports (display: grid) {\n    @media screen and (min-width: 600px) {\n      .a { color: red; }\n\n      .b {}\n    }\n  }\n}")
+        );
+        assert_eq!(
+            inline_import("@import \"base.css\" layer;").as_deref(),
+            Some("@layer {\n  .a { color: red; }\n\n  .b {}\n}")
+        );
+
+        assert_eq!(
+            inline_import("@import \"compact.css\";").as_deref(),
+            Some(".a {}\n.b {}")
+        );
+
+        assert!(inline_import("@import \"base.css\";\n@import \"other.css\";").is_none());
+        assert!(inline_import("@import \"chain.css\";").is_none());
+        assert!(inline_import("@import \"lib/icons.css\";").is_none());
+        assert!(inline_import("@import \"missing.css\";").is_none());
+        assert!(inline_import("@import \"https://example.com/a.css\";").is_none());
+    }
+
+    #[test]
+    fn test_unify_colors_action() {
+        let a: Uri = "file:///a.css".parse().unwrap();
+        let b: Uri = "file:///b.css".parse().unwrap();
+        let stylesheets = [
+            (a.clone(), "a { color: #3366ff; }\nb { color: #3367ff; }"),
+            (b.clone(), "c { color: #3467fe; }"),
+        ];
+        let clusters =
+            crate::features::palette::get_color_palette(&stylesheets, 2.3, PositionEncoding::Utf16);
+        let action = get_unify_colors_action(&clusters[0], "var(--brand)").unwrap();
+        assert_eq!(action.title, "Replace 3 similar colors with `var(--brand)`");
+        let edit = action.edit.unwrap();
+        assert_eq!(edit.changes.as_ref().unwrap()[&a].len(), 2);
+        assert_eq!(
+            edit.changes.as_ref().unwrap()[&b][0].new_text,
+            "var(--brand)"
+        );
+
+        let json = serde_json::to_string(&clusters[0]).unwrap();
+        let action = get_unify_colors_action_wasm(&json, "#3366ff").unwrap();
+        assert!(action.contains("Replace 2 similar colors"));
+        assert!(get_unify_colors_action_wasm("{}", "#3366ff").is_none());
+    }
+}
@@ -1,5 +1,16 @@
+use crate::budget::{Budget, Budgeted};
+use crate::case_folding::fold_case;
+use crate::chunks::run_in_chunks;
+use crate::data::colors::{is_color_property, named_color, NAMED_COLORS};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{
+    find_function_calls, parse_css, read_declaration, split_components, split_list,
+};
 use crate::{text_document, types::RangeWASM};
-use lsp_types::ColorInformation;
+use biome_css_parser::CssParse;
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Color, ColorInformation, ColorPresentation, Range, TextEdit};
+use std::ops::Range as ByteRange;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(js_name = ColorInformation)]
@@ -50,9 +61,590 @@ impl ColorInformationWASM {
     }
 }
 
+impl From<ColorInformation> for ColorInformationWASM {
+    fn from(color_information: ColorInformation) -> Self {
+        ColorInformationWASM(color_information)
+    }
+}
+
+/// Finds the colors used in the given CSS source code: hex colors, `rgb()` and `hsl()`
+/// colors, named colors in the values of color properties and custom properties, and the
+/// named colors of gradient color stops, each stop being a color of its own.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `ColorInformation` with the range and RGBA value of each color.
+pub fn get_document_colors(source: &str) -> Vec<ColorInformation> {
+    get_document_colors_with_budget(source, &Budget::UNLIMITED).items
+}
+
+/// Finds the colors used in the given CSS source code, stopping early once the budget runs
+/// out.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `ColorInformation` found within the budget, flagged as truncated if some were
+///   dropped.
+pub fn get_document_colors_with_budget(
+    source: &str,
+    budget: &Budget,
+) -> Budgeted<ColorInformation> {
+    get_document_colors_with_parse(source, parse_css(source), budget, PositionEncoding::Utf16)
+}
+
+/// Finds the colors in the given CSS source code from an existing parse of it, see
+/// `get_document_colors_with_budget`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `budget` - The `Budget` of the request.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `ColorInformation` found within the budget, flagged as truncated if some were
+///   dropped.
+pub fn get_document_colors_with_parse(
+    source: &str,
+    parse: CssParse,
+    budget: &Budget,
+    encoding: PositionEncoding,
+) -> Budgeted<ColorInformation> {
+    let tracker = budget.start();
+    let line_index = LineIndex::with_encoding(source, encoding);
+
+    let mut colors: Vec<(ByteRange<usize>, Color)> = Vec::new();
+    for node in parse.syntax().descendants() {
+        if !tracker.has_time() {
+            break;
+        }
+        if node.kind() == CssSyntaxKind::CSS_COLOR {
+            let range = node.text_trimmed_range();
+            let range = usize::from(range.start())..usize::from(range.end());
+            if let Some(color) = parse_hex_color(&source[range.clone()]) {
+                colors.push((range, color));
+            }
+            continue;
+        }
+        let Some(declaration) = read_declaration(node) else {
+            continue;
+        };
+        let (value, value_start) = (&declaration.value, declaration.value_range.start);
+        colors.extend(function_colors(value, value_start));
+        colors.extend(gradient_named_colors(value, value_start));
+        if is_color_property(&declaration.name) || declaration.name.starts_with("--") {
+            colors.extend(named_colors(value, value_start));
+        }
+    }
+    // Sorted first, so the colors kept within the budget are the first ones of the document
+    colors.sort_by_key(|(range, _)| range.start);
+
+    let colors = colors
+        .into_iter()
+        .take_while(|_| tracker.allow())
+        .map(|(range, color)| ColorInformation {
+            range: line_index.range(source, range.start, range.end),
+            color,
+        })
+        .collect();
+    tracker.finish(colors)
+}
+
+/// Finds the colors of a stylesheet too large to be parsed at once, one chunk of whole
+/// top-level rules at a time, see `split_chunks`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `budget` - The `Budget` of the request, shared by the chunks.
+/// * `chunk_size` - The size in bytes from which a chunk ends.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `ColorInformation` found within the budget, flagged as truncated if some were
+///   dropped.
+pub fn get_document_colors_in_chunks(
+    source: &str,
+    budget: &Budget,
+    chunk_size: usize,
+    encoding: PositionEncoding,
+) -> Budgeted<ColorInformation> {
+    run_in_chunks(source, chunk_size, encoding, budget, |chunk, budget| {
+        let parse = parse_css(chunk.text);
+        let mut result = get_document_colors_with_parse(chunk.text, parse, budget, encoding);
+        for color in &mut result.items {
+            color.range = chunk.shift_range(color.range);
+        }
+        result
+    })
+}
+
+/// Finds the `rgb()`, `rgba()`, `hsl()` and `hsla()` colors of a value, including the ones
+/// nested in other functions, skipping those with computed channels like `var(--red)`.
+fn function_colors(value: &str, value_start: usize) -> Vec<(ByteRange<usize>, Color)> {
+    find_function_calls(value, value_start)
+        .into_iter()
+        .filter_map(|call| {
+            let color = parse_color_function(call.name, call.arguments)?;
+            Some((call.range, color))
+        })
+        .collect()
+}
+
+/// Finds the named colors among the components of a value, like `red` in
+/// `1px solid red, blue`.
+fn named_colors(value: &str, value_start: usize) -> Vec<(ByteRange<usize>, Color)> {
+    split_list(value, value_start)
+        .into_iter()
+        .flat_map(|(item, range)| split_components(item, range.start))
+        .filter_map(|(component, range)| {
+            let color = parse_hex_color(named_color(component)?)?;
+            Some((range, color))
+        })
+        .collect()
+}
+
+/// Finds the named colors of the color stops of the gradients of a value, like `red` in
+/// `linear-gradient(red, #00f 50%)`. Hex colors are found from the syntax tree.
+fn gradient_named_colors(value: &str, value_start: usize) -> Vec<(ByteRange<usize>, Color)> {
+    find_function_calls(value, value_start)
+        .into_iter()
+        .filter(|call| is_gradient(call.name))
+        .flat_map(|call| split_list(call.arguments, call.arguments_start))
+        .flat_map(|(stop, range)| split_components(stop, range.start))
+        .filter_map(|(component, range)| {
+            let color = parse_hex_color(named_color(component)?)?;
+            Some((range, color))
+        })
+        .collect()
+}
+
+/// Whether a function is a gradient, like `repeating-linear-gradient` or
+/// `-webkit-radial-gradient`.
+fn is_gradient(name: &str) -> bool {
+    let name = fold_case(name);
+    let name = name
+        .strip_prefix('-')
+        .and_then(|name| name.split_once('-'))
+        .map_or(name.as_ref(), |(_, name)| name);
+    let name = name.strip_prefix("repeating-").unwrap_or(name);
+    matches!(
+        name,
+        "linear-gradient" | "radial-gradient" | "conic-gradient"
+    )
+}
+
+/// Computes the ways a color can be written, replacing only the color at a range, like a
+/// single stop of a gradient.
+///
+/// # Arguments
+///
+/// * `color` - The `Color` picked by the user.
+/// * `range` - The `Range` of the color being replaced, as reported by
+///   `get_document_colors`.
+///
+/// # Returns
+///
+/// * A vector of `ColorPresentation`: the hex notation, the named color if one has this
+///   exact value, then the `rgb()`, `hsl()` and `oklch()` notations.
+pub fn get_color_presentations(color: Color, range: Range) -> Vec<ColorPresentation> {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    let (red, green, blue) = (
+        channel(color.red),
+        channel(color.green),
+        channel(color.blue),
+    );
+    let mut hex = format!("#{red:02x}{green:02x}{blue:02x}");
+    if channel(color.alpha) < 255 {
+        hex.push_str(&format!("{:02x}", channel(color.alpha)));
+    }
+    let named = NAMED_COLORS
+        .iter()
+        .find(|(_, value)| *value == hex)
+        .map(|(name, _)| name.to_string());
+    // The alpha channel, after the `/` of the functional notations
+    let alpha = match color.alpha.clamp(0.0, 1.0) {
+        alpha if alpha >= 1.0 => String::new(),
+        alpha => format!(" / {}", format_number(alpha, 2)),
+    };
+
+    let (hue, saturation, lightness) = rgb_to_hsl(color.red, color.green, color.blue);
+    let hsl = format!(
+        "hsl({} {}% {}%{alpha})",
+        format_number(hue, 0),
+        format_number(saturation * 100.0, 0),
+        format_number(lightness * 100.0, 0)
+    );
+    let (lightness, chroma, hue) = rgb_to_oklch(color.red, color.green, color.blue);
+    let oklch = format!(
+        "oklch({}% {} {}{alpha})",
+        format_number(lightness * 100.0, 2),
+        format_number(chroma, 3),
+        format_number(hue, 2)
+    );
+
+    std::iter::once(hex)
+        .chain(named)
+        .chain([format!("rgb({red} {green} {blue}{alpha})"), hsl, oklch])
+        .map(|label| ColorPresentation {
+            text_edit: Some(TextEdit {
+                range,
+                new_text: label.clone(),
+            }),
+            label,
+            additional_text_edits: None,
+        })
+        .collect()
+}
+
+/// Formats a number with at most `decimals` decimals, without trailing zeros.
+fn format_number(value: f32, decimals: usize) -> String {
+    let text = format!("{value:.decimals$}");
+    let text = if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.')
+    } else {
+        &text
+    };
+    match text {
+        "-0" => "0".to_string(),
+        text => text.to_string(),
+    }
+}
+
+/// Converts a color from RGB to HSL, with the hue in degrees and the other components
+/// between 0 and 1.
+fn rgb_to_hsl(red: f32, green: f32, blue: f32) -> (f32, f32, f32) {
+    let max = red.max(green).max(blue);
+    let min = red.min(green).min(blue);
+    let lightness = (max + min) / 2.0;
+    let delta = max - min;
+    if delta == 0.0 {
+        return (0.0, 0.0, lightness);
+    }
+    let saturation = delta / (1.0 - (2.0 * lightness - 1.0).abs());
+    let hue = if max == red {
+        ((green - blue) / delta).rem_euclid(6.0)
+    } else if max == green {
+        (blue - red) / delta + 2.0
+    } else {
+        (red - green) / delta + 4.0
+    };
+    (hue * 60.0, saturation, lightness)
+}
+
+/// Converts a color from sRGB to OKLCH, with the lightness between 0 and 1 and the hue in
+/// degrees, 0 for grays.
+fn rgb_to_oklch(red: f32, green: f32, blue: f32) -> (f32, f32, f32) {
+    let linear = |value: f32| {
+        if value <= 0.04045 {
+            value / 12.92
+        } else {
+            ((value + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(red), linear(green), linear(blue));
+    let l = (0.412_221_46 * r + 0.536_332_55 * g + 0.051_445_995 * b).cbrt();
+    let m = (0.211_903_5 * r + 0.680_699_5 * g + 0.107_396_96 * b).cbrt();
+    let s = (0.088_302_46 * r + 0.281_718_85 * g + 0.629_978_7 * b).cbrt();
+
+    let lightness = 0.210_454_26 * l + 0.793_617_8 * m - 0.004_072_047 * s;
+    let a = 1.977_998_5 * l - 2.428_592_2 * m + 0.450_593_7 * s;
+    let b = 0.025_904_037 * l + 0.782_771_77 * m - 0.808_675_77 * s;
+    let chroma = (a * a + b * b).sqrt();
+    let hue = if chroma < 0.000_1 {
+        0.0
+    } else {
+        b.atan2(a).to_degrees().rem_euclid(360.0)
+    };
+    (lightness, chroma, hue)
+}
+
+/// Parses a `#rgb`, `#rgba`, `#rrggbb` or `#rrggbbaa` hex color.
+pub(crate) fn parse_hex_color(text: &str) -> Option<Color> {
+    let hex = text.strip_prefix('#')?;
+    if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+        return None;
+    }
+    let digits: Vec<u8> = match hex.len() {
+        3 | 4 => hex
+            .chars()
+            .map(|c| u8::from_str_radix(&c.to_string().repeat(2), 16).ok())
+            .collect::<Option<_>>()?,
+        6 | 8 => (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    Some(Color {
+        red: digits[0] as f32 / 255.0,
+        green: digits[1] as f32 / 255.0,
+        blue: digits[2] as f32 / 255.0,
+        alpha: digits.get(3).map_or(1.0, |&alpha| alpha as f32 / 255.0),
+    })
+}
+
+/// Parses the arguments of a `rgb()`, `rgba()`, `hsl()` or `hsla()` color, in the legacy
+/// comma separated syntax or the space separated one with an optional `/ alpha`.
+pub(crate) fn parse_color_function(name: &str, arguments: &str) -> Option<Color> {
+    let name = fold_case(name);
+    let is_hsl = match name.as_ref() {
+        "rgb" | "rgba" => false,
+        "hsl" | "hsla" => true,
+        _ => return None,
+    };
+    let arguments = fold_case(arguments);
+    let channels: Vec<&str> = arguments
+        .split(|c: char| c == ',' || c == '/' || c.is_whitespace())
+        .filter(|channel| !channel.is_empty())
+        .collect();
+    if !(3..=4).contains(&channels.len()) {
+        return None;
+    }
+    let alpha = match channels.get(3) {
+        Some(alpha) => parse_channel(alpha, 1.0)?,
+        None => 1.0,
+    };
+    let (red, green, blue) = if is_hsl {
+        let hue = parse_hue(channels[0])?;
+        let saturation = parse_channel(channels[1], 100.0)?;
+        let lightness = parse_channel(channels[2], 100.0)?;
+        hsl_to_rgb(hue, saturation, lightness)
+    } else {
+        (
+            parse_channel(channels[0], 255.0)?,
+            parse_channel(channels[1], 255.0)?,
+            parse_channel(channels[2], 255.0)?,
+        )
+    };
+    Some(Color {
+        red,
+        green,
+        blue,
+        alpha,
+    })
+}
+
+/// Parses a channel, a percentage or a number up to `max`, as a fraction between 0 and 1.
+/// `none` is a missing channel, read as zero.
+fn parse_channel(text: &str, max: f32) -> Option<f32> {
+    let fraction = match text.strip_suffix('%') {
+        _ if text == "none" => 0.0,
+        Some(percentage) => percentage.parse::<f32>().ok()? / 100.0,
+        None => text.parse::<f32>().ok()? / max,
+    };
+    fraction.is_finite().then(|| fraction.clamp(0.0, 1.0))
+}
+
+/// Parses a hue, in degrees unless it has an angle unit, as a fraction of a turn.
+fn parse_hue(text: &str) -> Option<f32> {
+    let (number, degrees_per_unit) = [
+        ("deg", 1.0),
+        ("grad", 0.9),
+        ("rad", 180.0 / std::f32::consts::PI),
+        ("turn", 360.0),
+    ]
+    .iter()
+    .find_map(|(unit, factor)| Some((text.strip_suffix(unit)?, *factor)))
+    .unwrap_or((text, 1.0));
+    let degrees = match number {
+        "none" => 0.0,
+        number => number.parse::<f32>().ok()? * degrees_per_unit,
+    };
+    degrees
+        .is_finite()
+        .then(|| degrees.rem_euclid(360.0) / 360.0)
+}
+
+/// Converts a color from HSL, with every component between 0 and 1, to RGB.
+fn hsl_to_rgb(hue: f32, saturation: f32, lightness: f32) -> (f32, f32, f32) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let channel = |offset: f32| {
+        let k = (offset + hue * 12.0) % 12.0;
+        lightness - chroma / 2.0 * (k - 3.0).min(9.0 - k).clamp(-1.0, 1.0)
+    };
+    (channel(0.0), channel(8.0), channel(4.0))
+}
+
 #[wasm_bindgen]
 pub fn find_document_colors(
-    _text_document: &text_document::TextDocument,
+    text_document: &text_document::TextDocument,
 ) -> Vec<ColorInformationWASM> {
-    todo!("Implement find_document_colors")
+    get_document_colors(&text_document.text)
+        .into_iter()
+        .map(ColorInformationWASM::from)
+        .collect()
+}
+
+/// Computes the ways a color can be written, see `get_color_presentations`.
+///
+/// # Returns
+///
+/// * The `ColorPresentation` list as JSON.
+#[wasm_bindgen]
+pub fn get_color_presentations_wasm(
+    red: f32,
+    green: f32,
+    blue: f32,
+    alpha: f32,
+    range: &RangeWASM,
+) -> String {
+    let color = Color {
+        red,
+        green,
+        blue,
+        alpha,
+    };
+    serde_json::to_string(&get_color_presentations(color, range.into())).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_document_colors_empty() {
+        let colors = get_document_colors("body { margin: 0; }");
+        assert!(colors.is_empty(), "No colors expected");
+    }
+
+    #[test]
+    fn test_get_document_colors_hex() {
+        let code = "a {\n    color: #ff0000;\n    background: #0F08;\n}\n";
+        let colors = get_document_colors(code);
+
+        assert_eq!(colors.len(), 2, "Expected two colors");
+        assert_eq!(colors[0].color.red, 1.0);
+        assert_eq!(colors[0].color.green, 0.0);
+        assert_eq!(colors[0].range.start.line, 1);
+        assert_eq!(colors[0].range.start.character, 11);
+        assert_eq!(colors[0].range.end.character, 18);
+        assert_eq!(colors[1].color.green, 1.0);
+        assert!((colors[1].color.alpha - 0.533).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_get_document_colors_gradient_stops() {
+        let code = "a { background: linear-gradient(to right, red, #00f 50%, Lime), -webkit-repeating-radial-gradient(navy 10%, white); color: red; }";
+        let colors = get_document_colors(code);
+        let stops: Vec<_> = colors
+            .iter()
+            .map(|color| {
+                (
+                    color.range.start.character as usize,
+                    color.range.end.character as usize,
+                )
+            })
+            .map(|(start, end)| &code[start..end])
+            .collect();
+        assert_eq!(stops, vec!["red", "#00f", "Lime", "navy", "white", "red"]);
+        assert_eq!(colors[2].color.green, 1.0);
+    }
+
+    #[test]
+    fn test_get_document_colors_functions_and_names() {
+        let code = "a { color: rgb(255 0 0 / 50%); border: 1px solid Teal; --accent: hsla(120, 100%, 25%, .5); fill: rgb(var(--r) 0 0); animation-name: red; background: linear-gradient(hsl(0.5turn 100% 50%), red); }";
+        let colors = get_document_colors(code);
+        let texts: Vec<_> = colors
+            .iter()
+            .map(|color| {
+                &code[color.range.start.character as usize..color.range.end.character as usize]
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                "rgb(255 0 0 / 50%)",
+                "Teal",
+                "hsla(120, 100%, 25%, .5)",
+                "hsl(0.5turn 100% 50%)",
+                "red"
+            ]
+        );
+        assert_eq!(colors[0].color.red, 1.0);
+        assert_eq!(colors[0].color.alpha, 0.5);
+        assert!((colors[1].color.blue - 128.0 / 255.0).abs() < 0.01);
+        assert_eq!(colors[2].color.green, 0.5);
+        assert_eq!(colors[2].color.red, 0.0);
+        assert_eq!(colors[3].color.blue, 1.0);
+        assert_eq!(colors[3].color.green, 1.0);
+    }
+
+    #[test]
+    fn test_get_color_presentations() {
+        let code = "a { background: linear-gradient(red, blue); }";
+        let stop = get_document_colors(code)[1].clone();
+        let presentations = get_color_presentations(stop.color, stop.range);
+        let labels: Vec<_> = presentations
+            .iter()
+            .map(|presentation| presentation.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "#0000ff",
+                "blue",
+                "rgb(0 0 255)",
+                "hsl(240 100% 50%)",
+                "oklch(45.2% 0.313 264.05)"
+            ]
+        );
+        let edit = presentations[0].text_edit.as_ref().unwrap();
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (37, 41)
+        );
+
+        let translucent = Color {
+            red: 1.0,
+            green: 0.0,
+            blue: 0.0,
+            alpha: 0.5,
+        };
+        let presentations = get_color_presentations(translucent, stop.range);
+        let labels: Vec<_> = presentations
+            .iter()
+            .map(|presentation| presentation.label.as_str())
+            .collect();
+        assert_eq!(
+            labels,
+            vec![
+                "#ff000080",
+                "rgb(255 0 0 / 0.5)",
+                "hsl(0 100% 50% / 0.5)",
+                "oklch(62.8% 0.258 29.23 / 0.5)"
+            ]
+        );
+
+        let gray = Color {
+            red: 0.5,
+            green: 0.5,
+            blue: 0.5,
+            alpha: 1.0,
+        };
+        assert_eq!(
+            get_color_presentations(gray, stop.range)[3].label,
+            "hsl(0 0% 50%)"
+        );
+    }
+
+    #[test]
+    fn test_parse_hex_color_invalid() {
+        assert!(parse_hex_color("#ff").is_none());
+        assert!(parse_hex_color("#gggggg").is_none());
+        assert!(parse_color_function("rgb", "1 2").is_none());
+        assert!(parse_color_function("lab", "50% 40 59").is_none());
+    }
 }
@@ -0,0 +1,1881 @@
+use crate::case_folding::{eq_ignore_case, fold_case, match_case, starts_with_ignore_case};
+use crate::data::at_rules::BUILTIN_AT_RULES;
+use crate::data::colors::{is_color_property, NAMED_COLORS, SYSTEM_COLORS};
+use crate::data::media_features::{media_feature, MediaFeatureType, MEDIA_FEATURES};
+use crate::data::properties::{CssData, PropertyStatus};
+use crate::data::pseudos::{BUILTIN_PSEUDO_CLASSES, BUILTIN_PSEUDO_ELEMENTS};
+use crate::data::values::CSS_WIDE_KEYWORDS;
+use crate::dialect::Dialect;
+use crate::import_graph::find_imports;
+use crate::layers::find_layers;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::media_queries::{parse_media_query_list, MediaFeatureSyntax};
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use crate::parser::{parse_css, split_components, split_list};
+use crate::rules::images::RESOLUTION_UNITS;
+use crate::selectors::find_selector_names;
+use crate::shorthands::{
+    is_time, parse_animation, DIRECTION_KEYWORDS, EASING_FUNCTIONS, EASING_KEYWORDS,
+    FILL_MODE_KEYWORDS, PLAY_STATE_KEYWORDS,
+};
+use crate::text_document::TextDocument;
+use crate::types::{PositionWASM, RangeWASM};
+use biome_css_parser::CssParse;
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{
+    CompletionItem, CompletionItemKind, CompletionItemTag, CompletionTextEdit, Documentation,
+    InsertTextFormat, Position, TextEdit,
+};
+use std::collections::HashMap;
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// The properties suggested first in `transition`, as the most commonly animated ones.
+const ANIMATABLE_PROPERTIES: &[&str] = &[
+    "all",
+    "opacity",
+    "transform",
+    "translate",
+    "scale",
+    "rotate",
+    "color",
+    "background-color",
+    "border-color",
+    "box-shadow",
+    "filter",
+    "width",
+    "height",
+    "inset",
+    "margin",
+    "padding",
+    "visibility",
+];
+
+/// The declaration templates suggested where a property name is expected, as snippets with
+/// a placeholder per component of the value.
+const DECLARATION_SNIPPETS: &[(&str, &str)] = &[
+    (
+        "animation",
+        "animation: ${1:name} ${2:duration} ${3:timing-function};",
+    ),
+    ("border", "border: ${1:width} ${2:style} ${3:color};"),
+    (
+        "box-shadow",
+        "box-shadow: ${1:offset-x} ${2:offset-y} ${3:blur} ${4:color};",
+    ),
+    (
+        "grid-template-columns",
+        "grid-template-columns: repeat(${1:count}, ${2:1fr});",
+    ),
+    (
+        "transition",
+        "transition: ${1:property} ${2:duration} ${3:timing-function};",
+    ),
+];
+
+/// The durations suggested in `transition` and `animation`.
+const DURATIONS: &[&str] = &["150ms", "200ms", "300ms", "500ms", "1s"];
+
+/// Represents a completion item for the CSS code.
+#[wasm_bindgen(js_name = CompletionItem)]
+pub struct CompletionItemWASM(CompletionItem);
+
+#[wasm_bindgen(js_class = CompletionItem)]
+impl CompletionItemWASM {
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> String {
+        self.0.label.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> Option<String> {
+        self.0.kind.map(|kind| match kind {
+            CompletionItemKind::CLASS => "class".to_string(),
+            CompletionItemKind::COLOR => "color".to_string(),
+            CompletionItemKind::FUNCTION => "function".to_string(),
+            CompletionItemKind::KEYWORD => "keyword".to_string(),
+            CompletionItemKind::MODULE => "module".to_string(),
+            CompletionItemKind::PROPERTY => "property".to_string(),
+            CompletionItemKind::SNIPPET => "snippet".to_string(),
+            CompletionItemKind::UNIT => "unit".to_string(),
+            _ => "value".to_string(),
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn detail(&self) -> Option<String> {
+        self.0.detail.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn documentation(&self) -> Option<String> {
+        match &self.0.documentation {
+            Some(Documentation::String(documentation)) => Some(documentation.clone()),
+            Some(Documentation::MarkupContent(markup)) => Some(markup.value.clone()),
+            None => None,
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn insert_text(&self) -> String {
+        match &self.0.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => edit.new_text.clone(),
+            _ => self.0.label.clone(),
+        }
+    }
+
+    /// Whether the insert text is a snippet, with tab stops like `${1:name}`.
+    #[wasm_bindgen(getter)]
+    pub fn is_snippet(&self) -> bool {
+        self.0.insert_text_format == Some(InsertTextFormat::SNIPPET)
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> Option<RangeWASM> {
+        match &self.0.text_edit {
+            Some(CompletionTextEdit::Edit(edit)) => Some(edit.range.into()),
+            _ => None,
+        }
+    }
+}
+
+impl From<CompletionItem> for CompletionItemWASM {
+    fn from(completion_item: CompletionItem) -> Self {
+        CompletionItemWASM(completion_item)
+    }
+}
+
+/// Provides completions on top of the built-in ones, like design tokens from a host
+/// extension.
+pub trait CompletionProvider {
+    /// Computes additional completion items at a position of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `TextDocument` to complete.
+    /// * `position` - The `Position` of the cursor.
+    ///
+    /// # Returns
+    ///
+    /// * A vector of `CompletionItem`, merged with the built-in ones.
+    fn provide_completions(
+        &self,
+        document: &TextDocument,
+        position: Position,
+    ) -> Vec<CompletionItem>;
+}
+
+/// Computes the completion items at a position of a document, merged with the items of
+/// additional providers.
+///
+/// Provider items with the label of a built-in item, or of an item from an earlier provider,
+/// are dropped.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+/// * `data` - The `CssData` of the known properties.
+/// * `providers` - The additional `CompletionProvider`s, in priority order.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, the built-in ones first.
+pub fn get_completions_with_providers(
+    document: &TextDocument,
+    position: Position,
+    data: &CssData,
+    providers: &[Box<dyn CompletionProvider>],
+) -> Vec<CompletionItem> {
+    let items = get_completions_with_data(document, position, data);
+    add_provider_completions(items, document, position, providers)
+}
+
+/// Computes the completion items at a position of a document from an existing parse of it,
+/// see `get_completions_with_providers`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `parse` - The `CssParse` of the text of `document`, or `None` to parse it only when
+///   it is needed.
+/// * `position` - The `Position` of the cursor.
+/// * `data` - The `CssData` of the known properties.
+/// * `providers` - The additional `CompletionProvider`s, in priority order.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, the built-in ones first.
+pub fn get_completions_with_parse(
+    document: &TextDocument,
+    parse: Option<CssParse>,
+    position: Position,
+    data: &CssData,
+    providers: &[Box<dyn CompletionProvider>],
+    encoding: PositionEncoding,
+) -> Vec<CompletionItem> {
+    let items = find_completions(document, parse.as_ref(), position, data, encoding);
+    add_provider_completions(items, document, position, providers)
+}
+
+/// Adds the items of the providers to the built-in completion items, dropping those with
+/// the label of an item already there.
+fn add_provider_completions(
+    mut items: Vec<CompletionItem>,
+    document: &TextDocument,
+    position: Position,
+    providers: &[Box<dyn CompletionProvider>],
+) -> Vec<CompletionItem> {
+    for provider in providers {
+        for item in provider.provide_completions(document, position) {
+            if !items.iter().any(|known| known.label == item.label) {
+                items.push(item);
+            }
+        }
+    }
+    items
+}
+
+/// Options for computing completion items.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CompletionOptions {
+    /// The maximum number of items, the first ones being kept.
+    pub max_items: Option<usize>,
+    /// Whether the client supports snippets, its `completionItem.snippetSupport`. Without
+    /// it, the items are inserted as plain text, see `strip_snippets`.
+    pub snippet_support: bool,
+}
+
+/// Replaces the snippets of completion items by plain text, for clients without snippet
+/// support: placeholders are replaced by their default text, choices by their first one, and
+/// tab stops are removed. At-rules only insert their name and a space, like `@media `, as
+/// the default text of their prelude, like `value`, would have to be rewritten. Snippet
+/// items, like declaration templates, only make sense with their tab stops and are dropped.
+///
+/// # Arguments
+///
+/// * `items` - The `CompletionItem`s, some of them possibly snippets.
+///
+/// # Returns
+///
+/// * The items without snippets, in the same order.
+pub fn strip_snippets(items: Vec<CompletionItem>) -> Vec<CompletionItem> {
+    items
+        .into_iter()
+        .filter(|item| {
+            item.insert_text_format != Some(InsertTextFormat::SNIPPET)
+                || item.kind != Some(CompletionItemKind::SNIPPET)
+        })
+        .map(|mut item| {
+            if item.insert_text_format == Some(InsertTextFormat::SNIPPET) {
+                item.insert_text_format = Some(InsertTextFormat::PLAIN_TEXT);
+                let is_at_rule =
+                    item.kind == Some(CompletionItemKind::KEYWORD) && item.label.starts_with('@');
+                let to_text = |snippet: &str| match is_at_rule {
+                    true => format!("{} ", item.label),
+                    false => snippet_to_text(snippet),
+                };
+                item.insert_text = item.insert_text.as_deref().map(to_text);
+                if let Some(CompletionTextEdit::Edit(edit)) = &mut item.text_edit {
+                    edit.new_text = to_text(&edit.new_text);
+                }
+            }
+            item
+        })
+        .collect()
+}
+
+/// Converts a snippet to the text it inserts without editing its placeholders, like
+/// `:nth-child(${1:2n+1})` to `:nth-child(2n+1)`.
+fn snippet_to_text(snippet: &str) -> String {
+    let mut text = String::with_capacity(snippet.len());
+    let mut chars = snippet.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => text.extend(chars.next()),
+            '$' if chars.peek().is_some_and(char::is_ascii_digit) => {
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+            }
+            '$' if chars.peek() == Some(&'{') => {
+                chars.next();
+                while chars.next_if(char::is_ascii_digit).is_some() {}
+                match chars.next() {
+                    // A choice, like `${1|ltr,rtl|}`: its first option
+                    Some('|') => {
+                        let mut first = true;
+                        for c in chars.by_ref() {
+                            match c {
+                                ',' | '|' => first = false,
+                                '}' => break,
+                                _ if first => text.push(c),
+                                _ => {}
+                            }
+                        }
+                    }
+                    // A placeholder, like `${1:name}`: its default text, which may nest
+                    // other placeholders, up to its closing brace
+                    Some(':') => {
+                        let inner: String = {
+                            let mut depth = 0usize;
+                            let mut inner = String::new();
+                            while let Some(c) = chars.next() {
+                                match c {
+                                    '\\' => {
+                                        inner.push(c);
+                                        inner.extend(chars.next());
+                                        continue;
+                                    }
+                                    '{' => depth += 1,
+                                    '}' if depth == 0 => break,
+                                    '}' => depth -= 1,
+                                    _ => {}
+                                }
+                                inner.push(c);
+                            }
+                            inner
+                        };
+                        text.push_str(&snippet_to_text(&inner));
+                    }
+                    _ => {}
+                }
+            }
+            _ => text.push(c),
+        }
+    }
+    text
+}
+
+/// Where the cursor is, as far as completion is concerned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum CompletionContext {
+    /// In the value of a declaration starting at `value_start`, on the word spanning
+    /// `word_range`.
+    Value {
+        property: String,
+        value_start: usize,
+        word_range: Range<usize>,
+    },
+    /// Where a property name is expected, on the word spanning `word_range`, like `col` in
+    /// `a { col` before its colon is typed.
+    PropertyName { word_range: Range<usize> },
+    /// In the `layer()` of an `@import`, on the layer name spanning `word_range`, like
+    /// `base.re` in `@import url(a.css) layer(base.re`.
+    ImportLayer { word_range: Range<usize> },
+    /// Where an at-rule is being typed, on its name spanning `word_range`, with its `@`, like
+    /// `@me` in `@me`. `top_level` tells whether the statement isn't nested in a block.
+    AtRule {
+        word_range: Range<usize>,
+        top_level: bool,
+    },
+    /// In a selector, on the pseudo-class or pseudo-element spanning `word_range`, with its
+    /// colons, like `:ho` in `a:ho`.
+    Pseudo { word_range: Range<usize> },
+    /// Where a selector is expected, at the top level or in a conditional group rule, on the
+    /// compound selector spanning `word_range`, like `a.bt` in `nav > a.bt`.
+    Selector { word_range: Range<usize> },
+    /// In the prelude of `@media`, on the media feature name spanning `word_range`, like
+    /// `min-wi` in `@media (min-wi`.
+    MediaFeature { word_range: Range<usize> },
+    /// In the prelude of `@media`, on the value spanning `word_range` of a media feature,
+    /// like `da` in `@media (prefers-color-scheme: da`.
+    MediaFeatureValue {
+        feature: String,
+        word_range: Range<usize>,
+    },
+}
+
+/// Computes the completion items at a position of a document, with the built-in
+/// properties and the default `CompletionOptions`, so without snippets, see
+/// `get_completions_with_options`.
+///
+/// Where a property name is expected in a block, the known properties starting with the
+/// word being typed are suggested, standard properties first and obsolete ones last.
+///
+/// In the value of a color property, the named colors and system colors matching the word
+/// being typed are suggested, with their hex value as documentation so editors can show a
+/// swatch.
+///
+/// In the `layer()` of an `@import`, the layers declared in the document are suggested, see
+/// `get_layer_completions` for the layers of other stylesheets.
+///
+/// Where an at-rule is being typed, the at-rules of the dialect of the document are
+/// suggested, with their documentation, as snippets inserting their prelude and block
+/// when the client supports them.
+///
+/// After `:` or `::` in a selector, the pseudo-classes and pseudo-elements are suggested,
+/// with their documentation and browser support.
+///
+/// Where a selector is expected, at the top level or in a conditional group rule like
+/// `@media`, the class names, IDs and element and class combinations used elsewhere in the
+/// document are suggested, most used first, see
+/// `get_selector_completions` for the selectors of other stylesheets.
+///
+/// In the prelude of `@media`, the media features are suggested, with their `min-` and
+/// `max-` prefixes for range features, and the keywords of discrete features after their
+/// colon.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, empty if there is nothing to suggest.
+pub fn get_completions(document: &TextDocument, position: Position) -> Vec<CompletionItem> {
+    get_completions_with_options(document, position, &CompletionOptions::default())
+}
+
+/// Computes the completion items at a position of a document, with the built-in
+/// properties, see `get_completions`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+/// * `options` - The `CompletionOptions`, like whether the client supports snippets.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, empty if there is nothing to suggest.
+pub fn get_completions_with_options(
+    document: &TextDocument,
+    position: Position,
+    options: &CompletionOptions,
+) -> Vec<CompletionItem> {
+    let mut items = get_completions_with_data(document, position, CssData::builtin());
+    if !options.snippet_support {
+        items = strip_snippets(items);
+    }
+    if let Some(max_items) = options.max_items {
+        items.truncate(max_items);
+    }
+    items
+}
+
+/// Computes the completion items at a position of a document, see `get_completions`. In
+/// values, the keywords of the property known to `data` are suggested too. Snippets are
+/// always included, see `strip_snippets` for clients without snippet support.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+/// * `data` - The `CssData` of the known properties.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, empty if there is nothing to suggest.
+pub fn get_completions_with_data(
+    document: &TextDocument,
+    position: Position,
+    data: &CssData,
+) -> Vec<CompletionItem> {
+    find_completions(document, None, position, data, PositionEncoding::Utf16)
+}
+
+/// Computes the completion items at a position of a document, see
+/// `get_completions_with_data`, parsing the document only when it is needed if `parse` is
+/// `None`.
+fn find_completions(
+    document: &TextDocument,
+    parse: Option<&CssParse>,
+    position: Position,
+    data: &CssData,
+    encoding: PositionEncoding,
+) -> Vec<CompletionItem> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+
+    match find_completion_context(source, offset) {
+        Some(CompletionContext::Value { value_start, .. })
+            if in_image_set(&source[value_start..offset]) =>
+        {
+            resolution_completions(source, offset, &line_index)
+        }
+        Some(CompletionContext::Value {
+            property,
+            value_start,
+            word_range,
+        }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            let preceding = &source[value_start..word_range.start];
+            let word = &source[word_range];
+            let mut items = if is_color_property(&property) {
+                color_completions(word, range)
+            } else {
+                shorthand_completions(source, parse, &property, preceding, word, range)
+            };
+            for item in keyword_completions(data, &property, preceding, word, range) {
+                if !items.iter().any(|known| known.label == item.label) {
+                    items.push(item);
+                }
+            }
+            // CSS-wide keywords are only valid as the whole value of a known property
+            if preceding.trim().is_empty() && !items.is_empty() {
+                items.extend(css_wide_keyword_completions(word, range));
+            }
+            items
+        }
+        Some(CompletionContext::PropertyName { word_range }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            let word = &source[word_range];
+            let mut items = property_completions(data, word, range);
+            items.extend(declaration_snippet_completions(word, range));
+            items
+        }
+        Some(CompletionContext::ImportLayer { word_range }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            // Without the name being typed, which declares a layer of its own
+            let others = [&source[..word_range.start], &source[word_range.end..]].concat();
+            layer_completions(&find_layers(&others), &source[word_range], range)
+        }
+        Some(CompletionContext::AtRule {
+            word_range,
+            top_level,
+        }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            let dialect = Dialect::from_language_id(&document.language_id);
+            at_rule_completions(&source[word_range], top_level, dialect, range)
+        }
+        Some(CompletionContext::Pseudo { word_range }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            pseudo_completions(&source[word_range], range)
+        }
+        Some(CompletionContext::Selector { word_range }) => {
+            selector_completions(source, word_range, &[], &line_index)
+        }
+        Some(CompletionContext::MediaFeature { word_range }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            media_feature_completions(&source[word_range], range)
+        }
+        Some(CompletionContext::MediaFeatureValue {
+            feature,
+            word_range,
+        }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            media_feature_value_completions(&feature, &source[word_range], range)
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Computes the completion items for the layers of other stylesheets, like the indexed
+/// stylesheets of the workspace, in the `layer()` of an `@import`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+/// * `layers` - The full names of the known layers, like `base.reset`.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, empty outside of the `layer()` of an `@import`.
+pub fn get_layer_completions(
+    document: &TextDocument,
+    position: Position,
+    layers: &[String],
+    encoding: PositionEncoding,
+) -> Vec<CompletionItem> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+    match find_completion_context(source, offset) {
+        Some(CompletionContext::ImportLayer { word_range }) => {
+            let range = line_index.range(source, word_range.start, word_range.end);
+            layer_completions(layers, &source[word_range], range)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Computes the completion items for the selectors used in the document and in other
+/// stylesheets, like the indexed stylesheets of the workspace, where a selector is expected
+/// at the top level. Their uses are added up to rank them.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to complete.
+/// * `position` - The `Position` of the cursor.
+/// * `selectors` - The selectors of the other stylesheets with their number of uses, see
+///   `find_selector_names`.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `CompletionItem`, empty outside of a selector.
+pub fn get_selector_completions(
+    document: &TextDocument,
+    position: Position,
+    selectors: &[(String, usize)],
+    encoding: PositionEncoding,
+) -> Vec<CompletionItem> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+    match find_completion_context(source, offset) {
+        Some(CompletionContext::Selector { word_range }) => {
+            selector_completions(source, word_range, selectors, &line_index)
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// Suggests the selectors starting with the typed compound selector, most used first, from
+/// the document without the selector being typed, and from other stylesheets.
+fn selector_completions(
+    source: &str,
+    word_range: Range<usize>,
+    others: &[(String, usize)],
+    line_index: &LineIndex,
+) -> Vec<CompletionItem> {
+    let range = line_index.range(source, word_range.start, word_range.end);
+    let word = &source[word_range.clone()];
+    let mut total: HashMap<String, usize> =
+        find_selector_names(&[&source[..word_range.start], &source[word_range.end..]].concat())
+            .into_iter()
+            .collect();
+    for (name, uses) in others {
+        *total.entry(name.clone()).or_default() += uses;
+    }
+    let mut selectors: Vec<(String, usize)> = total.into_iter().collect();
+    selectors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    selectors
+        .into_iter()
+        .filter(|(name, _)| name.starts_with(word) && name != word)
+        .enumerate()
+        .map(|(idx, (name, uses))| CompletionItem {
+            label: name.clone(),
+            kind: Some(CompletionItemKind::CLASS),
+            detail: Some(match uses {
+                1 => "Used once".to_string(),
+                uses => format!("Used {uses} times"),
+            }),
+            sort_text: Some(format!("{idx:04}")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: name,
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the at-rules of the dialect starting with the typed name, as snippets inserting
+/// their prelude and block. The at-rules only valid at the top level, like `@import`, aren't
+/// suggested in blocks.
+fn at_rule_completions(
+    word: &str,
+    top_level: bool,
+    dialect: Dialect,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    BUILTIN_AT_RULES
+        .iter()
+        .filter(|at_rule| at_rule.supports(dialect) && (top_level || !at_rule.top_level))
+        .filter(|at_rule| starts_with_ignore_case(at_rule.name, word))
+        .map(|at_rule| CompletionItem {
+            label: at_rule.name.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            documentation: Some(Documentation::String(at_rule.description.to_string())),
+            insert_text_format: Some(InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: at_rule.snippet.to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the pseudo-classes and pseudo-elements starting with the typed name, documented
+/// with their browser support. After `::`, only pseudo-elements are suggested, and the
+/// functional ones are inserted as snippets with their argument.
+fn pseudo_completions(word: &str, range: lsp_types::Range) -> Vec<CompletionItem> {
+    let name = word.trim_start_matches(':');
+    let pseudos = if word.starts_with("::") {
+        &[BUILTIN_PSEUDO_ELEMENTS][..]
+    } else {
+        &[BUILTIN_PSEUDO_CLASSES, BUILTIN_PSEUDO_ELEMENTS][..]
+    };
+    pseudos
+        .iter()
+        .flat_map(|pseudos| pseudos.iter())
+        .filter(|pseudo| starts_with_ignore_case(pseudo.name.trim_start_matches(':'), name))
+        .map(|pseudo| CompletionItem {
+            label: pseudo.name.to_string(),
+            kind: Some(match pseudo.snippet {
+                Some(_) => CompletionItemKind::FUNCTION,
+                None => CompletionItemKind::KEYWORD,
+            }),
+            documentation: Some(Documentation::String(format!(
+                "{}\n\nBrowser support: {}",
+                pseudo.description, pseudo.browsers
+            ))),
+            insert_text_format: pseudo.snippet.map(|_| InsertTextFormat::SNIPPET),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: pseudo.snippet.unwrap_or(pseudo.name).to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the media features starting with the typed name, documented, with the `min-`
+/// and `max-` prefixed versions of range features. Deprecated features come last.
+fn media_feature_completions(word: &str, range: lsp_types::Range) -> Vec<CompletionItem> {
+    MEDIA_FEATURES
+        .iter()
+        .flat_map(|feature| {
+            let prefixes: &[&str] = if feature.range {
+                &["", "min-", "max-"]
+            } else {
+                &[""]
+            };
+            prefixes
+                .iter()
+                .map(move |prefix| (format!("{prefix}{}", feature.name), feature))
+        })
+        .filter(|(name, _)| starts_with_ignore_case(name, word))
+        .map(|(name, feature)| {
+            let rank = if feature.replacement.is_some() {
+                'b'
+            } else {
+                'a'
+            };
+            CompletionItem {
+                label: name.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                documentation: Some(Documentation::String(feature.description.to_string())),
+                sort_text: Some(format!("{rank}{name}")),
+                tags: feature
+                    .replacement
+                    .map(|_| vec![CompletionItemTag::DEPRECATED]),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: name,
+                })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Suggests the keywords of a discrete media feature starting with the typed word.
+fn media_feature_value_completions(
+    feature: &str,
+    word: &str,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    let Some(MediaFeatureType::Keywords(keywords)) =
+        media_feature(feature).map(|(feature, _)| feature.value_type)
+    else {
+        return Vec::new();
+    };
+    keywords
+        .iter()
+        .filter(|keyword| starts_with_ignore_case(keyword, word))
+        .map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::VALUE),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: keyword.to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the layers starting with the typed name, in the order they are given.
+fn layer_completions(
+    layers: &[String],
+    word: &str,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    layers
+        .iter()
+        .filter(|layer| layer.starts_with(word))
+        .enumerate()
+        .map(|(idx, layer)| CompletionItem {
+            label: layer.clone(),
+            kind: Some(CompletionItemKind::MODULE),
+            detail: Some("Cascade layer".to_string()),
+            sort_text: Some(format!("{idx:04}")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: layer.clone(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the properties starting with the typed word, standard ones first, inserting
+/// the colon after the name.
+fn property_completions(
+    data: &CssData,
+    word: &str,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    data.properties()
+        .filter(|property| starts_with_ignore_case(&property.name, word))
+        .map(|property| {
+            let rank = match property.status {
+                PropertyStatus::Standard => 'a',
+                PropertyStatus::Experimental => 'b',
+                PropertyStatus::Nonstandard => 'c',
+                PropertyStatus::Obsolete => 'd',
+            };
+            CompletionItem {
+                label: property.name.clone(),
+                kind: Some(CompletionItemKind::PROPERTY),
+                detail: property.syntax.clone(),
+                documentation: property
+                    .description
+                    .as_ref()
+                    .map(|description| Documentation::String(description.clone())),
+                sort_text: Some(format!("{rank}{}", property.name)),
+                tags: (property.status == PropertyStatus::Obsolete)
+                    .then(|| vec![CompletionItemTag::DEPRECATED]),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: format!("{}: ", property.name),
+                })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Suggests the declaration templates of the properties starting with the typed word, like
+/// `transition: property duration timing-function`.
+fn declaration_snippet_completions(word: &str, range: lsp_types::Range) -> Vec<CompletionItem> {
+    if word.is_empty() {
+        return Vec::new();
+    }
+    DECLARATION_SNIPPETS
+        .iter()
+        .filter(|(property, _)| starts_with_ignore_case(property, word))
+        .map(|(property, snippet)| {
+            let text = snippet_to_text(snippet);
+            CompletionItem {
+                label: text.trim_end_matches(';').to_string(),
+                kind: Some(CompletionItemKind::SNIPPET),
+                sort_text: Some(format!("e{property}")),
+                insert_text_format: Some(InsertTextFormat::SNIPPET),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: snippet.to_string(),
+                })),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+/// Suggests the keywords of a property starting with the typed word, except those already
+/// in the value.
+fn keyword_completions(
+    data: &CssData,
+    property: &str,
+    preceding: &str,
+    word: &str,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    let Some(property) = data.property(property) else {
+        return Vec::new();
+    };
+    let used: Vec<&str> = preceding
+        .split(|c: char| c.is_whitespace() || c == ',' || c == '/')
+        .collect();
+    property
+        .values
+        .iter()
+        .filter(|value| starts_with_ignore_case(&value.name, word))
+        .filter(|value| !used.iter().any(|used| eq_ignore_case(used, &value.name)))
+        .map(|value| CompletionItem {
+            label: value.name.clone(),
+            kind: Some(CompletionItemKind::VALUE),
+            documentation: value
+                .description
+                .as_ref()
+                .map(|description| Documentation::String(description.clone())),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: value.name.clone(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the components of a `transition` or `animation` the cursor can still be in,
+/// from the components already written in the current comma separated item: animatable
+/// properties first, then durations, easings, the keyframes of the document, and the
+/// other keywords of an animation.
+fn shorthand_completions(
+    source: &str,
+    parse: Option<&CssParse>,
+    property: &str,
+    preceding: &str,
+    word: &str,
+    range: lsp_types::Range,
+) -> Vec<CompletionItem> {
+    let property = fold_case(property);
+    let property = property.strip_prefix("-webkit-").unwrap_or(&property);
+    let item = split_list(preceding, 0).pop().map_or("", |(item, _)| item);
+    // The slots, with their rank and suggestions, still free in the current item
+    let mut slots: Vec<(&str, CompletionItemKind, Vec<String>)> = Vec::new();
+    let to_strings = |values: &[&str]| values.iter().map(|value| value.to_string()).collect();
+    match property {
+        "transition" => {
+            let components: Vec<String> = split_components(item, 0)
+                .into_iter()
+                .map(|(component, _)| fold_case(component).into_owned())
+                .collect();
+            let times = components.iter().filter(|c| is_time(c)).count();
+            let has_easing = components.iter().any(|c| is_easing(c));
+            let has_property = components
+                .iter()
+                .any(|c| !is_time(c) && !is_easing(c) && c != "allow-discrete");
+            if !has_property {
+                slots.push((
+                    "a",
+                    CompletionItemKind::PROPERTY,
+                    to_strings(ANIMATABLE_PROPERTIES),
+                ));
+            }
+            if times < 2 {
+                slots.push(("b", CompletionItemKind::VALUE, to_strings(DURATIONS)));
+            }
+            if !has_easing {
+                slots.push(("c", CompletionItemKind::VALUE, to_strings(EASING_KEYWORDS)));
+            }
+        }
+        "animation" => {
+            let Ok(Some(layers)) = parse_animation(item, 0) else {
+                return Vec::new();
+            };
+            let layer = layers.last().cloned().unwrap_or_default();
+            if layer.delay.is_none() {
+                slots.push(("b", CompletionItemKind::VALUE, to_strings(DURATIONS)));
+            }
+            if layer.timing_function.is_none() {
+                slots.push(("c", CompletionItemKind::VALUE, to_strings(EASING_KEYWORDS)));
+            }
+            if layer.name.is_none() {
+                slots.push((
+                    "d",
+                    CompletionItemKind::REFERENCE,
+                    keyframes_names(source, parse),
+                ));
+            }
+            let keywords = [
+                (layer.iteration_count.is_none(), &["infinite"][..]),
+                (layer.direction.is_none(), DIRECTION_KEYWORDS),
+                (layer.fill_mode.is_none(), FILL_MODE_KEYWORDS),
+                (layer.play_state.is_none(), PLAY_STATE_KEYWORDS),
+            ];
+            let mut others: Vec<String> = Vec::new();
+            for (free, values) in keywords {
+                for value in values.iter().filter(|_| free) {
+                    if !others.iter().any(|other| other == value) {
+                        others.push(value.to_string());
+                    }
+                }
+            }
+            slots.push(("e", CompletionItemKind::VALUE, others));
+        }
+        _ => return Vec::new(),
+    }
+
+    let mut items: Vec<CompletionItem> = Vec::new();
+    for (rank, kind, values) in slots {
+        for (idx, value) in values.into_iter().enumerate() {
+            if !starts_with_ignore_case(&value, word)
+                || items.iter().any(|item| item.label == value)
+            {
+                continue;
+            }
+            items.push(CompletionItem {
+                label: value.clone(),
+                kind: Some(kind),
+                sort_text: Some(format!("{rank}{idx:03}")),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                    range,
+                    new_text: value,
+                })),
+                ..Default::default()
+            });
+        }
+    }
+    items
+}
+
+/// Whether a lowercase component is an easing function, like `ease-in` or `steps(4)`.
+fn is_easing(component: &str) -> bool {
+    let function = component.split_once('(').map(|(name, _)| name);
+    EASING_KEYWORDS.contains(&component)
+        || function.is_some_and(|name| EASING_FUNCTIONS.contains(&name))
+}
+
+/// Returns the names of the `@keyframes` of a document, in source order. Quoted names
+/// aren't suggested, as they are rarely meant to be referenced.
+fn keyframes_names(source: &str, parse: Option<&CssParse>) -> Vec<String> {
+    let root = match parse {
+        Some(parse) => parse.syntax(),
+        None => parse_css(source).syntax(),
+    };
+    root.descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_KEYFRAMES_AT_RULE)
+        .filter_map(|at_rule| {
+            let name = at_rule
+                .children()
+                .find(|node| node.kind() == CssSyntaxKind::CSS_CUSTOM_IDENTIFIER)?;
+            Some(name.text_trimmed().to_string())
+        })
+        .collect()
+}
+
+/// Suggests the CSS-wide keywords starting with the typed word, after the other items.
+fn css_wide_keyword_completions(word: &str, range: lsp_types::Range) -> Vec<CompletionItem> {
+    CSS_WIDE_KEYWORDS
+        .iter()
+        .filter(|keyword| starts_with_ignore_case(keyword, word))
+        .map(|keyword| CompletionItem {
+            label: keyword.to_string(),
+            kind: Some(CompletionItemKind::KEYWORD),
+            detail: Some("CSS-wide keyword".to_string()),
+            sort_text: Some(format!("z{keyword}")),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: keyword.to_string(),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Returns whether the end of a value is directly in the arguments of `image-set()`.
+fn in_image_set(value: &str) -> bool {
+    let mut open: Vec<&str> = Vec::new();
+    for (idx, c) in value.char_indices() {
+        match c {
+            '(' => {
+                let name_start = value[..idx]
+                    .rfind(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_'))
+                    .map_or(0, |start| start + 1);
+                open.push(&value[name_start..idx]);
+            }
+            ')' => {
+                open.pop();
+            }
+            _ => {}
+        }
+    }
+    open.last().is_some_and(|name| {
+        eq_ignore_case(name, "image-set") || eq_ignore_case(name, "-webkit-image-set")
+    })
+}
+
+/// Suggests the resolution units after the number being typed, like `2x` and `2dppx`.
+fn resolution_completions(
+    source: &str,
+    offset: usize,
+    line_index: &LineIndex,
+) -> Vec<CompletionItem> {
+    let word_start = source[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| c.is_ascii_alphanumeric() || c == '.')
+        .last()
+        .map_or(offset, |(idx, _)| idx);
+    let word = &source[word_start..offset];
+    let number_len = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(word.len());
+    let (number, unit) = word.split_at(number_len);
+    if number.is_empty() {
+        return Vec::new();
+    }
+
+    let range = line_index.range(source, word_start, offset);
+    RESOLUTION_UNITS
+        .iter()
+        .filter(|(known, _, _)| starts_with_ignore_case(known, unit))
+        .enumerate()
+        .map(|(idx, (known, _, description))| CompletionItem {
+            label: format!("{number}{known}"),
+            kind: Some(CompletionItemKind::UNIT),
+            detail: Some(description.to_string()),
+            sort_text: Some(idx.to_string()),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: format!("{number}{known}"),
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Suggests the named colors, `transparent`, `currentcolor` and system colors starting with
+/// the typed word, keeping the case of the word for named colors.
+fn color_completions(word: &str, range: lsp_types::Range) -> Vec<CompletionItem> {
+    let named = NAMED_COLORS
+        .iter()
+        .chain([("transparent", "#00000000")].iter())
+        .map(|&(name, hex)| (match_case(name, word), Some(hex), "a"));
+    let current_color = std::iter::once((match_case("currentcolor", word), None, "b"));
+    let system = SYSTEM_COLORS
+        .iter()
+        .map(|&(name, hex)| (name.to_string(), Some(hex), "c"));
+
+    named
+        .chain(current_color)
+        .chain(system)
+        .filter(|(name, _, _)| starts_with_ignore_case(name, word))
+        .map(|(name, hex, group)| CompletionItem {
+            label: name.clone(),
+            kind: Some(match hex {
+                Some(_) => CompletionItemKind::COLOR,
+                None => CompletionItemKind::VALUE,
+            }),
+            detail: (group == "c").then(|| "System color".to_string()),
+            documentation: hex.map(|hex| Documentation::String(hex.to_string())),
+            sort_text: Some(format!("{group}_{}", name.to_ascii_lowercase())),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit {
+                range,
+                new_text: name,
+            })),
+            ..Default::default()
+        })
+        .collect()
+}
+
+/// Finds what is being completed at an offset, skipping over comments and strings.
+pub(crate) fn find_completion_context(source: &str, offset: usize) -> Option<CompletionContext> {
+    let offset = offset.min(source.len());
+    // Whether each enclosing block holds rules, like the block of `@media`
+    let mut blocks: Vec<bool> = Vec::new();
+    let mut statement_start = 0;
+    for token in Tokenizer::new(&source[..offset], 0) {
+        match token.kind {
+            // The cursor is in the comment or the string
+            TokenKind::Comment | TokenKind::String if token.is_unterminated() => return None,
+            TokenKind::LeftBrace => {
+                blocks.push(is_group_rule(&source[statement_start..token.range.start]));
+                statement_start = token.range.end;
+            }
+            TokenKind::RightBrace => {
+                blocks.pop();
+                statement_start = token.range.end;
+            }
+            TokenKind::Semicolon => statement_start = token.range.end,
+            _ => {}
+        }
+    }
+    let statement = &source[statement_start..offset];
+    if let Some(name) = statement
+        .trim_start()
+        .strip_prefix('@')
+        .filter(|name| name.chars().all(is_name_char))
+    {
+        return Some(CompletionContext::AtRule {
+            word_range: offset - name.len() - 1..offset,
+            top_level: blocks.is_empty(),
+        });
+    }
+    if let Some(context) = media_feature_context(source, statement, offset) {
+        return Some(context);
+    }
+    match blocks.last() {
+        None => {
+            return import_layer_context(source, offset)
+                .or_else(|| selector_context(statement, offset));
+        }
+        Some(true) => return selector_context(statement, offset),
+        // Nested rules starting with `&` can't be mistaken for declarations
+        Some(false) if statement.trim_start().starts_with('&') => {
+            return selector_context(statement, offset);
+        }
+        Some(false) => {}
+    }
+
+    let word_start = source[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_name_char(c))
+        .last()
+        .map_or(offset, |(idx, _)| idx);
+    let statement = incomplete_declaration(source[statement_start..offset].trim_start());
+    let Some((property, _)) = statement.split_once(':') else {
+        // No colon yet: a property name is being typed
+        return statement
+            .chars()
+            .all(is_name_char)
+            .then_some(CompletionContext::PropertyName {
+                word_range: word_start..offset,
+            });
+    };
+    let value_start = offset - statement.len() + property.len() + 1;
+    let property = property.trim_end();
+    if property.is_empty() || !property.chars().all(is_name_char) {
+        return None;
+    }
+
+    Some(CompletionContext::Value {
+        property: property.to_string(),
+        value_start,
+        word_range: word_start..offset,
+    })
+}
+
+/// Finds the media feature, or the value of a media feature, being typed in the prelude of
+/// `@media`, like `min-wi` in `@media screen and (min-wi`.
+fn media_feature_context(
+    source: &str,
+    statement: &str,
+    offset: usize,
+) -> Option<CompletionContext> {
+    let statement = statement.trim_start();
+    let prelude = statement
+        .get(..6)
+        .filter(|name| name.eq_ignore_ascii_case("@media"))
+        .map(|_| &statement[6..])
+        .filter(|prelude| !prelude.starts_with(is_name_char))?;
+    let prelude_start = offset - prelude.len();
+    // The feature being typed is the last one, not closed yet
+    let feature = parse_media_query_list(prelude, prelude_start)
+        .features
+        .pop()
+        .filter(|feature| !source[..feature.range.end].ends_with(')'))?;
+    let word_start = source[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_name_char(c))
+        .last()
+        .map_or(offset, |(idx, _)| idx);
+    let word_range = word_start..offset;
+    let is_empty = feature.name.is_empty() && feature.syntax == MediaFeatureSyntax::Boolean;
+    if is_empty || feature.name_range.contains(&word_start) {
+        Some(CompletionContext::MediaFeature { word_range })
+    } else if feature.syntax == MediaFeatureSyntax::Plain && word_start > feature.name_range.end {
+        Some(CompletionContext::MediaFeatureValue {
+            feature: feature.name,
+            word_range,
+        })
+    } else {
+        None
+    }
+}
+
+/// Finds the layer name being typed in the `layer()` of an `@import`, like `base.re` in
+/// `@import url(a.css) layer(base.re`.
+fn import_layer_context(source: &str, offset: usize) -> Option<CompletionContext> {
+    let import = find_imports(source)
+        .into_iter()
+        .find(|import| (import.target_range.end..=import.range.end).contains(&offset))?;
+    let (before, name) = source[import.target_range.end..offset].rsplit_once('(')?;
+    let is_layer = eq_ignore_case(before.trim(), "layer");
+    (is_layer && name.chars().all(|c| is_name_char(c) || c == '.')).then(|| {
+        CompletionContext::ImportLayer {
+            word_range: offset - name.len()..offset,
+        }
+    })
+}
+
+/// Returns whether the prelude of a block is a conditional group rule holding style rules,
+/// like `@media print` or `@layer base`.
+fn is_group_rule(prelude: &str) -> bool {
+    let Some(at_rule) = prelude.trim_start().strip_prefix('@') else {
+        return false;
+    };
+    let name_len = at_rule
+        .find(|c: char| !is_name_char(c))
+        .unwrap_or(at_rule.len());
+    GROUP_RULES
+        .iter()
+        .any(|name| eq_ignore_case(&at_rule[..name_len], name))
+}
+
+/// The at-rules whose block holds style rules.
+const GROUP_RULES: &[&str] = &[
+    "media",
+    "supports",
+    "layer",
+    "container",
+    "scope",
+    "starting-style",
+    "document",
+    "-moz-document",
+];
+
+/// Finds the compound selector being typed in a rule statement, like `a.bt` in
+/// `nav > a.bt`, or the pseudo-class or pseudo-element being typed, like `:ho` in `a:ho`,
+/// unless the statement is an at-rule or the cursor is in an attribute selector.
+fn selector_context(statement: &str, offset: usize) -> Option<CompletionContext> {
+    let statement = statement.trim_start();
+    if statement.starts_with('@') || statement.matches('[').count() > statement.matches(']').count()
+    {
+        return None;
+    }
+    let name_len = statement
+        .chars()
+        .rev()
+        .take_while(|&c| is_name_char(c))
+        .map(char::len_utf8)
+        .sum::<usize>();
+    let before_name = &statement[..statement.len() - name_len];
+    if before_name.ends_with(':') {
+        let colons = if before_name.ends_with("::") { 2 } else { 1 };
+        return Some(CompletionContext::Pseudo {
+            word_range: offset - name_len - colons..offset,
+        });
+    }
+    let word_len = statement
+        .chars()
+        .rev()
+        .take_while(|&c| is_name_char(c) || c == '.' || c == '#')
+        .map(char::len_utf8)
+        .sum::<usize>();
+    let before = &statement[..statement.len() - word_len];
+    let after_combinator = before.is_empty()
+        || before
+            .ends_with(|c: char| c.is_whitespace() || matches!(c, ',' | '>' | '+' | '~' | '('));
+    after_combinator.then(|| CompletionContext::Selector {
+        word_range: offset - word_len..offset,
+    })
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+/// Recovers the declaration being typed when the previous one isn't terminated by a
+/// semicolon yet, like `background: re` in `color: red\n  background: re`: a later line
+/// that starts with a property name, followed by a colon or by nothing else, starts a new
+/// declaration unless the previous line ends with a comma.
+fn incomplete_declaration(statement: &str) -> &str {
+    if !statement.contains(':') {
+        return statement;
+    }
+    let mut line_start = statement.len();
+    while let Some(newline) = statement[..line_start].rfind('\n') {
+        let line = statement[newline + 1..].trim_start();
+        let name_len = line.find(|c: char| !is_name_char(c)).unwrap_or(line.len());
+        let rest = line[name_len..].trim_start();
+        // Lists of values continue after a comma, like `font-family: a,\n  b`
+        let continues_value = statement[..newline].trim_end().ends_with(',');
+        let starts_declaration = name_len > 0
+            && !continues_value
+            && !line.starts_with(|c: char| c.is_ascii_digit())
+            && (rest.is_empty() || rest.starts_with(':'));
+        if starts_declaration {
+            return line;
+        }
+        line_start = newline;
+    }
+    statement
+}
+
+#[wasm_bindgen]
+pub fn get_completions_wasm(
+    document: &TextDocument,
+    position: &PositionWASM,
+    snippet_support: Option<bool>,
+) -> Vec<CompletionItemWASM> {
+    let options = CompletionOptions {
+        snippet_support: snippet_support.unwrap_or_default(),
+        ..Default::default()
+    };
+    get_completions_with_options(document, position.into(), &options)
+        .into_iter()
+        .map(CompletionItemWASM::from)
+        .collect()
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JS function computing additional completions, called with the JSON of
+    /// `{ uri, languageId, text, position }` and returning the JSON of an array of LSP
+    /// completion items, or `undefined`. A handler that throws adds no completions.
+    #[wasm_bindgen(typescript_type = "CompletionHandler")]
+    pub type JsCompletionHandler;
+
+    #[wasm_bindgen(method, catch, js_name = call)]
+    fn call(
+        this: &JsCompletionHandler,
+        this_arg: &JsValue,
+        params: &str,
+    ) -> Result<Option<String>, JsValue>;
+}
+
+impl CompletionProvider for JsCompletionHandler {
+    fn provide_completions(
+        &self,
+        document: &TextDocument,
+        position: Position,
+    ) -> Vec<CompletionItem> {
+        let params = serde_json::json!({
+            "uri": document.uri,
+            "languageId": document.language_id,
+            "text": document.text,
+            "position": position,
+        });
+        self.call(&JsValue::NULL, &params.to_string())
+            .ok()
+            .flatten()
+            .and_then(|items| serde_json::from_str(&items).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const COMPLETION_HANDLER: &str = r#"
+export type CompletionHandler = (params: string) => string | undefined;
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn complete(code: &str) -> Vec<CompletionItem> {
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let document = TextDocument::new("file:///test.css", "css", 1, &code);
+        let position = LineIndex::new(&code).position(&code, offset);
+        let options = CompletionOptions {
+            snippet_support: true,
+            ..Default::default()
+        };
+        get_completions_with_options(&document, position, &options)
+    }
+
+    fn labels(items: &[CompletionItem]) -> Vec<&str> {
+        items.iter().map(|item| item.label.as_str()).collect()
+    }
+
+    #[test]
+    fn test_get_completions_named_colors() {
+        let items = complete("a {\n  color: rebecca|\n}");
+        assert_eq!(labels(&items), vec!["rebeccapurple"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::COLOR));
+        assert_eq!(
+            items[0].documentation,
+            Some(Documentation::String("#663399".to_string()))
+        );
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.range.start, Position::new(1, 9));
+        assert_eq!(edit.range.end, Position::new(1, 16));
+    }
+
+    #[test]
+    fn test_get_completions_system_colors_and_case() {
+        let items = complete("a { BACKGROUND: 1px solid CA|; }");
+        assert_eq!(labels(&items), vec!["CADETBLUE", "Canvas", "CanvasText"]);
+        assert_eq!(items[1].detail.as_deref(), Some("System color"));
+    }
+
+    #[test]
+    fn test_get_completions_only_in_color_values() {
+        assert!(complete("a { margin: re| }").is_empty());
+        assert!(complete("a:hov| {}")
+            .iter()
+            .all(|item| item.kind != Some(CompletionItemKind::COLOR)));
+        assert!(complete("a { color: 're| }").is_empty());
+        assert!(complete("a { /* color: re| */ }").is_empty());
+        assert_eq!(
+            complete("a { color: |").len(),
+            150 + SYSTEM_COLORS.len() + CSS_WIDE_KEYWORDS.len()
+        );
+    }
+
+    #[test]
+    fn test_get_completions_property_keywords() {
+        let items = complete("a { display: |; }");
+        let suggested = labels(&items);
+        assert_eq!(&suggested[..3], &["none", "block", "inline"]);
+        assert!(suggested.ends_with(&["inherit", "initial", "revert", "revert-layer", "unset"]));
+        assert_eq!(
+            items.last().unwrap().kind,
+            Some(CompletionItemKind::KEYWORD)
+        );
+
+        assert_eq!(
+            labels(&complete("a { DISPLAY: Inl| }")),
+            vec!["inline", "inline-block", "inline-flex", "inline-grid"]
+        );
+        assert_eq!(
+            labels(&complete("a { flex-direction: row |; }")),
+            vec!["row-reverse", "column", "column-reverse"],
+            "Keywords already in the value and CSS-wide keywords aren't suggested"
+        );
+        assert!(complete("a { unknown: |; }").is_empty());
+    }
+
+    #[test]
+    fn test_get_completions_transition() {
+        let items = complete("a { transition: | }");
+        assert_eq!(&labels(&items)[..2], &["all", "opacity"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::PROPERTY));
+        assert!(labels(&items).contains(&"300ms"));
+        assert!(labels(&items).contains(&"ease-in-out"));
+
+        let items = complete("a { transition: opacity 1s ease, transform 2s linear | }");
+        assert_eq!(labels(&items), DURATIONS.to_vec());
+        assert_eq!(
+            labels(&complete("a { -webkit-transition: opacity e| }")),
+            vec!["ease", "ease-in", "ease-out", "ease-in-out"]
+        );
+    }
+
+    #[test]
+    fn test_get_completions_animation() {
+        let code = "@keyframes spin {}
+@keyframes \"quoted\" {}
+a { animation: 1s |; }
+@keyframes fade {}";
+        let items = complete(code);
+        let suggested = labels(&items);
+        assert!(suggested.starts_with(&["150ms"]));
+        let spin = suggested.iter().position(|label| *label == "spin").unwrap();
+        assert_eq!(suggested[spin + 1], "fade");
+        assert_eq!(items[spin].kind, Some(CompletionItemKind::REFERENCE));
+        assert!(suggested.contains(&"infinite"));
+
+        let code = "@keyframes spin {}
+a { animation: spin 1s 2s ease infinite r| }";
+        assert_eq!(
+            labels(&complete(code)),
+            vec!["reverse", "running"],
+            "Filled slots aren't suggested again"
+        );
+        assert!(complete("a { animation: 1s 2s 3s | }").is_empty());
+    }
+
+    #[test]
+    fn test_get_completions_import_layers() {
+        let code = "@import url(a.css) layer(ba|);\n@layer reset, base.elements;";
+        let items = complete(code);
+        assert_eq!(labels(&items), vec!["base", "base.elements"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::MODULE));
+        assert!(complete("@import url(a.css) scr|;\n@layer base;").is_empty());
+
+        let document = TextDocument::new("file:///test.css", "css", 1, "@import \"a.css\" layer(");
+        let items = get_layer_completions(
+            &document,
+            Position::new(0, 22),
+            &["vendor".to_string()],
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(labels(&items), vec!["vendor"]);
+    }
+
+    #[test]
+    fn test_get_completions_selectors() {
+        let code = ".card {}\n.btn, a.btn {}\n.btn:hover {}\n#main .bt|";
+        let items = complete(code);
+        assert_eq!(labels(&items), vec![".btn"]);
+        assert_eq!(items[0].detail.as_deref(), Some("Used 3 times"));
+        assert_eq!(
+            labels(&complete(".card {}\n.btn, a.btn {}\n|")),
+            vec![".btn", ".card", "a.btn"]
+        );
+        assert_eq!(labels(&complete(".card {}\nnav > a|")), Vec::<&str>::new());
+        assert_eq!(labels(&complete(".card {}\na:hov|")), vec![":hover"]);
+        assert!(complete(".card {}\n@media |").is_empty());
+        assert!(complete(".card {}\n[c|").is_empty());
+        assert_eq!(
+            labels(&complete(".card {}\n@media print {\n  #main, .c|")),
+            vec![".card"]
+        );
+        assert!(!complete(".card {}\na {\n  fl|").is_empty());
+        assert!(complete(".card {}\n@font-face {\n  .c|").is_empty());
+
+        let document = TextDocument::new("file:///test.css", "css", 1, ".card {}\n.");
+        let selectors = [(".btn".to_string(), 2), (".card".to_string(), 2)];
+        let items = get_selector_completions(
+            &document,
+            Position::new(1, 1),
+            &selectors,
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(labels(&items), vec![".card", ".btn"]);
+        assert_eq!(items[0].detail.as_deref(), Some("Used 3 times"));
+    }
+
+    #[test]
+    fn test_get_completions_at_rules() {
+        let items = complete("@me|");
+        assert_eq!(labels(&items), vec!["@media"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::KEYWORD));
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(
+            edit.new_text,
+            "@media (${1:min-width}: ${2:value}) {\n\t$0\n}"
+        );
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (0, 3)
+        );
+
+        assert!(labels(&complete("a {}\n@|")).contains(&"@import"));
+        assert!(!labels(&complete("@media print {\n  @|")).contains(&"@import"));
+        assert!(labels(&complete("a {\n  @|")).contains(&"@media"));
+        assert!(!labels(&complete("@|")).contains(&"@use"));
+        assert!(complete("@media |").is_empty());
+
+        let document = TextDocument::new("file:///test.css", "css", 1, "@me");
+        let items = get_completions(&document, Position::new(0, 3));
+        assert_eq!(
+            items[0].insert_text_format,
+            Some(InsertTextFormat::PLAIN_TEXT)
+        );
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.new_text, "@media ");
+
+        let document = TextDocument::new("file:///test.scss", "scss", 1, "a {\n  @inc");
+        let items = get_completions(&document, Position::new(1, 6));
+        assert_eq!(labels(&items), vec!["@include"]);
+    }
+
+    #[test]
+    fn test_get_completions_pseudos() {
+        let items = complete("li:nth-c|");
+        assert_eq!(labels(&items), vec![":nth-child"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::FUNCTION));
+        assert_eq!(items[0].insert_text_format, Some(InsertTextFormat::SNIPPET));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.new_text, ":nth-child(${1:2n+1})");
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (2, 8)
+        );
+        let Some(Documentation::String(documentation)) = &items[0].documentation else {
+            panic!("Expected documentation");
+        };
+        assert!(documentation.contains("Browser support: Chrome 1"));
+
+        assert_eq!(labels(&complete("a:be|")), vec!["::before"]);
+        assert_eq!(
+            labels(&complete("a::f|")),
+            vec!["::first-letter", "::first-line"]
+        );
+        assert!(labels(&complete("@media print {\n  .a:is(.b, :|")).contains(&":hover"));
+        assert_eq!(labels(&complete("a {\n  &:foc|")).len(), 3);
+        assert!(complete("a[title=\"x:|").is_empty());
+        assert!(complete("a { color:|")
+            .iter()
+            .all(|item| !item.label.starts_with(':')));
+    }
+
+    #[test]
+    fn test_get_completions_media_features() {
+        assert_eq!(
+            labels(&complete("@media screen and (max-wi|")),
+            vec!["max-width"]
+        );
+        let items = complete("@media (prefers-reduced-m|");
+        assert_eq!(labels(&items), vec!["prefers-reduced-motion"]);
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (8, 25)
+        );
+        let items = complete("a {}\n@media print, (|");
+        assert!(labels(&items).contains(&"hover"));
+        let device_width = items.iter().find(|item| item.label == "device-width");
+        assert_eq!(
+            device_width.and_then(|item| item.tags.clone()),
+            Some(vec![CompletionItemTag::DEPRECATED])
+        );
+        assert_eq!(labels(&complete("@media (400px <= wid|")), vec!["width"]);
+
+        assert_eq!(
+            labels(&complete("@media (min-width: 1px) and (ORIENTATION: |")),
+            vec!["portrait", "landscape"]
+        );
+        assert_eq!(
+            labels(&complete("@media (prefers-color-scheme: d|")),
+            vec!["dark"]
+        );
+        assert!(complete("@media (width: |").is_empty());
+        assert!(complete("@media (hover) |").is_empty());
+        assert!(complete("@media (width <= |").is_empty());
+    }
+
+    #[test]
+    fn test_get_completions_declaration_snippets() {
+        let items = complete("a { trans| }");
+        assert_eq!(
+            labels(&items),
+            vec!["transition: property duration timing-function"]
+        );
+        assert_eq!(items[0].kind, Some(CompletionItemKind::SNIPPET));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(
+            edit.new_text,
+            "transition: ${1:property} ${2:duration} ${3:timing-function};"
+        );
+        assert!(complete("a { | }")
+            .iter()
+            .all(|item| item.kind != Some(CompletionItemKind::SNIPPET)));
+    }
+
+    #[test]
+    fn test_strip_snippets() {
+        assert_eq!(snippet_to_text(":nth-child(${1:2n+1})"), ":nth-child(2n+1)");
+        assert_eq!(snippet_to_text(":dir(${1|ltr,rtl|})"), ":dir(ltr)");
+        assert_eq!(
+            snippet_to_text("@container ${1:(min-width: ${2:400px})} {\n\t$0\n}"),
+            "@container (min-width: 400px) {\n\t\n}"
+        );
+        assert_eq!(snippet_to_text("@each \\$${1:item} in"), "@each $item in");
+
+        let mut items = complete("a { trans| }");
+        items.extend(complete("li:nth-c|"));
+        items.extend(complete("a { Fl| }"));
+        let items = strip_snippets(items);
+        assert_eq!(
+            labels(&items),
+            vec![":nth-child", "flex-direction", "flex-wrap"]
+        );
+        assert_eq!(
+            items[0].insert_text_format,
+            Some(InsertTextFormat::PLAIN_TEXT)
+        );
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.new_text, ":nth-child(2n+1)");
+        assert_eq!(items[1].insert_text_format, None);
+    }
+
+    #[test]
+    fn test_get_completions_resolution_units() {
+        let items = complete("a { background: image-set(url(a.png) 1.5d|) }");
+        assert_eq!(labels(&items), vec!["1.5dppx", "1.5dpi", "1.5dpcm"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::UNIT));
+
+        let items = complete("a { background: image-set(url(a.png) 2|, url(b.png)) }");
+        assert_eq!(items.len(), 4);
+        assert!(complete("a { background: image-set(url(a2|.png)) }").is_empty());
+    }
+
+    #[test]
+    fn test_get_completions_property_names() {
+        let items = complete("a { Fl| }");
+        assert_eq!(labels(&items), vec!["flex-direction", "flex-wrap"]);
+        assert_eq!(items[0].kind, Some(CompletionItemKind::PROPERTY));
+        let Some(CompletionTextEdit::Edit(edit)) = &items[0].text_edit else {
+            panic!("Expected a text edit");
+        };
+        assert_eq!(edit.new_text, "flex-direction: ");
+        assert_eq!(
+            (edit.range.start.character, edit.range.end.character),
+            (4, 6)
+        );
+
+        let items = complete("a {\n  color: red;\n  |\n}");
+        let mut sorted = items.clone();
+        sorted.sort_by(|a, b| a.sort_text.cmp(&b.sort_text));
+        let last = sorted.last().unwrap();
+        assert_eq!(last.label, "word-wrap", "Obsolete properties come last");
+        assert_eq!(last.tags, Some(vec![CompletionItemTag::DEPRECATED]));
+        assert_eq!(items.len(), CssData::builtin().properties().count());
+        assert!(complete("a { .b| }").is_empty());
+    }
+
+    fn context(code: &str) -> Option<CompletionContext> {
+        let offset = code.find('|').unwrap();
+        find_completion_context(&code.replace('|', ""), offset)
+    }
+
+    #[test]
+    fn test_find_completion_context_incomplete_declarations() {
+        let property_name =
+            |range: Range<usize>| Some(CompletionContext::PropertyName { word_range: range });
+        assert_eq!(context("a { col|"), property_name(4..7));
+        assert_eq!(context("a { color: red; |}"), property_name(16..16));
+        assert_eq!(
+            context("a {\n  color: red\n  back|\n}"),
+            property_name(19..23)
+        );
+        assert_eq!(context("a { .b| }"), None);
+
+        let Some(CompletionContext::Value {
+            property,
+            word_range,
+            ..
+        }) = context("a {\n  color: red\n  background: re|")
+        else {
+            panic!("Expected a value context");
+        };
+        assert_eq!(property, "background");
+        assert_eq!(word_range, 31..33);
+
+        // Values spanning lines aren't split
+        let Some(CompletionContext::Value { property, .. }) =
+            context("a {\n  font-family: Arial,\n    sans-|")
+        else {
+            panic!("Expected a value context");
+        };
+        assert_eq!(property, "font-family");
+        assert_eq!(
+            labels(&complete("a {\n  color: red\n  background: re|")),
+            vec!["rebeccapurple", "red", "revert", "revert-layer"]
+        );
+    }
+
+    struct DesignTokens;
+
+    impl CompletionProvider for DesignTokens {
+        fn provide_completions(
+            &self,
+            _document: &TextDocument,
+            _position: Position,
+        ) -> Vec<CompletionItem> {
+            ["red", "var(--brand)"]
+                .iter()
+                .map(|label| CompletionItem::new_simple(label.to_string(), String::new()))
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_get_completions_with_providers() {
+        let code = "a { color: r }";
+        let document = TextDocument::new("file:///test.css", "css", 1, code);
+        let providers: Vec<Box<dyn CompletionProvider>> = vec![Box::new(DesignTokens)];
+        let items = get_completions_with_providers(
+            &document,
+            Position::new(0, 12),
+            CssData::builtin(),
+            &providers,
+        );
+        let labels = labels(&items);
+        assert_eq!(labels.iter().filter(|label| **label == "red").count(), 1);
+        assert_eq!(labels.last(), Some(&"var(--brand)"));
+        assert_eq!(items[0].kind, Some(CompletionItemKind::COLOR));
+    }
+}
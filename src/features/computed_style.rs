@@ -0,0 +1,257 @@
+use crate::case_folding::fold_case;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, parse_css};
+use crate::selectors::{match_selector, Specificity};
+use crate::text_document::TextDocument;
+use crate::types::RangeWASM;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use lsp_types::{Range, Uri};
+use std::collections::BTreeMap;
+use wasm_bindgen::prelude::*;
+
+/// A declaration winning the cascade for an element.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedDeclaration {
+    /// The property name, lowercased unless it is a custom property.
+    pub property: String,
+    /// The value, as written.
+    pub value: String,
+    pub important: bool,
+    /// The selector of the rule that matched the element, normalized.
+    pub selector: String,
+    pub specificity: Specificity,
+    /// The stylesheet declaring the value.
+    pub uri: Uri,
+    /// The range of the declaration in its stylesheet.
+    pub range: Range,
+    /// The number of other declarations of the property it wins over.
+    pub overridden: usize,
+}
+
+/// Represents a declaration winning the cascade for an element.
+#[wasm_bindgen(js_name = ComputedDeclaration)]
+pub struct ComputedDeclarationWASM(ComputedDeclaration);
+
+#[wasm_bindgen(js_class = ComputedDeclaration)]
+impl ComputedDeclarationWASM {
+    #[wasm_bindgen(getter)]
+    pub fn property(&self) -> String {
+        self.0.property.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn value(&self) -> String {
+        self.0.value.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn important(&self) -> bool {
+        self.0.important
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn selector(&self) -> String {
+        self.0.selector.clone()
+    }
+
+    /// The specificity of the selector, as `[ids, classes, types]`.
+    #[wasm_bindgen(getter)]
+    pub fn specificity(&self) -> Vec<u32> {
+        let specificity = self.0.specificity;
+        vec![specificity.ids, specificity.classes, specificity.types]
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn uri(&self) -> String {
+        self.0.uri.as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> RangeWASM {
+        self.0.range.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn overridden(&self) -> usize {
+        self.0.overridden
+    }
+}
+
+impl From<ComputedDeclaration> for ComputedDeclarationWASM {
+    fn from(declaration: ComputedDeclaration) -> Self {
+        ComputedDeclarationWASM(declaration)
+    }
+}
+
+/// Previews the style of an element, from the style rules of stylesheets matching it.
+///
+/// This is experimental: the matching rules are ordered by the cascade, `!important` first,
+/// then by specificity, then by order of appearance, and the winning declaration of each
+/// property is kept. Rules nested in at-rules, like `@media`, and nested style rules are
+/// conditional or relative, and are ignored. Shorthands and longhands aren't expanded, and
+/// nothing is inherited.
+///
+/// # Arguments
+///
+/// * `element` - A complex selector describing the element, like `nav > a.btn`, see
+///   `match_selector`.
+/// * `stylesheets` - The URIs and sources of the stylesheets, in the order they are loaded.
+/// * `encoding` - The `PositionEncoding` of the ranges.
+///
+/// # Returns
+///
+/// * The winning `ComputedDeclaration` of each property, sorted by property.
+pub fn get_computed_style(
+    element: &str,
+    stylesheets: &[(Uri, &str)],
+    encoding: PositionEncoding,
+) -> Vec<ComputedDeclaration> {
+    let mut winners: BTreeMap<String, ComputedDeclaration> = BTreeMap::new();
+    for (uri, source) in stylesheets {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        let root = parse_css(source).syntax();
+        for rule in root
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_QUALIFIED_RULE)
+            .filter(|rule| {
+                !rule
+                    .ancestors()
+                    .any(|ancestor| ancestor.kind() == CssSyntaxKind::CSS_AT_RULE)
+            })
+        {
+            let Some(selector_list) = rule.first_child() else {
+                continue;
+            };
+            let Some((selector, specificity)) =
+                match_selector(&selector_list.text_trimmed().to_string(), element)
+            else {
+                continue;
+            };
+            for declaration in find_declarations(&rule)
+                .into_iter()
+                .filter(|declaration| nearest_rule(&declaration.node).as_ref() == Some(&rule))
+            {
+                let property = if declaration.name.starts_with("--") {
+                    declaration.name.clone()
+                } else {
+                    fold_case(&declaration.name).into_owned()
+                };
+                let important = declaration
+                    .node
+                    .children()
+                    .any(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_IMPORTANT);
+                let candidate = ComputedDeclaration {
+                    property: property.clone(),
+                    value: declaration.value,
+                    important,
+                    selector: selector.clone(),
+                    specificity,
+                    uri: uri.clone(),
+                    range: line_index.range(
+                        source,
+                        declaration.name_range.start,
+                        declaration.value_range.end,
+                    ),
+                    overridden: 0,
+                };
+                match winners.get_mut(&property) {
+                    // Later declarations win ties
+                    Some(winner)
+                        if (winner.important, winner.specificity)
+                            > (candidate.important, candidate.specificity) =>
+                    {
+                        winner.overridden += 1;
+                    }
+                    Some(winner) => {
+                        let overridden = winner.overridden + 1;
+                        *winner = ComputedDeclaration {
+                            overridden,
+                            ..candidate
+                        };
+                    }
+                    None => {
+                        winners.insert(property, candidate);
+                    }
+                }
+            }
+        }
+    }
+    winners.into_values().collect()
+}
+
+/// Returns the style rule a declaration belongs to.
+fn nearest_rule(declaration: &CssSyntaxNode) -> Option<CssSyntaxNode> {
+    declaration.ancestors().find(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+        )
+    })
+}
+
+/// Previews the style of an element from the style rules of documents, see
+/// `get_computed_style`.
+#[wasm_bindgen]
+pub fn get_computed_style_wasm(
+    element: &str,
+    documents: Vec<TextDocument>,
+) -> Vec<ComputedDeclarationWASM> {
+    let stylesheets: Vec<(Uri, &str)> = documents
+        .iter()
+        .filter_map(|document| Some((document.lsp_uri()?, document.text.as_str())))
+        .collect();
+    get_computed_style(element, &stylesheets, PositionEncoding::Utf16)
+        .into_iter()
+        .map(ComputedDeclarationWASM::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_get_computed_style() {
+        let base: Uri = "file:///base.css".parse().unwrap();
+        let theme: Uri = "file:///theme.css".parse().unwrap();
+        let stylesheets = [
+            (
+                base.clone(),
+                "a { color: blue; margin: 0 }\n.btn { COLOR: black !important; padding: 1px }\n@media print { a { color: gray } }",
+            ),
+            (
+                theme.clone(),
+                "nav .btn { padding: 2px; color: red }\n.btn { padding: 3px }\n.btn { .icon { margin: 1px } }",
+            ),
+        ];
+        let style = get_computed_style("nav > a.btn", &stylesheets, PositionEncoding::Utf16);
+        let summary: Vec<_> = style
+            .iter()
+            .map(|declaration| {
+                (
+                    declaration.property.as_str(),
+                    declaration.value.as_str(),
+                    declaration.selector.as_str(),
+                    declaration.overridden,
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("color", "black", ".btn", 2),
+                ("margin", "0", "a", 0),
+                ("padding", "2px", "nav .btn", 2),
+            ]
+        );
+        assert_eq!(style[0].uri, base);
+        assert!(style[0].important);
+        assert_eq!(style[2].uri, theme);
+        assert_eq!(
+            style[2].range,
+            Range::new(Position::new(0, 11), Position::new(0, 23))
+        );
+        assert!(get_computed_style("p", &stylesheets, PositionEncoding::Utf16).is_empty());
+    }
+}
@@ -0,0 +1,453 @@
+use crate::case_folding::eq_ignore_case;
+use crate::file_system::resolve_reference;
+use crate::identifiers::{is_identifier, unescape_identifier};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, parse_css, split_components};
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{
+    DocumentChanges, Location, OneOf, OptionalVersionedTextDocumentIdentifier, Position,
+    TextDocumentEdit, TextEdit, Uri, WorkspaceEdit,
+};
+use std::ops::Range;
+
+/// The extensions of CSS Modules, whose class names are scoped to the file.
+const MODULE_EXTENSIONS: &[&str] = &[".module.css", ".module.scss", ".module.less"];
+
+/// Where a class name of a CSS Module is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClassReferenceKind {
+    /// In a selector, like `.button`, defining the class.
+    Selector,
+    /// In a `composes` declaration, like `composes: button from './base.module.css'`.
+    Composes,
+}
+
+/// A class name written in a CSS Module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClassReference {
+    /// The class name, without the dot and with its escapes replaced, like `123` for
+    /// `.\31 23`, so names written differently can be compared.
+    pub name: String,
+    /// The byte range of the name.
+    pub range: Range<usize>,
+    pub kind: ClassReferenceKind,
+    /// The module a `composes` declaration takes the class from, as written, if it isn't
+    /// the same file.
+    pub from: Option<String>,
+}
+
+/// Returns whether a document is a CSS Module, like `button.module.css`.
+pub fn is_css_module(uri: &Uri) -> bool {
+    let path = uri.as_str().split(['?', '#']).next().unwrap_or_default();
+    MODULE_EXTENSIONS
+        .iter()
+        .any(|extension| path.to_ascii_lowercase().ends_with(extension))
+}
+
+/// Finds the class names of a stylesheet, in selectors and `composes` declarations.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `ClassReference`, in source order. Classes in `:global(...)` and composed
+///   `from global` aren't included, as they aren't scoped to a module.
+pub fn find_class_references(source: &str) -> Vec<ClassReference> {
+    let root = parse_css(source).syntax();
+    let mut references: Vec<ClassReference> = root
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_CLASS_SELECTOR)
+        .filter(|selector| {
+            !selector.ancestors().any(|node| {
+                node.kind() == CssSyntaxKind::CSS_PSEUDO_CLASS_FUNCTION_SELECTOR
+                    && node
+                        .first_token()
+                        .is_some_and(|token| token.kind() == CssSyntaxKind::GLOBAL_KW)
+            })
+        })
+        .filter_map(|selector| {
+            let name = selector
+                .children()
+                .find(|node| node.kind() == CssSyntaxKind::CSS_CUSTOM_IDENTIFIER)?;
+            let range = name.text_trimmed_range();
+            Some(ClassReference {
+                name: unescape_identifier(&name.text_trimmed().to_string()).into_owned(),
+                range: range.start().into()..range.end().into(),
+                kind: ClassReferenceKind::Selector,
+                from: None,
+            })
+        })
+        .collect();
+
+    for declaration in find_declarations(&root) {
+        if !eq_ignore_case(&declaration.name, "composes") {
+            continue;
+        }
+        let components = split_components(&declaration.value, declaration.value_range.start);
+        let from_idx = components
+            .iter()
+            .position(|(component, _)| eq_ignore_case(component, "from"));
+        let from = match from_idx.and_then(|idx| components.get(idx + 1)) {
+            Some((target, _)) if eq_ignore_case(target, "global") => continue,
+            Some((target, _)) => Some(target.trim_matches(['"', '\'']).to_string()),
+            None => None,
+        };
+        let names = &components[..from_idx.unwrap_or(components.len())];
+        references.extend(names.iter().map(|(name, range)| ClassReference {
+            name: unescape_identifier(name).into_owned(),
+            range: range.clone(),
+            kind: ClassReferenceKind::Composes,
+            from: from.clone(),
+        }));
+    }
+    references.sort_by_key(|reference| reference.range.start);
+    references
+}
+
+/// Finds the references to the class of a CSS Module at a position: its selectors, and the
+/// `composes` declarations using it in the module itself and in the other modules.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on a class name of a selector or of a
+///   `composes` declaration.
+/// * `stylesheets` - The URIs and sources of the other stylesheets, like the indexed ones.
+/// * `include_declaration` - Whether to include the selectors defining the class.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Location` of each reference, sorted by URI, empty if the document isn't a CSS
+///   Module or the cursor isn't on a class name.
+pub fn get_class_references(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    stylesheets: &[(Uri, &str)],
+    include_declaration: bool,
+    encoding: PositionEncoding,
+) -> Vec<Location> {
+    let Some((module, name)) = class_at(uri, source, position, encoding) else {
+        return Vec::new();
+    };
+    let mut documents: Vec<(&Uri, &str)> = stylesheets
+        .iter()
+        .filter(|(other, _)| other != uri)
+        .map(|(other, source)| (other, *source))
+        .collect();
+    documents.push((uri, source));
+    documents.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut locations = Vec::new();
+    for (document, source) in documents {
+        if !is_css_module(document) {
+            continue;
+        }
+        let line_index = LineIndex::with_encoding(source, encoding);
+        for reference in find_class_references(source) {
+            if reference.name != name
+                || (reference.kind == ClassReferenceKind::Selector && !include_declaration)
+            {
+                continue;
+            }
+            let target = match &reference.from {
+                Some(from) => resolve_reference(document, from),
+                None => Some(document.clone()),
+            };
+            if target.as_ref() == Some(&module) {
+                locations.push(Location {
+                    uri: document.clone(),
+                    range: line_index.range(source, reference.range.start, reference.range.end),
+                });
+            }
+        }
+    }
+    locations
+}
+
+/// Renames the class of a CSS Module at a position, in its selectors and in the
+/// `composes` declarations using it across modules, see `get_class_references`.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on a class name.
+/// * `new_name` - The new class name, without the dot, escaped if needed.
+/// * `stylesheets` - The URIs and sources of the other stylesheets, like the indexed ones.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `WorkspaceEdit` renaming every reference, `Ok(None)` if the cursor isn't on the
+///   class of a CSS Module, or an error message if the new name isn't a class name.
+pub fn get_class_rename(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    new_name: &str,
+    stylesheets: &[(Uri, &str)],
+    encoding: PositionEncoding,
+) -> Result<Option<WorkspaceEdit>, String> {
+    if !is_identifier(new_name) {
+        return Err(format!("`{new_name}` isn't a valid class name."));
+    }
+    let locations = get_class_references(uri, source, position, stylesheets, true, encoding);
+    if locations.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(rename_locations(locations, new_name)))
+}
+
+/// Builds the `WorkspaceEdit` replacing the text of each location by a new name, the
+/// locations of a document being consecutive.
+pub(crate) fn rename_locations(locations: Vec<Location>, new_name: &str) -> WorkspaceEdit {
+    let mut edits: Vec<TextDocumentEdit> = Vec::new();
+    for location in locations {
+        let edit = OneOf::Left(TextEdit {
+            range: location.range,
+            new_text: new_name.to_string(),
+        });
+        match edits.last_mut() {
+            Some(document) if document.text_document.uri == location.uri => {
+                document.edits.push(edit)
+            }
+            _ => edits.push(TextDocumentEdit {
+                text_document: OptionalVersionedTextDocumentIdentifier {
+                    uri: location.uri,
+                    version: None,
+                },
+                edits: vec![edit],
+            }),
+        }
+    }
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Edits(edits)),
+        ..Default::default()
+    }
+}
+
+/// Returns the range of the class name of a CSS Module at a position, to check that it
+/// can be renamed before asking for a new name.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Range` of the class name, without the dot, or `None` if the cursor isn't on the
+///   class of a CSS Module.
+pub fn prepare_class_rename(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    encoding: PositionEncoding,
+) -> Option<lsp_types::Range> {
+    class_at(uri, source, position, encoding)?;
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+    find_class_references(source)
+        .into_iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)
+        .map(|reference| line_index.range(source, reference.range.start, reference.range.end))
+}
+
+/// Finds the class name at a position of a CSS Module.
+///
+/// # Returns
+///
+/// * The `Uri` of the module defining the class and its name.
+fn class_at(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    encoding: PositionEncoding,
+) -> Option<(Uri, String)> {
+    if !is_css_module(uri) {
+        return None;
+    }
+    let offset = LineIndex::with_encoding(source, encoding).offset(source, position);
+    let reference = find_class_references(source)
+        .into_iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)?;
+    let module = match &reference.from {
+        Some(from) => resolve_reference(uri, from)?,
+        None => uri.clone(),
+    };
+    is_css_module(&module).then_some((module, reference.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(text: &str) -> Uri {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_find_class_references() {
+        let code = ".btn:is(.primary), a.btn { composes: base Rounded from \"./base.module.css\"; }\n.icon { composes: btn; composes: reset from global; }\n:global(.theme .dark) .icon {}";
+        let references: Vec<_> = find_class_references(code)
+            .into_iter()
+            .map(|reference| {
+                assert_eq!(&code[reference.range.clone()], reference.name);
+                (reference.name, reference.kind, reference.from)
+            })
+            .collect();
+        let base = Some("./base.module.css".to_string());
+        assert_eq!(
+            references,
+            vec![
+                ("btn".to_string(), ClassReferenceKind::Selector, None),
+                ("primary".to_string(), ClassReferenceKind::Selector, None),
+                ("btn".to_string(), ClassReferenceKind::Selector, None),
+                (
+                    "base".to_string(),
+                    ClassReferenceKind::Composes,
+                    base.clone()
+                ),
+                ("Rounded".to_string(), ClassReferenceKind::Composes, base),
+                ("icon".to_string(), ClassReferenceKind::Selector, None),
+                ("btn".to_string(), ClassReferenceKind::Composes, None),
+                ("icon".to_string(), ClassReferenceKind::Selector, None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_class_references_escapes() {
+        let code = ".\\31 23, .md\\:w-1\\/2 {}\n.a { composes: \\31 23 md\\:w-1\\/2; }";
+        let references: Vec<(String, &str)> = find_class_references(code)
+            .into_iter()
+            .map(|reference| (reference.name, &code[reference.range]))
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                ("123".to_string(), "\\31 23"),
+                ("md:w-1/2".to_string(), "md\\:w-1\\/2"),
+                ("a".to_string(), "a"),
+                ("123".to_string(), "\\31 23"),
+                ("md:w-1/2".to_string(), "md\\:w-1\\/2"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_class_references() {
+        let base = uri("file:///p/base.module.css");
+        let button = uri("file:///p/components/button.module.css");
+        let plain = uri("file:///p/global.css");
+        let base_source = ".base { color: red; }\n.base:hover {}\n.other {}";
+        let button_source = ".button { composes: base from '../base.module.css'; }\n.base {}";
+        let stylesheets = [
+            (button.clone(), button_source),
+            (plain.clone(), ".base {}"),
+            (base.clone(), "outdated"),
+        ];
+
+        let locations = get_class_references(
+            &base,
+            base_source,
+            Position::new(1, 3),
+            &stylesheets,
+            true,
+            PositionEncoding::Utf16,
+        );
+        let found: Vec<_> = locations
+            .iter()
+            .map(|location| (location.uri.as_str(), location.range.start))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("file:///p/base.module.css", Position::new(0, 1)),
+                ("file:///p/base.module.css", Position::new(1, 1)),
+                (
+                    "file:///p/components/button.module.css",
+                    Position::new(0, 20)
+                ),
+            ]
+        );
+
+        // From the `composes` declaration of another module
+        let locations = get_class_references(
+            &button,
+            button_source,
+            Position::new(0, 21),
+            &[(base.clone(), base_source)],
+            false,
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].uri, button);
+
+        assert!(
+            get_class_references(
+                &plain,
+                ".base {}",
+                Position::new(0, 2),
+                &stylesheets,
+                true,
+                PositionEncoding::Utf16
+            )
+            .is_empty(),
+            "Classes of global stylesheets aren't scoped"
+        );
+    }
+
+    #[test]
+    fn test_get_class_rename() {
+        let base = uri("file:///p/base.module.css");
+        let button = uri("file:///p/button.module.css");
+        let stylesheets = [(
+            button.clone(),
+            ".button { composes: base from './base.module.css'; }",
+        )];
+        let edit = get_class_rename(
+            &base,
+            ".base {}",
+            Position::new(0, 1),
+            "root",
+            &stylesheets,
+            PositionEncoding::Utf16,
+        )
+        .unwrap()
+        .unwrap();
+        let Some(DocumentChanges::Edits(edits)) = edit.document_changes else {
+            panic!("Expected document edits");
+        };
+        assert_eq!(edits.len(), 2);
+        assert_eq!(edits[1].text_document.uri, button);
+
+        assert!(get_class_rename(
+            &base,
+            ".base {}",
+            Position::new(0, 1),
+            ".root",
+            &[],
+            PositionEncoding::Utf16
+        )
+        .is_err());
+        assert_eq!(
+            get_class_rename(
+                &base,
+                ".base {}",
+                Position::new(0, 7),
+                "root",
+                &[],
+                PositionEncoding::Utf16
+            ),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,493 @@
+use crate::case_folding::eq_ignore_case;
+use crate::features::css_modules::rename_locations;
+use crate::identifiers::{is_identifier, unescape_identifier};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, find_function_calls, parse_css, split_list};
+use crate::text_document::TextDocument;
+use crate::types::PositionWASM;
+use lsp_types::{DocumentHighlight, DocumentHighlightKind, Location, Position, Uri, WorkspaceEdit};
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// Where a custom property is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CustomPropertyReferenceKind {
+    /// As the name of a declaration, like `--gap: 4px`, defining the property.
+    Declaration,
+    /// As the first argument of a `var()`, like `var(--gap)`.
+    Usage,
+}
+
+/// A custom property written in a stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CustomPropertyReference {
+    /// The name, with its `--` and with its escapes replaced, like `--{a}` for `--\{a\}`.
+    pub name: String,
+    /// The byte range of the name.
+    pub range: Range<usize>,
+    pub kind: CustomPropertyReferenceKind,
+}
+
+/// Finds the custom properties of a stylesheet, in declarations and `var()` calls, including
+/// the `var()` calls nested in fallbacks, like `var(--a, var(--b))`.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `CustomPropertyReference`, in source order.
+pub fn find_custom_property_references(source: &str) -> Vec<CustomPropertyReference> {
+    let mut references = Vec::new();
+    for declaration in find_declarations(&parse_css(source).syntax()) {
+        if declaration.name.starts_with("--") {
+            references.push(CustomPropertyReference {
+                name: unescape_identifier(&declaration.name).into_owned(),
+                range: declaration.name_range.clone(),
+                kind: CustomPropertyReferenceKind::Declaration,
+            });
+        }
+        let calls = find_function_calls(&declaration.value, declaration.value_range.start);
+        for call in calls.iter().filter(|call| eq_ignore_case(call.name, "var")) {
+            let Some((name, range)) = split_list(call.arguments, call.arguments_start)
+                .into_iter()
+                .next()
+                .filter(|(name, _)| name.starts_with("--"))
+            else {
+                continue;
+            };
+            references.push(CustomPropertyReference {
+                name: unescape_identifier(name).into_owned(),
+                range,
+                kind: CustomPropertyReferenceKind::Usage,
+            });
+        }
+    }
+    references.sort_by_key(|reference| reference.range.start);
+    references
+}
+
+/// Returns the custom property touching an offset, the cursor being on or right after it.
+pub(crate) fn custom_property_at(
+    references: &[CustomPropertyReference],
+    offset: usize,
+) -> Option<&CustomPropertyReference> {
+    references
+        .iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)
+}
+
+/// Finds the references to the custom property at a position: its declarations and its
+/// `var()` usages, in the document and in the other stylesheets, as custom properties
+/// cascade across every stylesheet of a page.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on a custom property.
+/// * `stylesheets` - The URIs and sources of the other stylesheets, like the indexed ones
+///   and the open documents.
+/// * `include_declaration` - Whether to include the declarations of the property.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Location` of each reference, sorted by URI then in source order, empty if the
+///   cursor isn't on a custom property.
+pub fn get_custom_property_references(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    stylesheets: &[(Uri, &str)],
+    include_declaration: bool,
+    encoding: PositionEncoding,
+) -> Vec<Location> {
+    let offset = LineIndex::with_encoding(source, encoding).offset(source, position);
+    let references = find_custom_property_references(source);
+    let Some(name) = custom_property_at(&references, offset).map(|target| target.name.clone())
+    else {
+        return Vec::new();
+    };
+    let mut documents: Vec<(&Uri, &str)> = stylesheets
+        .iter()
+        .filter(|(other, _)| other != uri)
+        .map(|(other, source)| (other, *source))
+        .collect();
+    documents.push((uri, source));
+    documents.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut locations = Vec::new();
+    for (document, source) in documents {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        for reference in find_custom_property_references(source) {
+            if reference.name != name
+                || (reference.kind == CustomPropertyReferenceKind::Declaration
+                    && !include_declaration)
+            {
+                continue;
+            }
+            locations.push(Location {
+                uri: document.clone(),
+                range: line_index.range(source, reference.range.start, reference.range.end),
+            });
+        }
+    }
+    locations
+}
+
+/// Finds the declarations of the custom property at a position, to jump from a `var()`
+/// usage to the values the property may take.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on a custom property.
+/// * `stylesheets` - The URIs and sources of the other stylesheets.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Location` of each declaration, those of the document first, then those of the
+///   other stylesheets by URI, empty if the cursor isn't on a custom property.
+pub fn get_custom_property_definitions(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    stylesheets: &[(Uri, &str)],
+    encoding: PositionEncoding,
+) -> Vec<Location> {
+    let offset = LineIndex::with_encoding(source, encoding).offset(source, position);
+    let references = find_custom_property_references(source);
+    let Some(name) = custom_property_at(&references, offset).map(|target| target.name.clone())
+    else {
+        return Vec::new();
+    };
+    let mut others: Vec<(&Uri, &str)> = stylesheets
+        .iter()
+        .filter(|(other, _)| other != uri)
+        .map(|(other, source)| (other, *source))
+        .collect();
+    others.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut locations = Vec::new();
+    for (document, source) in std::iter::once((uri, source)).chain(others) {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        locations.extend(
+            find_custom_property_references(source)
+                .into_iter()
+                .filter(|reference| {
+                    reference.kind == CustomPropertyReferenceKind::Declaration
+                        && reference.name == name
+                })
+                .map(|reference| Location {
+                    uri: document.clone(),
+                    range: line_index.range(source, reference.range.start, reference.range.end),
+                }),
+        );
+    }
+    locations
+}
+
+/// Returns the range of the custom property at a position, to check that it can be renamed
+/// before asking for a new name. Property names, values and selectors can't be renamed.
+///
+/// # Arguments
+///
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Range` of the name, with its `--`, or `None` if the cursor isn't on a custom
+///   property.
+pub fn prepare_custom_property_rename(
+    source: &str,
+    position: Position,
+    encoding: PositionEncoding,
+) -> Option<lsp_types::Range> {
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let references = find_custom_property_references(source);
+    let reference = custom_property_at(&references, line_index.offset(source, position))?;
+    Some(line_index.range(source, reference.range.start, reference.range.end))
+}
+
+/// Renames the custom property at a position, in its declarations and `var()` usages
+/// across stylesheets, see `get_custom_property_references`.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on a custom property.
+/// * `new_name` - The new name, with its `--`, escaped if needed.
+/// * `stylesheets` - The URIs and sources of the other stylesheets.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `WorkspaceEdit` renaming every reference, `Ok(None)` if the cursor isn't on a
+///   custom property, or an error message if the new name isn't a custom property name.
+pub fn get_custom_property_rename(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    new_name: &str,
+    stylesheets: &[(Uri, &str)],
+    encoding: PositionEncoding,
+) -> Result<Option<WorkspaceEdit>, String> {
+    if !is_custom_property_name(new_name) {
+        return Err(format!("`{new_name}` isn't a valid custom property name."));
+    }
+    let locations =
+        get_custom_property_references(uri, source, position, stylesheets, true, encoding);
+    if locations.is_empty() {
+        return Ok(None);
+    }
+    Ok(Some(rename_locations(locations, new_name)))
+}
+
+/// Whether a name can be written as a custom property, like `--brand-1` or `--\{a\}`.
+fn is_custom_property_name(name: &str) -> bool {
+    name.len() > 2 && name.starts_with("--") && is_identifier(name)
+}
+
+/// Highlights the occurrences in a document of the custom property at a position: its
+/// declarations as writes and its `var()` usages as reads. Custom property names are case
+/// sensitive, so `--Gap` and `--gap` are different properties.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `position` - The `Position` of the cursor, on a custom property.
+///
+/// # Returns
+///
+/// * A vector of `DocumentHighlight`, in source order, empty if the cursor isn't on a
+///   custom property.
+pub fn get_document_highlights(source: &str, position: Position) -> Vec<DocumentHighlight> {
+    let line_index = LineIndex::new(source);
+    let references = find_custom_property_references(source);
+    let Some(target) = custom_property_at(&references, line_index.offset(source, position)) else {
+        return Vec::new();
+    };
+    references
+        .iter()
+        .filter(|reference| reference.name == target.name)
+        .map(|reference| DocumentHighlight {
+            range: line_index.range(source, reference.range.start, reference.range.end),
+            kind: Some(match reference.kind {
+                CustomPropertyReferenceKind::Declaration => DocumentHighlightKind::WRITE,
+                CustomPropertyReferenceKind::Usage => DocumentHighlightKind::READ,
+            }),
+        })
+        .collect()
+}
+
+/// Highlights the occurrences of the custom property at a position, see
+/// `get_document_highlights`.
+///
+/// # Returns
+///
+/// * The `DocumentHighlight` list as JSON.
+#[wasm_bindgen]
+pub fn get_document_highlights_wasm(document: &TextDocument, position: &PositionWASM) -> String {
+    serde_json::to_string(&get_document_highlights(&document.text, position.into()))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_custom_property_references() {
+        let code = ":root { --gap: 4px; --Gap: 2px; }\na { margin: var( --gap ) var(--x, var(--gap)); color: var(red); }";
+        let found: Vec<(&str, CustomPropertyReferenceKind)> = find_custom_property_references(code)
+            .into_iter()
+            .map(|reference| (&code[reference.range], reference.kind))
+            .collect();
+        use CustomPropertyReferenceKind::*;
+        assert_eq!(
+            found,
+            vec![
+                ("--gap", Declaration),
+                ("--Gap", Declaration),
+                ("--gap", Usage),
+                ("--x", Usage),
+                ("--gap", Usage),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_document_highlights() {
+        let code = ":root {\n  --brand: red;\n  --Brand: blue;\n}\na { color: var(--brand); }";
+        let highlights = get_document_highlights(code, Position::new(4, 19));
+        assert_eq!(
+            highlights,
+            vec![
+                DocumentHighlight {
+                    range: lsp_types::Range::new(Position::new(1, 2), Position::new(1, 9)),
+                    kind: Some(DocumentHighlightKind::WRITE),
+                },
+                DocumentHighlight {
+                    range: lsp_types::Range::new(Position::new(4, 15), Position::new(4, 22)),
+                    kind: Some(DocumentHighlightKind::READ),
+                },
+            ]
+        );
+        assert!(get_document_highlights(code, Position::new(4, 5)).is_empty());
+    }
+
+    #[test]
+    fn test_get_custom_property_references() {
+        let uri: Uri = "file:///p/b.css".parse().unwrap();
+        let tokens: Uri = "file:///p/a.css".parse().unwrap();
+        let code = "a { color: var(--brand); --brand: blue; }";
+        let stylesheets = [
+            (tokens.clone(), ":root { --brand: red; --brandon: 0; }"),
+            (uri.clone(), "outdated"),
+        ];
+        let locations = get_custom_property_references(
+            &uri,
+            code,
+            Position::new(0, 17),
+            &stylesheets,
+            true,
+            PositionEncoding::Utf16,
+        );
+        let found: Vec<(&str, u32)> = locations
+            .iter()
+            .map(|location| (location.uri.as_str(), location.range.start.character))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("file:///p/a.css", 8),
+                ("file:///p/b.css", 15),
+                ("file:///p/b.css", 25)
+            ]
+        );
+        let usages = get_custom_property_references(
+            &uri,
+            code,
+            Position::new(0, 26),
+            &stylesheets,
+            false,
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(usages.len(), 1);
+        assert!(get_custom_property_references(
+            &uri,
+            code,
+            Position::new(0, 5),
+            &stylesheets,
+            true,
+            PositionEncoding::Utf16
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_get_custom_property_definitions() {
+        let uri: Uri = "file:///p/b.css".parse().unwrap();
+        let tokens: Uri = "file:///p/a.css".parse().unwrap();
+        let code = "a { color: var(--brand); --brand: blue; }";
+        let stylesheets = [(tokens, ":root { --brand: red; --brandon: 0; }")];
+        let locations = get_custom_property_definitions(
+            &uri,
+            code,
+            Position::new(0, 17),
+            &stylesheets,
+            PositionEncoding::Utf16,
+        );
+        let found: Vec<(&str, u32)> = locations
+            .iter()
+            .map(|location| (location.uri.as_str(), location.range.start.character))
+            .collect();
+        assert_eq!(found, vec![("file:///p/b.css", 25), ("file:///p/a.css", 8)]);
+        assert!(get_custom_property_definitions(
+            &uri,
+            code,
+            Position::new(0, 5),
+            &stylesheets,
+            PositionEncoding::Utf16
+        )
+        .is_empty());
+    }
+
+    #[test]
+    fn test_get_custom_property_rename() {
+        let uri: Uri = "file:///p/b.css".parse().unwrap();
+        let tokens: Uri = "file:///p/a.css".parse().unwrap();
+        let code = "a { color: var(--brand); }";
+        let stylesheets = [(tokens, ":root { --brand: red; }")];
+        assert_eq!(
+            prepare_custom_property_rename(code, Position::new(0, 17), PositionEncoding::Utf16),
+            Some(lsp_types::Range::new(
+                Position::new(0, 15),
+                Position::new(0, 22)
+            ))
+        );
+        assert_eq!(
+            prepare_custom_property_rename(code, Position::new(0, 6), PositionEncoding::Utf16),
+            None
+        );
+
+        let edit = get_custom_property_rename(
+            &uri,
+            code,
+            Position::new(0, 17),
+            "--accent",
+            &stylesheets,
+            PositionEncoding::Utf16,
+        )
+        .unwrap()
+        .unwrap();
+        let Some(lsp_types::DocumentChanges::Edits(edits)) = edit.document_changes else {
+            panic!("Expected text document edits");
+        };
+        let uris: Vec<&str> = edits
+            .iter()
+            .map(|edit| edit.text_document.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["file:///p/a.css", "file:///p/b.css"]);
+        assert!(get_custom_property_rename(
+            &uri,
+            code,
+            Position::new(0, 17),
+            "accent",
+            &stylesheets,
+            PositionEncoding::Utf16
+        )
+        .is_err());
+        assert_eq!(
+            get_custom_property_rename(
+                &uri,
+                code,
+                Position::new(0, 6),
+                "--accent",
+                &stylesheets,
+                PositionEncoding::Utf16
+            ),
+            Ok(None)
+        );
+    }
+
+    #[test]
+    fn test_custom_property_escapes() {
+        let code = "a { --\\{weird\\}: 1; --\\7B weird\\7D : 2; width: var(--\\{weird\\}, 0); }";
+        let highlights = get_document_highlights(code, Position::new(0, 6));
+        let ranges: Vec<u32> = highlights
+            .iter()
+            .map(|highlight| highlight.range.start.character)
+            .collect();
+        assert_eq!(ranges, vec![4, 20, 51]);
+        assert!(is_custom_property_name("--\\31 23"));
+        assert!(!is_custom_property_name("--a b"));
+    }
+}
@@ -0,0 +1,462 @@
+use crate::budget::{Budget, Budgeted};
+use crate::dialect::Dialect;
+use crate::line_index::LineIndex;
+use crate::parser::{find_declarations, parse_css_with_dialect};
+use crate::rules::font_face::is_unicode_range;
+use crate::rules::syntax::check_syntax;
+use crate::rules::{RuleContext, RuleRegistry, BUILTIN_RULES, STRING_RULE};
+use crate::types::RangeWASM;
+use biome_css_parser::CssParse;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range, Uri};
+use wasm_bindgen::prelude::*;
+
+/// Represents a diagnostic in the CSS code.
+#[wasm_bindgen(js_name = Diagnostic)]
+pub struct DiagnosticWASM(Diagnostic);
+
+#[wasm_bindgen(js_class = Diagnostic)]
+impl DiagnosticWASM {
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> RangeWASM {
+        self.0.range.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn severity(&self) -> Option<u8> {
+        self.0.severity.map(|severity| {
+            if severity == DiagnosticSeverity::ERROR {
+                1
+            } else if severity == DiagnosticSeverity::WARNING {
+                2
+            } else if severity == DiagnosticSeverity::INFORMATION {
+                3
+            } else {
+                4
+            }
+        })
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn source(&self) -> Option<String> {
+        self.0.source.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn message(&self) -> String {
+        self.0.message.clone()
+    }
+}
+
+impl From<Diagnostic> for DiagnosticWASM {
+    fn from(diagnostic: Diagnostic) -> Self {
+        DiagnosticWASM(diagnostic)
+    }
+}
+
+/// Computes the diagnostics for the given CSS source code.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document, used for related information.
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` for every syntax error reported by the parser and every
+///   problem reported by the built-in rules.
+pub fn get_diagnostics(uri: &Uri, source: &str) -> Vec<Diagnostic> {
+    get_diagnostics_with_budget(uri, source, &Budget::UNLIMITED).items
+}
+
+/// Computes the diagnostics for the given CSS source code, skipping the remaining rules
+/// once the budget runs out.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document, used for related information.
+/// * `source` - The original CSS source code as a string slice.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `Diagnostic` found within the budget, sorted by position and flagged as truncated
+///   if some were dropped.
+pub fn get_diagnostics_with_budget(
+    uri: &Uri,
+    source: &str,
+    budget: &Budget,
+) -> Budgeted<Diagnostic> {
+    get_diagnostics_with_rules(uri, source, &RuleRegistry::default(), budget)
+}
+
+/// Computes the diagnostics for the given CSS source code, running the registered rules
+/// after the built-in ones.
+///
+/// Diagnostics of registered rules without a code or source get the code of the rule and
+/// the `csslsrs` source.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document, used for related information.
+/// * `source` - The original CSS source code as a string slice.
+/// * `rules` - The `RuleRegistry` of additional rules, with the options of the built-in ones.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `Diagnostic` found within the budget, sorted by position and flagged as truncated
+///   if some were dropped.
+pub fn get_diagnostics_with_rules(
+    uri: &Uri,
+    source: &str,
+    rules: &RuleRegistry,
+    budget: &Budget,
+) -> Budgeted<Diagnostic> {
+    let dialect = Dialect::from_path(uri.as_str()).unwrap_or_default();
+    let parse = parse_css_with_dialect(source, dialect);
+    get_diagnostics_with_parse(uri, source, parse, dialect, rules, budget)
+}
+
+/// Computes the diagnostics for the given CSS source code from an existing parse of it, see
+/// `get_diagnostics_with_rules`.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document, used for related information.
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `dialect` - The stylesheet language `source` was parsed in, like the one of the
+///   `DocumentStore`, which the rules read it in too.
+/// * `rules` - The `RuleRegistry` of additional rules, with the options of the built-in ones.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `Diagnostic` found within the budget, sorted by position and flagged as truncated
+///   if some were dropped.
+pub fn get_diagnostics_with_parse(
+    uri: &Uri,
+    source: &str,
+    parse: CssParse,
+    dialect: Dialect,
+    rules: &RuleRegistry,
+    budget: &Budget,
+) -> Budgeted<Diagnostic> {
+    let tracker = budget.start();
+    let context = RuleContext {
+        uri,
+        source,
+        parse,
+        dialect,
+        line_index: LineIndex::with_encoding(source, rules.position_encoding()),
+        options: rules.options().clone(),
+        data: rules.data(),
+    };
+    let string_diagnostics = (STRING_RULE.check)(&context);
+    // Values the parser doesn't understand, but which are validated by the rules
+    let validated_values: Vec<Range> = find_declarations(&context.parse.syntax())
+        .iter()
+        .filter(|declaration| is_unicode_range(declaration))
+        .map(|declaration| {
+            context.range(declaration.value_range.start, declaration.value_range.end)
+        })
+        .collect();
+
+    let mut diagnostics: Vec<Diagnostic> = check_syntax(&context)
+        .into_iter()
+        // Errors already covered by the rules, with more precise ranges and fixes
+        .filter(|diagnostic| {
+            !string_diagnostics
+                .iter()
+                .map(|string_diagnostic| string_diagnostic.range)
+                .chain(validated_values.iter().copied())
+                .any(|range| intersects(diagnostic.range, range))
+        })
+        .collect();
+
+    diagnostics.extend(string_diagnostics);
+    for rule in BUILTIN_RULES {
+        if !tracker.has_time() {
+            break;
+        }
+        let rule_diagnostics = (rule.check)(&context);
+        debug_assert!(
+            rule_diagnostics.iter().all(|diagnostic| matches!(
+                &diagnostic.code,
+                Some(NumberOrString::String(code)) if rule.codes.contains(&code.as_str())
+            )),
+            "a built-in rule reported a code missing from its `codes`"
+        );
+        diagnostics.extend(rule_diagnostics);
+    }
+    for rule in rules.rules() {
+        if !tracker.has_time() {
+            break;
+        }
+        let mut rule_diagnostics = Vec::new();
+        rule.check(&context, &mut rule_diagnostics);
+        diagnostics.extend(rule_diagnostics.into_iter().map(|mut diagnostic| {
+            diagnostic
+                .code
+                .get_or_insert_with(|| NumberOrString::String(rule.code().to_string()));
+            diagnostic
+                .source
+                .get_or_insert_with(|| "csslsrs".to_string());
+            diagnostic
+        }));
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.range.start);
+    let diagnostics = diagnostics
+        .into_iter()
+        .take_while(|_| tracker.allow())
+        .collect();
+    tracker.finish(diagnostics)
+}
+
+/// Computes the diagnostics of many documents in one call, like every stylesheet of a
+/// bundle, on several threads where available.
+///
+/// # Arguments
+///
+/// * `inputs` - The URI and source code of every document.
+///
+/// # Returns
+///
+/// * The URI and diagnostics of every document, in the order of `inputs`.
+pub fn get_diagnostics_batch(inputs: Vec<(Uri, String)>) -> Vec<(Uri, Vec<Diagnostic>)> {
+    let diagnose = |inputs: &[(Uri, String)]| -> Vec<Vec<Diagnostic>> {
+        inputs
+            .iter()
+            .map(|(uri, source)| get_diagnostics(uri, source))
+            .collect()
+    };
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let diagnostics: Vec<Vec<Diagnostic>> = {
+        let threads = std::thread::available_parallelism().map_or(1, usize::from);
+        let chunk_size = inputs.len().div_ceil(threads).max(1);
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = inputs
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(move || diagnose(chunk)))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+                })
+                .collect()
+        })
+    };
+    #[cfg(target_arch = "wasm32")]
+    let diagnostics = diagnose(&inputs);
+
+    inputs
+        .into_iter()
+        .map(|(uri, _)| uri)
+        .zip(diagnostics)
+        .collect()
+}
+
+/// Represents the diagnostics of one document of a batch.
+#[wasm_bindgen(js_name = FileDiagnostics)]
+pub struct FileDiagnosticsWASM(Uri, Vec<Diagnostic>);
+
+#[wasm_bindgen(js_class = FileDiagnostics)]
+impl FileDiagnosticsWASM {
+    #[wasm_bindgen(getter)]
+    pub fn uri(&self) -> String {
+        self.0.as_str().to_string()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> Vec<DiagnosticWASM> {
+        self.1.iter().cloned().map(DiagnosticWASM::from).collect()
+    }
+}
+
+/// Computes the diagnostics of many documents in one call, skipping invalid URIs.
+#[wasm_bindgen]
+pub fn get_diagnostics_batch_wasm(
+    uris: Vec<String>,
+    sources: Vec<String>,
+) -> Vec<FileDiagnosticsWASM> {
+    let inputs = uris
+        .iter()
+        .zip(sources)
+        .filter_map(|(uri, source)| Some((uri.parse().ok()?, source)))
+        .collect();
+    get_diagnostics_batch(inputs)
+        .into_iter()
+        .map(|(uri, diagnostics)| FileDiagnosticsWASM(uri, diagnostics))
+        .collect()
+}
+
+fn intersects(a: Range, b: Range) -> bool {
+    a.start <= b.end && b.start <= a.end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnose(code: &str) -> Vec<Diagnostic> {
+        get_diagnostics(&"file:///test.css".parse().unwrap(), code)
+    }
+
+    #[test]
+    fn test_get_diagnostics_valid_css() {
+        let code = "body { margin: 0; }";
+        let diagnostics = diagnose(code);
+
+        assert!(
+            diagnostics.is_empty(),
+            "Valid CSS should not produce diagnostics"
+        );
+    }
+
+    #[test]
+    fn test_get_diagnostics_invalid_css() {
+        let code = "body {\n    margin\n}\n";
+        let diagnostics = diagnose(code);
+
+        assert!(
+            !diagnostics.is_empty(),
+            "Invalid CSS should produce diagnostics"
+        );
+        let diagnostic = &diagnostics[0];
+        assert_eq!(diagnostic.severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(
+            diagnostic.range.start.line, 1,
+            "Error should be reported on the line of `margin`"
+        );
+        assert!(
+            !diagnostic.message.is_empty(),
+            "Message should not be empty"
+        );
+    }
+
+    #[test]
+    fn test_get_diagnostics_replaces_string_errors() {
+        let code = "a { content: \"abc\n}\n";
+        let diagnostics = diagnose(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected a single string diagnostic");
+        assert_eq!(
+            diagnostics[0].code,
+            Some(lsp_types::NumberOrString::String(
+                "newline-in-string".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_get_diagnostics_quotes_in_line_comments() {
+        for uri in ["file:///test.scss", "file:///test.less"] {
+            let code = "// don't\na { color: red; }\nb { margin }\n";
+            let diagnostics = get_diagnostics(&uri.parse().unwrap(), code);
+            // The syntax error of `b` isn't hidden by a string diagnostic
+            assert_eq!(diagnostics.len(), 1, "{uri}: {diagnostics:?}");
+            assert_eq!(diagnostics[0].range.start.line, 2, "{uri}");
+        }
+    }
+
+    #[test]
+    fn test_get_diagnostics_accepts_unicode_range_wildcards() {
+        let code =
+            "@font-face { font-family: A; src: local(A); unicode-range: U+0000-00FF, U+4??; }";
+        let diagnostics = diagnose(code);
+        assert!(
+            diagnostics.is_empty(),
+            "Valid unicode-range values should not produce diagnostics"
+        );
+    }
+
+    #[test]
+    fn test_get_diagnostics_ignores_case() {
+        let code = "A { COLOR: RED; BACKGROUND: #FFF; }\n@FONT-FACE { FONT-FAMILY: X; SRC: LOCAL(X), URL(x.woff2) FORMAT(\"WOFF2\"); UNICODE-RANGE: U+0-FF; }\n";
+        let diagnostics = diagnose(code);
+        assert!(
+            diagnostics.is_empty(),
+            "Uppercase CSS should not produce diagnostics: {diagnostics:?}"
+        );
+    }
+
+    #[test]
+    fn test_get_diagnostics_batch() {
+        let inputs: Vec<(Uri, String)> = (0..10)
+            .map(|idx| {
+                let source = if idx % 3 == 0 {
+                    "a { margin }"
+                } else {
+                    "a { margin: 0; }"
+                };
+                (
+                    format!("file:///{idx}.css").parse().unwrap(),
+                    source.to_string(),
+                )
+            })
+            .collect();
+        let results = get_diagnostics_batch(inputs);
+        assert_eq!(results.len(), 10);
+        for (idx, (uri, diagnostics)) in results.iter().enumerate() {
+            assert_eq!(uri.as_str(), format!("file:///{idx}.css"));
+            assert_eq!(!diagnostics.is_empty(), idx % 3 == 0, "{}", uri.as_str());
+        }
+        assert!(get_diagnostics_batch(Vec::new()).is_empty());
+    }
+
+    struct NoIdSelectors;
+
+    impl crate::rules::LintRule for NoIdSelectors {
+        fn name(&self) -> &str {
+            "No ID selectors"
+        }
+
+        fn code(&self) -> &str {
+            "no-id-selectors"
+        }
+
+        fn check(&self, context: &RuleContext, diagnostics: &mut Vec<Diagnostic>) {
+            for (start, _) in context.source.match_indices('#') {
+                diagnostics.push(Diagnostic {
+                    range: context.range(start, start + 1),
+                    message: "Avoid ID selectors".to_string(),
+                    ..Default::default()
+                });
+            }
+        }
+    }
+
+    #[test]
+    fn test_get_diagnostics_with_rules() {
+        let mut rules = RuleRegistry::new();
+        rules.register(NoIdSelectors);
+        rules.register(NoIdSelectors);
+        assert_eq!(rules.len(), 1, "Rules are unique by code");
+
+        let uri = "file:///test.css".parse().unwrap();
+        let diagnostics = get_diagnostics_with_rules(
+            &uri,
+            "#a { margin: 0; }\n#b { margin: 0; }",
+            &rules,
+            &Budget::UNLIMITED,
+        )
+        .items;
+        assert_eq!(diagnostics.len(), 2);
+        assert_eq!(
+            diagnostics[1].code,
+            Some(NumberOrString::String("no-id-selectors".to_string()))
+        );
+        assert_eq!(diagnostics[1].range.start.line, 1);
+
+        assert!(rules.unregister("no-id-selectors"));
+        assert!(
+            get_diagnostics_with_rules(&uri, "#a { margin: 0; }", &rules, &Budget::UNLIMITED)
+                .items
+                .is_empty()
+        );
+    }
+}
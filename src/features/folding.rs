@@ -1,4 +1,17 @@
-use lsp_types::{FoldingRange, FoldingRangeKind};
+use crate::budget::{Budget, BudgetTracker, Budgeted};
+use crate::chunks::run_in_chunks;
+use crate::compat::CompatibilityMode;
+use crate::dialect::Dialect;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::parse_css;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use crate::types::PositionWASM;
+use biome_css_parser::{parse_css as parse_css_with_options, CssParse, CssParserOptions};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_rowan::Direction;
+use lsp_types::{FoldingRange, FoldingRangeKind, Position};
+use std::collections::HashMap;
+use std::ops::Range;
 use wasm_bindgen::prelude::*;
 
 /// Represents a folding range in the CSS code.
@@ -48,6 +61,117 @@ impl From<FoldingRange> for FoldingRangeWASM {
     }
 }
 
+/// A `{ ... }` block found in the CSS code, shared by folding and the features built on it.
+pub(crate) struct Block {
+    /// Identifier of the block, unique within a document and in order of the opening `{`.
+    pub id: usize,
+    /// Identifier of the block containing this one, if any.
+    pub parent: Option<usize>,
+    /// Byte offset of the opening `{`.
+    pub open_offset: usize,
+    /// Byte offset of the closing `}`.
+    pub close_offset: usize,
+    /// Line of the opening `{`.
+    pub start_line: usize,
+    /// Line of the closing `}`.
+    pub end_line: usize,
+    /// Whether the closing `}` is the first token of its line.
+    pub close_after_newline: bool,
+    /// The selector or at-rule prelude preceding the `{`, with whitespace collapsed.
+    pub prelude: String,
+}
+
+/// Finds every matched pair of braces in the CSS code, in order of their closing brace.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `Block` describing each matched `{ ... }` pair.
+pub(crate) fn find_blocks(source: &str) -> Vec<Block> {
+    let tracker = Budget::UNLIMITED.start();
+    find_blocks_in_tree(source, &parse_css(source).syntax(), &tracker)
+}
+
+/// Finds the matched pairs of braces from the tokens of the syntax tree, so the braces of
+/// strings, URLs and comments don't count, until the time of the budget runs out.
+fn find_blocks_in_tree(source: &str, root: &CssSyntaxNode, tracker: &BudgetTracker) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    let mut stack: Vec<(usize, Option<usize>, usize, usize, String)> = Vec::new();
+    let line_index = LineIndex::new(source);
+    // Start of the text that may become the prelude of the next block
+    let mut segment_start = 0;
+    let mut next_id = 0;
+
+    for token in root.descendants_tokens(Direction::Next) {
+        if !tracker.has_time() {
+            break;
+        }
+        let offset = usize::from(token.text_trimmed_range().start());
+        match token.kind() {
+            CssSyntaxKind::L_CURLY => {
+                let prelude = collapse_prelude(&source[segment_start..offset]);
+                let parent = stack.last().map(|(id, ..)| *id);
+                stack.push((next_id, parent, offset, line_index.line(offset), prelude));
+                next_id += 1;
+                segment_start = offset + 1;
+            }
+            CssSyntaxKind::R_CURLY => {
+                if let Some((id, parent, open_offset, start_line, prelude)) = stack.pop() {
+                    let previous_token_end = source[..offset].trim_end().len();
+                    blocks.push(Block {
+                        id,
+                        parent,
+                        open_offset,
+                        close_offset: offset,
+                        start_line,
+                        end_line: line_index.line(offset),
+                        close_after_newline: source[previous_token_end..offset].contains('\n'),
+                        prelude,
+                    });
+                }
+                segment_start = offset + 1;
+            }
+            CssSyntaxKind::SEMICOLON => segment_start = offset + 1,
+            _ => {}
+        }
+    }
+
+    blocks
+}
+
+/// Strips comments from a prelude and collapses its whitespace.
+fn collapse_prelude(text: &str) -> String {
+    let mut result = String::new();
+    for token in Tokenizer::new(text, 0) {
+        match token.kind {
+            TokenKind::Comment => result.push(' '),
+            _ => result.push_str(token.text),
+        }
+    }
+    result.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Options for computing folding ranges.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct FoldingOptions {
+    /// Whose conventions the folding ranges follow.
+    pub compatibility: CompatibilityMode,
+    /// The stylesheet language of the source.
+    pub dialect: Dialect,
+    /// The maximum number of ranges the client accepts, from its `foldingRange.rangeLimit`
+    /// capability. The outermost ranges are kept.
+    pub range_limit: Option<usize>,
+    /// Whether the client only folds whole lines, from its `foldingRange.lineFoldingOnly`
+    /// capability, so ranges have no character columns.
+    pub line_folding_only: bool,
+    /// The encoding of the character columns of the ranges.
+    pub position_encoding: PositionEncoding,
+}
+
 /// Computes the folding ranges for the given CSS source code.
 ///
 /// # Arguments
@@ -58,43 +182,568 @@ impl From<FoldingRange> for FoldingRangeWASM {
 ///
 /// * A vector of `FoldingRange` indicating the foldable regions in the CSS code.
 pub fn get_folding_ranges(source: &str) -> Vec<FoldingRange> {
+    get_folding_ranges_with_options(source, &FoldingOptions::default())
+}
+
+/// Computes the folding ranges for the given CSS source code with custom options.
+///
+/// In `VscodeCss` compatibility mode, a closing brace on its own line is left out of the
+/// range so it stays visible when folded. Ranges are sorted by their start line. In
+/// dialects with `//` line comments, runs of consecutive comment lines fold as comments.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `FoldingOptions` to apply.
+///
+/// # Returns
+///
+/// * A vector of `FoldingRange` indicating the foldable regions in the CSS code.
+pub fn get_folding_ranges_with_options(
+    source: &str,
+    options: &FoldingOptions,
+) -> Vec<FoldingRange> {
+    get_folding_ranges_with_budget(source, options, &Budget::UNLIMITED).items
+}
+
+/// Computes the folding ranges for the given CSS source code, stopping early once the
+/// budget runs out.
+///
+/// Ranges are sorted by their start line before the item limit applies, so the ranges kept
+/// are the first ones of the document.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `FoldingOptions` to apply.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `FoldingRange` found within the budget, flagged as truncated if some were dropped.
+pub fn get_folding_ranges_with_budget(
+    source: &str,
+    options: &FoldingOptions,
+    budget: &Budget,
+) -> Budgeted<FoldingRange> {
+    let parser_options = if options.dialect.has_line_comments() {
+        CssParserOptions::default().allow_wrong_line_comments()
+    } else {
+        CssParserOptions::default()
+    };
+    let root = parse_css_with_options(source, parser_options).syntax();
+    collect_folding_ranges(source, &root, options, budget.start())
+}
+
+/// Computes the folding ranges for the given CSS source code from an existing parse of it,
+/// see `get_folding_ranges_with_budget`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`, read with `//` comments if the dialect has them.
+/// * `options` - The `FoldingOptions` to apply.
+/// * `budget` - The `Budget` of the request.
+///
+/// # Returns
+///
+/// * The `FoldingRange` found within the budget, flagged as truncated if some were dropped.
+pub fn get_folding_ranges_with_parse(
+    source: &str,
+    parse: CssParse,
+    options: &FoldingOptions,
+    budget: &Budget,
+) -> Budgeted<FoldingRange> {
+    collect_folding_ranges(source, &parse.syntax(), options, budget.start())
+}
+
+/// Computes the folding ranges from the syntax tree of the CSS code, see
+/// `get_folding_ranges_with_budget`.
+fn collect_folding_ranges(
+    source: &str,
+    root: &CssSyntaxNode,
+    options: &FoldingOptions,
+    tracker: BudgetTracker,
+) -> Budgeted<FoldingRange> {
+    let vscode_css = options.compatibility == CompatibilityMode::VscodeCss;
+    let line_index = LineIndex::with_encoding(source, options.position_encoding);
+    let blocks = find_blocks_in_tree(source, root, &tracker);
+    let blocks_by_id: HashMap<usize, &Block> =
+        blocks.iter().map(|block| (block.id, block)).collect();
+    let children = count_children(&blocks);
+
+    let mut folding_ranges: Vec<FoldingRange> = blocks
+        .iter()
+        .map(|block| {
+            let end_line = if vscode_css && block.close_after_newline {
+                block.end_line - 1
+            } else {
+                block.end_line
+            };
+            (block, end_line)
+        })
+        .filter(|(block, end_line)| *end_line > block.start_line)
+        .map(|(block, end_line)| FoldingRange {
+            start_line: block.start_line as u32,
+            // After the `{`, and before the `}` when it is on the end line
+            start_character: Some(line_index.position(source, block.open_offset + 1).character),
+            end_line: end_line as u32,
+            end_character: (end_line == block.end_line)
+                .then(|| line_index.position(source, block.close_offset).character),
+            kind: None,
+            collapsed_text: (!vscode_css)
+                .then(|| {
+                    nested_at_rule_summary(block, &blocks_by_id, &children)
+                        .or_else(|| prelude_summary(&block.prelude))
+                })
+                .flatten(),
+        })
+        .collect();
+
+    if tracker.has_time() {
+        let comments = find_comments(source, root, &tracker);
+        folding_ranges.extend(find_comment_ranges(source, &comments, &line_index));
+        folding_ranges.extend(find_imports_range(root, &line_index));
+        if options.dialect.has_line_comments() {
+            folding_ranges.extend(find_line_comment_ranges(source, &comments, &line_index));
+        }
+    }
+
+    // VS Code's CSS service only folds lines
+    if vscode_css {
+        for folding_range in &mut folding_ranges {
+            folding_range.start_character = None;
+            folding_range.end_character = None;
+        }
+    }
+
+    // Sorted first, so the ranges kept within the budget are the first ones of the document
+    folding_ranges.sort_by_key(|folding_range| folding_range.start_line);
+    let folding_ranges = folding_ranges
+        .into_iter()
+        .take_while(|_| tracker.allow())
+        .collect();
+    tracker.finish(apply_client_capabilities(folding_ranges, options))
+}
+
+/// Applies the folding capabilities of the client: keeps the outermost ranges up to the
+/// range limit, then the ranges starting first at the same nesting level, in their order,
+/// and drops the character columns if the client only folds lines.
+fn apply_client_capabilities(
+    mut folding_ranges: Vec<FoldingRange>,
+    options: &FoldingOptions,
+) -> Vec<FoldingRange> {
+    if let Some(range_limit) = options
+        .range_limit
+        .filter(|&limit| limit < folding_ranges.len())
+    {
+        // Sorted by start line, then outermost first, so enclosing ranges come before
+        // the ranges they contain
+        let mut order: Vec<usize> = (0..folding_ranges.len()).collect();
+        order.sort_by_key(|&idx| {
+            let folding_range = &folding_ranges[idx];
+            (
+                folding_range.start_line,
+                std::cmp::Reverse(folding_range.end_line),
+                idx,
+            )
+        });
+        let mut depths = vec![0; folding_ranges.len()];
+        // The end lines of the ranges enclosing the current one
+        let mut enclosing: Vec<u32> = Vec::new();
+        for &idx in &order {
+            let folding_range = &folding_ranges[idx];
+            while enclosing
+                .last()
+                .is_some_and(|&end_line| end_line < folding_range.end_line)
+            {
+                enclosing.pop();
+            }
+            depths[idx] = enclosing.len();
+            enclosing.push(folding_range.end_line);
+        }
+        order.sort_by_key(|&idx| (depths[idx], folding_ranges[idx].start_line, idx));
+        let mut kept = vec![false; folding_ranges.len()];
+        for &idx in order.iter().take(range_limit) {
+            kept[idx] = true;
+        }
+        let mut kept = kept.into_iter();
+        folding_ranges.retain(|_| kept.next().unwrap_or(false));
+    }
+    if options.line_folding_only {
+        for folding_range in &mut folding_ranges {
+            folding_range.start_character = None;
+            folding_range.end_character = None;
+        }
+    }
+    folding_ranges
+}
+
+/// Computes the folding ranges of a stylesheet too large to be parsed at once, one chunk
+/// of whole top-level rules at a time, see `split_chunks`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `FoldingOptions` to apply.
+/// * `budget` - The `Budget` of the request, shared by the chunks.
+/// * `chunk_size` - The size in bytes from which a chunk ends.
+///
+/// # Returns
+///
+/// * The `FoldingRange` found within the budget, flagged as truncated if some were dropped.
+pub fn get_folding_ranges_in_chunks(
+    source: &str,
+    options: &FoldingOptions,
+    budget: &Budget,
+    chunk_size: usize,
+) -> Budgeted<FoldingRange> {
+    // The range limit applies to the whole stylesheet
+    let chunk_options = FoldingOptions {
+        range_limit: None,
+        ..options.clone()
+    };
+    let encoding = options.position_encoding;
+    let mut result = run_in_chunks(source, chunk_size, encoding, budget, |chunk, budget| {
+        let mut result = get_folding_ranges_with_budget(chunk.text, &chunk_options, budget);
+        for folding_range in &mut result.items {
+            let start = chunk.shift_position(Position::new(
+                folding_range.start_line,
+                folding_range.start_character.unwrap_or(0),
+            ));
+            let end = chunk.shift_position(Position::new(
+                folding_range.end_line,
+                folding_range.end_character.unwrap_or(0),
+            ));
+            folding_range.start_line = start.line;
+            folding_range.end_line = end.line;
+            folding_range.start_character = folding_range.start_character.map(|_| start.character);
+            folding_range.end_character = folding_range.end_character.map(|_| end.character);
+        }
+        result
+    });
+    result.items = apply_client_capabilities(result.items, options);
+    result
+}
+
+/// The maximum length of the prelude shown by a folded block, in characters.
+const MAX_PRELUDE_SUMMARY_LENGTH: usize = 48;
+
+/// Summarizes a block by its selector or at-rule prelude, shortened if needed, like
+/// `.card:hover …`, so the folded line still tells what it hides.
+fn prelude_summary(prelude: &str) -> Option<String> {
+    if prelude.is_empty() {
+        return None;
+    }
+    let summary = match prelude.char_indices().nth(MAX_PRELUDE_SUMMARY_LENGTH) {
+        Some((end, _)) => prelude[..end].trim_end(),
+        None => prelude,
+    };
+    Some(format!("{summary} …"))
+}
+
+/// Summarizes two levels of nesting when at-rules are involved, like `@media screen → .card`.
+///
+/// A block nested in an at-rule is summarized with its parent, and an at-rule block with its
+/// first nested block, so the folded line still tells what it hides.
+///
+/// # Arguments
+///
+/// * `block` - The `Block` to summarize.
+/// * `blocks_by_id` - The blocks of the document, by identifier.
+/// * `children` - The first nested block and the number of nested blocks of each block,
+///   by identifier, see `count_children`.
+fn nested_at_rule_summary(
+    block: &Block,
+    blocks_by_id: &HashMap<usize, &Block>,
+    children: &HashMap<usize, (&Block, usize)>,
+) -> Option<String> {
+    let parent = block.parent.and_then(|id| blocks_by_id.get(&id));
+    if let Some(parent) = parent.filter(|parent| parent.prelude.starts_with('@')) {
+        return Some(format!("{} → {}", parent.prelude, block.prelude));
+    }
+
+    if !block.prelude.starts_with('@') {
+        return None;
+    }
+    let &(first_child, count) = children.get(&block.id)?;
+    Some(match count - 1 {
+        0 => format!("{} → {}", block.prelude, first_child.prelude),
+        more => format!(
+            "{} → {} (+{} more)",
+            block.prelude, first_child.prelude, more
+        ),
+    })
+}
+
+/// Returns the first nested block, in order of the opening brace, and the number of nested
+/// blocks of each block having some, by identifier.
+fn count_children(blocks: &[Block]) -> HashMap<usize, (&Block, usize)> {
+    let mut children: HashMap<usize, (&Block, usize)> = HashMap::new();
+    for block in blocks {
+        let Some(parent) = block.parent else {
+            continue;
+        };
+        children
+            .entry(parent)
+            .and_modify(|(first_child, count)| {
+                if block.id < first_child.id {
+                    *first_child = block;
+                }
+                *count += 1;
+            })
+            .or_insert((block, 1));
+    }
+    children
+}
+
+/// Finds the comments of the CSS code in the trivia of the syntax tree.
+///
+/// # Returns
+///
+/// * The byte ranges of the `/* ... */` comments, and of the `//` comments in dialects
+///   having them, with whether they are `//` comments.
+fn find_comments(
+    source: &str,
+    root: &CssSyntaxNode,
+    tracker: &BudgetTracker,
+) -> Vec<(Range<usize>, bool)> {
+    root.descendants_tokens(Direction::Next)
+        .take_while(|_| tracker.has_time())
+        .flat_map(|token| {
+            let trivia = token
+                .leading_trivia()
+                .pieces()
+                .chain(token.trailing_trivia().pieces());
+            trivia
+                .filter(|piece| piece.is_comments())
+                .map(|piece| {
+                    let range = piece.text_range();
+                    usize::from(range.start())..usize::from(range.end())
+                })
+                .collect::<Vec<_>>()
+        })
+        .map(|range| {
+            let is_line_comment = source[range.clone()].starts_with("//");
+            (range, is_line_comment)
+        })
+        .collect()
+}
+
+/// A `#region` or `#endregion` marker comment.
+enum RegionMarker<'a> {
+    /// The start of a region, with its name, possibly empty.
+    Start(&'a str),
+    End,
+}
+
+/// Reads the text of a comment as a region marker, like `/* #region Colors */`.
+fn region_marker(comment: &str) -> Option<RegionMarker<'_>> {
+    let text = comment
+        .strip_prefix("//")
+        .or_else(|| comment.strip_prefix("/*"))?;
+    let text = text.strip_suffix("*/").unwrap_or(text).trim();
+    let is_keyword = |rest: &str| !rest.starts_with(|c: char| c.is_alphanumeric() || c == '-');
+    if let Some(name) = text.strip_prefix("#region").filter(|rest| is_keyword(rest)) {
+        return Some(RegionMarker::Start(name.trim()));
+    }
+    text.strip_prefix("#endregion")
+        .filter(|rest| is_keyword(rest))
+        .map(|_| RegionMarker::End)
+}
+
+/// Finds the `/* ... */` comments spanning several lines, and the regions between
+/// `#region` and `#endregion` marker comments, named by their `#region` marker.
+fn find_comment_ranges<'a>(
+    source: &'a str,
+    comments: &[(Range<usize>, bool)],
+    line_index: &LineIndex,
+) -> Vec<FoldingRange> {
     let mut folding_ranges = Vec::new();
-    let mut stack = Vec::new();
+    // The line and name of the regions open at this point
+    let mut regions: Vec<(usize, &'a str)> = Vec::new();
+    for (range, is_line_comment) in comments.iter().cloned() {
+        let start_line = line_index.line(range.start);
+        let end_line = line_index.line(range.end.saturating_sub(1).max(range.start));
+        match region_marker(&source[range.clone()]) {
+            Some(RegionMarker::Start(name)) => regions.push((start_line, name)),
+            Some(RegionMarker::End) => {
+                if let Some((region_line, name)) = regions.pop() {
+                    if start_line > region_line {
+                        folding_ranges.push(FoldingRange {
+                            start_line: region_line as u32,
+                            start_character: None,
+                            end_line: start_line as u32,
+                            end_character: None,
+                            kind: Some(FoldingRangeKind::Region),
+                            collapsed_text: (!name.is_empty()).then(|| name.to_string()),
+                        });
+                    }
+                }
+            }
+            None if !is_line_comment && end_line > start_line => {
+                // Between the `/*` and the `*/`, which an unclosed comment lacks
+                let end = if source[range.clone()].ends_with("*/") {
+                    range.end - 2
+                } else {
+                    range.end
+                };
+                folding_ranges.push(FoldingRange {
+                    start_line: start_line as u32,
+                    start_character: Some(line_index.position(source, range.start + 2).character),
+                    end_line: end_line as u32,
+                    end_character: Some(line_index.position(source, end).character),
+                    kind: Some(FoldingRangeKind::Comment),
+                    collapsed_text: None,
+                });
+            }
+            None => {}
+        }
+    }
+    folding_ranges
+}
 
-    // Precompute line start offsets
-    let line_starts: Vec<usize> = std::iter::once(0)
-        .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+/// Finds the run of `@import` and `@charset` statements starting the stylesheet, when it
+/// spans several lines.
+fn find_imports_range(root: &CssSyntaxNode, line_index: &LineIndex) -> Option<FoldingRange> {
+    let rules = root
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_RULE_LIST)?;
+    let imports: Vec<CssSyntaxNode> = rules
+        .children()
+        .take_while(|rule| {
+            rule.kind() == CssSyntaxKind::CSS_AT_RULE
+                && rule.children().any(|child| {
+                    matches!(
+                        child.kind(),
+                        CssSyntaxKind::CSS_IMPORT_AT_RULE | CssSyntaxKind::CSS_CHARSET_AT_RULE
+                    )
+                })
+        })
         .collect();
+    let start_line = line_index.line(imports.first()?.text_trimmed_range().start().into());
+    let end = usize::from(imports.last()?.text_trimmed_range().end());
+    let end_line = line_index.line(end.saturating_sub(1));
+    (end_line > start_line).then_some(FoldingRange {
+        start_line: start_line as u32,
+        start_character: None,
+        end_line: end_line as u32,
+        end_character: None,
+        kind: Some(FoldingRangeKind::Imports),
+        collapsed_text: None,
+    })
+}
 
-    for (offset, c) in source.char_indices() {
-        if c == '{' {
-            // Determine line number based on offset
-            let line_number = line_starts.partition_point(|&line_start| line_start <= offset) - 1;
-            stack.push((offset, line_number));
-        } else if c == '}' {
-            let line_number = line_starts.partition_point(|&line_start| line_start <= offset) - 1;
-            if let Some((_start_offset, start_line)) = stack.pop() {
-                if line_number > start_line {
-                    let folding_range = FoldingRange {
-                        start_line: start_line as u32,
+/// Finds runs of two or more consecutive lines containing only a `//` comment.
+fn find_line_comment_ranges(
+    source: &str,
+    comments: &[(Range<usize>, bool)],
+    line_index: &LineIndex,
+) -> Vec<FoldingRange> {
+    let mut folding_ranges = Vec::new();
+    // The first and last lines of the current run
+    let mut run: Option<(usize, usize)> = None;
+    let own_lines = comments
+        .iter()
+        .filter(|(range, is_line_comment)| {
+            let line_start = source[..range.start].rfind('\n').map_or(0, |idx| idx + 1);
+            *is_line_comment && source[line_start..range.start].trim().is_empty()
+        })
+        .map(|(range, _)| line_index.line(range.start));
+    for line in own_lines.map(Some).chain(std::iter::once(None)) {
+        match (run, line) {
+            (Some((start, end)), Some(line)) if line == end + 1 => run = Some((start, line)),
+            _ => {
+                if let Some((start, end)) = run.filter(|(start, end)| end > start) {
+                    folding_ranges.push(FoldingRange {
+                        start_line: start as u32,
                         start_character: None,
-                        end_line: line_number as u32,
+                        end_line: end as u32,
                         end_character: None,
-                        kind: None,           // You can set FoldingRangeKind if needed
-                        collapsed_text: None, // Optionally set collapsed text
-                    };
-                    folding_ranges.push(folding_range);
+                        kind: Some(FoldingRangeKind::Comment),
+                        collapsed_text: None,
+                    });
                 }
+                run = line.map(|line| (line, line));
             }
         }
     }
-
     folding_ranges
 }
 
+/// Computes how many blocks contain a position, like 2 in the declarations of a rule
+/// nested in `@media`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `position` - The `Position` to measure.
+///
+/// # Returns
+///
+/// * The number of matched `{ ... }` pairs around the position, 0 at the top level.
+pub fn get_nesting_depth(source: &str, position: Position) -> usize {
+    let offset = LineIndex::new(source).offset(source, position);
+    find_blocks(source)
+        .iter()
+        .filter(|block| block.open_offset < offset && offset <= block.close_offset)
+        .count()
+}
+
+/// Computes the depth of the most nested block of the CSS code, for complexity reports.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * The largest number of nested `{ ... }` pairs, 0 if there is no block.
+pub fn get_max_nesting_depth(source: &str) -> usize {
+    let blocks = find_blocks(source);
+    let parents: HashMap<usize, Option<usize>> = blocks
+        .iter()
+        .map(|block| (block.id, block.parent))
+        .collect();
+    let mut depths: HashMap<usize, usize> = HashMap::new();
+    // Blocks come in order of their closing brace, so children come before their parent
+    for block in blocks.iter().rev() {
+        let depth = block
+            .parent
+            .filter(|parent| parents.contains_key(parent))
+            .and_then(|parent| depths.get(&parent))
+            .map_or(1, |depth| depth + 1);
+        depths.insert(block.id, depth);
+    }
+    depths.into_values().max().unwrap_or(0)
+}
+
+#[wasm_bindgen]
+pub fn get_nesting_depth_wasm(source: &str, position: &PositionWASM) -> usize {
+    get_nesting_depth(source, position.into())
+}
+
 #[wasm_bindgen]
-pub fn get_folding_ranges_wasm(source: &str) -> Vec<FoldingRangeWASM> {
-    let folding_ranges = get_folding_ranges(source);
+pub fn get_max_nesting_depth_wasm(source: &str) -> usize {
+    get_max_nesting_depth(source)
+}
+
+#[wasm_bindgen]
+pub fn get_folding_ranges_wasm(
+    source: &str,
+    compatibility: Option<CompatibilityMode>,
+    dialect: Option<Dialect>,
+    range_limit: Option<u32>,
+    line_folding_only: Option<bool>,
+) -> Vec<FoldingRangeWASM> {
+    let options = FoldingOptions {
+        compatibility: compatibility.unwrap_or_default(),
+        dialect: dialect.unwrap_or_default(),
+        range_limit: range_limit.map(|limit| limit as usize),
+        line_folding_only: line_folding_only.unwrap_or_default(),
+        ..Default::default()
+    };
+    let folding_ranges = get_folding_ranges_with_options(source, &options);
     folding_ranges
         .into_iter()
         .map(FoldingRangeWASM::from)
@@ -207,13 +856,95 @@ mod tests {
     #[test]
     fn test_get_folding_ranges_with_comments() {
         let code = "/* Comment block\nspanning multiple lines\n*/\nbody {\n    margin: 0;\n}\n";
-        let folding_ranges = get_folding_ranges(code);
+        let mut folding_ranges = get_folding_ranges(code);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
 
-        assert_eq!(folding_ranges.len(), 1, "Expected one folding range");
+        assert_eq!(folding_ranges.len(), 2, "Expected two folding ranges");
 
-        let range = &folding_ranges[0];
+        let comment = &folding_ranges[0];
+        assert_eq!(comment.start_line, 0, "The comment should fold from line 0");
+        assert_eq!(comment.end_line, 2, "The comment should fold to line 2");
+        assert_eq!(comment.kind, Some(FoldingRangeKind::Comment));
+
+        let range = &folding_ranges[1];
         assert_eq!(range.start_line, 3, "Folding should start at line 3");
         assert_eq!(range.end_line, 5, "Folding should end at line 5");
+        assert_eq!(range.kind, None);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_skips_strings_and_comments() {
+        let code = "a {\n  content: \"}\";\n  background: url(x{y}.png);\n}\n/* b {\n} */\nc {\n  top: 0;\n}\n";
+        let mut folding_ranges = get_folding_ranges(code);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+        let ranges: Vec<(u32, u32, Option<FoldingRangeKind>)> = folding_ranges
+            .into_iter()
+            .map(|fr| (fr.start_line, fr.end_line, fr.kind))
+            .collect();
+        assert_eq!(
+            ranges,
+            vec![
+                (0, 3, None),
+                (4, 5, Some(FoldingRangeKind::Comment)),
+                (6, 8, None)
+            ]
+        );
+        assert_eq!(get_max_nesting_depth("a { content: '{'; }"), 1);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_comment_kinds() {
+        let code = "a { content: '/*'; }\n/* one line */\nb {\n  /* in\n     a rule */\n}\n";
+        let comments: Vec<(u32, u32)> = get_folding_ranges(code)
+            .iter()
+            .filter(|fr| fr.kind == Some(FoldingRangeKind::Comment))
+            .map(|fr| (fr.start_line, fr.end_line))
+            .collect();
+        assert_eq!(comments, vec![(3, 4)]);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_regions() {
+        let code = "/* #region Colors */\n:root {\n  /* #region */\n  --a: red;\n  /* #endregion */\n}\n/* #endregion */\n/* #regionless\n */\n/* #endregion */\n";
+        let regions: Vec<(u32, u32, Option<String>)> = get_folding_ranges(code)
+            .into_iter()
+            .filter(|fr| fr.kind == Some(FoldingRangeKind::Region))
+            .map(|fr| (fr.start_line, fr.end_line, fr.collapsed_text))
+            .collect();
+        assert_eq!(
+            regions,
+            vec![(0, 6, Some("Colors".to_string())), (2, 4, None)]
+        );
+
+        let code = "// #region Spacing\n$a: 1px;\n// #endregion\n";
+        let options = FoldingOptions {
+            dialect: Dialect::Scss,
+            ..Default::default()
+        };
+        let regions: Vec<(u32, u32)> = get_folding_ranges_with_options(code, &options)
+            .iter()
+            .filter(|fr| fr.kind == Some(FoldingRangeKind::Region))
+            .map(|fr| (fr.start_line, fr.end_line))
+            .collect();
+        assert_eq!(regions, vec![(0, 2)]);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_imports() {
+        let code = "@charset \"utf-8\";\n@import url(a.css)\n  screen;\n\n@IMPORT 'b.css';\n.a {}\n@import 'late.css';\n";
+        let imports: Vec<(u32, u32)> = get_folding_ranges(code)
+            .iter()
+            .filter(|fr| fr.kind == Some(FoldingRangeKind::Imports))
+            .map(|fr| (fr.start_line, fr.end_line))
+            .collect();
+        assert_eq!(imports, vec![(0, 4)]);
+        assert!(get_folding_ranges("@import 'a.css';\n.a {}\n").is_empty());
+        assert!(
+            !get_folding_ranges("@import 'a.css'; /* @import\n'b.css'; */\n.a {}\n")
+                .iter()
+                .any(|fr| fr.kind == Some(FoldingRangeKind::Imports)),
+            "Imports in comments don't extend the run"
+        );
     }
 
     #[test]
@@ -241,4 +972,245 @@ mod tests {
         assert_eq!(range3.start_line, 2, "Third folding should start at line 2");
         assert_eq!(range3.end_line, 4, "Third folding should end at line 4");
     }
+
+    #[test]
+    fn test_get_folding_ranges_vscode_css_compatibility() {
+        let code = "@media screen {\n    body {\n        margin: 0; }\n}\n.a {\n}\n";
+        let options = FoldingOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        let folding_ranges = get_folding_ranges_with_options(code, &options);
+
+        assert_eq!(folding_ranges.len(), 2, "Expected two folding ranges");
+        assert_eq!(folding_ranges[0].start_line, 0);
+        assert_eq!(
+            folding_ranges[0].end_line, 2,
+            "A closing brace on its own line should stay visible"
+        );
+        assert_eq!(folding_ranges[1].start_line, 1);
+        assert_eq!(
+            folding_ranges[1].end_line, 2,
+            "A closing brace after a declaration should be folded"
+        );
+    }
+
+    #[test]
+    fn test_get_folding_ranges_scss_line_comments() {
+        let code = "// Colors\n// and spacing\n$a: 1px;\n// single\n.a {\n  // one\n  // two\n  // three\n}\n";
+        let options = FoldingOptions {
+            dialect: Dialect::Scss,
+            ..Default::default()
+        };
+        let mut folding_ranges = get_folding_ranges_with_options(code, &options);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+
+        assert_eq!(folding_ranges.len(), 3, "Expected three folding ranges");
+        assert_eq!(folding_ranges[0].start_line, 0);
+        assert_eq!(folding_ranges[0].end_line, 1);
+        assert_eq!(folding_ranges[0].kind, Some(FoldingRangeKind::Comment));
+        assert_eq!(
+            folding_ranges[1].start_line, 4,
+            "The rule should still fold"
+        );
+        assert_eq!(folding_ranges[2].start_line, 5);
+        assert_eq!(folding_ranges[2].end_line, 7);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_scss_line_comments_in_block_comment() {
+        let code = "/*\n// a\n// b\n*/\n.a { b: c; } // d\n// e\n";
+        let options = FoldingOptions {
+            dialect: Dialect::Scss,
+            ..Default::default()
+        };
+        let folding_ranges: Vec<(u32, u32)> = get_folding_ranges_with_options(code, &options)
+            .iter()
+            .map(|fr| (fr.start_line, fr.end_line))
+            .collect();
+        assert_eq!(
+            folding_ranges,
+            vec![(0, 3)],
+            "Only the block comment folds, and a trailing `//` comment doesn't start a run"
+        );
+    }
+
+    #[test]
+    fn test_get_folding_ranges_css_ignores_line_comments() {
+        let code = "// not\n// a comment\n";
+        let folding_ranges = get_folding_ranges(code);
+        assert!(
+            folding_ranges.is_empty(),
+            "`//` is not a comment in plain CSS"
+        );
+    }
+
+    #[test]
+    fn test_get_folding_ranges_nested_at_rule_collapsed_text() {
+        let code = "@media screen {\n  .container {\n    margin: 0;\n  }\n  .card {\n    margin: 0;\n  }\n}\n.a {\n  color: red;\n}\n";
+        let mut folding_ranges = get_folding_ranges(code);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+
+        assert_eq!(folding_ranges.len(), 4, "Expected four folding ranges");
+        assert_eq!(
+            folding_ranges[0].collapsed_text.as_deref(),
+            Some("@media screen → .container (+1 more)")
+        );
+        assert_eq!(
+            folding_ranges[1].collapsed_text.as_deref(),
+            Some("@media screen → .container")
+        );
+        assert_eq!(
+            folding_ranges[2].collapsed_text.as_deref(),
+            Some("@media screen → .card")
+        );
+        assert_eq!(folding_ranges[3].collapsed_text.as_deref(), Some(".a …"));
+    }
+
+    #[test]
+    fn test_get_folding_ranges_prelude_collapsed_text() {
+        let long_selector = format!(".{}", "x".repeat(60));
+        let code = format!(
+            ".card:hover,\n/* main */ .card:focus {{\n  color: red;\n}}\n{long_selector} {{\n}}\n"
+        );
+        let mut folding_ranges = get_folding_ranges(&code);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+        assert_eq!(
+            folding_ranges[0].collapsed_text.as_deref(),
+            Some(".card:hover, .card:focus …")
+        );
+        let expected = format!("{} …", &long_selector[..MAX_PRELUDE_SUMMARY_LENGTH]);
+        assert_eq!(folding_ranges[1].collapsed_text, Some(expected));
+
+        let options = FoldingOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        let folding_ranges = get_folding_ranges_with_options(&code, &options);
+        assert!(folding_ranges.iter().all(|fr| fr.collapsed_text.is_none()));
+    }
+
+    #[test]
+    fn test_get_folding_ranges_with_budget_truncates() {
+        let code = ".a {\n}\n.b {\n}\n.c {\n}\n";
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let result = get_folding_ranges_with_budget(code, &FoldingOptions::default(), &budget);
+
+        assert_eq!(result.items.len(), 2, "Expected the first two ranges");
+        assert!(result.truncated, "Expected the result to be flagged");
+    }
+
+    #[test]
+    fn test_get_folding_ranges_with_budget_keeps_first_ranges() {
+        let code = "/* one\n   two */\n@media print {\n  .a {\n    top: 0;\n  }\n}\n.b {\n}\n";
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let result = get_folding_ranges_with_budget(code, &FoldingOptions::default(), &budget);
+        let start_lines: Vec<u32> = result.items.iter().map(|fr| fr.start_line).collect();
+
+        assert_eq!(
+            start_lines,
+            vec![0, 2],
+            "Expected the first ranges of the document"
+        );
+        assert!(result.truncated);
+    }
+
+    #[test]
+    fn test_get_folding_ranges_range_limit() {
+        let code = "@media print {\n  .a {\n    top: 0;\n  }\n}\n/* one\n   two */\n.b {\n  &:hover {\n    top: 0;\n  }\n}\n";
+        let options = FoldingOptions {
+            range_limit: Some(3),
+            line_folding_only: true,
+            ..Default::default()
+        };
+        let mut folding_ranges = get_folding_ranges_with_options(code, &options);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+        let start_lines: Vec<u32> = folding_ranges.iter().map(|fr| fr.start_line).collect();
+        assert_eq!(start_lines, vec![0, 5, 7], "The outermost ranges are kept");
+
+        let options = FoldingOptions {
+            range_limit: Some(4),
+            ..Default::default()
+        };
+        let mut folding_ranges = get_folding_ranges_with_options(code, &options);
+        folding_ranges.sort_by_key(|fr| fr.start_line);
+        let start_lines: Vec<u32> = folding_ranges.iter().map(|fr| fr.start_line).collect();
+        assert_eq!(start_lines, vec![0, 1, 5, 7], "Ties go to the first range");
+    }
+
+    #[test]
+    fn test_get_folding_ranges_characters() {
+        let code = "/* 😀\n */\n.😀 {\n  top: 0;\n}\n";
+        let characters = [
+            (PositionEncoding::Utf8, 7),
+            (PositionEncoding::Utf16, 5),
+            (PositionEncoding::Utf32, 4),
+        ];
+        for (position_encoding, open) in characters {
+            let options = FoldingOptions {
+                position_encoding,
+                ..Default::default()
+            };
+            let mut folding_ranges = get_folding_ranges_with_options(code, &options);
+            folding_ranges.sort_by_key(|fr| fr.start_line);
+            let columns: Vec<(Option<u32>, Option<u32>)> = folding_ranges
+                .iter()
+                .map(|fr| (fr.start_character, fr.end_character))
+                .collect();
+            // After the `/*` and the `{`, before the `*/` and the `}`
+            assert_eq!(
+                columns,
+                vec![(Some(2), Some(1)), (Some(open), Some(0))],
+                "{position_encoding:?}"
+            );
+        }
+
+        let options = FoldingOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        let folding_ranges = get_folding_ranges_with_options(code, &options);
+        assert!(folding_ranges
+            .iter()
+            .all(|fr| fr.start_character.is_none() && fr.end_character.is_none()));
+    }
+
+    #[test]
+    fn test_apply_client_capabilities_line_folding_only() {
+        let folding_range = FoldingRange {
+            start_line: 0,
+            start_character: Some(3),
+            end_line: 2,
+            end_character: Some(0),
+            kind: None,
+            collapsed_text: None,
+        };
+        let options = FoldingOptions {
+            line_folding_only: true,
+            ..Default::default()
+        };
+        let folding_ranges = apply_client_capabilities(vec![folding_range], &options);
+        assert_eq!(folding_ranges[0].start_character, None);
+        assert_eq!(folding_ranges[0].end_character, None);
+    }
+
+    #[test]
+    fn test_nesting_depth() {
+        let code =
+            "@media print {\n  .a {\n    color: red;\n    &:hover { color: blue; }\n  }\n}\n.b { }";
+        assert_eq!(get_nesting_depth(code, Position::new(0, 3)), 0);
+        assert_eq!(get_nesting_depth(code, Position::new(1, 2)), 1);
+        assert_eq!(get_nesting_depth(code, Position::new(2, 4)), 2);
+        assert_eq!(get_nesting_depth(code, Position::new(3, 16)), 3);
+        assert_eq!(get_nesting_depth(code, Position::new(6, 4)), 1);
+        assert_eq!(get_max_nesting_depth(code), 3);
+        assert_eq!(get_max_nesting_depth("a { color: red; }"), 1);
+        assert_eq!(get_max_nesting_depth("a {"), 0);
+    }
 }
@@ -0,0 +1,467 @@
+use crate::formatter::{pretty_print_css, pretty_print_statements, FormatOptions};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::parse_css;
+use crate::text_document::TextDocument;
+use crate::types::{RangeWASM, TextEditWASM};
+use biome_css_parser::CssParse;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode, CssSyntaxToken};
+use biome_rowan::{NodeOrToken, TextRange, TextSize};
+use lsp_types::{FormattingOptions, TextEdit};
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// How many lines ahead the diff of `get_minimal_edits` looks for the original and the
+/// formatted code to match again, before replacing the rest of the code at once.
+const RESYNC_WINDOW: usize = 64;
+
+/// Formats a whole document, see `pretty_print_css`.
+///
+/// The formatted code is compared with the document line by line, so only the changed
+/// parts are edited, which keeps the cursor, the selection and the undo history of the
+/// editor where they are. Line endings follow those of the document.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `options` - The `FormattingOptions` sent by the client, for the indentation.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit`, empty if the document is already formatted or has syntax
+///   errors.
+pub fn get_formatting_edits(source: &str, options: &FormattingOptions) -> Vec<TextEdit> {
+    get_formatting_edits_with_parse(source, parse_css(source), options, PositionEncoding::Utf16)
+}
+
+/// Formats a whole document from an existing parse of it, see `get_formatting_edits`.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `options` - The `FormattingOptions` sent by the client, for the indentation.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit`, empty if the document is already formatted or has syntax
+///   errors.
+pub fn get_formatting_edits_with_parse(
+    source: &str,
+    parse: CssParse,
+    options: &FormattingOptions,
+    encoding: PositionEncoding,
+) -> Vec<TextEdit> {
+    let Some(mut formatted) = pretty_print_css(&parse, &format_options(options)) else {
+        return Vec::new();
+    };
+    if source.contains("\r\n") {
+        formatted = formatted.replace("\r\n", "\n").replace('\n', "\r\n");
+    }
+    get_minimal_edits(source, 0..source.len(), &formatted, encoding)
+}
+
+/// Formats the part of a document in a range, like the rule being edited, leaving the rest
+/// of the document untouched.
+///
+/// The range is widened to the smallest statements enclosing it: the declaration or rule
+/// it is in, or the rules and declarations it overlaps when it spans several of them.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `range` - The `Range` to format, like the selection.
+/// * `options` - The `FormattingOptions` sent by the client, for the indentation.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit`, empty if the statements are already formatted or have syntax
+///   errors.
+pub fn get_range_formatting_edits(
+    source: &str,
+    range: lsp_types::Range,
+    options: &FormattingOptions,
+) -> Vec<TextEdit> {
+    get_range_formatting_edits_with_parse(
+        source,
+        parse_css(source),
+        range,
+        options,
+        PositionEncoding::Utf16,
+    )
+}
+
+/// Formats the part of a document in a range from an existing parse of it, see
+/// `get_range_formatting_edits`.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `range` - The `Range` to format, like the selection.
+/// * `options` - The `FormattingOptions` sent by the client, for the indentation.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit`, empty if the statements are already formatted or have syntax
+///   errors.
+pub fn get_range_formatting_edits_with_parse(
+    source: &str,
+    parse: CssParse,
+    range: lsp_types::Range,
+    options: &FormattingOptions,
+    encoding: PositionEncoding,
+) -> Vec<TextEdit> {
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let start = line_index.offset(source, range.start);
+    let end = line_index.offset(source, range.end).max(start);
+    let root = parse.syntax();
+    let Some((statements, depth)) = find_enclosing_statements(&root, start..end) else {
+        return Vec::new();
+    };
+    let Some(mut formatted) =
+        pretty_print_statements(&parse, &statements, depth, &format_options(options))
+    else {
+        return Vec::new();
+    };
+    if source.contains("\r\n") {
+        formatted = formatted.replace("\r\n", "\n").replace('\n', "\r\n");
+    }
+    let mut span_start = usize::from(statements[0].text_trimmed_range().start());
+    let span_end = statements
+        .last()
+        .map_or(span_start, |last| usize::from(last.text_range().end()));
+    let span_end = span_start + source[span_start..span_end].trim_end().len();
+    // The indentation of the first line is fixed too, unless code precedes it on its line
+    let line_start = source[..span_start]
+        .rfind('\n')
+        .map_or(0, |newline| newline + 1);
+    if source[line_start..span_start].trim().is_empty() {
+        span_start = line_start;
+    } else {
+        formatted = formatted.trim_start().to_string();
+    }
+    get_minimal_edits(source, span_start..span_end, &formatted, encoding)
+}
+
+/// Finds the smallest statements enclosing a byte range, and their nesting level.
+fn find_enclosing_statements(
+    root: &CssSyntaxNode,
+    range: Range<usize>,
+) -> Option<(Vec<CssSyntaxNode>, usize)> {
+    let text_range = TextRange::new(
+        TextSize::try_from(range.start).ok()?,
+        TextSize::try_from(range.end).ok()?,
+    );
+    let covering = match root.covering_element(text_range) {
+        NodeOrToken::Node(node) => node,
+        NodeOrToken::Token(token) => token.parent()?,
+    };
+    let statements = covering.ancestors().find_map(|node| {
+        if node
+            .parent()
+            .is_some_and(|parent| is_statement_list(&parent))
+        {
+            return Some(vec![node]);
+        }
+        let list = if node.kind() == CssSyntaxKind::CSS_ROOT {
+            node.children().find(is_statement_list)?
+        } else if is_statement_list(&node) {
+            node
+        } else {
+            return None;
+        };
+        Some(
+            list.children()
+                .filter(|statement| {
+                    let statement_range = statement.text_trimmed_range();
+                    usize::from(statement_range.start()) <= range.end
+                        && range.start <= usize::from(statement_range.end())
+                })
+                .collect(),
+        )
+    })?;
+    let depth = statements
+        .first()?
+        .ancestors()
+        .skip(1)
+        .filter(|node| l_curly(node).is_some())
+        .count();
+    Some((statements, depth))
+}
+
+/// Returns whether a node is the list of the statements of a stylesheet or of a block.
+fn is_statement_list(node: &CssSyntaxNode) -> bool {
+    node.kind().is_list()
+        && node.parent().is_some_and(|parent| {
+            parent.kind() == CssSyntaxKind::CSS_ROOT || l_curly(&parent).is_some()
+        })
+}
+
+fn l_curly(node: &CssSyntaxNode) -> Option<CssSyntaxToken> {
+    node.children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == CssSyntaxKind::L_CURLY)
+}
+
+/// Converts the options of the client into `FormatOptions`.
+fn format_options(options: &FormattingOptions) -> FormatOptions {
+    FormatOptions {
+        tab_size: options.tab_size,
+        insert_spaces: options.insert_spaces,
+        ..Default::default()
+    }
+}
+
+/// Computes the edits turning a part of a text into another text, as small as possible:
+/// the lines are diffed, then each changed block of lines is trimmed of its common start
+/// and end.
+///
+/// # Arguments
+///
+/// * `source` - The original text.
+/// * `span` - The byte range of the part of `source` to replace.
+/// * `target` - The text to turn it into.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit` on `source`, in order and not overlapping.
+pub(crate) fn get_minimal_edits(
+    source: &str,
+    span: Range<usize>,
+    target: &str,
+    encoding: PositionEncoding,
+) -> Vec<TextEdit> {
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = span.start;
+    let text = &source[span];
+    let old_lines = split_lines(text);
+    let new_lines = split_lines(target);
+    let mut edits = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old_lines.len() || j < new_lines.len() {
+        if i < old_lines.len()
+            && j < new_lines.len()
+            && text[old_lines[i].clone()] == target[new_lines[j].clone()]
+        {
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let (next_i, next_j) = resync(text, &old_lines[i..], target, &new_lines[j..])
+            .map_or((old_lines.len(), new_lines.len()), |(a, b)| (i + a, j + b));
+        let old = line_span(&old_lines, i, next_i, text.len());
+        let new = line_span(&new_lines, j, next_j, target.len());
+        let (old, new) = trim_common(text, old, target, new);
+        edits.push(TextEdit {
+            range: line_index.range(source, offset + old.start, offset + old.end),
+            new_text: target[new].to_string(),
+        });
+        (i, j) = (next_i, next_j);
+    }
+    edits
+}
+
+/// Splits a text into the byte ranges of its lines, line ends included.
+fn split_lines(text: &str) -> Vec<Range<usize>> {
+    let mut start = 0;
+    text.split_inclusive('\n')
+        .map(|line| {
+            let range = start..start + line.len();
+            start = range.end;
+            range
+        })
+        .collect()
+}
+
+/// Finds the closest pair of identical lines, as the offsets of the lines in each text.
+fn resync(
+    source: &str,
+    old_lines: &[Range<usize>],
+    target: &str,
+    new_lines: &[Range<usize>],
+) -> Option<(usize, usize)> {
+    for distance in 1..=2 * RESYNC_WINDOW {
+        for a in distance.saturating_sub(RESYNC_WINDOW)..=distance.min(RESYNC_WINDOW) {
+            let b = distance - a;
+            let (Some(old), Some(new)) = (old_lines.get(a), new_lines.get(b)) else {
+                continue;
+            };
+            if source[old.clone()] == target[new.clone()] {
+                return Some((a, b));
+            }
+        }
+    }
+    None
+}
+
+/// Returns the byte range of the lines from `start` to `end`, excluded.
+fn line_span(lines: &[Range<usize>], start: usize, end: usize, len: usize) -> Range<usize> {
+    let span_start = lines.get(start).map_or(len, |line| line.start);
+    let span_end = lines.get(end).map_or(len, |line| line.start);
+    span_start..span_end
+}
+
+/// Shrinks two ranges by the text they start and end with in common.
+fn trim_common(
+    source: &str,
+    old: Range<usize>,
+    target: &str,
+    new: Range<usize>,
+) -> (Range<usize>, Range<usize>) {
+    let (old_text, new_text) = (&source[old.clone()], &target[new.clone()]);
+    let prefix: usize = old_text
+        .chars()
+        .zip(new_text.chars())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    let suffix: usize = old_text[prefix..]
+        .chars()
+        .rev()
+        .zip(new_text[prefix..].chars().rev())
+        .take_while(|(a, b)| a == b)
+        .map(|(c, _)| c.len_utf8())
+        .sum();
+    (
+        old.start + prefix..old.end - suffix,
+        new.start + prefix..new.end - suffix,
+    )
+}
+
+#[wasm_bindgen]
+pub fn get_formatting_edits_wasm(
+    document: &TextDocument,
+    tab_size: u32,
+    insert_spaces: bool,
+) -> Vec<TextEditWASM> {
+    let options = FormattingOptions {
+        tab_size,
+        insert_spaces,
+        ..Default::default()
+    };
+    get_formatting_edits(&document.text, &options)
+        .into_iter()
+        .map(TextEditWASM::from)
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn get_range_formatting_edits_wasm(
+    document: &TextDocument,
+    range: &RangeWASM,
+    tab_size: u32,
+    insert_spaces: bool,
+) -> Vec<TextEditWASM> {
+    let options = FormattingOptions {
+        tab_size,
+        insert_spaces,
+        ..Default::default()
+    };
+    get_range_formatting_edits(&document.text, range.into(), &options)
+        .into_iter()
+        .map(TextEditWASM::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply_edits(code: &str, edits: &[TextEdit]) -> String {
+        let line_index = LineIndex::new(code);
+        let mut result = code.to_string();
+        for edit in edits.iter().rev() {
+            let start = line_index.offset(code, edit.range.start);
+            let end = line_index.offset(code, edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    fn options(tab_size: u32, insert_spaces: bool) -> FormattingOptions {
+        FormattingOptions {
+            tab_size,
+            insert_spaces,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_get_formatting_edits() {
+        let code = "UL>LI,a{color:red;;margin : 0  auto !important;/* end */\n&:hover{top:0}}\n\n\n@media screen and (min-width:600px){.a{}}";
+        let edits = get_formatting_edits(code, &options(2, true));
+        assert_eq!(
+            apply_edits(code, &edits),
+            "ul > li, a {\n  color: red;\n  margin: 0 auto !important; /* end */\n  &:hover {\n    top: 0;\n  }\n}\n\n@media screen and (min-width: 600px) {\n  .a {}\n}\n"
+        );
+
+        let code = "a {\r\n  color: red;\r\n}\r\n";
+        assert!(get_formatting_edits(code, &options(2, true)).is_empty());
+        let edits = get_formatting_edits(code, &options(4, false));
+        assert_eq!(apply_edits(code, &edits), "a {\r\n\tcolor: red;\r\n}\r\n");
+        assert!(get_formatting_edits("a { color: red", &options(2, true)).is_empty());
+    }
+
+    #[test]
+    fn test_get_range_formatting_edits() {
+        let code = "a{color:red}\n@media print {\n.b{top:0;left:0}\n  .c{top:0}\n}\n";
+        let format_range = |start: (u32, u32), end: (u32, u32)| {
+            let range = lsp_types::Range::new(
+                lsp_types::Position::new(start.0, start.1),
+                lsp_types::Position::new(end.0, end.1),
+            );
+            apply_edits(
+                code,
+                &get_range_formatting_edits(code, range, &options(2, true)),
+            )
+        };
+        assert_eq!(
+            format_range((2, 3), (2, 3)),
+            "a{color:red}\n@media print {\n  .b {\n    top: 0;\n    left: 0;\n  }\n  .c{top:0}\n}\n"
+        );
+        assert_eq!(
+            format_range((2, 0), (3, 4)),
+            "a{color:red}\n@media print {\n  .b {\n    top: 0;\n    left: 0;\n  }\n  .c {\n    top: 0;\n  }\n}\n"
+        );
+        assert_eq!(
+            format_range((0, 4), (0, 4)),
+            "a{color: red;}\n@media print {\n.b{top:0;left:0}\n  .c{top:0}\n}\n"
+        );
+        assert_eq!(format_range((1, 14), (1, 14)), format_range((2, 0), (3, 4)));
+
+        let code = "a{top:0}\nb{";
+        let range = |line| {
+            lsp_types::Range::new(
+                lsp_types::Position::new(line, 1),
+                lsp_types::Position::new(line, 1),
+            )
+        };
+        let edits = get_range_formatting_edits(code, range(0), &options(2, true));
+        assert_eq!(apply_edits(code, &edits), "a {\n  top: 0;\n}\nb{");
+        assert!(get_range_formatting_edits(code, range(1), &options(2, true)).is_empty());
+    }
+
+    #[test]
+    fn test_get_minimal_edits() {
+        let code = "a {\n  color: red;\n    top: 0;\n}\n\nb {\nleft: 0;\n}\n";
+        let edits = get_formatting_edits(code, &options(2, true));
+        assert_eq!(edits.len(), 2, "{edits:?}");
+        assert_eq!(edits[0].range.start.line, 2);
+        assert_eq!(edits[0].new_text, "");
+        assert_eq!(edits[1].range.start.line, 6);
+        assert_eq!(edits[1].new_text, "  ");
+
+        let source = "one\ntwo\nthree";
+        let target = "zero\none\n2\nthree\nfour\n";
+        assert_eq!(
+            apply_edits(
+                source,
+                &get_minimal_edits(source, 0..source.len(), target, PositionEncoding::Utf16)
+            ),
+            target
+        );
+    }
+}
@@ -0,0 +1,1116 @@
+use crate::case_folding::fold_case;
+use crate::data::properties::{CssData, PropertyStatus};
+use crate::dialect::Dialect;
+use crate::easing::parse_easing;
+use crate::features::computed_style::get_computed_style;
+use crate::features::symbols::get_document_symbols;
+use crate::file_system::{FileSystemProvider, JsFileSystemProvider, StdFileSystem};
+use crate::import_graph::{find_imports, is_external, resolve_import, Import};
+use crate::layers::{layer_names, name_text};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, parse_css, split_components, split_list, Declaration};
+use crate::selectors::{selector_to_html, specificity};
+use crate::shorthands::{parse_animation, ANIMATION_LONGHANDS};
+use crate::text_document::TextDocument;
+use crate::types::{PositionWASM, RangeWASM};
+use biome_css_parser::CssParse;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_rowan::{Direction, TextRange};
+use lsp_types::{DocumentSymbol, Hover, HoverContents, MarkupContent, MarkupKind, Position, Uri};
+use std::collections::HashSet;
+use wasm_bindgen::prelude::*;
+
+/// The number of contributed symbols listed before summarizing the rest.
+const MAX_LISTED_SYMBOLS: usize = 8;
+
+/// The optional sections of the hover information.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct HoverOptions {
+    /// Whether hovering a selector previews the style of the elements it matches, from the
+    /// document and the stylesheets it imports. Experimental, see `get_computed_style`.
+    pub computed_style: bool,
+    /// Whether the contents are plain text rather than Markdown, for clients that can't
+    /// render it.
+    pub prefer_plaintext: bool,
+}
+
+/// Represents the hover information of a position in the CSS code.
+#[wasm_bindgen(js_name = Hover)]
+pub struct HoverWASM(Hover);
+
+#[wasm_bindgen(js_class = Hover)]
+impl HoverWASM {
+    /// The Markdown contents of the hover.
+    #[wasm_bindgen(getter)]
+    pub fn contents(&self) -> String {
+        match &self.0.contents {
+            HoverContents::Markup(markup) => markup.value.clone(),
+            _ => String::new(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> Option<RangeWASM> {
+        self.0.range.map(RangeWASM::from)
+    }
+}
+
+impl From<Hover> for HoverWASM {
+    fn from(hover: Hover) -> Self {
+        HoverWASM(hover)
+    }
+}
+
+/// Computes the hover information at a position of a document, reading other documents
+/// from the local file system.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to hover.
+/// * `position` - The hovered `Position`.
+///
+/// # Returns
+///
+/// * The `Hover` at the position, or `None` if there is nothing to show.
+pub fn get_hover(document: &TextDocument, position: Position) -> Option<Hover> {
+    get_hover_with_file_system(document, position, &StdFileSystem)
+}
+
+/// Computes the hover information at a position of a document, reading other documents
+/// through a custom `FileSystemProvider`.
+///
+/// Hovering a property name documents the property: its description, syntax, initial value
+/// and status. Hovering an `@import`, `@use` or `@forward` rule summarizes the resolved target: its
+/// location, whether it exists, its size and rule count, and the symbols it contributes.
+/// Hovering an `animation` shorthand lists the longhands it sets, and hovering a declaration
+/// nested in at-rules shows its cascade context: the layer, media, supports, container and
+/// scope conditions it applies in. Hovering an `!important` flag explains what it overrides
+/// from the layer it is declared in, and hovering the `layer()` of an `@import` lists the
+/// layer order of the document. Hovering the selector of a style rule shows its specificity
+/// and an HTML snippet it matches. Hovering the name of an at-rule, or a descriptor in the
+/// block of an at-rule like `@font-face`, documents it with its syntax.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to hover.
+/// * `position` - The hovered `Position`.
+/// * `file_system` - The `FileSystemProvider` used to read imported documents.
+///
+/// # Returns
+///
+/// * The `Hover` at the position, or `None` if there is nothing to show.
+pub fn get_hover_with_file_system(
+    document: &TextDocument,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+) -> Option<Hover> {
+    get_hover_with_options(document, position, file_system, &HoverOptions::default())
+}
+
+/// Computes the hover information at a position of a document, with optional sections, see
+/// `get_hover_with_file_system`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to hover.
+/// * `position` - The hovered `Position`.
+/// * `file_system` - The `FileSystemProvider` used to read imported documents.
+/// * `options` - The `HoverOptions` enabling optional sections.
+///
+/// # Returns
+///
+/// * The `Hover` at the position, or `None` if there is nothing to show.
+pub fn get_hover_with_options(
+    document: &TextDocument,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+    options: &HoverOptions,
+) -> Option<Hover> {
+    get_hover_with_data(document, position, file_system, options, CssData::builtin())
+}
+
+/// Computes the hover information at a position of a document, documenting the properties
+/// known to `data`, see `get_hover_with_file_system`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to hover.
+/// * `position` - The hovered `Position`.
+/// * `file_system` - The `FileSystemProvider` used to read imported documents.
+/// * `options` - The `HoverOptions` enabling optional sections.
+/// * `data` - The `CssData` of the known properties.
+///
+/// # Returns
+///
+/// * The `Hover` at the position, or `None` if there is nothing to show.
+pub fn get_hover_with_data(
+    document: &TextDocument,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+    options: &HoverOptions,
+    data: &CssData,
+) -> Option<Hover> {
+    let parse = parse_css(&document.text);
+    get_hover_with_parse(
+        document,
+        parse,
+        position,
+        file_system,
+        options,
+        data,
+        PositionEncoding::Utf16,
+    )
+}
+
+/// Computes the hover information at a position of a document from an existing parse of
+/// it and with the position encoding of the client, see `get_hover_with_data`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to hover.
+/// * `parse` - The `CssParse` of the text of `document`.
+/// * `position` - The hovered `Position`.
+/// * `file_system` - The `FileSystemProvider` used to read imported documents.
+/// * `options` - The `HoverOptions` enabling optional sections.
+/// * `data` - The `CssData` of the known properties.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Hover` at the position, or `None` if there is nothing to show.
+pub fn get_hover_with_parse(
+    document: &TextDocument,
+    parse: CssParse,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+    options: &HoverOptions,
+    data: &CssData,
+    encoding: PositionEncoding,
+) -> Option<Hover> {
+    let hover = find_hover(
+        document,
+        &parse,
+        position,
+        file_system,
+        options,
+        data,
+        encoding,
+    )?;
+    match hover.contents {
+        HoverContents::Markup(markup) if options.prefer_plaintext => Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::PlainText,
+                value: markdown_to_plaintext(&markup.value),
+            }),
+            range: hover.range,
+        }),
+        _ => Some(hover),
+    }
+}
+
+fn find_hover(
+    document: &TextDocument,
+    parse: &CssParse,
+    position: Position,
+    file_system: &dyn FileSystemProvider,
+    options: &HoverOptions,
+    data: &CssData,
+    encoding: PositionEncoding,
+) -> Option<Hover> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, encoding);
+    let offset = line_index.offset(source, position);
+
+    if let Some(layer) = parse
+        .syntax()
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_IMPORT_NAMED_LAYER)
+        .find(|node| {
+            let range = node.text_trimmed_range();
+            (usize::from(range.start())..=usize::from(range.end())).contains(&offset)
+        })
+    {
+        let name = layer
+            .children()
+            .find(|node| node.kind() == CssSyntaxKind::CSS_LAYER_NAME_LIST)?;
+        let range = layer.text_trimmed_range();
+        return Some(markdown_hover(
+            layer_order(&layer_names(&parse.syntax()), &name_text(&name)),
+            line_index.range(source, range.start().into(), range.end().into()),
+        ));
+    }
+
+    if let Some(import) = find_imports(source)
+        .into_iter()
+        .find(|import| import.range.contains(&offset))
+    {
+        return Some(markdown_hover(
+            import_summary(document, &import, file_system),
+            line_index.range(source, import.range.start, import.range.end),
+        ));
+    }
+
+    if let Some((name, range)) = hovered_at_rule_name(&parse.syntax(), offset) {
+        if let Some(documentation) = at_rule_documentation(&name, data) {
+            return Some(markdown_hover(
+                documentation,
+                line_index.range(source, range.start().into(), range.end().into()),
+            ));
+        }
+    }
+
+    if let Some(selector) = hovered_selector(&parse.syntax(), offset) {
+        let range = selector.text_trimmed_range();
+        let selector = selector.text_trimmed().to_string();
+        let mut sections = vec![selector_summary(&selector)];
+        if options.computed_style {
+            let stylesheets = stylesheets_of(document, file_system);
+            let stylesheets: Vec<(Uri, &str)> = stylesheets
+                .iter()
+                .map(|(uri, source)| (uri.clone(), source.as_str()))
+                .collect();
+            sections.push(computed_style_summary(document, &selector, &stylesheets));
+        }
+        return Some(markdown_hover(
+            sections.join("\n\n---\n\n"),
+            line_index.range(source, range.start().into(), range.end().into()),
+        ));
+    }
+
+    let declaration = find_declarations(&parse.syntax())
+        .into_iter()
+        .filter(|declaration| {
+            declaration.node.parent().is_some_and(|parent| {
+                parent.kind() != CssSyntaxKind::CSS_SUPPORTS_FEATURE_DECLARATION
+            })
+        })
+        .find(|declaration| {
+            let end = usize::from(declaration.node.text_trimmed_range().end());
+            (declaration.name_range.start..=end).contains(&offset)
+        })?;
+    if let Some(important) = declaration
+        .node
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_IMPORTANT)
+        .filter(|node| {
+            let range = node.text_trimmed_range();
+            (usize::from(range.start())..=usize::from(range.end())).contains(&offset)
+        })
+    {
+        let range = important.text_trimmed_range();
+        return Some(markdown_hover(
+            important_summary(&declaration),
+            line_index.range(source, range.start().into(), range.end().into()),
+        ));
+    }
+    if offset > declaration.value_range.end {
+        return None;
+    }
+    let sections: Vec<String> = [
+        property_documentation(&declaration, offset, data),
+        easing_description(&declaration, offset),
+        shorthand_expansion(&declaration),
+        cascade_context(&declaration),
+    ]
+    .into_iter()
+    .flatten()
+    .collect();
+    if sections.is_empty() {
+        return None;
+    }
+    Some(markdown_hover(
+        sections.join("\n\n---\n\n"),
+        line_index.range(
+            source,
+            declaration.name_range.start,
+            declaration.value_range.end,
+        ),
+    ))
+}
+
+fn markdown_hover(value: String, range: lsp_types::Range) -> Hover {
+    Hover {
+        contents: HoverContents::Markup(MarkupContent {
+            kind: MarkupKind::Markdown,
+            value,
+        }),
+        range: Some(range),
+    }
+}
+
+/// Strips the Markdown of the hover contents: emphasis, code spans, code fences, leading
+/// links and separators.
+fn markdown_to_plaintext(markdown: &str) -> String {
+    markdown
+        .lines()
+        .filter(|line| !line.starts_with("```"))
+        .map(|line| match line {
+            "---" => String::new(),
+            _ => {
+                let line = match line
+                    .strip_prefix('[')
+                    .and_then(|rest| rest.split_once("]("))
+                {
+                    Some((text, rest)) => match rest.split_once(')') {
+                        Some((_, rest)) => format!("{text}{rest}"),
+                        None => line.to_string(),
+                    },
+                    None => line.to_string(),
+                };
+                line.replace("**", "").replace('`', "")
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Finds the hovered selector of a style rule that isn't nested in another rule.
+fn hovered_selector(root: &CssSyntaxNode, offset: usize) -> Option<CssSyntaxNode> {
+    root.descendants()
+        .filter(|node| {
+            node.parent().is_some_and(|list| {
+                list.kind() == CssSyntaxKind::CSS_SELECTOR_LIST
+                    && list.parent().is_some_and(|rule| {
+                        rule.kind() == CssSyntaxKind::CSS_QUALIFIED_RULE
+                            && !is_unknown_at_rule(&rule)
+                    })
+            })
+        })
+        .find(|selector| {
+            let range = selector.text_trimmed_range();
+            (usize::from(range.start())..=usize::from(range.end())).contains(&offset)
+        })
+}
+
+/// Returns whether a style rule is the recovered rest of an unknown at-rule, like `x {}`
+/// after the lone `@` of `@unknown x {}`.
+fn is_unknown_at_rule(rule: &CssSyntaxNode) -> bool {
+    rule.prev_sibling().is_some_and(|previous| {
+        previous.kind() == CssSyntaxKind::CSS_BOGUS_RULE
+            && previous.text_trimmed() == "@"
+            && previous.text_trimmed_range().end() == rule.text_trimmed_range().start()
+    })
+}
+
+/// Returns the stylesheets a document imports, recursively and in the order they are
+/// loaded, followed by the document itself.
+fn stylesheets_of(
+    document: &TextDocument,
+    file_system: &dyn FileSystemProvider,
+) -> Vec<(Uri, String)> {
+    fn visit(
+        uri: Uri,
+        source: String,
+        dialect: Dialect,
+        file_system: &dyn FileSystemProvider,
+        visited: &mut HashSet<String>,
+        stylesheets: &mut Vec<(Uri, String)>,
+    ) {
+        for import in find_imports(&source) {
+            let Some(resolved) = resolve_import(&uri, &import.target, dialect, file_system) else {
+                continue;
+            };
+            if !visited.insert(resolved.uri.as_str().to_string()) {
+                continue;
+            }
+            if let Some(contents) = file_system.read_file(&resolved.uri) {
+                visit(
+                    resolved.uri,
+                    contents,
+                    dialect,
+                    file_system,
+                    visited,
+                    stylesheets,
+                );
+            }
+        }
+        stylesheets.push((uri, source));
+    }
+
+    let Some(uri) = document.lsp_uri() else {
+        return Vec::new();
+    };
+    let mut stylesheets = Vec::new();
+    visit(
+        uri.clone(),
+        document.text.clone(),
+        Dialect::from_language_id(&document.language_id),
+        file_system,
+        &mut HashSet::from([uri.as_str().to_string()]),
+        &mut stylesheets,
+    );
+    stylesheets
+}
+
+/// Describes the specificity of a selector and an HTML snippet it matches, as Markdown.
+fn selector_summary(selector: &str) -> String {
+    let mut summary = format!(
+        "[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): {}",
+        specificity(selector)
+    );
+    if let Some(html) = selector_to_html(selector) {
+        summary.push_str(&format!("\n\n```html\n{html}\n```"));
+    }
+    summary
+}
+
+/// Describes the style computed for the elements matched by a selector, as Markdown.
+fn computed_style_summary(
+    document: &TextDocument,
+    selector: &str,
+    stylesheets: &[(Uri, &str)],
+) -> String {
+    let style = get_computed_style(selector, stylesheets, PositionEncoding::Utf16);
+    let mut lines = vec![format!("**Computed style** of `{}`\n", selector)];
+    if style.is_empty() {
+        lines.push("No declarations.".to_string());
+    }
+    for declaration in style {
+        let important = if declaration.important {
+            " !important"
+        } else {
+            ""
+        };
+        let mut line = format!(
+            "- `{}: {}{important}` from `{}` {}",
+            declaration.property, declaration.value, declaration.selector, declaration.specificity
+        );
+        if declaration.uri.as_str() != document.uri {
+            line.push_str(&format!(" in `{}`", declaration.uri.as_str()));
+        }
+        lines.push(line);
+    }
+    lines.join("\n")
+}
+
+/// Describes the hovered easing keyword or function of a value, with a sparkline of its
+/// curve, as Markdown.
+fn easing_description(declaration: &Declaration, offset: usize) -> Option<String> {
+    let (component, range) = split_list(&declaration.value, declaration.value_range.start)
+        .into_iter()
+        .flat_map(|(item, range)| split_components(item, range.start))
+        .find(|(_, range)| range.start <= offset && offset <= range.end)?;
+    // Keywords like `linear` only name easing functions in animations and transitions
+    let name = fold_case(&declaration.name);
+    if !component.contains('(') && !name.contains("animation") && !name.contains("transition") {
+        return None;
+    }
+    let easing = parse_easing(component, range.start).ok()??;
+    Some(format!(
+        "**Easing** `{component}`\n\n{}\n\n```text\n{}\n```",
+        easing.describe(),
+        easing.sparkline(16)
+    ))
+}
+
+/// Lists the longhands set by an `animation` shorthand, as Markdown.
+fn shorthand_expansion(declaration: &Declaration) -> Option<String> {
+    let name = fold_case(&declaration.name);
+    if name != "animation" && name != "-webkit-animation" {
+        return None;
+    }
+    let layers = parse_animation(&declaration.value, declaration.value_range.start).ok()??;
+    let mut lines = vec![format!("**Longhands** of `{}`\n", declaration.name)];
+    for (longhand, values) in ANIMATION_LONGHANDS
+        .iter()
+        .enumerate()
+        .map(|(idx, longhand)| {
+            let values: Vec<&str> = layers
+                .iter()
+                .map(|layer| layer.longhands()[idx].1)
+                .collect();
+            (longhand, values)
+        })
+    {
+        lines.push(format!("- `{longhand}: {}`", values.join(", ")));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Documents the property of a declaration when its name is hovered, as Markdown.
+///
+/// # Returns
+///
+/// * The documentation, or `None` if the property isn't known to `data`.
+fn property_documentation(
+    declaration: &Declaration,
+    offset: usize,
+    data: &CssData,
+) -> Option<String> {
+    if !(declaration.name_range.start..=declaration.name_range.end).contains(&offset) {
+        return None;
+    }
+    // In the block of an at-rule like `@font-face`, the names are those of descriptors
+    let at_rule = enclosing_at_rule_name(&declaration.node);
+    let (property, title) = match at_rule
+        .as_deref()
+        .and_then(|at_rule| Some((at_rule, data.descriptor(at_rule, &declaration.name)?)))
+    {
+        Some((at_rule, descriptor)) => (
+            descriptor,
+            format!("**`{}`** descriptor of `{at_rule}`", descriptor.name),
+        ),
+        None => {
+            let property = data.property(&declaration.name)?;
+            (property, format!("**`{}`**", property.name))
+        }
+    };
+    let mut lines = vec![title];
+    if let Some(description) = &property.description {
+        lines.push(format!("\n{description}\n"));
+    }
+    if let Some(syntax) = &property.syntax {
+        lines.push(format!("- Syntax: `{syntax}`"));
+    }
+    if let Some(initial) = &property.initial {
+        lines.push(format!("- Initial value: `{initial}`"));
+    }
+    match property.status {
+        PropertyStatus::Standard => {}
+        PropertyStatus::Experimental => lines.push("- Status: experimental".to_string()),
+        PropertyStatus::Nonstandard => lines.push("- Status: non-standard".to_string()),
+        PropertyStatus::Obsolete => lines.push("- Status: obsolete".to_string()),
+    }
+    Some(lines.join("\n").trim_end().to_string())
+}
+
+/// Finds the at-rule whose name is hovered, like `@supports`, with the range of its name.
+fn hovered_at_rule_name(root: &CssSyntaxNode, offset: usize) -> Option<(String, TextRange)> {
+    root.descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_AT_RULE)
+        .find_map(|at_rule| {
+            let at = at_rule.first_token()?;
+            let name = at_rule.children().next()?.first_token()?;
+            let range = TextRange::new(
+                at.text_trimmed_range().start(),
+                name.text_trimmed_range().end(),
+            );
+            (usize::from(range.start())..=usize::from(range.end()))
+                .contains(&offset)
+                .then(|| (format!("@{}", name.text_trimmed()), range))
+        })
+}
+
+/// Returns the name of the at-rule whose block directly holds a declaration, like
+/// `@font-face`, or `None` if the declaration is in a style rule.
+fn enclosing_at_rule_name(declaration: &CssSyntaxNode) -> Option<String> {
+    for ancestor in declaration.ancestors().skip(1) {
+        if matches!(
+            ancestor.kind(),
+            CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+        ) {
+            return None;
+        }
+        if ancestor
+            .parent()
+            .is_some_and(|parent| parent.kind() == CssSyntaxKind::CSS_AT_RULE)
+        {
+            let name = ancestor.first_token()?;
+            return Some(format!("@{}", name.text_trimmed()));
+        }
+    }
+    None
+}
+
+/// Documents an at-rule known to `data`: its description and syntax, as Markdown.
+fn at_rule_documentation(name: &str, data: &CssData) -> Option<String> {
+    let at_directive = data.at_directive(name)?;
+    let mut lines = vec![format!("**`{}`**", at_directive.name)];
+    if let Some(description) = &at_directive.description {
+        lines.push(format!("\n{description}\n"));
+    }
+    if let Some(syntax) = &at_directive.syntax {
+        lines.push(format!("- Syntax: `{syntax}`"));
+    }
+    Some(lines.join("\n").trim_end().to_string())
+}
+
+/// Describes the at-rules a declaration is nested in, outermost first, as Markdown.
+///
+/// # Returns
+///
+/// * The description, or `None` if the declaration isn't nested in a conditional or layer
+///   at-rule.
+fn cascade_context(declaration: &Declaration) -> Option<String> {
+    let layers = enclosing_layers(&declaration.node);
+    let mut conditions: Vec<(&str, String)> = Vec::new();
+    let mut at_rules: Vec<CssSyntaxNode> = declaration
+        .node
+        .ancestors()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_LAYER_AT_RULE
+                    | CssSyntaxKind::CSS_MEDIA_AT_RULE
+                    | CssSyntaxKind::CSS_SUPPORTS_AT_RULE
+                    | CssSyntaxKind::CSS_CONTAINER_AT_RULE
+                    | CssSyntaxKind::CSS_SCOPE_AT_RULE
+                    | CssSyntaxKind::CSS_STARTING_STYLE_AT_RULE
+            )
+        })
+        .collect();
+    at_rules.reverse();
+    for at_rule in &at_rules {
+        let prelude = at_rule_prelude(at_rule);
+        match at_rule.kind() {
+            CssSyntaxKind::CSS_LAYER_AT_RULE => {}
+            CssSyntaxKind::CSS_MEDIA_AT_RULE => conditions.push(("Media", prelude)),
+            CssSyntaxKind::CSS_SUPPORTS_AT_RULE => conditions.push(("Supports", prelude)),
+            CssSyntaxKind::CSS_CONTAINER_AT_RULE => conditions.push(("Container", prelude)),
+            CssSyntaxKind::CSS_SCOPE_AT_RULE => conditions.push(("Scope", prelude)),
+            _ => conditions.push(("Starting style", String::new())),
+        }
+    }
+    if at_rules.is_empty() {
+        return None;
+    }
+
+    let mut lines = vec![format!("**Cascade context** of `{}`\n", declaration.name)];
+    if !layers.is_empty() {
+        lines.push(format!("- Layer: `{}`", layers.join(".")));
+    }
+    for (label, condition) in conditions {
+        if condition.is_empty() {
+            lines.push(format!("- {label}"));
+        } else {
+            lines.push(format!("- {label}: `{condition}`"));
+        }
+    }
+    Some(lines.join("\n"))
+}
+
+/// Returns the names of the `@layer` blocks a node is nested in, outermost first, like
+/// `["base", "inner"]` for the `base.inner` layer.
+fn enclosing_layers(node: &CssSyntaxNode) -> Vec<String> {
+    let mut layers: Vec<String> = node
+        .ancestors()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_LAYER_AT_RULE)
+        .map(|at_rule| match at_rule_prelude(&at_rule) {
+            prelude if prelude.is_empty() => "<anonymous>".to_string(),
+            prelude => prelude,
+        })
+        .collect();
+    layers.reverse();
+    layers
+}
+
+/// Lists the layers of a stylesheet in cascade order, highlighting one, as Markdown.
+fn layer_order(layers: &[String], hovered: &str) -> String {
+    let mut lines = vec![
+        format!("**Cascade layer** `{hovered}`\n"),
+        "Layer order, from the lowest to the highest priority:\n".to_string(),
+    ];
+    lines.extend(layers.iter().enumerate().map(|(idx, layer)| {
+        if layer == hovered {
+            format!("{}. **`{layer}`**", idx + 1)
+        } else {
+            format!("{}. `{layer}`", idx + 1)
+        }
+    }));
+    lines.push("\nUnlayered styles win over every layer.".to_string());
+    lines.join("\n")
+}
+
+/// Explains what the `!important` flag of a declaration changes in the cascade, from the
+/// layer it is declared in, as Markdown.
+fn important_summary(declaration: &Declaration) -> String {
+    let mut lines = vec![
+        format!("**`!important`** on `{}`\n", declaration.name),
+        "Moves the declaration to the important cascade: it wins over every normal author \
+         declaration, whatever its layer, specificity or order, inline styles included."
+            .to_string(),
+    ];
+    let layers = enclosing_layers(&declaration.node);
+    if layers.is_empty() {
+        lines.push(
+            "Unlayered, it loses to the `!important` declarations of every cascade layer, as \
+             layer order is reversed for important declarations."
+                .to_string(),
+        );
+    } else {
+        let layer = layers.join(".");
+        lines.push(format!(
+            "In layer `{layer}`, it wins over the `!important` declarations of the layers \
+             declared after `{layer}` and of unlayered styles, as layer order is reversed for \
+             important declarations."
+        ));
+    }
+    lines.push(
+        "It still loses to `!important` inline styles, to the `!important` declarations of \
+         user and user-agent stylesheets, and to transitions."
+            .to_string(),
+    );
+    lines.push(
+        "Enable the opt-in `important-usage` rule to count the `!important` flags of each \
+         file."
+            .to_string(),
+    );
+    lines.join("\n\n")
+}
+
+/// Returns the prelude of an at-rule, between its keyword and its block, with whitespace
+/// collapsed.
+fn at_rule_prelude(at_rule: &CssSyntaxNode) -> String {
+    let mut tokens = at_rule.descendants_tokens(Direction::Next);
+    // The keyword, like `media`
+    tokens.next();
+    let prelude: Vec<String> = tokens
+        .take_while(|token| token.kind() != CssSyntaxKind::L_CURLY)
+        .map(|token| token.text().to_string())
+        .collect();
+    prelude
+        .concat()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Describes the target of an import, as Markdown.
+fn import_summary(
+    document: &TextDocument,
+    import: &Import,
+    file_system: &dyn FileSystemProvider,
+) -> String {
+    let title = format!("**{}** `{}`", import.kind.keyword(), import.target);
+    let dialect = Dialect::from_language_id(&document.language_id);
+    let resolved = document
+        .lsp_uri()
+        .and_then(|uri| resolve_import(&uri, &import.target, dialect, file_system));
+    let Some(resolved) = resolved else {
+        return if is_external(&import.target) {
+            format!("{title}\n\nExternal stylesheet, not resolved in the workspace.")
+        } else {
+            format!("{title}\n\nCan't be resolved relative to this document.")
+        };
+    };
+
+    let location = format!("`{}`", resolved.uri.as_str());
+    let Some(contents) = file_system.read_file(&resolved.uri) else {
+        return format!("{title}\n\n{location}\n\n⚠ File not found.");
+    };
+
+    let symbols = get_document_symbols(&contents);
+    let rule_count = count_symbols(&symbols);
+    let mut summary = format!(
+        "{title}\n\n{location}\n\n{} · {rule_count} {}",
+        format_size(contents.len()),
+        if rule_count == 1 { "rule" } else { "rules" }
+    );
+    if !symbols.is_empty() {
+        let listed: Vec<String> = symbols
+            .iter()
+            .take(MAX_LISTED_SYMBOLS)
+            .map(|symbol| format!("`{}`", symbol.name))
+            .collect();
+        summary.push_str(&format!("\n\nContributes: {}", listed.join(", ")));
+        if symbols.len() > MAX_LISTED_SYMBOLS {
+            summary.push_str(&format!(" (+{} more)", symbols.len() - MAX_LISTED_SYMBOLS));
+        }
+    }
+    summary
+}
+
+fn count_symbols(symbols: &[DocumentSymbol]) -> usize {
+    symbols
+        .iter()
+        .map(|symbol| 1 + count_symbols(symbol.children.as_deref().unwrap_or_default()))
+        .sum()
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{bytes} B")
+    } else {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    }
+}
+
+#[wasm_bindgen]
+pub fn get_hover_wasm(
+    document: &TextDocument,
+    position: &PositionWASM,
+    file_system: Option<JsFileSystemProvider>,
+) -> Option<HoverWASM> {
+    let hover = match file_system {
+        Some(file_system) => get_hover_with_file_system(document, position.into(), &file_system),
+        None => get_hover(document, position.into()),
+    };
+    hover.map(HoverWASM::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Uri;
+    use std::collections::HashMap;
+
+    struct MemoryFileSystem(HashMap<&'static str, &'static str>);
+
+    impl FileSystemProvider for MemoryFileSystem {
+        fn read_file(&self, uri: &Uri) -> Option<String> {
+            self.0
+                .get(uri.as_str())
+                .map(|contents| contents.to_string())
+        }
+    }
+
+    fn hover_contents(code: &str, position: Position) -> Option<String> {
+        let file_system = MemoryFileSystem(HashMap::from([(
+            "file:///p/base.css",
+            ".btn { color: red; }\n@media print {\n  .btn { color: black; }\n}\n",
+        )]));
+        let document = TextDocument::new("file:///p/main.css", "css", 1, code);
+        let hover = get_hover_with_file_system(&document, position, &file_system)?;
+        match hover.contents {
+            HoverContents::Markup(markup) => Some(markup.value),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_get_hover_import_summary() {
+        let contents = hover_contents("@import './base.css';\n", Position::new(0, 3)).unwrap();
+
+        assert!(contents.starts_with("**@import** `./base.css`"));
+        assert!(contents.contains("`file:///p/base.css`"));
+        assert!(contents.contains("63 B · 3 rules"));
+        assert!(contents.contains("Contributes: `.btn`, `@media print`"));
+    }
+
+    #[test]
+    fn test_get_hover_import_missing() {
+        let contents = hover_contents("@import url(missing.css);", Position::new(0, 15)).unwrap();
+        assert!(contents.contains("`file:///p/missing.css`"));
+        assert!(contents.contains("File not found"));
+    }
+
+    #[test]
+    fn test_get_hover_import_external() {
+        let contents =
+            hover_contents("@import 'https://cdn.test/a.css';", Position::new(0, 0)).unwrap();
+        assert!(contents.contains("External stylesheet"));
+    }
+
+    #[test]
+    fn test_get_hover_import_untitled() {
+        let document = TextDocument::new("untitled:Untitled-1", "css", 1, "@import 'a.css';");
+        let hover = get_hover_with_file_system(&document, Position::new(0, 1), &StdFileSystem);
+        let Some(HoverContents::Markup(markup)) = hover.map(|hover| hover.contents) else {
+            panic!("Expected a hover");
+        };
+        assert!(markup.value.contains("Can't be resolved"));
+    }
+
+    #[test]
+    fn test_get_hover_outside_imports() {
+        let code = "@import 'base.css';\na { color: red; }";
+        assert_eq!(hover_contents(code, Position::new(1, 4)), None);
+    }
+
+    #[test]
+    fn test_get_hover_cascade_context() {
+        let code = "@layer base {\n  @layer   inner {\n    @media (min-width: 600px) {\n      @supports (display: grid) and (not (display: inline-grid)) {\n        a { color: red; }\n      }\n    }\n  }\n}\n";
+        let contents = hover_contents(code, Position::new(4, 14)).unwrap();
+        assert_eq!(
+            contents,
+            "**Cascade context** of `color`\n\n- Layer: `base.inner`\n- Media: `(min-width: 600px)`\n- Supports: `(display: grid) and (not (display: inline-grid))`"
+        );
+    }
+
+    #[test]
+    fn test_get_hover_property_documentation() {
+        let code = "a { DISPLAY: flex; field-sizing: content; }";
+        let contents = hover_contents(code, Position::new(0, 6)).unwrap();
+        assert_eq!(
+            contents,
+            "**`display`**\n\nHow an element generates boxes, and how its children are laid out.\n\n- Syntax: `[ <display-outside> || <display-inside> ] | <display-listitem> | <display-internal> | <display-box> | <display-legacy>`\n- Initial value: `inline`"
+        );
+        let contents = hover_contents(code, Position::new(0, 22)).unwrap();
+        assert!(contents.ends_with("- Initial value: `fixed`\n- Status: experimental"));
+        assert!(
+            hover_contents(code, Position::new(0, 15)).is_none(),
+            "Values don't show the documentation of their property"
+        );
+
+        let data = crate::data::properties::CssDataBuilder::empty()
+            .property("--gap")
+            .syntax("<length>")
+            .build();
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { --gap: 1px }");
+        let hover = get_hover_with_data(
+            &document,
+            Position::new(0, 5),
+            &StdFileSystem,
+            &HoverOptions::default(),
+            &data,
+        )
+        .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected Markdown");
+        };
+        assert_eq!(markup.value, "**`--gap`**\n- Syntax: `<length>`");
+    }
+
+    #[test]
+    fn test_get_hover_at_rules() {
+        let code = "@SUPPORTS (display: grid) {}\n@font-face {\n  font-display: swap;\n  font-style: italic;\n  color: red;\n}\n@property --x { syntax: '<length>'; }";
+        let contents = hover_contents(code, Position::new(0, 3)).unwrap();
+        assert_eq!(
+            contents,
+            "**`@supports`**\n\nApplies styles depending on whether the browser supports a feature.\n\n- Syntax: `@supports <supports-condition> { <rule-list> }`"
+        );
+        assert!(hover_contents(code, Position::new(0, 12)).is_none());
+
+        let contents = hover_contents(code, Position::new(2, 4)).unwrap();
+        assert!(contents.starts_with("**`font-display`** descriptor of `@font-face`"));
+        assert!(contents.contains("- Syntax: `auto | block | swap | fallback | optional`"));
+        let contents = hover_contents(code, Position::new(3, 4)).unwrap();
+        assert!(contents.starts_with("**`font-style`** descriptor of `@font-face`"));
+        assert!(hover_contents(code, Position::new(4, 4)).is_none());
+        let contents = hover_contents(code, Position::new(6, 17)).unwrap();
+        assert!(contents.starts_with("**`syntax`** descriptor of `@property`"));
+        assert!(hover_contents("@unknown x {}", Position::new(0, 2)).is_none());
+    }
+
+    #[test]
+    fn test_get_hover_important() {
+        let code =
+            "@layer base {\n  a { color: red !important; }\n}\nb { color: blue ! IMPORTANT }";
+        let contents = hover_contents(code, Position::new(1, 21)).unwrap();
+        assert!(contents.starts_with("**`!important`** on `color`"));
+        assert!(contents.contains("In layer `base`, it wins over"));
+        assert!(contents.contains("`important-usage`"));
+
+        let contents = hover_contents(code, Position::new(3, 22)).unwrap();
+        assert!(contents.contains("Unlayered, it loses"));
+        assert!(
+            !hover_contents(code, Position::new(1, 12))
+                .unwrap()
+                .contains("`!important`"),
+            "Hovering the rest of the declaration shows its cascade context"
+        );
+    }
+
+    #[test]
+    fn test_get_hover_import_layer() {
+        let code = "@import url(a.css) layer(base.x);\n@layer reset, base;";
+        let contents = hover_contents(code, Position::new(0, 27)).unwrap();
+        assert_eq!(
+            contents,
+            "**Cascade layer** `base.x`\n\nLayer order, from the lowest to the highest priority:\n\n1. `base`\n2. **`base.x`**\n3. `reset`\n\nUnlayered styles win over every layer."
+        );
+    }
+
+    #[test]
+    fn test_get_hover_cascade_context_anonymous_layer() {
+        let code = "@layer {\n  @container card (width > 10em) {\n    a { color: red; }\n  }\n}";
+        let contents = hover_contents(code, Position::new(2, 8)).unwrap();
+        assert!(contents.contains("- Layer: `<anonymous>`"));
+        assert!(contents.contains("- Container: `card (width > 10em)`"));
+    }
+
+    #[test]
+    fn test_get_hover_computed_style() {
+        let file_system = MemoryFileSystem(HashMap::from([(
+            "file:///p/base.css",
+            ".btn { color: red; }\n@media print {\n  .btn { color: black; }\n}\n",
+        )]));
+        let document = TextDocument::new(
+            "file:///p/main.css",
+            "css",
+            1,
+            "@import './base.css';\na.btn, b { padding: 0; }",
+        );
+        let options = HoverOptions {
+            computed_style: true,
+            ..Default::default()
+        };
+        let hover =
+            get_hover_with_options(&document, Position::new(1, 3), &file_system, &options).unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected Markdown");
+        };
+        assert_eq!(
+            markup.value,
+            "[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): (0, 1, 1)\n\n```html\n<a class=\"btn\">\n```\n\n---\n\n**Computed style** of `a.btn`\n\n- `color: red` from `.btn` (0, 1, 0) in `file:///p/base.css`\n- `padding: 0` from `a.btn` (0, 1, 1)"
+        );
+        assert_eq!(
+            hover.range,
+            Some(lsp_types::Range::new(
+                Position::new(1, 0),
+                Position::new(1, 5)
+            ))
+        );
+        let hover = get_hover_with_file_system(&document, Position::new(1, 3), &file_system);
+        let Some(HoverContents::Markup(markup)) = hover.map(|hover| hover.contents) else {
+            panic!("Expected a hover");
+        };
+        assert!(!markup.value.contains("Computed style"));
+    }
+
+    #[test]
+    fn test_get_hover_selector() {
+        let code = "ul > li.active, #main {}
+@media print { a:hover { color: red; } }";
+        assert_eq!(
+            hover_contents(code, Position::new(0, 8)).unwrap(),
+            "[Selector Specificity](https://developer.mozilla.org/docs/Web/CSS/Specificity): (0, 1, 2)\n\n```html\n<ul>\n  <li class=\"active\">\n```"
+        );
+        assert!(hover_contents(code, Position::new(0, 17))
+            .unwrap()
+            .contains("(1, 0, 0)"));
+        assert!(hover_contents(code, Position::new(1, 16))
+            .unwrap()
+            .contains("<a>"));
+    }
+
+    #[test]
+    fn test_get_hover_animation_longhands() {
+        let code = "@media (hover) {\n  a { animation: spin 1s infinite, fade 2s; }\n}";
+        let contents = hover_contents(code, Position::new(1, 8)).unwrap();
+        assert!(contents.starts_with("**Longhands** of `animation`\n\n- `animation-name: spin, fade`\n- `animation-duration: 1s, 2s`"));
+        assert!(contents.contains("- `animation-iteration-count: infinite, 1`"));
+        assert!(
+            contents.ends_with("---\n\n**Cascade context** of `animation`\n\n- Media: `(hover)`")
+        );
+    }
+
+    #[test]
+    fn test_get_hover_easing() {
+        let code = "a { transition: opacity 1s ease-in; transition-timing-function: steps(2, jump-none); background: linear-gradient(red, blue); }";
+        let contents = hover_contents(code, Position::new(0, 30)).unwrap();
+        assert!(contents
+            .starts_with("**Easing** `ease-in`\n\nStarts slowly and ends quickly.\n\n```text\n▁▁"));
+        let contents = hover_contents(code, Position::new(0, 70)).unwrap();
+        assert!(contents.contains(
+            "Moves in 2 steps, holding its start and end values.\n\n```text\n▁▁▁▁▁▁▁▁████████\n```"
+        ));
+        assert_eq!(hover_contents(code, Position::new(0, 105)), None);
+    }
+
+    #[test]
+    fn test_get_hover_plaintext() {
+        let document = TextDocument::new(
+            "file:///p/main.css",
+            "css",
+            1,
+            "@media print {\n  a { transition: color 1s ease; }\n}",
+        );
+        let options = HoverOptions {
+            prefer_plaintext: true,
+            ..Default::default()
+        };
+        let hover =
+            get_hover_with_options(&document, Position::new(1, 28), &StdFileSystem, &options)
+                .unwrap();
+        let HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup");
+        };
+        assert_eq!(markup.kind, MarkupKind::PlainText);
+        assert_eq!(
+            markup.value,
+            "Easing ease\n\nStarts slowly and ends slowly.\n\n▁▁▂▃▄▅▆▆▇▇▇█████\n\n\n\nCascade context of transition\n\n- Media: print"
+        );
+    }
+}
@@ -0,0 +1,137 @@
+use crate::features::folding::find_blocks;
+use crate::line_index::LineIndex;
+use crate::types::PositionWASM;
+use lsp_types::{InlayHint, InlayHintLabel};
+use wasm_bindgen::prelude::*;
+
+/// Blocks spanning fewer lines than this don't get a closing brace hint by default.
+pub const DEFAULT_MIN_BLOCK_LINES: u32 = 25;
+
+/// Represents an inlay hint in the CSS code.
+#[wasm_bindgen(js_name = InlayHint)]
+pub struct InlayHintWASM(InlayHint);
+
+#[wasm_bindgen(js_class = InlayHint)]
+impl InlayHintWASM {
+    #[wasm_bindgen(getter)]
+    pub fn position(&self) -> PositionWASM {
+        self.0.position.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn label(&self) -> String {
+        match &self.0.label {
+            InlayHintLabel::String(label) => label.clone(),
+            InlayHintLabel::LabelParts(parts) => {
+                parts.iter().map(|part| part.value.as_str()).collect()
+            }
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn padding_left(&self) -> Option<bool> {
+        self.0.padding_left
+    }
+}
+
+impl From<InlayHint> for InlayHintWASM {
+    fn from(inlay_hint: InlayHint) -> Self {
+        InlayHintWASM(inlay_hint)
+    }
+}
+
+/// Computes the inlay hints shown after the closing brace of long blocks.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `min_lines` - The minimum number of lines a block must span to get a hint.
+///
+/// # Returns
+///
+/// * A vector of `InlayHint` labelled with the selector or at-rule each brace closes.
+pub fn get_inlay_hints(source: &str, min_lines: u32) -> Vec<InlayHint> {
+    let line_index = LineIndex::new(source);
+
+    find_blocks(source)
+        .into_iter()
+        .filter(|block| {
+            !block.prelude.is_empty() && block.end_line - block.start_line + 1 >= min_lines as usize
+        })
+        .map(|block| InlayHint {
+            position: line_index.position(source, block.close_offset + 1),
+            label: InlayHintLabel::String(format!("/* {} */", block.prelude)),
+            kind: None,
+            text_edits: None,
+            tooltip: None,
+            padding_left: Some(true),
+            padding_right: None,
+            data: None,
+        })
+        .collect()
+}
+
+#[wasm_bindgen]
+pub fn get_inlay_hints_wasm(source: &str, min_lines: Option<u32>) -> Vec<InlayHintWASM> {
+    let inlay_hints = get_inlay_hints(source, min_lines.unwrap_or(DEFAULT_MIN_BLOCK_LINES));
+    inlay_hints.into_iter().map(InlayHintWASM::from).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    fn label(hint: &InlayHint) -> &str {
+        match &hint.label {
+            InlayHintLabel::String(label) => label,
+            InlayHintLabel::LabelParts(_) => panic!("Expected a string label"),
+        }
+    }
+
+    #[test]
+    fn test_get_inlay_hints_short_block() {
+        let code = "body {\n    margin: 0;\n}\n";
+        let inlay_hints = get_inlay_hints(code, 5);
+
+        assert!(
+            inlay_hints.is_empty(),
+            "No inlay hints expected for blocks shorter than the threshold"
+        );
+    }
+
+    #[test]
+    fn test_get_inlay_hints_long_block() {
+        let code = "@media (max-width: 600px) {\n  body {\n    margin: 0;\n  }\n}\n";
+        let inlay_hints = get_inlay_hints(code, 5);
+
+        assert_eq!(inlay_hints.len(), 1, "Expected one inlay hint");
+        let hint = &inlay_hints[0];
+        assert_eq!(label(hint), "/* @media (max-width: 600px) */");
+        assert_eq!(
+            hint.position,
+            Position::new(4, 1),
+            "Hint should sit right after the closing brace"
+        );
+    }
+
+    #[test]
+    fn test_get_inlay_hints_nested_blocks() {
+        let code = "@media screen {\n  .card,\n  .panel {\n    margin: 0;\n  }\n}\n";
+        let mut inlay_hints = get_inlay_hints(code, 2);
+        inlay_hints.sort_by_key(|hint| hint.position.line);
+
+        assert_eq!(inlay_hints.len(), 2, "Expected two inlay hints");
+        assert_eq!(label(&inlay_hints[0]), "/* .card, .panel */");
+        assert_eq!(label(&inlay_hints[1]), "/* @media screen */");
+    }
+
+    #[test]
+    fn test_get_inlay_hints_ignores_comments_in_prelude() {
+        let code = "/* Layout */\nbody {\n  margin: 0;\n}\n";
+        let inlay_hints = get_inlay_hints(code, 2);
+
+        assert_eq!(inlay_hints.len(), 1, "Expected one inlay hint");
+        assert_eq!(label(&inlay_hints[0]), "/* body */");
+    }
+}
@@ -0,0 +1,346 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case};
+use crate::data::property_names::split_vendor_prefix;
+use crate::data::values::CSS_WIDE_KEYWORDS;
+use crate::identifiers::unescape_identifier;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, parse_css, split_list};
+use crate::shorthands::parse_animation;
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Location, Position, Uri};
+use std::ops::Range;
+
+/// Where the name of an animation is written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyframesReferenceKind {
+    /// In the prelude of `@keyframes`, defining the animation.
+    Definition,
+    /// In the value of `animation-name` or of the `animation` shorthand.
+    Usage,
+}
+
+/// The name of an animation written in a stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyframesReference {
+    /// The name, without quotes and with its escapes replaced, as `"slide"` and `slide`
+    /// name the same animation.
+    pub name: String,
+    /// The byte range of the name as written, quotes included.
+    pub range: Range<usize>,
+    pub kind: KeyframesReferenceKind,
+}
+
+/// Finds the names of the animations of a stylesheet, in `@keyframes` preludes and in
+/// `animation-name` and `animation` declarations, vendor-prefixed or not.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `KeyframesReference`, in source order.
+pub fn find_keyframes_references(source: &str) -> Vec<KeyframesReference> {
+    let root = parse_css(source).syntax();
+    let mut references: Vec<KeyframesReference> = root
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_KEYFRAMES_AT_RULE)
+        .filter_map(|at_rule| {
+            let name = at_rule.children().find(|node| {
+                matches!(
+                    node.kind(),
+                    CssSyntaxKind::CSS_CUSTOM_IDENTIFIER | CssSyntaxKind::CSS_STRING
+                )
+            })?;
+            let range = name.text_trimmed_range();
+            Some(KeyframesReference {
+                name: animation_name(&name.text_trimmed().to_string()),
+                range: range.start().into()..range.end().into(),
+                kind: KeyframesReferenceKind::Definition,
+            })
+        })
+        .collect();
+
+    for declaration in find_declarations(&root) {
+        let property = split_vendor_prefix(&declaration.name)
+            .map_or(declaration.name.as_str(), |(_, property)| property);
+        let names: Vec<(String, Range<usize>)> = if eq_ignore_case(property, "animation-name") {
+            split_list(&declaration.value, declaration.value_range.start)
+                .into_iter()
+                .map(|(name, range)| (name.to_string(), range))
+                .collect()
+        } else if eq_ignore_case(property, "animation") {
+            // The name is picked out of the other parts of the shorthand
+            parse_animation(&declaration.value, declaration.value_range.start)
+                .ok()
+                .flatten()
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|layer| layer.name)
+                .map(|name| (name.text, name.range))
+                .collect()
+        } else {
+            continue;
+        };
+        for (name, range) in names {
+            if name.is_empty()
+                || eq_ignore_case(&name, "none")
+                || contains_ignore_case(CSS_WIDE_KEYWORDS, &name)
+            {
+                continue;
+            }
+            references.push(KeyframesReference {
+                name: animation_name(&name),
+                range,
+                kind: KeyframesReferenceKind::Usage,
+            });
+        }
+    }
+    references.sort_by_key(|reference| reference.range.start);
+    references
+}
+
+/// Finds the `@keyframes` defining the animation named at a position.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on an animation name of `animation-name` or
+///   `animation`.
+/// * `stylesheets` - The URIs and sources of the other stylesheets, like the indexed ones.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Location` of the name of each `@keyframes` defining the animation, those of the
+///   document first, then those of the other stylesheets by URI.
+pub fn get_keyframes_definitions(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    stylesheets: &[(Uri, &str)],
+    encoding: PositionEncoding,
+) -> Vec<Location> {
+    let offset = LineIndex::with_encoding(source, encoding).offset(source, position);
+    let Some(name) = find_keyframes_references(source)
+        .into_iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)
+        .filter(|reference| reference.kind == KeyframesReferenceKind::Usage)
+        .map(|reference| reference.name)
+    else {
+        return Vec::new();
+    };
+    let mut others: Vec<(&Uri, &str)> = stylesheets
+        .iter()
+        .filter(|(other, _)| other != uri)
+        .map(|(other, source)| (other, *source))
+        .collect();
+    others.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut locations = Vec::new();
+    for (document, source) in std::iter::once((uri, source)).chain(others) {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        locations.extend(
+            find_keyframes_references(source)
+                .into_iter()
+                .filter(|reference| {
+                    reference.kind == KeyframesReferenceKind::Definition && reference.name == name
+                })
+                .map(|reference| Location {
+                    uri: document.clone(),
+                    range: line_index.range(source, reference.range.start, reference.range.end),
+                }),
+        );
+    }
+    locations
+}
+
+/// Finds the references to the animation named at a position: its `@keyframes` and the
+/// declarations using it, in the document and in the other stylesheets.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` of the document.
+/// * `source` - The source code of the document.
+/// * `position` - The `Position` of the cursor, on the name of a `@keyframes` or on an
+///   animation name of `animation-name` or `animation`.
+/// * `stylesheets` - The URIs and sources of the other stylesheets, like the indexed ones
+///   and the open documents.
+/// * `include_declaration` - Whether to include the `@keyframes` defining the animation.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `Location` of each reference, sorted by URI then in source order, empty if the
+///   cursor isn't on an animation name.
+pub fn get_keyframes_references(
+    uri: &Uri,
+    source: &str,
+    position: Position,
+    stylesheets: &[(Uri, &str)],
+    include_declaration: bool,
+    encoding: PositionEncoding,
+) -> Vec<Location> {
+    let offset = LineIndex::with_encoding(source, encoding).offset(source, position);
+    let Some(name) = find_keyframes_references(source)
+        .into_iter()
+        .find(|reference| reference.range.start <= offset && offset <= reference.range.end)
+        .map(|reference| reference.name)
+    else {
+        return Vec::new();
+    };
+    let mut documents: Vec<(&Uri, &str)> = stylesheets
+        .iter()
+        .filter(|(other, _)| other != uri)
+        .map(|(other, source)| (other, *source))
+        .collect();
+    documents.push((uri, source));
+    documents.sort_by(|a, b| a.0.cmp(b.0));
+
+    let mut locations = Vec::new();
+    for (document, source) in documents {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        locations.extend(
+            find_keyframes_references(source)
+                .into_iter()
+                .filter(|reference| {
+                    reference.name == name
+                        && (include_declaration || reference.kind == KeyframesReferenceKind::Usage)
+                })
+                .map(|reference| Location {
+                    uri: document.clone(),
+                    range: line_index.range(source, reference.range.start, reference.range.end),
+                }),
+        );
+    }
+    locations
+}
+
+/// Returns the name of an animation as written, a string or an identifier.
+fn animation_name(text: &str) -> String {
+    let unquoted = text
+        .strip_prefix(['"', '\''])
+        .and_then(|text| text.strip_suffix(['"', '\'']));
+    match unquoted {
+        Some(name) => name.to_string(),
+        None => unescape_identifier(text).into_owned(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_keyframes_references() {
+        let code = "@keyframes slide {}\n@keyframes \"fade\" {}\na { animation-name: fade, none, sl\\69 de; }";
+        let references: Vec<(String, KeyframesReferenceKind)> = find_keyframes_references(code)
+            .into_iter()
+            .map(|reference| (reference.name, reference.kind))
+            .collect();
+        use KeyframesReferenceKind::*;
+        assert_eq!(
+            references,
+            vec![
+                ("slide".to_string(), Definition),
+                ("fade".to_string(), Definition),
+                ("fade".to_string(), Usage),
+                ("slide".to_string(), Usage),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_find_keyframes_references_in_shorthands() {
+        let code = "a { animation: 2s ease-in none slide, fade 1s infinite; -webkit-animation: none 1s; }\nb { -webkit-animation-name: 'fade'; animation: var(--motion); }";
+        let references: Vec<(String, &str)> = find_keyframes_references(code)
+            .into_iter()
+            .map(|reference| (reference.name, &code[reference.range]))
+            .collect();
+        assert_eq!(
+            references,
+            vec![
+                ("slide".to_string(), "slide"),
+                ("fade".to_string(), "fade"),
+                ("fade".to_string(), "'fade'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_keyframes_definitions() {
+        let uri: Uri = "file:///p/b.css".parse().unwrap();
+        let animations: Uri = "file:///p/a.css".parse().unwrap();
+        let code = "a { animation-name: slide; }\n@keyframes slide {}";
+        let stylesheets = [(animations, "@keyframes slide {}\n@keyframes Slide {}")];
+        let locations = get_keyframes_definitions(
+            &uri,
+            code,
+            Position::new(0, 22),
+            &stylesheets,
+            PositionEncoding::Utf16,
+        );
+        let found: Vec<(&str, u32)> = locations
+            .iter()
+            .map(|location| (location.uri.as_str(), location.range.start.line))
+            .collect();
+        assert_eq!(found, vec![("file:///p/b.css", 1), ("file:///p/a.css", 0)]);
+        assert!(get_keyframes_definitions(
+            &uri,
+            code,
+            Position::new(1, 13),
+            &stylesheets,
+            PositionEncoding::Utf16
+        )
+        .is_empty());
+        let code = "a { animation: slide 1s; }";
+        assert_eq!(
+            get_keyframes_definitions(
+                &uri,
+                code,
+                Position::new(0, 16),
+                &stylesheets,
+                PositionEncoding::Utf16
+            )
+            .len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_get_keyframes_references() {
+        let uri: Uri = "file:///p/b.css".parse().unwrap();
+        let animations: Uri = "file:///p/a.css".parse().unwrap();
+        let code = "@keyframes slide {}\na { animation: 1s slide; }\nb { animation-name: fade; }";
+        let stylesheets = [(animations, "a { animation-name: slide, fade; }")];
+        let found = |position, include_declaration| -> Vec<(String, u32, u32)> {
+            get_keyframes_references(
+                &uri,
+                code,
+                position,
+                &stylesheets,
+                include_declaration,
+                PositionEncoding::Utf16,
+            )
+            .into_iter()
+            .map(|location| {
+                (
+                    location.uri.to_string(),
+                    location.range.start.line,
+                    location.range.start.character,
+                )
+            })
+            .collect()
+        };
+        assert_eq!(
+            found(Position::new(0, 12), true),
+            vec![
+                ("file:///p/a.css".to_string(), 0, 20),
+                ("file:///p/b.css".to_string(), 0, 11),
+                ("file:///p/b.css".to_string(), 1, 18),
+            ]
+        );
+        assert_eq!(found(Position::new(1, 19), false).len(), 2);
+        assert!(found(Position::new(1, 5), true).is_empty());
+    }
+}
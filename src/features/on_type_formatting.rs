@@ -0,0 +1,367 @@
+use crate::features::completion::{find_completion_context, CompletionContext};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::parse_css;
+use crate::parser::tokenizer::{tokenize, TokenKind, Tokenizer};
+use crate::text_document::TextDocument;
+use crate::types::{PositionWASM, TextEditWASM};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_rowan::{TextSize, TokenAtOffset};
+use lsp_types::{Position, TextEdit};
+use wasm_bindgen::prelude::*;
+
+/// The characters triggering on-type formatting, to register with the client.
+pub const ON_TYPE_TRIGGER_CHARACTERS: &[&str] = &["{", "}", ";", "\n"];
+
+/// The settings of on-type formatting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct OnTypeFormattingOptions {
+    /// Whether typing `{` inserts the matching `}` when the block isn't closed.
+    pub close_blocks: bool,
+    /// Whether typing a newline after a declaration adds its missing semicolon.
+    pub add_missing_semicolons: bool,
+    /// Whether typing `;`, `}` or a newline re-indents the line of the cursor, by its
+    /// nesting level.
+    pub indent_lines: bool,
+    /// The width of an indentation level, in spaces.
+    pub tab_size: u32,
+    /// Whether to indent with spaces rather than tabs.
+    pub insert_spaces: bool,
+    /// The encoding of the character columns of the positions.
+    pub position_encoding: PositionEncoding,
+}
+
+impl Default for OnTypeFormattingOptions {
+    fn default() -> Self {
+        OnTypeFormattingOptions {
+            close_blocks: true,
+            add_missing_semicolons: false,
+            indent_lines: true,
+            tab_size: 2,
+            insert_spaces: true,
+            position_encoding: PositionEncoding::Utf16,
+        }
+    }
+}
+
+/// Computes the edits made after a character is typed, for clients relying on the server
+/// to structure the code as it is typed, without running the whole formatter.
+///
+/// After `{`, the matching `}` is inserted on its own line, at the indentation of the line
+/// of the `{`, when the parser finds the block, or a block around it, unclosed. After a
+/// newline, the semicolon of the declaration ending the previous line is added, if it is
+/// missing. After `;`, `}` or a newline, the line of the cursor is re-indented by its
+/// nesting level, a line starting with `}` at the level of the block it closes.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument`, with the character already typed.
+/// * `position` - The `Position` of the cursor, after the typed character.
+/// * `ch` - The typed character.
+/// * `options` - The `OnTypeFormattingOptions` enabling each edit.
+///
+/// # Returns
+///
+/// * A vector of `TextEdit`, in order, empty if there is nothing to do.
+pub fn get_on_type_formatting_edits(
+    document: &TextDocument,
+    position: Position,
+    ch: &str,
+    options: &OnTypeFormattingOptions,
+) -> Vec<TextEdit> {
+    let source = document.text.as_str();
+    let line_index = LineIndex::with_encoding(source, options.position_encoding);
+    let offset = line_index.offset(source, position);
+    let mut edits = Vec::new();
+    match ch {
+        "{" if options.close_blocks => edits.extend(close_block(source, offset, &line_index)),
+        "\n" | "\r\n" if options.add_missing_semicolons => {
+            edits.extend(add_missing_semicolon(source, offset, &line_index));
+        }
+        _ => {}
+    }
+    if matches!(ch, ";" | "}" | "\n" | "\r\n") && options.indent_lines {
+        edits.extend(indent_line(source, offset, &line_index, options));
+    }
+    edits
+}
+
+fn close_block(source: &str, offset: usize, line_index: &LineIndex) -> Option<TextEdit> {
+    if offset == 0 || !source[..offset].ends_with('{') {
+        return None;
+    }
+    // Only at the end of a line, so the `}` doesn't capture code after the cursor
+    let rest_of_line = source[offset..].split('\n').next().unwrap_or_default();
+    if !rest_of_line.trim().is_empty() {
+        return None;
+    }
+    let parse = parse_css(source);
+    let token = match parse
+        .syntax()
+        .token_at_offset(TextSize::try_from(offset - 1).ok()?)
+    {
+        TokenAtOffset::Single(token) => token,
+        TokenAtOffset::Between(_, right) => right,
+        TokenAtOffset::None => return None,
+    };
+    if token.kind() != CssSyntaxKind::L_CURLY {
+        return None;
+    }
+    // The parser pairs the new `{` with the next `}`, which may have closed an enclosing
+    // block until now: the block is unclosed if the parser finds any of them unclosed
+    let is_unclosed = |block: &CssSyntaxNode| {
+        let mut tokens = block
+            .children_with_tokens()
+            .filter_map(|element| element.into_token());
+        tokens.any(|token| token.kind() == CssSyntaxKind::L_CURLY)
+            && !block
+                .children_with_tokens()
+                .filter_map(|element| element.into_token())
+                .any(|token| token.kind() == CssSyntaxKind::R_CURLY)
+    };
+    if !token.parent()?.ancestors().any(|block| is_unclosed(&block)) {
+        return None;
+    }
+
+    let line_start = source[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let indentation: String = source[line_start..]
+        .chars()
+        .take_while(|&c| c == ' ' || c == '\t')
+        .collect();
+    let position = line_index.position(source, offset);
+    Some(TextEdit {
+        range: lsp_types::Range::new(position, position),
+        new_text: format!("\n{indentation}}}"),
+    })
+}
+
+fn add_missing_semicolon(source: &str, offset: usize, line_index: &LineIndex) -> Option<TextEdit> {
+    let line_start = source[..offset].rfind('\n')?;
+    let previous_line_end = source[..line_start].trim_end_matches('\r').len();
+    let previous_line_start = source[..previous_line_end]
+        .rfind('\n')
+        .map_or(0, |idx| idx + 1);
+    let previous_line = &source[previous_line_start..previous_line_end];
+    let declaration_end = previous_line_start + previous_line.trim_end().len();
+    // Selectors of nested rules, whose block is opened on the line or the next one
+    if previous_line.trim_end().ends_with('{') || source[offset..].trim_start().starts_with('{') {
+        return None;
+    }
+    let Some(CompletionContext::Value { value_start, .. }) =
+        find_completion_context(source, declaration_end)
+    else {
+        return None;
+    };
+    // The declaration starts on the previous line and has a value
+    let value = source.get(value_start..declaration_end)?.trim();
+    if value_start < previous_line_start || value.is_empty() {
+        return None;
+    }
+    let tokens: Vec<_> = tokenize(value, 0)
+        .into_iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let open = tokens
+        .iter()
+        .filter(|token| {
+            matches!(
+                token.kind,
+                TokenKind::LeftParen | TokenKind::Function { .. }
+            )
+        })
+        .count();
+    let closed = tokens
+        .iter()
+        .filter(|token| token.kind == TokenKind::RightParen)
+        .count();
+    let ends_early = tokens.last().is_some_and(|token| {
+        matches!(
+            token.kind,
+            TokenKind::Comma
+                | TokenKind::LeftParen
+                | TokenKind::Function { .. }
+                | TokenKind::Delim('/')
+        )
+    });
+    if open != closed || ends_early {
+        return None;
+    }
+    let position = line_index.position(source, declaration_end);
+    Some(TextEdit {
+        range: lsp_types::Range::new(position, position),
+        new_text: ";".to_string(),
+    })
+}
+
+/// Re-indents the line of an offset by its nesting level.
+fn indent_line(
+    source: &str,
+    offset: usize,
+    line_index: &LineIndex,
+    options: &OnTypeFormattingOptions,
+) -> Option<TextEdit> {
+    let line_start = source[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+    let line = source[line_start..].split('\n').next().unwrap_or_default();
+    let indentation_len = line.len() - line.trim_start_matches([' ', '\t']).len();
+    let content = &line[indentation_len..];
+    let mut depth = nesting_depth(&source[..line_start + indentation_len])?;
+    if content.starts_with('}') {
+        depth = depth.saturating_sub(1);
+    }
+    let unit = if options.insert_spaces {
+        " ".repeat(options.tab_size as usize)
+    } else {
+        "\t".to_string()
+    };
+    let indentation = unit.repeat(depth);
+    if line[..indentation_len] == indentation {
+        return None;
+    }
+    Some(TextEdit {
+        range: line_index.range(source, line_start, line_start + indentation_len),
+        new_text: indentation,
+    })
+}
+
+/// Counts the blocks open at the end of some code, skipping strings, comments and
+/// escapes.
+///
+/// # Returns
+///
+/// * The number of open blocks, or `None` if the code ends in a comment or a string, whose
+///   lines mustn't be re-indented.
+fn nesting_depth(code: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for token in Tokenizer::new(code, 0) {
+        match token.kind {
+            TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightBrace => depth = depth.saturating_sub(1),
+            _ if token.is_unterminated() => return None,
+            _ => {}
+        }
+    }
+    Some(depth)
+}
+
+#[wasm_bindgen]
+pub fn get_on_type_formatting_edits_wasm(
+    document: &TextDocument,
+    position: &PositionWASM,
+    ch: &str,
+    add_missing_semicolons: bool,
+    tab_size: u32,
+    insert_spaces: bool,
+) -> Vec<TextEditWASM> {
+    let options = OnTypeFormattingOptions {
+        add_missing_semicolons,
+        tab_size,
+        insert_spaces,
+        ..Default::default()
+    };
+    get_on_type_formatting_edits(document, position.into(), ch, &options)
+        .into_iter()
+        .map(TextEditWASM::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn type_char(code: &str, ch: &str) -> String {
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let document = TextDocument::new("file:///test.css", "css", 1, &code);
+        let line_index = LineIndex::new(&code);
+        let options = OnTypeFormattingOptions {
+            add_missing_semicolons: true,
+            ..Default::default()
+        };
+        let edits = get_on_type_formatting_edits(
+            &document,
+            line_index.position(&code, offset),
+            ch,
+            &options,
+        );
+        let mut result = code.clone();
+        for edit in edits.iter().rev() {
+            let start = line_index.offset(&code, edit.range.start);
+            let end = line_index.offset(&code, edit.range.end);
+            result.replace_range(start..end, &edit.new_text);
+        }
+        result
+    }
+
+    #[test]
+    fn test_close_block() {
+        assert_eq!(type_char("a {|", "{"), "a {\n}");
+        assert_eq!(
+            type_char("@media print {\n  .a {|\n}\n", "{"),
+            "@media print {\n  .a {\n  }\n}\n"
+        );
+        assert_eq!(type_char("a {|\n.b { }", "{"), "a {\n}\n.b { }");
+        assert_eq!(type_char("a {|}", "{"), "a {}", "Already closed");
+        assert_eq!(type_char("a {| color: red", "{"), "a { color: red");
+        assert_eq!(type_char("a { content: '{|' }", "{"), "a { content: '{' }");
+    }
+
+    #[test]
+    fn test_add_missing_semicolon() {
+        assert_eq!(
+            type_char("a {\n  color: red\n  |\n}", "\n"),
+            "a {\n  color: red;\n  \n}"
+        );
+        assert_eq!(
+            type_char("a {\n  color: red;\n  |", "\n"),
+            "a {\n  color: red;\n  "
+        );
+        assert_eq!(
+            type_char("a {\n  font-family: a,\n  |", "\n"),
+            "a {\n  font-family: a,\n  "
+        );
+        assert_eq!(type_char("a {\n  color:\n  |", "\n"), "a {\n  color:\n  ");
+        assert_eq!(type_char("a,\n|", "\n"), "a,\n");
+        assert_eq!(
+            type_char("a {\n  &:hover {\n    |\n  }\n}", "\n"),
+            "a {\n  &:hover {\n    \n  }\n}",
+            "Nested rule"
+        );
+        assert_eq!(
+            type_char("a {\n  b:hover\n  |{\n  }\n}", "\n"),
+            "a {\n  b:hover\n  {\n  }\n}",
+            "Nested rule opening its block on the next line"
+        );
+        assert_eq!(
+            type_char("a {\n  color: red\n|", "{"),
+            "a {\n  color: red\n"
+        );
+        assert_eq!(
+            type_char("a {\n  content: \"(\"\n  |", "\n"),
+            "a {\n  content: \"(\";\n  ",
+            "Parenthesis in a string"
+        );
+    }
+
+    #[test]
+    fn test_indent_line() {
+        assert_eq!(type_char("a {\ncolor: red;|", ";"), "a {\n  color: red;");
+        assert_eq!(
+            type_char("@media print {\n  a {\n    top: 0;\n      }|", "}"),
+            "@media print {\n  a {\n    top: 0;\n  }"
+        );
+        assert_eq!(type_char("a {\n  top: 0;\n  }|", "}"), "a {\n  top: 0;\n}");
+        assert_eq!(
+            type_char("a { content: '{'; }\n    b {}|", "}"),
+            "a { content: '{'; }\nb {}"
+        );
+        assert_eq!(type_char("/* a {\n    b;| */", ";"), "/* a {\n    b; */");
+        assert_eq!(
+            type_char("a { background: url({) }\n    b {}|", "}"),
+            "a { background: url({) }\nb {}"
+        );
+        assert_eq!(
+            type_char("a {\n  color: red\n|", "\n"),
+            "a {\n  color: red;\n  "
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::features::colors::get_document_colors;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::text_document::TextDocument;
+use lsp_types::{Color, Range, Uri};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// The CIE76 color difference under which two colors are considered the same, about the
+/// smallest difference noticeable side by side.
+pub const DEFAULT_DELTA_E_THRESHOLD: f32 = 2.3;
+
+/// A color written in a stylesheet.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorUsage {
+    pub uri: Uri,
+    pub range: Range,
+    /// The color as written, like `#FFF`.
+    pub text: String,
+    pub color: Color,
+}
+
+/// Colors of a palette close enough to be unified.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColorCluster {
+    /// The most used color of the cluster, as written.
+    pub representative: String,
+    /// Every usage of the colors of the cluster, by stylesheet and position.
+    pub usages: Vec<ColorUsage>,
+}
+
+/// Extracts the colors of stylesheets and clusters the near-identical ones, for design
+/// consistency cleanups.
+///
+/// Colors are compared by their CIE76 difference in the CIELAB space, and their alpha:
+/// colors join the cluster of the most used color they are close to.
+///
+/// # Arguments
+///
+/// * `stylesheets` - The URIs and sources of the stylesheets.
+/// * `threshold` - The largest difference between colors of a cluster, like
+///   `DEFAULT_DELTA_E_THRESHOLD`.
+/// * `encoding` - The `PositionEncoding` of the ranges.
+///
+/// # Returns
+///
+/// * The clusters written in more than one way, most used first. Colors written the same
+///   way everywhere aren't reported.
+pub fn get_color_palette(
+    stylesheets: &[(Uri, &str)],
+    threshold: f32,
+    encoding: PositionEncoding,
+) -> Vec<ColorCluster> {
+    let mut usages: Vec<ColorUsage> = Vec::new();
+    for (uri, source) in stylesheets {
+        let line_index = LineIndex::with_encoding(source, encoding);
+        for information in get_document_colors(source) {
+            let start = line_index.offset(source, information.range.start);
+            let end = line_index.offset(source, information.range.end);
+            usages.push(ColorUsage {
+                uri: uri.clone(),
+                range: information.range,
+                text: source[start..end].to_string(),
+                color: information.color,
+            });
+        }
+    }
+
+    // The distinct colors as written, case aside, most used first
+    let mut written: Vec<(String, Color, usize)> = Vec::new();
+    for usage in &usages {
+        let key = usage.text.to_ascii_lowercase();
+        match written.iter_mut().find(|(text, _, _)| *text == key) {
+            Some((_, _, count)) => *count += 1,
+            None => written.push((key, usage.color, 1)),
+        }
+    }
+    written.sort_by_key(|(_, _, count)| std::cmp::Reverse(*count));
+
+    let mut clusters: Vec<(Color, ColorCluster)> = Vec::new();
+    for (text, color, _) in written {
+        let members = usages
+            .iter()
+            .filter(|usage| usage.text.eq_ignore_ascii_case(&text))
+            .cloned();
+        match clusters
+            .iter_mut()
+            .find(|(representative, _)| delta_e(representative, &color) <= threshold)
+        {
+            Some((_, cluster)) => cluster.usages.extend(members),
+            None => {
+                let usages: Vec<ColorUsage> = members.collect();
+                let representative = usages[0].text.clone();
+                clusters.push((
+                    color,
+                    ColorCluster {
+                        representative,
+                        usages,
+                    },
+                ));
+            }
+        }
+    }
+    clusters
+        .into_iter()
+        .map(|(_, cluster)| cluster)
+        .filter(|cluster| {
+            cluster
+                .usages
+                .iter()
+                .any(|usage| !usage.text.eq_ignore_ascii_case(&cluster.representative))
+        })
+        .collect()
+}
+
+/// Returns the CIE76 difference between two colors, with the difference of their alpha
+/// scaled to the same range.
+pub fn delta_e(a: &Color, b: &Color) -> f32 {
+    let (l1, a1, b1) = to_lab(a);
+    let (l2, a2, b2) = to_lab(b);
+    let alpha = (a.alpha - b.alpha) * 100.0;
+    ((l1 - l2).powi(2) + (a1 - a2).powi(2) + (b1 - b2).powi(2) + alpha.powi(2)).sqrt()
+}
+
+/// Converts an sRGB color to CIELAB, under the D65 illuminant.
+fn to_lab(color: &Color) -> (f32, f32, f32) {
+    let linear = |channel: f32| {
+        if channel <= 0.04045 {
+            channel / 12.92
+        } else {
+            ((channel + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    let (r, g, b) = (linear(color.red), linear(color.green), linear(color.blue));
+    let x = (0.4124 * r + 0.3576 * g + 0.1805 * b) / 0.95047;
+    let y = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+    let z = (0.0193 * r + 0.1192 * g + 0.9505 * b) / 1.08883;
+    let f = |t: f32| {
+        if t > 0.008856 {
+            t.cbrt()
+        } else {
+            7.787 * t + 16.0 / 116.0
+        }
+    };
+    let (fx, fy, fz) = (f(x), f(y), f(z));
+    (116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz))
+}
+
+/// Extracts the color palette of documents and clusters near-identical colors, see
+/// `get_color_palette`.
+///
+/// # Returns
+///
+/// * The `ColorCluster` list as JSON.
+#[wasm_bindgen]
+pub fn get_color_palette_wasm(documents: Vec<TextDocument>, threshold: Option<f32>) -> String {
+    let stylesheets: Vec<(Uri, &str)> = documents
+        .iter()
+        .filter_map(|document| Some((document.lsp_uri()?, document.text.as_str())))
+        .collect();
+    let threshold = threshold.unwrap_or(DEFAULT_DELTA_E_THRESHOLD);
+    let clusters = get_color_palette(&stylesheets, threshold, PositionEncoding::Utf16);
+    serde_json::to_string(&clusters).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_color_palette() {
+        let a: Uri = "file:///a.css".parse().unwrap();
+        let b: Uri = "file:///b.css".parse().unwrap();
+        let stylesheets = [
+            (
+                a.clone(),
+                "a { color: #3366ff; background: #000; }\nb { color: #3366FF; }",
+            ),
+            (
+                b.clone(),
+                "c { color: #3467fe; border-color: #000000fe; outline-color: #f00; }",
+            ),
+        ];
+        let clusters = get_color_palette(
+            &stylesheets,
+            DEFAULT_DELTA_E_THRESHOLD,
+            PositionEncoding::Utf16,
+        );
+        assert_eq!(clusters.len(), 2, "{clusters:?}");
+        assert_eq!(clusters[0].representative, "#3366ff");
+        let texts: Vec<_> = clusters[0]
+            .usages
+            .iter()
+            .map(|usage| usage.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["#3366ff", "#3366FF", "#3467fe"]);
+        assert_eq!(clusters[0].usages[2].uri, b);
+        assert_eq!(clusters[1].representative, "#000");
+        assert_eq!(clusters[1].usages.len(), 2);
+
+        assert!(get_color_palette(&stylesheets, 0.0, PositionEncoding::Utf16).is_empty());
+    }
+
+    #[test]
+    fn test_delta_e() {
+        let color = |red, green, blue| Color {
+            red,
+            green,
+            blue,
+            alpha: 1.0,
+        };
+        assert_eq!(delta_e(&color(0.2, 0.4, 1.0), &color(0.2, 0.4, 1.0)), 0.0);
+        assert!((delta_e(&color(0.0, 0.0, 0.0), &color(1.0, 1.0, 1.0)) - 100.0).abs() < 0.1);
+        assert!(delta_e(&color(1.0, 0.0, 0.0), &color(0.0, 1.0, 0.0)) > 100.0);
+    }
+}
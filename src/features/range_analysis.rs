@@ -0,0 +1,254 @@
+use crate::features::colors::{get_document_colors, ColorInformationWASM};
+use crate::features::diagnostics::{get_diagnostics, DiagnosticWASM};
+use crate::features::symbols::{get_document_symbols, DocumentSymbolWASM};
+use crate::line_index::LineIndex;
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use crate::text_document::TextDocument;
+use crate::types::RangeWASM;
+use lsp_types::{ColorInformation, Diagnostic, DocumentSymbol, Position, Range, Uri};
+use wasm_bindgen::prelude::*;
+
+/// The results of analyzing only the rules intersecting a range.
+#[derive(Debug, Default)]
+pub struct RangeAnalysis {
+    pub diagnostics: Vec<Diagnostic>,
+    pub symbols: Vec<DocumentSymbol>,
+    pub colors: Vec<ColorInformation>,
+}
+
+/// Represents the results of a range analysis.
+#[wasm_bindgen(js_name = RangeAnalysis)]
+pub struct RangeAnalysisWASM(RangeAnalysis);
+
+#[wasm_bindgen(js_class = RangeAnalysis)]
+impl RangeAnalysisWASM {
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> Vec<DiagnosticWASM> {
+        self.0
+            .diagnostics
+            .iter()
+            .cloned()
+            .map(DiagnosticWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn symbols(&self) -> Vec<DocumentSymbolWASM> {
+        self.0
+            .symbols
+            .iter()
+            .cloned()
+            .map(DocumentSymbolWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colors(&self) -> Vec<ColorInformationWASM> {
+        self.0
+            .colors
+            .iter()
+            .cloned()
+            .map(ColorInformationWASM::from)
+            .collect()
+    }
+}
+
+impl From<RangeAnalysis> for RangeAnalysisWASM {
+    fn from(range_analysis: RangeAnalysis) -> Self {
+        RangeAnalysisWASM(range_analysis)
+    }
+}
+
+/// Analyzes only the top-level rules intersecting a range of the CSS code.
+///
+/// The intersecting rules are parsed on their own, so the cost depends on the size of the
+/// range rather than the size of the document.
+///
+/// # Arguments
+///
+/// * `uri` - The URI of the document, used for related information.
+/// * `source` - The original CSS source code as a string slice.
+/// * `range` - The `Range` to analyze.
+///
+/// # Returns
+///
+/// * A `RangeAnalysis` with the diagnostics, symbols, and colors of the intersecting rules,
+///   positioned relative to the whole document.
+pub fn analyze_range(uri: &Uri, source: &str, range: Range) -> RangeAnalysis {
+    let line_index = LineIndex::new(source);
+    let range_start = line_index.offset(source, range.start);
+    let range_end = line_index.offset(source, range.end).max(range_start);
+
+    let statements = find_top_level_statements(source);
+    let intersecting: Vec<_> = statements
+        .iter()
+        .filter(|&&(start, end)| start <= range_end && end >= range_start)
+        .collect();
+    let (Some(&&(start, _)), Some(&&(_, end))) = (intersecting.first(), intersecting.last()) else {
+        return RangeAnalysis::default();
+    };
+
+    let fragment = &source[start..end];
+    let origin = line_index.position(source, start);
+
+    let mut diagnostics = get_diagnostics(uri, fragment);
+    for diagnostic in &mut diagnostics {
+        diagnostic.range = shift_range(diagnostic.range, origin);
+        for related in diagnostic.related_information.iter_mut().flatten() {
+            related.location.range = shift_range(related.location.range, origin);
+        }
+    }
+    let mut symbols = get_document_symbols(fragment);
+    for symbol in &mut symbols {
+        shift_symbol(symbol, origin);
+    }
+    let mut colors = get_document_colors(fragment);
+    for color in &mut colors {
+        color.range = shift_range(color.range, origin);
+    }
+
+    RangeAnalysis {
+        diagnostics,
+        symbols,
+        colors,
+    }
+}
+
+/// Splits the CSS code into the byte spans of its top-level statements, skipping over
+/// comments and strings.
+fn find_top_level_statements(source: &str) -> Vec<(usize, usize)> {
+    let mut statements = Vec::new();
+    let mut depth = 0usize;
+    let mut statement_start = 0;
+
+    for token in Tokenizer::new(source, 0) {
+        let ends_statement = match token.kind {
+            TokenKind::LeftBrace => {
+                depth += 1;
+                false
+            }
+            TokenKind::RightBrace if depth > 0 => {
+                depth -= 1;
+                depth == 0
+            }
+            TokenKind::Semicolon => depth == 0,
+            _ => false,
+        };
+        if ends_statement {
+            statements.push((statement_start, token.range.end));
+            statement_start = token.range.end;
+        }
+    }
+
+    if !source[statement_start..].trim().is_empty() {
+        statements.push((statement_start, source.len()));
+    }
+    statements
+}
+
+/// Moves a position found in a fragment to its place in the whole document.
+fn shift_position(position: Position, origin: Position) -> Position {
+    if position.line == 0 {
+        Position::new(origin.line, origin.character + position.character)
+    } else {
+        Position::new(origin.line + position.line, position.character)
+    }
+}
+
+fn shift_range(range: Range, origin: Position) -> Range {
+    Range::new(
+        shift_position(range.start, origin),
+        shift_position(range.end, origin),
+    )
+}
+
+fn shift_symbol(symbol: &mut DocumentSymbol, origin: Position) {
+    symbol.range = shift_range(symbol.range, origin);
+    symbol.selection_range = shift_range(symbol.selection_range, origin);
+    for child in symbol.children.iter_mut().flatten() {
+        shift_symbol(child, origin);
+    }
+}
+
+/// Analyzes only the top-level rules intersecting a range of a document, see
+/// `analyze_range`.
+///
+/// # Returns
+///
+/// * The `RangeAnalysis`, or an error if the URI of the document is invalid.
+#[wasm_bindgen]
+pub fn analyze_range_wasm(
+    document: &TextDocument,
+    range: &RangeWASM,
+) -> Result<RangeAnalysisWASM, JsError> {
+    let uri = document
+        .lsp_uri()
+        .ok_or_else(|| JsError::new(&format!("Invalid document URI: `{}`.", document.uri)))?;
+    Ok(analyze_range(&uri, &document.text, range.into()).into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn analyze(code: &str, range: Range) -> RangeAnalysis {
+        analyze_range(&"file:///test.css".parse().unwrap(), code, range)
+    }
+
+    #[test]
+    fn test_analyze_range_selects_intersecting_rules() {
+        let code = "a { color: #f00; }\nb {\n  color: #0f0;\n}\ni { color: #00f; }\n";
+        let analysis = analyze(code, Range::new(Position::new(2, 4), Position::new(2, 4)));
+
+        assert_eq!(
+            analysis.symbols.len(),
+            1,
+            "Expected only the rule at the cursor"
+        );
+        assert_eq!(analysis.symbols[0].name, "b");
+        assert_eq!(analysis.symbols[0].range.start, Position::new(1, 0));
+        assert_eq!(analysis.symbols[0].range.end, Position::new(3, 1));
+
+        assert_eq!(
+            analysis.colors.len(),
+            1,
+            "Expected only the color of that rule"
+        );
+        assert_eq!(analysis.colors[0].range.start, Position::new(2, 9));
+        assert_eq!(analysis.colors[0].color.green, 1.0);
+    }
+
+    #[test]
+    fn test_analyze_range_shifts_diagnostics() {
+        let code = "a { color: red; }\nb { color }\n";
+        let analysis = analyze(code, Range::new(Position::new(1, 0), Position::new(1, 1)));
+
+        assert!(!analysis.diagnostics.is_empty(), "Expected a syntax error");
+        assert_eq!(analysis.diagnostics[0].range.start.line, 1);
+        assert_eq!(analysis.symbols.len(), 1);
+    }
+
+    #[test]
+    fn test_analyze_range_spanning_rules() {
+        let code = "a { color: red; }\nb { color: red; }\ni { color: red; }\n";
+        let analysis = analyze(code, Range::new(Position::new(0, 5), Position::new(1, 5)));
+
+        assert_eq!(
+            analysis.symbols.len(),
+            2,
+            "Expected the two intersecting rules"
+        );
+        assert_eq!(analysis.symbols[1].range.start, Position::new(1, 0));
+    }
+
+    #[test]
+    fn test_find_top_level_statements_skips_strings_and_comments() {
+        let code = "@import \"a;b\";\n/* { */ a { content: \"}\"; }";
+        let statements = find_top_level_statements(code);
+
+        assert_eq!(statements.len(), 2, "Expected two statements");
+        assert_eq!(&code[statements[0].0..statements[0].1], "@import \"a;b\";");
+        let statements = find_top_level_statements("a { background: url(}); } b {}");
+        assert_eq!(statements.len(), 2, "Unquoted URLs should be skipped too");
+    }
+}
@@ -0,0 +1,132 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+/// A breakpoint of the design system, like `{ "name": "tablet", "minWidth": "768px" }`.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breakpoint {
+    pub name: String,
+    /// The minimum viewport width, with its unit.
+    pub min_width: String,
+}
+
+/// The `snippets` section of the settings, parameterizing the rule templates.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[non_exhaustive]
+pub struct SnippetSettings {
+    /// The breakpoints offered as `@media` templates, in order.
+    pub breakpoints: Vec<Breakpoint>,
+}
+
+/// A rule an editor can insert from an "Insert CSS snippet" command.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleTemplate {
+    /// A stable identifier, like `media-tablet`.
+    pub id: String,
+    /// The label of the quick pick item.
+    pub label: String,
+    /// A sentence describing the inserted rule.
+    pub description: String,
+    /// The rule, in the snippet syntax of the protocol, with tab stops like `${1:name}`.
+    pub snippet: String,
+}
+
+/// Lists the rule templates: a `@media` rule per configured breakpoint, then the
+/// `@keyframes` and `@font-face` skeletons.
+///
+/// # Arguments
+///
+/// * `settings` - The `SnippetSettings` giving the breakpoints.
+///
+/// # Returns
+///
+/// * A vector of `RuleTemplate`, in the order to show them.
+pub fn get_rule_templates(settings: &SnippetSettings) -> Vec<RuleTemplate> {
+    let mut templates: Vec<RuleTemplate> = settings
+        .breakpoints
+        .iter()
+        .map(|breakpoint| RuleTemplate {
+            id: format!("media-{}", breakpoint.name),
+            label: format!("@media {} ({})", breakpoint.name, breakpoint.min_width),
+            description: format!(
+                "Styles for viewports at least {} wide.",
+                breakpoint.min_width
+            ),
+            snippet: format!(
+                "@media (min-width: {}) {{\n\t$0\n}}",
+                escape_snippet(&breakpoint.min_width)
+            ),
+        })
+        .collect();
+    templates.push(RuleTemplate {
+        id: "keyframes".to_string(),
+        label: "@keyframes".to_string(),
+        description: "An animation, from its first to its last keyframe.".to_string(),
+        snippet: "@keyframes ${1:name} {\n\tfrom {\n\t\t$2\n\t}\n\tto {\n\t\t$0\n\t}\n}"
+            .to_string(),
+    });
+    templates.push(RuleTemplate {
+        id: "font-face".to_string(),
+        label: "@font-face".to_string(),
+        description: "A web font, with its family name and source.".to_string(),
+        snippet: "@font-face {\n\tfont-family: \"${1:family}\";\n\tsrc: url(\"${2:font.woff2}\") format(\"woff2\");\n\tfont-display: ${3:swap};\n}"
+            .to_string(),
+    });
+    templates
+}
+
+/// Escapes the characters with a meaning in the snippet syntax.
+fn escape_snippet(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('$', "\\$")
+        .replace('}', "\\}")
+}
+
+/// Lists the rule templates as JSON, see `get_rule_templates`.
+///
+/// # Arguments
+///
+/// * `settings` - The `snippets` section of the settings as JSON, like
+///   `{ "breakpoints": [{ "name": "tablet", "minWidth": "768px" }] }`.
+///
+/// # Returns
+///
+/// * The templates as JSON, or `undefined` if the settings are invalid.
+#[wasm_bindgen]
+pub fn get_rule_templates_wasm(settings: &str) -> Option<String> {
+    let settings: SnippetSettings = serde_json::from_str(settings).ok()?;
+    serde_json::to_string(&get_rule_templates(&settings)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_rule_templates() {
+        let settings: SnippetSettings = serde_json::from_str(
+            r#"{ "breakpoints": [{ "name": "tablet", "minWidth": "768px" }] }"#,
+        )
+        .unwrap();
+        let templates = get_rule_templates(&settings);
+        let ids: Vec<_> = templates
+            .iter()
+            .map(|template| template.id.as_str())
+            .collect();
+        assert_eq!(ids, vec!["media-tablet", "keyframes", "font-face"]);
+        assert_eq!(templates[0].label, "@media tablet (768px)");
+        assert_eq!(templates[0].snippet, "@media (min-width: 768px) {\n\t$0\n}");
+
+        assert_eq!(get_rule_templates(&SnippetSettings::default()).len(), 2);
+    }
+
+    #[test]
+    fn test_get_rule_templates_wasm() {
+        let json = get_rule_templates_wasm("{}").unwrap();
+        assert!(json.contains(r#""id":"keyframes""#));
+        assert!(json.contains(r#""snippet":"@keyframes ${1:name} {"#));
+        assert!(get_rule_templates_wasm(r#"{ "breakpoints": 1 }"#).is_none());
+    }
+}
@@ -0,0 +1,173 @@
+use crate::line_index::LineIndex;
+use crate::parser::parse_css;
+use crate::text_document::TextDocument;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode, CssSyntaxToken};
+use biome_rowan::{TextRange, TextSize};
+use lsp_types::{Position, SelectionRange};
+use wasm_bindgen::prelude::*;
+
+/// Computes the ranges "expand selection" walks through from each position, outward from
+/// the word under the cursor: value, declaration, block, rule, up to the whole stylesheet.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `positions` - The `Position`s of the cursors.
+///
+/// # Returns
+///
+/// * A vector of `SelectionRange`, one per position, each with its enclosing ranges as
+///   parents.
+pub fn get_selection_ranges(source: &str, positions: &[Position]) -> Vec<SelectionRange> {
+    let parse = parse_css(source);
+    let root = parse.syntax();
+    let line_index = LineIndex::new(source);
+
+    positions
+        .iter()
+        .map(|&position| {
+            let offset = line_index.offset(source, position);
+            // Sources over 4 GiB can't be parsed, positions past the limit are clamped to it
+            let offset = TextSize::try_from(offset).unwrap_or(TextSize::from(u32::MAX));
+            let mut ranges = enclosing_ranges(&root, offset);
+            // Between tokens, or in the trivia of the whole stylesheet
+            if ranges.is_empty() {
+                ranges.push(TextRange::empty(offset));
+            }
+            ranges
+                .into_iter()
+                .rev()
+                .fold(None, |parent, range| {
+                    Some(SelectionRange {
+                        range: line_index.range(source, range.start().into(), range.end().into()),
+                        parent: parent.map(Box::new),
+                    })
+                })
+                .expect("At least one range")
+        })
+        .collect()
+}
+
+/// Returns the distinct ranges of the token at an offset and of its ancestors, innermost
+/// first, skipping the ranges that don't contain the offset, like a token's trivia.
+fn enclosing_ranges(root: &CssSyntaxNode, offset: TextSize) -> Vec<TextRange> {
+    let Some(token) = token_at(root, offset) else {
+        return Vec::new();
+    };
+    let mut ranges: Vec<TextRange> = Vec::new();
+    let nodes = token
+        .parent()
+        .into_iter()
+        .flat_map(|parent| parent.ancestors())
+        // The at-rules without their `@`, like `media print { }`
+        .filter(|node| {
+            node.parent()
+                .is_none_or(|parent| parent.kind() != CssSyntaxKind::CSS_AT_RULE)
+        });
+    for range in std::iter::once(token.text_trimmed_range())
+        .chain(nodes.map(|node| node.text_trimmed_range()))
+    {
+        let is_new = ranges.last() != Some(&range);
+        if is_new && range.contains_inclusive(offset) {
+            ranges.push(range);
+        }
+    }
+    ranges
+}
+
+/// Finds the token at an offset, preferring the word the cursor touches over punctuation,
+/// like `red` rather than `;` in `color: red|;`.
+fn token_at(root: &CssSyntaxNode, offset: TextSize) -> Option<CssSyntaxToken> {
+    let tokens: Vec<CssSyntaxToken> = root
+        .token_at_offset(offset)
+        .filter(|token| token.kind() != CssSyntaxKind::EOF)
+        .collect();
+    let is_word = |token: &CssSyntaxToken| {
+        token
+            .text_trimmed()
+            .starts_with(|c: char| c.is_alphanumeric() || matches!(c, '-' | '_' | '#' | '.'))
+    };
+    let touches = |token: &CssSyntaxToken| token.text_trimmed_range().contains_inclusive(offset);
+    tokens
+        .iter()
+        .find(|token| touches(token) && is_word(token))
+        .or_else(|| tokens.iter().find(|token| touches(token)))
+        .or_else(|| tokens.first())
+        .cloned()
+}
+
+/// Computes the selection ranges of positions, see `get_selection_ranges`.
+///
+/// # Arguments
+///
+/// * `document` - The `TextDocument` to select in.
+/// * `positions` - The positions as JSON, like `[{ "line": 0, "character": 4 }]`.
+///
+/// # Returns
+///
+/// * The `SelectionRange` list as JSON, or `undefined` if the positions are invalid.
+#[wasm_bindgen]
+pub fn get_selection_ranges_wasm(document: &TextDocument, positions: &str) -> Option<String> {
+    let positions: Vec<Position> = serde_json::from_str(positions).ok()?;
+    serde_json::to_string(&get_selection_ranges(&document.text, &positions)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Returns the texts "expand selection" walks through from the `|` of `code`.
+    fn expand(code: &str) -> Vec<String> {
+        let offset = code.find('|').unwrap();
+        let code = code.replace('|', "");
+        let position = LineIndex::new(&code).position(&code, offset);
+        let line_index = LineIndex::new(&code);
+        let mut texts = Vec::new();
+        let mut selection = get_selection_ranges(&code, &[position]).into_iter().next();
+        while let Some(range) = selection {
+            let start = line_index.offset(&code, range.range.start);
+            let end = line_index.offset(&code, range.range.end);
+            texts.push(code[start..end].to_string());
+            selection = range.parent.map(|parent| *parent);
+        }
+        texts
+    }
+
+    #[test]
+    fn test_get_selection_ranges() {
+        let code = "@media print {\n  a, b {\n    margin: 0 au|to;\n  }\n}\n";
+        let texts = expand(code);
+        assert_eq!(texts[0], "auto");
+        assert_eq!(texts[1], "0 auto");
+        assert_eq!(texts[2], "margin: 0 auto");
+        assert_eq!(texts[3], "margin: 0 auto;");
+        assert_eq!(texts[4], "{\n    margin: 0 auto;\n  }");
+        assert_eq!(texts[5], "a, b {\n    margin: 0 auto;\n  }");
+        assert_eq!(
+            texts.last().unwrap(),
+            "@media print {\n  a, b {\n    margin: 0 auto;\n  }\n}"
+        );
+        for pair in texts.windows(2) {
+            assert!(
+                pair[1].contains(&pair[0]),
+                "Each range contains the previous one"
+            );
+            assert_ne!(pair[0], pair[1]);
+        }
+
+        assert_eq!(expand("a { color: red|; }")[0], "red");
+        assert_eq!(
+            expand("a { color: red; }|"),
+            vec!["}", "{ color: red; }", "a { color: red; }"]
+        );
+    }
+
+    #[test]
+    fn test_get_selection_ranges_wasm() {
+        let document = TextDocument::new("file:///a.css", "css", 1, "a {}");
+        let json =
+            get_selection_ranges_wasm(&document, r#"[{ "line": 0, "character": 0 }]"#).unwrap();
+        assert!(json.starts_with(r#"[{"range":{"start":{"line":0,"character":0}"#));
+        assert!(get_selection_ranges_wasm(&document, "{}").is_none());
+    }
+}
@@ -0,0 +1,357 @@
+use crate::data::properties::{CssData, PropertyStatus};
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::{find_declarations, parse_css};
+use biome_css_parser::CssParse;
+use lsp_types::{
+    Position, Range, SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokens,
+    SemanticTokensEdit, SemanticTokensLegend,
+};
+use wasm_bindgen::prelude::*;
+
+/// The token types, in the order of their index in `SemanticToken::token_type`.
+pub const TOKEN_TYPES: &[SemanticTokenType] =
+    &[SemanticTokenType::PROPERTY, SemanticTokenType::VARIABLE];
+
+/// The token modifiers, in the order of their bit in `SemanticToken::token_modifiers_bitset`.
+pub const TOKEN_MODIFIERS: &[SemanticTokenModifier] = &[
+    SemanticTokenModifier::new("vendorPrefixed"),
+    SemanticTokenModifier::DEPRECATED,
+    SemanticTokenModifier::new("experimental"),
+];
+
+const PROPERTY: u32 = 0;
+const VARIABLE: u32 = 1;
+
+const VENDOR_PREFIXED: u32 = 1 << 0;
+const DEPRECATED: u32 = 1 << 1;
+const EXPERIMENTAL: u32 = 1 << 2;
+
+/// Returns the legend of the semantic tokens, to advertise in the server capabilities.
+pub fn get_semantic_tokens_legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: TOKEN_MODIFIERS.to_vec(),
+    }
+}
+
+/// Computes the semantic tokens of the property names of a stylesheet, so themes can render
+/// categories of properties distinctly, like deprecated ones with a strikethrough.
+///
+/// Custom properties are variables, and other properties get the `vendorPrefixed`
+/// modifier when they start with a vendor prefix, and the `deprecated` or `experimental`
+/// modifier from the status of the property, or of the unprefixed property, in the data.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `data` - The `CssData` giving the status of properties.
+///
+/// # Returns
+///
+/// * The `SemanticTokens`, encoded relative to each other as in the protocol.
+pub fn get_semantic_tokens(source: &str, data: &CssData) -> SemanticTokens {
+    get_semantic_tokens_with_parse(source, parse_css(source), data, PositionEncoding::Utf16)
+}
+
+/// Computes the semantic tokens of a stylesheet from an existing parse of it and with the
+/// position encoding of the client, see `get_semantic_tokens`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `data` - The `CssData` giving the status of properties.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `SemanticTokens`, encoded relative to each other as in the protocol.
+pub fn get_semantic_tokens_with_parse(
+    source: &str,
+    parse: CssParse,
+    data: &CssData,
+    encoding: PositionEncoding,
+) -> SemanticTokens {
+    SemanticTokens {
+        result_id: None,
+        data: encode_relative(find_tokens(source, &parse, data, encoding)),
+    }
+}
+
+/// Computes the semantic tokens starting in a range of a stylesheet, like the visible part
+/// of a large document, see `get_semantic_tokens`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `data` - The `CssData` giving the status of properties.
+/// * `range` - The `Range` of the tokens to compute.
+///
+/// # Returns
+///
+/// * The `SemanticTokens`, the first one relative to the start of the document.
+pub fn get_semantic_tokens_in_range(source: &str, data: &CssData, range: Range) -> SemanticTokens {
+    get_semantic_tokens_in_range_with_parse(
+        source,
+        parse_css(source),
+        data,
+        range,
+        PositionEncoding::Utf16,
+    )
+}
+
+/// Computes the semantic tokens starting in a range of a stylesheet from an existing parse
+/// of it and with the position encoding of the client, see `get_semantic_tokens_in_range`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `data` - The `CssData` giving the status of properties.
+/// * `range` - The `Range` of the tokens to compute.
+/// * `encoding` - The `PositionEncoding` of the positions.
+///
+/// # Returns
+///
+/// * The `SemanticTokens`, the first one relative to the start of the document.
+pub fn get_semantic_tokens_in_range_with_parse(
+    source: &str,
+    parse: CssParse,
+    data: &CssData,
+    range: Range,
+    encoding: PositionEncoding,
+) -> SemanticTokens {
+    let tokens = find_tokens(source, &parse, data, encoding)
+        .into_iter()
+        .filter(|(token_range, _, _)| {
+            range.start <= token_range.start && token_range.start < range.end
+        })
+        .collect();
+    SemanticTokens {
+        result_id: None,
+        data: encode_relative(tokens),
+    }
+}
+
+/// Computes the edits turning the previous semantic tokens of a document into the current
+/// ones, so a client doesn't get the whole stream again after each keystroke.
+///
+/// The tokens are compared as the integers sent in the protocol, and their common start and
+/// end kept, so typing in a declaration usually yields a single small edit.
+///
+/// # Arguments
+///
+/// * `previous` - The tokens last sent to the client.
+/// * `current` - The tokens of the document now.
+///
+/// # Returns
+///
+/// * The `SemanticTokensEdit`, empty if the tokens didn't change.
+pub fn get_semantic_tokens_edits(
+    previous: &[SemanticToken],
+    current: &[SemanticToken],
+) -> Vec<SemanticTokensEdit> {
+    let prefix = previous
+        .iter()
+        .zip(current)
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let suffix = previous[prefix..]
+        .iter()
+        .rev()
+        .zip(current[prefix..].iter().rev())
+        .take_while(|(previous, current)| previous == current)
+        .count();
+    let deleted = previous.len() - prefix - suffix;
+    let inserted = &current[prefix..current.len() - suffix];
+    if deleted == 0 && inserted.is_empty() {
+        return Vec::new();
+    }
+    vec![SemanticTokensEdit {
+        // Offsets in the integers of the protocol, five per token
+        start: (prefix * 5) as u32,
+        delete_count: (deleted * 5) as u32,
+        data: Some(inserted.to_vec()),
+    }]
+}
+
+/// Finds the property names of a stylesheet, with their range, type and modifiers.
+fn find_tokens(
+    source: &str,
+    parse: &CssParse,
+    data: &CssData,
+    encoding: PositionEncoding,
+) -> Vec<(Range, u32, u32)> {
+    let line_index = LineIndex::with_encoding(source, encoding);
+    find_declarations(&parse.syntax())
+        .into_iter()
+        .map(|declaration| {
+            let name = declaration.name.as_str();
+            let (token_type, modifiers) = if name.starts_with("--") {
+                (VARIABLE, 0)
+            } else {
+                (PROPERTY, property_modifiers(name, data))
+            };
+            let range = line_index.range(
+                source,
+                declaration.name_range.start,
+                declaration.name_range.end,
+            );
+            (range, token_type, modifiers)
+        })
+        .collect()
+}
+
+/// Encodes tokens relative to each other, as in the protocol.
+fn encode_relative(tokens: Vec<(Range, u32, u32)>) -> Vec<SemanticToken> {
+    let mut previous = Position::default();
+    tokens
+        .into_iter()
+        .map(|(range, token_type, modifiers)| {
+            let delta_line = range.start.line - previous.line;
+            let delta_start = if delta_line == 0 {
+                range.start.character - previous.character
+            } else {
+                range.start.character
+            };
+            previous = range.start;
+            SemanticToken {
+                delta_line,
+                delta_start,
+                length: range.end.character - range.start.character,
+                token_type,
+                token_modifiers_bitset: modifiers,
+            }
+        })
+        .collect()
+}
+
+fn property_modifiers(name: &str, data: &CssData) -> u32 {
+    let unprefixed = vendor_unprefixed(name);
+    let mut modifiers = 0;
+    if unprefixed.is_some() {
+        modifiers |= VENDOR_PREFIXED;
+    }
+    let property = data
+        .property(name)
+        .or_else(|| unprefixed.and_then(|name| data.property(name)));
+    match property.map(|property| property.status) {
+        Some(PropertyStatus::Obsolete) => modifiers |= DEPRECATED,
+        Some(PropertyStatus::Experimental) => modifiers |= EXPERIMENTAL,
+        _ => {}
+    }
+    modifiers
+}
+
+/// Returns the property without its vendor prefix, like `transform` for `-webkit-transform`.
+fn vendor_unprefixed(name: &str) -> Option<&str> {
+    let rest = name.strip_prefix('-')?;
+    let (_, unprefixed) = rest.split_once('-')?;
+    (!rest.starts_with('-') && !unprefixed.is_empty()).then_some(unprefixed)
+}
+
+/// Computes the semantic tokens with the built-in data, see `get_semantic_tokens`.
+///
+/// # Returns
+///
+/// * The tokens encoded as in the protocol, five integers per token.
+#[wasm_bindgen]
+pub fn get_semantic_tokens_wasm(source: &str) -> Vec<u32> {
+    encode_semantic_tokens(&get_semantic_tokens(source, CssData::builtin()))
+}
+
+/// Returns the legend of the semantic tokens, as JSON.
+#[wasm_bindgen]
+pub fn get_semantic_tokens_legend_wasm() -> String {
+    serde_json::to_string(&get_semantic_tokens_legend()).unwrap_or_default()
+}
+
+/// Flattens semantic tokens to five integers per token, as sent in the protocol.
+pub(crate) fn encode_semantic_tokens(tokens: &SemanticTokens) -> Vec<u32> {
+    tokens
+        .data
+        .iter()
+        .flat_map(|token| {
+            [
+                token.delta_line,
+                token.delta_start,
+                token.length,
+                token.token_type,
+                token.token_modifiers_bitset,
+            ]
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::properties::CssDataBuilder;
+
+    #[test]
+    fn test_get_semantic_tokens() {
+        let code = "a {\n  -webkit-clip: auto; --x: 1; CLIP: auto;\n  field-sizing: content;\n  -moz-user-select: none; color: red;\n}";
+        let tokens: Vec<_> = get_semantic_tokens(code, CssData::builtin())
+            .data
+            .iter()
+            .map(|token| {
+                (
+                    token.delta_line,
+                    token.delta_start,
+                    token.length,
+                    token.token_type,
+                    token.token_modifiers_bitset,
+                )
+            })
+            .collect();
+        assert_eq!(
+            tokens,
+            vec![
+                (1, 2, 12, PROPERTY, VENDOR_PREFIXED | DEPRECATED),
+                (0, 20, 3, VARIABLE, 0),
+                (0, 8, 4, PROPERTY, DEPRECATED),
+                (1, 2, 12, PROPERTY, EXPERIMENTAL),
+                (1, 2, 16, PROPERTY, VENDOR_PREFIXED),
+                (0, 24, 5, PROPERTY, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_semantic_tokens_custom_data() {
+        let data = CssDataBuilder::new()
+            .property("color")
+            .status(PropertyStatus::Experimental)
+            .build();
+        let tokens = get_semantic_tokens("a { color: red }", &data);
+        assert_eq!(
+            encode_semantic_tokens(&tokens),
+            vec![0, 4, 5, PROPERTY, EXPERIMENTAL]
+        );
+        let legend = get_semantic_tokens_legend();
+        assert_eq!(legend.token_modifiers[1].as_str(), "deprecated");
+    }
+
+    #[test]
+    fn test_get_semantic_tokens_in_range() {
+        let code = "a { color: red; }\nb { --x: 1; top: 0 }\nc { left: 0 }";
+        let range = Range::new(Position::new(1, 0), Position::new(2, 0));
+        let tokens = get_semantic_tokens_in_range(code, CssData::builtin(), range);
+        assert_eq!(
+            encode_semantic_tokens(&tokens),
+            vec![1, 4, 3, VARIABLE, 0, 0, 8, 3, PROPERTY, 0]
+        );
+    }
+
+    #[test]
+    fn test_get_semantic_tokens_edits() {
+        let data = CssData::builtin();
+        let previous = get_semantic_tokens("a { top: 0; left: 0; right: 0 }", data).data;
+        let current = get_semantic_tokens("a { top: 0; --left: 0; right: 0 }", data).data;
+        let edits = get_semantic_tokens_edits(&previous, &current);
+        assert_eq!(edits.len(), 1);
+        assert_eq!((edits[0].start, edits[0].delete_count), (5, 10));
+        assert_eq!(edits[0].data.as_ref().unwrap(), &current[1..]);
+        assert!(get_semantic_tokens_edits(&current, &current).is_empty());
+    }
+}
@@ -0,0 +1,483 @@
+use crate::budget::{Budget, BudgetTracker, Budgeted};
+use crate::chunks::{run_in_chunks, Chunk};
+use crate::compat::CompatibilityMode;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::parse_css;
+use crate::types::RangeWASM;
+use biome_css_parser::CssParse;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use lsp_types::{DocumentSymbol, SymbolKind};
+use wasm_bindgen::prelude::*;
+
+/// Represents a document symbol in the CSS code.
+#[wasm_bindgen(js_name = DocumentSymbol)]
+pub struct DocumentSymbolWASM(DocumentSymbol);
+
+#[wasm_bindgen(js_class = DocumentSymbol)]
+impl DocumentSymbolWASM {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kind(&self) -> String {
+        match self.0.kind {
+            SymbolKind::MODULE => "module".to_string(),
+            SymbolKind::VARIABLE => "variable".to_string(),
+            _ => "class".to_string(),
+        }
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> RangeWASM {
+        self.0.range.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn selection_range(&self) -> RangeWASM {
+        self.0.selection_range.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn children(&self) -> Vec<DocumentSymbolWASM> {
+        self.0
+            .children
+            .iter()
+            .flatten()
+            .cloned()
+            .map(DocumentSymbolWASM::from)
+            .collect()
+    }
+}
+
+impl From<DocumentSymbol> for DocumentSymbolWASM {
+    fn from(document_symbol: DocumentSymbol) -> Self {
+        DocumentSymbolWASM(document_symbol)
+    }
+}
+
+/// Options for computing document symbols.
+#[derive(Debug, Clone, Default)]
+#[non_exhaustive]
+pub struct DocumentSymbolOptions {
+    /// Whose conventions the symbols follow.
+    pub compatibility: CompatibilityMode,
+    /// The encoding of the character columns of the ranges.
+    pub position_encoding: PositionEncoding,
+}
+
+/// Computes the hierarchical document symbols for the given CSS source code: rules named
+/// after their selectors, at-rules like `@media`, `@supports` or `@keyframes` containing the
+/// rules and keyframes of their block, and nested rules under their parent.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of top-level `DocumentSymbol`, with nested rules as their children.
+pub fn get_document_symbols(source: &str) -> Vec<DocumentSymbol> {
+    get_document_symbols_with_options(source, &DocumentSymbolOptions::default())
+}
+
+/// Computes the hierarchical document symbols for the given CSS source code with custom
+/// options.
+///
+/// In `VscodeCss` compatibility mode, every selector of a rule gets its own symbol, and
+/// only `@media`, `@font-face` and `@keyframes` produce at-rule symbols.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `DocumentSymbolOptions` to apply.
+///
+/// # Returns
+///
+/// * A vector of top-level `DocumentSymbol`, with nested rules as their children.
+pub fn get_document_symbols_with_options(
+    source: &str,
+    options: &DocumentSymbolOptions,
+) -> Vec<DocumentSymbol> {
+    get_document_symbols_with_budget(source, options, &Budget::UNLIMITED).items
+}
+
+/// Computes the hierarchical document symbols for the given CSS source code, stopping early
+/// once the budget runs out.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `DocumentSymbolOptions` to apply.
+/// * `budget` - The `Budget` of the request, where each rule counts as one item.
+///
+/// # Returns
+///
+/// * The `DocumentSymbol` found within the budget, flagged as truncated if some were dropped.
+pub fn get_document_symbols_with_budget(
+    source: &str,
+    options: &DocumentSymbolOptions,
+    budget: &Budget,
+) -> Budgeted<DocumentSymbol> {
+    get_document_symbols_with_parse(source, parse_css(source), options, budget)
+}
+
+/// Computes the hierarchical document symbols for the given CSS source code from an existing
+/// parse of it, see `get_document_symbols_with_budget`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `parse` - The `CssParse` of `source`.
+/// * `options` - The `DocumentSymbolOptions` to apply.
+/// * `budget` - The `Budget` of the request, where each rule counts as one item.
+///
+/// # Returns
+///
+/// * The `DocumentSymbol` found within the budget, flagged as truncated if some were dropped.
+pub fn get_document_symbols_with_parse(
+    source: &str,
+    parse: CssParse,
+    options: &DocumentSymbolOptions,
+    budget: &Budget,
+) -> Budgeted<DocumentSymbol> {
+    let tracker = budget.start();
+    let context = SymbolContext {
+        source,
+        line_index: LineIndex::with_encoding(source, options.position_encoding),
+        compatibility: options.compatibility,
+        tracker,
+    };
+    let symbols = collect_symbols(&parse.syntax(), &context);
+    context.tracker.finish(symbols)
+}
+
+/// Computes the document symbols of a stylesheet too large to be parsed at once, one chunk
+/// of whole top-level rules at a time, see `split_chunks`.
+///
+/// # Arguments
+///
+/// * `source` - The original CSS source code as a string slice.
+/// * `options` - The `DocumentSymbolOptions` to apply.
+/// * `budget` - The `Budget` of the request, shared by the chunks.
+/// * `chunk_size` - The size in bytes from which a chunk ends.
+///
+/// # Returns
+///
+/// * The `DocumentSymbol` found within the budget, flagged as truncated if some were dropped.
+pub fn get_document_symbols_in_chunks(
+    source: &str,
+    options: &DocumentSymbolOptions,
+    budget: &Budget,
+    chunk_size: usize,
+) -> Budgeted<DocumentSymbol> {
+    let encoding = options.position_encoding;
+    run_in_chunks(source, chunk_size, encoding, budget, |chunk, budget| {
+        let mut result = get_document_symbols_with_budget(chunk.text, options, budget);
+        for symbol in &mut result.items {
+            shift_symbol(symbol, chunk);
+        }
+        result
+    })
+}
+
+/// Moves a symbol found in a chunk, and its children, to its place in the stylesheet.
+fn shift_symbol(symbol: &mut DocumentSymbol, chunk: &Chunk) {
+    symbol.range = chunk.shift_range(symbol.range);
+    symbol.selection_range = chunk.shift_range(symbol.selection_range);
+    for child in symbol.children.iter_mut().flatten() {
+        shift_symbol(child, chunk);
+    }
+}
+
+struct SymbolContext<'a> {
+    source: &'a str,
+    line_index: LineIndex,
+    compatibility: CompatibilityMode,
+    tracker: BudgetTracker,
+}
+
+/// Collects the symbols of every rule found below `node`, stopping at each rule.
+fn collect_symbols(node: &CssSyntaxNode, context: &SymbolContext) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+    for child in node.children() {
+        let is_rule = matches!(
+            child.kind(),
+            CssSyntaxKind::CSS_QUALIFIED_RULE
+                | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+                | CssSyntaxKind::CSS_AT_RULE
+                | CssSyntaxKind::CSS_KEYFRAMES_ITEM
+        );
+        if is_rule && !context.tracker.allow() {
+            break;
+        }
+        match (child.kind(), context.compatibility) {
+            (
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE,
+                CompatibilityMode::Default,
+            ) => {
+                symbols.extend(rule_symbol(&child, SymbolKind::CLASS, context));
+            }
+            (
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE,
+                CompatibilityMode::VscodeCss,
+            ) => {
+                symbols.extend(selector_symbols(&child, context));
+            }
+            (CssSyntaxKind::CSS_AT_RULE, CompatibilityMode::Default) => {
+                symbols.extend(rule_symbol(&child, SymbolKind::MODULE, context));
+            }
+            // Keyframe selectors, like `from` or `50%`, under their `@keyframes`
+            (CssSyntaxKind::CSS_KEYFRAMES_ITEM, CompatibilityMode::Default) => {
+                symbols.extend(rule_symbol(&child, SymbolKind::CLASS, context));
+            }
+            (CssSyntaxKind::CSS_KEYFRAMES_ITEM, CompatibilityMode::VscodeCss) => {}
+            (CssSyntaxKind::CSS_AT_RULE, CompatibilityMode::VscodeCss) => {
+                match vscode_at_rule_symbol(&child, context) {
+                    Some(symbol) => symbols.push(symbol),
+                    None => symbols.extend(collect_symbols(&child, context)),
+                }
+            }
+            _ => symbols.extend(collect_symbols(&child, context)),
+        }
+    }
+    symbols
+}
+
+/// Builds the symbol of a rule, named after the prelude before its block.
+fn rule_symbol(
+    node: &CssSyntaxNode,
+    kind: SymbolKind,
+    context: &SymbolContext,
+) -> Option<DocumentSymbol> {
+    let block = find_block(node)?;
+    let start = usize::from(node.text_trimmed_range().start());
+    let prelude_end = usize::from(block.text_trimmed_range().start());
+    let prelude = &context.source[start..prelude_end];
+
+    let name = prelude.split_whitespace().collect::<Vec<_>>().join(" ");
+    if name.is_empty() {
+        return None;
+    }
+    let prelude_end = start + prelude.trim_end().len();
+    Some(new_symbol(
+        name,
+        kind,
+        node,
+        start..prelude_end,
+        &block,
+        context,
+    ))
+}
+
+/// Builds one symbol per selector of a rule, like `vscode-css-languageservice`.
+fn selector_symbols(node: &CssSyntaxNode, context: &SymbolContext) -> Vec<DocumentSymbol> {
+    let (Some(selector_list), Some(block)) = (node.first_child(), find_block(node)) else {
+        return Vec::new();
+    };
+    selector_list
+        .children()
+        .enumerate()
+        .map(|(idx, selector)| {
+            let range = selector.text_trimmed_range();
+            let range = usize::from(range.start())..usize::from(range.end());
+            let name = context.source[range.clone()].to_string();
+            let mut symbol = new_symbol(name, SymbolKind::CLASS, node, range, &block, context);
+            // Nested rules are only listed once, under the first selector
+            if idx > 0 {
+                symbol.children = Some(Vec::new());
+            }
+            symbol
+        })
+        .collect()
+}
+
+/// Builds the symbol of the at-rules `vscode-css-languageservice` lists.
+fn vscode_at_rule_symbol(node: &CssSyntaxNode, context: &SymbolContext) -> Option<DocumentSymbol> {
+    let at_rule = node.first_child()?;
+    let block = find_block(node)?;
+    let text = |node: &CssSyntaxNode| node.text_trimmed().to_string();
+    let (name, kind) = match at_rule.kind() {
+        CssSyntaxKind::CSS_MEDIA_AT_RULE => {
+            let queries = at_rule
+                .children()
+                .find(|child| child.kind() == CssSyntaxKind::CSS_MEDIA_QUERY_LIST)?;
+            (format!("@media {}", text(&queries)), SymbolKind::MODULE)
+        }
+        CssSyntaxKind::CSS_FONT_FACE_AT_RULE => ("@font-face".to_string(), SymbolKind::CLASS),
+        CssSyntaxKind::CSS_KEYFRAMES_AT_RULE => {
+            let name = at_rule.children().find(|child| !is_block(child.kind()))?;
+            (format!("@keyframes {}", text(&name)), SymbolKind::CLASS)
+        }
+        _ => return None,
+    };
+
+    let start = usize::from(node.text_trimmed_range().start());
+    let prelude_end = start
+        + context.source[start..usize::from(block.text_trimmed_range().start())]
+            .trim_end()
+            .len();
+    Some(new_symbol(
+        name,
+        kind,
+        node,
+        start..prelude_end,
+        &block,
+        context,
+    ))
+}
+
+fn new_symbol(
+    name: String,
+    kind: SymbolKind,
+    node: &CssSyntaxNode,
+    selection: std::ops::Range<usize>,
+    block: &CssSyntaxNode,
+    context: &SymbolContext,
+) -> DocumentSymbol {
+    let range = node.text_trimmed_range();
+    let line_index = &context.line_index;
+
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name,
+        detail: None,
+        kind,
+        tags: None,
+        deprecated: None,
+        range: line_index.range(context.source, range.start().into(), range.end().into()),
+        selection_range: line_index.range(context.source, selection.start, selection.end),
+        children: Some(collect_symbols(block, context)),
+    }
+}
+
+/// Finds the `{ ... }` block of a rule.
+fn find_block(node: &CssSyntaxNode) -> Option<CssSyntaxNode> {
+    node.descendants().skip(1).find(|n| is_block(n.kind()))
+}
+
+/// Returns whether a syntax kind is the `{ ... }` block of a rule.
+pub(crate) fn is_block(kind: CssSyntaxKind) -> bool {
+    matches!(
+        kind,
+        CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK
+            | CssSyntaxKind::CSS_DECLARATION_OR_AT_RULE_BLOCK
+            | CssSyntaxKind::CSS_DECLARATION_LIST_BLOCK
+            | CssSyntaxKind::CSS_RULE_LIST_BLOCK
+            | CssSyntaxKind::CSS_KEYFRAMES_BLOCK
+            | CssSyntaxKind::CSS_FONT_FEATURE_VALUES_BLOCK
+            | CssSyntaxKind::CSS_PAGE_AT_RULE_BLOCK
+            | CssSyntaxKind::CSS_BOGUS_BLOCK
+    )
+}
+
+#[wasm_bindgen]
+pub fn get_document_symbols_wasm(
+    source: &str,
+    compatibility: Option<CompatibilityMode>,
+) -> Vec<DocumentSymbolWASM> {
+    let options = DocumentSymbolOptions {
+        compatibility: compatibility.unwrap_or_default(),
+        ..Default::default()
+    };
+    let document_symbols = get_document_symbols_with_options(source, &options);
+    document_symbols
+        .into_iter()
+        .map(DocumentSymbolWASM::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_document_symbols_empty() {
+        let symbols = get_document_symbols("");
+        assert!(symbols.is_empty(), "No symbols expected for empty input");
+    }
+
+    #[test]
+    fn test_get_document_symbols_rules() {
+        let code = "body {\n    margin: 0;\n}\n\n.a:hover,  #b > p { color: red; }\n";
+        let symbols = get_document_symbols(code);
+
+        assert_eq!(symbols.len(), 2, "Expected two symbols");
+        assert_eq!(symbols[0].name, "body");
+        assert_eq!(symbols[0].kind, SymbolKind::CLASS);
+        assert_eq!(symbols[0].range.start.line, 0);
+        assert_eq!(symbols[0].range.end.line, 2);
+        assert_eq!(symbols[1].name, ".a:hover, #b > p");
+    }
+
+    #[test]
+    fn test_get_document_symbols_nested() {
+        let code = "@media screen {\n    body {\n        margin: 0;\n    }\n}\n";
+        let symbols = get_document_symbols(code);
+
+        assert_eq!(symbols.len(), 1, "Expected one top-level symbol");
+        let media = &symbols[0];
+        assert_eq!(media.name, "@media screen");
+        assert_eq!(media.kind, SymbolKind::MODULE);
+
+        let children = media.children.as_ref().unwrap();
+        assert_eq!(children.len(), 1, "Expected one nested symbol");
+        assert_eq!(children[0].name, "body");
+        assert_eq!(children[0].selection_range.start.line, 1);
+    }
+
+    #[test]
+    fn test_get_document_symbols_keyframes() {
+        let code = "@keyframes spin {
+  from,  50% { top: 0; }
+  to {}
+}
+.a { & .b {} }
+";
+        let symbols = get_document_symbols(code);
+
+        let keyframes = symbols[0].children.as_ref().unwrap();
+        let names: Vec<_> = keyframes
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["from, 50%", "to"]);
+        assert_eq!(keyframes[1].range.start.line, 2);
+        assert_eq!(symbols[1].children.as_ref().unwrap()[0].name, "& .b");
+    }
+
+    #[test]
+    fn test_get_document_symbols_vscode_css_compatibility() {
+        let code = "@supports (display: grid) {\n  .a, .b { color: red; }\n}\n@keyframes slide { from { top: 0; } }\n@font-face { font-family: A; }\n";
+        let options = DocumentSymbolOptions {
+            compatibility: CompatibilityMode::VscodeCss,
+            ..Default::default()
+        };
+        let symbols = get_document_symbols_with_options(code, &options);
+        let names: Vec<_> = symbols.iter().map(|symbol| symbol.name.as_str()).collect();
+
+        assert_eq!(names, vec![".a", ".b", "@keyframes slide", "@font-face"]);
+        assert_eq!(
+            symbols[1].range, symbols[0].range,
+            "Selectors share the rule range"
+        );
+        assert_eq!(symbols[1].selection_range.start.character, 6);
+        assert_eq!(symbols[2].kind, SymbolKind::CLASS);
+    }
+
+    #[test]
+    fn test_get_document_symbols_with_budget_truncates() {
+        let code = "@media screen {\n  .a {}\n  .b {}\n}\n.c {}\n";
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let result =
+            get_document_symbols_with_budget(code, &DocumentSymbolOptions::default(), &budget);
+
+        assert!(result.truncated, "Expected the result to be flagged");
+        assert_eq!(result.items.len(), 1);
+        assert_eq!(result.items[0].children.as_ref().unwrap().len(), 1);
+    }
+}
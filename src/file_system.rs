@@ -0,0 +1,428 @@
+use lsp_types::Uri;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use wasm_bindgen::prelude::*;
+
+/// Gives features access to documents other than the one being analyzed, such as the
+/// targets of `@import` rules.
+pub trait FileSystemProvider {
+    /// Reads the contents of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The `Uri` of the document to read.
+    ///
+    /// # Returns
+    ///
+    /// * The contents of the document, or `None` if it doesn't exist or can't be read.
+    fn read_file(&self, uri: &Uri) -> Option<String>;
+
+    /// Checks whether a document exists, without necessarily reading it.
+    fn exists(&self, uri: &Uri) -> bool {
+        self.read_file(uri).is_some()
+    }
+
+    /// Resolves a reference found in a document, like the target of an `@import`.
+    ///
+    /// Defaults to resolving against the hierarchy of the base URI, which providers of
+    /// schemes without one, like `untitled:`, may override.
+    ///
+    /// # Arguments
+    ///
+    /// * `base` - The `Uri` of the document containing the reference.
+    /// * `reference` - The relative reference, like `../a.css`.
+    ///
+    /// # Returns
+    ///
+    /// * The resolved `Uri`, or `None` if it can't be resolved.
+    fn resolve(&self, base: &Uri, reference: &str) -> Option<Uri> {
+        resolve_reference(base, reference)
+    }
+
+    /// Lists the documents under a folder, recursively, to index a workspace.
+    ///
+    /// Defaults to nothing, for providers that can't enumerate their documents.
+    ///
+    /// # Arguments
+    ///
+    /// * `folder` - The `Uri` of the folder.
+    ///
+    /// # Returns
+    ///
+    /// * The `Uri` of every document under the folder, in no particular order.
+    fn list_files(&self, _folder: &Uri) -> Vec<Uri> {
+        Vec::new()
+    }
+}
+
+/// Supplies the contents of read-only virtual documents, like the CSS generated by a build
+/// tool under a `tailwind-generated:` scheme, so references into them resolve and
+/// navigating to them opens a readable document instead of a dead link.
+pub trait TextDocumentContentProvider {
+    /// Provides the contents of a virtual document.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The `Uri` of the document.
+    ///
+    /// # Returns
+    ///
+    /// * The contents of the document, or `None` if the provider doesn't know it.
+    fn provide_content(&self, uri: &Uri) -> Option<String>;
+}
+
+/// Serves the documents of a `TextDocumentContentProvider` to the features, like any other
+/// document.
+pub struct VirtualDocuments<P>(pub P);
+
+impl<P: TextDocumentContentProvider> FileSystemProvider for VirtualDocuments<P> {
+    fn read_file(&self, uri: &Uri) -> Option<String> {
+        self.0.provide_content(uri)
+    }
+}
+
+/// Returns the lowercase scheme of a URI, like `file` or `untitled`.
+pub fn uri_scheme(uri: &Uri) -> String {
+    uri.as_str()
+        .split_once(':')
+        .map_or(String::new(), |(scheme, _)| scheme.to_ascii_lowercase())
+}
+
+/// Routes file system requests to the provider registered for the scheme of each URI, so
+/// hosts can serve `untitled:`, `vscode-vfs:` and other custom schemes. URIs of unknown
+/// schemes don't exist.
+pub struct SchemeFileSystem {
+    providers: HashMap<String, Box<dyn FileSystemProvider>>,
+}
+
+impl std::fmt::Debug for SchemeFileSystem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut schemes: Vec<&String> = self.providers.keys().collect();
+        schemes.sort();
+        f.debug_struct("SchemeFileSystem")
+            .field("schemes", &schemes)
+            .finish()
+    }
+}
+
+impl Default for SchemeFileSystem {
+    /// Creates a router serving `file://` URIs from the local file system.
+    fn default() -> Self {
+        let mut file_system = SchemeFileSystem::empty();
+        file_system.register("file", StdFileSystem);
+        file_system
+    }
+}
+
+impl SchemeFileSystem {
+    /// Creates a router without any provider.
+    pub fn empty() -> Self {
+        SchemeFileSystem {
+            providers: HashMap::new(),
+        }
+    }
+
+    /// Registers the provider of a scheme, replacing the previous one.
+    ///
+    /// # Arguments
+    ///
+    /// * `scheme` - The URI scheme, like `vscode-vfs`, matched case-insensitively.
+    /// * `provider` - The `FileSystemProvider` serving the URIs of the scheme.
+    pub fn register(&mut self, scheme: &str, provider: impl FileSystemProvider + 'static) {
+        self.providers
+            .insert(scheme.to_ascii_lowercase(), Box::new(provider));
+    }
+
+    fn provider(&self, uri: &Uri) -> Option<&dyn FileSystemProvider> {
+        self.providers.get(&uri_scheme(uri)).map(Box::as_ref)
+    }
+}
+
+impl FileSystemProvider for SchemeFileSystem {
+    fn read_file(&self, uri: &Uri) -> Option<String> {
+        self.provider(uri)?.read_file(uri)
+    }
+
+    fn exists(&self, uri: &Uri) -> bool {
+        self.provider(uri)
+            .is_some_and(|provider| provider.exists(uri))
+    }
+
+    fn resolve(&self, base: &Uri, reference: &str) -> Option<Uri> {
+        match self.provider(base) {
+            Some(provider) => provider.resolve(base, reference),
+            None => resolve_reference(base, reference),
+        }
+    }
+
+    fn list_files(&self, folder: &Uri) -> Vec<Uri> {
+        self.provider(folder)
+            .map_or(Vec::new(), |provider| provider.list_files(folder))
+    }
+}
+
+/// Reads `file://` documents from the local file system.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdFileSystem;
+
+impl FileSystemProvider for StdFileSystem {
+    fn read_file(&self, uri: &Uri) -> Option<String> {
+        std::fs::read_to_string(uri_to_path(uri)?).ok()
+    }
+
+    fn exists(&self, uri: &Uri) -> bool {
+        uri_to_path(uri).is_some_and(|path| path.is_file())
+    }
+
+    /// Lists the files under a folder, skipping hidden folders and `node_modules`.
+    fn list_files(&self, folder: &Uri) -> Vec<Uri> {
+        let Some(root) = uri_to_path(folder) else {
+            return Vec::new();
+        };
+        let mut files = Vec::new();
+        let mut dirs = vec![root];
+        while let Some(dir) = dirs.pop() {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                if path.is_dir() {
+                    if !name.starts_with('.') && name != "node_modules" {
+                        dirs.push(path);
+                    }
+                } else if let Some(uri) = path_to_uri(&path) {
+                    files.push(uri);
+                }
+            }
+        }
+        files
+    }
+}
+
+/// Converts a `file://` URI to a local path.
+///
+/// # Arguments
+///
+/// * `uri` - The `Uri` to convert.
+///
+/// # Returns
+///
+/// * The decoded path, or `None` if the URI doesn't use the `file` scheme.
+pub fn uri_to_path(uri: &Uri) -> Option<PathBuf> {
+    let path = uri.as_str().strip_prefix("file://")?;
+    let path = path.split(['?', '#']).next().unwrap_or_default();
+    Some(PathBuf::from(percent_decode(path)))
+}
+
+/// Converts an absolute local path to a `file://` URI.
+pub fn path_to_uri(path: &Path) -> Option<Uri> {
+    let path = path.to_str()?.replace('\\', "/");
+    let path = if path.starts_with('/') {
+        path
+    } else {
+        format!("/{path}")
+    };
+    format!("file://{}", percent_encode(&path)).parse().ok()
+}
+
+/// Resolves a relative reference against a hierarchical base URI, like `file:///a/b.css` or
+/// `vscode-vfs://github/org/repo/b.css`.
+///
+/// # Arguments
+///
+/// * `base` - The `Uri` the reference is relative to.
+/// * `reference` - The relative reference, like `../a.css`.
+///
+/// # Returns
+///
+/// * The resolved `Uri`, or `None` if the base URI has no hierarchy to resolve against,
+///   like `untitled:Untitled-1`.
+pub fn resolve_reference(base: &Uri, reference: &str) -> Option<Uri> {
+    let base = base.as_str();
+    let base = base.split(['?', '#']).next().unwrap_or_default();
+    let authority_start = base.find("://")? + 3;
+    let path_start = base[authority_start..]
+        .find('/')
+        .map_or(base.len(), |idx| authority_start + idx);
+    let (origin, base_path) = base.split_at(path_start);
+
+    let path = if reference.starts_with('/') {
+        reference.to_string()
+    } else {
+        let dir = base_path.rfind('/').map_or("/", |idx| &base_path[..=idx]);
+        format!("{dir}{reference}")
+    };
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/').skip(1) {
+        match segment {
+            "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    format!("{origin}/{}", percent_encode(&segments.join("/")))
+        .parse()
+        .ok()
+}
+
+fn percent_encode(path: &str) -> String {
+    let mut encoded = String::with_capacity(path.len());
+    for c in path.chars() {
+        if c.is_ascii_alphanumeric() || "-._~/!$&'()*+,;=:@%".contains(c) {
+            encoded.push(c);
+        } else {
+            let mut buffer = [0; 4];
+            for byte in c.encode_utf8(&mut buffer).bytes() {
+                encoded.push_str(&format!("%{byte:02X}"));
+            }
+        }
+    }
+    encoded
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escaped = (bytes[idx] == b'%')
+            .then(|| text.get(idx + 1..idx + 3))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                idx += 3;
+            }
+            None => {
+                decoded.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JS object with a `readFile(uri: string): string | undefined` method, letting hosts
+    /// serve documents from their own file system. A method that throws serves no document.
+    #[wasm_bindgen(typescript_type = "FileSystemProvider")]
+    pub type JsFileSystemProvider;
+
+    #[wasm_bindgen(method, catch, js_name = readFile)]
+    fn read_file(this: &JsFileSystemProvider, uri: &str) -> Result<Option<String>, JsValue>;
+}
+
+impl FileSystemProvider for JsFileSystemProvider {
+    fn read_file(&self, uri: &Uri) -> Option<String> {
+        JsFileSystemProvider::read_file(self, uri.as_str())
+            .ok()
+            .flatten()
+    }
+}
+
+#[wasm_bindgen]
+extern "C" {
+    /// A JS object with a `provideTextDocumentContent(uri: string): string | undefined`
+    /// method, serving virtual documents. A method that throws serves no document.
+    #[wasm_bindgen(typescript_type = "TextDocumentContentProvider")]
+    pub type JsTextDocumentContentProvider;
+
+    #[wasm_bindgen(method, catch, js_name = provideTextDocumentContent)]
+    fn provide_text_document_content(
+        this: &JsTextDocumentContentProvider,
+        uri: &str,
+    ) -> Result<Option<String>, JsValue>;
+}
+
+impl TextDocumentContentProvider for JsTextDocumentContentProvider {
+    fn provide_content(&self, uri: &Uri) -> Option<String> {
+        self.provide_text_document_content(uri.as_str())
+            .ok()
+            .flatten()
+    }
+}
+
+#[wasm_bindgen(typescript_custom_section)]
+const FILE_SYSTEM_PROVIDER: &str = r#"
+export interface FileSystemProvider {
+    readFile(uri: string): string | undefined;
+}
+
+export interface TextDocumentContentProvider {
+    provideTextDocumentContent(uri: string): string | undefined;
+}
+"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uri_to_path() {
+        let uri = "file:///home/me/my%20styles/a.css".parse().unwrap();
+        assert_eq!(
+            uri_to_path(&uri),
+            Some(PathBuf::from("/home/me/my styles/a.css"))
+        );
+
+        let uri = "untitled:Untitled-1".parse().unwrap();
+        assert_eq!(uri_to_path(&uri), None, "Only file URIs have a path");
+    }
+
+    #[test]
+    fn test_path_to_uri() {
+        let uri = path_to_uri(Path::new("/home/me/my styles/a.css")).unwrap();
+        assert_eq!(uri.as_str(), "file:///home/me/my%20styles/a.css");
+        assert_eq!(
+            uri_to_path(&uri),
+            Some(PathBuf::from("/home/me/my styles/a.css"))
+        );
+    }
+
+    #[test]
+    fn test_resolve_reference_schemes() {
+        let resolve = |base: &str, reference: &str| {
+            resolve_reference(&base.parse().unwrap(), reference).map(|uri| uri.as_str().to_string())
+        };
+
+        assert_eq!(
+            resolve("vscode-vfs://github/org/repo/src/a.css", "../b.css").as_deref(),
+            Some("vscode-vfs://github/org/repo/b.css")
+        );
+        assert_eq!(resolve("untitled:Untitled-1", "b.css"), None);
+        assert_eq!(resolve("untitled:/Untitled-1", "b.css"), None);
+    }
+
+    struct UntitledFileSystem;
+
+    impl FileSystemProvider for UntitledFileSystem {
+        fn read_file(&self, uri: &Uri) -> Option<String> {
+            (uri.as_str() == "untitled:b.css").then(|| "b {}".to_string())
+        }
+
+        fn resolve(&self, _base: &Uri, reference: &str) -> Option<Uri> {
+            format!("untitled:{reference}").parse().ok()
+        }
+    }
+
+    #[test]
+    fn test_scheme_file_system() {
+        let mut file_system = SchemeFileSystem::default();
+        file_system.register("Untitled", UntitledFileSystem);
+
+        let base: Uri = "untitled:Untitled-1".parse().unwrap();
+        let resolved = file_system.resolve(&base, "b.css").unwrap();
+        assert_eq!(file_system.read_file(&resolved).as_deref(), Some("b {}"));
+
+        let unknown: Uri = "tailwind-generated:out.css".parse().unwrap();
+        assert!(!file_system.exists(&unknown), "Unknown schemes don't exist");
+        assert_eq!(uri_scheme(&unknown), "tailwind-generated");
+    }
+}
@@ -1,16 +1,969 @@
+use crate::case_folding::contains_ignore_case;
+use crate::data::values::LENGTH_UNITS;
+use crate::parser::tokenizer::{tokenize, Token, TokenKind};
+use crate::parser::{find_declarations, parse_css};
+use crate::value_syntax::is_property_keyword;
 use biome_css_parser::CssParse;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode, CssSyntaxToken};
+use biome_diagnostics::Diagnostic as _;
+use biome_rowan::Direction;
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// Options for formatting stylesheets.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct FormatOptions {
+    /// Whether to also format the CSS commented out in block comments, like a rule
+    /// temporarily disabled, see `is_commented_out_code`. Other comments are always left
+    /// untouched.
+    pub format_commented_code: bool,
+    /// The width of an indentation level, in spaces.
+    pub tab_size: u32,
+    /// Whether to indent with spaces rather than tabs.
+    pub insert_spaces: bool,
+}
+
+impl Default for FormatOptions {
+    fn default() -> Self {
+        FormatOptions {
+            format_commented_code: false,
+            tab_size: 2,
+            insert_spaces: true,
+        }
+    }
+}
+
+impl FormatOptions {
+    /// Returns the text of an indentation level.
+    fn indent_unit(&self) -> String {
+        if self.insert_spaces {
+            " ".repeat(self.tab_size as usize)
+        } else {
+            "\t".to_string()
+        }
+    }
+}
 
 /// Formats the parsed CSS and returns the formatted code.
 ///
+/// Only the selectors of style rules are normalized for now, see `normalize_selector`;
+/// selectors containing comments are left untouched so no comment is lost, as is the
+/// content of comments, even when it is commented-out code.
+///
 /// # Arguments
 ///
 /// * `parse` - The `CssParse` result from parsing.
 ///
 /// # Returns
 ///
-/// * A `String` containing the original CSS code.
+/// * A `String` containing the formatted CSS code.
 pub fn format_css(parse: &CssParse) -> String {
-    parse.tree().to_string()
+    format_css_with_options(parse, &FormatOptions::default())
+}
+
+/// Formats the parsed CSS with custom options, see `format_css`.
+///
+/// # Arguments
+///
+/// * `parse` - The `CssParse` result from parsing.
+/// * `options` - The `FormatOptions`, like whether to format commented-out code.
+///
+/// # Returns
+///
+/// * A `String` containing the formatted CSS code.
+pub fn format_css_with_options(parse: &CssParse, options: &FormatOptions) -> String {
+    let mut code = parse.tree().to_string();
+    let root = parse.syntax();
+    let mut replacements: Vec<(Range<usize>, String)> = root
+        .descendants()
+        .filter(|node| {
+            node.kind() == CssSyntaxKind::CSS_SELECTOR_LIST
+                && node
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == CssSyntaxKind::CSS_QUALIFIED_RULE)
+        })
+        .filter_map(|node| {
+            let range = node.text_trimmed_range();
+            let range = usize::from(range.start())..usize::from(range.end());
+            (!contains_comments(&code[range.clone()]))
+                .then(|| (range.clone(), normalize_selector(&code[range])))
+        })
+        .collect();
+
+    if options.format_commented_code {
+        let comments = root.descendants_tokens(Direction::Next).flat_map(|token| {
+            let trivia = token
+                .leading_trivia()
+                .pieces()
+                .chain(token.trailing_trivia().pieces());
+            trivia
+                .filter_map(|piece| piece.as_comments())
+                .map(|comment| comment.text_range())
+                .collect::<Vec<_>>()
+        });
+        for range in comments {
+            let range = usize::from(range.start())..usize::from(range.end());
+            let Some(body) = code[range.clone()]
+                .strip_prefix("/*")
+                .and_then(|body| body.strip_suffix("*/"))
+            else {
+                continue;
+            };
+            if is_commented_out_code(body) {
+                let formatted = format_css_with_options(&parse_css(body), options);
+                replacements.push((range, format!("/*{formatted}*/")));
+            }
+        }
+    }
+
+    replacements.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, replacement) in replacements {
+        code.replace_range(range, &replacement);
+    }
+    code
+}
+
+/// Pretty-prints the parsed CSS: one rule, declaration or statement per line, blocks
+/// indented by nesting level, and whitespace normalized around combinators, colons and
+/// commas.
+///
+/// Selectors are normalized with `normalize_selector`, and values only have their
+/// whitespace collapsed, so their meaning never changes. Comments are kept where they
+/// are, on their own line or at the end of a line, as are single blank lines between
+/// statements; a selector, prelude or value containing a comment is kept as written.
+///
+/// # Arguments
+///
+/// * `parse` - The `CssParse` result from parsing.
+/// * `options` - The `FormatOptions`, like the indentation.
+///
+/// # Returns
+///
+/// * The pretty-printed code ending with a newline, or `None` if the code has syntax
+///   errors other than stray semicolons, which are dropped, as moving code around them
+///   could change how it is recovered.
+pub fn pretty_print_css(parse: &CssParse, options: &FormatOptions) -> Option<String> {
+    let root = parse.syntax();
+    if has_syntax_errors(parse, 0..usize::from(root.text_range().end())) {
+        return None;
+    }
+    let mut printer = Printer::new(options);
+    let items: Vec<CssSyntaxNode> = root.children().flat_map(|list| list.children()).collect();
+    printer.print_items(&items, 0);
+    if let Some(eof) = root.last_token() {
+        printer.print_comments(&eof, 0);
+    }
+    let mut code = printer.out.trim_end().to_string();
+    if !code.is_empty() {
+        code.push('\n');
+    }
+    Some(code)
+}
+
+/// Pretty-prints consecutive statements of a stylesheet, like the rule being edited, see
+/// `pretty_print_css`.
+///
+/// # Arguments
+///
+/// * `parse` - The `CssParse` result from parsing the stylesheet.
+/// * `statements` - The statements, siblings in the same rule list or block.
+/// * `depth` - The nesting level of the statements, for their indentation.
+/// * `options` - The `FormatOptions`, like the indentation.
+///
+/// # Returns
+///
+/// * The code replacing the statements, from the start of the first to the end of the
+///   last, its trailing comments included, with its first line indented and without a
+///   final newline. `None` if the statements have syntax errors, see `pretty_print_css`.
+pub fn pretty_print_statements(
+    parse: &CssParse,
+    statements: &[CssSyntaxNode],
+    depth: usize,
+    options: &FormatOptions,
+) -> Option<String> {
+    let (first, last) = (statements.first()?, statements.last()?);
+    let start = usize::from(first.text_trimmed_range().start());
+    if has_syntax_errors(parse, start..usize::from(last.text_range().end())) {
+        return None;
+    }
+    let mut printer = Printer::new(options);
+    printer.print_statement(first, depth);
+    printer.print_items(&statements[1..], depth);
+    Some(printer.out.trim_end().to_string())
+}
+
+/// Returns whether the parser reported errors in a byte range, other than stray
+/// semicolons.
+fn has_syntax_errors(parse: &CssParse, range: Range<usize>) -> bool {
+    let code = parse.syntax().to_string();
+    parse.diagnostics().iter().any(|diagnostic| {
+        let span = diagnostic.location().span.unwrap_or_default();
+        let span = usize::from(span.start())..usize::from(span.end());
+        if span.start > range.end || span.end < range.start {
+            return false;
+        }
+        let text = code.get(span).unwrap_or_default();
+        text.is_empty() || !text.chars().all(|c| c == ';' || c.is_whitespace())
+    })
+}
+
+/// Builds the pretty-printed code of `pretty_print_css`.
+struct Printer {
+    indent_unit: String,
+    out: String,
+}
+
+impl Printer {
+    fn new(options: &FormatOptions) -> Self {
+        Printer {
+            indent_unit: options.indent_unit(),
+            out: String::new(),
+        }
+    }
+
+    fn print_items(&mut self, items: &[CssSyntaxNode], depth: usize) {
+        for item in items {
+            self.print_item(item, depth);
+        }
+    }
+
+    fn print_item(&mut self, item: &CssSyntaxNode, depth: usize) {
+        if let Some(first) = item.first_token() {
+            self.print_comments(&first, depth);
+        }
+        self.print_statement(item, depth);
+    }
+
+    /// Prints a statement without the comments before it.
+    fn print_statement(&mut self, item: &CssSyntaxNode, depth: usize) {
+        let text = item.text_trimmed().to_string();
+        if item.kind() == CssSyntaxKind::CSS_BOGUS && text.chars().all(|c| c == ';') {
+            return;
+        }
+        if item.kind() == CssSyntaxKind::CSS_DECLARATION_WITH_SEMICOLON {
+            let line = format_declaration(item).unwrap_or_else(|| {
+                let declaration = collapse_whitespace(&text);
+                if declaration.ends_with(';') {
+                    declaration
+                } else {
+                    format!("{declaration};")
+                }
+            });
+            self.line(depth, &line, item.last_token().as_ref());
+            return;
+        }
+        let Some((block, l_curly)) = find_block(item) else {
+            self.line(
+                depth,
+                &collapse_whitespace(&text),
+                item.last_token().as_ref(),
+            );
+            return;
+        };
+        let start = usize::from(item.text_trimmed_range().start());
+        let header_end = usize::from(l_curly.text_trimmed_range().start()) - start;
+        let header = format_header(item.kind(), text[..header_end].trim());
+        let items: Vec<CssSyntaxNode> = block
+            .children()
+            .flat_map(|child| {
+                if child.kind().is_list() {
+                    child.children().collect()
+                } else {
+                    vec![child]
+                }
+            })
+            .collect();
+        let r_curly = block
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() == CssSyntaxKind::R_CURLY);
+        let is_empty = items.is_empty()
+            && !has_comments(&l_curly)
+            && r_curly
+                .as_ref()
+                .is_none_or(|r_curly| !has_comments(r_curly));
+        if is_empty {
+            self.line(depth, &format!("{header} {{}}"), r_curly.as_ref());
+            return;
+        }
+        self.line(depth, &format!("{header} {{"), Some(&l_curly));
+        self.print_items(&items, depth + 1);
+        if let Some(r_curly) = &r_curly {
+            self.print_comments(r_curly, depth + 1);
+        }
+        self.line(depth, "}", r_curly.as_ref());
+    }
+
+    /// Prints the comments before a token on their own lines, and keeps a single blank
+    /// line where the source has some.
+    fn print_comments(&mut self, token: &CssSyntaxToken, depth: usize) {
+        let mut newlines = 0;
+        for piece in token.leading_trivia().pieces() {
+            if piece.is_newline() {
+                newlines += 1;
+            } else if piece.is_comments() {
+                if newlines > 1 {
+                    self.blank_line();
+                }
+                newlines = 0;
+                self.line(depth, piece.text().trim(), None);
+            }
+        }
+        if newlines > 1 {
+            self.blank_line();
+        }
+    }
+
+    /// Prints a line, followed by the comments trailing a token.
+    fn line(&mut self, depth: usize, text: &str, trailing: Option<&CssSyntaxToken>) {
+        for _ in 0..depth {
+            self.out.push_str(&self.indent_unit);
+        }
+        self.out.push_str(text);
+        if let Some(token) = trailing {
+            for piece in token.trailing_trivia().pieces() {
+                if piece.is_comments() {
+                    self.out.push(' ');
+                    self.out.push_str(piece.text().trim());
+                }
+            }
+        }
+        self.out.push('\n');
+    }
+
+    /// Adds a blank line, except at the start of the code or of a block.
+    fn blank_line(&mut self) {
+        if !self.out.is_empty() && !self.out.ends_with("\n\n") && !self.out.ends_with("{\n") {
+            self.out.push('\n');
+        }
+    }
+}
+
+/// Returns the block of a rule or at-rule, with its `{`, if it has one.
+fn find_block(item: &CssSyntaxNode) -> Option<(CssSyntaxNode, CssSyntaxToken)> {
+    item.descendants().find_map(|node| {
+        let l_curly = node
+            .children_with_tokens()
+            .filter_map(|element| element.into_token())
+            .find(|token| token.kind() == CssSyntaxKind::L_CURLY)?;
+        Some((node, l_curly))
+    })
+}
+
+/// Formats a declaration as `name: value;`, or returns `None` to keep it as written, if it
+/// contains comments or its property isn't a generic one.
+fn format_declaration(item: &CssSyntaxNode) -> Option<String> {
+    let declaration = item
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION)?;
+    if contains_comments(&declaration.text_trimmed().to_string()) {
+        return None;
+    }
+    let property = declaration
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_GENERIC_PROPERTY)?;
+    let mut parts = property.children();
+    let name = parts.next()?.text_trimmed().to_string();
+    let value = parts
+        .next()
+        .map(|value| value.text_trimmed().to_string())
+        .unwrap_or_default();
+    let value = normalize_value(&name, &value, &ValueNormalization::NONE);
+    let important = declaration
+        .children()
+        .any(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_IMPORTANT);
+    let important = if important { " !important" } else { "" };
+    Some(format!("{name}: {value}{important};"))
+}
+
+/// Formats the selector of a rule or the prelude of an at-rule, kept as written if it
+/// contains comments.
+fn format_header(kind: CssSyntaxKind, header: &str) -> String {
+    if contains_comments(header) {
+        return header.to_string();
+    }
+    match kind {
+        CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE => {
+            normalize_selector(header)
+        }
+        _ if header.starts_with('@') => normalize_prelude(header),
+        _ => normalize_value("", header, &ValueNormalization::NONE),
+    }
+}
+
+/// Normalizes the prelude of an at-rule, with a single space after the colons of media
+/// features, like `@media (min-width:600px)` to `@media (min-width: 600px)`.
+fn normalize_prelude(prelude: &str) -> String {
+    let collapsed = collapse_whitespace(prelude);
+    let mut out = String::with_capacity(collapsed.len());
+    // Whether each open parenthesis is a feature rather than a function
+    let mut features: Vec<bool> = Vec::new();
+    let mut tokens = tokenize(&collapsed, 0).into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::Function { .. } => {
+                features.push(token.kind == TokenKind::LeftParen);
+                out.push_str(token.text);
+                tokens.next_if(|token| token.kind == TokenKind::Whitespace);
+            }
+            TokenKind::RightParen => {
+                features.pop();
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push(')');
+            }
+            TokenKind::Colon if features.last() == Some(&true) => {
+                if out.ends_with(' ') {
+                    out.pop();
+                }
+                out.push_str(": ");
+                tokens.next_if(|token| token.kind == TokenKind::Whitespace);
+            }
+            _ => out.push_str(token.text),
+        }
+    }
+    out
+}
+
+/// Collapses the whitespace of a statement into single spaces, except in strings; a
+/// statement containing comments is kept as written.
+fn collapse_whitespace(text: &str) -> String {
+    let text = text.trim();
+    let tokens = tokenize(text, 0);
+    if tokens.iter().any(|token| token.kind == TokenKind::Comment) {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    for token in tokens {
+        match token.kind {
+            TokenKind::Whitespace => out.push(' '),
+            _ => out.push_str(token.text),
+        }
+    }
+    out
+}
+
+/// Returns whether CSS code contains comments, outside of strings.
+fn contains_comments(code: &str) -> bool {
+    tokenize(code, 0)
+        .iter()
+        .any(|token| token.kind == TokenKind::Comment)
+}
+
+fn has_comments(token: &CssSyntaxToken) -> bool {
+    token
+        .leading_trivia()
+        .pieces()
+        .chain(token.trailing_trivia().pieces())
+        .any(|piece| piece.is_comments())
+}
+
+/// Returns whether the text of a block comment is CSS commented out, rather than prose,
+/// with a heuristic: the text is code if it parses without errors as rules, or as
+/// declarations each ending with a `;` and named like properties.
+///
+/// # Arguments
+///
+/// * `text` - The text of the comment, without its `/*` and `*/`.
+///
+/// # Returns
+///
+/// * `true` for code like `a { color: red; }` or `color: red;`, `false` for prose like
+///   `Header styles` or `TODO: fix this`.
+pub fn is_commented_out_code(text: &str) -> bool {
+    let text = text.trim();
+    if text.is_empty() {
+        return false;
+    }
+    let rules = parse_css(text);
+    if rules.diagnostics().is_empty() {
+        return rules.syntax().descendants().any(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_AT_RULE
+            )
+        });
+    }
+
+    let block = parse_css(&format!("x{{{text}}}"));
+    let declarations = find_declarations(&block.syntax());
+    block.diagnostics().is_empty()
+        && text.ends_with(';')
+        && !declarations.is_empty()
+        && declarations.iter().all(|declaration| {
+            !declaration.value.is_empty()
+                && (declaration.name.starts_with("--")
+                    || declaration
+                        .name
+                        .chars()
+                        .all(|c| c.is_ascii_lowercase() || c == '-'))
+        })
+}
+
+/// Normalizes a selector, so equivalent selectors are written the same way.
+///
+/// Whitespace is collapsed, with a single space around combinators and after commas,
+/// attribute values are double-quoted, attribute flags and type selectors are lowercased,
+/// and comments are dropped. Classes and IDs keep their case since they are
+/// case-sensitive.
+///
+/// # Arguments
+///
+/// * `selector` - A selector or selector list, like `UL>LI , a[href='#']`.
+///
+/// # Returns
+///
+/// * The normalized selector, like `ul > li, a[href="#"]`.
+pub fn normalize_selector(selector: &str) -> String {
+    let mut normalizer = SelectorNormalizer {
+        tokens: tokenize(selector, 0),
+        idx: 0,
+        out: String::with_capacity(selector.len()),
+    };
+    normalizer.run();
+    normalizer.out
+}
+
+#[wasm_bindgen]
+pub fn normalize_selector_wasm(selector: &str) -> String {
+    normalize_selector(selector)
+}
+
+/// The functions whose arguments must keep the unit of zero lengths, like `calc(0px + 1em)`.
+const MATH_FUNCTIONS: &[&str] = &["calc", "min", "max", "clamp", "round", "mod", "rem"];
+
+/// The properties whose zero lengths must keep their unit, like the basis of `flex: 1 0px`,
+/// which would be read as a flex factor without it.
+const ZERO_UNIT_PROPERTIES: &[&str] = &["flex", "-webkit-flex", "-ms-flex"];
+
+/// The rewrites of `normalize_value`, all enabled by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ValueNormalization {
+    /// Lowercases the keywords of the property, function names and units, like `Block` or
+    /// `10PX`. Other identifiers, like animation names, keep their case.
+    pub lowercase_keywords: bool,
+    /// Lowercases hex colors and shortens them when possible, like `#AABBCC` to `#abc`.
+    pub shorten_hex_colors: bool,
+    /// Drops the unit of zero lengths and the trailing zeros of numbers, like `0px` to `0`
+    /// and `1.50` to `1.5`. Zero lengths keep their unit in math functions and `flex`.
+    pub normalize_zeros: bool,
+}
+
+impl ValueNormalization {
+    /// Keeps values as written, only normalizing their whitespace.
+    pub(crate) const NONE: ValueNormalization = ValueNormalization {
+        lowercase_keywords: false,
+        shorten_hex_colors: false,
+        normalize_zeros: false,
+    };
+}
+
+impl Default for ValueNormalization {
+    fn default() -> Self {
+        ValueNormalization {
+            lowercase_keywords: true,
+            shorten_hex_colors: true,
+            normalize_zeros: true,
+        }
+    }
+}
+
+/// Normalizes the value of a declaration, so equivalent values are written the same way,
+/// for tools diffing stylesheets semantically.
+///
+/// Whitespace is collapsed, with a single space after commas and none inside parentheses,
+/// then the rewrites enabled in `options` are applied. Strings, unquoted URLs and
+/// identifiers that aren't keywords of the property, like animation names or the names of
+/// custom properties, keep their case, and the values of custom properties only have their
+/// whitespace collapsed, as they aren't parsed until used.
+///
+/// # Arguments
+///
+/// * `property` - The property of the declaration, like `margin`.
+/// * `value` - The value, like `0PX  AUTO`.
+/// * `options` - The `ValueNormalization` rewrites to apply.
+///
+/// # Returns
+///
+/// * The normalized value, like `0 auto`.
+pub fn normalize_value(property: &str, value: &str, options: &ValueNormalization) -> String {
+    let options = if property.starts_with("--") {
+        ValueNormalization::NONE
+    } else {
+        *options
+    };
+    let mut out = String::with_capacity(value.len());
+    // The names of the functions the cursor is in, lowercased
+    let mut functions: Vec<String> = Vec::new();
+    let mut pending_space = false;
+    for token in tokenize(value, 0) {
+        match token.kind {
+            TokenKind::Whitespace => {
+                pending_space = true;
+                continue;
+            }
+            TokenKind::RightParen => {
+                functions.pop();
+                out.push(')');
+                pending_space = false;
+                continue;
+            }
+            TokenKind::Comma => {
+                out.push(',');
+                pending_space = true;
+                continue;
+            }
+            _ => {}
+        }
+        if pending_space && !out.is_empty() && !out.ends_with('(') {
+            out.push(' ');
+        }
+        pending_space = false;
+        match token.kind {
+            TokenKind::Function { name } => {
+                out.push_str(&normalize_word(name, true, false, &options));
+                out.push('(');
+                functions.push(name.to_ascii_lowercase());
+            }
+            TokenKind::LeftParen => {
+                out.push('(');
+                functions.push(String::new());
+            }
+            // An unquoted URL keeps its case, without the whitespace around it
+            TokenKind::Url => {
+                let paren = token.text.find('(').unwrap_or_default();
+                let url = &token.text[paren + 1..];
+                out.push_str(&normalize_word(&token.text[..paren], true, false, &options));
+                out.push('(');
+                out.push_str(url.strip_suffix(')').unwrap_or(url).trim());
+                if url.ends_with(')') {
+                    out.push(')');
+                }
+            }
+            TokenKind::Ident
+            | TokenKind::Hash { .. }
+            | TokenKind::Number { .. }
+            | TokenKind::Percentage { .. }
+            | TokenKind::Dimension { .. } => {
+                let keep_zero_unit = contains_ignore_case(ZERO_UNIT_PROPERTIES, property)
+                    || functions
+                        .iter()
+                        .any(|function| MATH_FUNCTIONS.contains(&function.as_str()));
+                let is_keyword =
+                    options.lowercase_keywords && is_property_keyword(property, token.text);
+                out.push_str(&normalize_word(
+                    token.text,
+                    is_keyword,
+                    keep_zero_unit,
+                    &options,
+                ));
+            }
+            _ => out.push_str(token.text),
+        }
+    }
+    out
+}
+
+/// Normalizes a hex color, number, dimension or identifier of a value, lowercasing
+/// identifiers only if they are keywords or function names.
+fn normalize_word(
+    word: &str,
+    is_keyword: bool,
+    keep_zero_unit: bool,
+    options: &ValueNormalization,
+) -> String {
+    if let Some(hex) = word.strip_prefix('#') {
+        if !options.shorten_hex_colors
+            || !matches!(hex.len(), 3 | 4 | 6 | 8)
+            || !hex.chars().all(|c| c.is_ascii_hexdigit())
+        {
+            return word.to_string();
+        }
+        let hex = hex.to_ascii_lowercase();
+        let bytes = hex.as_bytes();
+        if hex.len() >= 6 && bytes.chunks(2).all(|pair| pair[0] == pair[1]) {
+            return bytes.chunks(2).fold("#".to_string(), |mut short, pair| {
+                short.push(pair[0] as char);
+                short
+            });
+        }
+        return format!("#{hex}");
+    }
+
+    let unsigned = word.strip_prefix(['-', '+']).unwrap_or(word);
+    let number_len = unsigned
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(unsigned.len());
+    if number_len == 0 || !unsigned[..number_len].chars().any(|c| c.is_ascii_digit()) {
+        if options.lowercase_keywords && is_keyword && !word.starts_with("--") {
+            return word.to_ascii_lowercase();
+        }
+        return word.to_string();
+    }
+    let sign = &word[..word.len() - unsigned.len()];
+    let (number, unit) = unsigned.split_at(number_len);
+    let unit = if options.lowercase_keywords {
+        unit.to_ascii_lowercase()
+    } else {
+        unit.to_string()
+    };
+    if !options.normalize_zeros {
+        return format!("{sign}{number}{unit}");
+    }
+    let number = match number.split_once('.') {
+        Some((integer, fraction)) => {
+            let fraction = fraction.trim_end_matches('0');
+            let integer = integer.trim_start_matches('0');
+            match (integer.is_empty(), fraction.is_empty()) {
+                (true, true) => "0".to_string(),
+                (true, false) => format!("0.{fraction}"),
+                (false, true) => integer.to_string(),
+                (false, false) => format!("{integer}.{fraction}"),
+            }
+        }
+        None => match number.trim_start_matches('0') {
+            "" => "0".to_string(),
+            integer => integer.to_string(),
+        },
+    };
+    if number == "0" {
+        let unit = if !keep_zero_unit && LENGTH_UNITS.contains(&unit.to_ascii_lowercase().as_str())
+        {
+            ""
+        } else {
+            unit.as_str()
+        };
+        return format!("0{unit}");
+    }
+    format!("{sign}{number}{unit}")
+}
+
+/// Normalizes the value of a declaration with every rewrite, see `normalize_value`.
+#[wasm_bindgen]
+pub fn normalize_value_wasm(property: &str, value: &str) -> String {
+    normalize_value(property, value, &ValueNormalization::default())
+}
+
+struct SelectorNormalizer<'a> {
+    tokens: Vec<Token<'a>>,
+    idx: usize,
+    out: String,
+}
+
+impl<'a> SelectorNormalizer<'a> {
+    fn run(&mut self) {
+        let mut pending_space = false;
+        // Whether the whitespace before the next token was already written
+        let mut spaced = true;
+        let mut compound_start = true;
+        while let Some(token) = self.next() {
+            match token.kind {
+                TokenKind::Whitespace | TokenKind::Comment => {
+                    pending_space = true;
+                    continue;
+                }
+                TokenKind::Comma => {
+                    self.trim_end();
+                    self.out.push_str(", ");
+                    (pending_space, spaced, compound_start) = (false, true, true);
+                    continue;
+                }
+                TokenKind::Delim(c @ ('>' | '+' | '~')) => {
+                    self.trim_end();
+                    if !self.out.is_empty() && !self.out.ends_with('(') {
+                        self.out.push(' ');
+                    }
+                    self.out.push(c);
+                    self.out.push(' ');
+                    (pending_space, spaced, compound_start) = (false, true, true);
+                    continue;
+                }
+                TokenKind::RightParen => {
+                    self.trim_end();
+                    self.out.push(')');
+                    (pending_space, spaced, compound_start) = (false, false, false);
+                    continue;
+                }
+                _ => {}
+            }
+
+            if pending_space && !spaced && !self.out.is_empty() && !self.out.ends_with('(') {
+                self.out.push(' ');
+                compound_start = true;
+            }
+            (pending_space, spaced) = (false, false);
+            match token.kind {
+                TokenKind::LeftBracket => self.attribute(),
+                TokenKind::LeftParen => {
+                    self.out.push('(');
+                    (spaced, compound_start) = (true, true);
+                    continue;
+                }
+                TokenKind::Function { name } => {
+                    let is_nth =
+                        self.out.ends_with(':') && name.to_ascii_lowercase().starts_with("nth-");
+                    self.out.push_str(token.text);
+                    if is_nth {
+                        self.nth_argument();
+                    } else {
+                        (spaced, compound_start) = (true, true);
+                        continue;
+                    }
+                }
+                // Classes, IDs and pseudo-classes keep their case, after their `.`, `#` or `:`
+                TokenKind::Ident if compound_start => {
+                    self.out.push_str(&token.text.to_ascii_lowercase());
+                }
+                TokenKind::Delim('|') => {
+                    // A namespace prefix, followed by the type selector
+                    self.out.push('|');
+                    compound_start = true;
+                    continue;
+                }
+                _ => self.out.push_str(token.text),
+            }
+            compound_start = false;
+        }
+        self.trim_end();
+    }
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let token = self.tokens.get(self.idx)?.clone();
+        self.idx += 1;
+        Some(token)
+    }
+
+    fn peek(&self, ahead: usize) -> Option<&TokenKind<'a>> {
+        self.tokens.get(self.idx + ahead).map(|token| &token.kind)
+    }
+
+    fn trim_end(&mut self) {
+        let len = self.out.trim_end().len();
+        self.out.truncate(len);
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.peek(0) == Some(&TokenKind::Whitespace) {
+            self.idx += 1;
+        }
+    }
+
+    /// Writes an attribute selector whose `[` was consumed, like `[name="value" i]`.
+    fn attribute(&mut self) {
+        self.out.push('[');
+        self.skip_whitespace();
+        // The name and its namespace prefix, like `xlink|href`
+        while let Some(kind) = self.peek(0) {
+            let is_name = match kind {
+                TokenKind::Ident => true,
+                TokenKind::Delim('|' | '*') => self.peek(1) != Some(&TokenKind::Delim('=')),
+                _ => false,
+            };
+            if !is_name {
+                break;
+            }
+            self.out.push_str(self.tokens[self.idx].text);
+            self.idx += 1;
+        }
+        self.skip_whitespace();
+
+        let operator_len = match (self.peek(0), self.peek(1)) {
+            (Some(TokenKind::Delim('=')), _) => Some(1),
+            (Some(TokenKind::Delim('~' | '|' | '^' | '$' | '*')), Some(TokenKind::Delim('='))) => {
+                Some(2)
+            }
+            _ => None,
+        };
+        if let Some(operator_len) = operator_len {
+            for token in &self.tokens[self.idx..self.idx + operator_len] {
+                self.out.push_str(token.text);
+            }
+            self.idx += operator_len;
+            self.skip_whitespace();
+            let value = match self.peek(0) {
+                Some(TokenKind::String | TokenKind::BadString) => {
+                    let token = &self.tokens[self.idx];
+                    let quote = token.text.chars().next().unwrap_or('"');
+                    let content = &token.text[1..];
+                    let content = match token.kind {
+                        TokenKind::String if !token.is_unterminated() => {
+                            &content[..content.len() - 1]
+                        }
+                        _ => content,
+                    };
+                    self.idx += 1;
+                    requote(content, quote)
+                }
+                Some(TokenKind::RightBracket) | None => String::new(),
+                Some(_) => self
+                    .next()
+                    .map(|token| token.text.to_string())
+                    .unwrap_or_default(),
+            };
+            self.out.push('"');
+            self.out.push_str(&value);
+            self.out.push('"');
+        }
+
+        let mut flags = String::new();
+        while let Some(token) = self.next() {
+            if token.kind == TokenKind::RightBracket {
+                break;
+            }
+            flags.push_str(token.text);
+        }
+        let flags = flags.trim();
+        if !flags.is_empty() {
+            self.out.push(' ');
+            self.out.push_str(&flags.to_ascii_lowercase());
+        }
+        self.out.push(']');
+    }
+
+    /// Writes an `An+B` argument with its whitespace collapsed, up to the closing `)`.
+    fn nth_argument(&mut self) {
+        let mut argument = String::new();
+        let mut depth = 0usize;
+        while let Some(token) = self.next() {
+            match token.kind {
+                TokenKind::LeftParen | TokenKind::Function { .. } => depth += 1,
+                TokenKind::RightParen if depth == 0 => break,
+                TokenKind::RightParen => depth -= 1,
+                _ => {}
+            }
+            argument.push_str(token.text);
+        }
+        let argument: Vec<&str> = argument.split_whitespace().collect();
+        self.out.push_str(&argument.join(" "));
+        self.out.push(')');
+    }
+}
+
+/// Converts the content of a string to the content of a double-quoted string.
+fn requote(value: &str, quote: char) -> String {
+    if quote == '"' {
+        return value.to_string();
+    }
+    let mut requoted = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\'') => requoted.push('\''),
+                Some(escaped) => {
+                    requoted.push('\\');
+                    requoted.push(escaped);
+                }
+                None => requoted.push('\\'),
+            },
+            '"' => requoted.push_str("\\\""),
+            c => requoted.push(c),
+        }
+    }
+    requoted
 }
 
 #[cfg(test)]
@@ -30,4 +983,144 @@ mod tests {
             "Formatted code should match original code"
         );
     }
+
+    #[test]
+    fn test_format_css_normalizes_selectors() {
+        let code = "UL>LI , .A{margin:0;}\na /* b */ , c {}";
+        let formatted_code = format_css(&parse_css(code));
+        assert_eq!(formatted_code, "ul > li, .A{margin:0;}\na /* b */ , c {}");
+        let code = "A[TITLE='/*']>B{}";
+        assert_eq!(format_css(&parse_css(code)), "a[TITLE=\"/*\"] > b{}");
+    }
+
+    #[test]
+    fn test_format_css_commented_out_code() {
+        let code = "/* UL>LI { color: red; } */\n/* Header: UL>LI */\nA>B {}";
+        assert_eq!(
+            format_css(&parse_css(code)),
+            "/* UL>LI { color: red; } */\n/* Header: UL>LI */\na > b {}"
+        );
+        let options = FormatOptions {
+            format_commented_code: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            format_css_with_options(&parse_css(code), &options),
+            "/* ul > li { color: red; } */\n/* Header: UL>LI */\na > b {}"
+        );
+    }
+
+    #[test]
+    fn test_pretty_print_css() {
+        let code = "/* head */\n\n\n@import url(a.css)   print;\n@keyframes x{from,TO{top:0}}\n@font-face{font-family:'A  b'}\n.a{\n/* in */\n}\n/* end */";
+        assert_eq!(
+            pretty_print_css(&parse_css(code), &FormatOptions::default()).unwrap(),
+            "/* head */\n\n@import url(a.css) print;\n@keyframes x {\n  from, TO {\n    top: 0;\n  }\n}\n@font-face {\n  font-family: 'A  b';\n}\n.a {\n  /* in */\n}\n/* end */\n"
+        );
+        let options = FormatOptions {
+            insert_spaces: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            pretty_print_css(&parse_css("a /* b */ >c{color:/* x */red}"), &options).unwrap(),
+            "a /* b */ >c {\n\tcolor:/* x */red;\n}\n"
+        );
+        assert_eq!(
+            pretty_print_css(&parse_css("a { color: red"), &options),
+            None
+        );
+    }
+
+    #[test]
+    fn test_is_commented_out_code() {
+        assert!(is_commented_out_code(" .a, .b { margin: 0 } "));
+        assert!(is_commented_out_code("@media print { a { color: red; } }"));
+        assert!(is_commented_out_code("\n  color: red;\n  --gap: 4px;\n"));
+        assert!(!is_commented_out_code(" Header styles "));
+        assert!(!is_commented_out_code("TODO: fix this"));
+        assert!(!is_commented_out_code("Note: Keep In Sync;"));
+        assert!(!is_commented_out_code("  "));
+    }
+
+    #[test]
+    fn test_normalize_value() {
+        let options = ValueNormalization::default();
+        let cases = [
+            ("margin", "0PX   AUTO", "0 auto"),
+            ("color", "#AABBCC", "#abc"),
+            ("color", "#AABBCD80", "#aabbcd80"),
+            ("width", "calc( 100% - 0px )", "calc(100% - 0px)"),
+            ("opacity", "0.500", "0.5"),
+            ("margin", "-0.0em 01.10Em", "0 1.1em"),
+            (
+                "transition",
+                "Opacity 0S EASE-IN ,color 1s",
+                "Opacity 0s ease-in, color 1s",
+            ),
+            (
+                "font-family",
+                "\"Open  Sans\" , Arial",
+                "\"Open  Sans\", Arial",
+            ),
+            (
+                "background",
+                "URL( Images/A.PNG ) no-repeat",
+                "url(Images/A.PNG) no-repeat",
+            ),
+            (
+                "color",
+                "VAR( --Brand-Color , RED )",
+                "var(--Brand-Color, red)",
+            ),
+            ("--Theme", "  Dark   #FFFFFF ", "Dark #FFFFFF"),
+            ("display", "Inline  FLEX", "inline flex"),
+            ("animation-name", "SlideIn", "SlideIn"),
+            ("grid-area", "Main", "Main"),
+            ("flex", "1 1 0PX", "1 1 0px"),
+            ("width", "calc(0PX + 1em)", "calc(0px + 1em)"),
+        ];
+        for (property, value, expected) in cases {
+            assert_eq!(
+                normalize_value(property, value, &options),
+                expected,
+                "{value:?}"
+            );
+        }
+
+        let options = ValueNormalization {
+            shorten_hex_colors: false,
+            normalize_zeros: false,
+            ..ValueNormalization::default()
+        };
+        assert_eq!(
+            normalize_value("border", "0PX SOLID #FFFFFF", &options),
+            "0px solid #FFFFFF"
+        );
+    }
+
+    #[test]
+    fn test_normalize_selector() {
+        let cases = [
+            ("  DIV.Foo#Bar  ", "div.Foo#Bar"),
+            ("a>b+c~d   e", "a > b + c ~ d e"),
+            ("a ,b,\n\tc", "a, b, c"),
+            ("[ HREF = '#' ]", "[HREF=\"#\"]"),
+            ("[lang|=en I]", "[lang|=\"en\" i]"),
+            (
+                "[title='say \"hi\"'], [title=\"it's\"]",
+                "[title=\"say \\\"hi\\\"\"], [title=\"it's\"]",
+            ),
+            (
+                "A:NOT( B , .C ):hover::BEFORE",
+                "a:NOT(b, .C):hover::BEFORE",
+            ),
+            (":has(> IMG)", ":has(> img)"),
+            ("li:nth-child( 2n + 1 )", "li:nth-child(2n + 1)"),
+            ("a /* comment */ b", "a b"),
+            ("*|A, & > SPAN", "*|a, & > span"),
+        ];
+        for (selector, expected) in cases {
+            assert_eq!(normalize_selector(selector), expected, "{selector:?}");
+        }
+    }
 }
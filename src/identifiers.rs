@@ -0,0 +1,183 @@
+use crate::parser::parse_css;
+use biome_css_syntax::CssSyntaxKind;
+use std::borrow::Cow;
+use std::str::CharIndices;
+
+/// Returns the value of an identifier as written, with its escapes replaced by the
+/// characters they stand for, so `\31 23`, `\00003123` and `\31\32\33` are all `123`.
+///
+/// Hex escapes take up to 6 digits and the single whitespace ending them; invalid code
+/// points, and a `\` ending the text, become U+FFFD.
+///
+/// # Arguments
+///
+/// * `text` - The identifier as written, like a class name or a custom property.
+///
+/// # Returns
+///
+/// * The value of the identifier, borrowed if it has no escape.
+pub fn unescape_identifier(text: &str) -> Cow<'_, str> {
+    if !text.contains('\\') {
+        return Cow::Borrowed(text);
+    }
+    let mut value = String::with_capacity(text.len());
+    let mut chars = text.char_indices();
+    while let Some((offset, c)) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        let start = offset + 1;
+        skip_escape(&mut chars);
+        let end = chars.clone().next().map_or(text.len(), |(idx, _)| idx);
+        let escape = &text[start..end];
+        let escaped = match escape.chars().next() {
+            None => char::REPLACEMENT_CHARACTER,
+            // Only the whitespace terminating a hex escape is dropped
+            Some(c) if c.is_ascii_hexdigit() => u32::from_str_radix(
+                escape.trim_end_matches(|c: char| !c.is_ascii_hexdigit()),
+                16,
+            )
+            .ok()
+            .filter(|&code| code != 0)
+            .and_then(char::from_u32)
+            .unwrap_or(char::REPLACEMENT_CHARACTER),
+            Some(c) => c,
+        };
+        value.push(escaped);
+    }
+    Cow::Owned(value)
+}
+
+/// Writes a value as an identifier, escaping the characters that can't be written as is,
+/// like `123` as `\31 23` or `a:b` as `a\:b`, as browsers serialize identifiers.
+///
+/// # Arguments
+///
+/// * `value` - The value of the identifier, without escapes.
+///
+/// # Returns
+///
+/// * The identifier, as it can be written in a stylesheet.
+pub fn escape_identifier(value: &str) -> String {
+    let mut identifier = String::with_capacity(value.len());
+    let starts_with_dash = value.starts_with('-');
+    for (idx, c) in value.chars().enumerate() {
+        let starts_with_digit = c.is_ascii_digit() && (idx == 0 || (idx == 1 && starts_with_dash));
+        if c == '\0' {
+            identifier.push(char::REPLACEMENT_CHARACTER);
+        } else if c.is_control() || starts_with_digit {
+            identifier.push_str(&format!("\\{:x} ", c as u32));
+        } else if idx == 0 && c == '-' && value.len() == 1 {
+            identifier.push_str("\\-");
+        } else if c.is_ascii_alphanumeric() || c == '-' || c == '_' || !c.is_ascii() {
+            identifier.push(c);
+        } else {
+            identifier.push('\\');
+            identifier.push(c);
+        }
+    }
+    identifier
+}
+
+/// Returns whether a text is a single identifier, escapes included, like `btn`, `\31 23`
+/// or `--brand`, so it can be used as a class name or a custom property name.
+pub fn is_identifier(text: &str) -> bool {
+    if text.is_empty() {
+        return false;
+    }
+    let parse = parse_css(&format!(".{text}{{}}"));
+    parse.diagnostics().is_empty()
+        && parse
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_CLASS_SELECTOR)
+            .map(|selector| selector.text_trimmed().to_string())
+            .eq([format!(".{text}")])
+}
+
+/// Advances over an escape, from the character after its `\`: a character, or up to 6 hex
+/// digits and the whitespace ending them.
+pub(crate) fn skip_escape(chars: &mut CharIndices) {
+    let Some((_, first)) = chars.next() else {
+        return;
+    };
+    if !first.is_ascii_hexdigit() {
+        return;
+    }
+    for _ in 0..5 {
+        let mut ahead = chars.clone();
+        match ahead.next() {
+            Some((_, c)) if c.is_ascii_hexdigit() => *chars = ahead,
+            _ => break,
+        }
+    }
+    let mut ahead = chars.clone();
+    match ahead.next() {
+        Some((_, '\r')) => {
+            *chars = ahead.clone();
+            if ahead.next().is_some_and(|(_, c)| c == '\n') {
+                *chars = ahead;
+            }
+        }
+        Some((_, c)) if c.is_whitespace() => *chars = ahead,
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_unescape_identifier() {
+        let cases = [
+            ("btn", "btn"),
+            (r"\31 23", "123"),
+            (r"\00003123", "123"),
+            (r"\31\32\33", "123"),
+            (r"a\:b", "a:b"),
+            (r"--\{weird\}", "--{weird}"),
+            (r"\1F600 x", "😀x"),
+            (r"\0 a", "\u{FFFD}a"),
+            ("a\\", "a\u{FFFD}"),
+            (r"a\ b", "a b"),
+            ("a\\\tb", "a\tb"),
+            ("\\31\r\nb", "1b"),
+        ];
+        for (text, expected) in cases {
+            assert_eq!(unescape_identifier(text), expected, "{text:?}");
+        }
+    }
+
+    #[test]
+    fn test_escape_identifier() {
+        let cases = [
+            ("btn", "btn"),
+            ("123", r"\31 23"),
+            ("-1a", r"-\31 a"),
+            ("-", r"\-"),
+            ("a:b", r"a\:b"),
+            ("--{weird}", r"--\{weird\}"),
+            ("md:w-1/2", r"md\:w-1\/2"),
+            ("é", "é"),
+        ];
+        for (value, expected) in cases {
+            assert_eq!(escape_identifier(value), expected, "{value:?}");
+            assert_eq!(unescape_identifier(&escape_identifier(value)), value);
+        }
+    }
+
+    #[test]
+    fn test_is_identifier() {
+        assert!(is_identifier("btn"));
+        assert!(is_identifier(r"\31 23"));
+        assert!(is_identifier(r"md\:w-1\/2"));
+        assert!(is_identifier("--brand"));
+        assert!(!is_identifier("1a"));
+        assert!(!is_identifier("a b"));
+        assert!(!is_identifier("a:b"));
+        assert!(!is_identifier("a{}"));
+        assert!(!is_identifier(""));
+    }
+}
@@ -0,0 +1,666 @@
+use crate::case_folding::{fold_case, starts_with_ignore_case};
+use crate::dialect::Dialect;
+use crate::file_system::FileSystemProvider;
+use lsp_types::Uri;
+use serde_json::json;
+use std::collections::HashMap;
+use std::ops::Range;
+use wasm_bindgen::prelude::*;
+
+/// The at-rule used to pull in another stylesheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportKind {
+    Import,
+    Use,
+    Forward,
+}
+
+impl ImportKind {
+    /// Returns the at-keyword of the import, like `@import`.
+    pub fn keyword(self) -> &'static str {
+        match self {
+            ImportKind::Import => "@import",
+            ImportKind::Use => "@use",
+            ImportKind::Forward => "@forward",
+        }
+    }
+}
+
+/// An `@import`, `@use` or `@forward` rule found in a stylesheet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Import {
+    pub kind: ImportKind,
+    /// The target as written, without quotes or `url()`.
+    pub target: String,
+    /// The byte range of the target, including quotes or `url()`.
+    pub target_range: Range<usize>,
+    /// The byte range of the whole rule, up to its `;`.
+    pub range: Range<usize>,
+}
+
+/// Finds the imports of a stylesheet, skipping over comments and strings.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of `Import`, in source order.
+pub fn find_imports(source: &str) -> Vec<Import> {
+    let mut imports = Vec::new();
+    let mut offset = 0;
+    while let Some(c) = source[offset..].chars().next() {
+        let rest = &source[offset..];
+        if rest.starts_with("/*") {
+            offset += rest.find("*/").map_or(rest.len(), |end| end + 2);
+        } else if c == '"' || c == '\'' {
+            offset += string_len(rest);
+        } else if c == '@' {
+            match parse_import(source, offset) {
+                Some(import) => {
+                    offset = import.range.end;
+                    imports.push(import);
+                }
+                None => offset += 1,
+            }
+        } else {
+            offset += c.len_utf8();
+        }
+    }
+    imports
+}
+
+fn parse_import(source: &str, start: usize) -> Option<Import> {
+    let name_len = source[start + 1..]
+        .find(|c: char| !c.is_ascii_alphabetic())
+        .unwrap_or(source.len() - start - 1);
+    let kind = match fold_case(&source[start + 1..start + 1 + name_len]).as_ref() {
+        "import" => ImportKind::Import,
+        "use" => ImportKind::Use,
+        "forward" => ImportKind::Forward,
+        _ => return None,
+    };
+
+    let after_name = start + 1 + name_len;
+    let target_start =
+        after_name + source[after_name..].len() - source[after_name..].trim_start().len();
+    let rest = &source[target_start..];
+    let (target, target_len) = if rest.starts_with('"') || rest.starts_with('\'') {
+        let len = string_len(rest);
+        (unquote(&rest[..len]), len)
+    } else if starts_with_ignore_case(rest, "url(") {
+        let len = rest.find(')').map_or(rest.len(), |end| end + 1);
+        (unquote(rest[4..len].trim_end_matches(')').trim()), len)
+    } else {
+        return None;
+    };
+
+    let target_end = target_start + target_len;
+    let end = source[target_end..]
+        .find([';', '{', '}'])
+        .map_or(source.len(), |idx| {
+            let idx = target_end + idx;
+            if source[idx..].starts_with(';') {
+                idx + 1
+            } else {
+                idx
+            }
+        });
+    Some(Import {
+        kind,
+        target: target.to_string(),
+        target_range: target_start..target_end,
+        range: start..end,
+    })
+}
+
+/// Returns the byte length of the string starting `text`, including its quotes.
+fn string_len(text: &str) -> usize {
+    let mut chars = text.char_indices();
+    let Some((_, quote)) = chars.next() else {
+        return 0;
+    };
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\\' => {
+                chars.next();
+            }
+            '\n' => return idx,
+            c if c == quote => return idx + 1,
+            _ => {}
+        }
+    }
+    text.len()
+}
+
+fn unquote(text: &str) -> &str {
+    let text = text.strip_prefix(['"', '\'']).unwrap_or(text);
+    text.strip_suffix(['"', '\'']).unwrap_or(text)
+}
+
+/// An import whose target was resolved to a document URI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResolvedImport {
+    pub uri: Uri,
+    /// Whether the `FileSystemProvider` found the target.
+    pub exists: bool,
+}
+
+/// Resolves the target of an import relative to the importing document.
+///
+/// In SCSS, the partial (`_name.scss`) and index (`name/_index.scss`) variants are tried as
+/// well, and in Less a missing extension defaults to `.less`.
+///
+/// # Arguments
+///
+/// * `base` - The `Uri` of the importing document.
+/// * `target` - The import target, as found by `find_imports`.
+/// * `dialect` - The `Dialect` of the importing document.
+/// * `file_system` - The `FileSystemProvider` used to check which candidate exists.
+///
+/// # Returns
+///
+/// * The first existing candidate, the most likely candidate if none exists, or `None` for
+///   targets that aren't documents of the workspace, like URLs and built-in modules.
+pub fn resolve_import(
+    base: &Uri,
+    target: &str,
+    dialect: Dialect,
+    file_system: &dyn FileSystemProvider,
+) -> Option<ResolvedImport> {
+    let candidates = import_candidates(target, dialect)
+        .iter()
+        .filter_map(|candidate| file_system.resolve(base, candidate))
+        .collect::<Vec<_>>();
+    let first = candidates.first()?.clone();
+    Some(
+        candidates
+            .into_iter()
+            .find(|uri| file_system.exists(uri))
+            .map_or(
+                ResolvedImport {
+                    uri: first,
+                    exists: false,
+                },
+                |uri| ResolvedImport { uri, exists: true },
+            ),
+    )
+}
+
+/// Returns whether an import target lives outside of the workspace, like URLs, packages and
+/// built-in modules.
+pub fn is_external(target: &str) -> bool {
+    target.starts_with("//")
+        || target.starts_with('~')
+        || target
+            .split('/')
+            .next()
+            .is_some_and(|segment| segment.contains(':'))
+}
+
+/// Lists the relative references an import target may point to, most likely first.
+fn import_candidates(target: &str, dialect: Dialect) -> Vec<String> {
+    if target.is_empty() || is_external(target) {
+        return Vec::new();
+    }
+
+    let (dir, name) = target
+        .rsplit_once('/')
+        .map_or(("", target), |(dir, name)| (dir, name));
+    let dir = if dir.is_empty() && target.starts_with('/') {
+        "/".to_string()
+    } else if dir.is_empty() {
+        String::new()
+    } else {
+        format!("{dir}/")
+    };
+    let has_extension = name.contains('.');
+    match dialect {
+        Dialect::Scss if has_extension => vec![target.to_string(), format!("{dir}_{name}")],
+        Dialect::Scss => vec![
+            format!("{dir}{name}.scss"),
+            format!("{dir}_{name}.scss"),
+            format!("{dir}{name}.sass"),
+            format!("{dir}_{name}.sass"),
+            format!("{dir}{name}.css"),
+            format!("{target}/_index.scss"),
+            format!("{target}/index.scss"),
+        ],
+        Dialect::Less if !has_extension => vec![format!("{target}.less")],
+        _ => vec![target.to_string()],
+    }
+}
+
+/// The formats the import graph can be exported to.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT, to render with `dot -Tsvg`.
+    Dot,
+    /// JSON with `nodes`, `edges` and `cycles`, for custom visualizations.
+    Json,
+}
+
+/// Tracks which documents import which, so features can follow imports in both
+/// directions.
+#[derive(Debug, Default)]
+pub struct ImportGraph {
+    edges: HashMap<Uri, Vec<(ImportKind, ResolvedImport)>>,
+}
+
+impl ImportGraph {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the resolved imports of a document, replacing the previous ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The `Uri` of the document.
+    /// * `source` - The source code of the document.
+    /// * `dialect` - The `Dialect` of the document.
+    /// * `file_system` - The `FileSystemProvider` used to resolve the imports.
+    pub fn update(
+        &mut self,
+        uri: &Uri,
+        source: &str,
+        dialect: Dialect,
+        file_system: &dyn FileSystemProvider,
+    ) {
+        let imports = find_imports(source)
+            .iter()
+            .filter_map(|import| {
+                let resolved = resolve_import(uri, &import.target, dialect, file_system)?;
+                Some((import.kind, resolved))
+            })
+            .collect();
+        self.edges.insert(uri.clone(), imports);
+    }
+
+    /// Forgets a document and its imports.
+    pub fn remove(&mut self, uri: &Uri) {
+        self.edges.remove(uri);
+    }
+
+    /// Returns the resolved imports of a document.
+    pub fn imports_of(&self, uri: &Uri) -> Vec<&ResolvedImport> {
+        self.edges.get(uri).map_or(Vec::new(), |imports| {
+            imports.iter().map(|(_, import)| import).collect()
+        })
+    }
+
+    /// Returns the documents importing a document, sorted by URI.
+    pub fn importers_of(&self, uri: &Uri) -> Vec<&Uri> {
+        let mut importers: Vec<&Uri> = self
+            .edges
+            .iter()
+            .filter(|(_, imports)| imports.iter().any(|(_, import)| &import.uri == uri))
+            .map(|(importer, _)| importer)
+            .collect();
+        importers.sort();
+        importers
+    }
+
+    /// Returns every document of the graph, importing or imported, sorted by URI.
+    fn nodes(&self) -> Vec<&Uri> {
+        let mut nodes: Vec<&Uri> = self
+            .edges
+            .iter()
+            .flat_map(|(uri, imports)| {
+                std::iter::once(uri).chain(imports.iter().map(|(_, import)| &import.uri))
+            })
+            .collect();
+        nodes.sort();
+        nodes.dedup();
+        nodes
+    }
+
+    /// Finds the import cycles, as the strongly connected components of the graph.
+    ///
+    /// # Returns
+    ///
+    /// * The documents of each cycle sorted by URI, the cycles sorted by their first
+    ///   document. A document importing itself is a cycle on its own.
+    pub fn cycles(&self) -> Vec<Vec<&Uri>> {
+        let nodes = self.nodes();
+        let successors: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|uri| {
+                self.imports_of(uri)
+                    .iter()
+                    .filter_map(|import| nodes.binary_search(&&import.uri).ok())
+                    .collect()
+            })
+            .collect();
+        let mut cycles: Vec<Vec<&Uri>> = strongly_connected_components(&successors)
+            .into_iter()
+            .filter(|component| {
+                component.len() > 1 || successors[component[0]].contains(&component[0])
+            })
+            .map(|component| {
+                let mut cycle: Vec<&Uri> = component.iter().map(|&idx| nodes[idx]).collect();
+                cycle.sort();
+                cycle
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    /// Exports the graph for visualization: documents are nodes, imports are edges labelled
+    /// with their at-rule, and the documents and imports of cycles are highlighted. Imports
+    /// of missing documents point to dashed nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * `format` - The `GraphFormat` to export to.
+    ///
+    /// # Returns
+    ///
+    /// * The graph, in DOT or as JSON.
+    pub fn export(&self, format: GraphFormat) -> String {
+        let nodes = self.nodes();
+        let cycles = self.cycles();
+        let cycle_of = |uri: &Uri| cycles.iter().position(|cycle| cycle.contains(&uri));
+        let exists = |uri: &Uri| {
+            self.edges.contains_key(uri)
+                || self
+                    .edges
+                    .values()
+                    .flatten()
+                    .any(|(_, import)| &import.uri == uri && import.exists)
+        };
+        let mut edges: Vec<(&Uri, &Uri, ImportKind)> = self
+            .edges
+            .iter()
+            .flat_map(|(uri, imports)| {
+                imports
+                    .iter()
+                    .map(move |(kind, import)| (uri, &import.uri, *kind))
+            })
+            .collect();
+        edges.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+        let in_cycle =
+            |from: &Uri, to: &Uri| cycle_of(from).is_some() && cycle_of(from) == cycle_of(to);
+
+        match format {
+            GraphFormat::Dot => {
+                let mut lines = vec!["digraph imports {".to_string()];
+                for uri in &nodes {
+                    let mut attributes = Vec::new();
+                    if cycle_of(uri).is_some() {
+                        attributes.push("color=red");
+                    }
+                    if !exists(uri) {
+                        attributes.push("style=dashed");
+                    }
+                    if attributes.is_empty() {
+                        lines.push(format!("  \"{}\";", uri.as_str()));
+                    } else {
+                        let attributes = attributes.join(", ");
+                        lines.push(format!("  \"{}\" [{attributes}];", uri.as_str()));
+                    }
+                }
+                for (from, to, kind) in &edges {
+                    let color = if in_cycle(from, to) {
+                        ", color=red"
+                    } else {
+                        ""
+                    };
+                    lines.push(format!(
+                        "  \"{}\" -> \"{}\" [label=\"{}\"{color}];",
+                        from.as_str(),
+                        to.as_str(),
+                        kind.keyword()
+                    ));
+                }
+                lines.push("}".to_string());
+                lines.join("\n")
+            }
+            GraphFormat::Json => json!({
+                "nodes": nodes
+                    .iter()
+                    .map(|uri| json!({
+                        "id": uri.as_str(),
+                        "exists": exists(uri),
+                        "cycle": cycle_of(uri),
+                    }))
+                    .collect::<Vec<_>>(),
+                "edges": edges
+                    .iter()
+                    .map(|(from, to, kind)| json!({
+                        "from": from.as_str(),
+                        "to": to.as_str(),
+                        "kind": kind.keyword(),
+                        "inCycle": in_cycle(from, to),
+                    }))
+                    .collect::<Vec<_>>(),
+                "cycles": cycles
+                    .iter()
+                    .map(|cycle| cycle.iter().map(|uri| uri.as_str()).collect::<Vec<_>>())
+                    .collect::<Vec<_>>(),
+            })
+            .to_string(),
+        }
+    }
+}
+
+/// Finds the strongly connected components of a graph with Tarjan's algorithm.
+///
+/// # Arguments
+///
+/// * `successors` - The successors of each node, by index.
+///
+/// # Returns
+///
+/// * The components, as node indices.
+fn strongly_connected_components(successors: &[Vec<usize>]) -> Vec<Vec<usize>> {
+    struct Tarjan<'a> {
+        successors: &'a [Vec<usize>],
+        index: Vec<Option<usize>>,
+        low_link: Vec<usize>,
+        on_stack: Vec<bool>,
+        stack: Vec<usize>,
+        next_index: usize,
+        components: Vec<Vec<usize>>,
+    }
+
+    impl Tarjan<'_> {
+        fn visit(&mut self, node: usize) {
+            self.index[node] = Some(self.next_index);
+            self.low_link[node] = self.next_index;
+            self.next_index += 1;
+            self.stack.push(node);
+            self.on_stack[node] = true;
+            for &successor in &self.successors[node] {
+                match self.index[successor] {
+                    None => {
+                        self.visit(successor);
+                        self.low_link[node] = self.low_link[node].min(self.low_link[successor]);
+                    }
+                    Some(index) if self.on_stack[successor] => {
+                        self.low_link[node] = self.low_link[node].min(index);
+                    }
+                    Some(_) => {}
+                }
+            }
+            if Some(self.low_link[node]) == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    self.on_stack[member] = false;
+                    component.push(member);
+                    if member == node {
+                        break;
+                    }
+                }
+                self.components.push(component);
+            }
+        }
+    }
+
+    let mut tarjan = Tarjan {
+        successors,
+        index: vec![None; successors.len()],
+        low_link: vec![0; successors.len()],
+        on_stack: vec![false; successors.len()],
+        stack: Vec::new(),
+        next_index: 0,
+        components: Vec::new(),
+    };
+    for node in 0..successors.len() {
+        if tarjan.index[node].is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    struct MemoryFileSystem(HashSet<&'static str>);
+
+    impl FileSystemProvider for MemoryFileSystem {
+        fn read_file(&self, uri: &Uri) -> Option<String> {
+            self.0.contains(uri.as_str()).then(String::new)
+        }
+    }
+
+    fn uri(text: &str) -> Uri {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn test_find_imports() {
+        let code = "/* @import 'no.css'; */\n@import url(\"a.css\") screen;\n@use 'b' as c;\n@IMPORT url(d.css);\na { content: '@import \"e\"'; }";
+        let imports = find_imports(code);
+        let targets: Vec<_> = imports
+            .iter()
+            .map(|import| import.target.as_str())
+            .collect();
+
+        assert_eq!(targets, vec!["a.css", "b", "d.css"]);
+        assert_eq!(imports[1].kind, ImportKind::Use);
+        assert_eq!(
+            &code[imports[0].range.clone()],
+            "@import url(\"a.css\") screen;"
+        );
+        assert_eq!(&code[imports[1].target_range.clone()], "'b'");
+    }
+
+    #[test]
+    fn test_resolve_import_relative() {
+        let file_system = MemoryFileSystem(HashSet::from(["file:///p/styles/base.css"]));
+        let base = uri("file:///p/styles/components/button.css");
+
+        let resolved = resolve_import(&base, "../base.css", Dialect::Css, &file_system).unwrap();
+        assert_eq!(resolved.uri, uri("file:///p/styles/base.css"));
+        assert!(resolved.exists);
+
+        let resolved = resolve_import(&base, "./my icons.css", Dialect::Css, &file_system).unwrap();
+        assert_eq!(
+            resolved.uri,
+            uri("file:///p/styles/components/my%20icons.css")
+        );
+        assert!(!resolved.exists);
+
+        assert_eq!(
+            resolve_import(&base, "https://cdn.test/a.css", Dialect::Css, &file_system),
+            None,
+            "URLs aren't workspace documents"
+        );
+    }
+
+    #[test]
+    fn test_resolve_import_scss_partials() {
+        let file_system = MemoryFileSystem(HashSet::from([
+            "file:///p/_variables.scss",
+            "file:///p/mixins/_index.scss",
+        ]));
+        let base = uri("file:///p/main.scss");
+
+        let resolved = resolve_import(&base, "variables", Dialect::Scss, &file_system).unwrap();
+        assert_eq!(resolved.uri, uri("file:///p/_variables.scss"));
+        let resolved = resolve_import(&base, "mixins", Dialect::Scss, &file_system).unwrap();
+        assert_eq!(resolved.uri, uri("file:///p/mixins/_index.scss"));
+        assert_eq!(
+            resolve_import(&base, "sass:math", Dialect::Scss, &file_system),
+            None,
+            "Built-in modules aren't documents"
+        );
+    }
+
+    #[test]
+    fn test_import_graph() {
+        let file_system = MemoryFileSystem(HashSet::from(["file:///p/base.css"]));
+        let mut graph = ImportGraph::new();
+        let main = uri("file:///p/main.css");
+        let other = uri("file:///p/other.css");
+        let base = uri("file:///p/base.css");
+
+        graph.update(&main, "@import 'base.css';", Dialect::Css, &file_system);
+        graph.update(
+            &other,
+            "@import url(./base.css);",
+            Dialect::Css,
+            &file_system,
+        );
+        assert_eq!(graph.imports_of(&main)[0].uri, base);
+        assert_eq!(graph.importers_of(&base), vec![&main, &other]);
+
+        graph.remove(&other);
+        assert_eq!(graph.importers_of(&base), vec![&main]);
+    }
+
+    #[test]
+    fn test_import_graph_cycles() {
+        let file_system = MemoryFileSystem(HashSet::from([
+            "file:///p/a.css",
+            "file:///p/b.css",
+            "file:///p/c.css",
+        ]));
+        let mut graph = ImportGraph::new();
+        let update = |graph: &mut ImportGraph, name: &str, source: &str| {
+            graph.update(&uri(name), source, Dialect::Css, &file_system)
+        };
+        update(
+            &mut graph,
+            "file:///p/a.css",
+            "@import 'b.css'; @import 'missing.css';",
+        );
+        update(&mut graph, "file:///p/b.css", "@import 'a.css';");
+        update(
+            &mut graph,
+            "file:///p/c.css",
+            "@import 'c.css'; @import 'a.css';",
+        );
+
+        let cycles = graph.cycles();
+        assert_eq!(cycles.len(), 2);
+        assert_eq!(
+            cycles[0],
+            vec![&uri("file:///p/a.css"), &uri("file:///p/b.css")]
+        );
+        assert_eq!(cycles[1], vec![&uri("file:///p/c.css")]);
+
+        let dot = graph.export(GraphFormat::Dot);
+        assert!(dot.starts_with("digraph imports {\n  \"file:///p/a.css\" [color=red];"));
+        assert!(dot.contains("  \"file:///p/missing.css\" [style=dashed];"));
+        assert!(dot.contains(
+            "  \"file:///p/a.css\" -> \"file:///p/b.css\" [label=\"@import\", color=red];"
+        ));
+        assert!(dot.contains("  \"file:///p/c.css\" -> \"file:///p/a.css\" [label=\"@import\"];"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&graph.export(GraphFormat::Json)).unwrap();
+        assert_eq!(json["nodes"].as_array().unwrap().len(), 4);
+        assert_eq!(json["nodes"][3]["exists"], false);
+        assert_eq!(json["edges"][1]["to"], "file:///p/missing.css");
+        assert_eq!(json["edges"][1]["inCycle"], false);
+        assert_eq!(json["cycles"][1][0], "file:///p/c.css");
+    }
+}
@@ -0,0 +1,98 @@
+use crate::parser::parse_css;
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+
+/// Finds the cascade layers declared in a stylesheet, by `@layer` statements and blocks or
+/// by `@import ... layer(name)`, in the order they are first declared, which is their order
+/// in the cascade.
+///
+/// Nested layers get their full name, like `base.reset`, right after their parent.
+/// Anonymous layers, and the layers nested in them, can't be referenced and are skipped.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of the full layer names, without duplicates.
+pub fn find_layers(source: &str) -> Vec<String> {
+    layer_names(&parse_css(source).syntax())
+}
+
+/// Finds the layers declared below a node, see `find_layers`.
+pub(crate) fn layer_names(root: &CssSyntaxNode) -> Vec<String> {
+    let mut layers: Vec<String> = Vec::new();
+    for list in root
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_LAYER_NAME_LIST)
+    {
+        let Some(mut name) = parent_layer(&list) else {
+            continue;
+        };
+        for segment in name_text(&list).split('.') {
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(segment);
+            if !layers.contains(&name) {
+                layers.push(name.clone());
+            }
+        }
+    }
+    layers
+}
+
+/// Returns the full name of the layer block a layer name is declared in, empty at the top
+/// level, or `None` in an anonymous layer.
+fn parent_layer(list: &CssSyntaxNode) -> Option<String> {
+    let mut parents: Vec<String> = list
+        .ancestors()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_LAYER_AT_RULE)
+        // The `@layer` declaring the name, unless it comes from an `@import`
+        .skip(usize::from(!is_import_layer(list)))
+        .map(|at_rule| {
+            let name = at_rule
+                .children()
+                .find(|node| node.kind() == CssSyntaxKind::CSS_LAYER_DECLARATION)
+                .and_then(|declaration| declaration.first_child())
+                .and_then(|references| references.first_child())
+                .map(|name| name_text(&name))
+                .unwrap_or_default();
+            (!name.is_empty()).then_some(name)
+        })
+        .collect::<Option<_>>()?;
+    parents.reverse();
+    Some(parents.join("."))
+}
+
+fn is_import_layer(list: &CssSyntaxNode) -> bool {
+    list.parent()
+        .is_some_and(|parent| parent.kind() == CssSyntaxKind::CSS_IMPORT_NAMED_LAYER)
+}
+
+/// Returns a layer name as written, without whitespace, like `base.reset`.
+pub(crate) fn name_text(list: &CssSyntaxNode) -> String {
+    list.text_trimmed().to_string().split_whitespace().collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_layers() {
+        let code = "@import url(a.css) layer(vendor);\n@import \"b.css\" layer;\n@layer reset, base.elements;\n@layer base {\n  @layer reset, components;\n  a {}\n}\n@layer {\n  @layer hidden;\n}\n@layer reset;";
+        assert_eq!(
+            find_layers(code),
+            vec![
+                "vendor",
+                "reset",
+                "base",
+                "base.elements",
+                "base.reset",
+                "base.components"
+            ]
+        );
+        assert!(find_layers("a { color: red; }").is_empty());
+    }
+}
@@ -1,12 +1,55 @@
 use wasm_bindgen::prelude::wasm_bindgen;
 
 pub mod analyzer;
+pub mod budget;
+pub mod case_folding;
+pub mod chunks;
+pub mod compat;
+pub mod data;
+pub mod dialect;
+pub mod document_store;
+pub mod easing;
+pub mod file_system;
 pub mod formatter;
+pub mod identifiers;
+pub mod import_graph;
+pub mod layers;
+pub mod line_index;
+pub mod media_queries;
 pub mod parser;
+pub mod prelude;
+pub mod registration;
+pub mod requests;
+pub mod rules;
+pub mod selectors;
+pub mod service;
+pub mod shorthands;
+pub mod statistics;
 pub mod text_document;
+pub mod text_range;
+pub mod value_syntax;
+pub mod workspace;
 pub mod features {
+    pub mod abbreviations;
+    pub mod code_actions;
     pub mod colors;
+    pub mod completion;
+    pub mod computed_style;
+    pub mod css_modules;
+    pub mod custom_properties;
+    pub mod diagnostics;
     pub mod folding;
+    pub mod formatting;
+    pub mod hover;
+    pub mod inlay_hints;
+    pub mod keyframes;
+    pub mod on_type_formatting;
+    pub mod palette;
+    pub mod range_analysis;
+    pub mod rule_templates;
+    pub mod selection_range;
+    pub mod semantic_tokens;
+    pub mod symbols;
 }
 
 pub mod types;
@@ -0,0 +1,321 @@
+use lsp_types::{Position, PositionEncodingKind, Range};
+
+/// The unit in which the characters of a `Position` are counted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum PositionEncoding {
+    /// Bytes of UTF-8.
+    Utf8,
+    /// Code units of UTF-16, the default of the protocol.
+    #[default]
+    Utf16,
+    /// Code points.
+    Utf32,
+}
+
+impl PositionEncoding {
+    /// Returns the encoding of a `PositionEncodingKind`, like `utf-8`, or `None` if it isn't
+    /// supported.
+    pub fn from_kind(kind: &PositionEncodingKind) -> Option<PositionEncoding> {
+        match kind.as_str() {
+            "utf-8" => Some(PositionEncoding::Utf8),
+            "utf-16" => Some(PositionEncoding::Utf16),
+            "utf-32" => Some(PositionEncoding::Utf32),
+            _ => None,
+        }
+    }
+
+    /// Returns the `PositionEncodingKind` announced to the client for this encoding.
+    pub fn kind(self) -> PositionEncodingKind {
+        match self {
+            PositionEncoding::Utf8 => PositionEncodingKind::UTF8,
+            PositionEncoding::Utf16 => PositionEncodingKind::UTF16,
+            PositionEncoding::Utf32 => PositionEncodingKind::UTF32,
+        }
+    }
+
+    /// Picks the encoding of the positions from the `general.positionEncodings` capability
+    /// of the client: the first one supported, in the client's order of preference, or
+    /// UTF-16, which every client supports.
+    pub fn negotiate(client_encodings: &[PositionEncodingKind]) -> PositionEncoding {
+        client_encodings
+            .iter()
+            .find_map(PositionEncoding::from_kind)
+            .unwrap_or_default()
+    }
+
+    /// Returns the length of a character in this encoding.
+    pub(crate) fn len(self, c: char) -> usize {
+        match self {
+            PositionEncoding::Utf8 => c.len_utf8(),
+            PositionEncoding::Utf16 => c.len_utf16(),
+            PositionEncoding::Utf32 => 1,
+        }
+    }
+}
+
+/// Maps byte offsets in a source string to LSP line/character positions and back.
+pub struct LineIndex {
+    line_starts: Vec<usize>,
+    /// The encoding of the characters of the positions, UTF-16 unless set.
+    encoding: PositionEncoding,
+}
+
+impl LineIndex {
+    /// Creates a new `LineIndex` for the given source.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code to index.
+    ///
+    /// # Returns
+    ///
+    /// * A `LineIndex` holding the byte offset of every line start.
+    pub fn new(source: &str) -> LineIndex {
+        LineIndex::with_encoding(source, PositionEncoding::Utf16)
+    }
+
+    /// Creates a new `LineIndex` for the given source, counting the characters of positions
+    /// in the given encoding, like the one negotiated with the client.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code to index.
+    /// * `encoding` - The `PositionEncoding` of `position`, `range` and `offset`.
+    ///
+    /// # Returns
+    ///
+    /// * A `LineIndex` holding the byte offset of every line start.
+    pub fn with_encoding(source: &str, encoding: PositionEncoding) -> LineIndex {
+        let line_starts = std::iter::once(0)
+            .chain(source.match_indices('\n').map(|(idx, _)| idx + 1))
+            .collect();
+        LineIndex {
+            line_starts,
+            encoding,
+        }
+    }
+
+    /// Returns the encoding in which this index counts the characters of positions.
+    pub fn encoding(&self) -> PositionEncoding {
+        self.encoding
+    }
+
+    /// Returns the zero-based line number containing the given byte offset.
+    pub fn line(&self, offset: usize) -> usize {
+        self.line_starts
+            .partition_point(|&line_start| line_start <= offset)
+            - 1
+    }
+
+    /// Converts a byte offset into an LSP `Position`, counting characters in the encoding of
+    /// the index.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code this index was built from.
+    /// * `offset` - A byte offset into `source`.
+    ///
+    /// # Returns
+    ///
+    /// * The `Position` of the offset.
+    pub fn position(&self, source: &str, offset: usize) -> Position {
+        self.position_in(source, offset, self.encoding)
+    }
+
+    /// Converts a byte offset into an LSP `Position`, counting characters in the given
+    /// encoding, see `position`.
+    pub fn position_in(&self, source: &str, offset: usize, encoding: PositionEncoding) -> Position {
+        let offset = offset.min(source.len());
+        let line = self.line(offset);
+        let line_start = self.line_starts[line];
+        let character = source[line_start..offset]
+            .chars()
+            .map(|c| encoding.len(c))
+            .sum::<usize>();
+        Position::new(line as u32, character as u32)
+    }
+
+    /// Converts a pair of byte offsets into an LSP `Range`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code this index was built from.
+    /// * `start` - The byte offset where the range starts.
+    /// * `end` - The byte offset where the range ends.
+    ///
+    /// # Returns
+    ///
+    /// * The `Range` covering the offsets.
+    pub fn range(&self, source: &str, start: usize, end: usize) -> Range {
+        self.range_in(source, start, end, self.encoding)
+    }
+
+    /// Converts a pair of byte offsets into an LSP `Range`, counting characters in the given
+    /// encoding, see `range`.
+    pub fn range_in(
+        &self,
+        source: &str,
+        start: usize,
+        end: usize,
+        encoding: PositionEncoding,
+    ) -> Range {
+        Range::new(
+            self.position_in(source, start, encoding),
+            self.position_in(source, end, encoding),
+        )
+    }
+
+    /// Converts an LSP `Position` back into a byte offset, clamping to the end of the line.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source code this index was built from.
+    /// * `position` - A `Position` with its character counted in the encoding of the index.
+    ///
+    /// # Returns
+    ///
+    /// * The byte offset of the position.
+    pub fn offset(&self, source: &str, position: Position) -> usize {
+        self.offset_in(source, position, self.encoding)
+    }
+
+    /// Converts an LSP `Position`, with its character counted in the given encoding, back
+    /// into a byte offset, see `offset`.
+    pub fn offset_in(&self, source: &str, position: Position, encoding: PositionEncoding) -> usize {
+        let Some(&line_start) = self.line_starts.get(position.line as usize) else {
+            return source.len();
+        };
+        let line_end = self
+            .line_starts
+            .get(position.line as usize + 1)
+            .map_or(source.len(), |&next| next - 1);
+
+        let mut remaining = position.character as usize;
+        for (idx, c) in source[line_start..line_end].char_indices() {
+            if remaining == 0 {
+                return line_start + idx;
+            }
+            remaining = remaining.saturating_sub(encoding.len(c));
+        }
+        line_end
+    }
+
+    /// Updates the index after an edit to its source, without reading the rest of it again.
+    ///
+    /// # Arguments
+    ///
+    /// * `replaced` - The byte range of the text replaced by the edit, in the previous source.
+    /// * `inserted` - The text inserted by the edit.
+    pub(crate) fn edit(&mut self, replaced: std::ops::Range<usize>, inserted: &str) {
+        let first = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= replaced.start);
+        let last = self
+            .line_starts
+            .partition_point(|&line_start| line_start <= replaced.end);
+        let shifted: Vec<usize> = self.line_starts[last..]
+            .iter()
+            .map(|&line_start| line_start - replaced.len() + inserted.len())
+            .collect();
+        self.line_starts.truncate(first);
+        self.line_starts.extend(
+            inserted
+                .match_indices('\n')
+                .map(|(idx, _)| replaced.start + idx + 1),
+        );
+        self.line_starts.extend(shifted);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_line_of_offset() {
+        let code = "a {\n  color: red;\n}\n";
+        let index = LineIndex::new(code);
+        assert_eq!(index.line(0), 0);
+        assert_eq!(index.line(4), 1);
+        assert_eq!(index.line(code.len() - 1), 2);
+    }
+
+    #[test]
+    fn test_position_counts_utf16() {
+        let code = "a { content: \"😀\"; }";
+        let index = LineIndex::new(code);
+        let offset = code.find(';').unwrap();
+        assert_eq!(index.position(code, offset), Position::new(0, 17));
+    }
+
+    #[test]
+    fn test_offset_round_trip() {
+        let code = "a {\n  content: \"é😀\";\n}\n";
+        let index = LineIndex::new(code);
+        for (offset, _) in code.char_indices() {
+            let position = index.position(code, offset);
+            assert_eq!(index.offset(code, position), offset);
+        }
+    }
+
+    #[test]
+    fn test_position_encodings() {
+        let code = "a {\n  content: \"é😀\";\n}\n";
+        let index = LineIndex::new(code);
+        let offset = code.find(';').unwrap();
+        let characters = [
+            (PositionEncoding::Utf8, 19),
+            (PositionEncoding::Utf16, 16),
+            (PositionEncoding::Utf32, 15),
+        ];
+        for (encoding, character) in characters {
+            let position = index.position_in(code, offset, encoding);
+            assert_eq!(position, Position::new(1, character), "{encoding:?}");
+            assert_eq!(index.offset_in(code, position, encoding), offset);
+            let encoded = LineIndex::with_encoding(code, encoding);
+            assert_eq!(encoded.position(code, offset), position, "{encoding:?}");
+            assert_eq!(encoded.offset(code, position), offset);
+        }
+    }
+
+    #[test]
+    fn test_edit() {
+        let code = "a {\n  color: red;\n}\nb {}\n";
+        let edits = [
+            (4..4, "\n"),
+            (3..18, " color: blue; "),
+            (0..code.len(), ""),
+            (6..6, "é\n\n"),
+        ];
+        for (replaced, inserted) in edits {
+            let mut index = LineIndex::new(code);
+            index.edit(replaced.clone(), inserted);
+            let mut edited = code.to_string();
+            edited.replace_range(replaced, inserted);
+            assert_eq!(
+                index.line_starts,
+                LineIndex::new(&edited).line_starts,
+                "{edited:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_negotiate_position_encoding() {
+        let utf8 = PositionEncodingKind::UTF8;
+        let other = PositionEncodingKind::new("utf-7");
+        assert_eq!(
+            PositionEncoding::negotiate(&[other.clone(), utf8, PositionEncodingKind::UTF16]),
+            PositionEncoding::Utf8
+        );
+        assert_eq!(
+            PositionEncoding::negotiate(&[other]),
+            PositionEncoding::Utf16
+        );
+        assert_eq!(PositionEncoding::negotiate(&[]), PositionEncoding::Utf16);
+        assert_eq!(
+            PositionEncoding::from_kind(&PositionEncoding::Utf32.kind()),
+            Some(PositionEncoding::Utf32)
+        );
+    }
+}
@@ -0,0 +1,1066 @@
+use crate::case_folding::{eq_ignore_case, fold_case};
+use crate::data::values::LENGTH_UNITS;
+use crate::parser::tokenizer::{Token as CssToken, TokenKind as CssTokenKind, Tokenizer};
+use std::ops::Range;
+
+/// The range features of Media Queries Level 4 whose constraints can be compared, with the
+/// units of their values.
+const RANGE_FEATURES: &[(&str, &[Unit])] = &[
+    ("width", LENGTH_FACTORS),
+    ("height", LENGTH_FACTORS),
+    ("device-width", LENGTH_FACTORS),
+    ("device-height", LENGTH_FACTORS),
+    ("resolution", RESOLUTION_FACTORS),
+    ("aspect-ratio", &[]),
+    ("device-aspect-ratio", &[]),
+    ("color", &[]),
+    ("monochrome", &[]),
+];
+
+/// A unit of a range feature, with its factor to the first unit of its group. Values are
+/// only compared within a group: absolute lengths can't be compared to the font size or
+/// the viewport size, which depend on the user and the device.
+struct Unit {
+    name: &'static str,
+    factor: f64,
+    group: &'static str,
+}
+
+const fn unit(name: &'static str, factor: f64, group: &'static str) -> Unit {
+    Unit {
+        name,
+        factor,
+        group,
+    }
+}
+
+/// The length units converted in media queries. `em` and `rem` both use the initial font
+/// size in media queries, so they are equivalent.
+const LENGTH_FACTORS: &[Unit] = &[
+    unit("px", 1.0, "px"),
+    unit("in", 96.0, "px"),
+    unit("cm", 96.0 / 2.54, "px"),
+    unit("mm", 96.0 / 25.4, "px"),
+    unit("q", 96.0 / 101.6, "px"),
+    unit("pt", 96.0 / 72.0, "px"),
+    unit("pc", 16.0, "px"),
+    unit("em", 1.0, "em"),
+    unit("rem", 1.0, "em"),
+    unit("vw", 1.0, "vw"),
+    unit("vh", 1.0, "vh"),
+    unit("vmin", 1.0, "vmin"),
+    unit("vmax", 1.0, "vmax"),
+];
+
+/// Resolution units, in `dppx`.
+const RESOLUTION_FACTORS: &[Unit] = &[
+    unit("dppx", 1.0, "dppx"),
+    unit("x", 1.0, "dppx"),
+    unit("dpi", 1.0 / 96.0, "dppx"),
+    unit("dpcm", 2.54 / 96.0, "dppx"),
+];
+
+/// A bound of a range feature, like `800px` in `(min-width: 800px)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bound {
+    /// The value in the first unit of its group, like pixels for absolute lengths, or a
+    /// plain number.
+    pub value: f64,
+    /// The group of the unit of the value, like `px` for absolute lengths, empty for plain
+    /// numbers, or `None` for a zero without unit, comparable to any value.
+    pub unit: Option<&'static str>,
+    /// The value, as written.
+    pub text: String,
+    /// Whether the value itself matches, as with `min-` and `<=`.
+    pub inclusive: bool,
+}
+
+/// The values a range feature is restricted to by a condition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constraint {
+    /// The feature, like `width` for `min-width`.
+    pub feature: String,
+    pub min: Option<Bound>,
+    pub max: Option<Bound>,
+}
+
+/// A condition of a media query, like `(min-width: 600px)` or `(hover)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaCondition {
+    /// The condition, lowercased with its whitespace normalized, to compare conditions.
+    pub normalized: String,
+    /// The byte range of the condition in the source, parentheses included.
+    pub range: Range<usize>,
+    /// The constraint of a range feature, if its values could be compared.
+    pub constraint: Option<Constraint>,
+    /// The feature and value of a discrete feature, like `orientation` and `portrait`.
+    pub discrete: Option<(String, String)>,
+}
+
+/// A media query made of an optional media type and conditions joined with `and`, like
+/// `screen and (min-width: 600px)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaQuery {
+    /// The query, lowercased with its whitespace normalized, to compare queries.
+    pub normalized: String,
+    /// The byte range of the query in the source.
+    pub range: Range<usize>,
+    /// Whether the query starts with `not`, matching when the rest doesn't.
+    pub negated: bool,
+    pub media_type: Option<String>,
+    pub conditions: Vec<MediaCondition>,
+}
+
+impl MediaQuery {
+    /// Evaluates the constraints of the conditions of the query together.
+    ///
+    /// # Returns
+    ///
+    /// * The constraint that no value can satisfy, with the bounds of every condition on
+    ///   its feature combined, or `None` if the conditions can all be true at once.
+    ///   Negated queries are never reported.
+    pub fn contradiction(&self) -> Option<Contradiction> {
+        if self.negated {
+            return None;
+        }
+        let constraints: Vec<&Constraint> = self
+            .conditions
+            .iter()
+            .filter_map(|condition| condition.constraint.as_ref())
+            .collect();
+        let range = constraints.iter().find_map(|constraint| {
+            let min = constraint.min.as_ref()?;
+            constraints
+                .iter()
+                .filter(|other| other.feature == constraint.feature)
+                .filter_map(|other| other.max.as_ref())
+                .find(|max| is_empty(min, max))
+                .map(|max| Constraint {
+                    feature: constraint.feature.clone(),
+                    min: Some(min.clone()),
+                    max: Some(max.clone()),
+                })
+        });
+        if let Some(constraint) = range {
+            return Some(Contradiction::Range(constraint));
+        }
+
+        let discrete: Vec<_> = self
+            .conditions
+            .iter()
+            .filter_map(|condition| condition.discrete.as_ref())
+            .collect();
+        discrete
+            .iter()
+            .enumerate()
+            .find_map(|(idx, (feature, value))| {
+                discrete[..idx]
+                    .iter()
+                    .find(|(other_feature, other_value)| {
+                        other_feature == feature && other_value != value
+                    })
+                    .map(|(_, other_value)| Contradiction::Discrete {
+                        feature: feature.clone(),
+                        values: (other_value.clone(), value.clone()),
+                    })
+            })
+    }
+}
+
+/// Why a media query can never match.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Contradiction {
+    /// A range feature would have to be above its maximum, like
+    /// `(min-width: 800px) and (max-width: 600px)`.
+    Range(Constraint),
+    /// A discrete feature would have to take two values, like
+    /// `(orientation: portrait) and (orientation: landscape)`.
+    Discrete {
+        feature: String,
+        values: (String, String),
+    },
+}
+
+impl std::fmt::Display for Contradiction {
+    /// Formats the constraint, like `800px <= width <= 600px`.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Contradiction::Range(constraint) => {
+                let operator = |bound: &Bound| if bound.inclusive { "<=" } else { "<" };
+                if let Some(min) = &constraint.min {
+                    write!(f, "{} {} ", min.text, operator(min))?;
+                }
+                write!(f, "{}", constraint.feature)?;
+                if let Some(max) = &constraint.max {
+                    write!(f, " {} {}", operator(max), max.text)?;
+                }
+                Ok(())
+            }
+            Contradiction::Discrete {
+                feature,
+                values: (first, second),
+            } => write!(f, "{feature}: {first} and {feature}: {second}"),
+        }
+    }
+}
+
+/// Whether no value is between two bounds, if their units can be compared.
+fn is_empty(min: &Bound, max: &Bound) -> bool {
+    let comparable = match (min.unit, max.unit) {
+        (Some(min_unit), Some(max_unit)) => min_unit == max_unit,
+        _ => true,
+    };
+    comparable
+        && (min.value > max.value || (min.value == max.value && !(min.inclusive && max.inclusive)))
+}
+
+/// Parses a media query list, like the prelude of a `@media` rule, reading its queries to
+/// compare them and its media features to check their syntax: the name and value of plain
+/// features, and the comparisons of the range syntax, like `(400px <= width <= 700px)`.
+///
+/// # Arguments
+///
+/// * `text` - The media query list, possibly followed by the block of the at-rule.
+/// * `start` - The byte offset of the list in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * The `MediaQueryList` of the queries, media features and syntax errors of the list.
+pub fn parse_media_query_list(text: &str, start: usize) -> MediaQueryList {
+    let tokens: Vec<CssToken> = Tokenizer::new(text, start)
+        .filter(|token| !matches!(token.kind, CssTokenKind::Whitespace | CssTokenKind::Comment))
+        .take_while(|token| {
+            !matches!(
+                token.kind,
+                CssTokenKind::LeftBrace | CssTokenKind::Semicolon | CssTokenKind::RightBrace
+            )
+        })
+        .collect();
+    read_media_query_list(text, start, &tokens)
+}
+
+/// Reads a media query list from its tokens, see `parse_media_query_list`.
+///
+/// # Arguments
+///
+/// * `text` - The source of the tokens.
+/// * `start` - The byte offset of `text` in the source.
+/// * `tokens` - The tokens of the list, without whitespace and comments.
+pub(crate) fn read_media_query_list(
+    text: &str,
+    start: usize,
+    tokens: &[CssToken],
+) -> MediaQueryList {
+    let tokens = media_tokens(text, start, tokens);
+    let mut list = MediaQueryList::default();
+    let mut depth = 0usize;
+    let mut query_start = 0;
+    for (idx, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            TokenKind::Comma if depth == 0 => {
+                list.queries
+                    .push(read_media_query(&tokens[query_start..idx]));
+                query_start = idx + 1;
+            }
+            _ => {}
+        }
+    }
+    if !tokens.is_empty() {
+        list.queries.push(read_media_query(&tokens[query_start..]));
+    }
+    read_media_features(&tokens, &mut list);
+    list
+}
+
+fn read_media_query(tokens: &[Token]) -> Option<MediaQuery> {
+    let range = tokens.first()?.range.start..tokens.last()?.range.end;
+    let mut negated = false;
+    let mut media_type = None;
+    let mut conditions = Vec::new();
+    let mut normalized = Vec::new();
+
+    let mut idx = 0;
+    let folded = fold_case(tokens[0].text);
+    if tokens[0].kind == TokenKind::Ident && (folded == "not" || folded == "only") {
+        negated = folded == "not";
+        normalized.push(folded.into_owned());
+        idx += 1;
+    }
+    let mut expects_condition = true;
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        if !expects_condition {
+            if token.kind != TokenKind::Ident || !eq_ignore_case(token.text, "and") {
+                return None;
+            }
+            normalized.push("and".to_string());
+            expects_condition = true;
+            idx += 1;
+            continue;
+        }
+        match token.kind {
+            TokenKind::LeftParen => {
+                let close = idx + closing_paren(&tokens[idx + 1..])? + 1;
+                let range = token.range.start..tokens[close].range.end;
+                let condition = read_condition(&tokens[idx + 1..close], range)?;
+                normalized.push(condition.normalized.clone());
+                conditions.push(condition);
+                idx = close + 1;
+            }
+            TokenKind::Ident if media_type.is_none() && conditions.is_empty() => {
+                let folded = fold_case(token.text).into_owned();
+                normalized.push(folded.clone());
+                media_type = Some(folded);
+                idx += 1;
+            }
+            _ => return None,
+        }
+        expects_condition = false;
+    }
+    if expects_condition {
+        return None;
+    }
+    Some(MediaQuery {
+        normalized: normalized.join(" "),
+        range,
+        negated,
+        media_type,
+        conditions,
+    })
+}
+
+/// Returns the index of the `)` closing a parenthesis opened before some tokens.
+fn closing_paren(tokens: &[Token]) -> Option<usize> {
+    let mut depth = 0usize;
+    tokens.iter().position(|token| {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen if depth == 0 => return true,
+            TokenKind::RightParen => depth -= 1,
+            _ => {}
+        }
+        false
+    })
+}
+
+fn read_condition(content: &[Token], range: Range<usize>) -> Option<MediaCondition> {
+    // Nested conditions and functions, like `var()`, can't be analyzed
+    if content.iter().any(|token| {
+        matches!(
+            token.kind,
+            TokenKind::LeftParen | TokenKind::RightParen | TokenKind::Function
+        )
+    }) {
+        return None;
+    }
+    // A ratio, like `16 / 9`, is a single word
+    let mut words: Vec<String> = Vec::new();
+    for token in content {
+        let folded = fold_case(token.text);
+        match words.last_mut() {
+            Some(last) if last.ends_with('/') || token.kind == TokenKind::Slash => {
+                last.push_str(&folded)
+            }
+            _ => words.push(folded.into_owned()),
+        }
+    }
+    let (normalized, constraint, discrete) = match words.as_slice() {
+        [] => return None,
+        [name] => (name.clone(), None, None),
+        [name, colon, value] if colon == ":" => {
+            let normalized = format!("{name}: {value}");
+            match parse_plain_constraint(name, value) {
+                Some(constraint) => (normalized, Some(constraint), None),
+                None if is_range_feature(name) => (normalized, None, None),
+                None => (normalized, None, Some((name.clone(), value.clone()))),
+            }
+        }
+        _ => (words.join(" "), parse_range_constraint(&words), None),
+    };
+    Some(MediaCondition {
+        normalized: format!("({normalized})"),
+        range,
+        constraint,
+        discrete,
+    })
+}
+
+fn is_range_feature(name: &str) -> bool {
+    RANGE_FEATURES
+        .iter()
+        .any(|(feature, _)| eq_ignore_case(feature, name))
+}
+
+/// Parses a condition like `min-width: 600px` or `width: 600px`.
+fn parse_plain_constraint(name: &str, value: &str) -> Option<Constraint> {
+    let (feature, min, max) = if let Some(feature) = name.strip_prefix("min-") {
+        (feature, true, false)
+    } else if let Some(feature) = name.strip_prefix("max-") {
+        (feature, false, true)
+    } else {
+        (name, true, true)
+    };
+    let bound = parse_bound(feature, value, true)?;
+    Some(Constraint {
+        feature: feature.to_string(),
+        min: min.then(|| bound.clone()),
+        max: max.then_some(bound),
+    })
+}
+
+/// Parses a condition in the range syntax, like `width >= 600px` or
+/// `400px < width <= 700px`.
+fn parse_range_constraint(tokens: &[String]) -> Option<Constraint> {
+    let feature_idx = tokens.iter().position(|token| is_range_feature(token))?;
+    let feature = tokens[feature_idx].as_str();
+    let mut constraint = Constraint {
+        feature: feature.to_string(),
+        min: None,
+        max: None,
+    };
+    let mut apply = |value: &str, operator: &str, value_first: bool| -> Option<()> {
+        let inclusive = operator != "<" && operator != ">";
+        let bound = parse_bound(feature, value, inclusive)?;
+        // `600px < width` is `width > 600px`
+        let is_min = matches!(
+            (operator, value_first),
+            ("<" | "<=", true) | (">" | ">=", false)
+        );
+        match operator {
+            "=" => {
+                constraint.min = Some(bound.clone());
+                constraint.max = Some(bound);
+            }
+            _ if is_min => constraint.min = Some(bound),
+            _ => constraint.max = Some(bound),
+        }
+        Some(())
+    };
+    match (&tokens[..feature_idx], &tokens[feature_idx + 1..]) {
+        ([], [operator, value]) => apply(value, operator, false)?,
+        ([value, operator], []) => apply(value, operator, true)?,
+        ([low, low_operator], [high_operator, high]) => {
+            apply(low, low_operator, true)?;
+            apply(high, high_operator, false)?;
+        }
+        _ => return None,
+    }
+    Some(constraint)
+}
+
+fn parse_bound(feature: &str, value: &str, inclusive: bool) -> Option<Bound> {
+    let units = RANGE_FEATURES
+        .iter()
+        .find(|(name, _)| eq_ignore_case(name, feature))
+        .map(|(_, units)| *units)?;
+    let (number, group) = if let Some((numerator, denominator)) = value.split_once('/') {
+        let numerator: f64 = numerator.trim().parse().ok()?;
+        let denominator: f64 = denominator.trim().parse().ok()?;
+        (
+            (denominator != 0.0).then(|| numerator / denominator)?,
+            Some(""),
+        )
+    } else if units.is_empty() {
+        (value.parse().ok()?, Some(""))
+    } else {
+        let unit_start = value
+            .find(|c: char| c.is_ascii_alphabetic())
+            .unwrap_or(value.len());
+        let (number, unit_name) = value.split_at(unit_start);
+        let number: f64 = number.parse().ok()?;
+        match unit_name {
+            // Zero may be written without a unit
+            "" if number == 0.0 => (0.0, None),
+            _ => match units
+                .iter()
+                .find(|unit| eq_ignore_case(unit.name, unit_name))
+            {
+                Some(unit) => (number * unit.factor, Some(unit.group)),
+                // The other lengths, like `ch`, can only be compared with themselves
+                None if units.iter().any(|unit| unit.group == "px") => {
+                    let name = LENGTH_UNITS
+                        .iter()
+                        .find(|name| eq_ignore_case(name, unit_name))?;
+                    (number, Some(*name))
+                }
+                None => return None,
+            },
+        }
+    };
+    number.is_finite().then(|| Bound {
+        value: number,
+        unit: group,
+        text: value.to_string(),
+        inclusive,
+    })
+}
+
+/// How a media feature is tested.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MediaFeatureSyntax {
+    /// Without value, like `(hover)`.
+    Boolean,
+    /// With a value after a colon, like `(min-width: 600px)`.
+    Plain,
+    /// Compared with one or two values, like `(width >= 600px)`.
+    Range,
+}
+
+/// A media feature in parentheses, like `(min-width: 600px)`, `(hover)` or
+/// `(400px <= width < 800px)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MediaFeature {
+    /// The name of the feature as written, like `min-width`, empty if it's missing.
+    pub name: String,
+    /// The byte range of the name, empty after the opening parenthesis if it's missing.
+    pub name_range: Range<usize>,
+    pub syntax: MediaFeatureSyntax,
+    /// The values compared to the feature as written, ratios like `16/9` being written
+    /// `16 / 9`, with their byte ranges, in source order: one for `(width: 600px)` and
+    /// `(width > 600px)`, two for `(400px < width < 800px)`.
+    pub values: Vec<(String, Range<usize>)>,
+    /// The comparison operators of the range syntax, with their byte ranges.
+    pub operators: Vec<(String, Range<usize>)>,
+    /// The byte range of the feature, parentheses included.
+    pub range: Range<usize>,
+    /// Whether the feature has syntax errors, reported in `MediaQueryList::errors`.
+    pub malformed: bool,
+}
+
+/// A syntax error in a media query list, like a missing value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MediaQuerySyntaxError {
+    pub message: String,
+    /// The byte range of the error in the source.
+    pub range: Range<usize>,
+}
+
+/// A media query list, with its media features and syntax errors.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaQueryList {
+    /// A `MediaQuery` per comma separated query, or `None` for queries that can't be
+    /// analyzed, like the ones using `or`, nested conditions or `var()`.
+    pub queries: Vec<Option<MediaQuery>>,
+    /// The media features in source order, malformed ones included.
+    pub features: Vec<MediaFeature>,
+    pub errors: Vec<MediaQuerySyntaxError>,
+}
+
+/// The kind of a token of a media query list.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TokenKind {
+    Ident,
+    /// Any other word, like a number, a dimension or a Sass variable.
+    Value,
+    /// A function with its arguments, like `calc(100vw - 2rem)`.
+    Function,
+    String,
+    LeftParen,
+    RightParen,
+    Comma,
+    Colon,
+    Slash,
+    /// `<`, `<=`, `>`, `>=` or `=`.
+    Comparison,
+}
+
+/// A token of a media query list, made of one or more CSS tokens.
+#[derive(Debug, Clone)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    range: Range<usize>,
+}
+
+/// Groups the CSS tokens of a media query list into the words and punctuation of its media
+/// features: a function and its arguments, a comparison like `<=`, or a Sass variable like
+/// `$tablet` are single tokens.
+fn media_tokens<'a>(text: &'a str, start: usize, tokens: &[CssToken]) -> Vec<Token<'a>> {
+    let mut media_tokens = Vec::new();
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        let adjacent = |kind: &dyn Fn(&CssTokenKind) -> bool| {
+            tokens
+                .get(idx + 1)
+                .is_some_and(|next| next.range.start == token.range.end && kind(&next.kind))
+        };
+        let mut last = idx;
+        let kind = match token.kind {
+            CssTokenKind::Ident => TokenKind::Ident,
+            CssTokenKind::Function { .. } => {
+                let mut depth = 0usize;
+                last = (idx..tokens.len())
+                    .find(|&idx| {
+                        match tokens[idx].kind {
+                            CssTokenKind::LeftParen | CssTokenKind::Function { .. } => depth += 1,
+                            CssTokenKind::RightParen => depth -= 1,
+                            _ => {}
+                        }
+                        depth == 0
+                    })
+                    .unwrap_or(tokens.len() - 1);
+                TokenKind::Function
+            }
+            CssTokenKind::Url | CssTokenKind::BadUrl => TokenKind::Function,
+            CssTokenKind::String | CssTokenKind::BadString => TokenKind::String,
+            CssTokenKind::LeftParen => TokenKind::LeftParen,
+            CssTokenKind::RightParen => TokenKind::RightParen,
+            CssTokenKind::Comma => TokenKind::Comma,
+            CssTokenKind::Colon => TokenKind::Colon,
+            CssTokenKind::Delim('/') => TokenKind::Slash,
+            CssTokenKind::Delim('<' | '>')
+                if adjacent(&|kind| *kind == CssTokenKind::Delim('=')) =>
+            {
+                last += 1;
+                TokenKind::Comparison
+            }
+            CssTokenKind::Delim('<' | '>' | '=') => TokenKind::Comparison,
+            CssTokenKind::Delim('$') if adjacent(&|kind| *kind == CssTokenKind::Ident) => {
+                last += 1;
+                TokenKind::Value
+            }
+            _ => TokenKind::Value,
+        };
+        let range = token.range.start..tokens[last].range.end;
+        media_tokens.push(Token {
+            kind,
+            text: &text[range.start - start..range.end - start],
+            range,
+        });
+        idx = last + 1;
+    }
+    media_tokens
+}
+
+/// Reads the media features of a media query list, the ones of nested conditions included
+/// and the general enclosed ones, like `(foo(bar))`, skipped, with their syntax errors.
+fn read_media_features(tokens: &[Token], list: &mut MediaQueryList) {
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let token = &tokens[idx];
+        if token.kind == TokenKind::Ident
+            && ["and", "or", "not", "only"]
+                .iter()
+                .any(|keyword| eq_ignore_case(token.text, keyword))
+            && tokens
+                .get(idx + 1)
+                .is_none_or(|next| matches!(next.kind, TokenKind::Comma | TokenKind::RightParen))
+        {
+            list.errors.push(MediaQuerySyntaxError {
+                message: format!(
+                    "Expected a media condition after `{}`.",
+                    fold_case(token.text)
+                ),
+                range: token.range.clone(),
+            });
+        }
+        if token.kind != TokenKind::LeftParen {
+            idx += 1;
+            continue;
+        }
+        // Nested conditions are walked token by token
+        let rest = &tokens[idx + 1..];
+        let is_condition = rest.first().is_some_and(|next| {
+            next.kind == TokenKind::LeftParen
+                || (eq_ignore_case(next.text, "not")
+                    && rest
+                        .get(1)
+                        .is_some_and(|next| next.kind == TokenKind::LeftParen))
+        });
+        if is_condition {
+            idx += 1;
+            continue;
+        }
+        let mut depth = 0;
+        let close = rest.iter().position(|token| {
+            match token.kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen if depth == 0 => return true,
+                TokenKind::RightParen => depth -= 1,
+                _ => {}
+            }
+            false
+        });
+        let content = &rest[..close.unwrap_or(rest.len())];
+        let end = match close {
+            Some(close) => rest[close].range.end,
+            None => content.last().unwrap_or(token).range.end,
+        };
+        let is_general_enclosed = content
+            .iter()
+            .any(|token| token.kind == TokenKind::LeftParen)
+            || content
+                .first()
+                .is_some_and(|token| token.kind == TokenKind::Function);
+        if !is_general_enclosed {
+            read_media_feature(content, token.range.start..end, list);
+        }
+        idx += 1 + close.map_or(rest.len(), |close| close + 1);
+    }
+}
+
+fn read_media_feature(content: &[Token], range: Range<usize>, list: &mut MediaQueryList) {
+    let mut feature = MediaFeature {
+        name: String::new(),
+        name_range: range.start + 1..range.start + 1,
+        syntax: MediaFeatureSyntax::Boolean,
+        values: Vec::new(),
+        operators: Vec::new(),
+        range: range.clone(),
+        malformed: false,
+    };
+    let mut error = |feature: &mut MediaFeature, message: String, range: Range<usize>| {
+        feature.malformed = true;
+        list.errors.push(MediaQuerySyntaxError { message, range });
+    };
+    let set_name = |feature: &mut MediaFeature, token: &Token| {
+        feature.name = token.text.to_string();
+        feature.name_range = token.range.clone();
+    };
+
+    if let Some(colon) = content
+        .iter()
+        .position(|token| token.kind == TokenKind::Colon)
+    {
+        feature.syntax = MediaFeatureSyntax::Plain;
+        match &content[..colon] {
+            [name] if name.kind == TokenKind::Ident => set_name(&mut feature, name),
+            _ => error(
+                &mut feature,
+                "Expected a media feature before `:`.".to_string(),
+                content[colon].range.clone(),
+            ),
+        }
+        let after = &content[colon + 1..];
+        match media_feature_value(after) {
+            None => {
+                let message = format!("Expected a value after `{}:`.", feature.name);
+                error(&mut feature, message, range);
+            }
+            Some((value, used)) => {
+                feature.values.push(value);
+                if let Some(unexpected) = after.get(used) {
+                    let message = format!(
+                        "Unexpected `{}` after the value of `{}`.",
+                        unexpected.text, feature.name
+                    );
+                    error(&mut feature, message, unexpected.range.clone());
+                }
+            }
+        }
+    } else if content
+        .iter()
+        .any(|token| token.kind == TokenKind::Comparison)
+    {
+        feature.syntax = MediaFeatureSyntax::Range;
+        let mut operands = Vec::new();
+        for (idx, part) in content
+            .split(|token| token.kind == TokenKind::Comparison)
+            .enumerate()
+        {
+            let operator = content
+                .iter()
+                .filter(|token| token.kind == TokenKind::Comparison)
+                .nth(idx.saturating_sub(1))
+                .expect("a comparison separates the parts");
+            match media_feature_value(part) {
+                None if idx == 0 => {
+                    let message = format!("Expected a value before `{}`.", operator.text);
+                    error(&mut feature, message, operator.range.clone());
+                }
+                None => {
+                    let message = format!("Expected a value after `{}`.", operator.text);
+                    error(&mut feature, message, operator.range.clone());
+                }
+                Some((value, used)) => {
+                    if let Some(unexpected) = part.get(used) {
+                        let message = format!("Unexpected `{}` in this range.", unexpected.text);
+                        error(&mut feature, message, unexpected.range.clone());
+                    }
+                    operands.push((value, part.len() == 1 && part[0].kind == TokenKind::Ident));
+                }
+            }
+            if idx > 0 {
+                feature
+                    .operators
+                    .push((operator.text.to_string(), operator.range.clone()));
+            }
+        }
+        if feature.malformed {
+            list.features.push(feature);
+            return;
+        }
+        let operators: Vec<&str> = feature
+            .operators
+            .iter()
+            .map(|(operator, _)| operator.as_str())
+            .collect();
+        let name = match operands.as_slice() {
+            [(name, true), _] | [_, (name, true)] => Some(name.clone()),
+            [_, (name, true), _] => {
+                let ascending = operators.iter().all(|op| op.starts_with('<'));
+                let descending = operators.iter().all(|op| op.starts_with('>'));
+                if !ascending && !descending {
+                    let operators_range = feature.operators[0].1.start..feature.operators[1].1.end;
+                    error(
+                        &mut feature,
+                        "The comparisons of a range must go in the same direction, like `400px <= width <= 700px`.".to_string(),
+                        operators_range,
+                    );
+                }
+                Some(name.clone())
+            }
+            [_, _, _] => {
+                error(
+                    &mut feature,
+                    "The media feature of a range with two comparisons must be in the middle, like `400px <= width <= 700px`.".to_string(),
+                    range,
+                );
+                None
+            }
+            [_, _] => {
+                error(
+                    &mut feature,
+                    "Expected a media feature in this range.".to_string(),
+                    range,
+                );
+                None
+            }
+            _ => {
+                let unexpected = feature.operators[2].1.clone();
+                error(
+                    &mut feature,
+                    "A range compares a media feature with at most two values.".to_string(),
+                    unexpected,
+                );
+                None
+            }
+        };
+        if let Some((name, name_range)) = name {
+            feature.name = name;
+            feature.name_range = name_range.clone();
+            feature.values = operands
+                .into_iter()
+                .map(|(value, _)| value)
+                .filter(|(_, range)| *range != name_range)
+                .collect();
+        }
+    } else {
+        match content {
+            [] => error(&mut feature, "Expected a media feature.".to_string(), range),
+            [name] if name.kind == TokenKind::Ident => set_name(&mut feature, name),
+            [name, unexpected, ..] if name.kind == TokenKind::Ident => {
+                set_name(&mut feature, name);
+                error(
+                    &mut feature,
+                    format!("Expected `:` or a comparison after `{}`.", name.text),
+                    unexpected.range.clone(),
+                );
+            }
+            [unexpected, ..] => error(
+                &mut feature,
+                format!("Expected a media feature, found `{}`.", unexpected.text),
+                unexpected.range.clone(),
+            ),
+        }
+    }
+    list.features.push(feature);
+}
+
+/// Reads the value at the start of some tokens, a ratio like `16 / 9` being a single
+/// value, and returns it with the number of tokens it spans.
+fn media_feature_value(tokens: &[Token]) -> Option<((String, Range<usize>), usize)> {
+    let first = tokens.first()?;
+    if !matches!(
+        first.kind,
+        TokenKind::Ident | TokenKind::Value | TokenKind::Function
+    ) {
+        return None;
+    }
+    match tokens {
+        [numerator, slash, denominator, ..]
+            if slash.kind == TokenKind::Slash && denominator.kind == TokenKind::Value =>
+        {
+            let text = format!("{} / {}", numerator.text, denominator.text);
+            Some(((text, numerator.range.start..denominator.range.end), 3))
+        }
+        _ => Some(((first.text.to_string(), first.range.clone()), 1)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contradiction(text: &str) -> Option<String> {
+        parse_media_query_list(text, 0).queries[0]
+            .as_ref()
+            .unwrap()
+            .contradiction()
+            .map(|contradiction| contradiction.to_string())
+    }
+
+    #[test]
+    fn test_parse_media_query_list() {
+        let queries =
+            parse_media_query_list("Screen AND (Min-Width:600px), not print, (a) or (b)", 10)
+                .queries;
+        let query = queries[0].as_ref().unwrap();
+        assert_eq!(query.normalized, "screen and (min-width: 600px)");
+        assert_eq!(query.conditions[0].range, 21..38);
+        let constraint = query.conditions[0].constraint.as_ref().unwrap();
+        assert_eq!(constraint.min.as_ref().unwrap().value, 600.0);
+        assert!(queries[1].as_ref().unwrap().negated);
+        assert_eq!(queries[2], None);
+
+        let query = parse_media_query_list("(400px < width <= 50em)", 0).queries[0]
+            .clone()
+            .unwrap();
+        let constraint = query.conditions[0].constraint.as_ref().unwrap();
+        assert!(!constraint.min.as_ref().unwrap().inclusive);
+        assert_eq!(constraint.max.as_ref().unwrap().value, 50.0);
+        assert_eq!(constraint.max.as_ref().unwrap().unit, Some("em"));
+    }
+
+    #[test]
+    fn test_media_query_contradiction() {
+        assert_eq!(
+            contradiction("(min-width: 800px) and (max-width: 600px)").as_deref(),
+            Some("800px <= width <= 600px")
+        );
+        assert_eq!(
+            contradiction("(width > 50em) and (width <= 40rem)").as_deref(),
+            Some("50em < width <= 40rem")
+        );
+        assert_eq!(
+            contradiction("(min-width: 20vw) and (max-width: 10vw)").as_deref(),
+            Some("20vw <= width <= 10vw")
+        );
+        assert_eq!(
+            contradiction("(min-width: 1px) and (max-width: 0)").as_deref(),
+            Some("1px <= width <= 0")
+        );
+        assert_eq!(
+            contradiction("(orientation: portrait) and (orientation: landscape)").as_deref(),
+            Some("orientation: portrait and orientation: landscape")
+        );
+        assert_eq!(
+            contradiction("(min-aspect-ratio: 16/9) and (max-aspect-ratio: 4 / 3)").as_deref(),
+            Some("16/9 <= aspect-ratio <= 4/3")
+        );
+        assert_eq!(
+            contradiction("(min-width: 10ch) and (max-width: 600px)"),
+            None
+        );
+        assert_eq!(
+            contradiction("(min-width: 10CH) and (max-width: 5ch)").as_deref(),
+            Some("10ch <= width <= 5ch")
+        );
+        assert_eq!(
+            contradiction("(min-width: 600px) and (max-width: 37.5em)"),
+            None
+        );
+        assert_eq!(
+            contradiction("(min-resolution: 2dppx) and (max-resolution: 192dpi)"),
+            None
+        );
+        assert_eq!(
+            contradiction("not all and (min-width: 2px) and (max-width: 1px)"),
+            None
+        );
+        assert_eq!(contradiction("(min-width: 1vw) and (max-width: 1px)"), None);
+        assert_eq!(contradiction("(width > 50em) and (width <= 800px)"), None);
+        assert_eq!(
+            contradiction("(min-width: 10vw) and (max-width: 20vh)"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_media_query_list_features() {
+        let text = "screen and (min-width: 600px), (400px <= width < 50em) and ((hover) or (aspect-ratio: 16/ 9)), (foo(bar))";
+        let list = parse_media_query_list(text, 0);
+        assert_eq!(list.errors, vec![]);
+        let features: Vec<(&str, MediaFeatureSyntax, Vec<&str>)> = list
+            .features
+            .iter()
+            .map(|feature| {
+                let values = feature.values.iter().map(|(value, _)| value.as_str());
+                (feature.name.as_str(), feature.syntax, values.collect())
+            })
+            .collect();
+        assert_eq!(
+            features,
+            vec![
+                ("min-width", MediaFeatureSyntax::Plain, vec!["600px"]),
+                ("width", MediaFeatureSyntax::Range, vec!["400px", "50em"]),
+                ("hover", MediaFeatureSyntax::Boolean, vec![]),
+                ("aspect-ratio", MediaFeatureSyntax::Plain, vec!["16 / 9"]),
+            ]
+        );
+        assert_eq!(list.features[1].name_range, 41..46);
+        assert_eq!(list.features[1].range, 31..54);
+        assert_eq!(list.features[3].values[0].1, 86..91);
+    }
+
+    #[test]
+    fn test_parse_media_query_list_errors() {
+        fn errors(text: &str) -> Vec<(String, &str)> {
+            parse_media_query_list(text, 0)
+                .errors
+                .into_iter()
+                .map(|error| (error.message, &text[error.range]))
+                .collect()
+        }
+        assert_eq!(
+            errors("(width: ), (width: 4px 5px), (width 4px)"),
+            vec![
+                ("Expected a value after `width:`.".to_string(), "(width: )"),
+                (
+                    "Unexpected `5px` after the value of `width`.".to_string(),
+                    "5px"
+                ),
+                (
+                    "Expected `:` or a comparison after `width`.".to_string(),
+                    "4px"
+                ),
+            ]
+        );
+        assert_eq!(
+            errors("(400px <= width >= 700px), (400px <= 700px), (width <= )"),
+            vec![
+                (
+                    "The comparisons of a range must go in the same direction, like `400px <= width <= 700px`.".to_string(),
+                    "<= width >="
+                ),
+                (
+                    "Expected a media feature in this range.".to_string(),
+                    "(400px <= 700px)"
+                ),
+                ("Expected a value after `<=`.".to_string(), "<="),
+            ]
+        );
+        assert_eq!(
+            errors("(width < 4px < 5px), (1 < width < 2 < 3), (4px), (), screen and"),
+            vec![
+                (
+                    "The media feature of a range with two comparisons must be in the middle, like `400px <= width <= 700px`.".to_string(),
+                    "(width < 4px < 5px)"
+                ),
+                (
+                    "A range compares a media feature with at most two values.".to_string(),
+                    "<"
+                ),
+                ("Expected a media feature, found `4px`.".to_string(), "4px"),
+                ("Expected a media feature.".to_string(), "()"),
+                (
+                    "Expected a media condition after `and`.".to_string(),
+                    "and"
+                ),
+            ]
+        );
+    }
+}
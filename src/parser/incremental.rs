@@ -0,0 +1,424 @@
+use crate::dialect::Dialect;
+use crate::parser::parse_css_with_dialect;
+use crate::parser::tokenizer::{tokenize, TokenKind};
+use biome_css_parser::{CssParse, ParseDiagnostic};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_diagnostics::console::fmt::{Display, Formatter};
+use biome_diagnostics::console::MarkupBuf;
+use biome_diagnostics::{Diagnostic as _, Location, LogCategory, Visit};
+use biome_rowan::{SyntaxKind as _, TextRange, TextSize};
+use std::io;
+use std::ops::Range;
+
+/// Parses a document again after an edit, reparsing only the top-level rules touched by the
+/// edit and splicing them into the previous syntax tree.
+///
+/// The rules are reparsed with the rule after them, whose leading trivia takes the line
+/// breaks typed at their end. The whole document is parsed again when the edit may change
+/// how the rest of it is read, like an unclosed `{` or comment, or when the rules touched
+/// don't start after a `}` or a `;`, or after balanced code.
+///
+/// # Arguments
+///
+/// * `previous` - The `CssParse` of the document before the edit.
+/// * `code` - The code of the document after the edit.
+/// * `replaced` - The byte range of the text replaced by the edit, in the previous code.
+/// * `inserted_len` - The length in bytes of the text inserted by the edit.
+/// * `dialect` - The stylesheet language of the document.
+///
+/// # Returns
+///
+/// * The `CssParse` of `code`, the same as `parse_css_with_dialect` would return.
+pub fn reparse_css(
+    previous: &CssParse,
+    code: &str,
+    replaced: Range<usize>,
+    inserted_len: usize,
+    dialect: Dialect,
+) -> CssParse {
+    reparse_rules(previous, code, replaced, inserted_len, dialect)
+        .unwrap_or_else(|| parse_css_with_dialect(code, dialect))
+}
+
+/// Reparses the top-level rules touched by an edit, see `reparse_css`, or returns `None` if
+/// the whole document must be parsed again.
+fn reparse_rules(
+    previous: &CssParse,
+    code: &str,
+    replaced: Range<usize>,
+    inserted_len: usize,
+    dialect: Dialect,
+) -> Option<CssParse> {
+    let root = previous.syntax();
+    let previous_len = usize::from(root.text_range().end());
+    if replaced.end > previous_len || previous_len - replaced.len() + inserted_len != code.len() {
+        return None;
+    }
+    let list = root
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_RULE_LIST)?;
+    let rules: Vec<CssSyntaxNode> = list.children().collect();
+    // Edits after the last rule are in the trivia of `EOF`, reparsed with the last rule
+    let mut first = rules
+        .iter()
+        .position(|rule| usize::from(rule.text_range().end()) >= replaced.start)
+        .or(rules.len().checked_sub(1))?;
+    // Rules are only read the same way when they start after the end of another rule
+    while !starts_statement(&rules, first) {
+        first = first.checked_sub(1)?;
+    }
+    let touched = rules
+        .iter()
+        .rposition(|rule| usize::from(rule.text_range().start()) <= replaced.end)?
+        .max(first);
+    let last = (touched + 1).min(rules.len() - 1);
+    // The end of the document is reparsed with its last rule, for the trivia of `EOF` and
+    // the errors of an unclosed rule
+    let to_end = last + 1 == rules.len();
+
+    let start = usize::from(rules[first].text_range().start());
+    // Invalid statements are only found the same way after balanced code. Rules read without
+    // errors are balanced, so only the rules with errors before the region are tokenized
+    let mut erroneous: Vec<Range<usize>> = previous
+        .diagnostics()
+        .iter()
+        .filter_map(|diagnostic| diagnostic.location().span)
+        .filter(|span| first > 0 && usize::from(span.start()) < start)
+        .map(|span| {
+            let before = &rules[..first];
+            let lo = before
+                .partition_point(|rule| rule.text_range().end() <= span.start())
+                .min(first - 1);
+            let hi = before
+                .partition_point(|rule| rule.text_range().start() < span.end())
+                .clamp(lo + 1, first);
+            usize::from(rules[lo].text_range().start())
+                ..usize::from(rules[hi - 1].text_range().end())
+        })
+        .collect();
+    erroneous.dedup();
+    if erroneous
+        .into_iter()
+        .any(|rules| !is_balanced(&code[rules]))
+    {
+        return None;
+    }
+    let previous_end = if to_end {
+        previous_len
+    } else {
+        usize::from(rules[last].text_range().end())
+    };
+    let end = previous_end - replaced.len() + inserted_len;
+    let region = code.get(start..end)?;
+    // The rules after the region were read after the previous rules, which must have been
+    // balanced too
+    if !to_end {
+        let previous_region = root.text().slice(TextRange::new(
+            TextSize::try_from(start).ok()?,
+            TextSize::try_from(previous_end).ok()?,
+        ));
+        if !is_self_contained(region) || !is_self_contained(&previous_region.to_string()) {
+            return None;
+        }
+    }
+
+    let parse = parse_css_with_dialect(region, dialect);
+    let region_root = parse.syntax();
+    let mut children = region_root.children_with_tokens();
+    let region_list = children.next()?.into_node()?;
+    let region_eof = children.next()?.into_token()?;
+    // Errors at the end of the region, like a missing `}`, would read the next rules
+    let ends_early = parse.diagnostics().iter().any(|diagnostic| {
+        diagnostic
+            .location()
+            .span
+            .is_some_and(|span| usize::from(span.start()) >= region.len())
+    });
+    if region_list.kind() != CssSyntaxKind::CSS_RULE_LIST
+        || (!to_end && (ends_early || !region_eof.text().is_empty()))
+    {
+        return None;
+    }
+
+    let spliced = list.clone().splice_slots(
+        first..=last,
+        region_list.children().map(|rule| Some(rule.into())),
+    );
+    let mut new_root = root.clone().replace_child(list.into(), spliced.into())?;
+    if to_end {
+        let eof = new_root.last_token()?;
+        new_root = new_root.replace_child(eof.into(), region_eof.into())?;
+    }
+
+    let diagnostics = shift_diagnostics(previous, start..previous_end, end, parse)?;
+    Some(CssParse::new(new_root, diagnostics))
+}
+
+/// Returns whether a top-level rule starts a statement: it is the first rule of the
+/// document, or follows a valid rule ending with a `}`, or an at-rule ending with a `;`,
+/// which can't take the tokens after it like an invalid rule does.
+fn starts_statement(rules: &[CssSyntaxNode], idx: usize) -> bool {
+    let Some(previous) = idx.checked_sub(1).map(|previous| &rules[previous]) else {
+        return rules[idx]
+            .first_token()
+            .is_some_and(|token| token.prev_token().is_none());
+    };
+    !previous.kind().is_bogus()
+        && previous.last_token().is_some_and(|token| {
+            token.kind() == CssSyntaxKind::R_CURLY
+                || (token.kind() == CssSyntaxKind::SEMICOLON
+                    && previous.kind() == CssSyntaxKind::CSS_AT_RULE)
+        })
+}
+
+/// Returns whether a region of code reads the same on its own and followed by more rules: its
+/// brackets are balanced, its comments closed, and it ends with a `}` or a `;`.
+fn is_self_contained(region: &str) -> bool {
+    matches!(
+        read_balanced(region),
+        Some(Some(TokenKind::RightBrace | TokenKind::Semicolon))
+    )
+}
+
+/// Returns whether the brackets of a region of code are balanced and its comments closed.
+fn is_balanced(region: &str) -> bool {
+    read_balanced(region).is_some()
+}
+
+/// Reads the tokens of a region of code, see `is_balanced`.
+///
+/// # Returns
+///
+/// * The kind of the last token that isn't whitespace or a comment, or `None` if the region
+///   isn't balanced.
+fn read_balanced(region: &str) -> Option<Option<TokenKind<'_>>> {
+    let mut open: Vec<char> = Vec::new();
+    let mut last = None;
+    for token in tokenize(region, 0) {
+        let closing = match token.kind {
+            TokenKind::Whitespace => continue,
+            TokenKind::Comment if token.text.len() < 4 || !token.text.ends_with("*/") => {
+                return None;
+            }
+            TokenKind::Comment => continue,
+            TokenKind::LeftBrace => {
+                open.push('}');
+                None
+            }
+            TokenKind::LeftBracket => {
+                open.push(']');
+                None
+            }
+            TokenKind::LeftParen | TokenKind::Function { .. } => {
+                open.push(')');
+                None
+            }
+            TokenKind::RightBrace => Some('}'),
+            TokenKind::RightBracket => Some(']'),
+            TokenKind::RightParen => Some(')'),
+            _ => None,
+        };
+        if let Some(closing) = closing {
+            if open.pop() != Some(closing) {
+                return None;
+            }
+        }
+        last = Some(token.kind);
+    }
+    open.is_empty().then_some(last)
+}
+
+/// Merges the diagnostics of the previous parse outside of the reparsed region with those of
+/// the region, moved to their offsets in the document.
+///
+/// # Arguments
+///
+/// * `previous` - The `CssParse` of the document before the edit.
+/// * `replaced` - The byte range of the region in the previous code.
+/// * `end` - The end of the region in the new code.
+/// * `parse` - The `CssParse` of the region.
+///
+/// # Returns
+///
+/// * The diagnostics, sorted by offset, or `None` if one of them spans the region and others,
+///   or can't be moved.
+fn shift_diagnostics(
+    previous: &CssParse,
+    replaced: Range<usize>,
+    end: usize,
+    parse: CssParse,
+) -> Option<Vec<ParseDiagnostic>> {
+    let offset = isize::try_from(end)
+        .ok()?
+        .checked_sub(isize::try_from(replaced.end).ok()?)?;
+    let mut diagnostics = Vec::new();
+    for diagnostic in previous.diagnostics() {
+        let span = diagnostic.location().span.unwrap_or_default();
+        let span = usize::from(span.start())..usize::from(span.end());
+        if span.end <= replaced.start && span.start < replaced.start {
+            diagnostics.push(diagnostic.clone());
+        } else if span.start > replaced.end || (span.start == replaced.end && !span.is_empty()) {
+            diagnostics.push(shift_diagnostic(diagnostic, offset)?);
+        } else if span.start < replaced.start || span.end > replaced.end {
+            return None;
+        }
+    }
+    let start = isize::try_from(replaced.start).ok()?;
+    for diagnostic in parse.diagnostics() {
+        diagnostics.push(shift_diagnostic(diagnostic, start)?);
+    }
+    diagnostics.sort_by_key(|diagnostic| diagnostic.location().span.map(|span| span.start()));
+    Some(diagnostics)
+}
+
+/// Moves a parse diagnostic by an offset, with its details and hints.
+///
+/// `ParseDiagnostic` doesn't let its span be changed, so it is built again from its message
+/// and the advices it records.
+///
+/// # Returns
+///
+/// * The moved diagnostic, or `None` if one of its spans would leave the range of a `TextSize`.
+fn shift_diagnostic(diagnostic: &ParseDiagnostic, offset: isize) -> Option<ParseDiagnostic> {
+    let shift = |span: TextRange| {
+        let shift = |size: TextSize| {
+            let size = usize::from(size).checked_add_signed(offset)?;
+            TextSize::try_from(size).ok()
+        };
+        Some(TextRange::new(shift(span.start())?, shift(span.end())?))
+    };
+    let mut message = MarkupBuf::default();
+    let _ = diagnostic.message(&mut Formatter::new(&mut message));
+    let mut advices = ParserAdvices::default();
+    let _ = diagnostic.advices(&mut advices);
+
+    let span = match diagnostic.location().span {
+        Some(span) => shift(span)?,
+        None => TextRange::default(),
+    };
+    let mut shifted = ParseDiagnostic::new(message, span);
+    let mut advices = advices.0.into_iter().peekable();
+    while let Some(advice) = advices.next() {
+        let ParserAdvice::Log(message) = advice else {
+            continue;
+        };
+        shifted = match advices.next_if(|advice| !matches!(advice, ParserAdvice::Log(_))) {
+            Some(ParserAdvice::Frame(Some(span))) => shifted.with_detail(shift(span)?, message),
+            Some(ParserAdvice::Frame(None)) => shifted.with_detail(None::<TextRange>, message),
+            Some(ParserAdvice::List(list)) => shifted.with_alternatives(message, &list),
+            _ => shifted.with_hint(message),
+        };
+    }
+    Some(shifted)
+}
+
+/// An advice recorded by a parse diagnostic: a detail is a log followed by a frame, a hint a
+/// lone log, and alternatives a log followed by a list.
+enum ParserAdvice {
+    Log(MarkupBuf),
+    Frame(Option<TextRange>),
+    List(Vec<MarkupBuf>),
+}
+
+/// Collects the advices of a parse diagnostic, see `shift_diagnostic`.
+#[derive(Default)]
+struct ParserAdvices(Vec<ParserAdvice>);
+
+impl Visit for ParserAdvices {
+    fn record_log(&mut self, _category: LogCategory, text: &dyn Display) -> io::Result<()> {
+        self.0.push(ParserAdvice::Log(markup_buf(text)));
+        Ok(())
+    }
+
+    fn record_list(&mut self, list: &[&dyn Display]) -> io::Result<()> {
+        let list = list.iter().map(|item| markup_buf(*item)).collect();
+        self.0.push(ParserAdvice::List(list));
+        Ok(())
+    }
+
+    fn record_frame(&mut self, location: Location<'_>) -> io::Result<()> {
+        self.0.push(ParserAdvice::Frame(location.span));
+        Ok(())
+    }
+}
+
+/// Writes a markup to an owned buffer, keeping its formatting.
+fn markup_buf(text: &dyn Display) -> MarkupBuf {
+    let mut buffer = MarkupBuf::default();
+    let _ = text.fmt(&mut Formatter::new(&mut buffer));
+    buffer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::parse_css;
+
+    /// Reparses `code` after replacing `replaced` with `text`, checking that the result is the
+    /// same as a full parse, and returns whether the rules were reparsed on their own.
+    fn assert_reparse(code: &str, replaced: Range<usize>, text: &str) -> bool {
+        let previous = parse_css(code);
+        let mut edited = code.to_string();
+        edited.replace_range(replaced.clone(), text);
+        let incremental = reparse_rules(
+            &previous,
+            &edited,
+            replaced.clone(),
+            text.len(),
+            Dialect::Css,
+        );
+        let parse = reparse_css(&previous, &edited, replaced, text.len(), Dialect::Css);
+        let expected = parse_css(&edited);
+        assert_eq!(
+            format!("{:#?}", parse.syntax()),
+            format!("{:#?}", expected.syntax()),
+            "{edited:?}"
+        );
+        // With their messages, spans, details and hints
+        let diagnostics = |parse: &CssParse| -> Vec<String> {
+            parse
+                .diagnostics()
+                .iter()
+                .map(|d| format!("{d:?}"))
+                .collect()
+        };
+        assert_eq!(diagnostics(&parse), diagnostics(&expected), "{edited:?}");
+        incremental.is_some()
+    }
+
+    #[test]
+    fn test_reparse_css() {
+        let code = "a { color: red; }\nb { top: 0 }\nc { margin: 0 }\n";
+        // Typing in a rule
+        assert!(assert_reparse(code, 22..22, "p"));
+        assert!(assert_reparse(code, 13..16, "blue"));
+        // Typing invalid code in a rule
+        assert!(assert_reparse(code, 22..25, "colo"));
+        // Adding a line break after a rule
+        assert!(assert_reparse(code, 17..17, "\n"));
+        // Appending at the end of the document
+        assert!(assert_reparse(code, code.len()..code.len(), "d { top: 0 }"));
+        assert!(assert_reparse(code, code.len()..code.len(), "d {"));
+        // Removing a rule
+        assert!(assert_reparse(code, 18..31, ""));
+        // Errors after the edit are moved with their details and hints
+        let code = "a { color: red; }\nb { color: red !imp; }\nc { top: 0; }\nd { margin: 0 ";
+        assert!(assert_reparse(code, 11..14, "blue"));
+        assert!(assert_reparse(code, 45..51, "top: 1px"));
+    }
+
+    #[test]
+    fn test_reparse_css_falls_back_to_full_parse() {
+        let code = "a { color: red; }\nb { top: 0 }\nc { margin: 0 }\nd {}\n";
+        // An unclosed block or comment takes the rules after it
+        assert!(!assert_reparse(code, 20..21, "{ {"));
+        assert!(!assert_reparse(code, 18..18, "/*"));
+        assert!(!assert_reparse(code, 16..17, ""));
+        // Errors before the edit are kept
+        assert!(assert_reparse(
+            "a { colo }\nb { top: 0 }\nc {}",
+            23..23,
+            "d"
+        ));
+    }
+}
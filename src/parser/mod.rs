@@ -0,0 +1,417 @@
+use crate::dialect::Dialect;
+use biome_css_parser::{parse_css as biome_parse_css, CssParse, CssParserOptions, ParseDiagnostic};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_diagnostics::Diagnostic as _;
+use biome_rowan::{TextRange, TextSize};
+use recovery::{mask_invalid_statements, restore_masked_text};
+use std::borrow::Cow;
+use std::ops::Range;
+use tokenizer::{tokenize, TokenKind};
+
+pub mod incremental;
+mod recovery;
+pub mod tokenizer;
+
+/// Parses CSS code and returns the parse result.
+///
+/// The syntax tree is lossless: it keeps every byte of the code, whitespace and comments
+/// included. Statements that are neither declarations nor rules, like a half-typed `colo`,
+/// are skipped up to the next `;` or `}` as browsers do, and kept as skipped trivia, so the
+/// declarations and rules after them are still read.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the CSS code to parse.
+///
+/// # Returns
+///
+/// * A `CssParse` struct containing the syntax tree and diagnostics.
+pub fn parse_css(code: &str) -> CssParse {
+    parse_css_with_dialect(code, Dialect::Css)
+}
+
+/// Parses the code of a stylesheet written in a dialect, see `parse_css`. Invalid
+/// statements are only skipped when they can't be valid in the dialect.
+///
+/// # Arguments
+///
+/// * `code` - A string slice that holds the code to parse.
+/// * `dialect` - The stylesheet language of the code.
+///
+/// # Returns
+///
+/// * A `CssParse` struct containing the syntax tree and diagnostics.
+pub fn parse_css_with_dialect(code: &str, dialect: Dialect) -> CssParse {
+    // Create parser options, reading `//` comments in SCSS and Less
+    let options = if dialect.has_line_comments() {
+        CssParserOptions::default().allow_wrong_line_comments()
+    } else {
+        CssParserOptions::default()
+    };
+
+    // Some media features get the parser stuck, and invalid statements make it skip the
+    // rest of their block, they are blanked out instead
+    let (masked, invalid) = mask_invalid_statements(code, dialect);
+    if let Cow::Borrowed(_) = masked {
+        let parse = biome_parse_css(code, options);
+        let root = parse.syntax();
+        let mut diagnostics = parse.into_diagnostics();
+        diagnostics.sort_by_key(|diagnostic| diagnostic.location().span.map(|span| span.start()));
+        return CssParse::new(root, diagnostics);
+    }
+
+    // Parse the CSS code, then put the blanked out text back as skipped trivia
+    let parse = biome_parse_css(&masked, options);
+    let root = restore_masked_text(parse.syntax(), code);
+    let mut diagnostics = parse.into_diagnostics();
+    diagnostics.extend(invalid.into_iter().filter_map(|statement| {
+        let range = TextRange::new(
+            TextSize::try_from(statement.range.start).ok()?,
+            TextSize::try_from(statement.range.end).ok()?,
+        );
+        Some(ParseDiagnostic::new(statement.message, range))
+    }));
+    diagnostics.sort_by_key(|diagnostic| diagnostic.location().span.map(|span| span.start()));
+    CssParse::new(root, diagnostics)
+}
+
+/// A declaration found in the syntax tree, valid or bogus.
+pub struct Declaration {
+    /// The `CSS_DECLARATION` node.
+    pub node: CssSyntaxNode,
+    /// The property name, as written.
+    pub name: String,
+    /// Byte range of the property name.
+    pub name_range: Range<usize>,
+    /// The value, as written and without surrounding whitespace.
+    pub value: String,
+    /// Byte range of the value, empty if the declaration has no value.
+    pub value_range: Range<usize>,
+}
+
+/// Finds every declaration below the given node, in source order.
+///
+/// # Arguments
+///
+/// * `node` - The node to search, usually the root of a `CssParse` or a block.
+///
+/// # Returns
+///
+/// * A vector of `Declaration`, including those nested in inner rules.
+pub fn find_declarations(node: &CssSyntaxNode) -> Vec<Declaration> {
+    node.descendants().filter_map(read_declaration).collect()
+}
+
+/// Reads a declaration from its node, for walks handling other nodes too, see
+/// `find_declarations`.
+///
+/// # Arguments
+///
+/// * `node` - Any node of the syntax tree.
+///
+/// # Returns
+///
+/// * The `Declaration`, or `None` if the node isn't a `CSS_DECLARATION` with a `:`.
+pub fn read_declaration(node: CssSyntaxNode) -> Option<Declaration> {
+    if node.kind() != CssSyntaxKind::CSS_DECLARATION {
+        return None;
+    }
+    let property = node.first_child()?;
+    let name_node = property.first_child()?;
+    let name_range = name_node.text_trimmed_range();
+    let name_range = usize::from(name_range.start())..usize::from(name_range.end());
+
+    let colon = property
+        .children_with_tokens()
+        .filter_map(|element| element.into_token())
+        .find(|token| token.kind() == CssSyntaxKind::COLON)?;
+    let value_start = usize::from(colon.text_trimmed_range().end());
+    let value_end = usize::from(property.text_trimmed_range().end()).max(value_start);
+    let text = property.text_trimmed().to_string();
+    let property_start = usize::from(property.text_trimmed_range().start());
+
+    let raw_value = &text[value_start - property_start..value_end - property_start];
+    let leading = raw_value.len() - raw_value.trim_start().len();
+    let value = raw_value.trim().to_string();
+    let value_range = value_start + leading..value_start + leading + value.len();
+
+    Some(Declaration {
+        name: text[name_range.start - property_start..name_range.end - property_start].to_string(),
+        name_range,
+        value,
+        value_range,
+        node,
+    })
+}
+
+/// Splits a comma separated value into its items, ignoring commas nested in functions or
+/// strings.
+///
+/// # Arguments
+///
+/// * `value` - The text of the value.
+/// * `value_start` - The byte offset of the value in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * A vector of the items without surrounding whitespace, with their byte ranges.
+pub fn split_list(value: &str, value_start: usize) -> Vec<(&str, Range<usize>)> {
+    let mut items = Vec::new();
+    let mut depth = 0usize;
+    let mut item_start = 0;
+
+    let mut push_item = |start: usize, end: usize| {
+        let part = &value[start..end];
+        let leading = part.len() - part.trim_start().len();
+        let item = part.trim();
+        let item_start = value_start + start + leading;
+        items.push((item, item_start..item_start + item.len()));
+    };
+
+    for token in tokenize(value, 0) {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::Function { .. } => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            TokenKind::Comma if depth == 0 => {
+                push_item(item_start, token.range.start);
+                item_start = token.range.end;
+            }
+            _ => {}
+        }
+    }
+    push_item(item_start, value.len());
+    items
+}
+
+/// Splits a value into its whitespace separated components, ignoring whitespace nested in
+/// functions or strings.
+///
+/// # Arguments
+///
+/// * `value` - The text of the value.
+/// * `value_start` - The byte offset of the value in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * A vector of the components, with their byte ranges.
+pub fn split_components(value: &str, value_start: usize) -> Vec<(&str, Range<usize>)> {
+    let mut components = Vec::new();
+    let mut depth = 0usize;
+    let mut component_start = None;
+
+    for token in tokenize(value, 0) {
+        match token.kind {
+            TokenKind::Whitespace if depth == 0 => {
+                if let Some(start) = component_start.take() {
+                    let end = token.range.start;
+                    components.push((&value[start..end], value_start + start..value_start + end));
+                }
+                continue;
+            }
+            TokenKind::LeftParen | TokenKind::Function { .. } => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        component_start.get_or_insert(token.range.start);
+    }
+    if let Some(start) = component_start {
+        components.push((
+            &value[start..],
+            value_start + start..value_start + value.len(),
+        ));
+    }
+    components
+}
+
+/// A function call found in a value, like `url(a.png)`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionCall<'a> {
+    /// The function name, as written.
+    pub name: &'a str,
+    /// The text between the parentheses.
+    pub arguments: &'a str,
+    /// The byte offset of the arguments in the source.
+    pub arguments_start: usize,
+    /// The byte range of the whole call, from its name to its closing parenthesis.
+    pub range: Range<usize>,
+}
+
+/// Finds the function calls of a value, including nested ones, ignoring strings and calls
+/// that aren't closed.
+///
+/// # Arguments
+///
+/// * `value` - The text of the value.
+/// * `value_start` - The byte offset of the value in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * A vector of `FunctionCall`, outer calls before the calls nested in them.
+pub fn find_function_calls(value: &str, value_start: usize) -> Vec<FunctionCall<'_>> {
+    let mut calls = Vec::new();
+    // The name start and arguments start of the open parentheses, without a name for a
+    // lone `(`
+    let mut open: Vec<Option<(usize, usize)>> = Vec::new();
+
+    for token in tokenize(value, 0) {
+        match token.kind {
+            TokenKind::Function { .. } => {
+                open.push(Some((token.range.start, token.range.end)));
+            }
+            TokenKind::LeftParen => open.push(None),
+            TokenKind::RightParen => {
+                if let Some(Some((name_start, arguments_start))) = open.pop() {
+                    calls.push(FunctionCall {
+                        name: &value[name_start..arguments_start - 1],
+                        arguments: &value[arguments_start..token.range.start],
+                        arguments_start: value_start + arguments_start,
+                        range: value_start + name_start..value_start + token.range.end,
+                    });
+                }
+            }
+            // An unquoted `url()` is a single token, closed if it ends with its `)`
+            TokenKind::Url | TokenKind::BadUrl if token.text.ends_with(')') => {
+                let paren = token.text.find('(').unwrap_or_default();
+                calls.push(FunctionCall {
+                    name: &token.text[..paren],
+                    arguments: &token.text[paren + 1..token.text.len() - 1],
+                    arguments_start: value_start + token.range.start + paren + 1,
+                    range: value_start + token.range.start..value_start + token.range.end,
+                });
+            }
+            _ => {}
+        }
+    }
+    calls.sort_by_key(|call| call.range.start);
+    calls
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_valid_css() {
+        let code = "body { margin: 0; }";
+        let parse = parse_css(code);
+        assert!(
+            !parse.has_errors(),
+            "Parsing valid CSS should not have errors"
+        );
+    }
+
+    #[test]
+    fn test_parse_invalid_css() {
+        let code = "body { margin }";
+        let parse = parse_css(code);
+        assert!(parse.has_errors(), "Parsing invalid CSS should have errors");
+    }
+
+    #[test]
+    fn test_parse_css_recovers_from_invalid_statements() {
+        let code = "a { color: red;\n  marg 0;\n  padding: 1px;\n}\nb { col }\nc { top: 0 }";
+        let parse = parse_css(code);
+        let root = parse.syntax();
+
+        assert_eq!(
+            root.to_string(),
+            code,
+            "The tree should keep the code as written"
+        );
+        let names: Vec<_> = find_declarations(&root)
+            .into_iter()
+            .map(|declaration| declaration.name)
+            .collect();
+        assert_eq!(names, vec!["color", "padding", "top"]);
+        let rules = root
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_QUALIFIED_RULE)
+            .count();
+        assert_eq!(rules, 3);
+
+        let diagnostics: Vec<_> = parse
+            .diagnostics()
+            .iter()
+            .map(|diagnostic| {
+                let span = diagnostic.location().span.unwrap_or_default();
+                &code[usize::from(span.start())..usize::from(span.end())]
+            })
+            .collect();
+        assert_eq!(diagnostics, vec!["marg 0", "col"]);
+    }
+
+    #[test]
+    fn test_parse_css_keeps_masked_media_features() {
+        let code = "@media (min-width: ) { a { top: 0 } }";
+        let parse = parse_css(code);
+        assert_eq!(parse.syntax().to_string(), code);
+    }
+
+    #[test]
+    fn test_find_declarations() {
+        let code = "a { color: red; b { margin :  0 auto ; } }";
+        let parse = parse_css(code);
+        let declarations = find_declarations(&parse.syntax());
+
+        assert_eq!(declarations.len(), 2, "Expected two declarations");
+        assert_eq!(declarations[0].name, "color");
+        assert_eq!(declarations[0].value, "red");
+        assert_eq!(&code[declarations[0].value_range.clone()], "red");
+        assert_eq!(declarations[1].name, "margin");
+        assert_eq!(&code[declarations[1].name_range.clone()], "margin");
+        assert_eq!(&code[declarations[1].value_range.clone()], "0 auto");
+    }
+
+    #[test]
+    fn test_split_list() {
+        let value = "url(a,b.woff) format(\"woff\", \"x,y\"),  local(A)";
+        let items = split_list(value, 10);
+
+        assert_eq!(items.len(), 2, "Nested commas should not split items");
+        assert_eq!(items[0].0, "url(a,b.woff) format(\"woff\", \"x,y\")");
+        assert_eq!(items[1].0, "local(A)");
+        assert_eq!(
+            items[1].1,
+            10 + value.find("local").unwrap()..10 + value.len()
+        );
+        let items: Vec<_> = split_list("a /* , */ b, c\\,d", 0)
+            .into_iter()
+            .map(|(item, _)| item)
+            .collect();
+        assert_eq!(items, vec!["a /* , */ b", "c\\,d"]);
+    }
+
+    #[test]
+    fn test_split_components() {
+        let value = "url( a b.png )  2x type(\"image/png\")";
+        let components: Vec<_> = split_components(value, 0)
+            .into_iter()
+            .map(|(component, _)| component)
+            .collect();
+        assert_eq!(
+            components,
+            vec!["url( a b.png )", "2x", "type(\"image/png\")"]
+        );
+        let escaped: Vec<_> = split_components("\\31 23 a\\ b c", 0)
+            .into_iter()
+            .map(|(component, _)| component)
+            .collect();
+        assert_eq!(escaped, vec!["\\31 23", "a\\ b", "c"]);
+    }
+
+    #[test]
+    fn test_find_function_calls() {
+        let value = "image-set(url(\"a(.png\") 1x, linear-gradient(red, blue) 2x)";
+        let calls = find_function_calls(value, 5);
+        let names: Vec<_> = calls.iter().map(|call| call.name).collect();
+
+        assert_eq!(names, vec!["image-set", "url", "linear-gradient"]);
+        assert_eq!(calls[0].range, 5..5 + value.len());
+        assert_eq!(calls[1].arguments, "\"a(.png\"");
+        assert_eq!(calls[2].arguments_start, 5 + value.find("red").unwrap());
+        let calls = find_function_calls("url(a).png) var(--b", 0);
+        assert_eq!(calls.len(), 1, "Unclosed calls should be ignored");
+        assert_eq!((calls[0].name, calls[0].arguments), ("url", "a"));
+        assert_eq!(calls[0].range, 0..6);
+    }
+}
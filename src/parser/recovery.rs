@@ -0,0 +1,498 @@
+use crate::case_folding::eq_ignore_case;
+use crate::dialect::Dialect;
+use crate::media_queries::read_media_query_list;
+use crate::parser::tokenizer::{tokenize_with_dialect, Token, TokenKind};
+use biome_css_syntax::{CssLanguage, CssSyntaxNode};
+use biome_rowan::{Direction, SyntaxTriviaPiece, TriviaPieceKind};
+use std::borrow::Cow;
+use std::ops::Range;
+
+/// At-rules whose block holds rules, unless they are nested in a style rule, like
+/// `@media` in `a { @media print { color: black } }`.
+const CONDITIONAL_AT_RULES: &[&str] = &[
+    "container",
+    "document",
+    "layer",
+    "media",
+    "scope",
+    "starting-style",
+    "supports",
+];
+
+/// A statement of a block that is neither a declaration nor a rule, like the half-typed
+/// `colo` in `a { colo }`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct InvalidStatement {
+    /// The byte range of the statement, without its `;`.
+    pub range: Range<usize>,
+    pub message: String,
+}
+
+/// What the statements of a block are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BlockKind {
+    /// Rules, like the top level of a stylesheet or the block of `@keyframes`.
+    Rules,
+    /// Declarations and nested rules, like the block of a style rule or `@font-face`.
+    Declarations,
+}
+
+/// Blanks out the statements of declaration blocks that are neither declarations nor
+/// rules, up to the next `;` or `}`, as browsers skip them, and the media features of
+/// `@media` and `@import` rules the parser can't read.
+///
+/// The parser would otherwise read them as the selector of a nested rule, swallowing the
+/// declarations and rules after them, which breaks every feature on half-typed files like
+/// `a { colo }\nb { color: red }`. Statements that may be valid in Sass or Less, like
+/// `$size: 1px;` or `.mixin();`, are left to the parser.
+///
+/// # Arguments
+///
+/// * `code` - The CSS code to parse.
+/// * `dialect` - The stylesheet language of the code.
+///
+/// # Returns
+///
+/// * The code with the statements replaced by spaces, line breaks kept, so offsets don't
+///   change, borrowed if there are none, and the `InvalidStatement` skipped.
+pub(crate) fn mask_invalid_statements(
+    code: &str,
+    dialect: Dialect,
+) -> (Cow<'_, str>, Vec<InvalidStatement>) {
+    let tokens: Vec<Token> = tokenize_with_dialect(code, 0, dialect)
+        .into_iter()
+        .filter(|token| !matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+        .collect();
+    let mut blocks = vec![BlockKind::Rules];
+    let mut invalid = Vec::new();
+    let mut masked = Vec::new();
+    let mut idx = 0;
+    while idx < tokens.len() {
+        let first = &tokens[idx];
+        match first.kind {
+            TokenKind::RightBrace => {
+                if blocks.len() > 1 {
+                    blocks.pop();
+                }
+                idx += 1;
+                continue;
+            }
+            TokenKind::Semicolon => {
+                idx += 1;
+                continue;
+            }
+            _ => {}
+        }
+        let in_declarations = blocks.last() == Some(&BlockKind::Declarations);
+        let is_declaration = in_declarations
+            && first.kind == TokenKind::Ident
+            && tokens
+                .get(idx + 1)
+                .is_some_and(|token| token.kind == TokenKind::Colon);
+        // Values of custom properties may contain blocks, like `--x: { a: b }`
+        let end = statement_end(&tokens, idx, is_declaration && first.text.starts_with("--"));
+        if first.kind == TokenKind::AtKeyword
+            && ["@media", "@import"]
+                .iter()
+                .any(|name| eq_ignore_case(name, first.text))
+        {
+            masked.extend(unreadable_media_features(code, &tokens[idx + 1..end]));
+        }
+        if tokens
+            .get(end)
+            .is_some_and(|token| token.kind == TokenKind::LeftBrace)
+        {
+            blocks.push(block_kind(first, in_declarations));
+            idx = end + 1;
+            continue;
+        }
+        let statement = &tokens[idx..end];
+        if in_declarations && !is_declaration && is_skipped(statement, dialect) {
+            let range = statement[0].range.start..statement[statement.len() - 1].range.end;
+            let message = match statement {
+                [name, ..] if name.kind == TokenKind::Ident => {
+                    format!("Expected a `:` after `{}`.", name.text)
+                }
+                _ => format!(
+                    "Expected a declaration or a rule, but found `{}`.",
+                    &code[range.clone()]
+                ),
+            };
+            // The `;` ending the statement is skipped with it
+            let mask_end = match tokens.get(end) {
+                Some(token) if token.kind == TokenKind::Semicolon => token.range.end,
+                _ => range.end,
+            };
+            masked.push(range.start..mask_end);
+            invalid.push(InvalidStatement { range, message });
+        }
+        idx = match tokens.get(end) {
+            Some(token) if token.kind == TokenKind::Semicolon => end + 1,
+            _ => end,
+        };
+    }
+    if masked.is_empty() {
+        return (Cow::Borrowed(code), invalid);
+    }
+    let mut text = code.to_string();
+    for range in masked {
+        let blank: String = code[range.clone()]
+            .chars()
+            .map(|c| match c {
+                '\n' | '\r' => c.to_string(),
+                c => " ".repeat(c.len_utf8()),
+            })
+            .collect();
+        text.replace_range(range, &blank);
+    }
+    (Cow::Owned(text), invalid)
+}
+
+/// Returns the parts of a media query list that the parser can't read, as it gets stuck on
+/// malformed media features like `(400px <= width >= 700px)` and on words like `$tablet`:
+/// the content of these features, and the words between conditions. Malformed features are
+/// reported by `parse_media_query_list`.
+fn unreadable_media_features(code: &str, prelude: &[Token]) -> Vec<Range<usize>> {
+    let mut unreadable: Vec<Range<usize>> = read_media_query_list(code, 0, prelude)
+        .features
+        .into_iter()
+        .filter(|feature| feature.range.len() > 1 && code[..feature.range.end].ends_with(')'))
+        .filter(|feature| {
+            let tokens: Vec<&Token> = prelude
+                .iter()
+                .filter(|token| feature.range.contains(&token.range.start))
+                .collect();
+            feature.malformed || !is_readable(&tokens)
+        })
+        .map(|feature| feature.range.start + 1..feature.range.end - 1)
+        .collect();
+    let mut depth = 0usize;
+    for token in prelude {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::Function { .. } => depth += 1,
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+        let is_readable = matches!(
+            token.kind,
+            TokenKind::Ident
+                | TokenKind::String
+                | TokenKind::Url
+                | TokenKind::Comma
+                | TokenKind::RightParen
+        );
+        if depth == 0 && !is_readable {
+            unreadable.push(token.range.clone());
+        }
+    }
+    unreadable
+}
+
+/// Returns whether the parser reads the tokens of a media feature, the arguments of
+/// functions being read whatever they are.
+fn is_readable(tokens: &[&Token]) -> bool {
+    let mut depth = 0usize;
+    tokens.iter().all(|token| {
+        match token.kind {
+            TokenKind::Function { .. } => depth += 1,
+            TokenKind::LeftParen if depth > 0 => depth += 1,
+            TokenKind::RightParen if depth > 0 => {
+                depth -= 1;
+                return true;
+            }
+            _ => {}
+        }
+        depth > 0
+            || matches!(
+                token.kind,
+                TokenKind::Ident
+                    | TokenKind::Number { .. }
+                    | TokenKind::Percentage { .. }
+                    | TokenKind::Dimension { .. }
+                    | TokenKind::LeftParen
+                    | TokenKind::RightParen
+                    | TokenKind::Colon
+                    | TokenKind::Delim('/' | '<' | '>' | '=')
+            )
+    })
+}
+
+/// Returns the index of the token ending the statement starting at `start`: its `;` or the
+/// `{` of its block outside of parentheses and brackets, the `}` of the enclosing block, or
+/// the number of tokens if it runs to the end of the code.
+///
+/// A `}` ends the statement even in unclosed parentheses, like the parser does, so a
+/// half-typed `rgb(0 0` doesn't take the rules after it.
+fn statement_end(tokens: &[Token], start: usize, nested_blocks: bool) -> usize {
+    let mut depth = 0usize;
+    let mut braces = 0usize;
+    for (idx, token) in tokens.iter().enumerate().skip(start) {
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::Function { .. } => {
+                depth += 1
+            }
+            TokenKind::RightParen | TokenKind::RightBracket => depth = depth.saturating_sub(1),
+            TokenKind::LeftBrace if nested_blocks => braces += 1,
+            TokenKind::RightBrace if braces > 0 => braces -= 1,
+            TokenKind::RightBrace => return idx,
+            TokenKind::LeftBrace | TokenKind::Semicolon if depth == 0 && braces == 0 => {
+                return idx;
+            }
+            _ => {}
+        }
+    }
+    tokens.len()
+}
+
+/// Returns what the statements of a block are, from the first token of its rule.
+fn block_kind(first: &Token, in_declarations: bool) -> BlockKind {
+    if first.kind != TokenKind::AtKeyword {
+        return BlockKind::Declarations;
+    }
+    let name = first.text.trim_start_matches('@');
+    // Vendor-prefixed at-rules, like `@-webkit-keyframes`
+    let name = match name.strip_prefix('-') {
+        Some(prefixed) => prefixed.split_once('-').map_or(name, |(_, name)| name),
+        None => name,
+    };
+    let holds_rules = eq_ignore_case(name, "keyframes")
+        || (!in_declarations
+            && CONDITIONAL_AT_RULES
+                .iter()
+                .any(|at_rule| eq_ignore_case(at_rule, name)));
+    if holds_rules {
+        BlockKind::Rules
+    } else {
+        BlockKind::Declarations
+    }
+}
+
+/// Returns whether an invalid statement is skipped: it starts like a selector or a value,
+/// which the parser doesn't recover from, and can't be valid in Sass or Less, like `.mixin();`,
+/// `$size: 1px;`, `// comment` or `&:extend(.a);`. Function calls are left to the parser in
+/// Less, where they may be statements, like `each(@list, { ... });`.
+fn is_skipped(statement: &[Token], dialect: Dialect) -> bool {
+    let starts_like_selector = match statement[0].kind {
+        TokenKind::Function { .. } => dialect != Dialect::Less,
+        TokenKind::Ident
+        | TokenKind::Colon
+        | TokenKind::LeftBracket
+        | TokenKind::Number { .. }
+        | TokenKind::Percentage { .. }
+        | TokenKind::Dimension { .. } => true,
+        TokenKind::Delim(c) => matches!(c, '&' | '*' | '>' | '+' | '~' | '|'),
+        _ => false,
+    };
+    starts_like_selector
+        && !statement.iter().any(|token| {
+            matches!(token.kind, TokenKind::Function { name } if eq_ignore_case(name, "extend"))
+        })
+}
+
+/// Restores the text blanked out before parsing as skipped trivia, like the parser does
+/// when it skips tokens, so the tree still covers the code as written.
+///
+/// # Arguments
+///
+/// * `root` - The root of the tree parsed from the blanked out code.
+/// * `code` - The code as written.
+///
+/// # Returns
+///
+/// * The root of the tree with the text of `code`.
+pub(crate) fn restore_masked_text(root: CssSyntaxNode, code: &str) -> CssSyntaxNode {
+    // Blanks are only ever parsed as trivia, so the tokens themselves are unchanged
+    let ranges: Vec<_> = root
+        .descendants_tokens(Direction::Next)
+        .filter(|token| {
+            let range = token.text_range();
+            token.text() != &code[usize::from(range.start())..usize::from(range.end())]
+        })
+        .map(|token| token.text_range())
+        .collect();
+    let mut root = root;
+    for range in ranges {
+        let Some(token) = root.covering_element(range).into_token() else {
+            continue;
+        };
+        let leading = restore_trivia(token.leading_trivia().pieces(), code);
+        let trailing = restore_trivia(token.trailing_trivia().pieces(), code);
+        let restored = token
+            .with_leading_trivia(leading.iter().copied())
+            .with_trailing_trivia(trailing.iter().copied());
+        if let Some(replaced) = root.clone().replace_child(token.into(), restored.into()) {
+            root = replaced;
+        }
+    }
+    root
+}
+
+/// Returns the pieces of a trivia with the text of `code`, the parts that were blanked out
+/// becoming skipped pieces.
+fn restore_trivia(
+    pieces: impl Iterator<Item = SyntaxTriviaPiece<CssLanguage>>,
+    code: &str,
+) -> Vec<(TriviaPieceKind, &str)> {
+    let mut restored = Vec::new();
+    for piece in pieces {
+        let start = usize::from(piece.text_range().start());
+        let original = &code[start..usize::from(piece.text_range().end())];
+        let masked = piece.text().as_bytes();
+        // Blanks are ASCII, so runs of equal and differing bytes split on char boundaries
+        let mut run_start = 0;
+        for idx in 1..=original.len() {
+            let same = |idx: usize| masked[idx] == original.as_bytes()[idx];
+            if idx == original.len() || same(idx) != same(run_start) {
+                let kind = if same(run_start) {
+                    piece.kind()
+                } else {
+                    TriviaPieceKind::Skipped
+                };
+                restored.push((kind, &original[run_start..idx]));
+                run_start = idx;
+            }
+        }
+    }
+    restored
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::tokenizer::tokenize;
+
+    fn messages(code: &str) -> Vec<(&str, String)> {
+        mask_invalid_statements(code, Dialect::Css)
+            .1
+            .into_iter()
+            .map(|statement| (&code[statement.range], statement.message))
+            .collect()
+    }
+
+    #[test]
+    fn test_mask_invalid_statements() {
+        let code = "a { marg 0; color: red; : red }";
+        let (masked, invalid) = mask_invalid_statements(code, Dialect::Css);
+        assert_eq!(masked, "a {         color: red;       }");
+        assert_eq!(invalid.len(), 2);
+        assert_eq!(
+            messages(code),
+            vec![
+                ("marg 0", "Expected a `:` after `marg`.".to_string()),
+                (
+                    ": red",
+                    "Expected a declaration or a rule, but found `: red`.".to_string()
+                ),
+            ]
+        );
+        assert_eq!(
+            mask_invalid_statements("a { colo\n  top: 0 }", Dialect::Css).0,
+            "a {     \n         }"
+        );
+    }
+
+    #[test]
+    fn test_mask_invalid_statements_keeps_rules() {
+        for code in [
+            "a { color: red; &:hover { color: blue } }",
+            "a { b { top: 0 } --x: { a: b }; }",
+            "@media print { a { top: 0 } b { top: 0 } }",
+            "@keyframes spin { from { top: 0 } to { top: 1px } }",
+            "a { @media print { top: 0 } }",
+            "a { .mixin(); $size: 1px; &:extend(.b); }",
+        ] {
+            let (masked, invalid) = mask_invalid_statements(code, Dialect::Css);
+            assert!(matches!(masked, Cow::Borrowed(_)), "{code}");
+            assert!(invalid.is_empty(), "{code}");
+        }
+    }
+
+    #[test]
+    fn test_mask_invalid_statements_less_calls() {
+        let code = "a { each(@list, { b: c }); foo(1); }";
+        let (masked, _) = mask_invalid_statements(code, Dialect::Less);
+        assert!(matches!(masked, Cow::Borrowed(_)));
+        assert_eq!(
+            mask_invalid_statements(code, Dialect::Css).0,
+            "a {                    }); foo(1); }"
+        );
+    }
+
+    #[test]
+    fn test_statement_end() {
+        let tokens: Vec<Token> = tokenize("color: rgb(0 0 } b { top: 0 }", 0)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+        // The `}` of the block ends a statement in unclosed parentheses
+        assert_eq!(tokens[statement_end(&tokens, 0, false)].text, "}");
+        assert_eq!(statement_end(&tokens, 0, false), 5);
+        let tokens: Vec<Token> = tokenize("--x: { a: b }; top: 0", 0)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .collect();
+        // The blocks of custom properties are part of their value
+        assert_eq!(tokens[statement_end(&tokens, 0, true)].text, ";");
+        assert_eq!(statement_end(&tokens, 0, false), 2);
+        assert_eq!(
+            messages("a { color: rgb(0 0 }\nb { top: 0 }"),
+            Vec::<(&str, String)>::new()
+        );
+    }
+
+    #[test]
+    fn test_mask_unreadable_media_features() {
+        let code = "/* @media (a b) */ @media (width: 4px 5px), (hover) { a {} }\n@import 'b.css' (400px <= 700px);\na { @media (width: $tablet) and (width: calc(1px * 2)), $query {} }";
+        let (masked, invalid) = mask_invalid_statements(code, Dialect::Css);
+        assert_eq!(
+            masked,
+            "/* @media (a b) */ @media (              ), (hover) { a {} }\n@import 'b.css' (              );\na { @media (              ) and (width: calc(1px * 2)),  query {} }"
+        );
+        assert!(invalid.is_empty());
+        assert!(matches!(
+            mask_invalid_statements("@media (width: 4px) {}", Dialect::Css).0,
+            Cow::Borrowed(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_unreadable_media_features() {
+        // Media features the parser panics on when they reach it unmasked
+        let panicking = [
+            "@media (width: $tablet) {}",
+            "@media (width: @a) {}",
+            "@media (width: 1px) and $x {}",
+            "@media $query {}",
+            "@media (400px <= width >= 700px) {}",
+            "@media (width: 1px 2px) {}",
+            "@media (width: a!b) {}",
+            "@media (width: %) {}",
+            "@media (width < $a) {}",
+            "@media (width: 1px) and (height: &) {}",
+            "@import url(a.css) (width: $x);",
+        ];
+        for code in panicking {
+            assert!(
+                std::panic::catch_unwind(|| crate::parser::parse_css(code)).is_ok(),
+                "Parsing panicked for {:?}",
+                code
+            );
+        }
+    }
+
+    #[test]
+    fn test_restore_masked_text() {
+        let code = "a {\n  colo ☃;\n  top: 0 }";
+        let (masked, _) = mask_invalid_statements(code, Dialect::Css);
+        let parse = biome_css_parser::parse_css(&masked, Default::default());
+        let root = restore_masked_text(parse.syntax(), code);
+        assert_eq!(root.to_string(), code);
+        let skipped: Vec<_> = root
+            .descendants_tokens(Direction::Next)
+            .flat_map(|token| token.leading_trivia().pieces().collect::<Vec<_>>())
+            .filter(|piece| piece.is_skipped())
+            .map(|piece| piece.text().to_string())
+            .collect();
+        assert_eq!(skipped, vec!["colo", "☃;"]);
+    }
+}
@@ -0,0 +1,650 @@
+use crate::case_folding::eq_ignore_case;
+use crate::dialect::Dialect;
+use crate::identifiers::skip_escape;
+use std::ops::Range;
+
+/// The kind of a token, as defined by CSS Syntax Level 3.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TokenKind<'a> {
+    Ident,
+    /// A name followed by `(`, like `rgb(`, the parenthesis included.
+    Function {
+        name: &'a str,
+    },
+    /// An `@` followed by a name, like `@media`.
+    AtKeyword,
+    /// A `#` followed by a name, like `#fff`, which is an `id` if the name is an identifier,
+    /// like `#main`.
+    Hash {
+        id: bool,
+    },
+    /// A quoted string, possibly unterminated at the end of the source.
+    String,
+    /// A string interrupted by a line break, which isn't part of it.
+    BadString,
+    /// An unquoted `url()`, like `url(a.png)`.
+    Url,
+    /// An unquoted `url()` with invalid characters, like `url(a b.png)`.
+    BadUrl,
+    Number {
+        integer: bool,
+        value: f64,
+    },
+    Percentage {
+        value: f64,
+    },
+    Dimension {
+        integer: bool,
+        value: f64,
+        unit: &'a str,
+    },
+    Whitespace,
+    Comment,
+    /// `<!--`.
+    Cdo,
+    /// `-->`.
+    Cdc,
+    Colon,
+    Semicolon,
+    Comma,
+    LeftBracket,
+    RightBracket,
+    LeftParen,
+    RightParen,
+    LeftBrace,
+    RightBrace,
+    /// Any other character, like `.`, `>` or `!`.
+    Delim(char),
+}
+
+/// A token of a stylesheet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Token<'a> {
+    pub kind: TokenKind<'a>,
+    /// The token, as written.
+    pub text: &'a str,
+    /// The byte range of the token in the source.
+    pub range: Range<usize>,
+}
+
+impl Token<'_> {
+    /// Returns whether the token is a string or a block comment running to the end of the
+    /// code without its closing quote or `*/`, like `"a` or `/* a`.
+    pub fn is_unterminated(&self) -> bool {
+        match self.kind {
+            TokenKind::Comment if self.text.starts_with("//") => false,
+            TokenKind::Comment => self.text.len() < 4 || !self.text.ends_with("*/"),
+            TokenKind::String => {
+                let quote = &self.text[..1];
+                let Some(content) = self.text[1..].strip_suffix(quote) else {
+                    return true;
+                };
+                // A quote after an odd number of backslashes is escaped
+                let backslashes = content.len() - content.trim_end_matches('\\').len();
+                backslashes % 2 == 1
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Splits CSS code into tokens, following the tokenization algorithm of CSS Syntax Level 3.
+///
+/// Unlike the specification, comments are kept as tokens, so the tokens cover the whole
+/// code, and the code isn't preprocessed: `\r\n` is a single line break, and ranges are
+/// byte offsets in the code as written.
+///
+/// # Arguments
+///
+/// * `code` - The CSS code to tokenize.
+/// * `start` - The byte offset of the code in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * A vector of `Token`, in order.
+pub fn tokenize(code: &str, start: usize) -> Vec<Token<'_>> {
+    Tokenizer::new(code, start).collect()
+}
+
+/// Splits the code of a stylesheet language into tokens, see `tokenize`. In SCSS and Less,
+/// `//` starts a comment running to the end of the line, its line break excluded.
+///
+/// # Arguments
+///
+/// * `code` - The code to tokenize.
+/// * `start` - The byte offset of the code in the source, used to compute ranges.
+/// * `dialect` - The stylesheet language of the code.
+///
+/// # Returns
+///
+/// * A vector of `Token`, in order.
+pub fn tokenize_with_dialect(code: &str, start: usize, dialect: Dialect) -> Vec<Token<'_>> {
+    Tokenizer::with_dialect(code, start, dialect).collect()
+}
+
+/// An iterator over the tokens of CSS code, for callers stopping early, like at the end of
+/// the prelude of an at-rule.
+pub struct Tokenizer<'a> {
+    code: &'a str,
+    start: usize,
+    offset: usize,
+    /// Whether `//` starts a comment, as in SCSS and Less.
+    line_comments: bool,
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Creates a tokenizer for CSS code starting at a byte offset of the source.
+    pub fn new(code: &'a str, start: usize) -> Tokenizer<'a> {
+        Tokenizer::with_dialect(code, start, Dialect::Css)
+    }
+
+    /// Creates a tokenizer for the code of a stylesheet language starting at a byte offset
+    /// of the source, see `tokenize_with_dialect`.
+    pub fn with_dialect(code: &'a str, start: usize, dialect: Dialect) -> Tokenizer<'a> {
+        Tokenizer {
+            code,
+            start,
+            offset: 0,
+            line_comments: dialect.has_line_comments(),
+        }
+    }
+
+    /// Returns the byte offset of the next token in the source.
+    pub fn offset(&self) -> usize {
+        self.start + self.offset
+    }
+
+    fn peek(&self, ahead: usize) -> Option<char> {
+        self.code[self.offset..].chars().nth(ahead)
+    }
+
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek(0)?;
+        self.offset += c.len_utf8();
+        Some(c)
+    }
+
+    fn token(&mut self) -> Option<TokenKind<'a>> {
+        let start = self.offset;
+        let c = self.bump()?;
+        let kind = match c {
+            '/' if self.peek(0) == Some('*') => {
+                self.offset = self.code[start + 2..]
+                    .find("*/")
+                    .map_or(self.code.len(), |end| start + 2 + end + 2);
+                TokenKind::Comment
+            }
+            '/' if self.line_comments && self.peek(0) == Some('/') => {
+                while self.peek(0).is_some_and(|c| !is_newline(c)) {
+                    self.bump();
+                }
+                TokenKind::Comment
+            }
+            c if is_whitespace(c) => {
+                while self.peek(0).is_some_and(is_whitespace) {
+                    self.bump();
+                }
+                TokenKind::Whitespace
+            }
+            '"' | '\'' => self.string(c),
+            '#' if self.peek(0).is_some_and(is_name_char) || self.starts_escape(0) => {
+                let id = self.starts_identifier(0);
+                self.name();
+                TokenKind::Hash { id }
+            }
+            '(' => TokenKind::LeftParen,
+            ')' => TokenKind::RightParen,
+            ',' => TokenKind::Comma,
+            ':' => TokenKind::Colon,
+            ';' => TokenKind::Semicolon,
+            '[' => TokenKind::LeftBracket,
+            ']' => TokenKind::RightBracket,
+            '{' => TokenKind::LeftBrace,
+            '}' => TokenKind::RightBrace,
+            '+' | '-' | '.' if self.starts_number(start) => self.numeric(start),
+            '-' if self.code[self.offset..].starts_with("->") => {
+                self.offset += 2;
+                TokenKind::Cdc
+            }
+            '<' if self.code[self.offset..].starts_with("!--") => {
+                self.offset += 3;
+                TokenKind::Cdo
+            }
+            '@' if self.starts_identifier(0) => {
+                self.name();
+                TokenKind::AtKeyword
+            }
+            '0'..='9' => self.numeric(start),
+            '-' | '\\' if self.starts_identifier_at(start) => self.ident_like(start),
+            c if is_name_start(c) => self.ident_like(start),
+            c => TokenKind::Delim(c),
+        };
+        Some(kind)
+    }
+
+    /// Whether the characters from a byte offset start an escape: a `\` not followed by a
+    /// line break.
+    fn escape_at(&self, offset: usize) -> bool {
+        let mut chars = self.code[offset..].chars();
+        chars.next() == Some('\\') && chars.next().is_some_and(|c| !is_newline(c))
+    }
+
+    fn starts_escape(&self, ahead: usize) -> bool {
+        self.peek(ahead) == Some('\\') && self.peek(ahead + 1).is_some_and(|c| !is_newline(c))
+    }
+
+    /// Whether the characters from a byte offset start an identifier, like `-a`, `--` or
+    /// `\31`.
+    fn starts_identifier_at(&self, offset: usize) -> bool {
+        let mut chars = self.code[offset..].chars();
+        match chars.next() {
+            Some('-') => match chars.next() {
+                Some(c) if is_name_start(c) || c == '-' => true,
+                Some('\\') => self.escape_at(offset + 1),
+                _ => false,
+            },
+            Some('\\') => self.escape_at(offset),
+            Some(c) => is_name_start(c),
+            None => false,
+        }
+    }
+
+    fn starts_identifier(&self, ahead: usize) -> bool {
+        let offset = self.code[self.offset..]
+            .char_indices()
+            .nth(ahead)
+            .map_or(self.code.len(), |(idx, _)| self.offset + idx);
+        self.starts_identifier_at(offset)
+    }
+
+    /// Whether the characters from a byte offset start a number, like `1`, `-.5` or `+2`.
+    fn starts_number(&self, offset: usize) -> bool {
+        let mut chars = self.code[offset..].chars();
+        let digit = |c: Option<char>| c.is_some_and(|c| c.is_ascii_digit());
+        match chars.next() {
+            Some('+' | '-') => match chars.next() {
+                Some('.') => digit(chars.next()),
+                c => digit(c),
+            },
+            Some('.') => digit(chars.next()),
+            c => digit(c),
+        }
+    }
+
+    /// Consumes the code points of a name, escapes included.
+    fn name(&mut self) {
+        loop {
+            if self.starts_escape(0) {
+                self.bump();
+                let mut chars = self.code[self.offset..].char_indices();
+                skip_escape(&mut chars);
+                self.offset = chars
+                    .next()
+                    .map_or(self.code.len(), |(idx, _)| self.offset + idx);
+            } else if self.peek(0).is_some_and(is_name_char) {
+                self.bump();
+            } else {
+                break;
+            }
+        }
+    }
+
+    fn string(&mut self, quote: char) -> TokenKind<'a> {
+        while let Some(c) = self.peek(0) {
+            match c {
+                c if c == quote => {
+                    self.bump();
+                    return TokenKind::String;
+                }
+                // The line break isn't part of the string
+                c if is_newline(c) => return TokenKind::BadString,
+                '\\' => {
+                    self.bump();
+                    // An escaped line break continues the string
+                    if self.code[self.offset..].starts_with("\r\n") {
+                        self.offset += 2;
+                    } else {
+                        self.bump();
+                    }
+                }
+                _ => {
+                    self.bump();
+                }
+            }
+        }
+        TokenKind::String
+    }
+
+    fn numeric(&mut self, start: usize) -> TokenKind<'a> {
+        self.offset = start;
+        if matches!(self.peek(0), Some('+' | '-')) {
+            self.bump();
+        }
+        let mut integer = true;
+        while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+            self.bump();
+        }
+        if self.peek(0) == Some('.') && self.peek(1).is_some_and(|c| c.is_ascii_digit()) {
+            integer = false;
+            self.bump();
+            while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+        let exponent_digit = match self.peek(1) {
+            Some('+' | '-') => 2,
+            _ => 1,
+        };
+        if matches!(self.peek(0), Some('e' | 'E'))
+            && self
+                .peek(exponent_digit)
+                .is_some_and(|c| c.is_ascii_digit())
+        {
+            integer = false;
+            for _ in 0..=exponent_digit {
+                self.bump();
+            }
+            while self.peek(0).is_some_and(|c| c.is_ascii_digit()) {
+                self.bump();
+            }
+        }
+        let value = self.code[start..self.offset].parse().unwrap_or_default();
+        if self.starts_identifier(0) {
+            let unit_start = self.offset;
+            self.name();
+            TokenKind::Dimension {
+                integer,
+                value,
+                unit: &self.code[unit_start..self.offset],
+            }
+        } else if self.peek(0) == Some('%') {
+            self.bump();
+            TokenKind::Percentage { value }
+        } else {
+            TokenKind::Number { integer, value }
+        }
+    }
+
+    /// Consumes an identifier, a function or an unquoted `url()`.
+    fn ident_like(&mut self, start: usize) -> TokenKind<'a> {
+        self.offset = start;
+        self.name();
+        let name = &self.code[start..self.offset];
+        if self.peek(0) != Some('(') {
+            return TokenKind::Ident;
+        }
+        self.bump();
+        let quoted = self.code[self.offset..]
+            .trim_start_matches(is_whitespace)
+            .starts_with(['"', '\'']);
+        if eq_ignore_case(name, "url") && !quoted {
+            self.url()
+        } else {
+            TokenKind::Function { name }
+        }
+    }
+
+    /// Consumes the content of an unquoted `url()`, after its `(`.
+    fn url(&mut self) -> TokenKind<'a> {
+        while self.peek(0).is_some_and(is_whitespace) {
+            self.bump();
+        }
+        while let Some(c) = self.bump() {
+            match c {
+                ')' => return TokenKind::Url,
+                c if is_whitespace(c) => {
+                    while self.peek(0).is_some_and(is_whitespace) {
+                        self.bump();
+                    }
+                    match self.peek(0) {
+                        None => return TokenKind::Url,
+                        Some(')') => {
+                            self.bump();
+                            return TokenKind::Url;
+                        }
+                        Some(_) => return self.bad_url(),
+                    }
+                }
+                '"' | '\'' | '(' => return self.bad_url(),
+                c if is_non_printable(c) => return self.bad_url(),
+                '\\' if self.peek(0).is_some_and(|c| !is_newline(c)) => {
+                    let mut chars = self.code[self.offset..].char_indices();
+                    skip_escape(&mut chars);
+                    self.offset = chars
+                        .next()
+                        .map_or(self.code.len(), |(idx, _)| self.offset + idx);
+                }
+                '\\' => return self.bad_url(),
+                _ => {}
+            }
+        }
+        TokenKind::Url
+    }
+
+    /// Consumes the rest of an invalid `url()`, up to its `)`, skipping escaped ones.
+    fn bad_url(&mut self) -> TokenKind<'a> {
+        while let Some(c) = self.bump() {
+            match c {
+                ')' => break,
+                '\\' if self.peek(0).is_some_and(|c| !is_newline(c)) => {
+                    self.bump();
+                }
+                _ => {}
+            }
+        }
+        TokenKind::BadUrl
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let start = self.offset;
+        let kind = self.token()?;
+        Some(Token {
+            kind,
+            text: &self.code[start..self.offset],
+            range: self.start + start..self.start + self.offset,
+        })
+    }
+}
+
+fn is_newline(c: char) -> bool {
+    matches!(c, '\n' | '\r' | '\x0C')
+}
+
+fn is_whitespace(c: char) -> bool {
+    is_newline(c) || c == ' ' || c == '\t'
+}
+
+fn is_non_printable(c: char) -> bool {
+    matches!(c, '\0'..='\x08' | '\x0B' | '\x0E'..='\x1F' | '\x7F')
+}
+
+fn is_name_start(c: char) -> bool {
+    c.is_ascii_alphabetic() || c == '_' || !c.is_ascii()
+}
+
+fn is_name_char(c: char) -> bool {
+    is_name_start(c) || c.is_ascii_digit() || c == '-'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(code: &str) -> Vec<(TokenKind<'_>, &str)> {
+        tokenize(code, 0)
+            .into_iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(|token| (token.kind, token.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        let code =
+            "@media (min-width: 600px) {\n  #main.a > b::before { color: #fff !important; }\n}";
+        let tokens = tokenize(code, 10);
+        assert_eq!(
+            tokens.iter().map(|token| token.text).collect::<String>(),
+            code,
+            "Tokens should cover the whole code"
+        );
+        assert_eq!(tokens[0].kind, TokenKind::AtKeyword);
+        assert_eq!(tokens[0].range, 10..16);
+        assert_eq!(
+            kinds("#main #fff #-1 #1a"),
+            vec![
+                (TokenKind::Hash { id: true }, "#main"),
+                (TokenKind::Hash { id: true }, "#fff"),
+                (TokenKind::Hash { id: false }, "#-1"),
+                (TokenKind::Hash { id: false }, "#1a"),
+            ]
+        );
+        assert_eq!(
+            kinds("a\\:b -x --y -\\31  - -->  <!-- @ # \\\n"),
+            vec![
+                (TokenKind::Ident, "a\\:b"),
+                (TokenKind::Ident, "-x"),
+                (TokenKind::Ident, "--y"),
+                (TokenKind::Ident, "-\\31 "),
+                (TokenKind::Delim('-'), "-"),
+                (TokenKind::Cdc, "-->"),
+                (TokenKind::Cdo, "<!--"),
+                (TokenKind::Delim('@'), "@"),
+                (TokenKind::Delim('#'), "#"),
+                (TokenKind::Delim('\\'), "\\"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_numbers() {
+        assert_eq!(
+            kinds("12 -.5 +3e2 1e 50% 1.5em 2\\70 x 1-a"),
+            vec![
+                (
+                    TokenKind::Number {
+                        integer: true,
+                        value: 12.0
+                    },
+                    "12"
+                ),
+                (
+                    TokenKind::Number {
+                        integer: false,
+                        value: -0.5
+                    },
+                    "-.5"
+                ),
+                (
+                    TokenKind::Number {
+                        integer: false,
+                        value: 300.0
+                    },
+                    "+3e2"
+                ),
+                (
+                    TokenKind::Dimension {
+                        integer: true,
+                        value: 1.0,
+                        unit: "e"
+                    },
+                    "1e"
+                ),
+                (TokenKind::Percentage { value: 50.0 }, "50%"),
+                (
+                    TokenKind::Dimension {
+                        integer: false,
+                        value: 1.5,
+                        unit: "em"
+                    },
+                    "1.5em"
+                ),
+                (
+                    TokenKind::Dimension {
+                        integer: true,
+                        value: 2.0,
+                        unit: "\\70 x"
+                    },
+                    "2\\70 x"
+                ),
+                (
+                    TokenKind::Dimension {
+                        integer: true,
+                        value: 1.0,
+                        unit: "-a"
+                    },
+                    "1-a"
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_strings_and_urls() {
+        assert_eq!(
+            kinds("'a\\'b' \"c\\\nd\" \"e\nf"),
+            vec![
+                (TokenKind::String, "'a\\'b'"),
+                (TokenKind::String, "\"c\\\nd\""),
+                (TokenKind::BadString, "\"e"),
+                (TokenKind::Ident, "f"),
+            ]
+        );
+        assert_eq!(
+            kinds("url( a.png ) URL(\"b.png\") url(c d) url(e\\)f) url(g"),
+            vec![
+                (TokenKind::Url, "url( a.png )"),
+                (TokenKind::Function { name: "URL" }, "URL("),
+                (TokenKind::String, "\"b.png\""),
+                (TokenKind::RightParen, ")"),
+                (TokenKind::BadUrl, "url(c d)"),
+                (TokenKind::Url, "url(e\\)f)"),
+                (TokenKind::Url, "url(g"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tokenize_comments() {
+        assert_eq!(
+            kinds("a/* b */c /* d"),
+            vec![
+                (TokenKind::Ident, "a"),
+                (TokenKind::Comment, "/* b */"),
+                (TokenKind::Ident, "c"),
+                (TokenKind::Comment, "/* d"),
+            ]
+        );
+        let unterminated: Vec<bool> = tokenize("/* a */ 'b' 'c\\\\' \"d\\\" /*/", 0)
+            .iter()
+            .filter(|token| token.kind != TokenKind::Whitespace)
+            .map(Token::is_unterminated)
+            .collect();
+        assert_eq!(unterminated, vec![false, false, false, true]);
+        assert!(tokenize("/*/", 0)[0].is_unterminated());
+        let line_comments: Vec<(TokenKind, &str)> =
+            tokenize_with_dialect("a // don't\n/* b */", 0, Dialect::Scss)
+                .into_iter()
+                .filter(|token| token.kind != TokenKind::Whitespace)
+                .map(|token| (token.kind, token.text))
+                .collect();
+        assert_eq!(
+            line_comments,
+            vec![
+                (TokenKind::Ident, "a"),
+                (TokenKind::Comment, "// don't"),
+                (TokenKind::Comment, "/* b */"),
+            ]
+        );
+        assert!(!tokenize_with_dialect("// a", 0, Dialect::Less)[0].is_unterminated());
+        assert_eq!(kinds("a // b")[1], (TokenKind::Delim('/'), "/"));
+        assert_eq!(
+            kinds("rgb(0 0 0 / 50%)")[0],
+            (TokenKind::Function { name: "rgb" }, "rgb(")
+        );
+    }
+}
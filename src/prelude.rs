@@ -0,0 +1,80 @@
+//! The stable API of csslsrs, for crates embedding the language service.
+//!
+//! Everything exported here follows semantic versioning: configuration structs are
+//! `#[non_exhaustive]`, so new settings can be added in minor releases. Build them from
+//! their `Default` and set the fields you need:
+//!
+//! ```
+//! use csslsrs::prelude::*;
+//!
+//! let mut options = HoverOptions::default();
+//! options.computed_style = true;
+//!
+//! let mut service = LanguageService::new();
+//! service.set_hover_options(options);
+//! let document = TextDocument::new("file:///a.css", "css", 1, "a { color: red; }");
+//! assert!(service.diagnostics(&document).items.is_empty());
+//! ```
+//!
+//! Modules outside of the prelude are public for the WASM bindings and for experiments,
+//! and may change in any release.
+
+pub use crate::budget::{Budget, Budgeted, Budgets};
+pub use crate::data::properties::{
+    CssData, CssDataBuilder, PropertyBuilder, PropertyData, PropertyStatus, ValueData,
+};
+pub use crate::dialect::Dialect;
+pub use crate::document_store::DocumentStore;
+pub use crate::file_system::{FileSystemProvider, StdFileSystem};
+pub use crate::line_index::{LineIndex, PositionEncoding};
+pub use crate::registration::RegistrationSettings;
+pub use crate::service::{DocumentAnalysis, LanguageService};
+pub use crate::statistics::{Feature, Statistics};
+pub use crate::text_document::TextDocument;
+pub use crate::text_range::{FromLsp, SourceText, TextRange, TextSize, ToLsp};
+pub use crate::workspace::{FileHandling, FileSettings, Workspace};
+
+// Features
+pub use crate::features::abbreviations::AbbreviationProvider;
+pub use crate::features::code_actions::{
+    document_edit, get_code_actions, get_fix_all_action, quick_fix_action, CodeActionContext,
+    CodeActionProvider, CodeActionRegistry,
+};
+pub use crate::features::colors::{get_color_presentations, get_document_colors};
+pub use crate::features::completion::{
+    get_completions, get_completions_with_options, CompletionOptions, CompletionProvider,
+};
+pub use crate::features::custom_properties::get_document_highlights;
+pub use crate::features::diagnostics::{get_diagnostics, get_diagnostics_batch};
+pub use crate::features::folding::{
+    get_folding_ranges, get_folding_ranges_with_options, FoldingOptions,
+};
+pub use crate::features::formatting::{get_formatting_edits, get_range_formatting_edits};
+pub use crate::features::hover::{get_hover, HoverOptions};
+pub use crate::features::inlay_hints::get_inlay_hints;
+pub use crate::features::on_type_formatting::{
+    get_on_type_formatting_edits, OnTypeFormattingOptions,
+};
+pub use crate::features::rule_templates::{get_rule_templates, RuleTemplate, SnippetSettings};
+pub use crate::features::selection_range::get_selection_ranges;
+pub use crate::features::semantic_tokens::{
+    get_semantic_tokens, get_semantic_tokens_edits, get_semantic_tokens_in_range,
+    get_semantic_tokens_legend,
+};
+pub use crate::features::symbols::{
+    get_document_symbols, get_document_symbols_with_options, DocumentSymbolOptions,
+};
+pub use crate::formatter::{
+    format_css, format_css_with_options, normalize_value, pretty_print_css,
+    pretty_print_statements, FormatOptions, ValueNormalization,
+};
+
+// Rules
+pub use crate::rules::catalog::{get_rule_catalog, RuleCategory, RuleMetadata};
+pub use crate::rules::profiles::{ExitClass, RuleLevel, SeverityProfile, SeverityProfiles};
+pub use crate::rules::{LintRule, QuickFix, RuleContext, RuleRegistry};
+
+// Syntax tree
+pub use crate::parser::{find_declarations, parse_css, parse_css_with_dialect, Declaration};
+pub use biome_css_parser::CssParse;
+pub use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode, CssSyntaxToken};
@@ -0,0 +1,289 @@
+use crate::features::on_type_formatting::ON_TYPE_TRIGGER_CHARACTERS;
+use lsp_types::{
+    DidChangeWatchedFilesRegistrationOptions, DocumentFilter,
+    DocumentOnTypeFormattingRegistrationOptions, FileSystemWatcher, GlobPattern, Registration,
+    RegistrationParams, TextDocumentRegistrationOptions, Unregistration, UnregistrationParams,
+    WatchKind,
+};
+use wasm_bindgen::prelude::*;
+
+/// The stylesheets watched for changes on disk.
+pub const STYLESHEETS_GLOB: &str = "**/*.{css,scss,less}";
+
+/// The languages the document-scoped features are registered for.
+pub const LANGUAGES: &[&str] = &["css", "scss", "less"];
+
+const FILE_WATCHERS_ID: &str = "csslsrs.fileWatchers";
+const FORMATTING_ID: &str = "csslsrs.formatting";
+const RANGE_FORMATTING_ID: &str = "csslsrs.rangeFormatting";
+const ON_TYPE_FORMATTING_ID: &str = "csslsrs.onTypeFormatting";
+const TEXT_DOCUMENT_CONTENT_ID: &str = "csslsrs.textDocumentContent";
+
+/// The settings deciding which features are registered dynamically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RegistrationSettings {
+    /// Whether to watch the stylesheets of the workspace for changes on disk.
+    pub watch_files: bool,
+    /// Whether document, range and on-type formatting are enabled.
+    pub format: bool,
+}
+
+impl Default for RegistrationSettings {
+    fn default() -> Self {
+        RegistrationSettings {
+            watch_files: true,
+            format: true,
+        }
+    }
+}
+
+/// Creates the registration of file watchers for the stylesheets of the workspace.
+pub fn file_watchers_registration() -> Registration {
+    let options = DidChangeWatchedFilesRegistrationOptions {
+        watchers: vec![FileSystemWatcher {
+            glob_pattern: GlobPattern::String(STYLESHEETS_GLOB.to_string()),
+            kind: Some(WatchKind::all()),
+        }],
+    };
+    Registration {
+        id: FILE_WATCHERS_ID.to_string(),
+        method: "workspace/didChangeWatchedFiles".to_string(),
+        register_options: serde_json::to_value(options).ok(),
+    }
+}
+
+/// Creates the registrations of document, range and on-type formatting for the stylesheet
+/// languages.
+pub fn formatting_registrations() -> Vec<Registration> {
+    let mut registrations: Vec<Registration> = [
+        (FORMATTING_ID, "textDocument/formatting"),
+        (RANGE_FORMATTING_ID, "textDocument/rangeFormatting"),
+    ]
+    .iter()
+    .map(|(id, method)| Registration {
+        id: id.to_string(),
+        method: method.to_string(),
+        register_options: serde_json::to_value(document_registration_options()).ok(),
+    })
+    .collect();
+    let on_type_options = DocumentOnTypeFormattingRegistrationOptions {
+        document_selector: document_registration_options().document_selector,
+        first_trigger_character: ON_TYPE_TRIGGER_CHARACTERS[0].to_string(),
+        more_trigger_character: Some(
+            ON_TYPE_TRIGGER_CHARACTERS[1..]
+                .iter()
+                .map(|ch| ch.to_string())
+                .collect(),
+        ),
+    };
+    registrations.push(Registration {
+        id: ON_TYPE_FORMATTING_ID.to_string(),
+        method: "textDocument/onTypeFormatting".to_string(),
+        register_options: serde_json::to_value(on_type_options).ok(),
+    });
+    registrations
+}
+
+/// Creates the registration of `workspace/textDocumentContent` for the schemes of virtual
+/// documents, so the client asks the server for their contents.
+///
+/// # Arguments
+///
+/// * `schemes` - The schemes served by content providers, like `tailwind-generated`.
+///
+/// # Returns
+///
+/// * The `Registration`, or `None` if no scheme is served.
+pub fn text_document_content_registration(schemes: &[String]) -> Option<Registration> {
+    if schemes.is_empty() {
+        return None;
+    }
+    Some(Registration {
+        id: TEXT_DOCUMENT_CONTENT_ID.to_string(),
+        method: "workspace/textDocumentContent".to_string(),
+        register_options: Some(serde_json::json!({ "schemes": schemes })),
+    })
+}
+
+fn document_registration_options() -> TextDocumentRegistrationOptions {
+    TextDocumentRegistrationOptions {
+        document_selector: Some(
+            LANGUAGES
+                .iter()
+                .map(|language| DocumentFilter {
+                    language: Some(language.to_string()),
+                    scheme: None,
+                    pattern: None,
+                })
+                .collect(),
+        ),
+    }
+}
+
+/// Creates the `client/registerCapability` payload of the features enabled by settings,
+/// for the `initialized` notification.
+///
+/// # Arguments
+///
+/// * `settings` - The `RegistrationSettings` of the client.
+///
+/// # Returns
+///
+/// * The `RegistrationParams` to send, with no registration if nothing is enabled.
+pub fn get_registrations(settings: &RegistrationSettings) -> RegistrationParams {
+    get_registration_changes(
+        &RegistrationSettings {
+            watch_files: false,
+            format: false,
+        },
+        settings,
+    )
+    .0
+}
+
+/// Creates the payloads updating the registrations after the settings changed, like when
+/// formatting gets disabled in `workspace/didChangeConfiguration`.
+///
+/// # Arguments
+///
+/// * `previous` - The `RegistrationSettings` the current registrations were made for.
+/// * `current` - The new `RegistrationSettings`.
+///
+/// # Returns
+///
+/// * The `client/registerCapability` and `client/unregisterCapability` payloads, either of
+///   which may be empty.
+pub fn get_registration_changes(
+    previous: &RegistrationSettings,
+    current: &RegistrationSettings,
+) -> (RegistrationParams, UnregistrationParams) {
+    let mut registrations = Vec::new();
+    let mut unregistrations = Vec::new();
+    let features = [
+        (
+            previous.watch_files,
+            current.watch_files,
+            vec![file_watchers_registration()],
+        ),
+        (previous.format, current.format, formatting_registrations()),
+    ];
+    for (was_enabled, is_enabled, feature_registrations) in features {
+        match (was_enabled, is_enabled) {
+            (false, true) => registrations.extend(feature_registrations),
+            (true, false) => unregistrations.extend(feature_registrations.iter().map(unregister)),
+            _ => {}
+        }
+    }
+    (
+        RegistrationParams { registrations },
+        UnregistrationParams {
+            unregisterations: unregistrations,
+        },
+    )
+}
+
+/// Creates the unregistration of a registration.
+pub fn unregister(registration: &Registration) -> Unregistration {
+    Unregistration {
+        id: registration.id.clone(),
+        method: registration.method.clone(),
+    }
+}
+
+/// Returns the `client/registerCapability` params of the enabled features, as JSON.
+#[wasm_bindgen]
+pub fn get_registrations_wasm(watch_files: bool, format: bool) -> String {
+    let params = get_registrations(&RegistrationSettings {
+        watch_files,
+        format,
+    });
+    serde_json::to_string(&params).unwrap_or_default()
+}
+
+/// Returns the `client/unregisterCapability` params of the features disabled since the
+/// previous settings, as JSON.
+#[wasm_bindgen]
+pub fn get_unregistrations_wasm(
+    previous_watch_files: bool,
+    previous_format: bool,
+    watch_files: bool,
+    format: bool,
+) -> String {
+    let (_, params) = get_registration_changes(
+        &RegistrationSettings {
+            watch_files: previous_watch_files,
+            format: previous_format,
+        },
+        &RegistrationSettings {
+            watch_files,
+            format,
+        },
+    );
+    serde_json::to_string(&params).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_get_registrations() {
+        let params = get_registrations(&RegistrationSettings::default());
+        let methods: Vec<_> = params
+            .registrations
+            .iter()
+            .map(|registration| registration.method.as_str())
+            .collect();
+        assert_eq!(
+            methods,
+            vec![
+                "workspace/didChangeWatchedFiles",
+                "textDocument/formatting",
+                "textDocument/rangeFormatting",
+                "textDocument/onTypeFormatting"
+            ]
+        );
+        let options = params.registrations[0].register_options.as_ref().unwrap();
+        assert_eq!(options["watchers"][0]["globPattern"], STYLESHEETS_GLOB);
+        let options = params.registrations[1].register_options.as_ref().unwrap();
+        assert_eq!(options["documentSelector"][1]["language"], "scss");
+        let options = params.registrations[3].register_options.as_ref().unwrap();
+        assert_eq!(options["firstTriggerCharacter"], "{");
+        assert_eq!(options["moreTriggerCharacter"], json!(["}", ";", "\n"]));
+    }
+
+    #[test]
+    fn test_get_registration_changes() {
+        let previous = RegistrationSettings::default();
+        let current = RegistrationSettings {
+            format: false,
+            ..previous
+        };
+        let (registrations, unregistrations) = get_registration_changes(&previous, &current);
+        assert!(registrations.registrations.is_empty());
+        let ids: Vec<_> = unregistrations
+            .unregisterations
+            .iter()
+            .map(|unregistration| unregistration.id.as_str())
+            .collect();
+        assert_eq!(
+            ids,
+            vec![FORMATTING_ID, RANGE_FORMATTING_ID, ON_TYPE_FORMATTING_ID]
+        );
+
+        let (registrations, _) = get_registration_changes(&current, &previous);
+        assert_eq!(registrations.registrations.len(), 3);
+    }
+
+    #[test]
+    fn test_text_document_content_registration() {
+        assert_eq!(text_document_content_registration(&[]), None);
+        let registration =
+            text_document_content_registration(&["tailwind-generated".to_string()]).unwrap();
+        assert_eq!(registration.method, "workspace/textDocumentContent");
+        let options = registration.register_options.unwrap();
+        assert_eq!(options["schemes"][0], "tailwind-generated");
+    }
+}
@@ -0,0 +1,527 @@
+use crate::features::colors::get_color_presentations;
+use crate::features::custom_properties::get_document_highlights;
+use crate::features::selection_range::get_selection_ranges;
+use crate::service::LanguageService;
+use crate::text_document::TextDocument;
+use lsp_types::{
+    CodeActionOrCommand, CodeActionParams, CodeActionResponse, ColorPresentationParams,
+    CompletionParams, CompletionResponse, DidChangeTextDocumentParams, DidCloseTextDocumentParams,
+    DidOpenTextDocumentParams, DocumentColorParams, DocumentDiagnosticParams,
+    DocumentDiagnosticReport, DocumentFormattingParams, DocumentHighlightParams,
+    DocumentOnTypeFormattingParams, DocumentRangeFormattingParams, DocumentSymbolParams,
+    DocumentSymbolResponse, FoldingRangeParams, FullDocumentDiagnosticReport, GotoDefinitionParams,
+    GotoDefinitionResponse, HoverParams, PrepareRenameResponse, ReferenceParams,
+    RelatedFullDocumentDiagnosticReport, RenameParams, SelectionRangeParams,
+    SemanticTokensDeltaParams, SemanticTokensParams, SemanticTokensRangeParams,
+    SemanticTokensRangeResult, SemanticTokensResult, TextDocumentPositionParams, Uri,
+};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{json, Value};
+use wasm_bindgen::prelude::*;
+
+/// The error codes of the protocol used by `handle_request`.
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+const REQUEST_FAILED: i64 = -32803;
+
+/// The notifications handled by `handle_request`, which have no response.
+const NOTIFICATIONS: &[&str] = &[
+    "textDocument/didOpen",
+    "textDocument/didChange",
+    "textDocument/didClose",
+];
+
+/// An error answering a request, like the `error` of a JSON-RPC response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+struct RequestError {
+    code: i64,
+    message: String,
+}
+
+impl RequestError {
+    fn new(code: i64, message: impl Into<String>) -> Self {
+        RequestError {
+            code,
+            message: message.into(),
+        }
+    }
+}
+
+#[wasm_bindgen]
+impl LanguageService {
+    /// Answers a raw LSP request or notification, for servers embedding CSS support, like an
+    /// HTML server delegating `<style>` blocks, without converting payloads to Rust types.
+    ///
+    /// Documents are synchronized with the `textDocument/didOpen`, `didChange` and
+    /// `didClose` notifications, full or incremental, and requests must refer to open
+    /// documents. The supported requests are `textDocument/completion`, `hover`,
+    /// `documentHighlight`, `documentSymbol`, `documentColor`, `colorPresentation`,
+    /// `foldingRange`, `selectionRange`, `semanticTokens/full`, `semanticTokens/full/delta`,
+    /// `semanticTokens/range`, `definition`, `references`, `prepareRename`, `rename`,
+    /// `codeAction`, `formatting`, `rangeFormatting`, `onTypeFormatting` and `diagnostic`.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The LSP method, like `textDocument/hover`.
+    /// * `params` - The `params` of the request, as JSON.
+    ///
+    /// # Returns
+    ///
+    /// * The response without its `jsonrpc` and `id` members, as JSON: `{ "result": ... }`,
+    ///   `null` for notifications, or `{ "error": { "code": ..., "message": ... } }` with
+    ///   the error codes of the protocol, for notifications too if their params are invalid.
+    #[wasm_bindgen(js_name = handleRequest)]
+    pub fn handle_request(&mut self, method: &str, params: &str) -> String {
+        let response = match self.dispatch(method, params) {
+            Ok(_) if NOTIFICATIONS.contains(&method) => Value::Null,
+            Ok(result) => json!({ "result": result }),
+            Err(error) => json!({ "error": error }),
+        };
+        response.to_string()
+    }
+}
+
+impl LanguageService {
+    fn dispatch(&mut self, method: &str, params: &str) -> Result<Value, RequestError> {
+        match method {
+            "textDocument/didOpen" => {
+                let params: DidOpenTextDocumentParams = parse_params(params)?;
+                let item = params.text_document;
+                self.open_document(&TextDocument::new(
+                    item.uri.as_str(),
+                    &item.language_id,
+                    item.version.into(),
+                    &item.text,
+                ));
+                Ok(Value::Null)
+            }
+            "textDocument/didChange" => {
+                let params: DidChangeTextDocumentParams = parse_params(params)?;
+                let document = params.text_document;
+                self.documents_mut().apply_changes(
+                    document.uri.as_str(),
+                    document.version.into(),
+                    &params.content_changes,
+                );
+                Ok(Value::Null)
+            }
+            "textDocument/didClose" => {
+                let params: DidCloseTextDocumentParams = parse_params(params)?;
+                self.close_document(params.text_document.uri.as_str());
+                Ok(Value::Null)
+            }
+            "textDocument/completion" => {
+                let params: CompletionParams = parse_params(params)?;
+                let position = params.text_document_position;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                let items = self.completions(&document, position.position);
+                to_value(CompletionResponse::Array(items))
+            }
+            "textDocument/hover" => {
+                let params: HoverParams = parse_params(params)?;
+                let position = params.text_document_position_params;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                to_value(self.hover(&document, position.position))
+            }
+            "textDocument/documentHighlight" => {
+                let params: DocumentHighlightParams = parse_params(params)?;
+                let position = params.text_document_position_params;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                to_value(get_document_highlights(&document.text, position.position))
+            }
+            "textDocument/documentSymbol" => {
+                let params: DocumentSymbolParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                let symbols = self.document_symbols(&document).items;
+                to_value(DocumentSymbolResponse::Nested(symbols))
+            }
+            "textDocument/documentColor" => {
+                let params: DocumentColorParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(self.document_colors(&document).items)
+            }
+            "textDocument/colorPresentation" => {
+                let params: ColorPresentationParams = parse_params(params)?;
+                to_value(get_color_presentations(params.color, params.range))
+            }
+            "textDocument/foldingRange" => {
+                let params: FoldingRangeParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(self.folding_ranges(&document).items)
+            }
+            "textDocument/selectionRange" => {
+                let params: SelectionRangeParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(get_selection_ranges(&document.text, &params.positions))
+            }
+            "textDocument/semanticTokens/full" => {
+                let params: SemanticTokensParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(SemanticTokensResult::Tokens(
+                    self.semantic_tokens_full(&document),
+                ))
+            }
+            "textDocument/semanticTokens/full/delta" => {
+                let params: SemanticTokensDeltaParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(self.semantic_tokens_delta(&document, &params.previous_result_id))
+            }
+            "textDocument/semanticTokens/range" => {
+                let params: SemanticTokensRangeParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(SemanticTokensRangeResult::Tokens(
+                    self.semantic_tokens_range(&document, params.range),
+                ))
+            }
+            "textDocument/definition" => {
+                let params: GotoDefinitionParams = parse_params(params)?;
+                let position = params.text_document_position_params;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                let locations = self.definition(&document, position.position);
+                to_value(GotoDefinitionResponse::Array(locations))
+            }
+            "textDocument/references" => {
+                let params: ReferenceParams = parse_params(params)?;
+                let position = params.text_document_position;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                let include_declaration = params.context.include_declaration;
+                to_value(self.references(&document, position.position, include_declaration))
+            }
+            "textDocument/prepareRename" => {
+                let params: TextDocumentPositionParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                let range = self.prepare_rename(&document, params.position);
+                to_value(range.map(PrepareRenameResponse::Range))
+            }
+            "textDocument/rename" => {
+                let params: RenameParams = parse_params(params)?;
+                let position = params.text_document_position;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                let edit = self
+                    .rename(&document, position.position, &params.new_name)
+                    .map_err(|message| RequestError::new(REQUEST_FAILED, message))?;
+                to_value(edit)
+            }
+            "textDocument/codeAction" => {
+                let params: CodeActionParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                let context = params.context;
+                let actions: CodeActionResponse = self
+                    .code_actions(
+                        &document,
+                        params.range,
+                        &context.diagnostics,
+                        context.only.as_deref(),
+                    )
+                    .into_iter()
+                    .map(CodeActionOrCommand::CodeAction)
+                    .collect();
+                to_value(actions)
+            }
+            "textDocument/formatting" => {
+                let params: DocumentFormattingParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(self.formatting(&document, &params.options))
+            }
+            "textDocument/rangeFormatting" => {
+                let params: DocumentRangeFormattingParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                to_value(self.range_formatting(&document, params.range, &params.options))
+            }
+            "textDocument/onTypeFormatting" => {
+                let params: DocumentOnTypeFormattingParams = parse_params(params)?;
+                let position = params.text_document_position;
+                let document = self.open_text_document(&position.text_document.uri)?;
+                to_value(self.on_type_formatting(
+                    &document,
+                    position.position,
+                    &params.ch,
+                    &params.options,
+                ))
+            }
+            "textDocument/diagnostic" => {
+                let params: DocumentDiagnosticParams = parse_params(params)?;
+                let document = self.open_text_document(&params.text_document.uri)?;
+                let report = RelatedFullDocumentDiagnosticReport {
+                    related_documents: None,
+                    full_document_diagnostic_report: FullDocumentDiagnosticReport {
+                        result_id: None,
+                        items: self.diagnostics(&document).items,
+                    },
+                };
+                to_value(DocumentDiagnosticReport::Full(report))
+            }
+            _ => Err(RequestError::new(
+                METHOD_NOT_FOUND,
+                format!("Unsupported method: {method}"),
+            )),
+        }
+    }
+
+    /// Returns a copy of an open document, as features may need the service mutably.
+    fn open_text_document(&self, uri: &Uri) -> Result<TextDocument, RequestError> {
+        self.documents().get(uri.as_str()).cloned().ok_or_else(|| {
+            RequestError::new(
+                INVALID_PARAMS,
+                format!("Document not open: {}", uri.as_str()),
+            )
+        })
+    }
+}
+
+fn parse_params<T: DeserializeOwned>(params: &str) -> Result<T, RequestError> {
+    serde_json::from_str(params)
+        .map_err(|error| RequestError::new(INVALID_PARAMS, format!("Invalid params: {error}")))
+}
+
+fn to_value(result: impl Serialize) -> Result<Value, RequestError> {
+    serde_json::to_value(result)
+        .map_err(|error| RequestError::new(REQUEST_FAILED, error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(service: &mut LanguageService, method: &str, params: Value) -> Value {
+        serde_json::from_str(&service.handle_request(method, &params.to_string())).unwrap()
+    }
+
+    #[test]
+    fn test_handle_request() {
+        let mut service = LanguageService::new();
+        let document = json!({ "uri": "file:///a.css" });
+        let opened = request(
+            &mut service,
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": "file:///a.css", "languageId": "css", "version": 1,
+                "text": "a { display: block; }"
+            } }),
+        );
+        assert_eq!(opened, Value::Null);
+
+        let hover = request(
+            &mut service,
+            "textDocument/hover",
+            json!({ "textDocument": document, "position": { "line": 0, "character": 5 } }),
+        );
+        assert!(hover["result"]["contents"]["value"]
+            .as_str()
+            .unwrap()
+            .contains("`display`"));
+
+        request(
+            &mut service,
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": "file:///a.css", "version": 2 },
+                "contentChanges": [{ "text": "a { color: #00f; }\nb {}" }]
+            }),
+        );
+        let colors = request(
+            &mut service,
+            "textDocument/documentColor",
+            json!({ "textDocument": document }),
+        );
+        assert_eq!(colors["result"][0]["color"]["blue"], 1.0);
+        let symbols = request(
+            &mut service,
+            "textDocument/documentSymbol",
+            json!({ "textDocument": document }),
+        );
+        assert_eq!(symbols["result"].as_array().unwrap().len(), 2);
+        let diagnostics = request(
+            &mut service,
+            "textDocument/diagnostic",
+            json!({ "textDocument": document }),
+        );
+        assert_eq!(diagnostics["result"]["kind"], "full");
+
+        request(
+            &mut service,
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": "file:///a.css", "version": 3 },
+                "contentChanges": [{ "text": ":root { --gap: 1px; }\na { margin: var(--gap); }" }]
+            }),
+        );
+        let definition = request(
+            &mut service,
+            "textDocument/definition",
+            json!({ "textDocument": document, "position": { "line": 1, "character": 19 } }),
+        );
+        assert_eq!(definition["result"][0]["range"]["start"]["character"], 8);
+
+        request(
+            &mut service,
+            "textDocument/didClose",
+            json!({ "textDocument": document }),
+        );
+        let closed = request(
+            &mut service,
+            "textDocument/foldingRange",
+            json!({ "textDocument": document }),
+        );
+        assert_eq!(closed["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[test]
+    fn test_handle_semantic_tokens_requests() {
+        let mut service = LanguageService::new();
+        let document = json!({ "uri": "file:///a.css" });
+        request(
+            &mut service,
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": "file:///a.css", "languageId": "css", "version": 1,
+                "text": "a { top: 0; }\nb { left: 0; }"
+            } }),
+        );
+        let full = request(
+            &mut service,
+            "textDocument/semanticTokens/full",
+            json!({ "textDocument": document }),
+        );
+        assert_eq!(
+            full["result"]["data"],
+            json!([0, 4, 3, 0, 0, 1, 4, 4, 0, 0])
+        );
+        let result_id = full["result"]["resultId"].clone();
+
+        request(
+            &mut service,
+            "textDocument/didChange",
+            json!({
+                "textDocument": { "uri": "file:///a.css", "version": 2 },
+                "contentChanges": [{ "text": "a { top: 0; }\nb { --left: 0; }" }]
+            }),
+        );
+        let delta = request(
+            &mut service,
+            "textDocument/semanticTokens/full/delta",
+            json!({ "textDocument": document, "previousResultId": result_id }),
+        );
+        assert_eq!(
+            delta["result"]["edits"],
+            json!([{ "start": 5, "deleteCount": 5, "data": [1, 4, 6, 1, 0] }])
+        );
+        assert_ne!(delta["result"]["resultId"], result_id);
+        let outdated = request(
+            &mut service,
+            "textDocument/semanticTokens/full/delta",
+            json!({ "textDocument": document, "previousResultId": result_id }),
+        );
+        assert_eq!(outdated["result"]["data"].as_array().unwrap().len(), 10);
+
+        let range = request(
+            &mut service,
+            "textDocument/semanticTokens/range",
+            json!({ "textDocument": document, "range": {
+                "start": { "line": 1, "character": 0 }, "end": { "line": 2, "character": 0 }
+            } }),
+        );
+        assert_eq!(range["result"]["data"], json!([1, 4, 6, 1, 0]));
+    }
+
+    #[test]
+    fn test_handle_code_action_request() {
+        let mut service = LanguageService::new();
+        request(
+            &mut service,
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": "file:///a.css", "languageId": "css", "version": 1,
+                "text": "a { margin: 0px; }"
+            } }),
+        );
+        let diagnostics = request(
+            &mut service,
+            "textDocument/diagnostic",
+            json!({ "textDocument": { "uri": "file:///a.css" } }),
+        );
+        let range = json!({
+            "start": { "line": 0, "character": 12 }, "end": { "line": 0, "character": 15 }
+        });
+        let actions = request(
+            &mut service,
+            "textDocument/codeAction",
+            json!({
+                "textDocument": { "uri": "file:///a.css" },
+                "range": range,
+                "context": { "diagnostics": diagnostics["result"]["items"] }
+            }),
+        );
+        assert_eq!(actions["result"][0]["kind"], "quickfix");
+        assert_eq!(actions["result"][0]["isPreferred"], true);
+        let edits = &actions["result"][0]["edit"]["changes"]["file:///a.css"];
+        assert_eq!(edits[0]["newText"], "");
+    }
+
+    #[test]
+    fn test_handle_formatting_request() {
+        let mut service = LanguageService::new();
+        request(
+            &mut service,
+            "textDocument/didOpen",
+            json!({ "textDocument": {
+                "uri": "file:///a.css", "languageId": "css", "version": 1,
+                "text": "a {\ncolor:red;\n}\n"
+            } }),
+        );
+        let edits = request(
+            &mut service,
+            "textDocument/formatting",
+            json!({
+                "textDocument": { "uri": "file:///a.css" },
+                "options": { "tabSize": 4, "insertSpaces": true }
+            }),
+        );
+        assert_eq!(
+            edits["result"],
+            json!([{ "range": {
+                "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 6 }
+            }, "newText": "    color: " }])
+        );
+
+        let edits = request(
+            &mut service,
+            "textDocument/rangeFormatting",
+            json!({
+                "textDocument": { "uri": "file:///a.css" },
+                "range": {
+                    "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 0 }
+                },
+                "options": { "tabSize": 2, "insertSpaces": true }
+            }),
+        );
+        assert_eq!(edits["result"][0]["newText"], "  color: ");
+
+        let edits = request(
+            &mut service,
+            "textDocument/onTypeFormatting",
+            json!({
+                "textDocument": { "uri": "file:///a.css" },
+                "position": { "line": 1, "character": 10 },
+                "ch": ";",
+                "options": { "tabSize": 2, "insertSpaces": true }
+            }),
+        );
+        assert_eq!(
+            edits["result"],
+            json!([{ "range": {
+                "start": { "line": 1, "character": 0 }, "end": { "line": 1, "character": 0 }
+            }, "newText": "  " }])
+        );
+    }
+
+    #[test]
+    fn test_handle_request_errors() {
+        let mut service = LanguageService::new();
+        let unknown = request(&mut service, "workspace/symbol", json!({}));
+        assert_eq!(unknown["error"]["code"], METHOD_NOT_FOUND);
+        let invalid = request(&mut service, "textDocument/hover", json!({ "position": 1 }));
+        assert_eq!(invalid["error"]["code"], INVALID_PARAMS);
+    }
+}
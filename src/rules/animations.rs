@@ -0,0 +1,62 @@
+use crate::case_folding::eq_ignore_case;
+use crate::parser::find_declarations;
+use crate::rules::{new_diagnostic, RuleContext};
+use crate::shorthands::parse_animation;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Checks the `animation` shorthands in the given CSS source code, decomposing each
+/// animation into its longhands.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each `animation` that can't be decomposed.
+pub fn check_animations(context: &RuleContext) -> Vec<Diagnostic> {
+    find_declarations(&context.parse.syntax())
+        .into_iter()
+        .filter(|declaration| {
+            eq_ignore_case(&declaration.name, "animation")
+                || eq_ignore_case(&declaration.name, "-webkit-animation")
+        })
+        .filter_map(|declaration| {
+            let error = parse_animation(&declaration.value, declaration.value_range.start).err()?;
+            Some(new_diagnostic(
+                context.range(error.range.start, error.range.end),
+                DiagnosticSeverity::ERROR,
+                "invalid-animation",
+                error.message,
+                Vec::new(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_animations() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "a { animation: spin 1s linear infinite, fade 1s 2s 3s; }\nb { -webkit-animation: 1s bounce bounce; animation: var(--a) 2s 3s 4s; }";
+        let diagnostics = check_animations(&RuleContext::new(&uri, code));
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| (diagnostic.range.start.line, diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    0,
+                    "An animation takes at most two times, its duration and its delay."
+                ),
+                (1, "An animation takes a single name."),
+            ]
+        );
+        assert_eq!(diagnostics[0].range.start.character, 51);
+    }
+}
@@ -0,0 +1,479 @@
+use lsp_types::DiagnosticSeverity;
+use serde::{Deserialize, Serialize, Serializer};
+use serde_json::json;
+use std::sync::OnceLock;
+use wasm_bindgen::prelude::*;
+
+/// The kind of problems reported by a rule, to configure the severity of related rules at
+/// once.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleCategory {
+    /// Syntax errors reported by the parser, which have no rule code.
+    Syntax,
+    /// Code that is invalid and ignored by browsers.
+    Correctness,
+    /// Code that is valid but likely a mistake.
+    Suspicious,
+    /// Code that is slower to load or render than needed.
+    Performance,
+    /// Code that is correct but could be written more consistently.
+    Style,
+}
+
+/// Describes a built-in rule, for settings UIs and documentation.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleMetadata {
+    /// The code of the diagnostics reported by the rule.
+    pub code: &'static str,
+    pub category: RuleCategory,
+    #[serde(serialize_with = "serialize_severity")]
+    pub default_severity: DiagnosticSeverity,
+    pub description: &'static str,
+    /// Whether the diagnostics of the rule may carry quick fixes.
+    pub fixable: bool,
+    /// The JSON schema of the options of the rule, if it has any.
+    pub options: Option<serde_json::Value>,
+}
+
+const fn rule(
+    code: &'static str,
+    category: RuleCategory,
+    default_severity: DiagnosticSeverity,
+    description: &'static str,
+    fixable: bool,
+) -> RuleMetadata {
+    RuleMetadata {
+        code,
+        category,
+        default_severity,
+        description,
+        fixable,
+        options: None,
+    }
+}
+
+const RULES: &[RuleMetadata] = &[
+    rule(
+        "complex-selector",
+        RuleCategory::Performance,
+        DiagnosticSeverity::WARNING,
+        "Selectors shouldn't nest or combine selector lists beyond what can be analyzed.",
+        false,
+    ),
+    rule(
+        "cursor-missing-fallback",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Cursor images must be followed by a fallback keyword.",
+        true,
+    ),
+    rule(
+        "deprecated",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Obsolete and non-standard properties, keywords and media features should be replaced with their standard equivalent.",
+        true,
+    ),
+    rule(
+        "duplicate-at-rule",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Keyframes, font faces and layer statements shouldn't be declared twice in the same scope.",
+        false,
+    ),
+    rule(
+        "duplicate-declaration",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "A block shouldn't repeat a declaration with the same value.",
+        true,
+    ),
+    rule(
+        "duplicate-image-set-resolution",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Options of an `image-set()` must have distinct resolutions.",
+        false,
+    ),
+    rule(
+        "duplicate-media-condition",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Media query lists shouldn't repeat a query, nor queries a condition.",
+        false,
+    ),
+    rule(
+        "duplicate-property",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "A block shouldn't declare a property again with another value, unless as a fallback.",
+        true,
+    ),
+    rule(
+        "empty-rule",
+        RuleCategory::Style,
+        DiagnosticSeverity::WARNING,
+        "Style rules should contain declarations or nested rules.",
+        true,
+    ),
+    rule(
+        "font-source-order",
+        RuleCategory::Performance,
+        DiagnosticSeverity::INFORMATION,
+        "WOFF2 sources of a `@font-face` should come before less compressed formats.",
+        true,
+    ),
+    rule(
+        "impossible-media-query",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Media queries shouldn't have contradictory conditions, which never match.",
+        false,
+    ),
+    rule(
+        "invalid-animation",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Animations must be made of a name, two times, an easing function and keywords.",
+        false,
+    ),
+    rule(
+        "invalid-cross-fade",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Arguments of `cross-fade()` must be images with optional percentages.",
+        false,
+    ),
+    rule(
+        "invalid-cursor",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Cursor images take two unitless hotspot coordinates, and keywords come last.",
+        false,
+    ),
+    rule(
+        "invalid-escape",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "A backslash cannot escape a newline or the end of the file outside of a string.",
+        true,
+    ),
+    rule(
+        "invalid-font-source",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Sources of a `@font-face` must be `local()` or `url()` with optional hints.",
+        false,
+    ),
+    rule(
+        "invalid-image-set",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Options of an `image-set()` must be images with an optional resolution and type.",
+        false,
+    ),
+    rule(
+        "invalid-media-query",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Media features must be well-formed and take values of the right type.",
+        false,
+    ),
+    rule(
+        "invalid-resolution",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Resolutions must be non-negative numbers with a known unit.",
+        true,
+    ),
+    rule(
+        "invalid-timing-function",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Arguments of `cubic-bezier()` and `steps()` must be in range.",
+        false,
+    ),
+    rule(
+        "invalid-unicode-escape",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::WARNING,
+        "Escaped code points must be valid, non-surrogate and non-zero.",
+        true,
+    ),
+    rule(
+        "invalid-unicode-range",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Ranges of `unicode-range` must be valid code point ranges.",
+        false,
+    ),
+    rule(
+        "invalid-value",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Values must match the grammar of their property or descriptor, like a `<color>` for `color`.",
+        false,
+    ),
+    rule(
+        "missing-descriptor",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "`@font-face` rules must declare a `font-family` and a `src`.",
+        false,
+    ),
+    rule(
+        "newline-in-string",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Strings cannot contain unescaped newlines.",
+        true,
+    ),
+    rule(
+        "overlapping-unicode-range",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "`@font-face` rules of the same family shouldn't cover the same code points.",
+        false,
+    ),
+    rule(
+        "unknown-cursor",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Cursor keywords must be known.",
+        false,
+    ),
+    rule(
+        "unknown-descriptor",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::WARNING,
+        "`@font-face` rules must use known descriptors.",
+        true,
+    ),
+    rule(
+        "unknown-font-format",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Font formats in `format()` must be known.",
+        false,
+    ),
+    rule(
+        "unknown-font-tech",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Font technologies in `tech()` must be known.",
+        false,
+    ),
+    rule(
+        "unknown-media-feature",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::WARNING,
+        "Media queries must use known media features.",
+        true,
+    ),
+    rule(
+        "unknown-property",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::WARNING,
+        "Declarations of style rules must use known properties.",
+        true,
+    ),
+    rule(
+        "unknown-pseudo",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::WARNING,
+        "Selectors must use known pseudo-classes and pseudo-elements.",
+        true,
+    ),
+    rule(
+        "unreachable-font-source",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::WARNING,
+        "Sources of a `@font-face` shouldn't duplicate earlier ones.",
+        true,
+    ),
+    rule(
+        "unterminated-string",
+        RuleCategory::Correctness,
+        DiagnosticSeverity::ERROR,
+        "Strings must be terminated.",
+        true,
+    ),
+    rule(
+        "vendor-prefix",
+        RuleCategory::Suspicious,
+        DiagnosticSeverity::INFORMATION,
+        "Vendor-prefixed properties should come with their standard property.",
+        true,
+    ),
+    rule(
+        "zero-units",
+        RuleCategory::Style,
+        DiagnosticSeverity::HINT,
+        "Zero lengths don't need a unit.",
+        true,
+    ),
+];
+
+fn serialize_severity<S: Serializer>(
+    severity: &DiagnosticSeverity,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(severity_name(*severity))
+}
+
+/// Returns the name of a severity, as used in settings, like `warning`.
+pub fn severity_name(severity: DiagnosticSeverity) -> &'static str {
+    if severity == DiagnosticSeverity::ERROR {
+        "error"
+    } else if severity == DiagnosticSeverity::WARNING {
+        "warning"
+    } else if severity == DiagnosticSeverity::INFORMATION {
+        "information"
+    } else {
+        "hint"
+    }
+}
+
+/// Returns the metadata of every built-in rule, sorted by code.
+pub fn get_rule_catalog() -> &'static [RuleMetadata] {
+    static CATALOG: OnceLock<Vec<RuleMetadata>> = OnceLock::new();
+    CATALOG.get_or_init(|| {
+        RULES
+            .iter()
+            .map(|rule| RuleMetadata {
+                options: options_schema(rule.code),
+                ..rule.clone()
+            })
+            .collect()
+    })
+}
+
+/// Returns the JSON schema of the options of a built-in rule, set in the `rules` section of
+/// the settings under the camel case code of the rule, see `RuleOptions`.
+fn options_schema(code: &str) -> Option<serde_json::Value> {
+    match code {
+        "duplicate-property" => Some(json!({
+            "type": "object",
+            "properties": {
+                "allowFallbacks": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Allow declaring a property again as a fallback, when one of the values is vendor-prefixed or the later one uses a function or a unit the earlier one doesn't."
+                },
+                "ignore": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": [],
+                    "description": "Properties that can be declared again with any value."
+                }
+            }
+        })),
+        "unknown-pseudo" => Some(json!({
+            "type": "object",
+            "properties": {
+                "ignore": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": [],
+                    "description": "Pseudo-classes and pseudo-elements to accept, with their colons, like `::-webkit-search-results-decoration`."
+                }
+            }
+        })),
+        "vendor-prefix" => Some(json!({
+            "type": "object",
+            "properties": {
+                "requireStandard": {
+                    "type": "boolean",
+                    "default": true,
+                    "description": "Require vendor-prefixed properties to come with their standard property."
+                },
+                "requirePrefixed": {
+                    "type": "array",
+                    "items": { "type": "string" },
+                    "default": [],
+                    "description": "Vendor-prefixed properties that must come with their standard property, like `-webkit-backdrop-filter`."
+                }
+            }
+        })),
+        _ => None,
+    }
+}
+
+/// Returns the metadata of a built-in rule.
+pub fn get_rule(code: &str) -> Option<&'static RuleMetadata> {
+    get_rule_catalog().iter().find(|rule| rule.code == code)
+}
+
+/// Returns the metadata of every built-in rule as a JSON array.
+#[wasm_bindgen]
+pub fn get_rule_catalog_wasm() -> String {
+    serde_json::to_string(get_rule_catalog()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::diagnostics::get_diagnostics;
+    use crate::rules::{BUILTIN_RULES, STRING_RULE};
+    use lsp_types::NumberOrString;
+
+    #[test]
+    fn test_rule_catalog_is_sorted() {
+        let codes: Vec<_> = RULES.iter().map(|rule| rule.code).collect();
+        let mut sorted = codes.clone();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(codes, sorted);
+    }
+
+    #[test]
+    fn test_rule_catalog_matches_builtin_rules() {
+        let mut codes: Vec<_> = std::iter::once(&STRING_RULE)
+            .chain(BUILTIN_RULES)
+            .flat_map(|rule| rule.codes.iter().copied())
+            .collect();
+        codes.sort();
+        codes.dedup();
+        let catalog: Vec<_> = RULES.iter().map(|rule| rule.code).collect();
+        assert_eq!(catalog, codes);
+    }
+
+    #[test]
+    fn test_rule_catalog_covers_diagnostics() {
+        let code = "a { content: \"a\n; cursor: url(a.cur) 1, poiner; background: image-set(url(a.png) 2, url(b.png) 2x) }\n@font-face { src: url(a.woff) format(\"wof\"), url(a.woff2); unicode-range: U+110000; }";
+        let uri = "file:///test.css".parse().unwrap();
+        let diagnostics = get_diagnostics(&uri, code);
+        assert!(diagnostics.len() > 4);
+        for diagnostic in diagnostics {
+            let Some(NumberOrString::String(code)) = &diagnostic.code else {
+                continue;
+            };
+            let rule = get_rule(code).unwrap_or_else(|| panic!("`{code}` is not in the catalog"));
+            assert_eq!(Some(rule.default_severity), diagnostic.severity, "{code}");
+        }
+    }
+
+    #[test]
+    fn test_get_rule_catalog_wasm() {
+        let catalog: serde_json::Value = serde_json::from_str(&get_rule_catalog_wasm()).unwrap();
+        let cursor_missing_fallback = catalog
+            .as_array()
+            .unwrap()
+            .iter()
+            .find(|rule| rule["code"] == "cursor-missing-fallback")
+            .unwrap();
+        assert_eq!(cursor_missing_fallback["category"], "correctness");
+        assert_eq!(cursor_missing_fallback["defaultSeverity"], "error");
+        assert_eq!(cursor_missing_fallback["fixable"], true);
+        assert_eq!(cursor_missing_fallback["options"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn test_rule_options_schema() {
+        let duplicate_property = get_rule("duplicate-property").unwrap();
+        let options = duplicate_property.options.as_ref().unwrap();
+        assert_eq!(options["properties"]["allowFallbacks"]["default"], true);
+    }
+}
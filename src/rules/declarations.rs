@@ -0,0 +1,476 @@
+use crate::case_folding::{
+    contains_ignore_case, eq_ignore_case, fold_case, starts_with_ignore_case,
+};
+use crate::data::property_names::VENDOR_PREFIXES;
+use crate::data::values::LENGTH_UNITS;
+use crate::parser::tokenizer::{tokenize, TokenKind};
+use crate::parser::{find_declarations, Declaration};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use biome_rowan::Direction;
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, TextEdit};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+/// Units supported by every browser, which a fallback declaration doesn't need to be
+/// repeated for, unlike newer ones like `dvh`.
+const BASELINE_UNITS: &[&str] = &[
+    "ch", "cm", "deg", "dpcm", "dpi", "dppx", "em", "ex", "fr", "grad", "hz", "in", "khz", "mm",
+    "ms", "pc", "pt", "px", "q", "rad", "rem", "s", "turn", "vh", "vmax", "vmin", "vw", "x",
+];
+
+/// The options of the `duplicate-property` rule.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DuplicatePropertyOptions {
+    /// Whether a property can be declared again as a fallback, when one of the values is
+    /// vendor-prefixed or the later one uses a function or a unit the earlier one doesn't,
+    /// like `height: 100vh` followed by `height: 100dvh`.
+    pub allow_fallbacks: bool,
+    /// The properties that can be declared again with any value, like `display`.
+    pub ignore: Vec<String>,
+}
+
+impl Default for DuplicatePropertyOptions {
+    fn default() -> Self {
+        Self {
+            allow_fallbacks: true,
+            ignore: Vec::new(),
+        }
+    }
+}
+
+/// Properties where a unitless zero doesn't mean a zero length, like the flex basis of
+/// `flex: 1 1 0`.
+const UNIT_REQUIRED_PROPERTIES: &[&str] = &["flex"];
+
+/// Checks the declarations and blocks of style rules for code that can be removed without
+/// changing the result: repeated declarations, declarations overridden later in their block,
+/// empty rules and units of zero lengths.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, each with a fix removing the unnecessary code.
+pub fn check_declarations(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let root = context.parse.syntax();
+    check_duplicates(context, &root, &mut diagnostics);
+    check_overridden(context, &root, &mut diagnostics);
+    check_empty_rules(context, &root, &mut diagnostics);
+    check_zero_units(context, &root, &mut diagnostics);
+    diagnostics
+}
+
+fn check_duplicates(
+    context: &RuleContext,
+    root: &CssSyntaxNode,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let declarations = find_declarations(root);
+    // The declarations repeated later in their block, found from the end
+    let mut later = HashSet::new();
+    let mut repeated = vec![false; declarations.len()];
+    for (idx, declaration) in declarations.iter().enumerate().rev() {
+        let key = (
+            declaration.node.parent().and_then(|node| node.parent()),
+            fold_case(&declaration.name).into_owned(),
+            declaration.value.as_str(),
+            important(&declaration.node),
+        );
+        repeated[idx] = !later.insert(key);
+    }
+    for (declaration, _) in declarations
+        .iter()
+        .zip(repeated)
+        .filter(|(_, repeated)| *repeated)
+    {
+        // The declaration with its semicolon
+        let node = declaration
+            .node
+            .parent()
+            .unwrap_or(declaration.node.clone());
+        let range = node.text_trimmed_range();
+        let removal = line_removal_range(context.source, range.start().into()..range.end().into());
+        let mut diagnostic = new_diagnostic(
+            context.range(declaration.name_range.start, declaration.value_range.end),
+            DiagnosticSeverity::WARNING,
+            "duplicate-declaration",
+            format!(
+                "`{}` is declared again with the same value later in this block.",
+                declaration.name
+            ),
+            vec![QuickFix {
+                title: "Remove the duplicate declaration".to_string(),
+                edits: vec![TextEdit {
+                    range: context.range(removal.start, removal.end),
+                    new_text: String::new(),
+                }],
+            }],
+        );
+        diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+        diagnostics.push(diagnostic);
+    }
+}
+
+fn check_overridden(
+    context: &RuleContext,
+    root: &CssSyntaxNode,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let options = &context.options.duplicate_property;
+    let declarations = find_declarations(root);
+    // The next declaration of the same property in the same block, if any
+    let mut next_declarations = vec![None; declarations.len()];
+    let mut later_by_key = HashMap::new();
+    for (idx, declaration) in declarations.iter().enumerate().rev() {
+        let block = declaration.node.parent().and_then(|node| node.parent());
+        let key = (block, fold_case(&declaration.name).into_owned());
+        next_declarations[idx] = later_by_key.insert(key, idx);
+    }
+    for (declaration, next) in declarations.iter().zip(next_declarations) {
+        if declaration.name.starts_with("--")
+            || options
+                .ignore
+                .iter()
+                .any(|property| eq_ignore_case(property, &declaration.name))
+        {
+            continue;
+        }
+        let Some(later) = next.map(|idx| &declarations[idx]) else {
+            continue;
+        };
+        // Repeated values are reported by `duplicate-declaration`, and a declaration marked
+        // `!important` isn't overridden by a later one without the flag
+        if (later.value == declaration.value
+            && important(&later.node) == important(&declaration.node))
+            || (important(&declaration.node) && !important(&later.node))
+            || (options.allow_fallbacks && is_fallback(declaration, later))
+        {
+            continue;
+        }
+        let node = declaration
+            .node
+            .parent()
+            .unwrap_or(declaration.node.clone());
+        let range = node.text_trimmed_range();
+        let removal = line_removal_range(context.source, range.start().into()..range.end().into());
+        let mut diagnostic = new_diagnostic(
+            context.range(declaration.name_range.start, declaration.value_range.end),
+            DiagnosticSeverity::WARNING,
+            "duplicate-property",
+            format!(
+                "`{}` is overridden by `{}: {}` later in this block.",
+                declaration.name, later.name, later.value
+            ),
+            vec![QuickFix {
+                title: "Remove the overridden declaration".to_string(),
+                edits: vec![TextEdit {
+                    range: context.range(removal.start, removal.end),
+                    new_text: String::new(),
+                }],
+            }],
+        );
+        diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+        diagnostics.push(diagnostic);
+    }
+}
+
+/// Returns whether a declaration looks like the fallback of a later one for older browsers:
+/// the value of one of them has a vendor-prefixed keyword or function, like
+/// `-webkit-fill-available`, or the later one uses a function or a recent unit the earlier
+/// one doesn't.
+fn is_fallback(declaration: &Declaration, later: &Declaration) -> bool {
+    let is_prefixed = |declaration: &Declaration| {
+        tokenize(&declaration.value, 0).iter().any(|token| {
+            let name = match token.kind {
+                TokenKind::Ident => token.text,
+                TokenKind::Function { name } => name,
+                _ => return false,
+            };
+            VENDOR_PREFIXES
+                .iter()
+                .any(|prefix| starts_with_ignore_case(name, prefix))
+        })
+    };
+    if is_prefixed(declaration) || is_prefixed(later) {
+        return true;
+    }
+    let features = fallback_features(&declaration.node);
+    fallback_features(&later.node)
+        .iter()
+        .any(|feature| !features.contains(feature))
+}
+
+/// Lists the functions and the units outside of `BASELINE_UNITS` used by a declaration,
+/// like `calc(` and `dvh` for `height: calc(100dvh - 1rem)`.
+fn fallback_features(declaration: &CssSyntaxNode) -> Vec<String> {
+    declaration
+        .descendants()
+        .filter_map(|node| match node.kind() {
+            CssSyntaxKind::CSS_FUNCTION => node
+                .first_token()
+                .map(|name| format!("{}(", fold_case(name.text_trimmed()))),
+            CssSyntaxKind::CSS_REGULAR_DIMENSION => node
+                .last_token()
+                .map(|unit| fold_case(unit.text_trimmed()).into_owned())
+                .filter(|unit| !BASELINE_UNITS.contains(&unit.as_str())),
+            _ => None,
+        })
+        .collect()
+}
+
+fn important(declaration: &CssSyntaxNode) -> bool {
+    declaration
+        .children()
+        .any(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_IMPORTANT)
+}
+
+fn check_empty_rules(
+    context: &RuleContext,
+    root: &CssSyntaxNode,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for rule in root.descendants().filter(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+        )
+    }) {
+        let Some(block) = rule
+            .children()
+            .find(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_OR_RULE_BLOCK)
+        else {
+            continue;
+        };
+        let is_empty = block
+            .children()
+            .all(|node| node.text_trimmed_range().is_empty());
+        // Comments and invalid statements skipped by the parser are trivia, kept out of the
+        // children
+        let has_trivia_content = block.descendants_tokens(Direction::Next).any(|token| {
+            token
+                .leading_trivia()
+                .pieces()
+                .chain(token.trailing_trivia().pieces())
+                .any(|piece| piece.is_comments() || piece.is_skipped())
+        });
+        if !is_empty || has_trivia_content {
+            continue;
+        }
+        let range = rule.text_trimmed_range();
+        let removal = line_removal_range(context.source, range.start().into()..range.end().into());
+        let mut diagnostic = new_diagnostic(
+            context.range(range.start().into(), range.end().into()),
+            DiagnosticSeverity::WARNING,
+            "empty-rule",
+            "Empty rules have no effect.".to_string(),
+            vec![QuickFix {
+                title: "Remove the empty rule".to_string(),
+                edits: vec![TextEdit {
+                    range: context.range(removal.start, removal.end),
+                    new_text: String::new(),
+                }],
+            }],
+        );
+        diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+        diagnostics.push(diagnostic);
+    }
+}
+
+fn check_zero_units(
+    context: &RuleContext,
+    root: &CssSyntaxNode,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for declaration in find_declarations(root) {
+        if declaration.name.starts_with("--")
+            || contains_ignore_case(UNIT_REQUIRED_PROPERTIES, &declaration.name)
+        {
+            continue;
+        }
+        for dimension in declaration
+            .node
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_REGULAR_DIMENSION)
+            // Units are required in math functions, like `calc(0px + 1em)`
+            .filter(|node| {
+                !node
+                    .ancestors()
+                    .take_while(|ancestor| ancestor != &declaration.node)
+                    .any(|ancestor| ancestor.kind() == CssSyntaxKind::CSS_FUNCTION)
+            })
+        {
+            let Some(number) = dimension.first_token() else {
+                continue;
+            };
+            let Some(unit) = dimension.last_token() else {
+                continue;
+            };
+            let is_zero = number
+                .text_trimmed()
+                .trim_start_matches(['+', '-'])
+                .parse::<f64>()
+                .is_ok_and(|number| number == 0.0);
+            if !is_zero || !LENGTH_UNITS.contains(&fold_case(unit.text_trimmed()).as_ref()) {
+                continue;
+            }
+            let range = dimension.text_trimmed_range();
+            let unit_range = unit.text_trimmed_range();
+            let mut diagnostic = new_diagnostic(
+                context.range(range.start().into(), range.end().into()),
+                DiagnosticSeverity::HINT,
+                "zero-units",
+                format!("The unit of `{}` is unnecessary.", dimension.text_trimmed()),
+                vec![QuickFix {
+                    title: "Remove the unit".to_string(),
+                    edits: vec![TextEdit {
+                        range: context.range(unit_range.start().into(), unit_range.end().into()),
+                        new_text: String::new(),
+                    }],
+                }],
+            );
+            diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+/// Extends the range of code to remove to its whole line, line break included, when
+/// nothing else is on the line, so no blank line is left behind.
+fn line_removal_range(source: &str, range: Range<usize>) -> Range<usize> {
+    let before = &source[..range.start];
+    let line_start = before.trim_end_matches([' ', '\t']).len();
+    let after = &source[range.end..];
+    let line_end = range.end + (after.len() - after.trim_start_matches([' ', '\t']).len());
+    let starts_line = line_start == 0 || source[..line_start].ends_with('\n');
+    if !starts_line {
+        return range;
+    }
+    if source[line_end..].starts_with("\r\n") {
+        line_start..line_end + 2
+    } else if source[line_end..].starts_with('\n') || line_end == source.len() {
+        line_start..(line_end + 1).min(source.len())
+    } else {
+        range
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_declarations(&RuleContext::new(&uri, code))
+    }
+
+    fn rule_code(diagnostic: &Diagnostic) -> &str {
+        match &diagnostic.code {
+            Some(lsp_types::NumberOrString::String(code)) => code,
+            _ => "",
+        }
+    }
+
+    fn apply_fix(code: &str, diagnostic: &Diagnostic) -> String {
+        let edit = &crate::rules::quick_fixes(diagnostic)[0].edits[0];
+        let line_index = crate::line_index::LineIndex::new(code);
+        let start = line_index.offset(code, edit.range.start);
+        let end = line_index.offset(code, edit.range.end);
+        format!("{}{}{}", &code[..start], edit.new_text, &code[end..])
+    }
+
+    #[test]
+    fn test_duplicate_declarations() {
+        let code = "a {\n  color: red;\n  COLOR: red;\n  margin: 0 !important;\n  margin: 0;\n}\nb { color: red; }\n";
+        let diagnostics = check(code);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(rule_code(&diagnostics[0]), "duplicate-declaration");
+        assert_eq!(
+            apply_fix(code, &diagnostics[0]),
+            "a {\n  COLOR: red;\n  margin: 0 !important;\n  margin: 0;\n}\nb { color: red; }\n"
+        );
+    }
+
+    #[test]
+    fn test_overridden_declarations() {
+        let code = "a {\n  color: red;\n  margin: 0 !important;\n  margin: 1px;\n  width: -webkit-fill-available;\n  width: fill-available;\n  height: 100vh;\n  height: 100dvh;\n  top: 0;\n  top: calc(1px + 1em);\n  --x: 1;\n  --x: 2;\n  COLOR: blue;\n}\n";
+        let diagnostics = check(code);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(rule_code(&diagnostics[0]), "duplicate-property");
+        assert_eq!(
+            diagnostics[0].message,
+            "`color` is overridden by `COLOR: blue` later in this block."
+        );
+        assert!(apply_fix(code, &diagnostics[0]).starts_with("a {\n  margin: 0 !important;\n"));
+    }
+
+    #[test]
+    fn test_overridden_declarations_prefixed_values() {
+        let code = "a { background: url(my-ms-icon.png); background: url(icon.png); width: -Moz-Available; width: stretch; b { width: 0; } }";
+        let diagnostics = check(code);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(
+            diagnostics[0].message,
+            "`background` is overridden by `background: url(icon.png)` later in this block."
+        );
+    }
+
+    #[test]
+    fn test_overridden_declarations_options() {
+        let code = "a { color: red; height: 100vh; height: 100dvh; display: block; display: flex; color: blue; }";
+        let uri = "file:///test.css".parse().unwrap();
+        let options = crate::rules::RuleOptions {
+            duplicate_property: DuplicatePropertyOptions {
+                allow_fallbacks: false,
+                ignore: vec!["Display".to_string()],
+            },
+            ..Default::default()
+        };
+        let diagnostics = check_declarations(&RuleContext::with_options(&uri, code, options));
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`color` is overridden by `color: blue` later in this block.",
+                "`height` is overridden by `height: 100dvh` later in this block.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_rules() {
+        let code = "a {}\nb { /* todo */ }\n.c { .d { } }\n";
+        let diagnostics = check(code);
+        let codes: Vec<_> = diagnostics.iter().map(rule_code).collect();
+        assert_eq!(codes, vec!["empty-rule", "empty-rule"]);
+        assert_eq!(
+            apply_fix(code, &diagnostics[0]),
+            "b { /* todo */ }\n.c { .d { } }\n"
+        );
+        assert_eq!(
+            apply_fix(code, &diagnostics[1]),
+            "a {}\nb { /* todo */ }\n.c {  }\n"
+        );
+    }
+
+    #[test]
+    fn test_zero_units() {
+        let code = "a { margin: 0px -0.0EM 1px 0%; width: calc(0px + 1em); flex: 1 1 0px; --x: 0px; transition: 0s; }";
+        let diagnostics = check(code);
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+        assert_eq!(rule_code(&diagnostics[0]), "zero-units");
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+        assert_eq!(
+            apply_fix(code, &diagnostics[1]),
+            code.replacen("-0.0EM", "-0.0", 1)
+        );
+    }
+}
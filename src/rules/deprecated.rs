@@ -0,0 +1,162 @@
+use crate::data::deprecated::{deprecated_value, property_replacement};
+use crate::data::properties::PropertyStatus;
+use crate::parser::{find_declarations, split_components};
+use crate::rules::properties::is_style_property;
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, TextEdit};
+use std::ops::Range;
+
+/// Checks for obsolete or non-standard properties and keywords, like `word-wrap` or
+/// `word-break: break-word`. Properties are deprecated by their status in the `CssData` of
+/// the context.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, tagged as deprecated and suggesting a replacement, with a fix
+///   when the replacement means the same thing.
+pub fn check_deprecated(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for declaration in find_declarations(&context.parse.syntax())
+        .into_iter()
+        .filter(is_style_property)
+    {
+        let status = context
+            .data
+            .property(&declaration.name)
+            .map(|property| property.status);
+        if let Some(status @ (PropertyStatus::Obsolete | PropertyStatus::Nonstandard)) = status {
+            diagnostics.push(deprecated(
+                context,
+                status,
+                property_replacement(&declaration.name),
+                &declaration.name,
+                declaration.name_range.clone(),
+            ));
+        }
+        for (component, range) in
+            split_components(&declaration.value, declaration.value_range.start)
+        {
+            if let Some(deprecation) = deprecated_value(&declaration.name, component) {
+                diagnostics.push(deprecated(
+                    context,
+                    deprecation.status,
+                    Some((deprecation.replacement, deprecation.replaceable)),
+                    &format!("{}: {component}", declaration.name),
+                    range,
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+fn deprecated(
+    context: &RuleContext,
+    status: PropertyStatus,
+    replacement: Option<(&str, bool)>,
+    text: &str,
+    range: Range<usize>,
+) -> Diagnostic {
+    let status = match status {
+        PropertyStatus::Nonstandard => "non-standard",
+        _ => "deprecated",
+    };
+    let message = match replacement {
+        Some((replacement, _)) => format!("`{text}` is {status}, use `{replacement}` instead."),
+        None => format!("`{text}` is {status}."),
+    };
+    let fixes = match replacement {
+        Some((replacement, true)) => vec![QuickFix {
+            title: format!("Replace with `{replacement}`"),
+            edits: vec![TextEdit {
+                range: context.range(range.start, range.end),
+                new_text: replacement.to_string(),
+            }],
+        }],
+        _ => Vec::new(),
+    };
+    let mut diagnostic = new_diagnostic(
+        context.range(range.start, range.end),
+        DiagnosticSeverity::WARNING,
+        "deprecated",
+        message,
+        fixes,
+    );
+    diagnostic.tags = Some(vec![DiagnosticTag::DEPRECATED]);
+    diagnostic
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::properties::CssDataBuilder;
+    use crate::rules::quick_fixes;
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_deprecated(&RuleContext::new(&uri, code))
+    }
+
+    #[test]
+    fn test_deprecated() {
+        let code = "a {\n  clip: rect(0 0 0 0);\n  Word-Wrap: break-word;\n  word-break: break-word;\n  display: -moz-box;\n  overflow: hidden overlay;\n  ime-mode: auto;\n  --clip: 0;\n}\n";
+        let diagnostics = check(code);
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`clip` is deprecated, use `clip-path` instead.",
+                "`Word-Wrap` is deprecated, use `overflow-wrap` instead.",
+                "`word-break: break-word` is deprecated, use `overflow-wrap: anywhere` instead.",
+                "`display: -moz-box` is non-standard, use `flex` instead.",
+                "`overflow: overlay` is deprecated, use `auto` instead.",
+                "`ime-mode` is deprecated.",
+            ]
+        );
+        assert!(diagnostics
+            .iter()
+            .all(|diagnostic| diagnostic.tags == Some(vec![DiagnosticTag::DEPRECATED])));
+        let fixes: Vec<usize> = diagnostics
+            .iter()
+            .map(|diagnostic| quick_fixes(diagnostic).len())
+            .collect();
+        assert_eq!(fixes, vec![0, 1, 0, 1, 1, 0]);
+        let edit = &quick_fixes(&diagnostics[4])[0].edits[0];
+        assert_eq!(edit.range.start.line, 5);
+        assert_eq!(edit.range.start.character, 19);
+        assert_eq!(edit.new_text, "auto");
+    }
+
+    #[test]
+    fn test_deprecated_custom_data() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "a { zoom: 2; word-wrap: normal; display: -webkit-box }";
+        let data = CssDataBuilder::empty()
+            .property("zoom")
+            .status(PropertyStatus::Nonstandard)
+            .finish()
+            .build();
+        let diagnostics = check_deprecated(&RuleContext {
+            data: &data,
+            ..RuleContext::new(&uri, code)
+        });
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`zoom` is non-standard.",
+                "`display: -webkit-box` is non-standard, use `flex` instead.",
+            ]
+        );
+    }
+}
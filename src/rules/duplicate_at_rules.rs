@@ -0,0 +1,222 @@
+use crate::case_folding::fold_case;
+use crate::formatter::{normalize_value, ValueNormalization};
+use crate::parser::find_declarations;
+use crate::rules::{new_diagnostic, RuleContext};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Range};
+use std::collections::HashMap;
+
+/// An at-rule that is redundant when repeated in the same scope.
+struct Declared {
+    /// The scope and identity of the at-rule, equal for duplicates.
+    key: String,
+    /// What is repeated, like `@keyframes spin`.
+    label: String,
+    range: Range,
+}
+
+/// Checks the document for `@keyframes` with the same name, `@font-face` rules with the
+/// same descriptors, and `@layer` statements with the same names, declared several times in
+/// the same scope: the same conditional at-rules and style rules.
+///
+/// Repeated keyframes and font faces override each other, and repeated layer statements
+/// have no effect, so these are likely leftovers of copies.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each repetition, related to the other declarations.
+pub fn check_duplicate_at_rules(context: &RuleContext) -> Vec<Diagnostic> {
+    let declared: Vec<Declared> = context
+        .parse
+        .syntax()
+        .descendants()
+        .filter_map(|node| match node.kind() {
+            CssSyntaxKind::CSS_KEYFRAMES_AT_RULE => keyframes(context, &node),
+            CssSyntaxKind::CSS_FONT_FACE_AT_RULE => font_face(context, &node),
+            CssSyntaxKind::CSS_LAYER_AT_RULE => layer_statement(context, &node),
+            _ => None,
+        })
+        .collect();
+
+    let mut by_key: HashMap<&str, Vec<&Declared>> = HashMap::new();
+    for at_rule in &declared {
+        by_key.entry(&at_rule.key).or_default().push(at_rule);
+    }
+
+    let mut diagnostics = Vec::new();
+    for at_rule in &declared {
+        let same = &by_key[at_rule.key.as_str()];
+        if std::ptr::eq(same[0], at_rule) {
+            continue;
+        }
+        let related_information: Vec<_> = same
+            .iter()
+            .filter(|other| !std::ptr::eq(**other, at_rule))
+            .map(|other| DiagnosticRelatedInformation {
+                location: Location::new(context.uri.clone(), other.range),
+                message: format!("`{}` is also declared here", other.label),
+            })
+            .collect();
+        let mut diagnostic = new_diagnostic(
+            at_rule.range,
+            DiagnosticSeverity::WARNING,
+            "duplicate-at-rule",
+            format!(
+                "`{}` is declared {} times in the same scope.",
+                at_rule.label,
+                related_information.len() + 1
+            ),
+            Vec::new(),
+        );
+        diagnostic.related_information = Some(related_information);
+        diagnostics.push(diagnostic);
+    }
+    diagnostics
+}
+
+fn keyframes(context: &RuleContext, at_rule: &CssSyntaxNode) -> Option<Declared> {
+    let keyword = at_rule.first_token()?;
+    let name = at_rule.children().find(|node| {
+        matches!(
+            node.kind(),
+            CssSyntaxKind::CSS_CUSTOM_IDENTIFIER | CssSyntaxKind::CSS_STRING
+        )
+    })?;
+    let name_text = name.text_trimmed().to_string();
+    let name_text = name_text.trim_matches(['"', '\'']);
+    let label = format!("@{} {name_text}", fold_case(keyword.text_trimmed()));
+    let range = name.text_trimmed_range();
+    Some(Declared {
+        key: format!("{}{label}", scope(at_rule)),
+        label,
+        range: context.range(range.start().into(), range.end().into()),
+    })
+}
+
+fn font_face(context: &RuleContext, at_rule: &CssSyntaxNode) -> Option<Declared> {
+    let mut descriptors: Vec<String> = find_declarations(at_rule)
+        .iter()
+        .map(|declaration| {
+            let name = fold_case(&declaration.name);
+            let value = if name == "font-family" {
+                // Family names are case-insensitive, quoted or not
+                fold_case(declaration.value.trim_matches(['"', '\''])).into_owned()
+            } else {
+                normalize_value(&name, &declaration.value, &ValueNormalization::default())
+            };
+            format!("{name}: {value}")
+        })
+        .collect();
+    descriptors.sort();
+    descriptors.dedup();
+    let keyword = at_rule.first_token()?.text_trimmed_range();
+    let label = descriptors
+        .iter()
+        .find(|descriptor| descriptor.starts_with("font-family:"))
+        .map_or("@font-face".to_string(), |family| {
+            format!("@font-face {{ {family} }}")
+        });
+    Some(Declared {
+        key: format!(
+            "{}@font-face {{ {} }}",
+            scope(at_rule),
+            descriptors.join("; ")
+        ),
+        label,
+        // From the `@`, so the range covers `@font-face`
+        range: context.range(usize::from(keyword.start()) - 1, keyword.end().into()),
+    })
+}
+
+fn layer_statement(context: &RuleContext, at_rule: &CssSyntaxNode) -> Option<Declared> {
+    let names = at_rule
+        .children()
+        .find(|node| node.kind() == CssSyntaxKind::CSS_LAYER_REFERENCE)?
+        .first_child()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_LAYER_REFERENCE_LIST)?;
+    let list: Vec<String> = names
+        .children()
+        .map(|name| name.text_trimmed().to_string().split_whitespace().collect())
+        .collect();
+    let label = format!("@layer {}", list.join(", "));
+    let range = names.text_trimmed_range();
+    Some(Declared {
+        key: format!("{}{label}", scope(at_rule)),
+        label,
+        range: context.range(range.start().into(), range.end().into()),
+    })
+}
+
+/// Describes the rules an at-rule is nested in, like `@media print { .card {`, with
+/// collapsed whitespace so equivalent scopes are equal.
+fn scope(at_rule: &CssSyntaxNode) -> String {
+    let mut scope: Vec<String> = at_rule
+        .ancestors()
+        // The at-rule itself and its `CSS_AT_RULE` wrapper
+        .skip(2)
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_AT_RULE
+                    | CssSyntaxKind::CSS_QUALIFIED_RULE
+                    | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+            )
+        })
+        .filter_map(|node| {
+            let text = node.text_trimmed().to_string();
+            let (prelude, _) = text.split_once('{')?;
+            Some(prelude.split_whitespace().collect::<Vec<_>>().join(" ") + " {")
+        })
+        .collect();
+    scope.reverse();
+    scope.join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_duplicate_at_rules() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "@keyframes spin {}\n@keyframes \"spin\" {}\n@-webkit-keyframes spin {}\n@media print { @keyframes spin {} }\n@font-face { font-family: A; src: url(a.woff2); }\n@font-face { src: url(a.woff2); font-family: \"A\"; }\n@font-face { font-family: A; font-weight: BOLD; src: url(a.woff2); }\n@layer reset,  base;\n@layer reset, base;\n@layer base, reset;\n@layer reset, base { }";
+        let diagnostics = check_duplicate_at_rules(&RuleContext::new(&uri, code));
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.range.start.line,
+                    diagnostic.range.start.character,
+                    diagnostic.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    1,
+                    11,
+                    "`@keyframes spin` is declared 2 times in the same scope."
+                ),
+                (
+                    5,
+                    0,
+                    "`@font-face { font-family: a }` is declared 2 times in the same scope."
+                ),
+                (
+                    8,
+                    7,
+                    "`@layer reset, base` is declared 2 times in the same scope."
+                ),
+            ]
+        );
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range.start.line, 0);
+    }
+}
@@ -0,0 +1,743 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case, fold_case};
+use crate::data::at_rules::{descriptor_syntax, BUILTIN_DESCRIPTORS};
+use crate::dialect::Dialect;
+use crate::parser::tokenizer::{tokenize, TokenKind};
+use crate::parser::{
+    find_declarations, find_function_calls, split_list, Declaration, FunctionCall,
+};
+use crate::rules::properties::{edit_distance, MAX_SUGGESTION_DISTANCE};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use crate::value_syntax::{parse_components, validate_descriptor_value};
+use biome_css_syntax::CssSyntaxKind;
+use biome_rowan::SyntaxKind;
+use lsp_types::{
+    Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, DiagnosticTag, Location, Range,
+    TextEdit,
+};
+
+/// Font formats accepted by `format()`.
+const FONT_FORMATS: &[&str] = &[
+    "collection",
+    "embedded-opentype",
+    "opentype",
+    "svg",
+    "truetype",
+    "woff",
+    "woff2",
+    // Legacy formats, superseded by `tech(variations)`
+    "opentype-variations",
+    "truetype-variations",
+    "woff-variations",
+    "woff2-variations",
+];
+
+/// Font technologies accepted by `tech()`.
+const FONT_TECHNOLOGIES: &[&str] = &[
+    "features-opentype",
+    "features-aat",
+    "features-graphite",
+    "color-colrv0",
+    "color-colrv1",
+    "color-svg",
+    "color-sbix",
+    "color-cbdt",
+    "variations",
+    "palettes",
+    "incremental",
+];
+
+/// A range of code points described by a `unicode-range` descriptor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnicodeRange {
+    pub start: u32,
+    pub end: u32,
+}
+
+impl UnicodeRange {
+    /// Returns whether two ranges share at least one code point.
+    pub fn overlaps(&self, other: &UnicodeRange) -> bool {
+        self.start <= other.end && other.start <= self.end
+    }
+}
+
+/// Parses a single `unicode-range` item, like `U+0025-00FF`, `U+4??` or `u+0131`.
+///
+/// # Arguments
+///
+/// * `text` - The text of the item, without surrounding whitespace or commas.
+///
+/// # Returns
+///
+/// * The `UnicodeRange` described by the item, or `None` if it is malformed or out of range.
+pub fn parse_unicode_range(text: &str) -> Option<UnicodeRange> {
+    let rest = text
+        .strip_prefix("U+")
+        .or_else(|| text.strip_prefix("u+"))?;
+    let is_hex =
+        |part: &str| (1..=6).contains(&part.len()) && part.chars().all(|c| c.is_ascii_hexdigit());
+
+    let range = if let Some((start, end)) = rest.split_once('-') {
+        if !is_hex(start) || !is_hex(end) {
+            return None;
+        }
+        UnicodeRange {
+            start: u32::from_str_radix(start, 16).ok()?,
+            end: u32::from_str_radix(end, 16).ok()?,
+        }
+    } else {
+        // Wildcards are only allowed as trailing digits, like `U+4??`
+        let digits = rest.trim_end_matches('?');
+        let wildcards = rest.len() - digits.len();
+        if rest.len() > 6 || (!digits.is_empty() && !is_hex(digits)) || rest.is_empty() {
+            return None;
+        }
+        let base = if digits.is_empty() {
+            0
+        } else {
+            u32::from_str_radix(digits, 16).ok()?
+        };
+        let shift = 4 * wildcards as u32;
+        UnicodeRange {
+            start: base << shift,
+            end: (base << shift) | ((1 << shift) - 1),
+        }
+    };
+
+    (range.start <= range.end && range.end <= 0x10FFFF).then_some(range)
+}
+
+/// A parsed `@font-face` rule.
+struct FontFace {
+    /// Family, weight and style, which together decide whether two faces compete.
+    key: (String, String, String),
+    family: String,
+    unicode_ranges: Vec<(UnicodeRange, Range)>,
+}
+
+/// Checks the `@font-face` rules of the document.
+///
+/// Reports malformed `unicode-range` items, and warns when faces of the same family,
+/// weight and style declare overlapping ranges, as only one of them will ever be used for
+/// the shared code points.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` for every invalid or overlapping range.
+pub fn check_font_faces(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut faces: Vec<FontFace> = Vec::new();
+
+    for node in context
+        .parse
+        .syntax()
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_FONT_FACE_AT_RULE)
+    {
+        let declarations = find_declarations(&node);
+        let descriptor = |name: &str| -> String {
+            declarations
+                .iter()
+                .rev()
+                .find(|declaration| eq_ignore_case(&declaration.name, name))
+                .map(|declaration| declaration.value.trim_matches(['"', '\'']).to_lowercase())
+                .unwrap_or_else(|| "normal".to_string())
+        };
+        let family = descriptor("font-family");
+
+        let mut unicode_ranges = Vec::new();
+        for declaration in declarations
+            .iter()
+            .filter(|declaration| eq_ignore_case(&declaration.name, "unicode-range"))
+        {
+            for (item, item_range) in split_list(&declaration.value, declaration.value_range.start)
+            {
+                let range = context.range(item_range.start, item_range.end);
+                match parse_unicode_range(item) {
+                    Some(unicode_range) => unicode_ranges.push((unicode_range, range)),
+                    None => diagnostics.push(new_diagnostic(
+                        range,
+                        DiagnosticSeverity::ERROR,
+                        "invalid-unicode-range",
+                        format!("`{}` is not a valid unicode-range.", item),
+                        vec![],
+                    )),
+                }
+            }
+        }
+
+        for declaration in declarations
+            .iter()
+            .filter(|declaration| eq_ignore_case(&declaration.name, "src"))
+        {
+            diagnostics.extend(check_src(context, declaration));
+        }
+
+        let face = FontFace {
+            key: (
+                family.clone(),
+                descriptor("font-weight"),
+                descriptor("font-style"),
+            ),
+            family,
+            unicode_ranges,
+        };
+
+        for (unicode_range, range) in &face.unicode_ranges {
+            let related_information: Vec<_> = faces
+                .iter()
+                .filter(|other| other.key == face.key)
+                .flat_map(|other| &other.unicode_ranges)
+                .filter(|(other_range, _)| unicode_range.overlaps(other_range))
+                .map(|(_, other_range)| DiagnosticRelatedInformation {
+                    location: Location::new(context.uri.clone(), *other_range),
+                    message: "Overlapping range".to_string(),
+                })
+                .collect();
+            if !related_information.is_empty() {
+                let mut diagnostic = new_diagnostic(
+                    *range,
+                    DiagnosticSeverity::WARNING,
+                    "overlapping-unicode-range",
+                    format!(
+                        "This range overlaps {} range(s) of another @font-face for `{}`.",
+                        related_information.len(),
+                        face.family
+                    ),
+                    vec![],
+                );
+                diagnostic.related_information = Some(related_information);
+                diagnostics.push(diagnostic);
+            }
+        }
+        faces.push(face);
+    }
+
+    diagnostics
+}
+
+/// The descriptors a `@font-face` rule is ignored without.
+const REQUIRED_DESCRIPTORS: &[&str] = &["font-family", "src"];
+
+/// Checks the descriptors of the `@font-face` rules of CSS documents: that they are known,
+/// that `font-family` and `src` are declared, and that values match the grammar of their
+/// descriptor, like `swap` for `font-display`. Browsers ignore invalid descriptors, and the
+/// whole rule without a family or a source, so fonts silently fail to load.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, with a fix replacing each unknown descriptor by the closest
+///   known one, if any is close enough.
+pub fn check_font_face_descriptors(context: &RuleContext) -> Vec<Diagnostic> {
+    if context.dialect != Dialect::Css {
+        return Vec::new();
+    }
+    let mut diagnostics = Vec::new();
+    for node in context
+        .parse
+        .syntax()
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_FONT_FACE_AT_RULE)
+    {
+        let declarations = find_declarations(&node);
+        for declaration in &declarations {
+            // Vendor hacks and custom properties are left to browsers
+            if declaration.name.starts_with('-') {
+                continue;
+            }
+            if descriptor_syntax("@font-face", &declaration.name).is_none() {
+                let range = context.range(declaration.name_range.start, declaration.name_range.end);
+                let fixes = closest_descriptor(&declaration.name)
+                    .map(|descriptor| QuickFix {
+                        title: format!("Replace with `{descriptor}`"),
+                        edits: vec![TextEdit {
+                            range,
+                            new_text: descriptor.to_string(),
+                        }],
+                    })
+                    .into_iter()
+                    .collect();
+                diagnostics.push(new_diagnostic(
+                    range,
+                    DiagnosticSeverity::WARNING,
+                    "unknown-descriptor",
+                    format!("Unknown `@font-face` descriptor `{}`.", declaration.name),
+                    fixes,
+                ));
+                continue;
+            }
+            // Values the parser doesn't understand are reported as syntax errors
+            if declaration.value.is_empty()
+                || declaration
+                    .node
+                    .descendants()
+                    .any(|node| node.kind().is_bogus())
+            {
+                continue;
+            }
+            let components = parse_components(&declaration.value, declaration.value_range.start);
+            if let Some(mismatch) =
+                validate_descriptor_value("@font-face", &declaration.name, &components)
+            {
+                diagnostics.push(new_diagnostic(
+                    context.range(mismatch.range.start, mismatch.range.end),
+                    DiagnosticSeverity::ERROR,
+                    "invalid-value",
+                    format!(
+                        "Invalid `{}` value: {}.",
+                        declaration.name, mismatch.message
+                    ),
+                    Vec::new(),
+                ));
+            }
+        }
+
+        let Some(keyword) = node.first_token() else {
+            continue;
+        };
+        let keyword = keyword.text_trimmed_range();
+        for required in REQUIRED_DESCRIPTORS {
+            if declarations
+                .iter()
+                .any(|declaration| eq_ignore_case(&declaration.name, required))
+            {
+                continue;
+            }
+            diagnostics.push(new_diagnostic(
+                // From the `@`, so the range covers `@font-face`
+                context.range(usize::from(keyword.start()) - 1, keyword.end().into()),
+                DiagnosticSeverity::ERROR,
+                "missing-descriptor",
+                format!("`@font-face` requires a `{required}` descriptor, or it is ignored."),
+                Vec::new(),
+            ));
+        }
+    }
+    diagnostics
+}
+
+/// Returns the `@font-face` descriptor closest to a misspelled name, if any is close enough.
+fn closest_descriptor(name: &str) -> Option<&'static str> {
+    let name = fold_case(name);
+    BUILTIN_DESCRIPTORS
+        .iter()
+        .filter(|(at_rule, ..)| *at_rule == "@font-face")
+        .map(|(_, descriptor, ..)| (edit_distance(&name, descriptor), *descriptor))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, descriptor)| descriptor)
+}
+
+/// Splits a `src` item into its function calls, or `None` if it contains anything else.
+fn parse_source_functions(item: &str, item_start: usize) -> Option<Vec<FunctionCall<'_>>> {
+    let is_blank = |text: &str| {
+        tokenize(text, 0)
+            .iter()
+            .all(|token| matches!(token.kind, TokenKind::Whitespace | TokenKind::Comment))
+    };
+    let mut functions = Vec::new();
+    let mut end = 0;
+    for call in find_function_calls(item, item_start) {
+        let start = call.range.start - item_start;
+        // The calls nested in the arguments of another
+        if start < end {
+            continue;
+        }
+        if !is_blank(&item[end..start]) {
+            return None;
+        }
+        end = call.range.end - item_start;
+        functions.push(call);
+    }
+    (!functions.is_empty() && is_blank(&item[end..])).then_some(functions)
+}
+
+/// A valid source of a `src` descriptor.
+struct FontSource<'a> {
+    text: &'a str,
+    range: std::ops::Range<usize>,
+    /// The font name for `local()`, or the URL for `url()`, used to find duplicates.
+    key: String,
+    is_local: bool,
+    is_woff2: bool,
+}
+
+/// Checks the grammar of a `src` descriptor, the keywords of its `format()` and `tech()`
+/// hints, and the order of its sources.
+fn check_src(context: &RuleContext, declaration: &Declaration) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let mut sources = Vec::new();
+    let items = split_list(&declaration.value, declaration.value_range.start);
+    let mut all_valid = true;
+
+    for (item, item_range) in &items {
+        let invalid = |message: &str| {
+            new_diagnostic(
+                context.range(item_range.start, item_range.end),
+                DiagnosticSeverity::ERROR,
+                "invalid-font-source",
+                message.to_string(),
+                vec![],
+            )
+        };
+        let Some(functions) = parse_source_functions(item, item_range.start) else {
+            diagnostics.push(invalid("Expected a `url()` or `local()` font source."));
+            all_valid = false;
+            continue;
+        };
+
+        let first = &functions[0];
+        let argument = first.arguments.trim().trim_matches(['"', '\'']);
+        if eq_ignore_case(first.name, "local") {
+            if functions.len() > 1 {
+                diagnostics.push(invalid(
+                    "`local()` cannot have `format()` or `tech()` hints.",
+                ));
+                all_valid = false;
+                continue;
+            }
+            sources.push(FontSource {
+                text: item,
+                range: item_range.clone(),
+                key: format!("local:{}", argument.to_lowercase()),
+                is_local: true,
+                is_woff2: false,
+            });
+        } else if eq_ignore_case(first.name, "url") {
+            let mut formats = Vec::new();
+            let mut hints = functions[1..].iter().peekable();
+            if let Some(format) = hints.next_if(|f| eq_ignore_case(f.name, "format")) {
+                formats = check_hint_keywords(
+                    context,
+                    format,
+                    FONT_FORMATS,
+                    "unknown-font-format",
+                    "font format",
+                    &mut diagnostics,
+                );
+            }
+            if let Some(tech) = hints.next_if(|f| eq_ignore_case(f.name, "tech")) {
+                check_hint_keywords(
+                    context,
+                    tech,
+                    FONT_TECHNOLOGIES,
+                    "unknown-font-tech",
+                    "font technology",
+                    &mut diagnostics,
+                );
+            }
+            if let Some(unexpected) = hints.next() {
+                diagnostics.push(new_diagnostic(
+                    context.range(unexpected.range.start, unexpected.range.end),
+                    DiagnosticSeverity::ERROR,
+                    "invalid-font-source",
+                    format!(
+                        "Unexpected `{}()`, a `url()` can only be followed by `format()` then `tech()`.",
+                        unexpected.name
+                    ),
+                    vec![],
+                ));
+                all_valid = false;
+                continue;
+            }
+            let is_woff2 = if formats.is_empty() {
+                argument.to_lowercase().ends_with(".woff2")
+            } else {
+                formats.iter().any(|format| format.starts_with("woff2"))
+            };
+            sources.push(FontSource {
+                text: item,
+                range: item_range.clone(),
+                key: format!("url:{}", argument),
+                is_local: false,
+                is_woff2,
+            });
+        } else {
+            diagnostics.push(invalid("Expected a `url()` or `local()` font source."));
+            all_valid = false;
+        }
+    }
+
+    // A source identical to an earlier one is never used
+    let mut unique_sources: Vec<&FontSource> = Vec::new();
+    for (idx, source) in sources.iter().enumerate() {
+        if sources[..idx].iter().any(|other| other.key == source.key) {
+            let removal_start = items
+                .iter()
+                .rev()
+                .map(|(_, range)| range.end)
+                .find(|&end| end < source.range.start)
+                .unwrap_or(source.range.start);
+            let mut diagnostic = new_diagnostic(
+                context.range(source.range.start, source.range.end),
+                DiagnosticSeverity::WARNING,
+                "unreachable-font-source",
+                "This source duplicates an earlier one and will never be used.".to_string(),
+                vec![QuickFix {
+                    title: "Remove the duplicate source".to_string(),
+                    edits: vec![TextEdit {
+                        range: context.range(removal_start, source.range.end),
+                        new_text: String::new(),
+                    }],
+                }],
+            );
+            diagnostic.tags = Some(vec![DiagnosticTag::UNNECESSARY]);
+            diagnostics.push(diagnostic);
+        } else {
+            unique_sources.push(source);
+        }
+    }
+
+    // Installed fonts are free, and woff2 is the smallest format every browser supports
+    let mut recommended = unique_sources.clone();
+    recommended.sort_by_key(|source| (!source.is_local, !source.is_woff2));
+    let in_order = recommended
+        .iter()
+        .zip(&unique_sources)
+        .all(|(a, b)| std::ptr::eq(*a, *b));
+    if all_valid && !in_order && unique_sources.len() == sources.len() {
+        let local_after_url = unique_sources
+            .windows(2)
+            .any(|pair| !pair[0].is_local && pair[1].is_local);
+        let message = if local_after_url {
+            "`local()` sources should come first, or the font will be downloaded even when installed."
+        } else {
+            "List woff2 sources first, so browsers skip the heavier formats."
+        };
+        diagnostics.push(new_diagnostic(
+            context.range(declaration.value_range.start, declaration.value_range.end),
+            DiagnosticSeverity::INFORMATION,
+            "font-source-order",
+            message.to_string(),
+            vec![QuickFix {
+                title: "Reorder sources".to_string(),
+                edits: vec![TextEdit {
+                    range: context
+                        .range(declaration.value_range.start, declaration.value_range.end),
+                    new_text: recommended
+                        .iter()
+                        .map(|source| source.text)
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                }],
+            }],
+        ));
+    }
+
+    diagnostics
+}
+
+/// Checks the keywords of a `format()` or `tech()` hint, returning them lowercased.
+fn check_hint_keywords(
+    context: &RuleContext,
+    function: &FunctionCall,
+    known: &[&str],
+    code: &str,
+    description: &str,
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Vec<String> {
+    split_list(function.arguments, function.arguments_start)
+        .into_iter()
+        .map(|(keyword, range)| {
+            let keyword = keyword.trim_matches(['"', '\'']);
+            if !contains_ignore_case(known, keyword) {
+                diagnostics.push(new_diagnostic(
+                    context.range(range.start, range.end),
+                    DiagnosticSeverity::WARNING,
+                    code,
+                    format!("Unknown {} `{}`.", description, keyword),
+                    vec![],
+                ));
+            }
+            fold_case(keyword).into_owned()
+        })
+        .collect()
+}
+
+/// Returns whether a declaration is a `unicode-range` descriptor, whose values the
+/// parser doesn't understand and reports as syntax errors.
+pub(crate) fn is_unicode_range(declaration: &Declaration) -> bool {
+    eq_ignore_case(&declaration.name, "unicode-range")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::{NumberOrString, Position};
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_font_faces(&RuleContext::new(&uri, code))
+    }
+
+    fn rule_code(diagnostic: &Diagnostic) -> &str {
+        match &diagnostic.code {
+            Some(NumberOrString::String(code)) => code,
+            _ => panic!("Expected a string code"),
+        }
+    }
+
+    #[test]
+    fn test_parse_unicode_range() {
+        assert_eq!(
+            parse_unicode_range("U+0025-00FF"),
+            Some(UnicodeRange {
+                start: 0x25,
+                end: 0xFF
+            })
+        );
+        assert_eq!(
+            parse_unicode_range("u+4??"),
+            Some(UnicodeRange {
+                start: 0x400,
+                end: 0x4FF
+            })
+        );
+        assert_eq!(
+            parse_unicode_range("U+131"),
+            Some(UnicodeRange {
+                start: 0x131,
+                end: 0x131
+            })
+        );
+        assert_eq!(parse_unicode_range("U+00FF-0025"), None);
+        assert_eq!(parse_unicode_range("U+110000"), None);
+        assert_eq!(parse_unicode_range("U+?4"), None);
+        assert_eq!(parse_unicode_range("0025"), None);
+    }
+
+    #[test]
+    fn test_check_font_faces_invalid_range() {
+        let code = "@font-face {\n  font-family: A;\n  unicode-range: U+0000-00FF, U+ZZ;\n}";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "invalid-unicode-range");
+        assert_eq!(diagnostics[0].range.start, Position::new(2, 30));
+        assert_eq!(diagnostics[0].range.end, Position::new(2, 34));
+    }
+
+    #[test]
+    fn test_check_font_faces_overlapping_ranges() {
+        let code = "@font-face { font-family: 'A'; unicode-range: U+0000-00FF; }\n\
+                    @font-face { font-family: \"A\"; unicode-range: U+00??, U+0400-04FF; }\n";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "overlapping-unicode-range");
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 46));
+        let related = diagnostics[0].related_information.as_ref().unwrap();
+        assert_eq!(related.len(), 1);
+        assert_eq!(related[0].location.range.start, Position::new(0, 46));
+    }
+
+    #[test]
+    fn test_check_font_faces_distinct_faces() {
+        let code = "@font-face { font-family: A; unicode-range: U+0-FF; }\n\
+                    @font-face { font-family: A; font-weight: bold; unicode-range: U+0-FF; }\n\
+                    @font-face { font-family: B; unicode-range: U+0-FF; }\n";
+        let diagnostics = check(code);
+        assert!(
+            diagnostics.is_empty(),
+            "Faces with different families or weights can share ranges"
+        );
+    }
+
+    #[test]
+    fn test_check_font_faces_valid_src() {
+        let code = "@font-face {\n  font-family: A;\n  src: local(\"A Regular\"), url(a.woff2) format(\"woff2\") tech(variations), url('a.woff') format(woff);\n}";
+        let diagnostics = check(code);
+        assert!(diagnostics.is_empty(), "Valid src should not be reported");
+        let code = "@font-face { src: url(a\\).woff2) /* modern */ format(\"woff2\"); }";
+        assert!(
+            check(code).is_empty(),
+            "Escapes and comments should be skipped"
+        );
+    }
+
+    #[test]
+    fn test_check_font_faces_invalid_src() {
+        let code = "@font-face { src: url(a.woff2) tech(variations) format(woff2), a.woff, url(a.ttf) format(\"ttf\") tech(color-rainbow); }";
+        let diagnostics = check(code);
+        let codes: Vec<_> = diagnostics.iter().map(rule_code).collect();
+
+        assert_eq!(
+            codes,
+            vec![
+                "invalid-font-source",
+                "invalid-font-source",
+                "unknown-font-format",
+                "unknown-font-tech"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_font_faces_src_order() {
+        let code = "@font-face { src: url(a.woff) format(\"woff\"), url(a.woff2) format(\"woff2\"), local(A); }";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "font-source-order");
+        let fixes = crate::rules::quick_fixes(&diagnostics[0]);
+        assert_eq!(
+            fixes[0].edits[0].new_text,
+            "local(A), url(a.woff2) format(\"woff2\"), url(a.woff) format(\"woff\")"
+        );
+    }
+
+    #[test]
+    fn test_check_font_faces_duplicate_src() {
+        let code = "@font-face { src: url(a.woff2), url(\"a.woff2\"); }";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "unreachable-font-source");
+        let fixes = crate::rules::quick_fixes(&diagnostics[0]);
+        assert_eq!(fixes[0].edits[0].range.start, Position::new(0, 30));
+        assert_eq!(fixes[0].edits[0].range.end, Position::new(0, 46));
+    }
+
+    #[test]
+    fn test_check_font_face_descriptors() {
+        let code = "@font-face {\n  font-family: A;\n  src: url(a.woff2);\n  font-display: slow;\n  font-wieght: 100 900;\n  color: red;\n  -webkit-font-smoothing: antialiased;\n  size-adjust: 90%;\n  font-stretch: 75% 125%;\n}\n@font-face { font-family: B; font-style: oblique 10deg 20deg; }\n";
+        let uri = "file:///test.css".parse().unwrap();
+        let diagnostics = check_font_face_descriptors(&RuleContext::new(&uri, code));
+        let messages: Vec<(&str, &str)> = diagnostics
+            .iter()
+            .map(|diagnostic| (rule_code(diagnostic), diagnostic.message.as_str()))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    "invalid-value",
+                    "Invalid `font-display` value: expected `auto`, `block`, `swap`, `fallback` or `optional` but found `slow`."
+                ),
+                ("unknown-descriptor", "Unknown `@font-face` descriptor `font-wieght`."),
+                ("unknown-descriptor", "Unknown `@font-face` descriptor `color`."),
+                (
+                    "missing-descriptor",
+                    "`@font-face` requires a `src` descriptor, or it is ignored."
+                ),
+            ]
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(3, 16));
+        let fixes = crate::rules::quick_fixes(&diagnostics[1]);
+        assert_eq!(fixes[0].edits[0].new_text, "font-weight");
+        assert!(crate::rules::quick_fixes(&diagnostics[2]).is_empty());
+        assert_eq!(diagnostics[3].range.start, Position::new(10, 0));
+        assert_eq!(diagnostics[3].range.end, Position::new(10, 10));
+
+        let uri = "file:///test.scss".parse().unwrap();
+        let code = "@font-face { font-display: $display; }";
+        assert!(check_font_face_descriptors(&RuleContext::new(&uri, code)).is_empty());
+    }
+}
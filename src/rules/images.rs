@@ -0,0 +1,471 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case, fold_case};
+use crate::data::values::CSS_WIDE_KEYWORDS;
+use crate::parser::{find_declarations, find_function_calls, split_components, split_list};
+use crate::parser::{Declaration, FunctionCall};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, TextEdit};
+use std::ops::Range;
+
+/// The units of `<resolution>` values, with how many `dppx` one unit is.
+pub(crate) const RESOLUTION_UNITS: &[(&str, f64, &str)] = &[
+    ("x", 1.0, "Dots per CSS pixel, an alias of `dppx`"),
+    ("dppx", 1.0, "Dots per CSS pixel"),
+    ("dpi", 1.0 / 96.0, "Dots per inch"),
+    ("dpcm", 2.54 / 96.0, "Dots per centimeter"),
+];
+
+/// The keywords of the `cursor` property.
+const CURSOR_KEYWORDS: &[&str] = &[
+    "auto",
+    "default",
+    "none",
+    "context-menu",
+    "help",
+    "pointer",
+    "progress",
+    "wait",
+    "cell",
+    "crosshair",
+    "text",
+    "vertical-text",
+    "alias",
+    "copy",
+    "move",
+    "no-drop",
+    "not-allowed",
+    "grab",
+    "grabbing",
+    "all-scroll",
+    "col-resize",
+    "row-resize",
+    "n-resize",
+    "e-resize",
+    "s-resize",
+    "w-resize",
+    "ne-resize",
+    "nw-resize",
+    "se-resize",
+    "sw-resize",
+    "ew-resize",
+    "ns-resize",
+    "nesw-resize",
+    "nwse-resize",
+    "zoom-in",
+    "zoom-out",
+];
+
+/// Checks the multi-argument image functions and the `cursor` property in the given CSS
+/// source code.
+///
+/// Validates the options of `image-set()` and their resolutions, the images and
+/// percentages of `cross-fade()`, and the `cursor` fallback list with its hotspot
+/// coordinates. Values using `var()` are skipped, as they can't be known statically.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` for every invalid image function or cursor.
+pub fn check_images(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for declaration in find_declarations(&context.parse.syntax()) {
+        if fold_case(&declaration.value).contains("var(") {
+            continue;
+        }
+        for call in find_function_calls(&declaration.value, declaration.value_range.start) {
+            match fold_case(call.name).as_ref() {
+                "image-set" | "-webkit-image-set" => {
+                    check_image_set(context, &call, &mut diagnostics)
+                }
+                "cross-fade" => check_cross_fade(context, &call, &mut diagnostics),
+                "-webkit-cross-fade" => check_legacy_cross_fade(context, &call, &mut diagnostics),
+                _ => {}
+            }
+        }
+        if eq_ignore_case(&declaration.name, "cursor") {
+            check_cursor(context, &declaration, &mut diagnostics);
+        }
+    }
+    diagnostics
+}
+
+/// Returns whether a component is an image, or a function that may return one.
+fn is_image(component: &str) -> bool {
+    component.starts_with(['"', '\'']) || (component.ends_with(')') && component.contains('('))
+}
+
+/// Why a component isn't a valid `<resolution>`.
+enum ResolutionError {
+    NotAResolution,
+    MissingUnit,
+    UnknownUnit,
+    Negative,
+}
+
+/// Parses a `<resolution>` like `2x` or `192dpi`.
+///
+/// # Returns
+///
+/// * The resolution in `dppx`.
+fn parse_resolution(component: &str) -> Result<f64, ResolutionError> {
+    let number_len = component
+        .char_indices()
+        .find(|&(idx, c)| !(c.is_ascii_digit() || c == '.' || (idx == 0 && "+-".contains(c))))
+        .map_or(component.len(), |(idx, _)| idx);
+    let (number, unit) = component.split_at(number_len);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| ResolutionError::NotAResolution)?;
+    if unit.is_empty() {
+        return Err(ResolutionError::MissingUnit);
+    }
+    let (_, dppx, _) = RESOLUTION_UNITS
+        .iter()
+        .find(|(known, _, _)| eq_ignore_case(known, unit))
+        .ok_or(ResolutionError::UnknownUnit)?;
+    if number < 0.0 {
+        return Err(ResolutionError::Negative);
+    }
+    Ok(number * dppx)
+}
+
+fn error(context: &RuleContext, range: &Range<usize>, code: &str, message: String) -> Diagnostic {
+    new_diagnostic(
+        context.range(range.start, range.end),
+        DiagnosticSeverity::ERROR,
+        code,
+        message,
+        vec![],
+    )
+}
+
+fn check_image_set(context: &RuleContext, call: &FunctionCall, diagnostics: &mut Vec<Diagnostic>) {
+    const CODE: &str = "invalid-image-set";
+    if call.arguments.trim().is_empty() {
+        let message = format!("`{}()` needs at least one image.", call.name);
+        diagnostics.push(error(context, &call.range, CODE, message));
+        return;
+    }
+
+    // The resolution of each option, in dppx
+    let mut resolutions: Vec<(f64, Range<usize>)> = Vec::new();
+    for (option, option_range) in split_list(call.arguments, call.arguments_start) {
+        let components = split_components(option, option_range.start);
+        let Some((image, image_range)) = components.first() else {
+            let message = "Empty image option.".to_string();
+            diagnostics.push(error(context, &call.range, CODE, message));
+            continue;
+        };
+        if !is_image(image) {
+            let message = format!("Expected an image, like `url()` or a string, found `{image}`.");
+            diagnostics.push(error(context, image_range, CODE, message));
+            continue;
+        }
+
+        let mut resolution = None;
+        for (component, range) in &components[1..] {
+            if fold_case(component).starts_with("type(") {
+                continue;
+            }
+            match parse_resolution(component) {
+                Ok(_) if resolution.is_some() => {
+                    let message = "An image option can only have one resolution.".to_string();
+                    diagnostics.push(error(context, range, CODE, message));
+                }
+                Ok(dppx) => resolution = Some((dppx, range.clone())),
+                Err(ResolutionError::NotAResolution) => {
+                    let message =
+                        format!("Expected a resolution or `type()`, found `{component}`.");
+                    diagnostics.push(error(context, range, CODE, message));
+                }
+                Err(ResolutionError::MissingUnit) => {
+                    let fix = QuickFix {
+                        title: "Add the `x` unit".to_string(),
+                        edits: vec![TextEdit {
+                            range: context.range(range.end, range.end),
+                            new_text: "x".to_string(),
+                        }],
+                    };
+                    diagnostics.push(new_diagnostic(
+                        context.range(range.start, range.end),
+                        DiagnosticSeverity::ERROR,
+                        "invalid-resolution",
+                        format!("`{component}` is missing a resolution unit."),
+                        vec![fix],
+                    ));
+                }
+                Err(ResolutionError::UnknownUnit) => {
+                    let message = format!(
+                        "Unknown resolution unit in `{component}`, expected `x`, `dppx`, `dpi` or `dpcm`."
+                    );
+                    diagnostics.push(error(context, range, "invalid-resolution", message));
+                }
+                Err(ResolutionError::Negative) => {
+                    let message = "Resolutions cannot be negative.".to_string();
+                    diagnostics.push(error(context, range, "invalid-resolution", message));
+                }
+            }
+        }
+
+        let has_invalid_resolution = resolution.is_none()
+            && components[1..]
+                .iter()
+                .any(|(component, _)| !fold_case(component).starts_with("type("));
+        if has_invalid_resolution {
+            continue;
+        }
+        // Options without a resolution default to 1x
+        let (dppx, range) = resolution.unwrap_or((1.0, image_range.clone()));
+        match resolutions
+            .iter()
+            .find(|(other, _)| (other - dppx).abs() < 1e-9)
+        {
+            Some((_, first_range)) => {
+                let mut diagnostic = new_diagnostic(
+                    context.range(range.start, range.end),
+                    DiagnosticSeverity::WARNING,
+                    "duplicate-image-set-resolution",
+                    format!("Another image option already has the resolution {dppx}x."),
+                    vec![],
+                );
+                diagnostic.related_information = Some(vec![DiagnosticRelatedInformation {
+                    location: Location::new(
+                        context.uri.clone(),
+                        context.range(first_range.start, first_range.end),
+                    ),
+                    message: "First option with this resolution".to_string(),
+                }]);
+                diagnostics.push(diagnostic);
+            }
+            None => resolutions.push((dppx, range)),
+        }
+    }
+}
+
+/// Parses a `<percentage>`, like `40%`.
+fn parse_percentage(component: &str) -> Option<f64> {
+    component.strip_suffix('%')?.parse().ok()
+}
+
+fn check_cross_fade(context: &RuleContext, call: &FunctionCall, diagnostics: &mut Vec<Diagnostic>) {
+    const CODE: &str = "invalid-cross-fade";
+    if call.arguments.trim().is_empty() {
+        let message = "`cross-fade()` needs at least one image.".to_string();
+        diagnostics.push(error(context, &call.range, CODE, message));
+        return;
+    }
+
+    for (argument, argument_range) in split_list(call.arguments, call.arguments_start) {
+        let components = split_components(argument, argument_range.start);
+        let (percentages, images): (Vec<_>, Vec<_>) = components
+            .iter()
+            .partition(|(component, _)| parse_percentage(component).is_some());
+
+        if images.is_empty() {
+            let message =
+                "Expected an image or a color in each `cross-fade()` argument.".to_string();
+            let range = if argument.is_empty() {
+                &call.range
+            } else {
+                &argument_range
+            };
+            diagnostics.push(error(context, range, CODE, message));
+        }
+        for (_, range) in images.iter().skip(1) {
+            let message = "Each `cross-fade()` argument takes a single image.".to_string();
+            diagnostics.push(error(context, range, CODE, message));
+        }
+        for (idx, (percentage, range)) in percentages.iter().enumerate() {
+            let value = parse_percentage(percentage).unwrap_or_default();
+            if idx > 0 {
+                let message = "Each `cross-fade()` argument takes a single percentage.".to_string();
+                diagnostics.push(error(context, range, CODE, message));
+            } else if !(0.0..=100.0).contains(&value) {
+                let message = format!("`{percentage}` must be between 0% and 100%.");
+                diagnostics.push(error(context, range, CODE, message));
+            }
+        }
+    }
+}
+
+/// Checks the prefixed `-webkit-cross-fade(<image>, <image>, <percentage>)`.
+fn check_legacy_cross_fade(
+    context: &RuleContext,
+    call: &FunctionCall,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let arguments = split_list(call.arguments, call.arguments_start);
+    let valid = arguments.len() == 3
+        && is_image(arguments[0].0)
+        && is_image(arguments[1].0)
+        && parse_percentage(arguments[2].0).is_some_and(|value| (0.0..=100.0).contains(&value));
+    if !valid {
+        let message =
+            "`-webkit-cross-fade()` takes two images and a percentage between 0% and 100%."
+                .to_string();
+        diagnostics.push(error(context, &call.range, "invalid-cross-fade", message));
+    }
+}
+
+fn check_cursor(
+    context: &RuleContext,
+    declaration: &Declaration,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    const CODE: &str = "invalid-cursor";
+    if contains_ignore_case(CSS_WIDE_KEYWORDS, &declaration.value) {
+        return;
+    }
+
+    let items = split_list(&declaration.value, declaration.value_range.start);
+    let Some((fallback, fallback_range)) = items.last() else {
+        return;
+    };
+    for (idx, (item, item_range)) in items.iter().enumerate() {
+        let components = split_components(item, item_range.start);
+        let Some(((image, image_range), hotspot)) = components.split_first() else {
+            continue;
+        };
+        let is_last = idx + 1 == items.len();
+        if is_last && !is_image(image) {
+            continue;
+        }
+        if !is_image(image) || image.starts_with(['"', '\'']) {
+            let message = format!(
+                "Expected a cursor image, like `url()`, found `{image}`. Only the last cursor can be a keyword."
+            );
+            diagnostics.push(error(context, image_range, CODE, message));
+            continue;
+        }
+        match hotspot {
+            [] => {}
+            [(x, x_range), (y, y_range)] => {
+                for (coordinate, range) in [(x, x_range), (y, y_range)] {
+                    if coordinate.parse::<f64>().is_err() {
+                        let message = format!(
+                            "Hotspot coordinates are unitless numbers, found `{coordinate}`."
+                        );
+                        diagnostics.push(error(context, range, CODE, message));
+                    }
+                }
+            }
+            [(_, first), .., (_, last)] | [(_, first @ last)] => {
+                let message =
+                    "A cursor hotspot needs exactly two coordinates, `x` and `y`.".to_string();
+                diagnostics.push(error(context, &(first.start..last.end), CODE, message));
+            }
+        }
+    }
+
+    if is_image(
+        split_components(fallback, fallback_range.start)
+            .first()
+            .map_or("", |(component, _)| component),
+    ) {
+        let end = declaration.value_range.end;
+        let fix = QuickFix {
+            title: "Add the `auto` fallback".to_string(),
+            edits: vec![TextEdit {
+                range: context.range(end, end),
+                new_text: ", auto".to_string(),
+            }],
+        };
+        diagnostics.push(new_diagnostic(
+            context.range(fallback_range.start, fallback_range.end),
+            DiagnosticSeverity::ERROR,
+            "cursor-missing-fallback",
+            "Cursor images need a fallback keyword, like `auto`, as the last cursor.".to_string(),
+            vec![fix],
+        ));
+    } else if !contains_ignore_case(CURSOR_KEYWORDS, fallback) {
+        diagnostics.push(new_diagnostic(
+            context.range(fallback_range.start, fallback_range.end),
+            DiagnosticSeverity::WARNING,
+            "unknown-cursor",
+            format!("Unknown cursor `{fallback}`."),
+            vec![],
+        ));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::quick_fixes;
+    use lsp_types::{NumberOrString, Position};
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_images(&RuleContext::new(&uri, code))
+    }
+
+    fn rule_codes(diagnostics: &[Diagnostic]) -> Vec<&str> {
+        diagnostics
+            .iter()
+            .map(|diagnostic| match &diagnostic.code {
+                Some(NumberOrString::String(code)) => code.as_str(),
+                _ => panic!("Expected a string code"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_images_valid() {
+        let code = "a {\n  background: image-set(url(a.png) 1x, \"a@2x.png\" 2dppx type(\"image/png\"), linear-gradient(red, blue) 288dpi);\n  mask: cross-fade(url(a.png) 30%, red);\n  cursor: url(a.cur) 4 12, -webkit-image-set(url(b.png) 1x) , pointer;\n  content: -webkit-cross-fade(url(a.png), url(b.png), 50%);\n  cursor: var(--cursor), url(a.cur);\n}";
+        let diagnostics = check(code);
+        assert!(diagnostics.is_empty(), "Unexpected {diagnostics:?}");
+    }
+
+    #[test]
+    fn test_check_image_set_resolutions() {
+        let code = "a { background: image-set(url(a.png), url(b.png) 2, url(c.png) 2dpx, url(d.png) 96dpi, red 3x); }";
+        let diagnostics = check(code);
+
+        assert_eq!(
+            rule_codes(&diagnostics),
+            vec![
+                "invalid-resolution",
+                "invalid-resolution",
+                "duplicate-image-set-resolution",
+                "invalid-image-set",
+            ]
+        );
+        let column = code.find(" 2,").unwrap() as u32 + 1;
+        assert_eq!(diagnostics[0].range.start, Position::new(0, column));
+        assert_eq!(quick_fixes(&diagnostics[0])[0].edits[0].new_text, "x");
+        assert!(diagnostics[2].related_information.is_some());
+    }
+
+    #[test]
+    fn test_check_cross_fade() {
+        let code = "a { background: cross-fade(url(a.png) 120%, url(b.png) url(c.png)); }";
+        let diagnostics = check(code);
+        assert_eq!(
+            rule_codes(&diagnostics),
+            vec!["invalid-cross-fade", "invalid-cross-fade"]
+        );
+        assert!(diagnostics[0].message.contains("120%"));
+    }
+
+    #[test]
+    fn test_check_cursor() {
+        let code = "a { cursor: url(a.cur) 4; }\nb { cursor: url(a.cur) 4px 4, url(b.cur); }\ni { cursor: hand; }";
+        let diagnostics = check(code);
+
+        assert_eq!(
+            rule_codes(&diagnostics),
+            vec![
+                "invalid-cursor",
+                "cursor-missing-fallback",
+                "invalid-cursor",
+                "cursor-missing-fallback",
+                "unknown-cursor",
+            ]
+        );
+        let fixes = quick_fixes(&diagnostics[1]);
+        assert_eq!(fixes[0].edits[0].new_text, ", auto");
+        assert_eq!(fixes[0].edits[0].range.start, Position::new(0, 24));
+    }
+}
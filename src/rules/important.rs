@@ -0,0 +1,93 @@
+use crate::rules::{new_diagnostic, LintRule, RuleContext};
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location};
+
+/// Counts the `!important` flags of a document, to keep their number in check. Opt-in:
+/// register it with `LanguageService::register_rule`, or `setImportantUsage` from
+/// JavaScript.
+///
+/// It reports a single diagnostic on the first flag, listing every flag as related
+/// information.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ImportantUsage;
+
+impl LintRule for ImportantUsage {
+    fn name(&self) -> &str {
+        "Important usage"
+    }
+
+    fn code(&self) -> &str {
+        "important-usage"
+    }
+
+    fn check(&self, context: &RuleContext, diagnostics: &mut Vec<Diagnostic>) {
+        let locations: Vec<Location> = context
+            .parse
+            .syntax()
+            .descendants()
+            .filter(|node| node.kind() == CssSyntaxKind::CSS_DECLARATION_IMPORTANT)
+            .map(|node| {
+                let range = node.text_trimmed_range();
+                Location {
+                    uri: context.uri.clone(),
+                    range: context.range(range.start().into(), range.end().into()),
+                }
+            })
+            .collect();
+        let Some(first) = locations.first() else {
+            return;
+        };
+        let message = match locations.len() {
+            1 => "This file uses `!important` once.".to_string(),
+            count => format!("This file uses `!important` {count} times."),
+        };
+        let mut diagnostic = new_diagnostic(
+            first.range,
+            DiagnosticSeverity::INFORMATION,
+            self.code(),
+            message,
+            Vec::new(),
+        );
+        diagnostic.related_information = Some(
+            locations
+                .into_iter()
+                .map(|location| DiagnosticRelatedInformation {
+                    location,
+                    message: "`!important` flag".to_string(),
+                })
+                .collect(),
+        );
+        diagnostics.push(diagnostic);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::Position;
+
+    #[test]
+    fn test_important_usage() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "a { color: red; }\nb { color: red !important; margin: 0 ! IMPORTANT; }";
+        let mut diagnostics = Vec::new();
+        ImportantUsage.check(&RuleContext::new(&uri, code), &mut diagnostics);
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(
+            diagnostics[0].message,
+            "This file uses `!important` 2 times."
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 15));
+        assert_eq!(
+            diagnostics[0].related_information.as_ref().unwrap().len(),
+            2
+        );
+
+        let mut diagnostics = Vec::new();
+        ImportantUsage.check(
+            &RuleContext::new(&uri, code.lines().next().unwrap()),
+            &mut diagnostics,
+        );
+        assert!(diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,375 @@
+use crate::case_folding::{eq_ignore_case, fold_case};
+use crate::data::media_features::{
+    is_media_feature_keyword, media_feature, MediaFeatureData, MediaFeatureType, MEDIA_FEATURES,
+};
+use crate::dialect::Dialect;
+use crate::media_queries::{
+    parse_media_query_list, MediaFeature, MediaFeatureSyntax, MediaQueryList,
+};
+use crate::rules::properties::{edit_distance, MAX_SUGGESTION_DISTANCE};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use crate::value_syntax::{matches_basic_type, parse_components, ComponentKind};
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Diagnostic, DiagnosticSeverity, DiagnosticTag, TextEdit};
+
+/// Checks the query lists of `@media` rules in the given CSS source code, for queries
+/// repeated in a list, conditions repeated in a query, and queries that can never match as
+/// their conditions contradict each other.
+///
+/// In CSS documents, the media features are checked too: their syntax, their names, their
+/// values, and the use of deprecated features like `device-width`.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each repeated query or condition, each query
+///   that can never match, and each invalid or deprecated media feature.
+pub fn check_media_queries(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let query_lists = context
+        .parse
+        .syntax()
+        .descendants()
+        .filter(|node| node.kind() == CssSyntaxKind::CSS_MEDIA_AT_RULE)
+        .filter_map(|at_rule| {
+            at_rule
+                .children()
+                .find(|node| node.kind() == CssSyntaxKind::CSS_MEDIA_QUERY_LIST)
+        });
+    for query_list in query_lists {
+        let range = query_list.text_trimmed_range();
+        let start = usize::from(range.start());
+        let text = &context.source[start..usize::from(range.end())];
+        let list = parse_media_query_list(text, start);
+        if context.dialect == Dialect::Css {
+            check_media_features(context, &list, &mut diagnostics);
+        }
+        let queries: Vec<_> = list.queries.into_iter().flatten().collect();
+        for (idx, query) in queries.iter().enumerate() {
+            if queries[..idx]
+                .iter()
+                .any(|other| other.normalized == query.normalized)
+            {
+                diagnostics.push(new_diagnostic(
+                    context.range(query.range.start, query.range.end),
+                    DiagnosticSeverity::WARNING,
+                    "duplicate-media-condition",
+                    format!(
+                        "`{}` is already a query of this media query list.",
+                        query.normalized
+                    ),
+                    Vec::new(),
+                ));
+                continue;
+            }
+            for (idx, condition) in query.conditions.iter().enumerate() {
+                if query.conditions[..idx]
+                    .iter()
+                    .any(|other| other.normalized == condition.normalized)
+                {
+                    diagnostics.push(new_diagnostic(
+                        context.range(condition.range.start, condition.range.end),
+                        DiagnosticSeverity::WARNING,
+                        "duplicate-media-condition",
+                        format!(
+                            "`{}` is already a condition of this media query.",
+                            condition.normalized
+                        ),
+                        Vec::new(),
+                    ));
+                }
+            }
+            if let Some(contradiction) = query.contradiction() {
+                diagnostics.push(new_diagnostic(
+                    context.range(query.range.start, query.range.end),
+                    DiagnosticSeverity::WARNING,
+                    "impossible-media-query",
+                    format!("This media query can never match, as it requires `{contradiction}`."),
+                    Vec::new(),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+fn check_media_features(
+    context: &RuleContext,
+    list: &MediaQueryList,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for error in &list.errors {
+        diagnostics.push(new_diagnostic(
+            context.range(error.range.start, error.range.end),
+            DiagnosticSeverity::ERROR,
+            "invalid-media-query",
+            error.message.clone(),
+            Vec::new(),
+        ));
+    }
+    for feature in list.features.iter().filter(|feature| !feature.malformed) {
+        // Vendor-prefixed features, like `-webkit-min-device-pixel-ratio`, aren't checked
+        if feature.name.starts_with('-') {
+            continue;
+        }
+        let name_range = context.range(feature.name_range.start, feature.name_range.end);
+        let Some((data, prefixed)) = media_feature(&feature.name) else {
+            let fixes = closest_media_feature(&feature.name)
+                .map(|closest| QuickFix {
+                    title: format!("Replace with `{closest}`"),
+                    edits: vec![TextEdit {
+                        range: name_range,
+                        new_text: closest,
+                    }],
+                })
+                .into_iter()
+                .collect();
+            diagnostics.push(new_diagnostic(
+                name_range,
+                DiagnosticSeverity::WARNING,
+                "unknown-media-feature",
+                format!("Unknown media feature `{}`.", feature.name),
+                fixes,
+            ));
+            continue;
+        };
+        if let Some(message) = invalid_media_feature(feature, data, prefixed) {
+            diagnostics.push(new_diagnostic(
+                context.range(feature.range.start, feature.range.end),
+                DiagnosticSeverity::ERROR,
+                "invalid-media-query",
+                message,
+                Vec::new(),
+            ));
+        }
+        if let Some(replacement) = data.replacement {
+            // `min-device-width` is replaced by `min-width`
+            let prefix = if prefixed {
+                fold_case(&feature.name[..4]).into_owned()
+            } else {
+                String::new()
+            };
+            let mut diagnostic = new_diagnostic(
+                name_range,
+                DiagnosticSeverity::WARNING,
+                "deprecated",
+                format!(
+                    "`{}` is deprecated, use `{prefix}{replacement}` instead.",
+                    feature.name
+                ),
+                Vec::new(),
+            );
+            diagnostic.tags = Some(vec![DiagnosticTag::DEPRECATED]);
+            diagnostics.push(diagnostic);
+        }
+    }
+}
+
+/// Checks how a known media feature is tested and its values.
+///
+/// # Returns
+///
+/// * Why the feature is invalid, or `None` if it's valid.
+fn invalid_media_feature(
+    feature: &MediaFeature,
+    data: &MediaFeatureData,
+    prefixed: bool,
+) -> Option<String> {
+    let name = &feature.name;
+    match feature.syntax {
+        MediaFeatureSyntax::Boolean if prefixed => {
+            return Some(format!("`{name}` requires a value."));
+        }
+        MediaFeatureSyntax::Range if prefixed => {
+            return Some(format!(
+                "`{name}` can't be compared in a range, compare `{}` instead.",
+                data.name
+            ));
+        }
+        MediaFeatureSyntax::Range if !data.range => {
+            return Some(format!(
+                "`{name}` is a discrete feature and can't be compared in a range."
+            ));
+        }
+        _ => {}
+    }
+    for (value, value_range) in &feature.values {
+        // Preprocessor variables and escaped browser hacks, like `0\0`, aren't validated
+        if value.starts_with(['$', '@']) || value.contains('\\') {
+            continue;
+        }
+        let components = parse_components(value, value_range.start);
+        let is_function = |names: &[&str]| {
+            matches!(&components[..], [component] if matches!(&component.kind,
+                ComponentKind::Function { name, .. } if names.iter().any(|known| eq_ignore_case(known, name))))
+        };
+        if is_function(&["var", "env", "attr"]) {
+            continue;
+        }
+        let (valid, expected) = match data.value_type {
+            MediaFeatureType::Length => (
+                matches!(&components[..], [component] if matches_basic_type("length", component)),
+                "a length".to_string(),
+            ),
+            MediaFeatureType::Resolution => (
+                matches!(&components[..], [component] if matches_basic_type("resolution", component)
+                    || eq_ignore_case(component.text, "infinite")),
+                "a resolution".to_string(),
+            ),
+            MediaFeatureType::Ratio => {
+                let is_number = |kind: &ComponentKind| matches!(kind, ComponentKind::Number { value, .. } if *value >= 0.0);
+                let valid = match &components[..] {
+                    [number] => is_number(&number.kind),
+                    [numerator, slash, denominator] => {
+                        is_number(&numerator.kind)
+                            && slash.kind == ComponentKind::Delim('/')
+                            && is_number(&denominator.kind)
+                    }
+                    _ => false,
+                };
+                (valid, "a ratio, like `16 / 9`".to_string())
+            }
+            MediaFeatureType::Integer => (
+                matches!(&components[..], [component] if matches_basic_type("integer", component)
+                    && !matches!(component.kind, ComponentKind::Number { value, .. } if value < 0.0)),
+                "a non-negative integer".to_string(),
+            ),
+            MediaFeatureType::Keywords(keywords) => (
+                is_media_feature_keyword(data, value),
+                describe_keywords(keywords),
+            ),
+        };
+        if !valid {
+            return Some(format!(
+                "Invalid value `{value}` for `{name}`, expected {expected}."
+            ));
+        }
+    }
+    None
+}
+
+/// Lists keywords for a message, like `` `a`, `b` or `c` ``.
+fn describe_keywords(keywords: &[&str]) -> String {
+    let quoted: Vec<String> = keywords
+        .iter()
+        .map(|keyword| format!("`{keyword}`"))
+        .collect();
+    match quoted.split_last() {
+        Some((last, rest)) if !rest.is_empty() => format!("{} or {last}", rest.join(", ")),
+        _ => quoted.concat(),
+    }
+}
+
+/// Finds the known media feature closest to a misspelled one, like `width` for `widht`,
+/// with its `min-` or `max-` prefix.
+fn closest_media_feature(name: &str) -> Option<String> {
+    let name = fold_case(name);
+    MEDIA_FEATURES
+        .iter()
+        .flat_map(|feature| {
+            let prefixed = feature.range.then(|| {
+                [
+                    format!("min-{}", feature.name),
+                    format!("max-{}", feature.name),
+                ]
+            });
+            std::iter::once(feature.name.to_string()).chain(prefixed.into_iter().flatten())
+        })
+        .map(|known| (edit_distance(&name, &known), known))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE && *distance < name.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_media_queries() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "@media screen, print, SCREEN { a {} }\n@media (min-width: 800px) and (max-width: 600px) { a {} }\n@media (hover) and (min-width:1px) and (HOVER) { a {} }\n@media (min-width: 600px) and (max-width: 800px), not all and (hover) { a {} }";
+        let diagnostics = check_media_queries(&RuleContext::new(&uri, code));
+        let messages: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.range.start.line,
+                    diagnostic.range.start.character,
+                    diagnostic.range.end.character,
+                    diagnostic.message.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                (
+                    0,
+                    22,
+                    28,
+                    "`screen` is already a query of this media query list."
+                ),
+                (
+                    1,
+                    7,
+                    48,
+                    "This media query can never match, as it requires `800px <= width <= 600px`."
+                ),
+                (
+                    2,
+                    39,
+                    46,
+                    "`(hover)` is already a condition of this media query."
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_media_features() {
+        let check = |uri: &str, code: &str| -> Vec<(String, String)> {
+            let uri = uri.parse().unwrap();
+            check_media_queries(&RuleContext::new(&uri, code))
+                .iter()
+                .map(|diagnostic| {
+                    let code = match &diagnostic.code {
+                        Some(lsp_types::NumberOrString::String(code)) => code.clone(),
+                        _ => String::new(),
+                    };
+                    (code, diagnostic.message.clone())
+                })
+                .collect()
+        };
+        let code = "@media (widht > 3px), (min-width), (orientation: diagonal), (orientation < portrait) {}\n@media (min-device-width: 600px) and (400px <= width >= 700px), (color: -1), (aspect-ratio: 16 / 9) and (resolution: 2x) and (-webkit-min-device-pixel-ratio: 2) {}\n@media (width: red), (min-width <= 3px), (width: var(--a)), (PREFERS-COLOR-SCHEME: DARK) {}";
+        let expected = [
+            ("unknown-media-feature", "Unknown media feature `widht`."),
+            ("invalid-media-query", "`min-width` requires a value."),
+            ("invalid-media-query", "Invalid value `diagonal` for `orientation`, expected `portrait` or `landscape`."),
+            ("invalid-media-query", "`orientation` is a discrete feature and can't be compared in a range."),
+            ("invalid-media-query", "The comparisons of a range must go in the same direction, like `400px <= width <= 700px`."),
+            ("deprecated", "`min-device-width` is deprecated, use `min-width` instead."),
+            ("invalid-media-query", "Invalid value `-1` for `color`, expected a non-negative integer."),
+            ("invalid-media-query", "Invalid value `red` for `width`, expected a length."),
+            ("invalid-media-query", "`min-width` can't be compared in a range, compare `width` instead."),
+        ];
+        assert_eq!(
+            check("file:///a.css", code),
+            expected.map(|(code, message)| (code.to_string(), message.to_string()))
+        );
+        assert!(check("file:///a.scss", "@media (widht: $tablet) {}").is_empty());
+    }
+
+    #[test]
+    fn test_closest_media_feature() {
+        assert_eq!(closest_media_feature("widht").as_deref(), Some("width"));
+        assert_eq!(
+            closest_media_feature("max-widht").as_deref(),
+            Some("max-width")
+        );
+        assert_eq!(closest_media_feature("prefers-color").as_deref(), None);
+    }
+}
@@ -0,0 +1,406 @@
+use crate::data::properties::CssData;
+use crate::dialect::Dialect;
+use crate::line_index::{LineIndex, PositionEncoding};
+use crate::parser::parse_css_with_dialect;
+use crate::rules::declarations::DuplicatePropertyOptions;
+use crate::rules::pseudos::UnknownPseudoOptions;
+use crate::rules::vendor_prefixes::VendorPrefixOptions;
+use biome_css_parser::CssParse;
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString, Range, TextEdit, Uri};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+pub mod animations;
+pub mod catalog;
+pub mod declarations;
+pub mod deprecated;
+pub mod duplicate_at_rules;
+pub mod font_face;
+pub mod images;
+pub mod important;
+pub mod media_queries;
+pub mod profiles;
+pub mod properties;
+pub mod pseudos;
+pub mod selector_complexity;
+pub mod strings;
+pub mod syntax;
+pub mod timing_functions;
+pub mod values;
+pub mod vendor_prefixes;
+
+/// The document being checked by the built-in rules.
+pub struct RuleContext<'a> {
+    pub uri: &'a Uri,
+    pub source: &'a str,
+    pub parse: CssParse,
+    /// The stylesheet language of the source, the one it was parsed with.
+    pub dialect: Dialect,
+    pub line_index: LineIndex,
+    pub options: RuleOptions,
+    /// The properties known to the service, the built-in ones unless replaced.
+    pub data: &'a CssData,
+}
+
+impl<'a> RuleContext<'a> {
+    /// Creates a new `RuleContext` by parsing the given source.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document, used for related information.
+    /// * `source` - The original CSS source code as a string slice.
+    ///
+    /// # Returns
+    ///
+    /// * A `RuleContext` shared by every rule checking the document.
+    pub fn new(uri: &'a Uri, source: &'a str) -> RuleContext<'a> {
+        let dialect = Dialect::from_path(uri.as_str()).unwrap_or_default();
+        RuleContext {
+            uri,
+            source,
+            parse: parse_css_with_dialect(source, dialect),
+            dialect,
+            line_index: LineIndex::new(source),
+            options: RuleOptions::default(),
+            data: CssData::builtin(),
+        }
+    }
+
+    /// Creates a new `RuleContext` by parsing the given source, with options for the
+    /// built-in rules.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document, used for related information.
+    /// * `source` - The original CSS source code as a string slice.
+    /// * `options` - The `RuleOptions` of the built-in rules.
+    ///
+    /// # Returns
+    ///
+    /// * A `RuleContext` shared by every rule checking the document.
+    pub fn with_options(uri: &'a Uri, source: &'a str, options: RuleOptions) -> RuleContext<'a> {
+        RuleContext {
+            options,
+            ..RuleContext::new(uri, source)
+        }
+    }
+
+    /// Creates a new `RuleContext` from an existing parse of the given source, like the one
+    /// kept up to date by the `DocumentStore`.
+    ///
+    /// The dialect is guessed from the extension of the URI: set `dialect` to the language
+    /// `parse` was read in when it is known otherwise, like the language ID of a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the document, used for related information.
+    /// * `source` - The original CSS source code as a string slice.
+    /// * `parse` - The `CssParse` of `source`.
+    /// * `options` - The `RuleOptions` of the built-in rules.
+    ///
+    /// # Returns
+    ///
+    /// * A `RuleContext` shared by every rule checking the document.
+    pub fn with_parse(
+        uri: &'a Uri,
+        source: &'a str,
+        parse: CssParse,
+        options: RuleOptions,
+    ) -> RuleContext<'a> {
+        RuleContext {
+            uri,
+            source,
+            parse,
+            dialect: Dialect::from_path(uri.as_str()).unwrap_or_default(),
+            line_index: LineIndex::new(source),
+            options,
+            data: CssData::builtin(),
+        }
+    }
+
+    /// Converts a pair of byte offsets into an LSP `Range`.
+    pub fn range(&self, start: usize, end: usize) -> Range {
+        self.line_index.range(self.source, start, end)
+    }
+}
+
+/// A built-in rule, with the codes of the diagnostics it may report.
+pub(crate) struct BuiltinRule {
+    pub codes: &'static [&'static str],
+    pub check: fn(&RuleContext) -> Vec<Diagnostic>,
+}
+
+/// The string rule, run first: its diagnostics replace the parser errors they overlap.
+pub(crate) const STRING_RULE: BuiltinRule = BuiltinRule {
+    codes: &[
+        "invalid-escape",
+        "invalid-unicode-escape",
+        "newline-in-string",
+        "unterminated-string",
+    ],
+    check: strings::check_strings,
+};
+
+/// The other built-in rules, run in order after the syntax and string checks.
+pub(crate) const BUILTIN_RULES: &[BuiltinRule] = &[
+    BuiltinRule {
+        codes: &[
+            "font-source-order",
+            "invalid-font-source",
+            "invalid-unicode-range",
+            "overlapping-unicode-range",
+            "unknown-font-format",
+            "unknown-font-tech",
+            "unreachable-font-source",
+        ],
+        check: font_face::check_font_faces,
+    },
+    BuiltinRule {
+        codes: &["invalid-value", "missing-descriptor", "unknown-descriptor"],
+        check: font_face::check_font_face_descriptors,
+    },
+    BuiltinRule {
+        codes: &[
+            "cursor-missing-fallback",
+            "duplicate-image-set-resolution",
+            "invalid-cross-fade",
+            "invalid-cursor",
+            "invalid-image-set",
+            "invalid-resolution",
+            "unknown-cursor",
+        ],
+        check: images::check_images,
+    },
+    BuiltinRule {
+        codes: &["invalid-animation"],
+        check: animations::check_animations,
+    },
+    BuiltinRule {
+        codes: &["invalid-timing-function"],
+        check: timing_functions::check_timing_functions,
+    },
+    BuiltinRule {
+        codes: &[
+            "duplicate-media-condition",
+            "impossible-media-query",
+            "invalid-media-query",
+            "unknown-media-feature",
+        ],
+        check: media_queries::check_media_queries,
+    },
+    BuiltinRule {
+        codes: &["complex-selector"],
+        check: selector_complexity::check_selector_complexity,
+    },
+    BuiltinRule {
+        codes: &[
+            "duplicate-declaration",
+            "duplicate-property",
+            "empty-rule",
+            "zero-units",
+        ],
+        check: declarations::check_declarations,
+    },
+    BuiltinRule {
+        codes: &["unknown-property"],
+        check: properties::check_properties,
+    },
+    BuiltinRule {
+        codes: &["unknown-pseudo"],
+        check: pseudos::check_pseudos,
+    },
+    BuiltinRule {
+        codes: &["invalid-value"],
+        check: values::check_values,
+    },
+    BuiltinRule {
+        codes: &["deprecated"],
+        check: deprecated::check_deprecated,
+    },
+    BuiltinRule {
+        codes: &["vendor-prefix"],
+        check: vendor_prefixes::check_vendor_prefixes,
+    },
+    BuiltinRule {
+        codes: &["duplicate-at-rule"],
+        check: duplicate_at_rules::check_duplicate_at_rules,
+    },
+];
+
+/// The options of the built-in rules having some, from the `rules` section of the settings,
+/// like `{ "duplicateProperty": { "allowFallbacks": false } }`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RuleOptions {
+    /// The options of the `duplicate-property` rule.
+    pub duplicate_property: DuplicatePropertyOptions,
+    /// The options of the `unknown-pseudo` rule.
+    pub unknown_pseudo: UnknownPseudoOptions,
+    /// The options of the `vendor-prefix` rule.
+    pub vendor_prefix: VendorPrefixOptions,
+}
+
+/// A lint rule added by a downstream crate, running alongside the built-in rules.
+///
+/// Rules receive the parsed document through the `RuleContext` and push their diagnostics,
+/// typically created with the same `code` so users can tell which rule reported them.
+pub trait LintRule {
+    /// A human readable name, like `No ID selectors`.
+    fn name(&self) -> &str;
+
+    /// The code of the diagnostics reported by the rule, like `no-id-selectors`.
+    fn code(&self) -> &str;
+
+    /// Checks a document.
+    ///
+    /// # Arguments
+    ///
+    /// * `context` - The `RuleContext` of the document, with its source and syntax tree.
+    /// * `diagnostics` - The diagnostics of the document, to push new ones to.
+    fn check(&self, context: &RuleContext, diagnostics: &mut Vec<Diagnostic>);
+}
+
+/// The lint rules registered on top of the built-in ones, run in registration order.
+#[derive(Default)]
+pub struct RuleRegistry {
+    rules: Vec<Box<dyn LintRule>>,
+    options: RuleOptions,
+    data: Option<Arc<CssData>>,
+    position_encoding: PositionEncoding,
+}
+
+impl std::fmt::Debug for RuleRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let codes: Vec<&str> = self.rules.iter().map(|rule| rule.code()).collect();
+        f.debug_struct("RuleRegistry")
+            .field("rules", &codes)
+            .field("options", &self.options)
+            .field("position_encoding", &self.position_encoding)
+            .finish()
+    }
+}
+
+impl RuleRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers a rule, replacing any rule with the same code.
+    pub fn register(&mut self, rule: impl LintRule + 'static) {
+        self.rules
+            .retain(|registered| registered.code() != rule.code());
+        self.rules.push(Box::new(rule));
+    }
+
+    /// Unregisters the rule with a code.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if a rule was registered with this code.
+    pub fn unregister(&mut self, code: &str) -> bool {
+        let len = self.rules.len();
+        self.rules.retain(|rule| rule.code() != code);
+        self.rules.len() != len
+    }
+
+    /// Returns the registered rules.
+    pub fn rules(&self) -> impl Iterator<Item = &dyn LintRule> {
+        self.rules.iter().map(Box::as_ref)
+    }
+
+    /// Returns the options of the built-in rules.
+    pub fn options(&self) -> &RuleOptions {
+        &self.options
+    }
+
+    /// Sets the options of the built-in rules.
+    pub fn set_options(&mut self, options: RuleOptions) {
+        self.options = options;
+    }
+
+    /// Returns the properties known to the built-in rules, the built-in ones unless
+    /// replaced.
+    pub fn data(&self) -> &CssData {
+        self.data.as_deref().unwrap_or(CssData::builtin())
+    }
+
+    /// Replaces the properties known to the built-in rules, like custom data.
+    pub fn set_data(&mut self, data: Arc<CssData>) {
+        self.data = Some(data);
+    }
+
+    /// Returns the encoding of the positions of the diagnostics, UTF-16 unless set.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding
+    }
+
+    /// Sets the encoding of the positions of the diagnostics, like the one negotiated with
+    /// the client.
+    pub fn set_position_encoding(&mut self, position_encoding: PositionEncoding) {
+        self.position_encoding = position_encoding;
+    }
+
+    pub fn len(&self) -> usize {
+        self.rules.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+}
+
+/// A fix resolving a diagnostic, carried in the diagnostic's `data` field.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QuickFix {
+    pub title: String,
+    pub edits: Vec<TextEdit>,
+}
+
+/// Creates a diagnostic reported by one of the built-in rules.
+///
+/// # Arguments
+///
+/// * `range` - The `Range` the diagnostic applies to.
+/// * `severity` - The `DiagnosticSeverity` of the diagnostic.
+/// * `code` - The code of the rule reporting the diagnostic.
+/// * `message` - A human readable message.
+/// * `fixes` - The quick fixes resolving the diagnostic, if any.
+///
+/// # Returns
+///
+/// * A `Diagnostic` with its fixes serialized in `data`.
+pub(crate) fn new_diagnostic(
+    range: Range,
+    severity: DiagnosticSeverity,
+    code: &str,
+    message: String,
+    fixes: Vec<QuickFix>,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(severity),
+        code: Some(NumberOrString::String(code.to_string())),
+        source: Some("csslsrs".to_string()),
+        message,
+        data: (!fixes.is_empty()).then(|| serde_json::json!({ "fixes": fixes })),
+        ..Default::default()
+    }
+}
+
+/// Returns the quick fixes carried by a diagnostic.
+///
+/// # Arguments
+///
+/// * `diagnostic` - A `Diagnostic` reported by one of the built-in rules.
+///
+/// # Returns
+///
+/// * A vector of `QuickFix`, empty if the diagnostic has no fixes.
+pub fn quick_fixes(diagnostic: &Diagnostic) -> Vec<QuickFix> {
+    diagnostic
+        .data
+        .as_ref()
+        .and_then(|data| data.get("fixes"))
+        .and_then(|fixes| serde_json::from_value(fixes.clone()).ok())
+        .unwrap_or_default()
+}
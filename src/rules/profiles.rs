@@ -0,0 +1,194 @@
+use crate::rules::catalog::{get_rule, RuleCategory};
+use lsp_types::{Diagnostic, DiagnosticSeverity, NumberOrString};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The severity a profile gives to the diagnostics of a rule or category.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleLevel {
+    /// The diagnostics are dropped.
+    Off,
+    Hint,
+    Information,
+    Warning,
+    Error,
+}
+
+impl RuleLevel {
+    fn severity(self) -> Option<DiagnosticSeverity> {
+        match self {
+            RuleLevel::Off => None,
+            RuleLevel::Hint => Some(DiagnosticSeverity::HINT),
+            RuleLevel::Information => Some(DiagnosticSeverity::INFORMATION),
+            RuleLevel::Warning => Some(DiagnosticSeverity::WARNING),
+            RuleLevel::Error => Some(DiagnosticSeverity::ERROR),
+        }
+    }
+}
+
+/// The severities of diagnostics in one environment, overriding the default severity of
+/// rules by category, then by rule code.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SeverityProfile {
+    pub categories: HashMap<RuleCategory, RuleLevel>,
+    /// The levels of single rules, by code, taking precedence over their category.
+    pub rules: HashMap<String, RuleLevel>,
+}
+
+impl SeverityProfile {
+    /// Returns the level of the diagnostics with a code, or `None` to keep their severity.
+    ///
+    /// Diagnostics without a code are syntax errors, and rules outside of the catalog, like
+    /// registered ones, can only be configured by code.
+    pub fn level(&self, code: Option<&str>) -> Option<RuleLevel> {
+        let Some(code) = code else {
+            return self.categories.get(&RuleCategory::Syntax).copied();
+        };
+        self.rules.get(code).copied().or_else(|| {
+            let rule = get_rule(code)?;
+            self.categories.get(&rule.category).copied()
+        })
+    }
+
+    /// Applies the profile to diagnostics.
+    ///
+    /// # Arguments
+    ///
+    /// * `diagnostics` - The diagnostics of a document, with their default severity.
+    ///
+    /// # Returns
+    ///
+    /// * The diagnostics with their severity overridden, without the ones turned off.
+    pub fn apply(&self, diagnostics: Vec<Diagnostic>) -> Vec<Diagnostic> {
+        diagnostics
+            .into_iter()
+            .filter_map(|mut diagnostic| {
+                let code = match &diagnostic.code {
+                    Some(NumberOrString::String(code)) => Some(code.as_str()),
+                    _ => None,
+                };
+                if let Some(level) = self.level(code) {
+                    diagnostic.severity = Some(level.severity()?);
+                }
+                Some(diagnostic)
+            })
+            .collect()
+    }
+}
+
+/// The `profiles` section of the settings: the severities of diagnostics in the editor and
+/// in CI, like stylistic rules reported as hints while editing but failing CI.
+///
+/// ```json
+/// {
+///   "editor": { "categories": { "style": "hint" } },
+///   "ci": { "categories": { "style": "error" }, "rules": { "zero-units": "off" } }
+/// }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(default)]
+#[non_exhaustive]
+pub struct SeverityProfiles {
+    pub editor: SeverityProfile,
+    pub ci: SeverityProfile,
+}
+
+/// The outcome of a CI run, deciding the exit code of the command line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ExitClass {
+    /// Nothing above information was reported.
+    Clean,
+    /// Warnings were reported, but no errors.
+    Warnings,
+    /// Errors were reported.
+    Errors,
+}
+
+impl ExitClass {
+    /// Classifies the diagnostics of a run, after the CI profile was applied.
+    pub fn from_diagnostics<'a>(diagnostics: impl IntoIterator<Item = &'a Diagnostic>) -> Self {
+        diagnostics
+            .into_iter()
+            .map(|diagnostic| match diagnostic.severity {
+                Some(DiagnosticSeverity::ERROR) => ExitClass::Errors,
+                Some(DiagnosticSeverity::WARNING) => ExitClass::Warnings,
+                _ => ExitClass::Clean,
+            })
+            .max()
+            .unwrap_or(ExitClass::Clean)
+    }
+
+    /// Returns the exit code of the class: 0 when clean, 1 with warnings and 2 with errors.
+    pub fn code(self) -> u8 {
+        match self {
+            ExitClass::Clean => 0,
+            ExitClass::Warnings => 1,
+            ExitClass::Errors => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::features::diagnostics::get_diagnostics;
+
+    #[test]
+    fn test_severity_profiles() {
+        let profiles: SeverityProfiles = serde_json::from_str(
+            r#"{
+                "editor": { "categories": { "style": "hint", "suspicious": "off" } },
+                "ci": { "categories": { "style": "error" }, "rules": { "empty-rule": "warning" } }
+            }"#,
+        )
+        .unwrap();
+        let uri = "file:///test.css".parse().unwrap();
+        let diagnostics = get_diagnostics(&uri, "a { margin: 0px; }\nb {}\nc { cursor: poiner }");
+        assert_eq!(diagnostics.len(), 3);
+
+        let editor = profiles.editor.apply(diagnostics.clone());
+        let severities: Vec<_> = editor
+            .iter()
+            .map(|diagnostic| diagnostic.severity)
+            .collect();
+        assert_eq!(
+            severities,
+            vec![
+                Some(DiagnosticSeverity::HINT),
+                Some(DiagnosticSeverity::HINT)
+            ]
+        );
+        assert_eq!(ExitClass::from_diagnostics(&editor), ExitClass::Clean);
+
+        let ci = profiles.ci.apply(diagnostics);
+        let severities: Vec<_> = ci.iter().map(|diagnostic| diagnostic.severity).collect();
+        assert_eq!(
+            severities,
+            vec![
+                Some(DiagnosticSeverity::ERROR),
+                Some(DiagnosticSeverity::WARNING),
+                Some(DiagnosticSeverity::WARNING)
+            ]
+        );
+        assert_eq!(ExitClass::from_diagnostics(&ci).code(), 2);
+    }
+
+    #[test]
+    fn test_severity_profile_syntax_errors() {
+        let profile: SeverityProfile =
+            serde_json::from_str(r#"{ "categories": { "syntax": "warning" } }"#).unwrap();
+        let uri = "file:///test.css".parse().unwrap();
+        let diagnostics = profile.apply(get_diagnostics(&uri, "a { margin }"));
+        assert!(!diagnostics.is_empty());
+        assert_eq!(
+            ExitClass::from_diagnostics(&diagnostics),
+            ExitClass::Warnings
+        );
+        assert!(
+            serde_json::from_str::<SeverityProfile>(r#"{ "rules": { "a": "fatal" } }"#).is_err()
+        );
+    }
+}
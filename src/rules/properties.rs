@@ -0,0 +1,224 @@
+use crate::case_folding::fold_case;
+use crate::data::properties::CssData;
+use crate::data::property_names::{
+    is_known_property, is_known_vendor_property, split_vendor_prefix, KNOWN_PROPERTIES,
+    VENDOR_PROPERTIES,
+};
+use crate::parser::{find_declarations, Declaration};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextEdit};
+
+/// The most edits between a misspelled name and the known name suggested instead.
+pub(crate) const MAX_SUGGESTION_DISTANCE: usize = 2;
+
+/// Checks that the declarations of style rules use known properties, and that
+/// vendor-prefixed properties prefix a known property. The properties of the `CssData` of
+/// the context, custom data included, are known along with the standard ones. Custom
+/// properties, unknown vendor prefixes and the descriptors of at-rules like `@font-face`
+/// aren't checked.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` on the name of each unknown property, with a fix replacing it
+///   by the closest known property, if any is close enough.
+pub fn check_properties(context: &RuleContext) -> Vec<Diagnostic> {
+    find_declarations(&context.parse.syntax())
+        .into_iter()
+        .filter(is_style_property)
+        .filter(|declaration| {
+            let is_known = |name: &str| context.data.property(name).is_some();
+            match split_vendor_prefix(&declaration.name) {
+                Some((_, property)) => {
+                    !is_known(&declaration.name)
+                        && !is_known(property)
+                        && !is_known_vendor_property(&declaration.name)
+                }
+                None => {
+                    !declaration.name.starts_with('-')
+                        && !is_known(&declaration.name)
+                        && !is_known_property(&declaration.name)
+                }
+            }
+        })
+        .map(|declaration| {
+            let range = context.range(declaration.name_range.start, declaration.name_range.end);
+            let fixes = closest_property(&declaration.name, context.data)
+                .map(|property| QuickFix {
+                    title: format!("Replace with `{property}`"),
+                    edits: vec![TextEdit {
+                        range,
+                        new_text: property.to_string(),
+                    }],
+                })
+                .into_iter()
+                .collect();
+            new_diagnostic(
+                range,
+                DiagnosticSeverity::WARNING,
+                "unknown-property",
+                format!("Unknown property `{}`.", declaration.name),
+                fixes,
+            )
+        })
+        .collect()
+}
+
+/// Returns whether a declaration is a property of a style rule, possibly vendor-prefixed:
+/// not a custom property, a feature tested by `@supports`, nor a descriptor in the block of
+/// an at-rule.
+pub(crate) fn is_style_property(declaration: &Declaration) -> bool {
+    let name = &declaration.name;
+    if name.starts_with("--") || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return false;
+    }
+    for ancestor in declaration.node.ancestors().skip(1) {
+        match ancestor.kind() {
+            CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE => {
+                return true
+            }
+            CssSyntaxKind::CSS_AT_RULE | CssSyntaxKind::CSS_SUPPORTS_FEATURE_DECLARATION => {
+                return false
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Finds the known property closest to a misspelled one, like `color` for `colr`, with the
+/// same vendor prefix if any, like `-webkit-transform` for `-webkit-transfrom`. The
+/// properties of `data` are known along with the standard ones.
+fn closest_property(name: &str, data: &CssData) -> Option<String> {
+    let name = fold_case(name);
+    let (prefix, property) = split_vendor_prefix(&name).unwrap_or(("", &name));
+    let vendor_properties = VENDOR_PROPERTIES
+        .iter()
+        .filter(|vendor_property| !prefix.is_empty() && vendor_property.starts_with(prefix))
+        .map(|vendor_property| {
+            (
+                edit_distance(&name, vendor_property),
+                vendor_property.to_string(),
+            )
+        });
+    let data_properties = data
+        .properties()
+        .map(|known| known.name.as_str())
+        .filter(|known| !known.starts_with('-'));
+    KNOWN_PROPERTIES
+        .iter()
+        .copied()
+        .chain(data_properties)
+        .map(|known| (edit_distance(property, known), format!("{prefix}{known}")))
+        .chain(vendor_properties)
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE && *distance < property.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, property)| property)
+}
+
+/// Counts the insertions, deletions, substitutions and transpositions of adjacent
+/// characters turning `a` into `b`.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    // The distances for the two previous rows and the current one
+    let mut previous2 = vec![0; b.len() + 1];
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for i in 1..=a.len() {
+        current[0] = i;
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            current[j] = (previous[j] + 1)
+                .min(current[j - 1] + 1)
+                .min(previous[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                current[j] = current[j].min(previous2[j - 2] + 1);
+            }
+        }
+        std::mem::swap(&mut previous2, &mut previous);
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::properties::CssDataBuilder;
+    use crate::rules::quick_fixes;
+    use lsp_types::Position;
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_properties(&RuleContext::new(&uri, code))
+    }
+
+    #[test]
+    fn test_check_properties() {
+        let code = "a {\n  colr: red;\n  Margin-Top: 0;\n  --brand: red;\n  -webkit-line-clamp: 2;\n  frobnicate: 1;\n  & b { heigth: 1px; }\n}\n@font-face { font-display: swap; }\n@supports (foo: bar) { b { color: red; } }\nc { -webkit-transfrom: none; -webkit-box-orient: vertical; -moz-box-orinet: vertical; -khtml-foo: 1; }";
+        let diagnostics = check(code);
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unknown property `colr`.",
+                "Unknown property `frobnicate`.",
+                "Unknown property `heigth`.",
+                "Unknown property `-webkit-transfrom`.",
+                "Unknown property `-moz-box-orinet`.",
+            ]
+        );
+        assert_eq!(diagnostics[0].range.start, Position::new(1, 2));
+        assert_eq!(diagnostics[0].range.end, Position::new(1, 6));
+
+        let fixes = quick_fixes(&diagnostics[0]);
+        assert_eq!(fixes[0].title, "Replace with `color`");
+        assert_eq!(fixes[0].edits[0].new_text, "color");
+        assert!(quick_fixes(&diagnostics[1]).is_empty());
+        assert_eq!(quick_fixes(&diagnostics[2])[0].edits[0].new_text, "height");
+        assert_eq!(
+            quick_fixes(&diagnostics[3])[0].edits[0].new_text,
+            "-webkit-transform"
+        );
+        assert_eq!(
+            quick_fixes(&diagnostics[4])[0].edits[0].new_text,
+            "-moz-box-orient"
+        );
+    }
+
+    #[test]
+    fn test_check_properties_custom_data() {
+        let uri = "file:///test.css".parse().unwrap();
+        let data = CssDataBuilder::new()
+            .property("frobnicate")
+            .finish()
+            .build();
+        let code = "a { frobnicate: 1; -webkit-frobnicate: 1; frobnicat: 1; }";
+        let diagnostics = check_properties(&RuleContext {
+            data: &data,
+            ..RuleContext::new(&uri, code)
+        });
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].message, "Unknown property `frobnicat`.");
+        assert_eq!(
+            quick_fixes(&diagnostics[0])[0].edits[0].new_text,
+            "frobnicate"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("color", "color"), 0);
+        assert_eq!(edit_distance("colr", "color"), 1);
+        assert_eq!(edit_distance("heigth", "height"), 1);
+        assert_eq!(edit_distance("", "gap"), 3);
+    }
+}
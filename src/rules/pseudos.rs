@@ -0,0 +1,180 @@
+use crate::case_folding::{eq_ignore_case, fold_case};
+use crate::data::pseudos::{is_known_pseudo, standard_pseudos, VENDOR_PSEUDOS};
+use crate::dialect::Dialect;
+use crate::rules::properties::{edit_distance, MAX_SUGGESTION_DISTANCE};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextEdit};
+use serde::Deserialize;
+
+/// The options of the `unknown-pseudo` rule.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct UnknownPseudoOptions {
+    /// The pseudo-classes and pseudo-elements to accept, with their colons, like the
+    /// engine-specific `::-webkit-search-results-decoration` or the `:deep` of a framework.
+    pub ignore: Vec<String>,
+}
+
+/// Checks that selectors use known pseudo-classes and pseudo-elements. The pseudo-classes
+/// styling a vendor-prefixed pseudo-element, like `::-webkit-scrollbar:horizontal`, aren't
+/// checked.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document, with the `UnknownPseudoOptions`.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` on each unknown pseudo-class or pseudo-element, colons
+///   included, with a fix replacing it by the closest known one, if any is close enough.
+pub fn check_pseudos(context: &RuleContext) -> Vec<Diagnostic> {
+    let options = &context.options.unknown_pseudo;
+    let is_less = context.dialect == Dialect::Less;
+    let mut diagnostics = Vec::new();
+    for node in context.parse.syntax().descendants() {
+        let kind = match node.kind() {
+            CssSyntaxKind::CSS_PSEUDO_CLASS_SELECTOR => "pseudo-class",
+            CssSyntaxKind::CSS_PSEUDO_ELEMENT_SELECTOR => "pseudo-element",
+            _ => continue,
+        };
+        let Some((pseudo, range)) = pseudo_name(&node) else {
+            continue;
+        };
+        if is_known_pseudo(&pseudo)
+            || (is_less && eq_ignore_case(&pseudo, ":extend"))
+            || (kind == "pseudo-class" && follows_vendor_pseudo_element(&node))
+            || options
+                .ignore
+                .iter()
+                .any(|ignored| eq_ignore_case(ignored, &pseudo))
+        {
+            continue;
+        }
+        let range = context.range(range.start, range.end);
+        let fixes = closest_pseudo(&pseudo)
+            .map(|closest| QuickFix {
+                title: format!("Replace with `{closest}`"),
+                edits: vec![TextEdit {
+                    range,
+                    new_text: closest.to_string(),
+                }],
+            })
+            .into_iter()
+            .collect();
+        diagnostics.push(new_diagnostic(
+            range,
+            DiagnosticSeverity::WARNING,
+            "unknown-pseudo",
+            format!("Unknown {kind} `{pseudo}`."),
+            fixes,
+        ));
+    }
+    diagnostics
+}
+
+/// Returns a pseudo-class or a pseudo-element as written, with its colons and without the
+/// arguments of functional ones, and its byte range. Names that aren't plain identifiers,
+/// like interpolations, escapes and the custom selectors of `:--heading`, are skipped.
+fn pseudo_name(node: &CssSyntaxNode) -> Option<(String, std::ops::Range<usize>)> {
+    let colon = node.first_token()?;
+    let name = colon.next_token()?;
+    let range = node.text_trimmed_range();
+    let end = name.text_trimmed_range().end();
+    let text = name.text_trimmed();
+    if end > range.end()
+        || !text.starts_with(|c: char| c.is_alphabetic() || c == '-' || c == '_')
+        || text.starts_with("--")
+        || text.contains('\\')
+    {
+        return None;
+    }
+    let start = colon.text_trimmed_range().start();
+    Some((
+        format!("{}{text}", colon.text_trimmed()),
+        start.into()..end.into(),
+    ))
+}
+
+/// Returns whether a pseudo-class comes after a vendor-prefixed pseudo-element in its
+/// compound selector, where engines define pseudo-classes of their own.
+fn follows_vendor_pseudo_element(node: &CssSyntaxNode) -> bool {
+    node.siblings(biome_rowan::Direction::Prev).any(|sibling| {
+        sibling.kind() == CssSyntaxKind::CSS_PSEUDO_ELEMENT_SELECTOR
+            && pseudo_name(&sibling).is_some_and(|(pseudo, _)| pseudo.starts_with("::-"))
+    })
+}
+
+/// Finds the known pseudo-class or pseudo-element closest to a misspelled one, like
+/// `:hover` for `:hoover`, or `::placeholder` for `:placeholder`.
+fn closest_pseudo(pseudo: &str) -> Option<&'static str> {
+    let pseudo = fold_case(pseudo);
+    let name = pseudo.trim_start_matches(':');
+    let vendor_pseudos = VENDOR_PSEUDOS
+        .iter()
+        .copied()
+        .filter(|_| name.starts_with('-'));
+    standard_pseudos()
+        .chain(vendor_pseudos)
+        .map(|known| (edit_distance(&pseudo, known), known))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE && *distance < name.len())
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, known)| known)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{quick_fixes, RuleOptions};
+
+    fn check(uri: &str, code: &str, options: RuleOptions) -> Vec<(String, String)> {
+        let uri = uri.parse().unwrap();
+        check_pseudos(&RuleContext::with_options(&uri, code, options))
+            .iter()
+            .map(|diagnostic| {
+                let fix = quick_fixes(diagnostic)
+                    .first()
+                    .map(|fix| fix.edits[0].new_text.clone())
+                    .unwrap_or_default();
+                (diagnostic.message.clone(), fix)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_unknown_pseudos() {
+        let code = "a:hoover, b::placehoder, c:HOVER:before, d:is(.x:focs), e:nth-child(2n)::after {}\ninput:placeholder, ::-webkit-scrollbar:horizontal, ::-webkit-scrolbar, :global(.a), ::v-deep, :--heading {}";
+        let diagnostics = check("file:///a.css", code, RuleOptions::default());
+        let expected = [
+            ("Unknown pseudo-class `:hoover`.", ":hover"),
+            ("Unknown pseudo-element `::placehoder`.", "::placeholder"),
+            ("Unknown pseudo-class `:focs`.", ":focus"),
+            ("Unknown pseudo-class `:placeholder`.", "::placeholder"),
+            (
+                "Unknown pseudo-element `::-webkit-scrolbar`.",
+                "::-webkit-scrollbar",
+            ),
+            ("Unknown pseudo-element `::v-deep`.", ""),
+        ];
+        assert_eq!(
+            diagnostics,
+            expected.map(|(message, fix)| (message.to_string(), fix.to_string()))
+        );
+    }
+
+    #[test]
+    fn test_unknown_pseudos_options() {
+        let code = "a::v-deep, b::-webkit-search-results-decoration, c:extend(.d) {}";
+        let options = RuleOptions {
+            unknown_pseudo: UnknownPseudoOptions {
+                ignore: vec![
+                    "::v-deep".to_string(),
+                    "::-WEBKIT-search-results-decoration".to_string(),
+                ],
+            },
+            ..Default::default()
+        };
+        assert_eq!(check("file:///a.less", code, options.clone()), vec![]);
+        assert_eq!(check("file:///a.css", code, options).len(), 1);
+    }
+}
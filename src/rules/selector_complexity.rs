@@ -0,0 +1,66 @@
+use crate::rules::{new_diagnostic, RuleContext};
+use crate::selectors::check_complexity;
+use biome_css_syntax::CssSyntaxKind;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Checks the selectors of style rules in the given CSS source code for complexity beyond
+/// what the selector engine analyzes, see `check_complexity`. Such selectors get no
+/// specificity and match no element in the features relying on the engine.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each selector list too complex to be analyzed.
+pub fn check_selector_complexity(context: &RuleContext) -> Vec<Diagnostic> {
+    context
+        .parse
+        .syntax()
+        .descendants()
+        .filter(|node| {
+            matches!(
+                node.kind(),
+                CssSyntaxKind::CSS_QUALIFIED_RULE | CssSyntaxKind::CSS_NESTED_QUALIFIED_RULE
+            )
+        })
+        .filter_map(|rule| rule.first_child())
+        .filter_map(|selector_list| {
+            let error = check_complexity(&selector_list.text_trimmed().to_string()).err()?;
+            let range = selector_list.text_trimmed_range();
+            Some(new_diagnostic(
+                context.range(range.start().into(), range.end().into()),
+                DiagnosticSeverity::WARNING,
+                "complex-selector",
+                error.to_string(),
+                Vec::new(),
+            ))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_selector_complexity() {
+        let uri = "file:///test.css".parse().unwrap();
+        let deep = format!("{}a{}", ":not(".repeat(17), ")".repeat(17));
+        let code = format!(
+            ".a :is(b, c) {{}}\n{deep} {{}}\n.b {{\n  & {} {{}}\n}}",
+            ":has(a, b, c, d, e, f) ".repeat(4)
+        );
+        let diagnostics = check_selector_complexity(&RuleContext::new(&uri, &code));
+        let lines: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.range.start.line)
+            .collect();
+        assert_eq!(lines, vec![1, 3]);
+        assert_eq!(
+            diagnostics[0].message,
+            "This selector nests 17 levels of parentheses, more than the 16 that can be analyzed."
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use crate::parser::tokenizer::{Token, TokenKind, Tokenizer};
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextEdit};
+
+/// Checks string literals and escape sequences in the given CSS source code.
+///
+/// Reports unterminated strings, raw newlines inside strings, escapes that don't escape
+/// anything, and unicode escapes outside of the valid code point range, each with quick
+/// fixes to terminate the string or escape the offending character. Strings are read by
+/// the tokenizer of the dialect of the document, so quotes in comments, `//` ones included
+/// in SCSS and Less, don't start strings.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic` for every invalid string or escape sequence.
+pub fn check_strings(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut checker = StringChecker {
+        context,
+        diagnostics: Vec::new(),
+    };
+    for token in Tokenizer::with_dialect(context.source, 0, context.dialect) {
+        checker.check_token(&token);
+    }
+    checker.diagnostics
+}
+
+struct StringChecker<'a> {
+    context: &'a RuleContext<'a>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl StringChecker<'_> {
+    fn check_token(&mut self, token: &Token) {
+        match token.kind {
+            TokenKind::Comment | TokenKind::Whitespace => {}
+            TokenKind::BadString => {
+                self.check_escapes(token);
+                // The newline isn't part of the token, it ends the (bad) string.
+                let (start, offset) = (token.range.start, token.range.end);
+                let quote = &token.text[..1];
+                self.report(
+                    start,
+                    offset,
+                    DiagnosticSeverity::ERROR,
+                    "newline-in-string",
+                    "Strings cannot contain unescaped newlines.".to_string(),
+                    vec![
+                        self.fix("Escape the newline", offset, offset, "\\"),
+                        self.fix("Terminate the string", offset, offset, quote),
+                    ],
+                );
+            }
+            TokenKind::String => {
+                self.check_escapes(token);
+                if token.is_unterminated() {
+                    let (start, end) = (token.range.start, token.range.end);
+                    let quote = &token.text[..1];
+                    self.report(
+                        start,
+                        end,
+                        DiagnosticSeverity::ERROR,
+                        "unterminated-string",
+                        "Unterminated string.".to_string(),
+                        vec![self.fix("Terminate the string", end, end, quote)],
+                    );
+                }
+            }
+            // A backslash before a newline or the end of the file doesn't start an escape
+            TokenKind::Delim('\\') => {
+                let start = token.range.start;
+                self.report(
+                    start,
+                    start + 1,
+                    DiagnosticSeverity::ERROR,
+                    "invalid-escape",
+                    "A backslash cannot escape a newline or the end of the file outside of a string."
+                        .to_string(),
+                    vec![self.fix("Escape the backslash", start, start + 1, "\\\\")],
+                );
+            }
+            _ => self.check_escapes(token),
+        }
+    }
+
+    /// Checks the unicode escapes of a token, like `\110000` in a string or an identifier.
+    fn check_escapes(&mut self, token: &Token) {
+        let text = token.text;
+        let mut chars = text.char_indices().peekable();
+        while let Some((idx, c)) = chars.next() {
+            if c != '\\' {
+                continue;
+            }
+            let digits = text[idx + 1..]
+                .chars()
+                .take(6)
+                .take_while(char::is_ascii_hexdigit)
+                .count();
+            if digits == 0 {
+                // The escaped character, like a newline continuing a string
+                chars.next();
+                continue;
+            }
+            let digits_end = idx + 1 + digits;
+            // A whitespace after the digits is part of the escape
+            let end = match text[digits_end..].chars().next() {
+                Some(c @ (' ' | '\t' | '\n')) => digits_end + c.len_utf8(),
+                _ => digits_end,
+            };
+            while chars.next_if(|&(offset, _)| offset < end).is_some() {}
+
+            let code_point =
+                u32::from_str_radix(&text[idx + 1..digits_end], 16).unwrap_or(u32::MAX);
+            if code_point == 0 || (0xD800..=0xDFFF).contains(&code_point) || code_point > 0x10FFFF {
+                let start = token.range.start;
+                self.report(
+                    start + idx,
+                    start + digits_end,
+                    DiagnosticSeverity::WARNING,
+                    "invalid-unicode-escape",
+                    format!(
+                        "`{}` is not a valid code point and will be replaced by U+FFFD.",
+                        &text[idx..digits_end]
+                    ),
+                    vec![self.fix("Replace with U+FFFD", start + idx, start + end, "\\fffd ")],
+                );
+            }
+        }
+    }
+
+    fn fix(&self, title: &str, start: usize, end: usize, new_text: &str) -> QuickFix {
+        QuickFix {
+            title: title.to_string(),
+            edits: vec![TextEdit {
+                range: self.context.range(start, end),
+                new_text: new_text.to_string(),
+            }],
+        }
+    }
+
+    fn report(
+        &mut self,
+        start: usize,
+        end: usize,
+        severity: DiagnosticSeverity,
+        code: &str,
+        message: String,
+        fixes: Vec<QuickFix>,
+    ) {
+        let range = self.context.range(start, end);
+        self.diagnostics
+            .push(new_diagnostic(range, severity, code, message, fixes));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::quick_fixes;
+    use lsp_types::{NumberOrString, Position};
+
+    fn check(code: &str) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_strings(&RuleContext::new(&uri, code))
+    }
+
+    fn rule_code(diagnostic: &Diagnostic) -> &str {
+        match &diagnostic.code {
+            Some(NumberOrString::String(code)) => code,
+            _ => panic!("Expected a string code"),
+        }
+    }
+
+    #[test]
+    fn test_check_strings_valid() {
+        let code = "a { content: \"it's \\\"fine\\\"\\\n\"; font-family: '\\26 co'; }\n.\\31 23 {}";
+        let diagnostics = check(code);
+        assert!(
+            diagnostics.is_empty(),
+            "Valid strings should not be reported"
+        );
+    }
+
+    #[test]
+    fn test_check_strings_unterminated() {
+        let code = "a { content: \"abc";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "unterminated-string");
+        let fixes = quick_fixes(&diagnostics[0]);
+        assert_eq!(fixes.len(), 1);
+        assert_eq!(fixes[0].edits[0].new_text, "\"");
+        assert_eq!(fixes[0].edits[0].range.start, Position::new(0, 17));
+    }
+
+    #[test]
+    fn test_check_strings_newline() {
+        let code = "a { content: 'abc\n}";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "newline-in-string");
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 13));
+        assert_eq!(diagnostics[0].range.end, Position::new(0, 17));
+        let fixes = quick_fixes(&diagnostics[0]);
+        assert_eq!(fixes.len(), 2);
+        assert_eq!(fixes[0].edits[0].new_text, "\\");
+        assert_eq!(fixes[1].edits[0].new_text, "'");
+    }
+
+    #[test]
+    fn test_check_strings_invalid_unicode_escape() {
+        let code = "a { content: \"\\110000\"; }";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "invalid-unicode-escape");
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+        assert_eq!(diagnostics[0].range.start, Position::new(0, 14));
+        assert_eq!(diagnostics[0].range.end, Position::new(0, 21));
+    }
+
+    #[test]
+    fn test_check_strings_invalid_escape_outside_string() {
+        let code = ".a\\\n{}";
+        let diagnostics = check(code);
+
+        assert_eq!(diagnostics.len(), 1, "Expected one diagnostic");
+        assert_eq!(rule_code(&diagnostics[0]), "invalid-escape");
+        assert_eq!(quick_fixes(&diagnostics[0])[0].edits[0].new_text, "\\\\");
+    }
+
+    #[test]
+    fn test_check_strings_ignores_line_comments() {
+        for uri in ["file:///test.scss", "file:///test.less"] {
+            let uri = uri.parse().unwrap();
+            let code = "// don't do this\na { content: \"it's\"; }\n// \"open";
+            let diagnostics = check_strings(&RuleContext::new(&uri, code));
+            assert!(
+                diagnostics.is_empty(),
+                "Quotes in line comments should be ignored in {}",
+                uri.as_str()
+            );
+        }
+        // In CSS, `//` doesn't start a comment
+        assert_eq!(check("// don't\n").len(), 1);
+    }
+
+    #[test]
+    fn test_check_strings_ignores_comments() {
+        let code = "/* \"unterminated */ a {}";
+        let diagnostics = check(code);
+        assert!(
+            diagnostics.is_empty(),
+            "Strings in comments should be ignored"
+        );
+    }
+}
@@ -0,0 +1,171 @@
+use crate::parser::find_declarations;
+use crate::rules::RuleContext;
+use biome_css_syntax::CssSyntaxKind;
+use biome_diagnostics::{Diagnostic as _, PrintDescription};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location};
+
+/// Reports the syntax errors of the document: the errors of the parser, with clearer
+/// messages and ranges for the most common ones, and declarations without a value, which
+/// the parser accepts but browsers drop.
+///
+/// Blocks that are never closed are reported on their `{` rather than at the end of the
+/// file, and a missing `;` between declarations on the name of the next declaration.
+/// Syntax errors have no rule code.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each syntax error.
+pub fn check_syntax(context: &RuleContext) -> Vec<Diagnostic> {
+    let source = context.source;
+    let root = context.parse.syntax();
+    let end_of_file = context.range(source.len(), source.len());
+
+    let unclosed: Vec<Diagnostic> = root
+        .descendants()
+        .filter_map(|node| {
+            let mut tokens = node
+                .children_with_tokens()
+                .filter_map(|element| element.into_token());
+            let open = tokens.find(|token| token.kind() == CssSyntaxKind::L_CURLY)?;
+            // The `}` may also end up in a bogus child of the block, after an error
+            if tokens.any(|token| token.kind() == CssSyntaxKind::R_CURLY)
+                || node
+                    .last_token()
+                    .is_some_and(|token| token.kind() == CssSyntaxKind::R_CURLY)
+            {
+                return None;
+            }
+            let range = open.text_trimmed_range();
+            Some(syntax_error(
+                context.range(range.start().into(), range.end().into()),
+                "This `{` is never closed.".to_string(),
+                Some(DiagnosticRelatedInformation {
+                    location: Location::new(context.uri.clone(), end_of_file),
+                    message: "The file ends here".to_string(),
+                }),
+            ))
+        })
+        .collect();
+
+    let mut diagnostics: Vec<Diagnostic> = context
+        .parse
+        .diagnostics()
+        .iter()
+        .filter_map(|diagnostic| {
+            let span = diagnostic.location().span.unwrap_or_default();
+            let (start, end) = (usize::from(span.start()), usize::from(span.end()));
+            // Replaced by the more precise unclosed block errors
+            if start == source.len() && !unclosed.is_empty() {
+                return None;
+            }
+            if let Some(name) = next_declaration_name(source, start) {
+                return Some(syntax_error(
+                    context.range(name.start, name.end),
+                    format!("Expected a `;` before `{}`.", &source[name]),
+                    None,
+                ));
+            }
+            Some(syntax_error(
+                context.range(start, end),
+                PrintDescription(diagnostic).to_string(),
+                None,
+            ))
+        })
+        .collect();
+    diagnostics.extend(unclosed);
+
+    diagnostics.extend(
+        find_declarations(&root)
+            .into_iter()
+            .filter(|declaration| {
+                declaration.value.is_empty() && !declaration.name.starts_with("--")
+            })
+            .map(|declaration| {
+                syntax_error(
+                    context.range(declaration.name_range.start, declaration.value_range.end),
+                    format!("`{}` has no value.", declaration.name),
+                    None,
+                )
+            }),
+    );
+    diagnostics
+}
+
+/// Returns the range of the name of a declaration whose colon is at an offset, like
+/// `margin` in `color: red margin: 0`, where the `;` before it is missing.
+fn next_declaration_name(source: &str, colon: usize) -> Option<std::ops::Range<usize>> {
+    if !source[colon..].starts_with(':') {
+        return None;
+    }
+    let end = source[..colon].trim_end().len();
+    let start = source[..end]
+        .rfind(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || !c.is_ascii()))
+        .map_or(0, |idx| idx + 1);
+    let name = &source[start..end];
+    let preceded_by_value = source[..start]
+        .trim_end()
+        .ends_with(|c: char| c != '{' && c != ';');
+    (!name.is_empty() && !name.starts_with(|c: char| c.is_ascii_digit()) && preceded_by_value)
+        .then_some(start..end)
+}
+
+fn syntax_error(
+    range: lsp_types::Range,
+    message: String,
+    related_information: Option<DiagnosticRelatedInformation>,
+) -> Diagnostic {
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::ERROR),
+        source: Some("csslsrs".to_string()),
+        message,
+        related_information: related_information.map(|information| vec![information]),
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(code: &str) -> Vec<(u32, u32, String)> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_syntax(&RuleContext::new(&uri, code))
+            .into_iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.range.start.character,
+                    diagnostic.range.end.character,
+                    diagnostic.message,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_check_syntax() {
+        assert_eq!(
+            check("@media x { a { color: red"),
+            vec![
+                (9, 10, "This `{` is never closed.".to_string()),
+                (13, 14, "This `{` is never closed.".to_string()),
+            ]
+        );
+        assert_eq!(
+            check("a { color: red margin: 0 }"),
+            vec![(15, 21, "Expected a `;` before `margin`.".to_string())]
+        );
+        assert_eq!(
+            check("a { color: ; --empty: ; }"),
+            vec![(4, 10, "`color` has no value.".to_string())]
+        );
+        let stray = check("a {} }");
+        assert_eq!(stray.len(), 1);
+        assert_eq!((stray[0].0, stray[0].1), (5, 6));
+        assert!(check("a { color: red; }").is_empty());
+    }
+}
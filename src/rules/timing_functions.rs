@@ -0,0 +1,67 @@
+use crate::case_folding::fold_case;
+use crate::easing::parse_easing;
+use crate::parser::{find_declarations, find_function_calls};
+use crate::rules::{new_diagnostic, RuleContext};
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Checks the arguments of the `cubic-bezier()` and `steps()` easing functions in the given
+/// CSS source code. Values using `var()` are skipped, as they can't be known statically.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document to check.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one for each invalid easing function.
+pub fn check_timing_functions(context: &RuleContext) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    for declaration in find_declarations(&context.parse.syntax()) {
+        if fold_case(&declaration.value).contains("var(") {
+            continue;
+        }
+        for call in find_function_calls(&declaration.value, declaration.value_range.start) {
+            if !matches!(fold_case(call.name).as_ref(), "cubic-bezier" | "steps") {
+                continue;
+            }
+            let text = &context.source[call.range.clone()];
+            if let Err(error) = parse_easing(text, call.range.start) {
+                diagnostics.push(new_diagnostic(
+                    context.range(error.range.start, error.range.end),
+                    DiagnosticSeverity::ERROR,
+                    "invalid-timing-function",
+                    error.message,
+                    Vec::new(),
+                ));
+            }
+        }
+    }
+    diagnostics
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_timing_functions() {
+        let uri = "file:///test.css".parse().unwrap();
+        let code = "a { transition: opacity 1s cubic-bezier(0.1, 0.7, 1.0, 0.1), color 1s Cubic-Bezier(2, 0, 0, 1); }\nb { animation-timing-function: steps(0, end); transition-timing-function: steps(4, jump-none), steps(var(--n)); }";
+        let diagnostics = check_timing_functions(&RuleContext::new(&uri, code));
+        let ranges: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| {
+                (
+                    diagnostic.range.start.line,
+                    diagnostic.range.start.character,
+                    diagnostic.range.end.character,
+                )
+            })
+            .collect();
+        assert_eq!(ranges, vec![(0, 83, 84), (1, 37, 38)]);
+        assert_eq!(
+            diagnostics[1].message,
+            "The number of steps must be a positive integer, but got `0`."
+        );
+    }
+}
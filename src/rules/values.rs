@@ -0,0 +1,114 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case};
+use crate::data::values::CSS_WIDE_KEYWORDS;
+use crate::dialect::Dialect;
+use crate::parser::find_declarations;
+use crate::rules::properties::is_style_property;
+use crate::rules::{new_diagnostic, RuleContext};
+use crate::value_syntax::{
+    is_validated, parse_components, validate_value, Component, ComponentKind,
+};
+use biome_rowan::SyntaxKind;
+use lsp_types::{Diagnostic, DiagnosticSeverity};
+
+/// Functions whose value is only known at computed time, like `var()`.
+const SUBSTITUTION_FUNCTIONS: &[&str] = &["attr", "env", "if", "var"];
+
+/// Checks the values of the declarations of style rules against the grammar of their
+/// property, like `<color>` for `color`. Values using `var()`, vendor-prefixed values and
+/// the documents of preprocessors aren't checked.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, one on the first component of each value that doesn't match
+///   the grammar of its property.
+pub fn check_values(context: &RuleContext) -> Vec<Diagnostic> {
+    if context.dialect != Dialect::Css {
+        return Vec::new();
+    }
+    let mut diagnostics = Vec::new();
+    for declaration in find_declarations(&context.parse.syntax()) {
+        if !is_style_property(&declaration)
+            || !is_validated(&declaration.name)
+            || declaration.value.is_empty()
+            // Values the parser doesn't understand are reported as syntax errors
+            || declaration
+                .node
+                .descendants()
+                .any(|node| node.kind().is_bogus())
+        {
+            continue;
+        }
+        let components = parse_components(&declaration.value, declaration.value_range.start);
+        if is_css_wide_keyword(&components) || !is_static(&components) {
+            continue;
+        }
+        if let Some(mismatch) = validate_value(&declaration.name, &components) {
+            diagnostics.push(new_diagnostic(
+                context.range(mismatch.range.start, mismatch.range.end),
+                DiagnosticSeverity::ERROR,
+                "invalid-value",
+                format!(
+                    "Invalid `{}` value: {}.",
+                    declaration.name, mismatch.message
+                ),
+                Vec::new(),
+            ));
+        }
+    }
+    diagnostics
+}
+
+fn is_css_wide_keyword(components: &[Component]) -> bool {
+    matches!(components, [component] if component.kind == ComponentKind::Ident
+        && contains_ignore_case(CSS_WIDE_KEYWORDS, component.text))
+}
+
+/// Returns whether a value can be validated statically: it doesn't use substitution
+/// functions like `var()`, nor vendor-prefixed keywords and functions.
+fn is_static(components: &[Component]) -> bool {
+    components.iter().all(|component| match &component.kind {
+        ComponentKind::Ident => !is_vendor_prefixed(component.text),
+        ComponentKind::Function { name, arguments } => {
+            !is_vendor_prefixed(name)
+                && !SUBSTITUTION_FUNCTIONS
+                    .iter()
+                    .any(|function| eq_ignore_case(function, name))
+                && is_static(arguments)
+        }
+        _ => true,
+    })
+}
+
+fn is_vendor_prefixed(name: &str) -> bool {
+    name.starts_with('-') && !name.starts_with("--")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(uri: &str, code: &str) -> Vec<String> {
+        let uri = uri.parse().unwrap();
+        check_values(&RuleContext::new(&uri, code))
+            .into_iter()
+            .map(|diagnostic| diagnostic.message)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_values() {
+        let code = "a {\n  color: 12px;\n  margin: 0 auto;\n  width: var(--w) 1px;\n  display: -webkit-box;\n  border: inherit;\n  transition: opacity 0.2s ease-in-out, transform 1s;\n  z-index: high;\n}\n@font-face { font-weight: 100 900; }\n";
+        assert_eq!(
+            check("file:///a.css", code),
+            vec![
+                "Invalid `color` value: expected `<color>` but found `12px`.",
+                "Invalid `z-index` value: expected `auto` or `<integer>` but found `high`.",
+            ]
+        );
+        assert!(check("file:///a.scss", code).is_empty());
+    }
+}
@@ -0,0 +1,284 @@
+use crate::case_folding::{eq_ignore_case, starts_with_ignore_case};
+use crate::data::property_names::{is_known_property, split_vendor_prefix};
+use crate::parser::tokenizer::{tokenize, TokenKind};
+use crate::parser::{find_declarations, Declaration};
+use crate::rules::properties::is_style_property;
+use crate::rules::{new_diagnostic, QuickFix, RuleContext};
+use biome_css_syntax::CssSyntaxNode;
+use lsp_types::{Diagnostic, DiagnosticSeverity, TextEdit};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// The options of the `vendor-prefix` rule.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VendorPrefixOptions {
+    /// Whether vendor-prefixed properties must come with their standard property, like
+    /// `transform` for `-webkit-transform`.
+    pub require_standard: bool,
+    /// The vendor-prefixed properties that must come with their standard property, for the
+    /// older browsers still targeted, like `-webkit-backdrop-filter`.
+    pub require_prefixed: Vec<String>,
+}
+
+impl Default for VendorPrefixOptions {
+    fn default() -> Self {
+        Self {
+            require_standard: true,
+            require_prefixed: Vec::new(),
+        }
+    }
+}
+
+/// Checks that the vendor-prefixed properties of style rules come with their standard
+/// property in the same block, and, when configured, that standard properties come with
+/// their prefixed versions.
+///
+/// # Arguments
+///
+/// * `context` - The `RuleContext` of the document, with the `VendorPrefixOptions`.
+///
+/// # Returns
+///
+/// * A vector of `Diagnostic`, each with a fix adding the missing property with the same
+///   value.
+pub fn check_vendor_prefixes(context: &RuleContext) -> Vec<Diagnostic> {
+    let options = &context.options.vendor_prefix;
+    let declarations: Vec<Declaration> = find_declarations(&context.parse.syntax())
+        .into_iter()
+        .filter(is_style_property)
+        .collect();
+    let mut blocks: HashMap<Option<CssSyntaxNode>, Vec<&Declaration>> = HashMap::new();
+    for declaration in &declarations {
+        blocks
+            .entry(block(declaration))
+            .or_default()
+            .push(declaration);
+    }
+    let mut diagnostics = Vec::new();
+    for declaration in &declarations {
+        let siblings = &blocks[&block(declaration)];
+        let declares = |name: &str| {
+            siblings
+                .iter()
+                .any(|sibling| eq_ignore_case(&sibling.name, name))
+        };
+        match split_vendor_prefix(&declaration.name) {
+            Some((prefix, standard)) if options.require_standard => {
+                if !is_known_property(standard) || declares(standard) {
+                    continue;
+                }
+                // The standard property is added after the last prefixed version
+                let Some(last) = siblings.iter().rev().find(|sibling| {
+                    split_vendor_prefix(&sibling.name)
+                        .is_some_and(|(_, property)| eq_ignore_case(property, standard))
+                }) else {
+                    continue;
+                };
+                let end = usize::from(statement(last).text_trimmed_range().end());
+                let value = unprefixed(value(context, last), prefix);
+                let new_text = if has_semicolon(last) {
+                    format!("{}{standard}: {value};", separator(context.source, last))
+                } else {
+                    format!(";{}{standard}: {value}", separator(context.source, last))
+                };
+                diagnostics.push(new_diagnostic(
+                    context.range(declaration.name_range.start, declaration.name_range.end),
+                    DiagnosticSeverity::INFORMATION,
+                    "vendor-prefix",
+                    format!(
+                        "`{}` should come with the standard `{standard}` property.",
+                        declaration.name
+                    ),
+                    vec![QuickFix {
+                        title: format!("Add `{standard}`"),
+                        edits: vec![TextEdit {
+                            range: context.range(end, end),
+                            new_text,
+                        }],
+                    }],
+                ));
+            }
+            Some(_) => {}
+            None => {
+                for prefixed in options.require_prefixed.iter().filter(|prefixed| {
+                    split_vendor_prefix(prefixed)
+                        .is_some_and(|(_, property)| eq_ignore_case(property, &declaration.name))
+                }) {
+                    if declares(prefixed) {
+                        continue;
+                    }
+                    let start = usize::from(statement(declaration).text_trimmed_range().start());
+                    diagnostics.push(new_diagnostic(
+                        context.range(declaration.name_range.start, declaration.name_range.end),
+                        DiagnosticSeverity::INFORMATION,
+                        "vendor-prefix",
+                        format!(
+                            "`{}` should come with the prefixed `{prefixed}` property.",
+                            declaration.name
+                        ),
+                        vec![QuickFix {
+                            title: format!("Add `{prefixed}`"),
+                            edits: vec![TextEdit {
+                                range: context.range(start, start),
+                                new_text: format!(
+                                    "{prefixed}: {};{}",
+                                    value(context, declaration),
+                                    separator(context.source, declaration)
+                                ),
+                            }],
+                        }],
+                    ));
+                }
+            }
+        }
+    }
+    diagnostics
+}
+
+fn block(declaration: &Declaration) -> Option<CssSyntaxNode> {
+    declaration.node.parent().and_then(|node| node.parent())
+}
+
+/// The declaration with its semicolon, if any.
+fn statement(declaration: &Declaration) -> CssSyntaxNode {
+    declaration
+        .node
+        .parent()
+        .unwrap_or(declaration.node.clone())
+}
+
+fn has_semicolon(declaration: &Declaration) -> bool {
+    statement(declaration)
+        .text_trimmed()
+        .to_string()
+        .ends_with(';')
+}
+
+/// The value of a declaration with its `!important` flag, if any.
+fn value<'a>(context: &RuleContext<'a>, declaration: &Declaration) -> &'a str {
+    let end = usize::from(declaration.node.text_trimmed_range().end());
+    &context.source[declaration.value_range.start..end.max(declaration.value_range.end)]
+}
+
+/// Removes a vendor prefix from the keywords and functions of a value, like
+/// `-webkit-transform 1s` to `transform 1s`.
+fn unprefixed(value: &str, prefix: &str) -> String {
+    let mut unprefixed = String::with_capacity(value.len());
+    for token in tokenize(value, 0) {
+        let is_prefixed = matches!(token.kind, TokenKind::Ident | TokenKind::Function { .. })
+            && starts_with_ignore_case(token.text, prefix)
+            && token.text.len() > prefix.len();
+        if is_prefixed {
+            unprefixed.push_str(&token.text[prefix.len()..]);
+        } else {
+            unprefixed.push_str(token.text);
+        }
+    }
+    unprefixed
+}
+
+/// What separates a declaration from the next one: a line break with the same indentation
+/// when it starts its line, a space otherwise.
+fn separator(source: &str, declaration: &Declaration) -> String {
+    let start = declaration.name_range.start;
+    let line_start = source[..start].rfind('\n').map_or(0, |idx| idx + 1);
+    let indentation = &source[line_start..start];
+    if indentation.chars().all(|c| c == ' ' || c == '\t') {
+        format!("\n{indentation}")
+    } else {
+        " ".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{quick_fixes, RuleOptions};
+
+    fn check(code: &str, options: RuleOptions) -> Vec<Diagnostic> {
+        let uri = "file:///test.css".parse().unwrap();
+        check_vendor_prefixes(&RuleContext::with_options(&uri, code, options))
+    }
+
+    fn apply_fix(code: &str, diagnostic: &Diagnostic) -> String {
+        let edit = &quick_fixes(diagnostic)[0].edits[0];
+        let line_index = crate::line_index::LineIndex::new(code);
+        let start = line_index.offset(code, edit.range.start);
+        let end = line_index.offset(code, edit.range.end);
+        format!("{}{}{}", &code[..start], edit.new_text, &code[end..])
+    }
+
+    #[test]
+    fn test_missing_standard_property() {
+        let code = "a {\n  -webkit-transform: rotate(1deg);\n  -moz-transform: rotate(1deg) !important;\n  -webkit-box-orient: vertical;\n  -webkit-user-select: none;\n  user-select: none;\n}\nb { -webkit-backdrop-filter: blur(2px) }\n";
+        let diagnostics = check(code, RuleOptions::default());
+        let messages: Vec<&str> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "`-webkit-transform` should come with the standard `transform` property.",
+                "`-moz-transform` should come with the standard `transform` property.",
+                "`-webkit-backdrop-filter` should come with the standard `backdrop-filter` property.",
+            ]
+        );
+        assert_eq!(
+            apply_fix(code, &diagnostics[0]),
+            code.replace(
+                "!important;\n",
+                "!important;\n  transform: rotate(1deg) !important;\n"
+            )
+        );
+        assert_eq!(
+            apply_fix(code, &diagnostics[2]),
+            code.replace("blur(2px) }", "blur(2px); backdrop-filter: blur(2px) }")
+        );
+    }
+
+    #[test]
+    fn test_missing_standard_property_prefixed_value() {
+        let code = "a { -webkit-transition: -webkit-transform 1s, opacity 1s; -moz-width: -moz-calc(1px + 2px) }";
+        let diagnostics = check(code, RuleOptions::default());
+        assert_eq!(diagnostics.len(), 2, "{diagnostics:?}");
+        assert_eq!(
+            apply_fix(code, &diagnostics[0]),
+            code.replace(
+                "opacity 1s;",
+                "opacity 1s; transition: transform 1s, opacity 1s;"
+            )
+        );
+        assert_eq!(
+            apply_fix(code, &diagnostics[1]),
+            code.replace("2px) }", "2px); width: calc(1px + 2px) }")
+        );
+    }
+
+    #[test]
+    fn test_missing_prefixed_property() {
+        let code = "a {\n  backdrop-filter: blur(2px);\n  -webkit-transform: none;\n}\nb { -webkit-backdrop-filter: none; backdrop-filter: none; }\n";
+        let options = RuleOptions {
+            vendor_prefix: VendorPrefixOptions {
+                require_standard: false,
+                require_prefixed: vec!["-webkit-backdrop-filter".to_string()],
+            },
+            ..Default::default()
+        };
+        let diagnostics = check(code, options);
+        assert_eq!(diagnostics.len(), 1, "{diagnostics:?}");
+        assert_eq!(
+            diagnostics[0].message,
+            "`backdrop-filter` should come with the prefixed `-webkit-backdrop-filter` property."
+        );
+        assert_eq!(
+            apply_fix(code, &diagnostics[0]),
+            code.replacen(
+                "  backdrop-filter",
+                "  -webkit-backdrop-filter: blur(2px);\n  backdrop-filter",
+                1
+            )
+        );
+    }
+}
@@ -0,0 +1,622 @@
+use crate::formatter::normalize_selector;
+use crate::parser::tokenizer::{tokenize, TokenKind};
+use crate::parser::{parse_css, split_list};
+use biome_css_syntax::{CssSyntaxKind, CssSyntaxNode};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// The specificity of a selector, compared component by component.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize)]
+pub struct Specificity {
+    /// The number of ID selectors.
+    pub ids: u32,
+    /// The number of class, attribute and pseudo-class selectors.
+    pub classes: u32,
+    /// The number of type and pseudo-element selectors.
+    pub types: u32,
+}
+
+impl Specificity {
+    fn add(self, other: Specificity) -> Specificity {
+        Specificity {
+            ids: self.ids + other.ids,
+            classes: self.classes + other.classes,
+            types: self.types + other.types,
+        }
+    }
+}
+
+impl fmt::Display for Specificity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {}, {})", self.ids, self.classes, self.types)
+    }
+}
+
+/// The deepest nesting of parentheses, like `:is(:not(a))`, a selector may have to be
+/// analyzed.
+pub const MAX_SELECTOR_DEPTH: usize = 16;
+
+/// The most complex selectors a selector may expand to to be analyzed, like 4 for
+/// `:is(a, b) :is(c, d)`.
+pub const MAX_SELECTOR_ALTERNATIVES: usize = 1024;
+
+/// Why a selector is too complex to be analyzed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ComplexityError {
+    /// The parentheses are nested deeper than `MAX_SELECTOR_DEPTH`.
+    TooDeep(usize),
+    /// The selector expands to more than `MAX_SELECTOR_ALTERNATIVES` complex selectors.
+    TooManyAlternatives,
+}
+
+impl fmt::Display for ComplexityError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ComplexityError::TooDeep(depth) => write!(
+                f,
+                "This selector nests {depth} levels of parentheses, more than the {MAX_SELECTOR_DEPTH} that can be analyzed."
+            ),
+            ComplexityError::TooManyAlternatives => write!(
+                f,
+                "This selector expands to more than {MAX_SELECTOR_ALTERNATIVES} alternatives through its selector lists, more than can be analyzed."
+            ),
+        }
+    }
+}
+
+/// Checks that a selector can be analyzed, so pathological generated selectors, like deeply
+/// nested or combined `:is()` and `:has()` lists, can't blow up matching.
+///
+/// # Arguments
+///
+/// * `selector` - A selector or selector list.
+///
+/// # Returns
+///
+/// * `Ok` with the number of complex selectors the selector expands to, or the
+///   `ComplexityError` of a selector exceeding the limits.
+pub fn check_complexity(selector: &str) -> Result<usize, ComplexityError> {
+    let mut depth = 0usize;
+    let mut max_depth = 0;
+    // Parentheses in strings, like `[title="("]`, and comments don't nest
+    for token in tokenize(selector, 0) {
+        match token.kind {
+            TokenKind::Function { .. } | TokenKind::LeftParen => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            TokenKind::RightParen => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    if max_depth > MAX_SELECTOR_DEPTH {
+        return Err(ComplexityError::TooDeep(max_depth));
+    }
+    // The depth is bounded, so the recursion is as well
+    alternatives(&normalize_selector(selector)).ok_or(ComplexityError::TooManyAlternatives)
+}
+
+/// Counts the complex selectors a selector list expands to, or `None` beyond the limit.
+fn alternatives(selector_list: &str) -> Option<usize> {
+    let mut total = 0usize;
+    for (selector, _) in split_list(selector_list, 0) {
+        let mut product = 1usize;
+        for simple in parse_complex_selector(selector)
+            .iter()
+            .flat_map(|compound| &compound.simple_selectors)
+        {
+            let Some(open) = simple.find('(').filter(|_| simple.starts_with(':')) else {
+                continue;
+            };
+            let name = simple[1..open].to_ascii_lowercase();
+            if matches!(name.as_str(), "is" | "where" | "not" | "has" | "matches") {
+                let argument = simple[open + 1..].trim_end_matches(')');
+                product = product.checked_mul(alternatives(argument)?.max(1))?;
+            }
+        }
+        total = total.checked_add(product)?;
+        if total > MAX_SELECTOR_ALTERNATIVES {
+            return None;
+        }
+    }
+    Some(total)
+}
+
+/// Pseudo-elements that may be written with a single colon, for compatibility with CSS 2.
+const LEGACY_PSEUDO_ELEMENTS: &[&str] = &["after", "before", "first-letter", "first-line"];
+
+/// Computes the specificity of a selector, following Selectors Level 4: `:is()`, `:not()`
+/// and `:has()` take the specificity of their most specific argument, and `:where()` has
+/// none.
+///
+/// # Arguments
+///
+/// * `selector` - A complex selector, like `ul > li.active`. For a selector list, the most
+///   specific selector counts.
+///
+/// # Returns
+///
+/// * The `Specificity` of the selector, or the default one if it is too complex to be
+///   analyzed, see `check_complexity`.
+pub fn specificity(selector: &str) -> Specificity {
+    if check_complexity(selector).is_err() {
+        return Specificity::default();
+    }
+    let normalized = normalize_selector(selector);
+    max_specificity(&normalized)
+}
+
+fn max_specificity(selector_list: &str) -> Specificity {
+    split_list(selector_list, 0)
+        .into_iter()
+        .map(|(selector, _)| {
+            parse_complex_selector(selector)
+                .iter()
+                .flat_map(|compound| &compound.simple_selectors)
+                .map(|simple| simple_specificity(simple))
+                .fold(Specificity::default(), Specificity::add)
+        })
+        .max()
+        .unwrap_or_default()
+}
+
+fn simple_specificity(simple: &str) -> Specificity {
+    let one = |ids, classes, types| Specificity {
+        ids,
+        classes,
+        types,
+    };
+    match simple.as_bytes().first() {
+        Some(b'#') => one(1, 0, 0),
+        Some(b'.' | b'[') => one(0, 1, 0),
+        Some(b':') if simple.starts_with("::") => one(0, 0, 1),
+        Some(b':') => {
+            let (name, argument) = match simple.find('(') {
+                Some(open) => (
+                    &simple[1..open],
+                    Some(simple[open + 1..].trim_end_matches(')')),
+                ),
+                None => (&simple[1..], None),
+            };
+            let name = name.to_ascii_lowercase();
+            match (name.as_str(), argument) {
+                ("where", _) => Specificity::default(),
+                ("is" | "not" | "has" | "matches", Some(argument)) => max_specificity(argument),
+                (name, Some(argument)) if name.starts_with("nth-") => {
+                    // `:nth-child(2n of .a)` adds the specificity of its selector
+                    match argument.split_once(" of ") {
+                        Some((_, selector)) => one(0, 1, 0).add(max_specificity(selector)),
+                        None => one(0, 1, 0),
+                    }
+                }
+                (name, None) if LEGACY_PSEUDO_ELEMENTS.contains(&name) => one(0, 0, 1),
+                _ => one(0, 1, 0),
+            }
+        }
+        Some(b'*' | b'&') | None => Specificity::default(),
+        Some(_) if simple.ends_with("|*") => Specificity::default(),
+        Some(_) => one(0, 0, 1),
+    }
+}
+
+/// A compound selector of a complex selector, like `li.active` in `ul > li.active`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct CompoundSelector {
+    /// The combinator before this compound: ` `, `>`, `+` or `~`, or ` ` for the first one.
+    combinator: char,
+    /// The simple selectors of the compound, normalized.
+    simple_selectors: Vec<String>,
+}
+
+/// Splits a normalized complex selector into its compound selectors.
+fn parse_complex_selector(selector: &str) -> Vec<CompoundSelector> {
+    let mut compounds = Vec::new();
+    let mut combinator = ' ';
+    for part in split_outside_groups(selector, |kind| *kind == TokenKind::Whitespace) {
+        match part {
+            ">" | "+" | "~" => combinator = part.chars().next().unwrap_or(' '),
+            _ => {
+                compounds.push(CompoundSelector {
+                    combinator,
+                    simple_selectors: split_compound(part),
+                });
+                combinator = ' ';
+            }
+        }
+    }
+    compounds
+}
+
+/// Splits a compound selector into its simple selectors, like `li`, `.active` and `:hover`.
+fn split_compound(compound: &str) -> Vec<String> {
+    let mut simple_selectors: Vec<String> = Vec::new();
+    let is_separator = |kind: &TokenKind| {
+        matches!(
+            kind,
+            TokenKind::Delim('.')
+                | TokenKind::Hash { .. }
+                | TokenKind::LeftBracket
+                | TokenKind::Colon
+        )
+    };
+    for part in split_outside_groups(compound, is_separator) {
+        // The separators start the next simple selector, and `::` starts a single one
+        match simple_selectors.last_mut() {
+            Some(last) if last == ":" => last.push_str(part),
+            _ => simple_selectors.push(part.to_string()),
+        }
+    }
+    simple_selectors
+}
+
+/// Splits a selector before each token matching `is_separator` outside of brackets and
+/// parentheses. Whitespace separators are dropped, others start the next part, and empty
+/// parts are skipped.
+fn split_outside_groups(selector: &str, is_separator: impl Fn(&TokenKind) -> bool) -> Vec<&str> {
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut part_start = 0;
+    for token in tokenize(selector, 0) {
+        if depth == 0 && is_separator(&token.kind) {
+            parts.push(&selector[part_start..token.range.start]);
+            part_start = match token.kind {
+                TokenKind::Whitespace => token.range.end,
+                _ => token.range.start,
+            };
+        }
+        match token.kind {
+            TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::Function { .. } => {
+                depth += 1;
+            }
+            TokenKind::RightParen | TokenKind::RightBracket => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    parts.push(&selector[part_start..]);
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Returns whether a selector matches the element described by another selector, like
+/// `.btn` and `nav .btn` matching `nav > a.btn.primary`.
+///
+/// The element is described by the compounds of `element`: it has every simple selector of
+/// its last compound, and its ancestors and siblings are described by the other compounds.
+/// A selector matches when each of its compounds is part of the corresponding described
+/// element. Pseudo-classes are compared as written, so `:is(a, b)` only matches an element
+/// described with `:is(a, b)`.
+///
+/// # Arguments
+///
+/// * `selector` - The selector or selector list of a style rule.
+/// * `element` - The complex selector describing the element.
+///
+/// # Returns
+///
+/// * The most specific selector of the list matching the element, normalized, or `None` if
+///   none matches or if either selector is too complex to be analyzed.
+pub fn match_selector(selector: &str, element: &str) -> Option<(String, Specificity)> {
+    check_complexity(selector).ok()?;
+    check_complexity(element).ok()?;
+    let element = parse_complex_selector(&normalize_selector(element));
+    if element.is_empty() {
+        return None;
+    }
+    let normalized = normalize_selector(selector);
+    split_list(&normalized, 0)
+        .into_iter()
+        .filter(|(selector, _)| {
+            let compounds = parse_complex_selector(selector);
+            !compounds.is_empty() && matches_at(&compounds, &element, element.len() - 1)
+        })
+        .map(|(selector, _)| (selector.to_string(), max_specificity(selector)))
+        .max_by_key(|(_, specificity)| *specificity)
+}
+
+/// Returns whether the selector, up to its last compound, matches the element described by
+/// `element[..=idx]`.
+fn matches_at(selector: &[CompoundSelector], element: &[CompoundSelector], idx: usize) -> bool {
+    let Some((last, rest)) = selector.split_last() else {
+        return true;
+    };
+    if !is_part_of(last, &element[idx]) {
+        return false;
+    }
+    if rest.is_empty() {
+        return true;
+    }
+    match last.combinator {
+        '>' | '+' => {
+            idx > 0
+                && element[idx].combinator == last.combinator
+                && matches_at(rest, element, idx - 1)
+        }
+        // Descendant or general sibling: any previous compound reached through compatible
+        // combinators
+        combinator => {
+            let compatible: &[char] = if combinator == '~' {
+                &['~', '+']
+            } else {
+                &[' ', '>']
+            };
+            (0..idx)
+                .rev()
+                .take_while(|&previous| compatible.contains(&element[previous + 1].combinator))
+                .any(|previous| matches_at(rest, element, previous))
+        }
+    }
+}
+
+fn is_part_of(compound: &CompoundSelector, element: &CompoundSelector) -> bool {
+    compound
+        .simple_selectors
+        .iter()
+        .all(|simple| simple == "*" || simple == "&" || element.simple_selectors.contains(simple))
+}
+
+/// Generates an HTML snippet the selector would match, like the preview of the VS Code CSS
+/// service: each compound becomes an element, with its type, ID, classes and attributes,
+/// nested under its ancestors or next to its siblings. Pseudo-classes and pseudo-elements
+/// are left out.
+///
+/// # Arguments
+///
+/// * `selector` - A complex selector, like `ul > li.active`. For a selector list, the first
+///   selector is used.
+///
+/// # Returns
+///
+/// * The HTML snippet, one element per line and without closing tags, or `None` if the
+///   selector is empty or too complex to be analyzed.
+pub fn selector_to_html(selector: &str) -> Option<String> {
+    check_complexity(selector).ok()?;
+    let normalized = normalize_selector(selector);
+    let (first, _) = split_list(&normalized, 0).into_iter().next()?;
+    let compounds = parse_complex_selector(first);
+    if compounds.is_empty() {
+        return None;
+    }
+    let mut lines = Vec::new();
+    let mut depth = 0usize;
+    for (idx, compound) in compounds.iter().enumerate() {
+        if idx > 0 {
+            match compound.combinator {
+                '>' => depth += 1,
+                '+' => {}
+                '~' => lines.push(format!("{}…", "  ".repeat(depth))),
+                _ => {
+                    depth += 1;
+                    lines.push(format!("{}…", "  ".repeat(depth)));
+                    depth += 1;
+                }
+            }
+        }
+        lines.push(format!("{}{}", "  ".repeat(depth), element_html(compound)));
+    }
+    Some(lines.join("\n"))
+}
+
+/// Describes the element matched by a compound selector as an HTML start tag.
+fn element_html(compound: &CompoundSelector) -> String {
+    let mut tag = "element";
+    let mut id = None;
+    let mut classes = Vec::new();
+    let mut attributes = Vec::new();
+    for simple in &compound.simple_selectors {
+        match simple.as_bytes().first() {
+            Some(b'#') => id = Some(&simple[1..]),
+            Some(b'.') => classes.push(&simple[1..]),
+            Some(b'[') => {
+                let attribute = simple.trim_start_matches('[').trim_end_matches(']');
+                match attribute.split_once('=') {
+                    Some((name, value)) => {
+                        let name = name.trim_end_matches(['~', '|', '^', '$', '*']);
+                        // Drop the quotes and the `i` or `s` flag of the value
+                        let value = match value.rsplit_once(' ') {
+                            Some((value, flag)) if flag.len() == 1 => value,
+                            _ => value,
+                        };
+                        let value = value.trim_matches(['"', '\'']);
+                        attributes.push(format!("{name}=\"{value}\""));
+                    }
+                    None => attributes.push(attribute.to_string()),
+                }
+            }
+            Some(b':' | b'*' | b'&') | None => {}
+            Some(_) => tag = simple.rsplit('|').next().unwrap_or(simple),
+        }
+    }
+    let mut html = format!("<{tag}");
+    if let Some(id) = id {
+        html.push_str(&format!(" id=\"{id}\""));
+    }
+    if !classes.is_empty() {
+        html.push_str(&format!(" class=\"{}\"", classes.join(" ")));
+    }
+    for attribute in attributes {
+        html.push(' ');
+        html.push_str(&attribute);
+    }
+    html.push('>');
+    html
+}
+
+/// Finds the class names, IDs and element and class combinations of the style rules of a
+/// stylesheet, like `.btn`, `#main` and `a.btn`, with the number of times each is used.
+///
+/// # Arguments
+///
+/// * `source` - The stylesheet source code as a string slice.
+///
+/// # Returns
+///
+/// * A vector of the selectors and their number of uses, most used first, then in
+///   alphabetical order.
+pub fn find_selector_names(source: &str) -> Vec<(String, usize)> {
+    selector_names(&parse_css(source).syntax())
+}
+
+/// Finds the selectors of the style rules below a node, see `find_selector_names`.
+pub(crate) fn selector_names(root: &CssSyntaxNode) -> Vec<(String, usize)> {
+    let mut uses: HashMap<String, usize> = HashMap::new();
+    let mut count = |name: String| *uses.entry(name).or_default() += 1;
+    for compound in root.descendants().filter(|node| {
+        node.kind() == CssSyntaxKind::CSS_COMPOUND_SELECTOR
+            && node.ancestors().any(|ancestor| {
+                ancestor.kind() == CssSyntaxKind::CSS_SELECTOR_LIST
+                    && ancestor
+                        .parent()
+                        .is_some_and(|rule| rule.kind() == CssSyntaxKind::CSS_QUALIFIED_RULE)
+            })
+    }) {
+        let mut has_type = false;
+        let mut only_names = true;
+        for child in compound.children() {
+            match child.kind() {
+                CssSyntaxKind::CSS_TYPE_SELECTOR => has_type = true,
+                CssSyntaxKind::CSS_SUB_SELECTOR_LIST => {
+                    for sub_selector in child.children() {
+                        match sub_selector.kind() {
+                            CssSyntaxKind::CSS_CLASS_SELECTOR | CssSyntaxKind::CSS_ID_SELECTOR => {
+                                count(sub_selector.text_trimmed().to_string())
+                            }
+                            _ => only_names = false,
+                        }
+                    }
+                }
+                _ => only_names = false,
+            }
+        }
+        let text = compound.text_trimmed().to_string();
+        if has_type && only_names && text.contains(['.', '#']) {
+            count(text);
+        }
+    }
+    let mut names: Vec<(String, usize)> = uses.into_iter().collect();
+    names.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec(ids: u32, classes: u32, types: u32) -> Specificity {
+        Specificity {
+            ids,
+            classes,
+            types,
+        }
+    }
+
+    #[test]
+    fn test_specificity() {
+        let cases = [
+            ("*", spec(0, 0, 0)),
+            ("LI", spec(0, 0, 1)),
+            ("ul li", spec(0, 0, 2)),
+            ("ul ol+li", spec(0, 0, 3)),
+            ("h1 + *[rel=up]", spec(0, 1, 1)),
+            ("ul ol li.red", spec(0, 1, 3)),
+            ("li.red.level", spec(0, 2, 1)),
+            ("#x34y", spec(1, 0, 0)),
+            ("#s12:not(FOO)", spec(1, 0, 1)),
+            (".foo :is(.bar, #baz)", spec(1, 1, 0)),
+            ("a:where(#a, .b)::before", spec(0, 0, 2)),
+            ("p:first-line", spec(0, 0, 2)),
+            ("li:nth-child(2n+1 of .a)", spec(0, 2, 1)),
+            ("a, #b", spec(1, 0, 0)),
+            ("#\\31 23.a\\.b", spec(1, 1, 0)),
+        ];
+        for (selector, expected) in cases {
+            assert_eq!(specificity(selector), expected, "{selector:?}");
+        }
+        assert!(spec(0, 1, 0) > spec(0, 0, 12));
+        assert_eq!(spec(1, 2, 3).to_string(), "(1, 2, 3)");
+    }
+
+    #[test]
+    fn test_match_selector() {
+        let element = "nav > a.btn.primary[href]:hover";
+        let matching = [
+            "a",
+            ".btn",
+            "A.primary.btn",
+            "nav a",
+            "nav > .btn",
+            "* > [href]",
+            ".btn:hover",
+        ];
+        for selector in matching {
+            assert!(match_selector(selector, element).is_some(), "{selector:?}");
+        }
+        let not_matching = ["b", ".btn.secondary", "ul a", "nav + a", "a::before", "#a"];
+        for selector in not_matching {
+            assert!(match_selector(selector, element).is_none(), "{selector:?}");
+        }
+        assert!(match_selector("main a", "main section > a").is_some());
+        assert!(match_selector("main > a", "main section > a").is_none());
+        assert!(match_selector("h1 ~ p", "h1 + div ~ p").is_some());
+        assert_eq!(
+            match_selector("a, nav .btn", element),
+            Some(("nav .btn".to_string(), spec(0, 1, 1)))
+        );
+    }
+
+    #[test]
+    fn test_check_complexity() {
+        assert_eq!(check_complexity("a, b"), Ok(2));
+        assert_eq!(check_complexity(":is(a, b) :has(> c, + d, e)"), Ok(6));
+        assert_eq!(check_complexity(":where() a"), Ok(1));
+        let quoted = format!("a[title=\"{}\"]", "(".repeat(40));
+        assert_eq!(check_complexity(&quoted), Ok(1), "Parentheses in strings");
+
+        let deep = format!("{}a{}", ":is(".repeat(40), ")".repeat(40));
+        assert_eq!(check_complexity(&deep), Err(ComplexityError::TooDeep(40)));
+        assert_eq!(specificity(&deep), spec(0, 0, 0));
+        assert!(match_selector(&deep, "a").is_none());
+
+        let wide = ":is(a, b, c, d, e, f, g, h) ".repeat(4);
+        assert_eq!(
+            check_complexity(&wide),
+            Err(ComplexityError::TooManyAlternatives)
+        );
+        assert_eq!(
+            specificity(":is(a, b, c, d, e, f, g, h) ".repeat(3).trim()),
+            spec(0, 0, 3)
+        );
+    }
+
+    #[test]
+    fn test_selector_to_html() {
+        assert_eq!(
+            selector_to_html("li.red.level#x:hover").as_deref(),
+            Some("<li id=\"x\" class=\"red level\">")
+        );
+        assert_eq!(
+            selector_to_html("input[type=\"text\" i][disabled]::placeholder").as_deref(),
+            Some("<input type=\"text\" disabled>")
+        );
+        assert_eq!(
+            selector_to_html("nav > ul  .item + a ~ *").as_deref(),
+            Some("<nav>\n  <ul>\n    …\n      <element class=\"item\">\n      <a>\n      …\n      <element>")
+        );
+        assert_eq!(selector_to_html("a, b").as_deref(), Some("<a>"));
+        assert_eq!(selector_to_html(""), None);
+    }
+
+    #[test]
+    fn test_find_selector_names() {
+        let code = ".btn, a.btn.primary, nav > #main .btn:hover {}\n@media print { .btn { color: red } }\ndiv[hidden] {}";
+        assert_eq!(
+            find_selector_names(code),
+            vec![
+                (".btn".to_string(), 4),
+                ("#main".to_string(), 1),
+                (".primary".to_string(), 1),
+                ("a.btn.primary".to_string(), 1),
+            ]
+        );
+    }
+}
@@ -0,0 +1,2402 @@
+use crate::budget::{Budget, Budgeted, Budgets};
+use crate::chunks::{size_limit_diagnostic, SizeLimits};
+use crate::data::properties::{CssData, CssDataBuilder};
+use crate::dialect::Dialect;
+use crate::document_store::DocumentStore;
+use crate::features::abbreviations::AbbreviationProvider;
+use crate::features::code_actions::{
+    get_code_actions_with_encoding, get_inline_import_action, CodeActionContext,
+    CodeActionProvider, CodeActionRegistry, CodeActionWASM,
+};
+use crate::features::colors::{
+    get_document_colors_in_chunks, get_document_colors_with_parse, ColorInformationWASM,
+};
+use crate::features::completion::{
+    get_completions_with_parse, get_layer_completions, get_selector_completions, strip_snippets,
+    CompletionItemWASM, CompletionOptions, CompletionProvider, JsCompletionHandler,
+};
+use crate::features::computed_style::{
+    get_computed_style, ComputedDeclaration, ComputedDeclarationWASM,
+};
+use crate::features::css_modules::{get_class_references, get_class_rename, prepare_class_rename};
+use crate::features::custom_properties::{
+    get_custom_property_definitions, get_custom_property_references, get_custom_property_rename,
+    prepare_custom_property_rename,
+};
+use crate::features::diagnostics::{get_diagnostics_with_parse, DiagnosticWASM};
+use crate::features::folding::{
+    get_folding_ranges_in_chunks, get_folding_ranges_with_budget, get_folding_ranges_with_parse,
+    FoldingOptions, FoldingRangeWASM,
+};
+use crate::features::formatting::{
+    get_formatting_edits_with_parse, get_range_formatting_edits_with_parse,
+};
+use crate::features::hover::{get_hover_with_parse, HoverOptions, HoverWASM};
+use crate::features::keyframes::{get_keyframes_definitions, get_keyframes_references};
+use crate::features::on_type_formatting::{get_on_type_formatting_edits, OnTypeFormattingOptions};
+use crate::features::palette::{get_color_palette, ColorCluster, DEFAULT_DELTA_E_THRESHOLD};
+use crate::features::rule_templates::{get_rule_templates, RuleTemplate, SnippetSettings};
+use crate::features::semantic_tokens::{
+    encode_semantic_tokens, get_semantic_tokens_edits, get_semantic_tokens_in_range_with_parse,
+    get_semantic_tokens_with_parse,
+};
+use crate::features::symbols::{
+    get_document_symbols_in_chunks, get_document_symbols_with_budget,
+    get_document_symbols_with_parse, DocumentSymbolOptions, DocumentSymbolWASM,
+};
+use crate::file_system::{
+    uri_scheme, FileSystemProvider, JsFileSystemProvider, JsTextDocumentContentProvider,
+    SchemeFileSystem, TextDocumentContentProvider, VirtualDocuments,
+};
+use crate::import_graph::GraphFormat;
+use crate::line_index::PositionEncoding;
+use crate::parser::parse_css_with_dialect;
+use crate::rules::important::ImportantUsage;
+use crate::rules::profiles::{ExitClass, SeverityProfiles};
+use crate::rules::{LintRule, RuleOptions, RuleRegistry};
+use crate::statistics::{Feature, Statistics};
+use crate::text_document::TextDocument;
+use crate::types::{PositionWASM, RangeWASM, TextEditWASM};
+use crate::workspace::{FileHandling, FileSettings, Workspace};
+use biome_css_parser::CssParse;
+use lsp_types::{
+    CodeAction, CodeActionKind, ColorInformation, CompletionClientCapabilities, CompletionItem,
+    Diagnostic, DocumentSymbol, FoldingRange, FoldingRangeClientCapabilities, FormattingOptions,
+    Hover, Location, Position, PositionEncodingKind, Range, SemanticTokens, SemanticTokensDelta,
+    SemanticTokensFullDeltaResult, TextEdit, Uri, WorkspaceEdit, WorkspaceFolder,
+    WorkspaceFoldersChangeEvent,
+};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+/// Serves the language features for documents, within the budget of each feature, and
+/// measures every run.
+///
+/// Open documents take precedence over the file system when features read other documents,
+/// and each URI scheme may be served by its own `FileSystemProvider`.
+///
+/// All the state lives in the service itself, so a process may hold several services, like
+/// one per worker of a Node worker pool, and requests for different documents may be
+/// interleaved freely. In WASM, a service can't be used again while one of its methods is
+/// running: JS callbacks, like file system providers and completion handlers, must not call
+/// back into the service that called them.
+#[wasm_bindgen]
+#[derive(Debug, Default)]
+pub struct LanguageService {
+    budgets: Budgets,
+    size_limits: SizeLimits,
+    statistics: Statistics,
+    documents: DocumentStore,
+    file_system: SchemeFileSystem,
+    /// The schemes of the virtual documents served by content providers, lowercased.
+    content_schemes: Vec<String>,
+    workspace: Workspace,
+    rules: RuleRegistry,
+    completion_providers: CompletionProviders,
+    code_action_providers: CodeActionRegistry,
+    hover_options: HoverOptions,
+    /// The folding capabilities of the client, like its range limit.
+    folding_capabilities: FoldingRangeClientCapabilities,
+    on_type_formatting_options: OnTypeFormattingOptions,
+    completion_options: CompletionOptions,
+    severity_profiles: SeverityProfiles,
+    snippet_settings: SnippetSettings,
+    css_data: Option<Arc<CssData>>,
+    /// The semantic tokens last sent for each open document, by URI, with their result ID.
+    semantic_tokens: HashMap<String, SemanticTokens>,
+    /// The number of semantic token results sent, giving the next result ID.
+    semantic_tokens_results: u64,
+    /// The encoding of the positions exchanged with the client.
+    position_encoding: PositionEncoding,
+}
+
+/// The completion providers registered by hosts.
+#[derive(Default)]
+struct CompletionProviders(Vec<Box<dyn CompletionProvider>>);
+
+impl std::fmt::Debug for CompletionProviders {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "CompletionProviders({})", self.0.len())
+    }
+}
+
+/// The results of the document features for one document, computed in a single call.
+#[derive(Debug, Clone)]
+pub struct DocumentAnalysis {
+    pub uri: String,
+    pub diagnostics: Vec<Diagnostic>,
+    pub symbols: Vec<DocumentSymbol>,
+    pub folding_ranges: Vec<FoldingRange>,
+    pub colors: Vec<ColorInformation>,
+}
+
+/// Represents the results of the document features for one document.
+#[wasm_bindgen(js_name = DocumentAnalysis)]
+pub struct DocumentAnalysisWASM(DocumentAnalysis);
+
+#[wasm_bindgen(js_class = DocumentAnalysis)]
+impl DocumentAnalysisWASM {
+    #[wasm_bindgen(getter)]
+    pub fn uri(&self) -> String {
+        self.0.uri.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn diagnostics(&self) -> Vec<DiagnosticWASM> {
+        self.0
+            .diagnostics
+            .iter()
+            .cloned()
+            .map(DiagnosticWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn symbols(&self) -> Vec<DocumentSymbolWASM> {
+        self.0
+            .symbols
+            .iter()
+            .cloned()
+            .map(DocumentSymbolWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn folding_ranges(&self) -> Vec<FoldingRangeWASM> {
+        self.0
+            .folding_ranges
+            .iter()
+            .cloned()
+            .map(FoldingRangeWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn colors(&self) -> Vec<ColorInformationWASM> {
+        self.0
+            .colors
+            .iter()
+            .cloned()
+            .map(ColorInformationWASM::from)
+            .collect()
+    }
+}
+
+impl From<DocumentAnalysis> for DocumentAnalysisWASM {
+    fn from(analysis: DocumentAnalysis) -> Self {
+        DocumentAnalysisWASM(analysis)
+    }
+}
+
+impl LanguageService {
+    /// Creates a language service with custom budgets.
+    ///
+    /// # Arguments
+    ///
+    /// * `budgets` - The `Budgets` of the features.
+    ///
+    /// # Returns
+    ///
+    /// * A `LanguageService` object.
+    pub fn with_budgets(budgets: Budgets) -> Self {
+        LanguageService {
+            budgets,
+            ..Default::default()
+        }
+    }
+
+    /// Creates a language service exchanging positions with the client in the given
+    /// encoding, negotiated with `PositionEncoding::negotiate`. It applies to the changes of
+    /// documents and to the positions and ranges of every feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `position_encoding` - The `PositionEncoding` of the positions.
+    ///
+    /// # Returns
+    ///
+    /// * A `LanguageService` object.
+    pub fn with_position_encoding(position_encoding: PositionEncoding) -> Self {
+        let mut rules = RuleRegistry::new();
+        rules.set_position_encoding(position_encoding);
+        LanguageService {
+            position_encoding,
+            documents: DocumentStore::with_position_encoding(position_encoding),
+            rules,
+            ..Default::default()
+        }
+    }
+
+    /// Returns the encoding of the positions exchanged with the client.
+    pub fn position_encoding(&self) -> PositionEncoding {
+        self.position_encoding
+    }
+
+    /// Returns the budgets of the features.
+    pub fn budgets(&self) -> &Budgets {
+        &self.budgets
+    }
+
+    /// Replaces the budget of a feature.
+    pub fn set_budget(&mut self, feature: Feature, budget: Budget) {
+        *self.budget_mut(feature) = budget;
+    }
+
+    /// Returns the limits on the size of the documents parsed at once.
+    pub fn size_limits(&self) -> &SizeLimits {
+        &self.size_limits
+    }
+
+    /// Sets the limits on the size of the documents parsed at once. Larger documents are
+    /// parsed in chunks by folding, symbols and colors, and skipped by the other features.
+    pub fn set_size_limits(&mut self, size_limits: SizeLimits) {
+        self.size_limits = size_limits;
+    }
+
+    fn budget_mut(&mut self, feature: Feature) -> &mut Budget {
+        match feature {
+            Feature::Folding => &mut self.budgets.folding,
+            Feature::Symbols => &mut self.budgets.symbols,
+            Feature::Colors => &mut self.budgets.colors,
+            Feature::Diagnostics => &mut self.budgets.diagnostics,
+        }
+    }
+
+    /// Returns the measurements of the features run so far.
+    pub fn statistics(&self) -> &Statistics {
+        &self.statistics
+    }
+
+    /// Returns the open documents.
+    pub fn documents(&self) -> &DocumentStore {
+        &self.documents
+    }
+
+    /// Returns the open documents, to open, update or close them.
+    pub fn documents_mut(&mut self) -> &mut DocumentStore {
+        &mut self.documents
+    }
+
+    /// Registers the provider serving the documents of a URI scheme, like `vscode-vfs`.
+    pub fn register_file_system(
+        &mut self,
+        scheme: &str,
+        provider: impl FileSystemProvider + 'static,
+    ) {
+        self.file_system.register(scheme, provider);
+    }
+
+    /// Registers the provider of the read-only virtual documents of a URI scheme, like the
+    /// output of a build tool, replacing the file system of the scheme.
+    pub fn register_content_provider(
+        &mut self,
+        scheme: &str,
+        provider: impl TextDocumentContentProvider + 'static,
+    ) {
+        self.file_system
+            .register(scheme, VirtualDocuments(provider));
+        let scheme = scheme.to_ascii_lowercase();
+        if !self.content_schemes.contains(&scheme) {
+            self.content_schemes.push(scheme);
+        }
+    }
+
+    /// Returns the schemes served by content providers, for the registration of
+    /// `workspace/textDocumentContent`.
+    pub fn content_schemes(&self) -> &[String] {
+        &self.content_schemes
+    }
+
+    /// Answers `workspace/textDocumentContent`: the contents of a virtual document.
+    ///
+    /// # Returns
+    ///
+    /// * The contents, or `None` if the URI isn't of a scheme served by a content provider
+    ///   or the provider doesn't know it.
+    pub fn text_document_content(&self, uri: &Uri) -> Option<String> {
+        if !self.content_schemes.contains(&uri_scheme(uri)) {
+            return None;
+        }
+        self.file_system.read_file(uri)
+    }
+
+    /// Runs the document features on open documents, each within its budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `uris` - The URIs of the documents to analyze.
+    ///
+    /// # Returns
+    ///
+    /// * A `DocumentAnalysis` for each open document, in the order of `uris`; documents that
+    ///   aren't open are skipped.
+    pub fn analyze_documents(&mut self, uris: &[&str]) -> Vec<DocumentAnalysis> {
+        let documents: Vec<TextDocument> = uris
+            .iter()
+            .filter_map(|uri| self.documents.get(uri).cloned())
+            .collect();
+        documents
+            .iter()
+            .map(|document| DocumentAnalysis {
+                uri: document.uri.clone(),
+                diagnostics: self.diagnostics(document).items,
+                symbols: self.document_symbols(document).items,
+                folding_ranges: self.folding_ranges(document).items,
+                colors: self.document_colors(document).items,
+            })
+            .collect()
+    }
+
+    /// Registers a lint rule, run after the built-in rules by `diagnostics`.
+    pub fn register_rule(&mut self, rule: impl LintRule + 'static) {
+        self.rules.register(rule);
+    }
+
+    /// Returns the lint rules registered on top of the built-in ones.
+    pub fn rules(&self) -> &RuleRegistry {
+        &self.rules
+    }
+
+    /// Sets the options of the built-in rules, like the fallbacks allowed by
+    /// `duplicate-property`.
+    pub fn set_rule_options(&mut self, options: RuleOptions) {
+        self.rules.set_options(options);
+    }
+
+    /// Registers a provider of completions, merged with the built-in ones by `completions`.
+    pub fn register_completion_provider(&mut self, provider: impl CompletionProvider + 'static) {
+        self.completion_providers.0.push(Box::new(provider));
+    }
+
+    /// Registers a provider of code actions, fixing the diagnostics of a rule or refactoring
+    /// ranges, see `code_actions`.
+    pub fn register_code_action_provider(&mut self, provider: impl CodeActionProvider + 'static) {
+        self.code_action_providers.register(provider);
+    }
+
+    /// Computes the completion items at a position of a document, including those of the
+    /// registered providers and the layers of the indexed stylesheets.
+    pub fn completions(&self, document: &TextDocument, position: Position) -> Vec<CompletionItem> {
+        self.completions_with_options(document, position, None)
+    }
+
+    /// Sets the options of completion.
+    pub fn set_completion_options(&mut self, options: CompletionOptions) {
+        self.completion_options = options;
+    }
+
+    /// Computes the completion items at a position of a document, with options superseding
+    /// the ones of the service for this call, like the settings of a single buffer.
+    pub fn completions_with_options(
+        &self,
+        document: &TextDocument,
+        position: Position,
+        options: Option<&CompletionOptions>,
+    ) -> Vec<CompletionItem> {
+        if self.size_limits.exceeds(&document.text) {
+            return Vec::new();
+        }
+        let options = options.unwrap_or(&self.completion_options);
+        let providers = &self.completion_providers.0;
+        let encoding = self.position_encoding;
+        let mut items = get_completions_with_parse(
+            document,
+            self.document_parse(document),
+            position,
+            self.css_data(),
+            providers,
+            encoding,
+        );
+        let layers = self.workspace.layers();
+        for item in get_layer_completions(document, position, &layers, encoding) {
+            if !items.iter().any(|known| known.label == item.label) {
+                items.push(item);
+            }
+        }
+        // The uses of a selector in the workspace add up with those in the document
+        let uri = document.uri.parse::<Uri>().ok();
+        let selectors = self.workspace.selectors(uri.as_ref());
+        if !selectors.is_empty() {
+            let selector_items = get_selector_completions(document, position, &selectors, encoding);
+            items.retain(|item| !selector_items.iter().any(|known| known.label == item.label));
+            items.extend(selector_items);
+        }
+        if !options.snippet_support {
+            items = strip_snippets(items);
+        }
+        if let Some(max_items) = options.max_items {
+            items.truncate(max_items);
+        }
+        items
+    }
+
+    /// Sets the completion capabilities of the client, its `completionItem.snippetSupport`,
+    /// keeping the other completion options.
+    pub fn set_completion_capabilities(&mut self, capabilities: &CompletionClientCapabilities) {
+        self.completion_options.snippet_support = capabilities
+            .completion_item
+            .as_ref()
+            .and_then(|item| item.snippet_support)
+            .unwrap_or_default();
+    }
+
+    /// Replaces the known properties, built with `CssDataBuilder`, for every feature. The
+    /// data may be shared by several services.
+    pub fn set_css_data(&mut self, data: impl Into<Arc<CssData>>) {
+        let data = data.into();
+        self.rules.set_data(Arc::clone(&data));
+        self.css_data = Some(data);
+    }
+
+    /// Returns the known properties, the built-in ones unless replaced.
+    pub fn css_data(&self) -> &CssData {
+        self.css_data.as_deref().unwrap_or(CssData::builtin())
+    }
+
+    /// Returns the indexed stylesheets of the workspace folders.
+    pub fn workspace(&self) -> &Workspace {
+        &self.workspace
+    }
+
+    /// Applies a `workspace/didChangeWorkspaceFolders` notification, dropping the documents
+    /// of removed folders and queuing the stylesheets of added ones for `index_workspace`.
+    pub fn change_workspace_folders(&mut self, event: &WorkspaceFoldersChangeEvent) {
+        self.workspace.change_folders(event, &self.file_system);
+    }
+
+    /// Sets the patterns of stylesheets that aren't linted, see `FileSettings`.
+    pub fn set_file_settings(&mut self, settings: FileSettings) {
+        self.workspace.set_file_settings(settings);
+    }
+
+    /// Sets the settings of the rule templates, like the breakpoints.
+    pub fn set_snippet_settings(&mut self, settings: SnippetSettings) {
+        self.snippet_settings = settings;
+    }
+
+    /// Lists the rules an editor can insert, see `get_rule_templates`.
+    pub fn rule_templates(&self) -> Vec<RuleTemplate> {
+        get_rule_templates(&self.snippet_settings)
+    }
+
+    /// Indexes queued stylesheets of the workspace, reading open documents first.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - The `Budget` of this run, counting one item per stylesheet.
+    ///
+    /// # Returns
+    ///
+    /// * The URIs indexed by this run, truncated if stylesheets are still queued.
+    pub fn index_workspace(&mut self, budget: &Budget) -> Budgeted<Uri> {
+        let file_system = self.documents.overlay(&self.file_system);
+        self.workspace.index_pending(budget, &file_system)
+    }
+
+    /// Sets the optional sections of the hover information.
+    pub fn set_hover_options(&mut self, options: HoverOptions) {
+        self.hover_options = options;
+    }
+
+    /// Computes the hover information at a position of a document.
+    pub fn hover(&self, document: &TextDocument, position: Position) -> Option<Hover> {
+        self.hover_with_options(document, position, None)
+    }
+
+    /// Computes the hover information at a position of a document, with options superseding
+    /// the ones of the service for this call.
+    pub fn hover_with_options(
+        &self,
+        document: &TextDocument,
+        position: Position,
+        options: Option<&HoverOptions>,
+    ) -> Option<Hover> {
+        if self.size_limits.exceeds(&document.text) {
+            return None;
+        }
+        let file_system = self.documents.overlay(&self.file_system);
+        let options = options.unwrap_or(&self.hover_options);
+        let parse = self.document_parse_or_new(document);
+        get_hover_with_parse(
+            document,
+            parse,
+            position,
+            &file_system,
+            options,
+            self.css_data(),
+            self.position_encoding,
+        )
+    }
+
+    /// Computes the action inlining the stylesheet imported at a position of a document,
+    /// reading open documents first, see `get_inline_import_action`.
+    pub fn inline_import_action(
+        &self,
+        document: &TextDocument,
+        position: Position,
+    ) -> Option<CodeAction> {
+        let file_system = self.documents.overlay(&self.file_system);
+        get_inline_import_action(document, position, &file_system, self.position_encoding)
+    }
+
+    /// Computes the code actions for a range of a document, see `get_code_actions`, with the
+    /// fixes and refactorings of the registered providers and the built-in refactorings,
+    /// like `inline_import_action`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `TextDocument`.
+    /// * `range` - The `Range` of the selection, or of the cursor.
+    /// * `diagnostics` - The diagnostics overlapping the range.
+    /// * `only` - The kinds of actions requested, or `None` for every kind.
+    ///
+    /// # Returns
+    ///
+    /// * A vector of `CodeAction` of the requested kinds.
+    pub fn code_actions(
+        &self,
+        document: &TextDocument,
+        range: Range,
+        diagnostics: &[Diagnostic],
+        only: Option<&[CodeActionKind]>,
+    ) -> Vec<CodeAction> {
+        let Some(uri) = document.lsp_uri() else {
+            return Vec::new();
+        };
+        let context = CodeActionContext {
+            uri: &uri,
+            document,
+            range,
+            diagnostics,
+            only,
+        };
+        let registry = &self.code_action_providers;
+        let mut actions =
+            get_code_actions_with_encoding(&context, registry, self.position_encoding);
+        if context.accepts(&CodeActionKind::REFACTOR_INLINE) {
+            actions.extend(self.inline_import_action(document, range.start));
+        }
+        actions
+    }
+
+    /// Finds the references to the symbol at a position of a document, across the indexed
+    /// stylesheets and the open documents, like the class names of CSS Modules, custom
+    /// properties or animation names.
+    pub fn references(
+        &self,
+        document: &TextDocument,
+        position: Position,
+        include_declaration: bool,
+    ) -> Vec<Location> {
+        let Ok(uri) = document.uri.parse::<Uri>() else {
+            return Vec::new();
+        };
+        let sources = self.stylesheets();
+        let sources = borrow_sources(&sources);
+        let mut locations = get_class_references(
+            &uri,
+            &document.text,
+            position,
+            &sources,
+            include_declaration,
+            self.position_encoding,
+        );
+        locations.extend(get_custom_property_references(
+            &uri,
+            &document.text,
+            position,
+            &sources,
+            include_declaration,
+            self.position_encoding,
+        ));
+        locations.extend(get_keyframes_references(
+            &uri,
+            &document.text,
+            position,
+            &sources,
+            include_declaration,
+            self.position_encoding,
+        ));
+        locations
+    }
+
+    /// Finds the definitions of the symbol at a position of a document, across the indexed
+    /// stylesheets and the open documents: the declarations of a custom property used in
+    /// `var()`, or the `@keyframes` of an animation named in `animation-name` or `animation`.
+    pub fn definition(&self, document: &TextDocument, position: Position) -> Vec<Location> {
+        let Ok(uri) = document.uri.parse::<Uri>() else {
+            return Vec::new();
+        };
+        let sources = self.stylesheets();
+        let sources = borrow_sources(&sources);
+        let encoding = self.position_encoding;
+        let mut locations =
+            get_custom_property_definitions(&uri, &document.text, position, &sources, encoding);
+        locations.extend(get_keyframes_definitions(
+            &uri,
+            &document.text,
+            position,
+            &sources,
+            encoding,
+        ));
+        locations
+    }
+
+    /// Renames the symbol at a position of a document, across the indexed stylesheets and
+    /// the open documents, see `references`.
+    ///
+    /// # Returns
+    ///
+    /// * The `WorkspaceEdit`, `Ok(None)` if there is nothing to rename at the position, or
+    ///   an error message for invalid names.
+    pub fn rename(
+        &self,
+        document: &TextDocument,
+        position: Position,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>, String> {
+        let Ok(uri) = document.uri.parse::<Uri>() else {
+            return Ok(None);
+        };
+        let sources = self.stylesheets();
+        let encoding = self.position_encoding;
+        if prepare_custom_property_rename(&document.text, position, encoding).is_some() {
+            return get_custom_property_rename(
+                &uri,
+                &document.text,
+                position,
+                new_name,
+                &borrow_sources(&sources),
+                encoding,
+            );
+        }
+        get_class_rename(
+            &uri,
+            &document.text,
+            position,
+            new_name,
+            &borrow_sources(&sources),
+            encoding,
+        )
+    }
+
+    /// Checks that the symbol at a position of a document can be renamed, a custom property
+    /// or the class of a CSS Module, see `rename`.
+    ///
+    /// # Returns
+    ///
+    /// * The `Range` of the name to rename, or `None` if there is nothing renameable at the
+    ///   position, like a property name.
+    pub fn prepare_rename(&self, document: &TextDocument, position: Position) -> Option<Range> {
+        let encoding = self.position_encoding;
+        prepare_custom_property_rename(&document.text, position, encoding).or_else(|| {
+            let uri = document.uri.parse::<Uri>().ok()?;
+            prepare_class_rename(&uri, &document.text, position, encoding)
+        })
+    }
+
+    /// Exports the import graph of the indexed stylesheets, with its cycles highlighted, see
+    /// `ImportGraph::export`.
+    pub fn export_import_graph(&self, format: GraphFormat) -> String {
+        self.workspace.import_graph().export(format)
+    }
+
+    /// Computes the semantic tokens of a document, with the status of properties from the
+    /// data of the service.
+    pub fn semantic_tokens(&self, document: &TextDocument) -> SemanticTokens {
+        if self.size_limits.exceeds(&document.text) {
+            return SemanticTokens::default();
+        }
+        let parse = self.document_parse_or_new(document);
+        get_semantic_tokens_with_parse(
+            &document.text,
+            parse,
+            self.css_data(),
+            self.position_encoding,
+        )
+    }
+
+    /// Computes the semantic tokens of a document with a result ID, and remembers them to
+    /// send only their edits next time, see `semantic_tokens_delta`.
+    pub fn semantic_tokens_full(&mut self, document: &TextDocument) -> SemanticTokens {
+        self.semantic_tokens_results += 1;
+        let tokens = SemanticTokens {
+            result_id: Some(self.semantic_tokens_results.to_string()),
+            ..self.semantic_tokens(document)
+        };
+        self.semantic_tokens
+            .insert(document.uri.clone(), tokens.clone());
+        tokens
+    }
+
+    /// Computes the edits from the semantic tokens last sent for a document to its current
+    /// tokens, with a new result ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `TextDocument`.
+    /// * `previous_result_id` - The result ID of the tokens the client has.
+    ///
+    /// # Returns
+    ///
+    /// * The edits, or all the tokens if those of `previous_result_id` are no longer known.
+    pub fn semantic_tokens_delta(
+        &mut self,
+        document: &TextDocument,
+        previous_result_id: &str,
+    ) -> SemanticTokensFullDeltaResult {
+        let previous = self
+            .semantic_tokens
+            .get(&document.uri)
+            .filter(|tokens| tokens.result_id.as_deref() == Some(previous_result_id))
+            .map(|tokens| tokens.data.clone());
+        let tokens = self.semantic_tokens_full(document);
+        match previous {
+            Some(previous) => SemanticTokensFullDeltaResult::TokensDelta(SemanticTokensDelta {
+                result_id: tokens.result_id,
+                edits: get_semantic_tokens_edits(&previous, &tokens.data),
+            }),
+            None => SemanticTokensFullDeltaResult::Tokens(tokens),
+        }
+    }
+
+    /// Computes the semantic tokens starting in a range of a document, like its visible
+    /// part, see `get_semantic_tokens_in_range`.
+    pub fn semantic_tokens_range(&self, document: &TextDocument, range: Range) -> SemanticTokens {
+        if self.size_limits.exceeds(&document.text) {
+            return SemanticTokens::default();
+        }
+        let parse = self.document_parse_or_new(document);
+        get_semantic_tokens_in_range_with_parse(
+            &document.text,
+            parse,
+            self.css_data(),
+            range,
+            self.position_encoding,
+        )
+    }
+
+    /// Formats a document, see `get_formatting_edits`. Documents over the size limit aren't
+    /// formatted.
+    pub fn formatting(
+        &self,
+        document: &TextDocument,
+        options: &FormattingOptions,
+    ) -> Vec<TextEdit> {
+        if self.size_limits.exceeds(&document.text) {
+            return Vec::new();
+        }
+        let parse = self.document_parse_or_new(document);
+        get_formatting_edits_with_parse(&document.text, parse, options, self.position_encoding)
+    }
+
+    /// Sets which edits on-type formatting makes. Their indentation comes from the options
+    /// of each request.
+    pub fn set_on_type_formatting_options(&mut self, options: OnTypeFormattingOptions) {
+        self.on_type_formatting_options = options;
+    }
+
+    /// Computes the edits made after a character is typed in a document, see
+    /// `get_on_type_formatting_edits`. Documents over the size limit aren't formatted.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `TextDocument`, with the character already typed.
+    /// * `position` - The `Position` of the cursor, after the typed character.
+    /// * `ch` - The typed character.
+    /// * `options` - The `FormattingOptions` sent by the client, for the indentation.
+    ///
+    /// # Returns
+    ///
+    /// * A vector of `TextEdit`, empty if there is nothing to do.
+    pub fn on_type_formatting(
+        &self,
+        document: &TextDocument,
+        position: Position,
+        ch: &str,
+        options: &FormattingOptions,
+    ) -> Vec<TextEdit> {
+        if self.size_limits.exceeds(&document.text) {
+            return Vec::new();
+        }
+        let options = OnTypeFormattingOptions {
+            tab_size: options.tab_size,
+            insert_spaces: options.insert_spaces,
+            position_encoding: self.position_encoding,
+            ..self.on_type_formatting_options
+        };
+        get_on_type_formatting_edits(document, position, ch, &options)
+    }
+
+    /// Formats the statements enclosing a range of a document, see
+    /// `get_range_formatting_edits`. Documents over the size limit aren't formatted.
+    pub fn range_formatting(
+        &self,
+        document: &TextDocument,
+        range: Range,
+        options: &FormattingOptions,
+    ) -> Vec<TextEdit> {
+        if self.size_limits.exceeds(&document.text) {
+            return Vec::new();
+        }
+        let parse = self.document_parse_or_new(document);
+        get_range_formatting_edits_with_parse(
+            &document.text,
+            parse,
+            range,
+            options,
+            self.position_encoding,
+        )
+    }
+
+    /// Previews the style of an element from the style rules of the indexed stylesheets and
+    /// the open documents, see `get_computed_style`.
+    ///
+    /// The order in which stylesheets are loaded isn't known, so they are taken in the order
+    /// of their URIs.
+    pub fn computed_style(&self, element: &str) -> Vec<ComputedDeclaration> {
+        let sources = self.stylesheets();
+        get_computed_style(element, &borrow_sources(&sources), self.position_encoding)
+    }
+
+    /// Extracts the colors of the indexed stylesheets and the open documents, and clusters
+    /// the near-identical ones, see `get_color_palette`.
+    pub fn color_palette(&self, threshold: f32) -> Vec<ColorCluster> {
+        let sources = self.stylesheets();
+        get_color_palette(&borrow_sources(&sources), threshold, self.position_encoding)
+    }
+
+    /// Returns the parse of a document if it is open with the same text, see
+    /// `DocumentStore::parse`. Open documents are reparsed incrementally as they are edited.
+    fn document_parse(&self, document: &TextDocument) -> Option<CssParse> {
+        let open = self.documents.get(document.uri.as_str())?;
+        if open.text != document.text {
+            return None;
+        }
+        self.documents.parse(document.uri.as_str())
+    }
+
+    /// Returns the stylesheet language of a document, the one of its stored parse if it is
+    /// open.
+    fn document_dialect(&self, document: &TextDocument) -> Dialect {
+        let language_id = self
+            .documents
+            .get(document.uri.as_str())
+            .map_or(&document.language_id, |open| &open.language_id);
+        Dialect::from_language_id(language_id)
+    }
+
+    /// Returns the parse of a document, the stored one if it is open and up to date, see
+    /// `document_parse`, or a new one in its stylesheet language.
+    fn document_parse_or_new(&self, document: &TextDocument) -> CssParse {
+        self.document_parse(document).unwrap_or_else(|| {
+            parse_css_with_dialect(&document.text, self.document_dialect(document))
+        })
+    }
+
+    /// Reads the indexed stylesheets and the open documents, sorted by URI.
+    fn stylesheets(&self) -> Vec<(Uri, String)> {
+        let file_system = self.documents.overlay(&self.file_system);
+        let mut uris: Vec<Uri> = self.workspace.indexed_uris().into_iter().cloned().collect();
+        uris.extend(
+            self.documents
+                .uris()
+                .into_iter()
+                .filter_map(|uri| uri.parse::<Uri>().ok()),
+        );
+        uris.sort();
+        uris.dedup();
+        uris.into_iter()
+            .filter_map(|uri| {
+                let source = file_system.read_file(&uri)?;
+                Some((uri, source))
+            })
+            .collect()
+    }
+
+    /// Sets the folding capabilities of the client, its `foldingRange.rangeLimit` and
+    /// `lineFoldingOnly`, applied to the folding ranges of every document.
+    pub fn set_folding_capabilities(&mut self, capabilities: FoldingRangeClientCapabilities) {
+        self.folding_capabilities = capabilities;
+    }
+
+    /// Computes the folding ranges of a document, within the folding budget.
+    pub fn folding_ranges(&mut self, document: &TextDocument) -> Budgeted<FoldingRange> {
+        self.folding_ranges_with_options(document, None)
+    }
+
+    /// Computes the folding ranges of a document, within the folding budget, with options
+    /// superseding the defaults of the document's language for this call.
+    pub fn folding_ranges_with_options(
+        &mut self,
+        document: &TextDocument,
+        options: Option<&FoldingOptions>,
+    ) -> Budgeted<FoldingRange> {
+        let default_options = FoldingOptions {
+            dialect: Dialect::from_language_id(&document.language_id),
+            range_limit: self
+                .folding_capabilities
+                .range_limit
+                .map(|limit| limit as usize),
+            line_folding_only: self
+                .folding_capabilities
+                .line_folding_only
+                .unwrap_or_default(),
+            position_encoding: self.position_encoding,
+            ..Default::default()
+        };
+        let options = options.unwrap_or(&default_options);
+        let budget = &self.budgets.folding;
+        let result = if self.size_limits.exceeds(&document.text) {
+            let chunk_size = self.size_limits.chunk_size;
+            get_folding_ranges_in_chunks(&document.text, options, budget, chunk_size)
+        } else {
+            // Options may read the document in another language than its stored parse
+            let parse = self
+                .document_parse(document)
+                .filter(|_| options.dialect == self.document_dialect(document));
+            match parse {
+                Some(parse) => {
+                    get_folding_ranges_with_parse(&document.text, parse, options, budget)
+                }
+                None => get_folding_ranges_with_budget(&document.text, options, budget),
+            }
+        };
+        self.statistics.record(Feature::Folding, &result);
+        result
+    }
+
+    /// Computes the document symbols of a document, within the symbols budget.
+    pub fn document_symbols(&mut self, document: &TextDocument) -> Budgeted<DocumentSymbol> {
+        self.document_symbols_with_options(document, None)
+    }
+
+    /// Computes the document symbols of a document, within the symbols budget, with options
+    /// superseding the defaults for this call.
+    pub fn document_symbols_with_options(
+        &mut self,
+        document: &TextDocument,
+        options: Option<&DocumentSymbolOptions>,
+    ) -> Budgeted<DocumentSymbol> {
+        let default_options = DocumentSymbolOptions {
+            position_encoding: self.position_encoding,
+            ..Default::default()
+        };
+        let options = options.unwrap_or(&default_options);
+        let budget = &self.budgets.symbols;
+        let result = if self.size_limits.exceeds(&document.text) {
+            let chunk_size = self.size_limits.chunk_size;
+            get_document_symbols_in_chunks(&document.text, options, budget, chunk_size)
+        } else {
+            match self.document_parse(document) {
+                Some(parse) => {
+                    get_document_symbols_with_parse(&document.text, parse, options, budget)
+                }
+                None => get_document_symbols_with_budget(&document.text, options, budget),
+            }
+        };
+        self.statistics.record(Feature::Symbols, &result);
+        result
+    }
+
+    /// Finds the colors of a document, within the colors budget.
+    pub fn document_colors(&mut self, document: &TextDocument) -> Budgeted<ColorInformation> {
+        let budget = &self.budgets.colors;
+        let encoding = self.position_encoding;
+        let result = if self.size_limits.exceeds(&document.text) {
+            let chunk_size = self.size_limits.chunk_size;
+            get_document_colors_in_chunks(&document.text, budget, chunk_size, encoding)
+        } else {
+            let parse = self.document_parse_or_new(document);
+            get_document_colors_with_parse(&document.text, parse, budget, encoding)
+        };
+        self.statistics.record(Feature::Colors, &result);
+        result
+    }
+
+    /// Sets the severities of diagnostics in the editor and in CI.
+    pub fn set_severity_profiles(&mut self, profiles: SeverityProfiles) {
+        self.severity_profiles = profiles;
+    }
+
+    /// Computes the diagnostics of a document, within the diagnostics budget, with the
+    /// severities of the editor profile. Documents that aren't linted have none, and those
+    /// exceeding the size limits a single diagnostic telling so.
+    pub fn diagnostics(&mut self, document: &TextDocument) -> Budgeted<Diagnostic> {
+        let uri = document
+            .lsp_uri()
+            .filter(|uri| self.workspace.handling(uri) == FileHandling::Lint);
+        let mut result = match uri {
+            Some(_) if self.size_limits.exceeds(&document.text) => {
+                Budget::UNLIMITED.start().finish(vec![size_limit_diagnostic(
+                    &document.text,
+                    &self.size_limits,
+                )])
+            }
+            Some(uri) => {
+                let parse = self.document_parse_or_new(document);
+                get_diagnostics_with_parse(
+                    &uri,
+                    &document.text,
+                    parse,
+                    self.document_dialect(document),
+                    &self.rules,
+                    &self.budgets.diagnostics,
+                )
+            }
+            None => Budget::UNLIMITED.start().finish(Vec::new()),
+        };
+        result.items = self.severity_profiles.editor.apply(result.items);
+        self.statistics.record(Feature::Diagnostics, &result);
+        result
+    }
+
+    /// Checks documents in CI, without budget, with the severities of the CI profile.
+    /// Documents that aren't linted are skipped, and those exceeding the size limits get a
+    /// single diagnostic telling so, like in `diagnostics`.
+    ///
+    /// # Returns
+    ///
+    /// * The diagnostics of each document with a valid URI, and the `ExitClass` of the run.
+    pub fn check_documents(
+        &self,
+        documents: &[TextDocument],
+    ) -> (Vec<(Uri, Vec<Diagnostic>)>, ExitClass) {
+        let results: Vec<(Uri, Vec<Diagnostic>)> = documents
+            .iter()
+            .filter_map(|document| {
+                let uri = document
+                    .lsp_uri()
+                    .filter(|uri| self.workspace.handling(uri) == FileHandling::Lint)?;
+                let diagnostics = if self.size_limits.exceeds(&document.text) {
+                    vec![size_limit_diagnostic(&document.text, &self.size_limits)]
+                } else {
+                    let dialect = Dialect::from_language_id(&document.language_id);
+                    get_diagnostics_with_parse(
+                        &uri,
+                        &document.text,
+                        parse_css_with_dialect(&document.text, dialect),
+                        dialect,
+                        &self.rules,
+                        &Budget::UNLIMITED,
+                    )
+                    .items
+                };
+                Some((uri, self.severity_profiles.ci.apply(diagnostics)))
+            })
+            .collect();
+        let exit_class =
+            ExitClass::from_diagnostics(results.iter().flat_map(|(_, diagnostics)| diagnostics));
+        (results, exit_class)
+    }
+}
+
+fn borrow_sources(sources: &[(Uri, String)]) -> Vec<(Uri, &str)> {
+    sources
+        .iter()
+        .map(|(uri, source)| (uri.clone(), source.as_str()))
+        .collect()
+}
+
+#[wasm_bindgen]
+impl LanguageService {
+    /// Creates a language service with the default budgets.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> LanguageService {
+        LanguageService::default()
+    }
+
+    /// Creates a language service exchanging positions in an encoding of the protocol, like
+    /// `utf-8`, see `with_position_encoding`.
+    ///
+    /// # Returns
+    ///
+    /// * The `LanguageService`, or `undefined` if the encoding isn't supported.
+    #[wasm_bindgen(js_name = withPositionEncoding)]
+    pub fn with_position_encoding_wasm(encoding: &str) -> Option<LanguageService> {
+        let kind = PositionEncodingKind::from(encoding.to_string());
+        PositionEncoding::from_kind(&kind).map(LanguageService::with_position_encoding)
+    }
+
+    /// Returns the encoding of the positions exchanged with the client, like `utf-16`.
+    #[wasm_bindgen(getter, js_name = positionEncoding)]
+    pub fn position_encoding_wasm(&self) -> String {
+        self.position_encoding.kind().as_str().to_string()
+    }
+
+    /// Sets the limits on the size of the documents parsed at once, in bytes, see
+    /// `set_size_limits`.
+    #[wasm_bindgen(js_name = setSizeLimits)]
+    pub fn set_size_limits_wasm(&mut self, max_document_size: u32, chunk_size: u32) {
+        self.set_size_limits(SizeLimits {
+            max_document_size: max_document_size as usize,
+            chunk_size: chunk_size as usize,
+        });
+    }
+
+    /// Sets the folding capabilities of the client, its `foldingRange.rangeLimit`, if any,
+    /// and `lineFoldingOnly`, see `set_folding_capabilities`.
+    #[wasm_bindgen(js_name = setFoldingCapabilities)]
+    pub fn set_folding_capabilities_wasm(
+        &mut self,
+        range_limit: Option<u32>,
+        line_folding_only: bool,
+    ) {
+        self.set_folding_capabilities(FoldingRangeClientCapabilities {
+            range_limit,
+            line_folding_only: Some(line_folding_only),
+            ..Default::default()
+        });
+    }
+
+    /// Replaces the budget of a feature, with durations in milliseconds.
+    #[wasm_bindgen(js_name = setBudget)]
+    pub fn set_budget_wasm(
+        &mut self,
+        feature: Feature,
+        max_duration_ms: Option<u32>,
+        max_items: Option<u32>,
+    ) {
+        self.set_budget(
+            feature,
+            Budget {
+                max_duration: max_duration_ms.map(|ms| Duration::from_millis(ms.into())),
+                max_items: max_items.map(|max_items| max_items as usize),
+            },
+        );
+    }
+
+    /// Opens a document, replacing any open document with the same URI. Its features are
+    /// then computed from its stored parse, kept up to date as it changes.
+    #[wasm_bindgen(js_name = openDocument)]
+    pub fn open_document(&mut self, document: &TextDocument) {
+        self.documents.open(document.clone());
+    }
+
+    /// Opens several documents in one call.
+    #[wasm_bindgen(js_name = openDocuments)]
+    pub fn open_documents(&mut self, documents: Vec<TextDocument>) {
+        for document in documents {
+            self.documents.open(document);
+        }
+    }
+
+    /// Runs the document features on several open documents in one call, to amortize the
+    /// cost of crossing the WASM boundary.
+    #[wasm_bindgen(js_name = analyzeDocuments)]
+    pub fn analyze_documents_wasm(&mut self, uris: Vec<String>) -> Vec<DocumentAnalysisWASM> {
+        let uris: Vec<&str> = uris.iter().map(String::as_str).collect();
+        self.analyze_documents(&uris)
+            .into_iter()
+            .map(DocumentAnalysisWASM::from)
+            .collect()
+    }
+
+    /// Replaces the text of an open document, see `DocumentStore::update`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the document isn't open or the version is older than the stored one.
+    #[wasm_bindgen(js_name = updateDocument)]
+    pub fn update_document(&mut self, uri: &str, version: i64, text: &str) -> bool {
+        self.documents.update(uri, version, text)
+    }
+
+    /// Applies the content changes of a `textDocument/didChange` notification, as JSON.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the changes are invalid, the document isn't open or the version is older
+    ///   than the stored one.
+    #[wasm_bindgen(js_name = applyChanges)]
+    pub fn apply_changes_wasm(&mut self, uri: &str, version: i64, changes: &str) -> bool {
+        match serde_json::from_str::<Vec<lsp_types::TextDocumentContentChangeEvent>>(changes) {
+            Ok(changes) => self.documents.apply_changes(uri, version, &changes),
+            Err(_) => false,
+        }
+    }
+
+    /// Keeps the last `limit` versions of each open document, to replay their edits.
+    #[wasm_bindgen(js_name = setHistoryLimit)]
+    pub fn set_history_limit(&mut self, limit: usize) {
+        self.documents.set_history_limit(limit);
+    }
+
+    /// Records the text of a retained version as the client has it, to check the replay of
+    /// its edits against, see `DocumentStore::report_text`.
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the version is retained in the history of the document.
+    #[wasm_bindgen(js_name = reportDocumentText)]
+    pub fn report_document_text(&mut self, uri: &str, version: i64, text: &str) -> bool {
+        self.documents.report_text(uri, version, text)
+    }
+
+    /// Replays the retained edits of a document, see `DocumentStore::replay`.
+    ///
+    /// # Returns
+    ///
+    /// * The history and the issues found, as `{ history, issues }` JSON, or `undefined` if
+    ///   there is no history for the document.
+    #[wasm_bindgen(js_name = replayDocument)]
+    pub fn replay_document(&self, uri: &str) -> Option<String> {
+        let issues = self.documents.replay(uri)?;
+        let history = self.documents.history(uri)?;
+        serde_json::to_string(&serde_json::json!({ "history": history, "issues": issues })).ok()
+    }
+
+    /// Closes a document, dropping its parse and the semantic tokens sent for it.
+    #[wasm_bindgen(js_name = closeDocument)]
+    pub fn close_document(&mut self, uri: &str) {
+        self.documents.close(uri);
+        self.semantic_tokens.remove(uri);
+    }
+
+    /// Registers a JS provider serving the documents of a URI scheme.
+    #[wasm_bindgen(js_name = registerFileSystem)]
+    pub fn register_file_system_wasm(&mut self, scheme: &str, provider: JsFileSystemProvider) {
+        self.register_file_system(scheme, provider);
+    }
+
+    /// Registers a JS provider serving the virtual documents of a URI scheme.
+    #[wasm_bindgen(js_name = registerContentProvider)]
+    pub fn register_content_provider_wasm(
+        &mut self,
+        scheme: &str,
+        provider: JsTextDocumentContentProvider,
+    ) {
+        self.register_content_provider(scheme, provider);
+    }
+
+    #[wasm_bindgen(js_name = getTextDocumentContent)]
+    pub fn text_document_content_wasm(&self, uri: &str) -> Option<String> {
+        self.text_document_content(&uri.parse().ok()?)
+    }
+
+    /// Adds and removes workspace folders, given by URI.
+    #[wasm_bindgen(js_name = didChangeWorkspaceFolders)]
+    pub fn change_workspace_folders_wasm(&mut self, added: Vec<String>, removed: Vec<String>) {
+        let folders = |uris: Vec<String>| -> Vec<WorkspaceFolder> {
+            uris.iter()
+                .filter_map(|uri| {
+                    Some(WorkspaceFolder {
+                        uri: uri.parse().ok()?,
+                        name: uri.trim_end_matches('/').rsplit('/').next()?.to_string(),
+                    })
+                })
+                .collect()
+        };
+        self.change_workspace_folders(&WorkspaceFoldersChangeEvent {
+            added: folders(added),
+            removed: folders(removed),
+        });
+    }
+
+    /// Indexes queued stylesheets of the workspace for at most `max_duration_ms`, returning
+    /// the number of stylesheets still queued, so hosts can call it again when idle.
+    #[wasm_bindgen(js_name = indexWorkspace)]
+    pub fn index_workspace_wasm(&mut self, max_duration_ms: Option<u32>) -> u32 {
+        self.index_workspace(&Budget {
+            max_duration: max_duration_ms.map(|ms| Duration::from_millis(ms.into())),
+            max_items: None,
+        });
+        self.workspace.pending_len() as u32
+    }
+
+    /// Registers a JS function whose completions are merged with the built-in ones.
+    #[wasm_bindgen(js_name = onCompletion)]
+    pub fn on_completion(&mut self, handler: JsCompletionHandler) {
+        self.register_completion_provider(handler);
+    }
+
+    /// Registers the provider expanding abbreviations like `m10` to `margin: 10px;`, with
+    /// the abbreviations of a custom data file, as JSON, added to the built-in ones.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the JSON is invalid, leaving the providers unchanged.
+    #[wasm_bindgen(js_name = enableAbbreviations)]
+    pub fn enable_abbreviations(&mut self, custom_data: Option<String>) -> bool {
+        let provider = match custom_data {
+            Some(json) => AbbreviationProvider::new().custom_data(&json),
+            None => Ok(AbbreviationProvider::new()),
+        };
+        match provider {
+            Ok(provider) => {
+                self.register_completion_provider(provider);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Adds the properties of a VS Code custom data file, as JSON, to the current ones, the
+    /// built-in ones or those set with `set_css_data` or earlier custom data.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the JSON is invalid, leaving the properties unchanged.
+    #[wasm_bindgen(js_name = setCustomData)]
+    pub fn set_custom_data(&mut self, json: &str) -> bool {
+        match CssDataBuilder::from_data(self.css_data()).custom_data(json) {
+            Ok(builder) => {
+                self.set_css_data(builder.build());
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    #[wasm_bindgen(js_name = getCompletions)]
+    pub fn completions_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+        max_items: Option<u32>,
+    ) -> Vec<CompletionItemWASM> {
+        let options = max_items.map(|max_items| CompletionOptions {
+            max_items: Some(max_items as usize),
+            ..self.completion_options
+        });
+        self.completions_with_options(document, position.into(), options.as_ref())
+            .into_iter()
+            .map(CompletionItemWASM::from)
+            .collect()
+    }
+
+    /// Returns the references to the symbol at a position as JSON `Location`s.
+    #[wasm_bindgen(js_name = getReferences)]
+    pub fn references_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+        include_declaration: bool,
+    ) -> String {
+        let locations = self.references(document, position.into(), include_declaration);
+        serde_json::to_string(&locations).unwrap_or_default()
+    }
+
+    /// Returns the definitions of the symbol at a position as JSON `Location`s.
+    #[wasm_bindgen(js_name = getDefinition)]
+    pub fn definition_wasm(&self, document: &TextDocument, position: &PositionWASM) -> String {
+        let locations = self.definition(document, position.into());
+        serde_json::to_string(&locations).unwrap_or_default()
+    }
+
+    /// Returns the range of the symbol to rename at a position, or `undefined` if there is
+    /// nothing renameable at the position.
+    #[wasm_bindgen(js_name = prepareRename)]
+    pub fn prepare_rename_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+    ) -> Option<RangeWASM> {
+        self.prepare_rename(document, position.into())
+            .map(RangeWASM::from)
+    }
+
+    /// Renames the symbol at a position, returning the `WorkspaceEdit` as JSON, or
+    /// `undefined` if there is nothing to rename or the name is invalid.
+    #[wasm_bindgen(js_name = rename)]
+    pub fn rename_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+        new_name: &str,
+    ) -> Option<String> {
+        let edit = self.rename(document, position.into(), new_name).ok()??;
+        serde_json::to_string(&edit).ok()
+    }
+
+    /// Returns the code actions for a range of a document as JSON, see `code_actions`.
+    ///
+    /// # Arguments
+    ///
+    /// * `document` - The `TextDocument`.
+    /// * `range` - The `Range` of the selection, or of the cursor.
+    /// * `diagnostics` - The diagnostics overlapping the range, as JSON.
+    /// * `only` - The kinds of actions requested, like `quickfix`, or `undefined` for every
+    ///   kind.
+    #[wasm_bindgen(js_name = getCodeActions)]
+    pub fn code_actions_wasm(
+        &self,
+        document: &TextDocument,
+        range: &RangeWASM,
+        diagnostics: &str,
+        only: Option<Vec<String>>,
+    ) -> String {
+        let diagnostics: Vec<Diagnostic> = serde_json::from_str(diagnostics).unwrap_or_default();
+        let only: Option<Vec<CodeActionKind>> =
+            only.map(|only| only.into_iter().map(CodeActionKind::from).collect());
+        let actions = self.code_actions(document, range.into(), &diagnostics, only.as_deref());
+        serde_json::to_string(&actions).unwrap_or_default()
+    }
+
+    /// Returns the action inlining the stylesheet imported at a position, or `undefined` if
+    /// it can't be inlined.
+    #[wasm_bindgen(js_name = getInlineImportAction)]
+    pub fn inline_import_action_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+    ) -> Option<CodeActionWASM> {
+        self.inline_import_action(document, position.into())
+            .map(CodeActionWASM::from)
+    }
+
+    #[wasm_bindgen(js_name = exportImportGraph)]
+    pub fn export_import_graph_wasm(&self, format: GraphFormat) -> String {
+        self.export_import_graph(format)
+    }
+
+    /// Returns the semantic tokens of a document, five integers per token as in the
+    /// protocol, see `get_semantic_tokens_legend_wasm`.
+    #[wasm_bindgen(js_name = getSemanticTokens)]
+    pub fn semantic_tokens_wasm(&self, document: &TextDocument) -> Vec<u32> {
+        encode_semantic_tokens(&self.semantic_tokens(document))
+    }
+
+    /// Returns the semantic tokens starting in a range of a document, five integers per
+    /// token as in the protocol.
+    #[wasm_bindgen(js_name = getSemanticTokensRange)]
+    pub fn semantic_tokens_range_wasm(
+        &self,
+        document: &TextDocument,
+        range: &RangeWASM,
+    ) -> Vec<u32> {
+        encode_semantic_tokens(&self.semantic_tokens_range(document, range.into()))
+    }
+
+    /// Returns the semantic tokens of a document with a result ID, as a JSON
+    /// `SemanticTokens`, see `semantic_tokens_full`.
+    #[wasm_bindgen(js_name = getSemanticTokensFull)]
+    pub fn semantic_tokens_full_wasm(&mut self, document: &TextDocument) -> String {
+        serde_json::to_string(&self.semantic_tokens_full(document)).unwrap_or_default()
+    }
+
+    /// Returns the edits of the semantic tokens of a document since those of a result ID,
+    /// as a JSON `SemanticTokensDelta`, or all the tokens as a JSON `SemanticTokens` if
+    /// they are no longer known, see `semantic_tokens_delta`.
+    #[wasm_bindgen(js_name = getSemanticTokensDelta)]
+    pub fn semantic_tokens_delta_wasm(
+        &mut self,
+        document: &TextDocument,
+        previous_result_id: &str,
+    ) -> String {
+        let result = self.semantic_tokens_delta(document, previous_result_id);
+        serde_json::to_string(&result).unwrap_or_default()
+    }
+
+    /// Returns the edits pretty-printing a document, indented with `tab_size` spaces or
+    /// with tabs, see `formatting`.
+    #[wasm_bindgen(js_name = getFormattingEdits)]
+    pub fn formatting_wasm(
+        &self,
+        document: &TextDocument,
+        tab_size: u32,
+        insert_spaces: bool,
+    ) -> Vec<TextEditWASM> {
+        let options = FormattingOptions {
+            tab_size,
+            insert_spaces,
+            ..Default::default()
+        };
+        self.formatting(document, &options)
+            .into_iter()
+            .map(TextEditWASM::from)
+            .collect()
+    }
+
+    /// Returns the edits pretty-printing the statements enclosing a range of a document,
+    /// indented with `tab_size` spaces or with tabs, see `range_formatting`.
+    #[wasm_bindgen(js_name = getRangeFormattingEdits)]
+    pub fn range_formatting_wasm(
+        &self,
+        document: &TextDocument,
+        range: &RangeWASM,
+        tab_size: u32,
+        insert_spaces: bool,
+    ) -> Vec<TextEditWASM> {
+        let options = FormattingOptions {
+            tab_size,
+            insert_spaces,
+            ..Default::default()
+        };
+        self.range_formatting(document, range.into(), &options)
+            .into_iter()
+            .map(TextEditWASM::from)
+            .collect()
+    }
+
+    /// Sets whether the client supports snippets in completion items, see
+    /// `set_completion_capabilities`.
+    #[wasm_bindgen(js_name = setSnippetSupport)]
+    pub fn set_snippet_support(&mut self, enabled: bool) {
+        self.completion_options.snippet_support = enabled;
+    }
+
+    /// Enables or disables the computed style preview when hovering selectors.
+    #[wasm_bindgen(js_name = setComputedStylePreview)]
+    pub fn set_computed_style_preview(&mut self, enabled: bool) {
+        self.hover_options.computed_style = enabled;
+    }
+
+    /// Enables or disables the opt-in `important-usage` rule, counting the `!important`
+    /// flags of each document.
+    #[wasm_bindgen(js_name = setImportantUsage)]
+    pub fn set_important_usage(&mut self, enabled: bool) {
+        if enabled {
+            self.rules.register(ImportantUsage);
+        } else {
+            self.rules.unregister(ImportantUsage.code());
+        }
+    }
+
+    #[wasm_bindgen(js_name = getComputedStyle)]
+    pub fn computed_style_wasm(&self, element: &str) -> Vec<ComputedDeclarationWASM> {
+        self.computed_style(element)
+            .into_iter()
+            .map(ComputedDeclarationWASM::from)
+            .collect()
+    }
+
+    /// Returns the clusters of near-identical colors of the workspace as JSON, see
+    /// `get_color_palette_wasm`.
+    #[wasm_bindgen(js_name = getColorPalette)]
+    pub fn color_palette_wasm(&self, threshold: Option<f32>) -> String {
+        let clusters = self.color_palette(threshold.unwrap_or(DEFAULT_DELTA_E_THRESHOLD));
+        serde_json::to_string(&clusters).unwrap_or_default()
+    }
+
+    #[wasm_bindgen(js_name = getHover)]
+    pub fn hover_wasm(
+        &self,
+        document: &TextDocument,
+        position: &PositionWASM,
+        prefer_plaintext: Option<bool>,
+    ) -> Option<HoverWASM> {
+        let options = prefer_plaintext.map(|prefer_plaintext| HoverOptions {
+            prefer_plaintext,
+            ..self.hover_options
+        });
+        self.hover_with_options(document, position.into(), options.as_ref())
+            .map(HoverWASM::from)
+    }
+
+    /// Returns whether the last results of a feature were truncated by its budget.
+    #[wasm_bindgen(js_name = isTruncated)]
+    pub fn is_truncated(&self, feature: Feature) -> bool {
+        self.statistics
+            .get(feature)
+            .is_some_and(|statistics| statistics.last_truncated)
+    }
+
+    #[wasm_bindgen(js_name = getFoldingRanges)]
+    pub fn folding_ranges_wasm(&mut self, document: &TextDocument) -> Vec<FoldingRangeWASM> {
+        let result = self.folding_ranges(document);
+        result
+            .items
+            .into_iter()
+            .map(FoldingRangeWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = getDocumentSymbols)]
+    pub fn document_symbols_wasm(&mut self, document: &TextDocument) -> Vec<DocumentSymbolWASM> {
+        let result = self.document_symbols(document);
+        result
+            .items
+            .into_iter()
+            .map(DocumentSymbolWASM::from)
+            .collect()
+    }
+
+    #[wasm_bindgen(js_name = getDocumentColors)]
+    pub fn document_colors_wasm(&mut self, document: &TextDocument) -> Vec<ColorInformationWASM> {
+        let result = self.document_colors(document);
+        result
+            .items
+            .into_iter()
+            .map(ColorInformationWASM::from)
+            .collect()
+    }
+
+    /// Sets the severities of diagnostics from the `profiles` section of the settings, as
+    /// JSON, see `SeverityProfiles`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the section is invalid, leaving the profiles unchanged.
+    #[wasm_bindgen(js_name = setSeverityProfiles)]
+    pub fn set_severity_profiles_wasm(&mut self, profiles: &str) -> bool {
+        match serde_json::from_str(profiles) {
+            Ok(profiles) => {
+                self.set_severity_profiles(profiles);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Sets the options of the built-in rules from the `rules` section of the settings, as
+    /// JSON, like `{ "duplicateProperty": { "allowFallbacks": false } }`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the section is invalid, leaving the options unchanged.
+    #[wasm_bindgen(js_name = setRuleOptions)]
+    pub fn set_rule_options_wasm(&mut self, options: &str) -> bool {
+        match serde_json::from_str(options) {
+            Ok(options) => {
+                self.set_rule_options(options);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Sets the patterns of stylesheets that aren't linted from the settings, as JSON, like
+    /// `{ "indexOnly": ["**/node_modules/**"], "ignore": ["**/dist/**"] }`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the settings are invalid, leaving them unchanged.
+    #[wasm_bindgen(js_name = setFileSettings)]
+    pub fn set_file_settings_wasm(&mut self, settings: &str) -> bool {
+        match serde_json::from_str(settings) {
+            Ok(settings) => {
+                self.set_file_settings(settings);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Sets the `snippets` section of the settings, as JSON, like
+    /// `{ "breakpoints": [{ "name": "tablet", "minWidth": "768px" }] }`.
+    ///
+    /// # Returns
+    ///
+    /// * `false` if the settings are invalid, leaving them unchanged.
+    #[wasm_bindgen(js_name = setSnippetSettings)]
+    pub fn set_snippet_settings_wasm(&mut self, settings: &str) -> bool {
+        match serde_json::from_str(settings) {
+            Ok(settings) => {
+                self.set_snippet_settings(settings);
+                true
+            }
+            Err(_) => false,
+        }
+    }
+
+    /// Lists the rules an editor can insert as JSON, for an "Insert CSS snippet" command.
+    #[wasm_bindgen(js_name = getRuleTemplates)]
+    pub fn rule_templates_wasm(&self) -> String {
+        serde_json::to_string(&self.rule_templates()).unwrap_or_default()
+    }
+
+    /// Checks documents in CI with the CI profile.
+    ///
+    /// # Returns
+    ///
+    /// * The exit code of the run: 0 when clean, 1 with warnings and 2 with errors.
+    #[wasm_bindgen(js_name = getExitCode)]
+    pub fn exit_code_wasm(&self, documents: Vec<TextDocument>) -> u8 {
+        self.check_documents(&documents).1.code()
+    }
+
+    #[wasm_bindgen(js_name = getDiagnostics)]
+    pub fn diagnostics_wasm(&mut self, document: &TextDocument) -> Vec<DiagnosticWASM> {
+        let result = self.diagnostics(document);
+        result.items.into_iter().map(DiagnosticWASM::from).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lsp_types::DiagnosticSeverity;
+
+    #[test]
+    fn test_language_service_records_truncation() {
+        let mut service = LanguageService::new();
+        service.set_budget(
+            Feature::Folding,
+            Budget {
+                max_duration: None,
+                max_items: Some(1),
+            },
+        );
+        let document = TextDocument::new("file:///a.css", "css", 1, ".a {\n}\n.b {\n}\n");
+
+        let folding_ranges = service.folding_ranges(&document);
+        assert_eq!(folding_ranges.items.len(), 1);
+        assert!(service.is_truncated(Feature::Folding));
+
+        let symbols = service.document_symbols(&document);
+        assert_eq!(symbols.items.len(), 2, "Symbols have their own budget");
+        assert!(!service.is_truncated(Feature::Symbols));
+        assert_eq!(
+            service.statistics().truncated_features(),
+            vec![Feature::Folding]
+        );
+    }
+
+    #[test]
+    fn test_language_service_hover_reads_open_documents() {
+        let mut service = LanguageService::new();
+        service.open_document(&TextDocument::new("untitled:Untitled-2", "css", 1, ".a {}"));
+        let document = TextDocument::new(
+            "vscode-vfs://github/o/r/main.css",
+            "css",
+            1,
+            "@import 'a.css';",
+        );
+        service.open_document(&TextDocument::new(
+            "vscode-vfs://github/o/r/a.css",
+            "css",
+            1,
+            ".a {}",
+        ));
+
+        let hover = service.hover(&document, Position::new(0, 0)).unwrap();
+        let lsp_types::HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected Markdown contents");
+        };
+        assert!(markup.value.contains("1 rule"), "{}", markup.value);
+    }
+
+    #[test]
+    fn test_language_service_indexes_workspace_folders() {
+        let mut service = LanguageService::new();
+        let root = std::env::temp_dir().join(format!("csslsrs-workspace-{}", std::process::id()));
+        std::fs::create_dir_all(root.join("node_modules")).unwrap();
+        std::fs::write(root.join("a.css"), ".a {}").unwrap();
+        std::fs::write(root.join("node_modules/b.css"), ".b {}").unwrap();
+        let folder = crate::file_system::path_to_uri(&root).unwrap();
+
+        service.change_workspace_folders_wasm(vec![folder.as_str().to_string()], vec![]);
+        assert_eq!(service.index_workspace_wasm(None), 0);
+        assert_eq!(service.workspace().indexed_uris().len(), 1);
+        let graph = service.export_import_graph(GraphFormat::Dot);
+        assert!(graph.contains(&format!("\"{}/a.css\";", folder.as_str())));
+
+        service.change_workspace_folders_wasm(vec![], vec![folder.as_str().to_string()]);
+        assert!(service.workspace().indexed_uris().is_empty());
+        std::fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn test_language_service_interleaved_documents() {
+        let mut service = LanguageService::new();
+        service.open_documents(
+            (0..20)
+                .map(|idx| {
+                    let text = format!(".a{idx} {{\n  color: #f00;\n}}\n");
+                    TextDocument::new(&format!("file:///{idx}.css"), "css", 1, &text)
+                })
+                .collect(),
+        );
+        let other = TextDocument::new("file:///other.css", "css", 1, "a {\n}\n");
+        assert_eq!(service.folding_ranges(&other).items.len(), 1);
+        assert!(service.update_document("file:///3.css", 2, "a { margin }"));
+
+        let analyses =
+            service.analyze_documents(&["file:///3.css", "file:///closed.css", "file:///4.css"]);
+        assert_eq!(analyses.len(), 2);
+        assert_eq!(analyses[0].uri, "file:///3.css");
+        assert!(!analyses[0].diagnostics.is_empty());
+        assert!(analyses[1].diagnostics.is_empty());
+        assert_eq!(analyses[1].symbols[0].name, ".a4");
+        assert_eq!(analyses[1].colors.len(), 1);
+        assert_eq!(
+            service.statistics().get(Feature::Folding).unwrap().runs,
+            3,
+            "Every analysis is measured"
+        );
+    }
+
+    #[test]
+    fn test_language_service_computed_style() {
+        let mut service = LanguageService::new();
+        service.open_documents(vec![
+            TextDocument::new("file:///b.css", "css", 1, ".btn { color: red; }"),
+            TextDocument::new("file:///a.css", "css", 1, "a { color: blue; margin: 0; }"),
+        ]);
+        let style = service.computed_style("a.btn");
+        assert_eq!(style.len(), 2);
+        assert_eq!(style[0].value, "red");
+        assert_eq!(style[0].uri.as_str(), "file:///b.css");
+        assert!(service.color_palette(DEFAULT_DELTA_E_THRESHOLD).is_empty());
+
+        let document = TextDocument::new("file:///c.css", "css", 1, "a.btn {}");
+        let computed_style = |service: &LanguageService| {
+            let hover = service.hover(&document, Position::new(0, 1)).unwrap();
+            let lsp_types::HoverContents::Markup(markup) = hover.contents else {
+                panic!("Expected Markdown");
+            };
+            markup.value.contains("Computed style")
+        };
+        assert!(!computed_style(&service));
+        service.set_computed_style_preview(true);
+        assert!(computed_style(&service));
+    }
+
+    #[test]
+    fn test_language_service_severity_profiles() {
+        let mut service = LanguageService::new();
+        assert!(!service.set_severity_profiles_wasm("{ \"editor\": 1 }"));
+        assert!(service.set_severity_profiles_wasm(
+            r#"{ "editor": { "categories": { "style": "hint" } }, "ci": { "categories": { "style": "error" } } }"#
+        ));
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { margin: 0px; }");
+        let diagnostics = service.diagnostics(&document).items;
+        assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+
+        let (results, exit_class) = service.check_documents(&[document]);
+        assert_eq!(results[0].1[0].severity, Some(DiagnosticSeverity::ERROR));
+        assert_eq!(exit_class, ExitClass::Errors);
+    }
+
+    #[test]
+    fn test_language_service_rule_options() {
+        let mut service = LanguageService::new();
+        let document = TextDocument::new(
+            "file:///a.css",
+            "css",
+            1,
+            "a { height: 100vh; height: 100dvh; }",
+        );
+        assert!(service.diagnostics(&document).items.is_empty());
+        assert!(!service.set_rule_options_wasm(r#"{ "duplicateProperty": { "ignore": 1 } }"#));
+        assert!(service
+            .set_rule_options_wasm(r#"{ "duplicateProperty": { "allowFallbacks": false } }"#));
+        let diagnostics = service.diagnostics(&document).items;
+        assert_eq!(
+            diagnostics[0].code,
+            Some(lsp_types::NumberOrString::String(
+                "duplicate-property".to_string()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_language_service_rule_templates() {
+        let mut service = LanguageService::new();
+        assert_eq!(service.rule_templates().len(), 2);
+        assert!(service.set_snippet_settings_wasm(
+            r#"{ "breakpoints": [{ "name": "sm", "minWidth": "40rem" }, { "name": "lg", "minWidth": "64rem" }] }"#
+        ));
+        assert!(!service.set_snippet_settings_wasm(r#"{ "breakpoints": 1 }"#));
+        let templates = service.rule_templates();
+        assert_eq!(templates.len(), 4);
+        assert_eq!(templates[1].snippet, "@media (min-width: 64rem) {\n\t$0\n}");
+        assert!(service.rule_templates_wasm().contains(r#""id":"media-sm""#));
+    }
+
+    #[test]
+    fn test_language_service_file_settings() {
+        let mut service = LanguageService::new();
+        assert!(service.set_file_settings_wasm(r#"{ "indexOnly": ["**/vendor/**"] }"#));
+        let vendored = TextDocument::new("file:///app/vendor/a.css", "css", 1, "a { margin }");
+        assert!(service.diagnostics(&vendored).items.is_empty());
+        let own = TextDocument::new("file:///app/a.css", "css", 1, "a { margin }");
+        assert!(!service.diagnostics(&own).items.is_empty());
+        assert!(service.check_documents(&[vendored]).0.is_empty());
+    }
+
+    #[test]
+    fn test_language_service_replay_document() {
+        let mut service = LanguageService::new();
+        service.set_history_limit(5);
+        service.open_document(&TextDocument::new("file:///a.css", "css", 1, "a {}"));
+        assert!(service.apply_changes_wasm(
+            "file:///a.css",
+            2,
+            r#"[{ "range": { "start": { "line": 0, "character": 3 }, "end": { "line": 0, "character": 3 } }, "text": "color: red;" }]"#
+        ));
+        assert!(!service.apply_changes_wasm("file:///a.css", 3, "{}"));
+        assert_eq!(
+            service.documents().get("file:///a.css").unwrap().text,
+            "a {color: red;}"
+        );
+        let replay: serde_json::Value =
+            serde_json::from_str(&service.replay_document("file:///a.css").unwrap()).unwrap();
+        assert_eq!(replay["history"]["entries"][0]["version"], 2);
+        assert_eq!(replay["issues"], serde_json::json!([]));
+        assert!(service.replay_document("file:///b.css").is_none());
+    }
+
+    #[test]
+    fn test_language_service_position_encoding() {
+        let mut service = LanguageService::with_position_encoding(PositionEncoding::Utf8);
+        assert_eq!(service.position_encoding_wasm(), "utf-8");
+        service.open_document(&TextDocument::new(
+            "file:///a.css",
+            "css",
+            1,
+            "a::before { content: \"é\" }",
+        ));
+        // The `}` is at byte 26, but at UTF-16 code unit 25
+        assert!(service.apply_changes_wasm(
+            "file:///a.css",
+            2,
+            r#"[{ "range": { "start": { "line": 0, "character": 26 }, "end": { "line": 0, "character": 27 } }, "text": "; }" }]"#
+        ));
+        assert_eq!(
+            service.documents().get("file:///a.css").unwrap().text,
+            "a::before { content: \"é\" ; }"
+        );
+
+        let service = LanguageService::with_position_encoding_wasm("utf-32").unwrap();
+        assert_eq!(service.position_encoding(), PositionEncoding::Utf32);
+        assert!(LanguageService::with_position_encoding_wasm("utf-7").is_none());
+        assert_eq!(LanguageService::new().position_encoding_wasm(), "utf-16");
+    }
+
+    #[test]
+    fn test_language_service_features_position_encoding() {
+        let mut service = LanguageService::with_position_encoding(PositionEncoding::Utf8);
+        let document = TextDocument::new(
+            "file:///a.css",
+            "css",
+            1,
+            "a::before { content: \"é\"; color: red; cursor: auto; colr: 0 }",
+        );
+        service.open_document(&document);
+        // The `é` takes two bytes, so the columns after it are one more than in UTF-16
+        let colors = service.document_colors(&document).items;
+        assert_eq!(
+            colors[0].range,
+            Range::new(Position::new(0, 34), Position::new(0, 37))
+        );
+        let diagnostics = service.diagnostics(&document).items;
+        assert_eq!(
+            diagnostics[0].range,
+            Range::new(Position::new(0, 53), Position::new(0, 57))
+        );
+        let hover = service.hover(&document, Position::new(0, 40)).unwrap();
+        assert_eq!(
+            hover.range,
+            Some(Range::new(Position::new(0, 39), Position::new(0, 51)))
+        );
+        let tokens = service.semantic_tokens(&document).data;
+        assert_eq!(tokens[1].delta_start, 15);
+    }
+
+    #[test]
+    fn test_language_service_diagnostics_after_changes() {
+        let mut service = LanguageService::new();
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { colo }\nb { top: 0 }");
+        service.open_document(&document);
+        assert_eq!(service.diagnostics(&document).items.len(), 1);
+
+        assert!(service.apply_changes_wasm(
+            "file:///a.css",
+            2,
+            r#"[{ "range": { "start": { "line": 0, "character": 8 }, "end": { "line": 0, "character": 8 } }, "text": "r: red;" }]"#
+        ));
+        let edited = service.documents().get("file:///a.css").unwrap().clone();
+        assert_eq!(edited.text, "a { color: red; }\nb { top: 0 }");
+        assert!(service.diagnostics(&edited).items.is_empty());
+        // Documents differing from the open one are parsed on their own
+        assert_eq!(service.diagnostics(&document).items.len(), 1);
+    }
+
+    #[test]
+    fn test_language_service_features_after_changes() {
+        let mut service = LanguageService::new();
+        let text = "a {\n  color: red;\n}\n@media screen {\n  b { top: 0 }\n}\n";
+        service.open_document(&TextDocument::new("file:///a.css", "css", 1, text));
+        assert!(service.apply_changes_wasm(
+            "file:///a.css",
+            2,
+            r##"[{ "range": { "start": { "line": 1, "character": 9 }, "end": { "line": 1, "character": 12 } }, "text": "#00f" }]"##
+        ));
+        // The features of the open document read its incremental parse, those of another
+        // document with the same text parse it on their own
+        let edited = service.documents().get("file:///a.css").unwrap().clone();
+        let other = TextDocument::new("file:///b.css", "css", 1, &edited.text);
+        assert_eq!(service.document_colors(&edited).items.len(), 1);
+        let position = Position::new(1, 4);
+        let options = FormattingOptions {
+            tab_size: 4,
+            insert_spaces: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            service.folding_ranges(&edited).items,
+            service.folding_ranges(&other).items
+        );
+        assert_eq!(
+            service.document_symbols(&edited).items.len(),
+            service.document_symbols(&other).items.len()
+        );
+        assert_eq!(
+            service.document_colors(&edited).items,
+            service.document_colors(&other).items
+        );
+        assert_eq!(
+            service.hover(&edited, position),
+            service.hover(&other, position)
+        );
+        assert_eq!(
+            service.semantic_tokens(&edited),
+            service.semantic_tokens(&other)
+        );
+        assert_eq!(
+            service.completions(&edited, position),
+            service.completions(&other, position)
+        );
+        assert_eq!(
+            service.formatting(&edited, &options),
+            service.formatting(&other, &options)
+        );
+        assert!(!service.formatting(&edited, &options).is_empty());
+    }
+
+    #[test]
+    fn test_language_service_css_data() {
+        let mut service = LanguageService::new();
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { border-radius: v }");
+        assert!(service
+            .completions(&document, Position::new(0, 20))
+            .is_empty());
+
+        let data = CssDataBuilder::new()
+            .property("border-radius")
+            .values(["var(--ds-radius)"])
+            .build();
+        service.set_css_data(data);
+        let items = service.completions(&document, Position::new(0, 20));
+        assert_eq!(items[0].label, "var(--ds-radius)");
+
+        assert!(!service.set_custom_data("{"));
+        assert!(service.set_custom_data(
+            r#"{ "properties": [{ "name": "--ds-gap", "values": [{ "name": "var(--ds-gap)" }] }] }"#
+        ));
+        let items = service.completions(&document, Position::new(0, 20));
+        assert_eq!(
+            items[0].label, "var(--ds-radius)",
+            "Layered on the current data"
+        );
+        assert!(service.css_data().property("--ds-gap").is_some());
+    }
+
+    #[test]
+    fn test_language_service_snippet_support() {
+        let mut service = LanguageService::new();
+        let document = TextDocument::new("file:///a.css", "css", 1, "@med");
+        let new_text = |service: &LanguageService| {
+            let items = service.completions(&document, Position::new(0, 4));
+            match &items[0].text_edit {
+                Some(lsp_types::CompletionTextEdit::Edit(edit)) => edit.new_text.clone(),
+                _ => panic!("Expected a text edit"),
+            }
+        };
+        assert_eq!(new_text(&service), "@media ");
+
+        service.set_completion_capabilities(&CompletionClientCapabilities {
+            completion_item: Some(lsp_types::CompletionItemCapability {
+                snippet_support: Some(true),
+                ..Default::default()
+            }),
+            ..Default::default()
+        });
+        assert_eq!(
+            new_text(&service),
+            "@media (${1:min-width}: ${2:value}) {\n\t$0\n}"
+        );
+    }
+
+    #[test]
+    fn test_language_service_per_request_options() {
+        let mut service = LanguageService::new();
+        service.set_completion_options(CompletionOptions {
+            max_items: Some(3),
+            ..Default::default()
+        });
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { color: r }");
+        assert_eq!(
+            service.completions(&document, Position::new(0, 12)).len(),
+            3
+        );
+        let options = CompletionOptions::default();
+        assert!(
+            service
+                .completions_with_options(&document, Position::new(0, 12), Some(&options))
+                .len()
+                > 3
+        );
+
+        let document =
+            TextDocument::new("file:///a.css", "css", 1, "a { transition: all 1s ease }");
+        let plaintext = |hover: Option<Hover>| match hover.map(|hover| hover.contents) {
+            Some(lsp_types::HoverContents::Markup(markup)) => {
+                markup.kind == lsp_types::MarkupKind::PlainText
+            }
+            _ => panic!("Expected markup"),
+        };
+        assert!(!plaintext(service.hover(&document, Position::new(0, 25))));
+        let options = HoverOptions {
+            prefer_plaintext: true,
+            ..Default::default()
+        };
+        assert!(plaintext(service.hover_with_options(
+            &document,
+            Position::new(0, 25),
+            Some(&options)
+        )));
+    }
+
+    struct GeneratedCss;
+
+    impl TextDocumentContentProvider for GeneratedCss {
+        fn provide_content(&self, uri: &Uri) -> Option<String> {
+            (uri.as_str() == "tailwind-generated://app/out.css")
+                .then(|| ".p-4 { padding: 1rem; }".to_string())
+        }
+    }
+
+    #[test]
+    fn test_language_service_content_providers() {
+        let mut service = LanguageService::new();
+        service.register_content_provider("Tailwind-Generated", GeneratedCss);
+        assert_eq!(service.content_schemes(), ["tailwind-generated"]);
+
+        let uri: Uri = "tailwind-generated://app/out.css".parse().unwrap();
+        assert_eq!(
+            service.text_document_content(&uri).as_deref(),
+            Some(".p-4 { padding: 1rem; }")
+        );
+        assert_eq!(
+            service.text_document_content_wasm("tailwind-generated://app/other.css"),
+            None
+        );
+        assert_eq!(
+            service.text_document_content(&"file:///out.css".parse().unwrap()),
+            None,
+            "Only the schemes of content providers are virtual documents"
+        );
+
+        // Imports of virtual documents resolve to readable documents
+        let document = TextDocument::new(
+            "tailwind-generated://app/main.css",
+            "css",
+            1,
+            "@import 'out.css';",
+        );
+        let hover = service.hover(&document, Position::new(0, 10)).unwrap();
+        let lsp_types::HoverContents::Markup(markup) = hover.contents else {
+            panic!("Expected markup");
+        };
+        assert!(
+            markup.value.contains("tailwind-generated://app/out.css"),
+            "{}",
+            markup.value
+        );
+        assert!(!markup.value.contains("not found"), "{}", markup.value);
+    }
+
+    #[test]
+    fn test_language_service_rename_css_module_class() {
+        let mut service = LanguageService::new();
+        let base = TextDocument::new("file:///p/base.module.css", "css", 1, ".base {}");
+        service.open_documents(vec![
+            base.clone(),
+            TextDocument::new(
+                "file:///p/card.module.css",
+                "css",
+                1,
+                ".card { composes: base from './base.module.css'; }",
+            ),
+        ]);
+        assert_eq!(
+            service.references(&base, Position::new(0, 2), true).len(),
+            2
+        );
+        let edit = service
+            .rename_wasm(&base, &Position::new(0, 2).into(), "root")
+            .unwrap();
+        assert!(edit.contains("file:///p/card.module.css"), "{edit}");
+        assert_eq!(
+            service.rename_wasm(&base, &Position::new(0, 2).into(), "1a"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_language_service_custom_property_references() {
+        let mut service = LanguageService::new();
+        let page = TextDocument::new("file:///p/page.css", "css", 1, "a { color: var(--brand); }");
+        service.open_documents(vec![
+            page.clone(),
+            TextDocument::new("file:///p/tokens.css", "css", 1, ":root { --brand: red; }"),
+        ]);
+        let locations = service.references(&page, Position::new(0, 18), true);
+        let uris: Vec<&str> = locations
+            .iter()
+            .map(|location| location.uri.as_str())
+            .collect();
+        assert_eq!(uris, vec!["file:///p/page.css", "file:///p/tokens.css"]);
+    }
+
+    #[test]
+    fn test_language_service_size_limits() {
+        let source = ".a {\n  color: red;\n}\n"
+            .repeat(20)
+            .replace("red", "#123456 /* { */")
+            + "@media print { .b { color: rgb(0 0 255) } } .c { color: blue; }";
+        let document = TextDocument::new("file:///big.css", "css", 1, &source);
+        let mut service = LanguageService::new();
+        let folding_ranges = service.folding_ranges(&document).items;
+        let symbols = service.document_symbols(&document).items;
+        let colors = service.document_colors(&document).items;
+
+        service.set_size_limits(SizeLimits {
+            max_document_size: 100,
+            chunk_size: 40,
+        });
+        assert_eq!(service.folding_ranges(&document).items, folding_ranges);
+        assert_eq!(service.document_symbols(&document).items, symbols);
+        assert_eq!(service.document_colors(&document).items, colors);
+
+        let diagnostics = service.diagnostics(&document).items;
+        assert_eq!(diagnostics.len(), 1);
+        let size = format!("({} bytes, the limit is 100 bytes)", source.len());
+        assert!(diagnostics[0].message.contains(&size));
+        let (results, _) = service.check_documents(std::slice::from_ref(&document));
+        assert_eq!(results[0].1.len(), 1);
+        assert!(results[0].1[0].message.contains(&size));
+        assert!(service.semantic_tokens(&document).data.is_empty());
+        assert!(service.hover(&document, Position::new(1, 3)).is_none());
+    }
+
+    #[test]
+    fn test_language_service_document_dialects() {
+        let code = "// Don't lint\n.a {\n  .b { color: red; }\n}\n";
+        let mut service = LanguageService::new();
+        // Without an extension, the language of the document tells how to read it
+        let document = TextDocument::new("untitled:Untitled-1", "scss", 1, code);
+        assert!(service.diagnostics(&document).items.is_empty());
+        service.open_document(&document);
+        assert!(service.diagnostics(&document).items.is_empty());
+        let (results, _) = service.check_documents(std::slice::from_ref(&document));
+        assert!(results[0].1.is_empty());
+
+        let document = TextDocument::new("untitled:Untitled-2", "css", 1, code);
+        assert!(!service.diagnostics(&document).items.is_empty());
+    }
+
+    #[test]
+    fn test_language_service_folding_capabilities() {
+        let mut service = LanguageService::new();
+        service.set_folding_capabilities(FoldingRangeClientCapabilities {
+            range_limit: Some(2),
+            line_folding_only: Some(true),
+            ..Default::default()
+        });
+        let source = "@media print {\n  .a {\n  }\n}\n.b {\n}\n.c {\n}\n";
+        let document = TextDocument::new("file:///a.css", "css", 1, source);
+        let start_lines = |folding_ranges: Vec<FoldingRange>| {
+            let mut start_lines: Vec<u32> = folding_ranges
+                .iter()
+                .map(|folding_range| folding_range.start_line)
+                .collect();
+            start_lines.sort();
+            start_lines
+        };
+        assert_eq!(
+            start_lines(service.folding_ranges(&document).items),
+            vec![0, 4]
+        );
+
+        service.set_size_limits(SizeLimits {
+            max_document_size: 10,
+            chunk_size: 10,
+        });
+        assert_eq!(
+            start_lines(service.folding_ranges(&document).items),
+            vec![0, 4],
+            "The limit applies to the whole stylesheet"
+        );
+    }
+
+    #[test]
+    fn test_language_service_definition() {
+        let mut service = LanguageService::new();
+        let page = TextDocument::new(
+            "file:///p/page.css",
+            "css",
+            1,
+            "a { animation-name: slide; }",
+        );
+        service.open_documents(vec![
+            page.clone(),
+            TextDocument::new("file:///p/motion.css", "css", 1, "@keyframes slide {}"),
+        ]);
+        let locations = service.definition(&page, Position::new(0, 22));
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].uri.as_str(), "file:///p/motion.css");
+        assert_eq!(locations[0].range.start, Position::new(0, 11));
+        assert!(service.definition(&page, Position::new(0, 5)).is_empty());
+        let locations = service.references(
+            &TextDocument::new("file:///p/motion.css", "css", 1, "@keyframes slide {}"),
+            Position::new(0, 12),
+            false,
+        );
+        assert_eq!(locations.len(), 1);
+        assert_eq!(locations[0].uri.as_str(), "file:///p/page.css");
+    }
+
+    #[test]
+    fn test_language_service_abbreviations() {
+        let mut service = LanguageService::new();
+        let document = TextDocument::new("file:///a.css", "css", 1, "a { dfc }");
+        assert!(service
+            .completions(&document, Position::new(0, 7))
+            .is_empty());
+        assert!(!service.enable_abbreviations(Some("{".to_string())));
+        assert!(service.enable_abbreviations(Some(
+            r#"{ "abbreviations": { "dfc": "display: flex; flex-direction: column" } }"#
+                .to_string()
+        )));
+        let items = service.completions(&document, Position::new(0, 7));
+        assert_eq!(
+            items[0].detail.as_deref(),
+            Some("display: flex; flex-direction: column;")
+        );
+    }
+
+    #[test]
+    fn test_language_service_rename_custom_property() {
+        let mut service = LanguageService::new();
+        let page = TextDocument::new("file:///p/page.css", "css", 1, "a { color: var(--brand); }");
+        service.open_documents(vec![
+            page.clone(),
+            TextDocument::new("file:///p/tokens.css", "css", 1, ":root { --brand: red; }"),
+        ]);
+        assert!(service
+            .prepare_rename(&page, Position::new(0, 18))
+            .is_some());
+        assert!(
+            service.prepare_rename(&page, Position::new(0, 5)).is_none(),
+            "Property names can't be renamed"
+        );
+        let edit = service
+            .rename_wasm(&page, &Position::new(0, 18).into(), "--accent")
+            .unwrap();
+        assert!(edit.contains("file:///p/tokens.css"), "{edit}");
+        assert_eq!(
+            service.rename(&page, Position::new(0, 18), "accent"),
+            Err("`accent` isn't a valid custom property name.".to_string())
+        );
+        assert_eq!(
+            service.rename(&page, Position::new(0, 5), "--accent"),
+            Ok(None)
+        );
+    }
+}
@@ -0,0 +1,313 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case, fold_case};
+use crate::data::values::CSS_WIDE_KEYWORDS;
+use crate::parser::{split_components, split_list};
+use std::ops::Range;
+
+/// The longhands of the `animation` shorthand, in the order of `AnimationLayer::longhands`.
+pub const ANIMATION_LONGHANDS: &[&str] = &[
+    "animation-name",
+    "animation-duration",
+    "animation-timing-function",
+    "animation-delay",
+    "animation-iteration-count",
+    "animation-direction",
+    "animation-fill-mode",
+    "animation-play-state",
+];
+
+/// The initial values of the `animation` longhands, in the same order.
+const ANIMATION_INITIAL_VALUES: &[&str] =
+    &["none", "0s", "ease", "0s", "1", "normal", "none", "running"];
+
+pub(crate) const EASING_KEYWORDS: &[&str] = &[
+    "linear",
+    "ease",
+    "ease-in",
+    "ease-out",
+    "ease-in-out",
+    "step-start",
+    "step-end",
+];
+pub(crate) const EASING_FUNCTIONS: &[&str] = &["cubic-bezier", "steps", "linear"];
+pub(crate) const DIRECTION_KEYWORDS: &[&str] =
+    &["normal", "reverse", "alternate", "alternate-reverse"];
+pub(crate) const FILL_MODE_KEYWORDS: &[&str] = &["none", "forwards", "backwards", "both"];
+pub(crate) const PLAY_STATE_KEYWORDS: &[&str] = &["running", "paused"];
+
+/// A component of a shorthand value assigned to a longhand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Component {
+    /// The component, as written.
+    pub text: String,
+    /// The byte range of the component in the source.
+    pub range: Range<usize>,
+}
+
+/// One comma separated animation of an `animation` value, decomposed into its longhands.
+/// Longhands that aren't written take their initial value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnimationLayer {
+    pub name: Option<Component>,
+    pub duration: Option<Component>,
+    pub timing_function: Option<Component>,
+    pub delay: Option<Component>,
+    pub iteration_count: Option<Component>,
+    pub direction: Option<Component>,
+    pub fill_mode: Option<Component>,
+    pub play_state: Option<Component>,
+    /// The byte range of the animation in the source.
+    pub range: Range<usize>,
+}
+
+impl AnimationLayer {
+    /// Returns the value of every longhand, written or initial, in the order of
+    /// `ANIMATION_LONGHANDS`.
+    pub fn longhands(&self) -> Vec<(&'static str, &str)> {
+        let components = [
+            &self.name,
+            &self.duration,
+            &self.timing_function,
+            &self.delay,
+            &self.iteration_count,
+            &self.direction,
+            &self.fill_mode,
+            &self.play_state,
+        ];
+        ANIMATION_LONGHANDS
+            .iter()
+            .zip(ANIMATION_INITIAL_VALUES)
+            .zip(components)
+            .map(|((longhand, initial), component)| {
+                let value = component
+                    .as_ref()
+                    .map_or(*initial, |component| component.text.as_str());
+                (*longhand, value)
+            })
+            .collect()
+    }
+}
+
+/// An error found while decomposing a shorthand.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShorthandError {
+    pub message: String,
+    /// The byte range of the offending component in the source.
+    pub range: Range<usize>,
+}
+
+/// Decomposes an `animation` value into its longhands, following the CSS Animations
+/// grammar: the first time is the duration and the second the delay, and keywords go to
+/// the first longhand accepting them that isn't set yet before being taken as the name.
+///
+/// # Arguments
+///
+/// * `value` - The text of the value.
+/// * `value_start` - The byte offset of the value in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * An `AnimationLayer` per comma separated animation, `Ok(None)` for values that can't
+///   be decomposed statically, like CSS-wide keywords and `var()`, or the first
+///   `ShorthandError`.
+pub fn parse_animation(
+    value: &str,
+    value_start: usize,
+) -> Result<Option<Vec<AnimationLayer>>, ShorthandError> {
+    let folded = fold_case(value);
+    if contains_ignore_case(CSS_WIDE_KEYWORDS, value.trim()) || folded.contains("var(") {
+        return Ok(None);
+    }
+    let mut layers = Vec::new();
+    for (item, range) in split_list(value, value_start) {
+        if item.is_empty() {
+            return Err(ShorthandError {
+                message: "Expected an animation.".to_string(),
+                range,
+            });
+        }
+        layers.push(parse_animation_layer(item, range.start)?);
+    }
+    Ok(Some(layers))
+}
+
+fn parse_animation_layer(item: &str, item_start: usize) -> Result<AnimationLayer, ShorthandError> {
+    let mut layer = AnimationLayer {
+        range: item_start..item_start + item.len(),
+        ..Default::default()
+    };
+    for (text, range) in split_components(item, item_start) {
+        let component = Some(Component {
+            text: text.to_string(),
+            range: range.clone(),
+        });
+        let folded = fold_case(text);
+        let function = folded.split_once('(').map(|(name, _)| name);
+        let (slot, repeated) = if is_time(&folded) {
+            let slot = if layer.duration.is_none() {
+                &mut layer.duration
+            } else {
+                &mut layer.delay
+            };
+            (
+                slot,
+                "An animation takes at most two times, its duration and its delay.",
+            )
+        } else if function.is_some_and(|name| EASING_FUNCTIONS.contains(&name))
+            || (EASING_KEYWORDS.contains(&folded.as_ref()) && layer.timing_function.is_none())
+        {
+            (
+                &mut layer.timing_function,
+                "An animation takes a single easing function.",
+            )
+        } else if folded == "infinite" || is_non_negative_number(&folded) {
+            (
+                &mut layer.iteration_count,
+                "An animation takes a single iteration count.",
+            )
+        } else if DIRECTION_KEYWORDS.contains(&folded.as_ref()) && layer.direction.is_none() {
+            (&mut layer.direction, "")
+        } else if FILL_MODE_KEYWORDS.contains(&folded.as_ref()) && layer.fill_mode.is_none() {
+            (&mut layer.fill_mode, "")
+        } else if PLAY_STATE_KEYWORDS.contains(&folded.as_ref()) && layer.play_state.is_none() {
+            (&mut layer.play_state, "")
+        } else if is_keyframes_name(text) {
+            (&mut layer.name, "An animation takes a single name.")
+        } else {
+            return Err(ShorthandError {
+                message: format!("`{text}` isn't a valid part of an animation."),
+                range,
+            });
+        };
+        // Keywords only go to unset longhands, so only these can be repeated
+        if slot.is_some() {
+            return Err(ShorthandError {
+                message: repeated.to_string(),
+                range,
+            });
+        }
+        *slot = component;
+    }
+    Ok(layer)
+}
+
+/// Whether a component is a `<time>`, like `300ms` or `.5s`, in lowercase.
+pub(crate) fn is_time(text: &str) -> bool {
+    let number = text
+        .strip_suffix("ms")
+        .or_else(|| text.strip_suffix('s'))
+        .unwrap_or("");
+    !number.is_empty() && number.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+fn is_non_negative_number(text: &str) -> bool {
+    text.parse::<f64>()
+        .is_ok_and(|number| number.is_finite() && number >= 0.0)
+}
+
+/// Whether a component can be a `<keyframes-name>`: a custom identifier or a string.
+fn is_keyframes_name(text: &str) -> bool {
+    if text.starts_with('"') || text.starts_with('\'') {
+        return true;
+    }
+    let mut chars = text.chars();
+    let first = chars.next();
+    let starts_identifier = match first {
+        Some('-') => chars
+            .next()
+            .is_some_and(|c| c == '-' || c.is_alphabetic() || c == '_' || !c.is_ascii()),
+        Some(c) => c.is_alphabetic() || c == '_' || c == '\\' || !c.is_ascii(),
+        None => false,
+    };
+    starts_identifier
+        && text
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '\\' || !c.is_ascii())
+        && !eq_ignore_case(text, "default")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn longhands(value: &str) -> Vec<Vec<(&'static str, String)>> {
+        parse_animation(value, 0)
+            .unwrap()
+            .unwrap()
+            .iter()
+            .map(|layer| {
+                layer
+                    .longhands()
+                    .into_iter()
+                    .map(|(longhand, value)| (longhand, value.to_string()))
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_animation() {
+        let layers = longhands("slide 300ms ease-in 1s infinite alternate both paused");
+        let values: Vec<_> = layers[0].iter().map(|(_, value)| value.as_str()).collect();
+        assert_eq!(
+            values,
+            vec![
+                "slide",
+                "300ms",
+                "ease-in",
+                "1s",
+                "infinite",
+                "alternate",
+                "both",
+                "paused"
+            ]
+        );
+
+        let layers = longhands("1s FADE, steps(4, end) 2 spin 0.5s");
+        assert_eq!(layers.len(), 2);
+        assert_eq!(layers[0][0], ("animation-name", "FADE".to_string()));
+        assert_eq!(
+            layers[0][2],
+            ("animation-timing-function", "ease".to_string())
+        );
+        assert_eq!(layers[1][1], ("animation-duration", "0.5s".to_string()));
+        assert_eq!(layers[1][2].1, "steps(4, end)");
+        assert_eq!(layers[1][4].1, "2");
+
+        // Keywords go to other longhands first, then to the name
+        let layers = longhands("none ease linear");
+        assert_eq!(layers[0][6].1, "none");
+        assert_eq!(layers[0][2].1, "ease");
+        assert_eq!(layers[0][0].1, "linear");
+    }
+
+    #[test]
+    fn test_parse_animation_ranges() {
+        let layer = &parse_animation("a 1s, b", 10).unwrap().unwrap()[1];
+        assert_eq!(layer.range, 16..17);
+        assert_eq!(layer.name.as_ref().unwrap().range, 16..17);
+        assert_eq!(parse_animation("inherit", 0), Ok(None));
+        assert_eq!(parse_animation("var(--a) 1s", 0), Ok(None));
+    }
+
+    #[test]
+    fn test_parse_animation_errors() {
+        let cases = [
+            ("a 1s 2s 3s", 8..10),
+            ("a b", 2..3),
+            ("a ease-in steps(2)", 10..18),
+            ("a 1s, , b", 6..6),
+            ("a -1", 2..4),
+            ("a 10px", 2..6),
+        ];
+        for (value, range) in cases {
+            let error = parse_animation(value, 0).unwrap_err();
+            assert_eq!(error.range, range, "{value:?}: {}", error.message);
+        }
+        assert_eq!(
+            parse_animation("a ease-in steps(2)", 0)
+                .unwrap_err()
+                .message,
+            "An animation takes a single easing function."
+        );
+    }
+}
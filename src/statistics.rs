@@ -0,0 +1,103 @@
+use crate::budget::Budgeted;
+use std::collections::HashMap;
+use std::time::Duration;
+use wasm_bindgen::prelude::*;
+
+/// A feature whose runs are measured by the `LanguageService`.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Feature {
+    Folding,
+    Symbols,
+    Colors,
+    Diagnostics,
+}
+
+/// The measurements of the runs of a feature.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureStatistics {
+    /// The number of runs.
+    pub runs: u32,
+    /// The number of runs whose results were truncated by their budget.
+    pub truncated_runs: u32,
+    /// Whether the results of the last run were truncated.
+    pub last_truncated: bool,
+    /// How long the last run took.
+    pub last_duration: Duration,
+    /// How long all the runs took.
+    pub total_duration: Duration,
+}
+
+/// Collects measurements of the feature runs, notably which results were truncated.
+#[derive(Debug, Clone, Default)]
+pub struct Statistics {
+    features: HashMap<Feature, FeatureStatistics>,
+}
+
+impl Statistics {
+    /// Records a run of a feature.
+    ///
+    /// # Arguments
+    ///
+    /// * `feature` - The `Feature` that ran.
+    /// * `result` - The `Budgeted` results of the run.
+    pub fn record<T>(&mut self, feature: Feature, result: &Budgeted<T>) {
+        let statistics = self.features.entry(feature).or_default();
+        statistics.runs += 1;
+        statistics.truncated_runs += u32::from(result.truncated);
+        statistics.last_truncated = result.truncated;
+        statistics.last_duration = result.duration;
+        statistics.total_duration += result.duration;
+    }
+
+    /// Returns the measurements of a feature, if it ran at least once.
+    pub fn get(&self, feature: Feature) -> Option<&FeatureStatistics> {
+        self.features.get(&feature)
+    }
+
+    /// Returns the features whose last results were truncated.
+    pub fn truncated_features(&self) -> Vec<Feature> {
+        let mut features: Vec<Feature> = self
+            .features
+            .iter()
+            .filter(|(_, statistics)| statistics.last_truncated)
+            .map(|(&feature, _)| feature)
+            .collect();
+        features.sort_by_key(|&feature| feature as u8);
+        features
+    }
+
+    /// Forgets every measurement.
+    pub fn reset(&mut self) {
+        self.features.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result(truncated: bool, millis: u64) -> Budgeted<()> {
+        Budgeted {
+            items: Vec::new(),
+            truncated,
+            duration: Duration::from_millis(millis),
+        }
+    }
+
+    #[test]
+    fn test_statistics_record() {
+        let mut statistics = Statistics::default();
+        statistics.record(Feature::Folding, &result(true, 60));
+        statistics.record(Feature::Folding, &result(false, 10));
+        statistics.record(Feature::Diagnostics, &result(true, 5));
+
+        let folding = statistics.get(Feature::Folding).unwrap();
+        assert_eq!(folding.runs, 2);
+        assert_eq!(folding.truncated_runs, 1);
+        assert!(!folding.last_truncated);
+        assert_eq!(folding.total_duration, Duration::from_millis(70));
+        assert_eq!(statistics.truncated_features(), vec![Feature::Diagnostics]);
+        assert_eq!(statistics.get(Feature::Colors), None);
+    }
+}
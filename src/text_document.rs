@@ -1,7 +1,9 @@
+use lsp_types::Uri;
 use wasm_bindgen::prelude::*;
 
 /// VSCode-like object that represents a text document.
 #[wasm_bindgen]
+#[derive(Debug, Clone)]
 pub struct TextDocument {
     #[wasm_bindgen(skip)]
     pub uri: String,
@@ -51,3 +53,14 @@ impl TextDocument {
         self.text.clone()
     }
 }
+
+impl TextDocument {
+    /// Parses the URI of the text document.
+    ///
+    /// # Returns
+    ///
+    /// * The `Uri` of the document, or `None` if it isn't a valid URI.
+    pub fn lsp_uri(&self) -> Option<Uri> {
+        self.uri.parse().ok()
+    }
+}
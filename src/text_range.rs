@@ -0,0 +1,240 @@
+use crate::line_index::{LineIndex, PositionEncoding};
+use lsp_types::{Position, Range};
+
+/// A byte offset in a source text, independent of any editor protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TextSize(u32);
+
+impl TextSize {
+    pub const fn new(offset: u32) -> TextSize {
+        TextSize(offset)
+    }
+}
+
+impl From<u32> for TextSize {
+    fn from(offset: u32) -> Self {
+        TextSize(offset)
+    }
+}
+
+/// Offsets are limited to 4 GiB, like the syntax trees of the parser.
+impl TryFrom<usize> for TextSize {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(offset: usize) -> Result<Self, Self::Error> {
+        u32::try_from(offset).map(TextSize)
+    }
+}
+
+impl From<TextSize> for usize {
+    fn from(size: TextSize) -> Self {
+        size.0 as usize
+    }
+}
+
+impl From<TextSize> for u32 {
+    fn from(size: TextSize) -> Self {
+        size.0
+    }
+}
+
+impl From<biome_rowan::TextSize> for TextSize {
+    fn from(size: biome_rowan::TextSize) -> Self {
+        TextSize(size.into())
+    }
+}
+
+/// A range of byte offsets in a source text, its end excluded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct TextRange {
+    start: TextSize,
+    end: TextSize,
+}
+
+impl TextRange {
+    /// Creates a range, panicking if it ends before it starts.
+    pub fn new(start: TextSize, end: TextSize) -> TextRange {
+        assert!(start <= end, "A range can't end before it starts");
+        TextRange { start, end }
+    }
+
+    /// Creates an empty range at an offset.
+    pub fn empty(offset: TextSize) -> TextRange {
+        TextRange::new(offset, offset)
+    }
+
+    pub fn start(&self) -> TextSize {
+        self.start
+    }
+
+    pub fn end(&self) -> TextSize {
+        self.end
+    }
+
+    pub fn len(&self) -> TextSize {
+        TextSize(self.end.0 - self.start.0)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns whether an offset is in the range, its end excluded.
+    pub fn contains(&self, offset: TextSize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+
+    /// Returns whether an offset is in the range, its end included, like a cursor right
+    /// after a word.
+    pub fn contains_inclusive(&self, offset: TextSize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+impl TryFrom<std::ops::Range<usize>> for TextRange {
+    type Error = std::num::TryFromIntError;
+
+    fn try_from(range: std::ops::Range<usize>) -> Result<Self, Self::Error> {
+        Ok(TextRange::new(
+            range.start.try_into()?,
+            range.end.try_into()?,
+        ))
+    }
+}
+
+impl From<TextRange> for std::ops::Range<usize> {
+    fn from(range: TextRange) -> Self {
+        range.start.into()..range.end.into()
+    }
+}
+
+impl From<biome_rowan::TextRange> for TextRange {
+    fn from(range: biome_rowan::TextRange) -> Self {
+        TextRange::new(range.start().into(), range.end().into())
+    }
+}
+
+/// A source text with its line index and the position encoding of the client, to convert
+/// offsets and ranges to and from the protocol.
+pub struct SourceText<'a> {
+    text: &'a str,
+    line_index: LineIndex,
+    encoding: PositionEncoding,
+}
+
+impl<'a> SourceText<'a> {
+    /// Indexes the lines of a source text.
+    ///
+    /// # Arguments
+    ///
+    /// * `text` - The source text.
+    /// * `encoding` - The `PositionEncoding` of the positions to convert.
+    ///
+    /// # Returns
+    ///
+    /// * A `SourceText` converting offsets of `text`.
+    pub fn new(text: &'a str, encoding: PositionEncoding) -> SourceText<'a> {
+        SourceText {
+            text,
+            line_index: LineIndex::new(text),
+            encoding,
+        }
+    }
+
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    pub fn encoding(&self) -> PositionEncoding {
+        self.encoding
+    }
+}
+
+/// Converts a crate-native offset or range to its protocol counterpart.
+pub trait ToLsp {
+    type Lsp;
+
+    /// Converts the value, with the lines and encoding of a source text.
+    fn to_lsp(&self, source: &SourceText) -> Self::Lsp;
+}
+
+/// Converts a protocol position or range to its crate-native counterpart.
+pub trait FromLsp<T>: Sized {
+    /// Converts the value, with the lines and encoding of a source text. Positions past
+    /// the end of their line are clamped to it.
+    fn from_lsp(value: T, source: &SourceText) -> Self;
+}
+
+impl ToLsp for TextSize {
+    type Lsp = Position;
+
+    fn to_lsp(&self, source: &SourceText) -> Position {
+        source
+            .line_index
+            .position_in(source.text, (*self).into(), source.encoding)
+    }
+}
+
+impl ToLsp for TextRange {
+    type Lsp = Range;
+
+    fn to_lsp(&self, source: &SourceText) -> Range {
+        Range::new(self.start.to_lsp(source), self.end.to_lsp(source))
+    }
+}
+
+impl FromLsp<Position> for TextSize {
+    fn from_lsp(position: Position, source: &SourceText) -> Self {
+        let offset = source
+            .line_index
+            .offset_in(source.text, position, source.encoding);
+        // Sources over 4 GiB can't be parsed, positions past the limit are clamped to it
+        TextSize::try_from(offset).unwrap_or(TextSize(u32::MAX))
+    }
+}
+
+impl FromLsp<Range> for TextRange {
+    fn from_lsp(range: Range, source: &SourceText) -> Self {
+        let start = TextSize::from_lsp(range.start, source);
+        let end = TextSize::from_lsp(range.end, source);
+        TextRange::new(start.min(end), end.max(start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_range() {
+        let range = TextRange::try_from(4..9).unwrap();
+        assert_eq!(range.len(), TextSize::new(5));
+        assert!(range.contains(TextSize::new(4)));
+        assert!(!range.contains(TextSize::new(9)));
+        assert!(range.contains_inclusive(TextSize::new(9)));
+        assert!(TextRange::empty(TextSize::new(3)).is_empty());
+        assert_eq!(std::ops::Range::<usize>::from(range), 4..9);
+
+        let parsed = crate::parser::parse_css("a { color: red; }").syntax();
+        assert_eq!(
+            TextRange::from(parsed.text_trimmed_range()),
+            TextRange::new(TextSize::new(0), TextSize::new(17))
+        );
+        assert!(TextSize::try_from(usize::MAX).is_err());
+    }
+
+    #[test]
+    fn test_lsp_conversions() {
+        let code = "a {\n  content: \"😀\";\n}";
+        let offset = TextSize::try_from(code.find(';').unwrap()).unwrap();
+        let range = TextRange::new(TextSize::new(6), offset);
+
+        let utf16 = SourceText::new(code, PositionEncoding::Utf16);
+        assert_eq!(offset.to_lsp(&utf16), Position::new(1, 15));
+        assert_eq!(TextRange::from_lsp(range.to_lsp(&utf16), &utf16), range);
+
+        let utf8 = SourceText::new(code, PositionEncoding::Utf8);
+        assert_eq!(offset.to_lsp(&utf8), Position::new(1, 17));
+        assert_eq!(TextSize::from_lsp(Position::new(1, 17), &utf8), offset);
+    }
+}
@@ -1,5 +1,5 @@
 // Implement wasm-bindgen friendly versions of Range and Position
-use lsp_types::{Position, Range};
+use lsp_types::{Position, Range, TextEdit};
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(js_name = Position)]
@@ -8,8 +8,16 @@ pub struct PositionWASM(Position);
 #[wasm_bindgen(js_name = Range)]
 pub struct RangeWASM(Range);
 
+#[wasm_bindgen(js_name = TextEdit)]
+pub struct TextEditWASM(TextEdit);
+
 #[wasm_bindgen(js_class = Position)]
 impl PositionWASM {
+    #[wasm_bindgen(constructor)]
+    pub fn new(line: u32, character: u32) -> PositionWASM {
+        PositionWASM(Position::new(line, character))
+    }
+
     #[wasm_bindgen(getter)]
     pub fn line(&self) -> u32 {
         self.0.line
@@ -35,6 +43,11 @@ impl From<Range> for RangeWASM {
 
 #[wasm_bindgen(js_class = Range)]
 impl RangeWASM {
+    #[wasm_bindgen(constructor)]
+    pub fn new(start: &PositionWASM, end: &PositionWASM) -> RangeWASM {
+        RangeWASM(Range::new(start.0, end.0))
+    }
+
     #[wasm_bindgen(getter)]
     pub fn start(&self) -> PositionWASM {
         self.0.start.into()
@@ -45,3 +58,34 @@ impl RangeWASM {
         self.0.end.into()
     }
 }
+
+#[wasm_bindgen(js_class = TextEdit)]
+impl TextEditWASM {
+    #[wasm_bindgen(getter)]
+    pub fn range(&self) -> RangeWASM {
+        self.0.range.into()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn new_text(&self) -> String {
+        self.0.new_text.clone()
+    }
+}
+
+impl From<TextEdit> for TextEditWASM {
+    fn from(edit: TextEdit) -> Self {
+        TextEditWASM(edit)
+    }
+}
+
+impl From<&PositionWASM> for Position {
+    fn from(position: &PositionWASM) -> Self {
+        position.0
+    }
+}
+
+impl From<&RangeWASM> for Range {
+    fn from(range: &RangeWASM) -> Self {
+        range.0
+    }
+}
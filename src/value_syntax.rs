@@ -0,0 +1,1073 @@
+use crate::case_folding::{contains_ignore_case, eq_ignore_case, fold_case};
+use crate::data::at_rules::descriptor_syntax;
+use crate::data::colors::{named_color, SYSTEM_COLORS};
+use crate::data::property_syntaxes::{property_syntax, type_syntax};
+use crate::data::values::{CSS_WIDE_KEYWORDS, LENGTH_UNITS};
+use crate::parser::tokenizer::{TokenKind, Tokenizer};
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::ops::Range;
+use std::sync::{Mutex, OnceLock};
+
+/// How deep property and type references are followed, so a grammar referencing itself
+/// can't loop forever.
+const MAX_DEPTH: usize = 32;
+
+/// How many expected values are listed in a mismatch message.
+const MAX_EXPECTED: usize = 6;
+
+const ANGLE_UNITS: &[&str] = &["deg", "grad", "rad", "turn"];
+const TIME_UNITS: &[&str] = &["ms", "s"];
+const RESOLUTION_UNITS: &[&str] = &["dpcm", "dpi", "dppx", "x"];
+
+/// Functions computing a number, a dimension or a percentage, which match any numeric type.
+const NUMERIC_FUNCTIONS: &[&str] = &[
+    "abs",
+    "acos",
+    "anchor",
+    "anchor-size",
+    "asin",
+    "atan",
+    "atan2",
+    "calc",
+    "clamp",
+    "cos",
+    "exp",
+    "hypot",
+    "log",
+    "max",
+    "min",
+    "mod",
+    "pow",
+    "rem",
+    "round",
+    "sign",
+    "sin",
+    "sqrt",
+    "tan",
+];
+const COLOR_FUNCTIONS: &[&str] = &[
+    "color",
+    "color-mix",
+    "contrast-color",
+    "device-cmyk",
+    "hsl",
+    "hsla",
+    "hwb",
+    "lab",
+    "lch",
+    "light-dark",
+    "oklab",
+    "oklch",
+    "rgb",
+    "rgba",
+];
+const IMAGE_FUNCTIONS: &[&str] = &[
+    "conic-gradient",
+    "cross-fade",
+    "element",
+    "image",
+    "image-set",
+    "linear-gradient",
+    "paint",
+    "radial-gradient",
+    "repeating-conic-gradient",
+    "repeating-linear-gradient",
+    "repeating-radial-gradient",
+];
+const EASING_KEYWORDS: &[&str] = &[
+    "ease",
+    "ease-in",
+    "ease-in-out",
+    "ease-out",
+    "linear",
+    "step-end",
+    "step-start",
+];
+const EASING_FUNCTIONS: &[&str] = &["cubic-bezier", "linear", "steps"];
+const FILTER_FUNCTIONS: &[&str] = &[
+    "blur",
+    "brightness",
+    "contrast",
+    "drop-shadow",
+    "grayscale",
+    "hue-rotate",
+    "invert",
+    "opacity",
+    "saturate",
+    "sepia",
+];
+const TRANSFORM_FUNCTIONS: &[&str] = &[
+    "matrix",
+    "matrix3d",
+    "perspective",
+    "rotate",
+    "rotate3d",
+    "rotatex",
+    "rotatey",
+    "rotatez",
+    "scale",
+    "scale3d",
+    "scalex",
+    "scaley",
+    "scalez",
+    "skew",
+    "skewx",
+    "skewy",
+    "translate",
+    "translate3d",
+    "translatex",
+    "translatey",
+    "translatez",
+];
+
+/// The types matching a single component, built into the matcher.
+const BASIC_TYPES: &[&str] = &[
+    "angle",
+    "color",
+    "custom-ident",
+    "dashed-ident",
+    "easing-function",
+    "filter-function",
+    "flex",
+    "ident",
+    "image",
+    "integer",
+    "length",
+    "length-percentage",
+    "number",
+    "percentage",
+    "resolution",
+    "string",
+    "time",
+    "transform-function",
+    "url",
+];
+
+/// A term of a grammar in the value definition syntax of CSS, like `<length> | auto`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Term {
+    /// A keyword, matched ignoring case, like `auto`.
+    Keyword(String),
+    /// A literal character, like `/` or `,`.
+    Literal(char),
+    /// A data type, like `length` for `<length>`, or the value of a property, like
+    /// `'margin-top'` for `<'margin-top'>`.
+    Type(String),
+    /// A function, with the grammar of its arguments, like `fit-content( <length> )`.
+    Function(String, Box<Term>),
+    /// Terms matched in order.
+    Sequence(Vec<Term>),
+    /// Terms matched in any order, all of them: `a && b`.
+    AllOf(Vec<Term>),
+    /// Terms matched in any order, at least one of them: `a || b`.
+    AnyOf(Vec<Term>),
+    /// Exactly one of the terms: `a | b`.
+    OneOf(Vec<Term>),
+    /// A repeated term, like `<length>{1,4}`, separated by commas for `<image>#`.
+    Repeat {
+        term: Box<Term>,
+        min: usize,
+        max: Option<usize>,
+        comma: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SyntaxToken {
+    Type(String),
+    Keyword(String),
+    Literal(char),
+    Function(String),
+    OpenBracket,
+    CloseBracket,
+    CloseParenthesis,
+    Bar,
+    DoubleBar,
+    DoubleAmpersand,
+    Multiplier(usize, Option<usize>, bool),
+}
+
+/// Parses a grammar in the value definition syntax of CSS, like `[ <length> | auto ]{1,4}`.
+/// Ranges of numeric types, like `<length [0,∞]>`, are ignored.
+///
+/// # Arguments
+///
+/// * `syntax` - The grammar, as found in the specifications.
+///
+/// # Returns
+///
+/// * The `Term` of the grammar, or `None` if it is malformed.
+pub fn parse_syntax(syntax: &str) -> Option<Term> {
+    let tokens = tokenize_syntax(syntax)?;
+    let mut position = 0;
+    let term = parse_one_of(&tokens, &mut position)?;
+    (position == tokens.len()).then_some(term)
+}
+
+fn tokenize_syntax(syntax: &str) -> Option<Vec<SyntaxToken>> {
+    let mut tokens = Vec::new();
+    let mut chars = syntax.char_indices().peekable();
+    while let Some((offset, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '<' => {
+                let end = syntax[offset..].find('>')? + offset;
+                let name = syntax[offset + 1..end]
+                    .split_once(' ')
+                    .map_or(&syntax[offset + 1..end], |(name, _)| name);
+                tokens.push(SyntaxToken::Type(name.to_string()));
+                while chars.next_if(|&(idx, _)| idx <= end).is_some() {}
+            }
+            '[' => tokens.push(SyntaxToken::OpenBracket),
+            ']' => tokens.push(SyntaxToken::CloseBracket),
+            ')' => tokens.push(SyntaxToken::CloseParenthesis),
+            '|' if chars.next_if(|&(_, c)| c == '|').is_some() => {
+                tokens.push(SyntaxToken::DoubleBar)
+            }
+            '|' => tokens.push(SyntaxToken::Bar),
+            '&' => {
+                chars.next_if(|&(_, c)| c == '&')?;
+                tokens.push(SyntaxToken::DoubleAmpersand);
+            }
+            '?' => tokens.push(SyntaxToken::Multiplier(0, Some(1), false)),
+            '*' => tokens.push(SyntaxToken::Multiplier(0, None, false)),
+            '+' => tokens.push(SyntaxToken::Multiplier(1, None, false)),
+            '#' => tokens.push(SyntaxToken::Multiplier(1, None, true)),
+            // Required groups, like `[ a? b? ]!`, aren't distinguished
+            '!' => {}
+            '{' => {
+                let end = syntax[offset..].find('}')? + offset;
+                let bounds = &syntax[offset + 1..end];
+                let (min, max) = match bounds.split_once(',') {
+                    Some((min, "")) => (min.trim().parse().ok()?, None),
+                    Some((min, max)) => (min.trim().parse().ok()?, Some(max.trim().parse().ok()?)),
+                    None => {
+                        let count = bounds.trim().parse().ok()?;
+                        (count, Some(count))
+                    }
+                };
+                // `#{1,4}` bounds a comma separated list
+                let comma = matches!(tokens.last(), Some(SyntaxToken::Multiplier(1, None, true)));
+                if comma {
+                    tokens.pop();
+                }
+                tokens.push(SyntaxToken::Multiplier(min, max, comma));
+                while chars.next_if(|&(idx, _)| idx <= end).is_some() {}
+            }
+            ',' | '/' => tokens.push(SyntaxToken::Literal(c)),
+            c if c.is_alphanumeric() || c == '-' => {
+                let mut end = offset + c.len_utf8();
+                while let Some((idx, c)) = chars.next_if(|&(_, c)| c.is_alphanumeric() || c == '-')
+                {
+                    end = idx + c.len_utf8();
+                }
+                let name = syntax[offset..end].to_string();
+                if chars.next_if(|&(_, c)| c == '(').is_some() {
+                    tokens.push(SyntaxToken::Function(name));
+                } else {
+                    tokens.push(SyntaxToken::Keyword(name));
+                }
+            }
+            _ => return None,
+        }
+    }
+    Some(tokens)
+}
+
+fn parse_one_of(tokens: &[SyntaxToken], position: &mut usize) -> Option<Term> {
+    let mut terms = vec![parse_any_of(tokens, position)?];
+    while tokens.get(*position) == Some(&SyntaxToken::Bar) {
+        *position += 1;
+        terms.push(parse_any_of(tokens, position)?);
+    }
+    Some(single_or(terms, Term::OneOf))
+}
+
+fn parse_any_of(tokens: &[SyntaxToken], position: &mut usize) -> Option<Term> {
+    let mut terms = vec![parse_all_of(tokens, position)?];
+    while tokens.get(*position) == Some(&SyntaxToken::DoubleBar) {
+        *position += 1;
+        terms.push(parse_all_of(tokens, position)?);
+    }
+    Some(single_or(terms, Term::AnyOf))
+}
+
+fn parse_all_of(tokens: &[SyntaxToken], position: &mut usize) -> Option<Term> {
+    let mut terms = vec![parse_sequence(tokens, position)?];
+    while tokens.get(*position) == Some(&SyntaxToken::DoubleAmpersand) {
+        *position += 1;
+        terms.push(parse_sequence(tokens, position)?);
+    }
+    Some(single_or(terms, Term::AllOf))
+}
+
+fn parse_sequence(tokens: &[SyntaxToken], position: &mut usize) -> Option<Term> {
+    let mut terms = Vec::new();
+    while let Some(token) = tokens.get(*position) {
+        let mut term = match token {
+            SyntaxToken::Type(name) => Term::Type(name.clone()),
+            SyntaxToken::Keyword(name) => Term::Keyword(name.clone()),
+            SyntaxToken::Literal(c) => Term::Literal(*c),
+            SyntaxToken::Function(name) => {
+                *position += 1;
+                let arguments = parse_one_of(tokens, position)?;
+                if tokens.get(*position) != Some(&SyntaxToken::CloseParenthesis) {
+                    return None;
+                }
+                Term::Function(name.clone(), Box::new(arguments))
+            }
+            SyntaxToken::OpenBracket => {
+                *position += 1;
+                let group = parse_one_of(tokens, position)?;
+                if tokens.get(*position) != Some(&SyntaxToken::CloseBracket) {
+                    return None;
+                }
+                group
+            }
+            _ => break,
+        };
+        *position += 1;
+        while let Some(SyntaxToken::Multiplier(min, max, comma)) = tokens.get(*position) {
+            term = Term::Repeat {
+                term: Box::new(term),
+                min: *min,
+                max: *max,
+                comma: *comma,
+            };
+            *position += 1;
+        }
+        terms.push(term);
+    }
+    (!terms.is_empty()).then(|| single_or(terms, Term::Sequence))
+}
+
+fn single_or(mut terms: Vec<Term>, combine: fn(Vec<Term>) -> Term) -> Term {
+    if terms.len() == 1 {
+        terms.remove(0)
+    } else {
+        combine(terms)
+    }
+}
+
+/// The kind of a component value.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ComponentKind<'a> {
+    Ident,
+    Number {
+        integer: bool,
+        value: f64,
+    },
+    Dimension {
+        value: f64,
+        unit: &'a str,
+    },
+    Percentage,
+    Hash,
+    String,
+    /// An unquoted `url()`, whose content isn't parsed.
+    Url,
+    Function {
+        name: &'a str,
+        arguments: Vec<Component<'a>>,
+    },
+    /// Any other character, like `,` or `/`.
+    Delim(char),
+    /// A string interrupted by a line break or an unquoted `url()` with invalid characters,
+    /// which matches nothing.
+    Invalid,
+}
+
+/// A component value of a declaration, like `12px` or `rgb(0 0 0)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Component<'a> {
+    pub kind: ComponentKind<'a>,
+    /// The component, as written.
+    pub text: &'a str,
+    /// The byte range of the component in the source.
+    pub range: Range<usize>,
+}
+
+/// Splits a value into its component values, with the arguments of functions nested in
+/// them.
+///
+/// # Arguments
+///
+/// * `value` - The text of the value.
+/// * `value_start` - The byte offset of the value in the source, used to compute ranges.
+///
+/// # Returns
+///
+/// * A vector of `Component`, without whitespace and comments.
+pub fn parse_components(value: &str, value_start: usize) -> Vec<Component<'_>> {
+    read_components(
+        value,
+        value_start,
+        &mut Tokenizer::new(value, value_start),
+        false,
+    )
+}
+
+/// Groups tokens into components until the end of the value or, in a function, until its
+/// closing parenthesis, consumed.
+fn read_components<'a>(
+    value: &'a str,
+    value_start: usize,
+    tokens: &mut Tokenizer<'a>,
+    nested: bool,
+) -> Vec<Component<'a>> {
+    let mut components = Vec::new();
+    while let Some(token) = tokens.next() {
+        let kind = match token.kind {
+            TokenKind::Whitespace | TokenKind::Comment => continue,
+            TokenKind::RightParen if nested => break,
+            TokenKind::Ident => ComponentKind::Ident,
+            TokenKind::Number { integer, value } => ComponentKind::Number { integer, value },
+            TokenKind::Percentage { .. } => ComponentKind::Percentage,
+            TokenKind::Dimension { value, unit, .. } => ComponentKind::Dimension { value, unit },
+            TokenKind::Hash { .. } => ComponentKind::Hash,
+            TokenKind::String => ComponentKind::String,
+            TokenKind::Url => ComponentKind::Url,
+            TokenKind::BadString | TokenKind::BadUrl => ComponentKind::Invalid,
+            TokenKind::Function { name } => ComponentKind::Function {
+                name,
+                arguments: read_components(value, value_start, tokens, true),
+            },
+            TokenKind::LeftParen => ComponentKind::Function {
+                name: "",
+                arguments: read_components(value, value_start, tokens, true),
+            },
+            _ => ComponentKind::Delim(token.text.chars().next().unwrap_or_default()),
+        };
+        // Functions end after their arguments
+        let range = token.range.start..tokens.offset();
+        components.push(Component {
+            kind,
+            text: &value[range.start - value_start..range.end - value_start],
+            range,
+        });
+    }
+    components
+}
+
+/// Why a value doesn't match a grammar.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Mismatch {
+    /// A human readable message, like ``expected `<color>` but found `12px` ``.
+    pub message: String,
+    /// The byte range of the offending component in the source.
+    pub range: Range<usize>,
+}
+
+/// Validates a value against the grammar of a property.
+///
+/// # Arguments
+///
+/// * `property` - The property name, like `border-width`.
+/// * `components` - The components of the value, from `parse_components`.
+///
+/// # Returns
+///
+/// * `None` if the value matches, or if the grammar of the property isn't known, or the
+///   `Mismatch` of the first component that doesn't match.
+pub fn validate_value(property: &str, components: &[Component]) -> Option<Mismatch> {
+    let term = cached_syntax(&format!("'{}'", fold_case(property)))?;
+    validate_term(&term, components)
+}
+
+/// Validates a value against the grammar of a descriptor of an at-rule, like
+/// `font-display` in `@font-face`.
+///
+/// # Arguments
+///
+/// * `at_rule` - The at-rule name, with its `@`, like `@font-face`.
+/// * `descriptor` - The descriptor name, like `font-display`.
+/// * `components` - The components of the value, from `parse_components`.
+///
+/// # Returns
+///
+/// * `None` if the value matches, or if the grammar of the descriptor isn't known or
+///   references an unknown type, or the `Mismatch` of the first component that doesn't
+///   match.
+pub fn validate_descriptor_value(
+    at_rule: &str,
+    descriptor: &str,
+    components: &[Component],
+) -> Option<Mismatch> {
+    let term = cached_syntax(&format!("{} {}", fold_case(at_rule), fold_case(descriptor)))?;
+    validate_term(&term, components)
+}
+
+fn validate_term(term: &Term, components: &[Component]) -> Option<Mismatch> {
+    let mut matcher = Matcher::default();
+    let ends = matcher.match_term(term, components, 0, 0);
+    if ends.contains(&components.len()) {
+        return None;
+    }
+    let reached = ends.last().copied();
+    match matcher.furthest {
+        Some((position, expected)) if reached.is_none_or(|reached| position >= reached) => {
+            let expected = describe_expected(&expected);
+            Some(match components.get(position) {
+                Some(component) => Mismatch {
+                    message: format!("expected {expected} but found `{}`", component.text),
+                    range: component.range.clone(),
+                },
+                None => {
+                    let last = components.last()?;
+                    Mismatch {
+                        message: format!("expected {expected} after `{}`", last.text),
+                        range: last.range.clone(),
+                    }
+                }
+            })
+        }
+        _ => {
+            let component = components.get(reached.unwrap_or_default())?;
+            Some(Mismatch {
+                message: format!("unexpected `{}`", component.text),
+                range: component.range.clone(),
+            })
+        }
+    }
+}
+
+/// Returns whether a word is a keyword of a property, matched ignoring case: a CSS-wide
+/// keyword, a keyword of its grammar, like `solid` for `border`, or of a type it references,
+/// like a named color for `<color>`. Custom identifiers, like animation and font family
+/// names, aren't keywords.
+pub fn is_property_keyword(property: &str, word: &str) -> bool {
+    if contains_ignore_case(CSS_WIDE_KEYWORDS, word) {
+        return true;
+    }
+    let components = parse_components(word, 0);
+    let [component] = components.as_slice() else {
+        return false;
+    };
+    component.kind == ComponentKind::Ident
+        && cached_syntax(&format!("'{}'", fold_case(property)))
+            .is_some_and(|term| has_keyword(&term, component, 0))
+}
+
+fn has_keyword(term: &Term, component: &Component, depth: usize) -> bool {
+    match term {
+        Term::Keyword(keyword) => eq_ignore_case(keyword, component.text),
+        Term::Literal(_) => false,
+        Term::Type(name) if BASIC_TYPES.contains(&name.as_str()) => {
+            matches!(name.as_str(), "color" | "easing-function")
+                && matches_basic_type(name, component)
+        }
+        Term::Type(name) => {
+            depth < MAX_DEPTH
+                && cached_syntax(name).is_some_and(|term| has_keyword(&term, component, depth + 1))
+        }
+        Term::Function(_, arguments) => has_keyword(arguments, component, depth),
+        Term::Repeat { term, .. } => has_keyword(term, component, depth),
+        Term::Sequence(terms) | Term::AllOf(terms) | Term::AnyOf(terms) | Term::OneOf(terms) => {
+            terms.iter().any(|term| has_keyword(term, component, depth))
+        }
+    }
+}
+
+/// Returns whether the grammar of a property is known, and only references known types.
+pub fn is_validated(property: &str) -> bool {
+    cached_syntax(&format!("'{}'", fold_case(property))).is_some()
+}
+
+/// Parses the grammar of a property, like `'margin-top'`, of a descriptor, like
+/// `@font-face font-display`, or of a data type once, or `None` if it is unknown or
+/// references an unknown type.
+fn cached_syntax(name: &str) -> Option<Term> {
+    static CACHE: OnceLock<Mutex<HashMap<String, Option<Term>>>> = OnceLock::new();
+    let cache = CACHE.get_or_init(Default::default);
+    if let Some(term) = cache.lock().ok()?.get(name) {
+        return term.clone();
+    }
+    let syntax = match (name.strip_prefix('\''), name.split_once(' ')) {
+        (Some(property), _) => property_syntax(property.trim_end_matches('\''))?,
+        (None, Some((at_rule, descriptor))) => descriptor_syntax(at_rule, descriptor)?,
+        (None, None) => type_syntax(name)?,
+    };
+    let term = parse_syntax(syntax).filter(|term| references_known_types(term, 0));
+    cache.lock().ok()?.insert(name.to_string(), term.clone());
+    term
+}
+
+fn references_known_types(term: &Term, depth: usize) -> bool {
+    match term {
+        Term::Keyword(_) | Term::Literal(_) => true,
+        Term::Type(name) => {
+            BASIC_TYPES.contains(&name.as_str())
+                || (depth < MAX_DEPTH
+                    && match name.strip_prefix('\'') {
+                        Some(property) => property_syntax(property.trim_end_matches('\'')),
+                        None => type_syntax(name),
+                    }
+                    .and_then(parse_syntax)
+                    .is_some_and(|term| references_known_types(&term, depth + 1)))
+        }
+        Term::Function(_, arguments) => references_known_types(arguments, depth),
+        Term::Repeat { term, .. } => references_known_types(term, depth),
+        Term::Sequence(terms) | Term::AllOf(terms) | Term::AnyOf(terms) | Term::OneOf(terms) => {
+            terms.iter().all(|term| references_known_types(term, depth))
+        }
+    }
+}
+
+fn describe_expected(expected: &[String]) -> String {
+    let quoted: Vec<String> = expected
+        .iter()
+        .take(MAX_EXPECTED)
+        .map(|item| format!("`{item}`"))
+        .collect();
+    match quoted.as_slice() {
+        [] => "a value".to_string(),
+        [single] => single.clone(),
+        _ if expected.len() > MAX_EXPECTED => format!("one of {}, …", quoted.join(", ")),
+        [rest @ .., last] => format!("{} or {last}", rest.join(", ")),
+    }
+}
+
+/// Matches components against grammars, remembering the furthest component that couldn't
+/// be matched and what was expected there.
+#[derive(Default)]
+struct Matcher {
+    furthest: Option<(usize, Vec<String>)>,
+}
+
+impl Matcher {
+    fn expect(&mut self, position: usize, expected: String) {
+        match &mut self.furthest {
+            Some((furthest, items)) if *furthest == position => {
+                if !items.contains(&expected) {
+                    items.push(expected);
+                }
+            }
+            Some((furthest, _)) if *furthest > position => {}
+            _ => self.furthest = Some((position, vec![expected])),
+        }
+    }
+
+    /// Returns every position where a match of the term starting at `position` can end.
+    fn match_term(
+        &mut self,
+        term: &Term,
+        components: &[Component],
+        position: usize,
+        depth: usize,
+    ) -> BTreeSet<usize> {
+        let mut ends = BTreeSet::new();
+        let component = components.get(position);
+        match term {
+            Term::Keyword(keyword) => {
+                if component.is_some_and(|component| {
+                    component.kind == ComponentKind::Ident
+                        && eq_ignore_case(component.text, keyword)
+                }) {
+                    ends.insert(position + 1);
+                } else {
+                    self.expect(position, keyword.clone());
+                }
+            }
+            Term::Literal(c) => {
+                if component.is_some_and(|component| component.kind == ComponentKind::Delim(*c)) {
+                    ends.insert(position + 1);
+                } else {
+                    self.expect(position, c.to_string());
+                }
+            }
+            Term::Type(name) if BASIC_TYPES.contains(&name.as_str()) => {
+                if component.is_some_and(|component| matches_basic_type(name, component)) {
+                    ends.insert(position + 1);
+                } else {
+                    self.expect(position, format!("<{name}>"));
+                }
+            }
+            Term::Type(name) => {
+                let Some(inner) = cached_syntax(name).filter(|_| depth < MAX_DEPTH) else {
+                    return ends;
+                };
+                let outer = self.furthest.take();
+                ends = self.match_term(&inner, components, position, depth + 1);
+                let inner_furthest = std::mem::replace(&mut self.furthest, outer);
+                match inner_furthest {
+                    // Data types are described by their name, unless partially matched
+                    Some((furthest, _)) if furthest == position && !name.starts_with('\'') => {
+                        self.expect(position, format!("<{name}>"));
+                    }
+                    Some((furthest, items)) => {
+                        for item in items {
+                            self.expect(furthest, item);
+                        }
+                    }
+                    None => {}
+                }
+            }
+            Term::Function(name, arguments) => match component.map(|component| &component.kind) {
+                Some(ComponentKind::Function {
+                    name: function,
+                    arguments: values,
+                }) if eq_ignore_case(function, name) => {
+                    if self
+                        .match_term(arguments, values, 0, depth)
+                        .contains(&values.len())
+                    {
+                        ends.insert(position + 1);
+                    }
+                }
+                _ => self.expect(position, format!("{name}()")),
+            },
+            Term::Sequence(terms) => {
+                ends.insert(position);
+                for term in terms {
+                    ends = ends
+                        .into_iter()
+                        .flat_map(|start| self.match_term(term, components, start, depth))
+                        .collect();
+                }
+            }
+            Term::OneOf(terms) => {
+                for term in terms {
+                    ends.extend(self.match_term(term, components, position, depth));
+                }
+            }
+            Term::AnyOf(terms) => {
+                ends = self.match_combination(terms, false, components, position, depth);
+            }
+            Term::AllOf(terms) => {
+                ends = self.match_combination(terms, true, components, position, depth);
+            }
+            Term::Repeat {
+                term,
+                min,
+                max,
+                comma,
+            } => {
+                let mut starts = BTreeSet::from([position]);
+                if *min == 0 {
+                    ends.insert(position);
+                }
+                let mut count = 0;
+                while !starts.is_empty() && max.is_none_or(|max| count < max) {
+                    count += 1;
+                    let mut next = BTreeSet::new();
+                    for start in starts {
+                        let start = if *comma && count > 1 {
+                            match components.get(start) {
+                                Some(component) if component.kind == ComponentKind::Delim(',') => {
+                                    start + 1
+                                }
+                                _ => {
+                                    self.expect(start, ",".to_string());
+                                    continue;
+                                }
+                            }
+                        } else {
+                            start
+                        };
+                        // Repetitions must consume components, or they would never end
+                        next.extend(
+                            self.match_term(term, components, start, depth)
+                                .into_iter()
+                                .filter(|&end| end > start),
+                        );
+                    }
+                    if count >= *min {
+                        ends.extend(next.iter().copied());
+                    }
+                    starts = next;
+                }
+            }
+        }
+        ends
+    }
+
+    /// Matches the terms of `a || b` or, with `all`, of `a && b`, in any order: a search over
+    /// the position reached and the set of terms already matched, each state visited once.
+    fn match_combination(
+        &mut self,
+        terms: &[Term],
+        all: bool,
+        components: &[Component],
+        position: usize,
+        depth: usize,
+    ) -> BTreeSet<usize> {
+        let mut ends = BTreeSet::new();
+        // Grammars combine a handful of terms, far fewer than the bits of the set
+        let terms = &terms[..terms.len().min(u64::BITS as usize)];
+        let every_term = u64::MAX
+            .checked_shr(u64::BITS - terms.len() as u32)
+            .unwrap_or(0);
+        let mut term_ends: HashMap<(usize, usize), BTreeSet<usize>> = HashMap::new();
+        let mut visited = HashSet::new();
+        let mut pending = vec![(position, 0u64)];
+        while let Some((start, used)) = pending.pop() {
+            if !visited.insert((start, used)) {
+                continue;
+            }
+            if (all && used == every_term) || (!all && used != 0) {
+                ends.insert(start);
+            }
+            for (idx, term) in terms.iter().enumerate() {
+                if used & (1 << idx) != 0 {
+                    continue;
+                }
+                let term_ends = term_ends
+                    .entry((idx, start))
+                    .or_insert_with(|| self.match_term(term, components, start, depth));
+                for &end in term_ends.iter() {
+                    // In `||`, terms matching nothing don't count as one of the terms
+                    if all || end > start {
+                        pending.push((end, used | (1 << idx)));
+                    }
+                }
+            }
+        }
+        ends
+    }
+}
+
+/// Returns whether a component is a value of a basic type of the value definition syntax,
+/// like `length` or `color`.
+pub(crate) fn matches_basic_type(name: &str, component: &Component) -> bool {
+    let is_numeric_function = matches!(
+        &component.kind,
+        ComponentKind::Function { name, .. } if contains_ignore_case(NUMERIC_FUNCTIONS, name)
+    );
+    let has_unit = |units: &[&str]| matches!(&component.kind, ComponentKind::Dimension { unit, .. } if contains_ignore_case(units, unit));
+    let is_zero = matches!(component.kind, ComponentKind::Number { value, .. } if value == 0.0);
+    let is_function = |names: &[&str]| matches!(&component.kind, ComponentKind::Function { name, .. } if contains_ignore_case(names, name));
+    let is_ident = component.kind == ComponentKind::Ident;
+    match name {
+        "length" => has_unit(LENGTH_UNITS) || is_zero || is_numeric_function,
+        "percentage" => component.kind == ComponentKind::Percentage || is_numeric_function,
+        "length-percentage" => {
+            has_unit(LENGTH_UNITS)
+                || is_zero
+                || component.kind == ComponentKind::Percentage
+                || is_numeric_function
+        }
+        "number" => matches!(component.kind, ComponentKind::Number { .. }) || is_numeric_function,
+        "integer" => {
+            matches!(component.kind, ComponentKind::Number { integer: true, .. })
+                || is_numeric_function
+        }
+        "angle" => has_unit(ANGLE_UNITS) || is_zero || is_numeric_function,
+        "time" => has_unit(TIME_UNITS) || is_numeric_function,
+        "resolution" => has_unit(RESOLUTION_UNITS) || is_numeric_function,
+        "flex" => has_unit(&["fr"]) || is_numeric_function,
+        "string" => component.kind == ComponentKind::String,
+        "url" => component.kind == ComponentKind::Url || is_function(&["url", "src"]),
+        "ident" => is_ident,
+        "custom-ident" => {
+            is_ident
+                && !contains_ignore_case(CSS_WIDE_KEYWORDS, component.text)
+                && !eq_ignore_case(component.text, "default")
+        }
+        "dashed-ident" => is_ident && component.text.starts_with("--"),
+        "color" => match &component.kind {
+            ComponentKind::Hash => {
+                let hex = &component.text[1..];
+                matches!(hex.len(), 3 | 4 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit())
+            }
+            ComponentKind::Ident => {
+                named_color(component.text).is_some()
+                    || SYSTEM_COLORS
+                        .iter()
+                        .any(|(system, _)| eq_ignore_case(system, component.text))
+                    || contains_ignore_case(&["currentcolor", "transparent"], component.text)
+            }
+            _ => is_function(COLOR_FUNCTIONS),
+        },
+        "image" => {
+            component.kind == ComponentKind::Url
+                || is_function(&["url", "src"])
+                || is_function(IMAGE_FUNCTIONS)
+        }
+        "easing-function" => {
+            (is_ident && contains_ignore_case(EASING_KEYWORDS, component.text))
+                || is_function(EASING_FUNCTIONS)
+        }
+        "filter-function" => is_function(FILTER_FUNCTIONS),
+        "transform-function" => is_function(TRANSFORM_FUNCTIONS),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(property: &str, value: &str) -> Option<String> {
+        validate_value(property, &parse_components(value, 0)).map(|mismatch| mismatch.message)
+    }
+
+    #[test]
+    fn test_parse_syntax() {
+        assert_eq!(
+            parse_syntax("<length [0,∞]>{1,4} | auto"),
+            Some(Term::OneOf(vec![
+                Term::Repeat {
+                    term: Box::new(Term::Type("length".to_string())),
+                    min: 1,
+                    max: Some(4),
+                    comma: false,
+                },
+                Term::Keyword("auto".to_string()),
+            ]))
+        );
+        assert_eq!(
+            parse_syntax("a && b || c"),
+            Some(Term::AnyOf(vec![
+                Term::AllOf(vec![
+                    Term::Keyword("a".to_string()),
+                    Term::Keyword("b".to_string()),
+                ]),
+                Term::Keyword("c".to_string()),
+            ]))
+        );
+        assert!(matches!(
+            parse_syntax("<image>#{1,2}"),
+            Some(Term::Repeat {
+                min: 1,
+                max: Some(2),
+                comma: true,
+                ..
+            })
+        ));
+        assert_eq!(parse_syntax("[ a | b"), None);
+    }
+
+    #[test]
+    fn test_parse_components() {
+        let components =
+            parse_components("1px -.5em 50% #fff \"a\" url(a.png) rgb(0 0 0 / 50%), a", 4);
+        let kinds: Vec<_> = components
+            .iter()
+            .map(|component| match &component.kind {
+                ComponentKind::Function { name, arguments } => {
+                    format!("{name}({})", arguments.len())
+                }
+                kind => format!("{kind:?}"),
+            })
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                "Dimension { value: 1.0, unit: \"px\" }",
+                "Dimension { value: -0.5, unit: \"em\" }",
+                "Percentage",
+                "Hash",
+                "String",
+                "Url",
+                "rgb(5)",
+                "Delim(',')",
+                "Ident",
+            ]
+        );
+        assert_eq!(components[1].range, 8..13);
+        assert_eq!(components[6].text, "rgb(0 0 0 / 50%)");
+        let kinds: Vec<_> = parse_components("\"a\nb url(a b)", 0)
+            .into_iter()
+            .map(|component| component.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ComponentKind::Invalid,
+                ComponentKind::Ident,
+                ComponentKind::Invalid
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_value() {
+        assert_eq!(validate("color", "red"), None);
+        assert_eq!(validate("COLOR", "#ff000080"), None);
+        assert_eq!(
+            validate("color", "12px"),
+            Some("expected `<color>` but found `12px`".to_string())
+        );
+        assert_eq!(validate("margin", "0 auto calc(1px + 2em)"), None);
+        assert_eq!(
+            validate("margin", "1px 2px 3px 4px 5px"),
+            Some("unexpected `5px`".to_string())
+        );
+        assert_eq!(validate("border", "1px solid rgba(0, 0, 0, 0.5)"), None);
+        assert_eq!(
+            validate("border", "1px solid 2px"),
+            Some("expected `<color>` but found `2px`".to_string())
+        );
+        assert_eq!(validate("transition-duration", "1s, 200ms"), None);
+        assert_eq!(
+            validate("transition-duration", "1s,"),
+            Some("expected `<time>` after `,`".to_string())
+        );
+        assert_eq!(validate("display", "inline flex"), None);
+        assert_eq!(validate("display", "list-item block"), None);
+        assert_eq!(
+            validate("overflow", "hidden red"),
+            Some(
+                "expected `visible`, `hidden`, `clip`, `scroll`, `auto` or `overlay` but found `red`"
+                    .to_string()
+            )
+        );
+        assert_eq!(
+            validate("box-shadow", "inset 0 1px 2px #000, 0 0 0 1px red"),
+            None
+        );
+        assert_eq!(
+            validate("font-family", "\"Open Sans\", Helvetica Neue, sans-serif"),
+            None
+        );
+        assert_eq!(validate("flex", "1 1 0%"), None);
+        assert_eq!(validate("width", "fit-content(20em)"), None);
+        assert_eq!(
+            validate("z-index", "1.5"),
+            Some("expected `auto` or `<integer>` but found `1.5`".to_string())
+        );
+        assert_eq!(validate("grid-template-columns", "nonsense"), None);
+    }
+
+    #[test]
+    fn test_validate_descriptor_value() {
+        let validate = |descriptor: &str, value: &str| {
+            validate_descriptor_value("@font-face", descriptor, &parse_components(value, 0))
+                .map(|mismatch| mismatch.message)
+        };
+        assert_eq!(validate("Font-Display", "SWAP"), None);
+        assert_eq!(validate("font-weight", "100 900"), None);
+        assert_eq!(
+            validate("font-feature-settings", "\"liga\" 0, \"smcp\""),
+            None
+        );
+        assert_eq!(
+            validate("size-adjust", "auto"),
+            Some("expected `<percentage>` but found `auto`".to_string())
+        );
+        // The grammar of `src` references types validated by its own rule
+        assert_eq!(validate("src", "nonsense"), None);
+        assert_eq!(validate("color", "red"), None);
+    }
+
+    #[test]
+    fn test_validate_combinations() {
+        let letters = "a b c d e f g h i j k l m n o p";
+        let any_of = parse_syntax(&letters.replace(' ', " || ")).unwrap();
+        let all_of = parse_syntax(&letters.replace(' ', " && ")).unwrap();
+        let reversed: String = letters.chars().rev().collect();
+        for term in [&any_of, &all_of] {
+            assert_eq!(validate_term(term, &parse_components(&reversed, 0)), None);
+        }
+        assert_eq!(validate_term(&any_of, &parse_components("p a", 0)), None);
+        assert!(validate_term(&all_of, &parse_components("p a", 0)).is_some());
+        assert!(validate_term(&any_of, &parse_components("a b a", 0)).is_some());
+    }
+
+    #[test]
+    fn test_property_syntaxes_are_valid() {
+        for (property, _) in crate::data::property_syntaxes::PROPERTY_SYNTAXES {
+            assert!(is_validated(property), "{property}");
+        }
+    }
+}
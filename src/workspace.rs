@@ -0,0 +1,580 @@
+use crate::budget::{Budget, Budgeted};
+use crate::dialect::Dialect;
+use crate::features::diagnostics::get_diagnostics_with_parse;
+use crate::features::symbols::{get_document_symbols_with_parse, DocumentSymbolOptions};
+use crate::file_system::FileSystemProvider;
+use crate::import_graph::ImportGraph;
+use crate::layers::layer_names;
+use crate::parser::parse_css_with_dialect;
+use crate::rules::RuleRegistry;
+use crate::selectors::selector_names;
+use biome_css_parser::CssParse;
+use lsp_types::{Diagnostic, DocumentSymbol, Uri, WorkspaceFolder, WorkspaceFoldersChangeEvent};
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+/// How a stylesheet of the workspace is handled, decided by `FileSettings`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileHandling {
+    /// The stylesheet is indexed and linted.
+    Lint,
+    /// The stylesheet is indexed for completion and navigation, but not linted, like
+    /// vendored or generated code.
+    IndexOnly,
+    /// The stylesheet is neither indexed nor linted.
+    Ignore,
+}
+
+/// The glob patterns of stylesheets that aren't linted, like `**/node_modules/**`.
+///
+/// Patterns match the path of stylesheets, or their path relative to a workspace folder:
+/// `**` matches any number of segments, `*` any characters of a segment and `?` a single
+/// one.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+#[non_exhaustive]
+pub struct FileSettings {
+    /// Stylesheets indexed but not linted.
+    pub index_only: Vec<String>,
+    /// Stylesheets neither indexed nor linted, taking precedence over `index_only`.
+    pub ignore: Vec<String>,
+}
+
+/// What is known about a stylesheet of the workspace once indexed.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexedDocument {
+    pub symbols: Vec<DocumentSymbol>,
+    pub diagnostics: Vec<Diagnostic>,
+    /// The full names of the cascade layers it declares, see `find_layers`.
+    pub layers: Vec<String>,
+    /// The selectors its style rules use, with their number of uses, see
+    /// `find_selector_names`.
+    pub selectors: Vec<(String, usize)>,
+}
+
+/// Indexes the stylesheets of the workspace folders, so features can work across
+/// documents that aren't open in the editor.
+///
+/// Adding a folder only queues its stylesheets; they are indexed by `index_pending`, a bit
+/// at a time, so hosts can do it in the background between requests.
+#[derive(Debug, Default)]
+pub struct Workspace {
+    folders: Vec<WorkspaceFolder>,
+    pending: VecDeque<Uri>,
+    documents: HashMap<Uri, IndexedDocument>,
+    import_graph: ImportGraph,
+    file_settings: FileSettings,
+}
+
+impl Workspace {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies a `workspace/didChangeWorkspaceFolders` notification.
+    ///
+    /// The documents of removed folders are dropped from the index and the import graph
+    /// right away, while the stylesheets of added folders are queued for indexing.
+    ///
+    /// # Arguments
+    ///
+    /// * `event` - The added and removed `WorkspaceFolder`s.
+    /// * `file_system` - The `FileSystemProvider` listing the documents of added folders.
+    pub fn change_folders(
+        &mut self,
+        event: &WorkspaceFoldersChangeEvent,
+        file_system: &dyn FileSystemProvider,
+    ) {
+        for removed in &event.removed {
+            self.folders.retain(|folder| folder.uri != removed.uri);
+            self.pending.retain(|uri| {
+                !is_in_folder(&removed.uri, uri) || is_in_any_folder(&self.folders, uri)
+            });
+            let dropped: Vec<Uri> = self
+                .documents
+                .keys()
+                .filter(|uri| {
+                    is_in_folder(&removed.uri, uri) && !is_in_any_folder(&self.folders, uri)
+                })
+                .cloned()
+                .collect();
+            for uri in dropped {
+                self.documents.remove(&uri);
+                self.import_graph.remove(&uri);
+            }
+        }
+
+        for added in &event.added {
+            if self.folders.iter().any(|folder| folder.uri == added.uri) {
+                continue;
+            }
+            self.folders.push(added.clone());
+            let mut stylesheets: Vec<Uri> = file_system
+                .list_files(&added.uri)
+                .into_iter()
+                .filter(|uri| Dialect::from_path(uri.as_str()).is_some())
+                .filter(|uri| self.handling(uri) != FileHandling::Ignore)
+                .filter(|uri| !self.pending.contains(uri))
+                .collect();
+            stylesheets.sort();
+            self.pending.extend(stylesheets);
+        }
+    }
+
+    /// Indexes queued stylesheets until the budget runs out.
+    ///
+    /// # Arguments
+    ///
+    /// * `budget` - The `Budget` of this run, counting one item per stylesheet.
+    /// * `file_system` - The `FileSystemProvider` reading the stylesheets.
+    ///
+    /// # Returns
+    ///
+    /// * The URIs indexed by this run, truncated if stylesheets are still queued.
+    pub fn index_pending(
+        &mut self,
+        budget: &Budget,
+        file_system: &dyn FileSystemProvider,
+    ) -> Budgeted<Uri> {
+        let tracker = budget.start();
+        let mut indexed = Vec::new();
+        while !self.pending.is_empty() && tracker.allow() {
+            let Some(uri) = self.pending.pop_front() else {
+                break;
+            };
+            let Some(source) = file_system.read_file(&uri) else {
+                continue;
+            };
+            let dialect = Dialect::from_path(uri.as_str()).unwrap_or_default();
+            self.import_graph
+                .update(&uri, &source, dialect, file_system);
+            // Every index is built from a single parse, with the dialect of the stylesheet
+            let parse = parse_css_with_dialect(&source, dialect);
+            let root = parse.syntax();
+            let unlimited = &Budget::UNLIMITED;
+            let diagnostics = match self.handling(&uri) {
+                FileHandling::Lint => {
+                    let rules = RuleRegistry::default();
+                    let parse = CssParse::new(root.clone(), parse.diagnostics().to_vec());
+                    get_diagnostics_with_parse(&uri, &source, parse, dialect, &rules, unlimited)
+                        .items
+                }
+                _ => Vec::new(),
+            };
+            let options = DocumentSymbolOptions::default();
+            self.documents.insert(
+                uri.clone(),
+                IndexedDocument {
+                    symbols: get_document_symbols_with_parse(&source, parse, &options, unlimited)
+                        .items,
+                    diagnostics,
+                    layers: layer_names(&root),
+                    selectors: selector_names(&root),
+                },
+            );
+            indexed.push(uri);
+        }
+        let mut result = tracker.finish(indexed);
+        result.truncated = !self.pending.is_empty();
+        result
+    }
+
+    /// Replaces the patterns of stylesheets that aren't linted.
+    ///
+    /// Indexed stylesheets that are now ignored are dropped, and the ones now indexed only
+    /// lose their diagnostics. The ones linted again are queued, to be indexed with their
+    /// diagnostics. Stylesheets that were ignored are indexed again once their folder is
+    /// added again.
+    pub fn set_file_settings(&mut self, settings: FileSettings) {
+        let mut linted: Vec<Uri> = self
+            .documents
+            .keys()
+            .filter(|uri| self.handling(uri) == FileHandling::IndexOnly)
+            .cloned()
+            .collect();
+        self.file_settings = settings;
+        linted
+            .retain(|uri| self.handling(uri) == FileHandling::Lint && !self.pending.contains(uri));
+        linted.sort();
+        self.pending.extend(linted);
+        let ignored: Vec<Uri> = self
+            .documents
+            .keys()
+            .chain(&self.pending)
+            .filter(|uri| self.handling(uri) == FileHandling::Ignore)
+            .cloned()
+            .collect();
+        for uri in ignored {
+            self.pending.retain(|pending| *pending != uri);
+            self.documents.remove(&uri);
+            self.import_graph.remove(&uri);
+        }
+        let index_only: Vec<Uri> = self
+            .documents
+            .keys()
+            .filter(|uri| self.handling(uri) == FileHandling::IndexOnly)
+            .cloned()
+            .collect();
+        for uri in index_only {
+            if let Some(document) = self.documents.get_mut(&uri) {
+                document.diagnostics.clear();
+            }
+        }
+    }
+
+    /// Returns how a stylesheet is handled, according to the `FileSettings`.
+    pub fn handling(&self, uri: &Uri) -> FileHandling {
+        let path = uri.path().as_str();
+        // The path itself, then its path relative to each folder containing it
+        let mut paths = vec![path.trim_start_matches('/')];
+        paths.extend(self.folders.iter().filter_map(|folder| {
+            uri.as_str()
+                .strip_prefix(folder.uri.as_str().trim_end_matches('/'))?
+                .strip_prefix('/')
+        }));
+        let matches = |patterns: &[String]| {
+            patterns
+                .iter()
+                .any(|pattern| paths.iter().any(|path| matches_glob(pattern, path)))
+        };
+        if matches(&self.file_settings.ignore) {
+            FileHandling::Ignore
+        } else if matches(&self.file_settings.index_only) {
+            FileHandling::IndexOnly
+        } else {
+            FileHandling::Lint
+        }
+    }
+
+    /// Returns the workspace folders.
+    pub fn folders(&self) -> &[WorkspaceFolder] {
+        &self.folders
+    }
+
+    /// Returns the number of stylesheets waiting to be indexed.
+    pub fn pending_len(&self) -> usize {
+        self.pending.len()
+    }
+
+    /// Returns an indexed stylesheet.
+    pub fn get(&self, uri: &Uri) -> Option<&IndexedDocument> {
+        self.documents.get(uri)
+    }
+
+    /// Returns the URIs of the indexed stylesheets, sorted.
+    pub fn indexed_uris(&self) -> Vec<&Uri> {
+        let mut uris: Vec<&Uri> = self.documents.keys().collect();
+        uris.sort();
+        uris
+    }
+
+    /// Returns the cascade layers declared by the indexed stylesheets, without duplicates,
+    /// in the order of the stylesheets' URIs, then of their declarations.
+    pub fn layers(&self) -> Vec<String> {
+        let mut seen = HashSet::new();
+        self.indexed_uris()
+            .into_iter()
+            .flat_map(|uri| &self.documents[uri].layers)
+            .filter(|layer| seen.insert(layer.as_str()))
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the selectors used by the indexed stylesheets, with their number of uses
+    /// added up, most used first.
+    ///
+    /// # Arguments
+    ///
+    /// * `except` - A stylesheet to leave out, like an open document whose indexed version
+    ///   is outdated.
+    pub fn selectors(&self, except: Option<&Uri>) -> Vec<(String, usize)> {
+        let mut total: HashMap<&str, usize> = HashMap::new();
+        for (uri, document) in &self.documents {
+            if Some(uri) == except {
+                continue;
+            }
+            for (name, uses) in &document.selectors {
+                *total.entry(name).or_default() += uses;
+            }
+        }
+        let mut selectors: Vec<(String, usize)> = total
+            .into_iter()
+            .map(|(name, uses)| (name.to_string(), uses))
+            .collect();
+        selectors.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        selectors
+    }
+
+    /// Returns the imports between the indexed stylesheets.
+    pub fn import_graph(&self) -> &ImportGraph {
+        &self.import_graph
+    }
+}
+
+/// Returns whether a URI is under any of the folders, which may be nested.
+fn is_in_any_folder(folders: &[WorkspaceFolder], uri: &Uri) -> bool {
+    folders.iter().any(|folder| is_in_folder(&folder.uri, uri))
+}
+
+/// Returns whether a URI is under a folder, comparing whole path segments.
+fn is_in_folder(folder: &Uri, uri: &Uri) -> bool {
+    let folder = folder.as_str().trim_end_matches('/');
+    uri.as_str()
+        .strip_prefix(folder)
+        .is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// Returns whether a path, without a leading `/`, matches a glob pattern, segment by
+/// segment.
+fn matches_glob(pattern: &str, path: &str) -> bool {
+    let pattern: Vec<&str> = pattern.trim_start_matches('/').split('/').collect();
+    let path: Vec<&str> = path.split('/').collect();
+    matches_segments(&pattern, &path)
+}
+
+fn matches_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((&"**", rest)) => {
+            (0..=path.len()).any(|skipped| matches_segments(rest, &path[skipped..]))
+        }
+        Some((segment, rest)) => path.split_first().is_some_and(|(first, path_rest)| {
+            matches_segment(segment.as_bytes(), first.as_bytes())
+                && matches_segments(rest, path_rest)
+        }),
+    }
+}
+
+fn matches_segment(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => {
+            (0..=name.len()).any(|skipped| matches_segment(rest, &name[skipped..]))
+        }
+        Some((b'?', rest)) => !name.is_empty() && matches_segment(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches_segment(rest, &name[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct MemoryFileSystem(Vec<(&'static str, &'static str)>);
+
+    impl FileSystemProvider for MemoryFileSystem {
+        fn read_file(&self, uri: &Uri) -> Option<String> {
+            self.0
+                .iter()
+                .find(|(file, _)| *file == uri.as_str())
+                .map(|(_, source)| source.to_string())
+        }
+
+        fn list_files(&self, folder: &Uri) -> Vec<Uri> {
+            self.0
+                .iter()
+                .map(|(file, _)| file.parse().unwrap())
+                .filter(|uri| is_in_folder(folder, uri))
+                .collect()
+        }
+    }
+
+    fn folder(uri: &str) -> WorkspaceFolder {
+        WorkspaceFolder {
+            uri: uri.parse().unwrap(),
+            name: uri.rsplit('/').next().unwrap().to_string(),
+        }
+    }
+
+    #[test]
+    fn test_workspace_folders_add_and_remove() {
+        let file_system = MemoryFileSystem(vec![
+            ("file:///app/main.css", "@import 'base.css';\n.a {}"),
+            ("file:///app/base.css", ".b { color: red; }"),
+            ("file:///app/README.md", "# App"),
+            ("file:///application/c.css", ".c {}"),
+        ]);
+        let mut workspace = Workspace::new();
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![folder("file:///app"), folder("file:///application")],
+                removed: vec![],
+            },
+            &file_system,
+        );
+        assert_eq!(workspace.pending_len(), 3, "Only stylesheets are indexed");
+
+        let budget = Budget {
+            max_duration: None,
+            max_items: Some(2),
+        };
+        let first = workspace.index_pending(&budget, &file_system);
+        assert_eq!(first.items.len(), 2);
+        assert!(first.truncated);
+        let second = workspace.index_pending(&budget, &file_system);
+        assert_eq!(second.items.len(), 1);
+        assert!(!second.truncated);
+
+        let base: Uri = "file:///app/base.css".parse().unwrap();
+        assert_eq!(workspace.get(&base).unwrap().symbols.len(), 1);
+        assert_eq!(workspace.import_graph().importers_of(&base).len(), 1);
+        let main: Uri = "file:///app/main.css".parse().unwrap();
+        assert_eq!(
+            workspace.selectors(Some(&main)),
+            vec![(".b".to_string(), 1), (".c".to_string(), 1)]
+        );
+
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![],
+                removed: vec![folder("file:///app")],
+            },
+            &file_system,
+        );
+        assert_eq!(workspace.folders().len(), 1);
+        assert_eq!(
+            workspace
+                .indexed_uris()
+                .iter()
+                .map(|uri| uri.as_str())
+                .collect::<Vec<_>>(),
+            vec!["file:///application/c.css"]
+        );
+        assert!(workspace.import_graph().importers_of(&base).is_empty());
+    }
+
+    #[test]
+    fn test_workspace_layers() {
+        let file_system = MemoryFileSystem(vec![
+            (
+                "file:///app/b.css",
+                "@layer base, theme;\n@layer base.reset {}",
+            ),
+            ("file:///app/a.css", "@layer theme, utilities;"),
+        ]);
+        let mut workspace = Workspace::new();
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![folder("file:///app")],
+                removed: vec![],
+            },
+            &file_system,
+        );
+        workspace.index_pending(&Budget::UNLIMITED, &file_system);
+        assert_eq!(
+            workspace.layers(),
+            vec!["theme", "utilities", "base", "base.reset"]
+        );
+    }
+
+    #[test]
+    fn test_workspace_dialects() {
+        let file_system = MemoryFileSystem(vec![(
+            "file:///app/a.scss",
+            "// don't index this as CSS\n.card {\n  .title { color: red; }\n}\n",
+        )]);
+        let mut workspace = Workspace::new();
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![folder("file:///app")],
+                removed: vec![],
+            },
+            &file_system,
+        );
+        workspace.index_pending(&Budget::UNLIMITED, &file_system);
+        let indexed = workspace
+            .get(&"file:///app/a.scss".parse().unwrap())
+            .unwrap();
+        assert!(indexed.diagnostics.is_empty(), "{:?}", indexed.diagnostics);
+        assert_eq!(indexed.symbols.len(), 1);
+        assert_eq!(indexed.symbols[0].name, ".card");
+        assert_eq!(indexed.symbols[0].children.as_ref().unwrap().len(), 1);
+        assert_eq!(indexed.selectors, vec![(".card".to_string(), 1)]);
+    }
+
+    #[test]
+    fn test_workspace_remove_folder_while_indexing() {
+        let file_system = MemoryFileSystem(vec![
+            ("file:///a/x.css", ".x {}"),
+            ("file:///a/nested/y.css", ".y {}"),
+        ]);
+        let mut workspace = Workspace::new();
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![folder("file:///a"), folder("file:///a/nested")],
+                removed: vec![],
+            },
+            &file_system,
+        );
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![],
+                removed: vec![folder("file:///a")],
+            },
+            &file_system,
+        );
+        assert_eq!(
+            workspace.pending_len(),
+            1,
+            "Documents of a remaining nested folder stay queued"
+        );
+    }
+
+    #[test]
+    fn test_matches_glob() {
+        assert!(matches_glob(
+            "**/node_modules/**",
+            "app/node_modules/a/b.css"
+        ));
+        assert!(matches_glob("**/node_modules/**", "node_modules/b.css"));
+        assert!(!matches_glob("**/node_modules/**", "app/node_modules.css"));
+        assert!(matches_glob("dist/*.min.css", "dist/app.min.css"));
+        assert!(!matches_glob("dist/*.min.css", "dist/nested/app.min.css"));
+        assert!(matches_glob("/vendor/?.css", "vendor/a.css"));
+        assert!(!matches_glob("vendor/?.css", "vendor/ab.css"));
+    }
+
+    #[test]
+    fn test_workspace_file_settings() {
+        let file_system = MemoryFileSystem(vec![
+            ("file:///app/main.css", ".a { margin }"),
+            ("file:///app/node_modules/lib/lib.css", ".lib { margin }"),
+            ("file:///app/dist/main.css", ".a { margin }"),
+        ]);
+        let mut workspace = Workspace::new();
+        workspace.set_file_settings(FileSettings {
+            index_only: vec!["**/node_modules/**".to_string()],
+            ignore: vec!["dist/**".to_string()],
+        });
+        workspace.change_folders(
+            &WorkspaceFoldersChangeEvent {
+                added: vec![folder("file:///app")],
+                removed: vec![],
+            },
+            &file_system,
+        );
+        workspace.index_pending(&Budget::UNLIMITED, &file_system);
+        assert_eq!(workspace.indexed_uris().len(), 2, "dist is ignored");
+
+        let main: Uri = "file:///app/main.css".parse().unwrap();
+        let lib: Uri = "file:///app/node_modules/lib/lib.css".parse().unwrap();
+        assert!(!workspace.get(&main).unwrap().diagnostics.is_empty());
+        let indexed_lib = workspace.get(&lib).unwrap();
+        assert_eq!(indexed_lib.symbols.len(), 1, "Still contributes symbols");
+        assert!(indexed_lib.diagnostics.is_empty());
+
+        workspace.set_file_settings(FileSettings {
+            index_only: vec!["main.css".to_string()],
+            ignore: vec!["**/node_modules/**".to_string()],
+        });
+        assert_eq!(workspace.handling(&lib), FileHandling::Ignore);
+        assert!(workspace.get(&lib).is_none());
+        assert!(workspace.get(&main).unwrap().diagnostics.is_empty());
+
+        // Linted again, so queued to get its diagnostics back
+        workspace.set_file_settings(FileSettings::default());
+        assert_eq!(workspace.pending_len(), 1);
+        workspace.index_pending(&Budget::UNLIMITED, &file_system);
+        assert!(!workspace.get(&main).unwrap().diagnostics.is_empty());
+    }
+}
@@ -0,0 +1,105 @@
+//! Runs the parser against the fixtures of `tests/fixtures/css-syntax`, so changes to the
+//! parser or its error recovery can't silently change how stylesheets are read.
+//!
+//! The fixtures follow the layout of the css-parsing-tests corpus: each file is a JSON
+//! array alternating an input stylesheet and its expected result. Here the result is the
+//! list of the byte offsets where the parser reports an error, empty for valid input.
+//! Every input must also round-trip: the syntax tree keeps every byte of the source.
+//!
+//! `known-deviations.json` lists the valid inputs the parser still rejects, each with the
+//! reason it is an accepted deviation. The test fails once the parser accepts one of them,
+//! so the input can be moved to the fixtures.
+
+use biome_diagnostics::Diagnostic as _;
+use csslsrs::parser::parse_css;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// The valid inputs the parser is known to reject, with the reason of each deviation.
+const DEVIATIONS: &str = "known-deviations.json";
+
+/// Parses an input and returns the start offsets of its errors, or a description of the
+/// failure if the tree doesn't round-trip.
+fn run(input: &str) -> Result<Vec<u64>, String> {
+    let parse = parse_css(input);
+    let text = parse.syntax().to_string();
+    if text != input {
+        return Err(format!("the tree doesn't round-trip, it reads {text:?}"));
+    }
+    Ok(parse
+        .diagnostics()
+        .iter()
+        .map(|diagnostic| {
+            let span = diagnostic.location().span.unwrap_or_default();
+            u64::from(u32::from(span.start()))
+        })
+        .collect())
+}
+
+#[test]
+fn css_syntax_conformance() {
+    let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/css-syntax");
+    let mut paths: Vec<_> = fs::read_dir(&directory)
+        .expect("The fixtures directory exists")
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+                && !path.ends_with(DEVIATIONS)
+        })
+        .collect();
+    paths.sort();
+
+    let mut total = 0;
+    let mut failures = Vec::new();
+    for path in &paths {
+        let name = path.file_name().unwrap().to_string_lossy();
+        let fixtures: Vec<Value> = serde_json::from_str(&fs::read_to_string(path).unwrap())
+            .unwrap_or_else(|error| panic!("{name} is not a JSON array: {error}"));
+        assert!(
+            fixtures.len().is_multiple_of(2),
+            "{name} must alternate inputs and expected results"
+        );
+        for pair in fixtures.chunks(2) {
+            total += 1;
+            let input = pair[0].as_str().expect("Inputs are strings");
+            let expected: Vec<u64> = pair[1]
+                .as_array()
+                .expect("Expected results are arrays of offsets")
+                .iter()
+                .map(|offset| offset.as_u64().expect("Offsets are integers"))
+                .collect();
+            match run(input) {
+                Ok(errors) if errors == expected => {}
+                Ok(errors) => failures.push(format!(
+                    "{name}: {input:?} has errors at {errors:?}, expected {expected:?}"
+                )),
+                Err(failure) => failures.push(format!("{name}: {input:?}: {failure}")),
+            }
+        }
+    }
+
+    let deviations: Vec<Value> =
+        serde_json::from_str(&fs::read_to_string(directory.join(DEVIATIONS)).unwrap())
+            .unwrap_or_else(|error| panic!("{DEVIATIONS} is not a JSON array: {error}"));
+    for deviation in &deviations {
+        let input = deviation["input"].as_str().expect("Inputs are strings");
+        assert!(
+            deviation["reason"]
+                .as_str()
+                .is_some_and(|reason| !reason.is_empty()),
+            "{DEVIATIONS}: {input:?} must give the reason of the deviation"
+        );
+        match run(input) {
+            Ok(errors) if errors.is_empty() => failures.push(format!(
+                "{DEVIATIONS}: {input:?} is now accepted, move it to the fixtures"
+            )),
+            Ok(_) => {}
+            Err(failure) => failures.push(format!("{DEVIATIONS}: {input:?}: {failure}")),
+        }
+    }
+
+    assert!(total > 0, "No fixtures found in {}", directory.display());
+    assert!(failures.is_empty(), "\n{}", failures.join("\n"));
+}